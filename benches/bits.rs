@@ -0,0 +1,188 @@
+//! Bit-at-a-time reads and writes, as made by arithmetic and Huffman decoders,
+//! and reads of whole records and runs of PCM samples.
+
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+
+use bitrw::{BitReader, BitWriter};
+
+const BYTES: usize = 1 << 16;
+
+fn single_bits(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..BYTES).map(|_| rng.gen::<u8>()).collect();
+    let bits = BYTES * 8;
+
+    let mut group = c.benchmark_group("read_single_bits");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+    group.bench_function("read_bits(1)", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::new(Cursor::new(&data[..]));
+            let mut ones = 0u64;
+            for _ in 0..bits {
+                ones += reader.read_bits(1).unwrap();
+            }
+            ones
+        })
+    });
+    group.bench_function("read_bit", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::new(Cursor::new(&data[..]));
+            let mut ones = 0u64;
+            for _ in 0..bits {
+                ones += reader.read_bit().unwrap() as u64;
+            }
+            ones
+        })
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("write_single_bits");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+    group.bench_function("write_bits(1)", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+            for i in 0..bits {
+                writer
+                    .write_bits(1, (data[i / 8] >> (i % 8)) as u64 & 1)
+                    .unwrap();
+            }
+            writer.into_inner()
+        })
+    });
+    group.bench_function("write_bit", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+            for i in 0..bits {
+                writer.write_bit((data[i / 8] >> (i % 8)) & 1).unwrap();
+            }
+            writer.into_inner()
+        })
+    });
+    group.finish();
+}
+
+fn records(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..BYTES).map(|_| rng.gen::<u8>()).collect();
+    // A 14 field, 128 bit telemetry record.
+    let widths = [8, 16, 3, 5, 12, 1, 1, 6, 32, 10, 7, 9, 4, 14];
+    let records = BYTES / 16;
+
+    let mut group = c.benchmark_group("read_record_14_fields");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+    group.bench_function("read_bits", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::new(Cursor::new(&data[..]));
+            let mut fields = [0u64; 14];
+            let mut sum = 0u64;
+            for _ in 0..records {
+                for (field, &nbits) in fields.iter_mut().zip(&widths) {
+                    *field = reader.read_bits(nbits).unwrap();
+                }
+                sum = sum.wrapping_add(fields[8]);
+            }
+            sum
+        })
+    });
+    group.bench_function("read_many", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::new(Cursor::new(&data[..]));
+            let mut fields = [0u64; 14];
+            let mut sum = 0u64;
+            for _ in 0..records {
+                reader.read_many(&widths, &mut fields).unwrap();
+                sum = sum.wrapping_add(fields[8]);
+            }
+            sum
+        })
+    });
+    group.finish();
+
+    let values: Vec<u64> = widths.iter().map(|&nbits| (1 << nbits) - 1).collect();
+    let fields: Vec<(u8, u64)> = widths.iter().copied().zip(values.iter().copied()).collect();
+    let mut group = c.benchmark_group("write_record_14_fields");
+    group.throughput(Throughput::Bytes(BYTES as u64));
+    group.bench_function("write_bits", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+            for _ in 0..records {
+                for &(nbits, value) in &fields {
+                    writer.write_bits(nbits, value).unwrap();
+                }
+            }
+            writer.into_inner()
+        })
+    });
+    group.bench_function("write_many", |b| {
+        b.iter(|| {
+            let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+            for _ in 0..records {
+                writer.write_many(&fields).unwrap();
+            }
+            writer.into_inner()
+        })
+    });
+    group.finish();
+}
+
+fn samples(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..BYTES).map(|_| rng.gen::<u8>()).collect();
+
+    for &bit_depth in &[12u8, 20, 24] {
+        let count = BYTES * 8 / bit_depth as usize;
+        let shift = 64 - bit_depth;
+        let mut group = c.benchmark_group(format!("read_samples_{}", bit_depth));
+        group.throughput(Throughput::Bytes(BYTES as u64));
+        group.bench_function("read_bits", |b| {
+            let mut out = vec![0i32; count];
+            b.iter(|| {
+                let mut reader = BitReader::new(Cursor::new(&data[..]));
+                for sample in out.iter_mut() {
+                    let bits = reader.read_bits(bit_depth).unwrap();
+                    *sample = ((bits << shift) as i64 >> shift) as i32;
+                }
+                out[count - 1]
+            })
+        });
+        group.bench_function("read_samples_i32", |b| {
+            let mut out = vec![0i32; count];
+            b.iter(|| {
+                let mut reader = BitReader::new(Cursor::new(&data[..]));
+                reader.read_samples_i32(bit_depth, &mut out).unwrap();
+                out[count - 1]
+            })
+        });
+        group.finish();
+
+        let mut reader = BitReader::new(&data[..]);
+        let mut samples = vec![0i32; count];
+        reader.read_samples_i32(bit_depth, &mut samples).unwrap();
+        let mut group = c.benchmark_group(format!("write_samples_{}", bit_depth));
+        group.throughput(Throughput::Bytes(BYTES as u64));
+        group.bench_function("write_bits", |b| {
+            let mask = u64::MAX >> shift;
+            b.iter(|| {
+                let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+                for &sample in &samples {
+                    writer.write_bits(bit_depth, sample as u64 & mask).unwrap();
+                }
+                writer.into_inner()
+            })
+        });
+        group.bench_function("write_samples_i32", |b| {
+            b.iter(|| {
+                let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+                writer.write_samples_i32(bit_depth, &samples).unwrap();
+                writer.into_inner()
+            })
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, single_bits, records, samples);
+criterion_main!(benches);
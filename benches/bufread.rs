@@ -0,0 +1,51 @@
+//! Sequential bit-level reads from a file through a `BufReader`, refilling
+//! byte by byte and from the buffer.
+
+use std::fs::{self, File};
+use std::io::BufReader;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+
+use bitrw::BitReader;
+
+const FILE_BYTES: usize = 16 << 20;
+
+fn sequential_reads(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..FILE_BYTES).map(|_| rng.gen::<u8>()).collect();
+    let path = std::env::temp_dir().join(format!("bitrw-bench-bufread-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+
+    let reads = FILE_BYTES as u64 * 8 / 37;
+
+    let mut group = c.benchmark_group("sequential_read_37");
+    group.throughput(Throughput::Bytes(FILE_BYTES as u64));
+    group.sample_size(10);
+    group.bench_function("bit_reader", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::new(BufReader::new(File::open(&path).unwrap()));
+            let mut sum = 0u64;
+            for _ in 0..reads {
+                sum = sum.wrapping_add(reader.read_bits(37).unwrap());
+            }
+            sum
+        })
+    });
+    group.bench_function("from_buf_read", |b| {
+        b.iter(|| {
+            let mut reader = BitReader::from_buf_read(BufReader::new(File::open(&path).unwrap()));
+            let mut sum = 0u64;
+            for _ in 0..reads {
+                sum = sum.wrapping_add(reader.read_bits(37).unwrap());
+            }
+            sum
+        })
+    });
+    group.finish();
+
+    let _ = fs::remove_file(&path);
+}
+
+criterion_group!(benches, sequential_reads);
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+//! Copying 100 MB between a reader and writer at every combination of aligned
+//! and unaligned phases, against copying through `read_bits()` and
+//! `write_bits()`.
+
+use std::io::Cursor;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use rand::Rng;
+
+use bitrw::{copy_bits, BitReader, BitWriter};
+
+const BYTES: usize = 100 << 20;
+
+fn copy(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..BYTES).map(|_| rng.gen::<u8>()).collect();
+    let nbits = (BYTES as u64 - 1) * 8;
+
+    let mut group = c.benchmark_group("copy_100mb");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(BYTES as u64));
+    for &(phase, out_phase) in &[(0, 0), (3, 3), (0, 5)] {
+        group.bench_function(format!("copy_bits/{}-{}", phase, out_phase), |b| {
+            b.iter(|| {
+                let mut reader = BitReader::new(Cursor::new(&data[..]));
+                reader.read_bits(phase).unwrap();
+                let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+                writer.write_bits(out_phase, 0).unwrap();
+                copy_bits(&mut reader, &mut writer, nbits).unwrap();
+                writer.into_inner()
+            })
+        });
+        group.bench_function(format!("read_write_bits/{}-{}", phase, out_phase), |b| {
+            b.iter(|| {
+                let mut reader = BitReader::new(Cursor::new(&data[..]));
+                reader.read_bits(phase).unwrap();
+                let mut writer = BitWriter::new(Vec::with_capacity(BYTES));
+                writer.write_bits(out_phase, 0).unwrap();
+                let mut remaining = nbits;
+                while remaining > 0 {
+                    let chunk = remaining.min(32) as u8;
+                    writer
+                        .write_bits(chunk, reader.read_bits(chunk).unwrap())
+                        .unwrap();
+                    remaining -= chunk as u64;
+                }
+                writer.into_inner()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, copy);
+criterion_main!(benches);
@@ -0,0 +1,51 @@
+//! Random bit-level reads from a file, through a `BufReader` and a mapping.
+
+use std::fs::{self, File};
+use std::io::{BufReader, SeekFrom};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use rand::Rng;
+
+use bitrw::BitReader;
+
+const FILE_BYTES: usize = 16 << 20;
+
+fn random_reads(c: &mut Criterion) {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..FILE_BYTES).map(|_| rng.gen::<u8>()).collect();
+    let path = std::env::temp_dir().join(format!("bitrw-bench-{}", std::process::id()));
+    fs::write(&path, &data).unwrap();
+
+    let bits = FILE_BYTES as u64 * 8;
+    let offsets: Vec<u64> = (0..1024).map(|_| rng.gen_range(0, bits - 64)).collect();
+
+    let mut group = c.benchmark_group("random_read_1024x37");
+    group.bench_function("bufreader", |b| {
+        let mut reader = BitReader::new(BufReader::new(File::open(&path).unwrap()));
+        b.iter(|| {
+            let mut sum = 0u64;
+            for &pos in &offsets {
+                reader.seek(SeekFrom::Start(pos)).unwrap();
+                sum = sum.wrapping_add(reader.read_bits(37).unwrap());
+            }
+            sum
+        })
+    });
+    group.bench_function("mmap", |b| {
+        let mut reader = BitReader::from_path(&path).unwrap();
+        b.iter(|| {
+            let mut sum = 0u64;
+            for &pos in &offsets {
+                reader.seek(SeekFrom::Start(pos)).unwrap();
+                sum = sum.wrapping_add(reader.read_bits(37).unwrap());
+            }
+            sum
+        })
+    });
+    group.finish();
+
+    let _ = fs::remove_file(&path);
+}
+
+criterion_group!(benches, random_reads);
+criterion_main!(benches);
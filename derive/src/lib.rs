@@ -0,0 +1,279 @@
+//! Derives `bitrw::FromBits` and `bitrw::ToBits` for structs, reading and
+//! writing each field in declaration order.  Use it through bitrw's `derive`
+//! feature, which re-exports the macros alongside the traits.
+//!
+//! Fields without attributes use their own `FromBits` and `ToBits`, so they
+//! can be any type implementing them, including other derived structs.
+//! Field attributes change that:
+//!
+//! * `#[bits(n)]` makes an integer field `n` bits wide, 1 to 64, read with
+//!   `BitRead::try_read_into()`.  Bits above the width are dropped when
+//!   writing, as by `write_bits()`.
+//! * `#[bits(signed, n)]` makes it an `n` bit two's complement field, read
+//!   with `BitRead::try_read_signed_into()`.
+//! * `#[skip(n)]` skips `n` reserved bits before the field, written as zero.
+//! * `#[align(n)]` skips to the next multiple of `n` bits before the field,
+//!   written as zero.  That needs the reader or writer to know its position,
+//!   and fails with `InvalidInput` if it doesn't.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote, quote_spanned};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Error, Expr, ExprLit, Fields, GenericParam,
+    Generics, Ident, Lit, LitInt, Member, Token, Type,
+};
+
+/// Derive `FromBits`, reading fields in declaration order.
+#[proc_macro_derive(FromBits, attributes(bits, skip, align))]
+pub fn derive_from_bits(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_bits(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `ToBits`, writing fields in declaration order.
+#[proc_macro_derive(ToBits, attributes(bits, skip, align))]
+pub fn derive_to_bits(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_bits(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field is read and written.
+enum Width {
+    /// With its own `FromBits` and `ToBits`.
+    Natural,
+    /// As a field of this many bits.
+    Unsigned(u8),
+    Signed(u8),
+}
+
+struct Field {
+    member: Member,
+    ty: Type,
+    width: Width,
+    skip: Option<u64>,
+    align: Option<u64>,
+    span: Span,
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<(Vec<Field>, &Fields)> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        Data::Enum(data) => {
+            return Err(Error::new(
+                data.enum_token.span,
+                "FromBits and ToBits can only be derived for structs",
+            ))
+        }
+        Data::Union(data) => {
+            return Err(Error::new(
+                data.union_token.span,
+                "FromBits and ToBits can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut parsed = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let member = match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(index.into()),
+        };
+        let mut width = Width::Natural;
+        let mut skip = None;
+        let mut align = None;
+        for attr in &field.attrs {
+            if attr.path().is_ident("bits") {
+                width = parse_bits(attr, &field.ty)?;
+            } else if attr.path().is_ident("skip") {
+                skip = Some(attr.parse_args::<LitInt>()?.base10_parse::<u64>()?);
+            } else if attr.path().is_ident("align") {
+                let lit = attr.parse_args::<LitInt>()?;
+                let n = lit.base10_parse::<u64>()?;
+                if n == 0 {
+                    return Err(Error::new(lit.span(), "alignment must be at least 1"));
+                }
+                align = Some(n);
+            }
+        }
+        parsed.push(Field {
+            member,
+            ty: field.ty.clone(),
+            width,
+            skip,
+            align,
+            span: field.span(),
+        });
+    }
+    Ok((parsed, fields))
+}
+
+/// Parse `#[bits(n)]` or `#[bits(signed, n)]`.
+fn parse_bits(attr: &syn::Attribute, ty: &Type) -> syn::Result<Width> {
+    let args = attr.parse_args_with(Punctuated::<Expr, Token![,]>::parse_terminated)?;
+    let mut signed = false;
+    let mut width = None;
+    for arg in args {
+        match arg {
+            Expr::Path(path) if path.path.is_ident("signed") && !signed => signed = true,
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) if width.is_none() => width = Some(lit),
+            other => return Err(Error::new(other.span(), "expected `signed` and a width")),
+        }
+    }
+
+    let lit = width.ok_or_else(|| Error::new(attr.span(), "expected a width in bits"))?;
+    let n = lit.base10_parse::<u64>()?;
+    if !(1..=64).contains(&n) {
+        return Err(Error::new(lit.span(), "width must be from 1 to 64 bits"));
+    }
+    if let Some(bits) = primitive_bits(ty) {
+        if n > bits {
+            return Err(Error::new(
+                lit.span(),
+                format!("width is more than the field type's {} bits", bits),
+            ));
+        }
+    }
+    let n = n as u8;
+    Ok(if signed {
+        Width::Signed(n)
+    } else {
+        Width::Unsigned(n)
+    })
+}
+
+/// The width of a field of a primitive integer type, if it is one.
+fn primitive_bits(ty: &Type) -> Option<u64> {
+    let ident = match ty {
+        Type::Path(path) if path.qself.is_none() => path.path.get_ident()?,
+        _ => return None,
+    };
+    Some(match ident.to_string().as_str() {
+        "u8" | "i8" => 8,
+        "u16" | "i16" => 16,
+        "u32" | "i32" => 32,
+        "u64" | "i64" => 64,
+        _ => return None,
+    })
+}
+
+/// Add `bound` to every type parameter.
+fn add_bounds(mut generics: Generics, bound: syn::TypeParamBound) -> Generics {
+    for param in &mut generics.params {
+        if let GenericParam::Type(param) = param {
+            param.bounds.push(bound.clone());
+        }
+    }
+    generics
+}
+
+fn from_bits(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, shape) = parse_fields(&input)?;
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::bitrw::FromBits));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut reads = Vec::new();
+    let mut vars = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let var = format_ident!("__field{}", index);
+        let ty = &field.ty;
+        if let Some(n) = field.skip {
+            reads.push(quote_spanned! {field.span=> ::bitrw::__derive::skip(r, #n)?; });
+        }
+        if let Some(n) = field.align {
+            reads.push(quote_spanned! {field.span=> ::bitrw::__derive::align_read(r, #n)?; });
+        }
+        let read = match field.width {
+            Width::Natural => quote_spanned! {field.span=>
+                <#ty as ::bitrw::FromBits>::from_bits(r)?
+            },
+            Width::Unsigned(n) => quote_spanned! {field.span=>
+                ::bitrw::BitRead::try_read_into::<#ty>(r, #n)?
+            },
+            Width::Signed(n) => quote_spanned! {field.span=>
+                ::bitrw::BitRead::try_read_signed_into::<#ty>(r, #n)?
+            },
+        };
+        reads.push(quote! { let #var = #read; });
+        vars.push((field.member.clone(), var));
+    }
+
+    let construct = construct(name, shape, &vars);
+    Ok(quote! {
+        impl #impl_generics ::bitrw::FromBits for #name #ty_generics #where_clause {
+            fn from_bits<__R: ::bitrw::BitRead>(r: &mut __R) -> ::bitrw::io::Result<Self> {
+                #(#reads)*
+                ::core::result::Result::Ok(#construct)
+            }
+        }
+    })
+}
+
+fn construct(name: &Ident, shape: &Fields, vars: &[(Member, Ident)]) -> TokenStream2 {
+    match shape {
+        Fields::Named(_) => {
+            let fields = vars.iter().map(|(member, var)| quote! { #member: #var });
+            quote! { #name { #(#fields),* } }
+        }
+        Fields::Unnamed(_) => {
+            let vars = vars.iter().map(|(_, var)| var);
+            quote! { #name(#(#vars),*) }
+        }
+        Fields::Unit => quote! { #name },
+    }
+}
+
+fn to_bits(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let (fields, _) = parse_fields(&input)?;
+    let name = &input.ident;
+    let generics = add_bounds(input.generics.clone(), parse_quote!(::bitrw::ToBits));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let mut writes = Vec::new();
+    for field in &fields {
+        let member = &field.member;
+        if let Some(n) = field.skip {
+            writes.push(quote_spanned! {field.span=>
+                written += ::bitrw::__derive::pad(w, #n)?;
+            });
+        }
+        if let Some(n) = field.align {
+            writes.push(quote_spanned! {field.span=>
+                written += ::bitrw::__derive::align_write(w, #n)?;
+            });
+        }
+        writes.push(match field.width {
+            Width::Natural => quote_spanned! {field.span=>
+                written += ::bitrw::ToBits::to_bits(&self.#member, w)?;
+            },
+            Width::Unsigned(n) | Width::Signed(n) => quote_spanned! {field.span=>
+                written += ::bitrw::BitWrite::write_bits(
+                    w,
+                    #n,
+                    ::bitrw::Primitive::to_bits(self.#member),
+                )?;
+            },
+        });
+    }
+
+    Ok(quote! {
+        impl #impl_generics ::bitrw::ToBits for #name #ty_generics #where_clause {
+            fn to_bits<__W: ::bitrw::BitWrite>(&self, w: &mut __W) -> ::bitrw::io::Result<usize> {
+                let mut written = 0;
+                #(#writes)*
+                ::core::result::Result::Ok(written)
+            }
+        }
+    })
+}
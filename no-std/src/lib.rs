@@ -0,0 +1,57 @@
+//! A `#![no_std]` consumer of bitrw, packing a telemetry frame into a fixed
+//! buffer using nothing but `core`.  Building this crate on its own
+//! (`cargo build -p bitrw-no-std`, or for a bare-metal target) checks the
+//! library compiles without `std` or `alloc`.
+
+#![no_std]
+
+use bitrw::io;
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+/// A 48-bit telemetry frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Frame {
+    /// Sensor id, 5 bits.
+    pub sensor: u8,
+    /// Temperature in tenths of a degree, 12 bits signed.
+    pub temperature: i16,
+    /// Status flags, 3 bits.
+    pub flags: u8,
+    /// Sequence number, 12 bits.
+    pub sequence: u16,
+    /// Battery level, 16 bits.
+    pub battery: u16,
+}
+
+/// Pack `frame` into `buf`, returning the number of bytes used.
+pub fn pack(frame: &Frame, buf: &mut [u8]) -> io::Result<usize> {
+    let capacity = buf.len();
+    let mut writer = BitWriter::new(buf);
+    writer.write_bits(5, frame.sensor as u64)?;
+    writer.write_bits(12, frame.temperature as u64)?;
+    writer.write_bits(3, frame.flags as u64)?;
+    writer.write_bits(12, frame.sequence as u64)?;
+    writer.write_be(frame.battery)?;
+    writer.flush()?;
+
+    Ok(capacity - writer.into_inner().len())
+}
+
+/// Unpack a frame from the start of `buf`.
+pub fn unpack(buf: &[u8]) -> io::Result<Frame> {
+    let mut reader = BitReader::new(buf);
+    let sensor = reader.read_bits(5)? as u8;
+    let temperature = reader.read_bits(12)? as i16;
+    let temperature = (temperature << 4) >> 4;
+    let flags = reader.read_bits(3)? as u8;
+    let sequence = reader.read_bits(12)? as u16;
+    let battery = reader.read_be()?;
+
+    Ok(Frame {
+        sensor,
+        temperature,
+        flags,
+        sequence,
+        battery,
+    })
+}
@@ -0,0 +1,31 @@
+use bitrw_no_std::{pack, unpack, Frame};
+
+#[test]
+fn frame_roundtrip() {
+    let frame = Frame {
+        sensor: 0b10110,
+        temperature: -273,
+        flags: 0b101,
+        sequence: 0xabc,
+        battery: 0xbeef,
+    };
+
+    let mut buf = [0u8; 8];
+    assert_eq!(pack(&frame, &mut buf).unwrap(), 6);
+    assert_eq!(unpack(&buf[..6]).unwrap(), frame);
+}
+
+#[test]
+fn frame_too_small() {
+    let frame = Frame {
+        sensor: 1,
+        temperature: 1,
+        flags: 1,
+        sequence: 1,
+        battery: 1,
+    };
+
+    let mut buf = [0u8; 5];
+    assert!(pack(&frame, &mut buf).is_err());
+    assert!(unpack(&buf[..5]).is_err());
+}
@@ -0,0 +1,110 @@
+use core::marker::PhantomData;
+
+use crate::order::BitOrder;
+
+/// A queue of up to 127 bits, in stream order.
+///
+/// This is the bit-manipulation core shared by every reader and writer, sync
+/// or async: readers push whole bytes in until a request can be satisfied and
+/// then pop the bits out in one go, while writers push values in and drain
+/// whole bytes out.  Keeping it separate from any IO means they can't diverge.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Accumulator<O> {
+    acc: u128,
+    len: u8,
+    order: PhantomData<O>,
+}
+
+impl<O: BitOrder> Accumulator<O> {
+    pub(crate) fn new() -> Self {
+        Self {
+            acc: 0,
+            len: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// The number of bits queued.
+    #[inline]
+    pub(crate) fn len(&self) -> u8 {
+        self.len
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.acc = 0;
+        self.len = 0;
+    }
+
+    /// The number of whole bytes which must be pushed before `nbits` can be
+    /// popped.
+    #[cfg(feature = "tokio")]
+    #[inline]
+    pub(crate) fn bytes_needed(&self, nbits: u8) -> usize {
+        (nbits.saturating_sub(self.len) as usize).div_ceil(8)
+    }
+
+    /// Queue the low `nbits` of `value`.
+    #[inline]
+    pub(crate) fn push(&mut self, nbits: u8, value: u64) {
+        debug_assert!(nbits <= 64 && self.len + nbits < 128);
+        self.acc = O::push(self.acc, self.len, nbits, value);
+        self.len += nbits;
+    }
+
+    /// Dequeue the first `nbits` queued.
+    #[inline]
+    pub(crate) fn pop(&mut self, nbits: u8) -> u64 {
+        debug_assert!(nbits <= 64 && nbits <= self.len);
+        let (value, acc) = O::pop(self.acc, self.len, nbits);
+        self.acc = acc;
+        self.len -= nbits;
+        value
+    }
+
+    /// Dequeue and drop the first `nbits` queued, which may be more than 64.
+    #[inline]
+    pub(crate) fn discard(&mut self, nbits: u8) {
+        let mut left = nbits;
+        while left > 0 {
+            let n = core::cmp::min(left, 64);
+            self.pop(n);
+            left -= n;
+        }
+    }
+
+    /// Move every complete byte queued into `out`, returning how many there
+    /// were.  `out` must have room for `len() / 8` bytes.
+    #[inline]
+    pub(crate) fn drain_bytes(&mut self, out: &mut [u8]) -> usize {
+        let n = (self.len / 8) as usize;
+        for byte in &mut out[..n] {
+            *byte = self.pop(8) as u8;
+        }
+        n
+    }
+
+    /// Queue zero bits, or one bits if `ones`, up to the next byte boundary,
+    /// returning how many were needed.
+    #[inline]
+    pub(crate) fn pad(&mut self, ones: bool) -> u8 {
+        let padding = (8 - self.len % 8) % 8;
+        self.push(padding, if ones { u64::MAX } else { 0 });
+        padding
+    }
+
+    /// The queued bits as a single value, packed as `pop(len())` would if it
+    /// could return more than 64 bits.
+    pub(crate) fn value(&self) -> u128 {
+        self.acc
+    }
+
+    /// A queue of the low `len` bits of `value`, packed as by `value()`.
+    pub(crate) fn from_value(len: u8, value: u128) -> Self {
+        debug_assert!(len < 128 && value >> len == 0);
+        Self {
+            acc: value,
+            len,
+            order: PhantomData,
+        }
+    }
+}
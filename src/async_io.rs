@@ -0,0 +1,301 @@
+//! Bit-level IO over tokio's `AsyncRead` and `AsyncWrite`, enabled with the
+//! `tokio` feature.
+//!
+//! These share the bit buffer implementation with `BitReader` and `BitWriter`,
+//! so the same stream encodes and decodes identically through either.
+
+use core::future::poll_fn;
+use core::pin::Pin;
+use core::task::Poll;
+
+use alloc::vec::Vec;
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use crate::accumulator::Accumulator;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::primitive::Primitive;
+
+/// The `AsyncBitReader` struct adds bit-level reading to any `AsyncRead`.
+///
+/// As with `BitReader`, refills only ever pull as many bytes as the current
+/// read needs, so most readers should be wrapped in a `tokio::io::BufReader`.
+///
+/// # Cancellation safety
+///
+/// All reads are cancellation safe.  Bytes are moved from the inner reader into
+/// the internal bit buffer as they arrive, and bits are only consumed once the
+/// complete value is available, so dropping a read future mid-refill leaves
+/// any bits it had already fetched buffered for the next read.
+#[derive(Debug)]
+pub struct AsyncBitReader<R, O = Msb0> {
+    inner: R,
+    bits: Accumulator<O>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncBitReader<R> {
+    /// Create a new `AsyncBitReader` around the given reader.
+    pub fn new(inner: R) -> Self {
+        Self::with_order(inner, Msb0)
+    }
+}
+
+impl<R: AsyncRead + Unpin, O: BitOrder> AsyncBitReader<R, O> {
+    /// Create a new `AsyncBitReader` around the given reader, unpacking bits in
+    /// the given order.
+    pub fn with_order(inner: R, _order: O) -> Self {
+        Self {
+            inner,
+            bits: Accumulator::new(),
+        }
+    }
+
+    /// Reset the internal state of the reader, discarding any buffered bits.
+    pub fn reset(&mut self) {
+        self.bits.clear();
+    }
+
+    async fn fill(&mut self, nbits: u8) -> io::Result<()> {
+        while self.bits.len() < nbits {
+            let mut buf = [0; 9];
+            let wanted = self.bits.bytes_needed(nbits);
+            let inner = &mut self.inner;
+            let n = poll_fn(|cx| {
+                let mut rb = ReadBuf::new(&mut buf[..wanted]);
+                match Pin::new(&mut *inner).poll_read(cx, &mut rb) {
+                    Poll::Ready(Ok(())) => Poll::Ready(Ok(rb.filled().len())),
+                    Poll::Ready(Err(e)) => Poll::Ready(Err(e)),
+                    Poll::Pending => Poll::Pending,
+                }
+            })
+            .await?;
+
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ));
+            }
+
+            for &byte in &buf[..n] {
+                self.bits.push(8, byte as u64);
+            }
+        }
+        Ok(())
+    }
+
+    /// Read a single bit from the reader.
+    pub async fn read_bit(&mut self) -> io::Result<u8> {
+        Ok(self.read_bits(1).await? as u8)
+    }
+
+    /// Read up to 64 bits from the reader.
+    pub async fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+
+        self.fill(nbits).await?;
+        Ok(self.bits.pop(nbits))
+    }
+
+    /// Read a primitive value as a single `T::BITS` wide field, as
+    /// `BitReader::read()`.
+    pub async fn read<T: Primitive>(&mut self) -> io::Result<T> {
+        Ok(T::from_bits(self.read_bits(T::BITS).await?))
+    }
+
+    /// Read a big-endian primitive value, as `BitReader::read_be()`.
+    pub async fn read_be<T: Primitive>(&mut self) -> io::Result<T> {
+        self.fill(T::BITS).await?;
+        let mut value = 0;
+        for _ in 0..T::BITS / 8 {
+            value = (value << 8) | self.bits.pop(8);
+        }
+        Ok(T::from_bits(value))
+    }
+
+    /// Read a little-endian primitive value, as `BitReader::read_le()`.
+    pub async fn read_le<T: Primitive>(&mut self) -> io::Result<T> {
+        self.fill(T::BITS).await?;
+        let mut value = 0;
+        for i in 0..T::BITS / 8 {
+            value |= self.bits.pop(8) << (i * 8);
+        }
+        Ok(T::from_bits(value))
+    }
+
+    /// Get a reference to the reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the reader.  Be sure to call `reset` if you
+    /// changed the stream position or otherwise mutated it.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this `AsyncBitReader`, returning the underlying reader and
+    /// discarding any unread buffered bits.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+/// The `AsyncBitWriter` struct adds bit-level writing to any `AsyncWrite`.
+///
+/// Completed bytes are collected in an internal buffer, which is only written
+/// out once it reaches its capacity (8 KiB by default) or the writer is
+/// flushed, so there's no need for an additional `BufWriter`.
+///
+/// # Cancellation safety
+///
+/// Buffered bytes are only discarded once the inner writer has accepted them,
+/// and each write drains the buffer *before* queueing its own bits, so a write
+/// future dropped part way through leaves the writer in a consistent state
+/// without having written its value.  As with `BitWriter`, you should
+/// `flush()` before dropping the writer or buffered data will be lost.
+#[derive(Debug)]
+pub struct AsyncBitWriter<W, O = Msb0> {
+    inner: W,
+    bits: Accumulator<O>,
+    buf: Vec<u8>,
+    pos: usize,
+    capacity: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncBitWriter<W> {
+    /// Create a new `AsyncBitWriter` around the given writer.
+    pub fn new(inner: W) -> Self {
+        Self::with_order(inner, Msb0)
+    }
+}
+
+impl<W: AsyncWrite + Unpin, O: BitOrder> AsyncBitWriter<W, O> {
+    /// Create a new `AsyncBitWriter` around the given writer, packing bits in
+    /// the given order.
+    pub fn with_order(inner: W, _order: O) -> Self {
+        Self {
+            inner,
+            bits: Accumulator::new(),
+            buf: Vec::with_capacity(DEFAULT_CAPACITY),
+            pos: 0,
+            capacity: DEFAULT_CAPACITY,
+        }
+    }
+
+    async fn drain(&mut self) -> io::Result<()> {
+        while self.pos < self.buf.len() {
+            let inner = &mut self.inner;
+            let pending = &self.buf[self.pos..];
+            let n = poll_fn(|cx| Pin::new(&mut *inner).poll_write(cx, pending)).await?;
+            if n == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ));
+            }
+            self.pos += n;
+        }
+        self.buf.clear();
+        self.pos = 0;
+        Ok(())
+    }
+
+    async fn reserve(&mut self) -> io::Result<()> {
+        if self.buf.len() >= self.capacity {
+            self.drain().await?;
+        }
+        Ok(())
+    }
+
+    fn queue(&mut self) {
+        let mut bytes = [0; 9];
+        let n = self.bits.drain_bytes(&mut bytes);
+        self.buf.extend_from_slice(&bytes[..n]);
+    }
+
+    /// Write a single bit to the writer.
+    pub async fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        assert!(bit <= 1);
+        self.write_bits(1, bit as u64).await?;
+        Ok(())
+    }
+
+    /// Write up to 64 bits to the writer.
+    pub async fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+
+        self.reserve().await?;
+        self.bits.push(nbits, value);
+        self.queue();
+        Ok(nbits as usize)
+    }
+
+    /// Write a primitive value as a single `T::BITS` wide field, as
+    /// `BitWriter::write()`.
+    pub async fn write<T: Primitive>(&mut self, value: T) -> io::Result<usize> {
+        self.write_bits(T::BITS, value.to_bits()).await
+    }
+
+    /// Write a big-endian primitive value, as `BitWriter::write_be()`.
+    pub async fn write_be<T: Primitive>(&mut self, value: T) -> io::Result<usize> {
+        let bits = value.to_bits();
+        self.reserve().await?;
+        for i in (0..T::BITS / 8).rev() {
+            self.bits.push(8, bits >> (i * 8));
+        }
+        self.queue();
+        Ok(T::BITS as usize)
+    }
+
+    /// Write a little-endian primitive value, as `BitWriter::write_le()`.
+    pub async fn write_le<T: Primitive>(&mut self, value: T) -> io::Result<usize> {
+        let bits = value.to_bits();
+        self.reserve().await?;
+        for i in 0..T::BITS / 8 {
+            self.bits.push(8, bits >> (i * 8));
+        }
+        self.queue();
+        Ok(T::BITS as usize)
+    }
+
+    /// Pad any partial byte with zero bits, write out everything buffered, and
+    /// flush the underlying writer, returning the number of padding bits
+    /// written.  As with `BitWriter::flush()`, the sum of bits written plus the
+    /// padding always ends on a byte boundary.
+    pub async fn flush(&mut self) -> io::Result<usize> {
+        let padding = self.flush_bits().await?;
+        poll_fn(|cx| Pin::new(&mut self.inner).poll_flush(cx)).await?;
+        Ok(padding)
+    }
+
+    /// Exactly the same as `flush()`, only it doesn't call `flush()` on the
+    /// wrapped writer.
+    pub async fn flush_bits(&mut self) -> io::Result<usize> {
+        let padding = self.bits.pad(false);
+        self.queue();
+        self.drain().await?;
+        Ok(padding as usize)
+    }
+
+    /// Get a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the writer.  You should `flush()` or at least
+    /// `flush_bits()` prior to making any changes.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap this `AsyncBitWriter`, returning the underlying writer and
+    /// discarding any unwritten buffered bits and bytes.  You should call
+    /// `flush()` if this is undesirable.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
@@ -0,0 +1,282 @@
+//! Bit streams as base64 text, for embedding packed payloads in JSON or log
+//! lines, keeping their exact length in bits.
+//!
+//! Bits are packed most significant first, as by a `BitWriter` of `Msb0`, so
+//! a whole number of bytes encodes exactly as the base64 of those bytes.
+//! Otherwise the last byte is padded with zero bits and the text ends with a
+//! `.` and the number of bits of that byte which are part of the stream, from
+//! 1 to 7: five bits `10110` are `sA==.5`.
+//!
+//! The standard alphabet pads the text to a multiple of four characters with
+//! `=`, and the URL-safe one leaves the padding off.  Either is read with or
+//! without it.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::Msb0;
+use crate::traits::{BitRead, BitWrite};
+
+/// Which 64 characters stand for the values of each 6 bits, from RFC 4648.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Base64Alphabet {
+    /// `A-Z`, `a-z`, `0-9`, `+` and `/`, padded with `=`.
+    #[default]
+    Standard,
+    /// `A-Z`, `a-z`, `0-9`, `-` and `_`, unpadded.
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn encode(self, sextet: u8) -> char {
+        (match sextet {
+            0..=25 => b'A' + sextet,
+            26..=51 => b'a' + sextet - 26,
+            52..=61 => b'0' + sextet - 52,
+            62 if self == Base64Alphabet::Standard => b'+',
+            63 if self == Base64Alphabet::Standard => b'/',
+            62 => b'-',
+            _ => b'_',
+        }) as char
+    }
+
+    fn decode(self, c: u8) -> Option<u8> {
+        Some(match (c, self) {
+            (b'A'..=b'Z', _) => c - b'A',
+            (b'a'..=b'z', _) => c - b'a' + 26,
+            (b'0'..=b'9', _) => c - b'0' + 52,
+            (b'+', Base64Alphabet::Standard) | (b'-', Base64Alphabet::UrlSafe) => 62,
+            (b'/', Base64Alphabet::Standard) | (b'_', Base64Alphabet::UrlSafe) => 63,
+            _ => return None,
+        })
+    }
+}
+
+/// Writes bits as base64 text to a `fmt::Write`, such as a `String`.
+///
+/// Characters are written as each 6 bits are complete, and `finish()` writes
+/// the rest, along with any padding and the length suffix.  A `fmt::Error`
+/// from the inner writer is an `io::Error` of kind `Other`.
+///
+/// ```
+/// use bitrw::{Base64Alphabet, Base64BitWriter};
+///
+/// let mut writer = Base64BitWriter::new(String::new(), Base64Alphabet::Standard);
+/// writer.write_bits(16, 0x4869).unwrap();
+/// assert_eq!(writer.finish().unwrap(), "SGk=");
+///
+/// let mut writer = Base64BitWriter::new(String::new(), Base64Alphabet::UrlSafe);
+/// writer.write_bits(13, 0x1fff).unwrap();
+/// assert_eq!(writer.finish().unwrap(), "__g.5");
+/// ```
+#[derive(Debug)]
+pub struct Base64BitWriter<W> {
+    inner: W,
+    alphabet: Base64Alphabet,
+    bits: Accumulator<Msb0>,
+    position: u64,
+    chars: u64,
+}
+
+impl<W: fmt::Write> Base64BitWriter<W> {
+    /// Wrap `inner`, writing characters of `alphabet`.
+    pub fn new(inner: W, alphabet: Base64Alphabet) -> Self {
+        Self {
+            inner,
+            alphabet,
+            bits: Accumulator::new(),
+            position: 0,
+            chars: 0,
+        }
+    }
+
+    /// Write up to 64 bits.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        self.bits.push(nbits, value);
+        self.position += nbits as u64;
+        self.write_sextets()?;
+        Ok(nbits as usize)
+    }
+
+    /// The number of bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Write the last character, with any padding and the length suffix, and
+    /// return the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let tail = (self.position % 8) as u8;
+        self.bits.push((8 - tail) % 8, 0);
+        self.write_sextets()?;
+        if self.bits.len() > 0 {
+            self.bits.push(6 - self.bits.len(), 0);
+        }
+        self.write_sextets()?;
+        if self.alphabet == Base64Alphabet::Standard {
+            for _ in 0..(4 - self.chars % 4) % 4 {
+                self.inner.write_char('=').map_err(fmt_error)?;
+            }
+        }
+        if tail > 0 {
+            write!(self.inner, ".{}", tail).map_err(fmt_error)?;
+        }
+        Ok(self.inner)
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    fn write_sextets(&mut self) -> io::Result<()> {
+        while self.bits.len() >= 6 {
+            let c = self.alphabet.encode(self.bits.pop(6) as u8);
+            self.inner.write_char(c).map_err(fmt_error)?;
+            self.chars += 1;
+        }
+        Ok(())
+    }
+}
+
+fn fmt_error(_: fmt::Error) -> io::Error {
+    Error::new(io::ErrorKind::Other, "base64", "formatter error").into()
+}
+
+impl<W: fmt::Write> BitWrite for Base64BitWriter<W> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        Base64BitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
+
+/// Reads bits from base64 text, as written by a `Base64BitWriter`, or any
+/// base64 of whole bytes.
+///
+/// ```
+/// use bitrw::{Base64Alphabet, Base64BitReader};
+///
+/// let mut reader = Base64BitReader::new("__g.5", Base64Alphabet::UrlSafe).unwrap();
+/// assert_eq!(reader.len_bits(), 13);
+/// assert_eq!(reader.read_bits(13).unwrap(), 0x1fff);
+/// assert!(reader.read_bits(1).is_err());
+/// ```
+#[derive(Debug)]
+pub struct Base64BitReader<'a> {
+    text: &'a [u8],
+    alphabet: Base64Alphabet,
+    bits: Accumulator<Msb0>,
+    position: u64,
+    len: u64,
+}
+
+impl<'a> Base64BitReader<'a> {
+    /// Read the bits encoded by `text`, in `alphabet`.
+    ///
+    /// The whole text is checked first.  A character outside the alphabet is
+    /// an `InvalidData` error at the offset of its first bit, with the
+    /// character as its value.  So is a text of the wrong length, with the
+    /// wrong padding, with padding bits which aren't zero, or with a suffix
+    /// other than a `.` and a digit from 1 to 7.
+    pub fn new(text: &'a str, alphabet: Base64Alphabet) -> io::Result<Self> {
+        let invalid = |message, offset: usize| {
+            Error::new(io::ErrorKind::InvalidData, "base64", message).at(Some(offset as u64 * 6))
+        };
+
+        let (text, tail) = match text.as_bytes() {
+            [text @ .., b'.', digit] => match digit {
+                b'1'..=b'7' => (text, (digit - b'0') as u64),
+                _ => return Err(invalid("invalid length suffix", text.len()).into()),
+            },
+            text => (text, 0),
+        };
+        let chars = text
+            .iter()
+            .rposition(|&c| c != b'=')
+            .map_or(0, |last| last + 1);
+        let padding = text.len() - chars;
+        if padding > 0 && (padding > 2 || text.len() % 4 != 0)
+            || chars % 4 == 1
+            || chars == 0 && tail > 0
+        {
+            return Err(invalid("invalid length", chars).into());
+        }
+        let text = &text[..chars];
+        if let Some(index) = text.iter().position(|&c| alphabet.decode(c).is_none()) {
+            return Err(invalid("invalid character", index)
+                .with_value(text[index] as u64)
+                .into());
+        }
+
+        let bytes = chars as u64 * 6 / 8;
+        let spare = (chars as u64 * 6 % 8) as u8;
+        if let Some(&last) = text.last() {
+            let last = alphabet.decode(last).unwrap();
+            if last & ((1 << spare) - 1) != 0 {
+                return Err(invalid("padding bits not zero", chars - 1).into());
+            }
+        }
+        let len = match tail {
+            0 => bytes * 8,
+            tail => bytes * 8 - 8 + tail,
+        };
+        Ok(Self {
+            text,
+            alphabet,
+            bits: Accumulator::new(),
+            position: 0,
+            len,
+        })
+    }
+
+    /// Read up to 64 bits.  Reading past the end of the stream is an
+    /// `UnexpectedEof` error, and reads nothing.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        if self.position + nbits as u64 > self.len {
+            return Err(
+                Error::new(io::ErrorKind::UnexpectedEof, "read_bits", "end of stream")
+                    .at(Some(self.position))
+                    .into(),
+            );
+        }
+        while self.bits.len() < nbits {
+            let (&c, rest) = self.text.split_first().unwrap();
+            self.text = rest;
+            // Every character was checked by new().
+            self.bits.push(6, self.alphabet.decode(c).unwrap() as u64);
+        }
+        self.position += nbits as u64;
+        Ok(self.bits.pop(nbits))
+    }
+
+    /// The number of bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The length of the whole stream in bits.
+    pub fn len_bits(&self) -> u64 {
+        self.len
+    }
+}
+
+impl BitRead for Base64BitReader<'_> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        Base64BitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
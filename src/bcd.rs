@@ -0,0 +1,96 @@
+//! Packed binary-coded decimal, one digit per 4 bit nibble.
+//!
+//! Digits are written most significant first, each nibble a 4 bit field
+//! packed as the stream's `BitOrder` packs any other, so in a byte-aligned
+//! `Msb0` stream the first digit is the high nibble of each byte, and in an
+//! `Lsb0` stream, as for telephony's TBCD, the low nibble.
+//!
+//! Fields with an odd number of digits are often filled out to whole bytes
+//! with a trailing 0xF nibble, which `BitWrite::write_bcd_filled()`
+//! and `BitRead::read_bcd_filled()` add and check.
+
+use crate::error::Error;
+use crate::io;
+use crate::{BitRead, BitWrite};
+
+/// The filler nibble after an odd number of digits.
+pub(crate) const FILLER: u64 = 0xf;
+
+/// Read one digit for `op`, which started at `start`.
+pub(crate) fn read_digit<R: BitRead>(
+    r: &mut R,
+    op: &'static str,
+    start: Option<u64>,
+) -> io::Result<u64> {
+    let at = r.bit_position();
+    let digit = r.read_bits(4).map_err(|e| Error::wrap(e, op, start))?;
+    if digit > 9 {
+        return Err(
+            Error::new(io::ErrorKind::InvalidData, op, "invalid BCD digit")
+                .at(at)
+                .with_value(digit)
+                .into(),
+        );
+    }
+    Ok(digit)
+}
+
+/// Read the filler nibble for `op`, which started at `start`.
+pub(crate) fn read_filler<R: BitRead>(
+    r: &mut R,
+    op: &'static str,
+    start: Option<u64>,
+) -> io::Result<()> {
+    let at = r.bit_position();
+    let filler = r.read_bits(4).map_err(|e| Error::wrap(e, op, start))?;
+    if filler != FILLER {
+        return Err(
+            Error::new(io::ErrorKind::InvalidData, op, "invalid BCD filler")
+                .at(at)
+                .with_value(filler)
+                .with_expected(FILLER)
+                .into(),
+        );
+    }
+    Ok(())
+}
+
+/// Write `value` as `digits` digits for `op`, returning the bits written.
+pub(crate) fn write_digits<W: BitWrite>(
+    w: &mut W,
+    op: &'static str,
+    value: u64,
+    digits: usize,
+) -> io::Result<usize> {
+    let start = w.bit_position();
+    // u64::MAX has 20 digits, so any more are leading zeros.
+    let mut nibbles = [0u8; 20];
+    let mut rest = value;
+    for nibble in nibbles.iter_mut().rev() {
+        *nibble = (rest % 10) as u8;
+        rest /= 10;
+    }
+    let len = 20 - nibbles.iter().take_while(|&&n| n == 0).count();
+    if len > digits {
+        return Err(Error::new(
+            io::ErrorKind::InvalidInput,
+            op,
+            "value too large for digits",
+        )
+        .at(start)
+        .with_value(value)
+        .into());
+    }
+
+    let wrap = |e| Error::wrap(e, op, start);
+    let mut zeros = digits - len;
+    while zeros > 0 {
+        let run = core::cmp::min(zeros, 16);
+        w.write_bits(run as u8 * 4, 0).map_err(wrap)?;
+        zeros -= run;
+    }
+    for &nibble in &nibbles[20 - len..] {
+        w.write_bits(4, nibble as u64).map_err(wrap)?;
+    }
+    Ok(digits * 4)
+}
@@ -0,0 +1,94 @@
+//! Arbitrarily wide fields as `num_bigint::BigUint`, enabled with the
+//! `num-bigint` feature.
+//!
+//! A field of `nbits` bits is an unsigned integer in the natural order of the
+//! stream, exactly as `read_bits()` would return it if it were wide enough:
+//! most significant bit first for `Msb0`, least significant first for `Lsb0`.
+//! Data is moved 64 bits at a time.
+
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+/// Split an `nbits` wide field into 64-bit digits, least significant first,
+/// yielding each digit's index and width.
+fn digits(nbits: u64) -> impl DoubleEndedIterator<Item = (usize, u8)> {
+    let count = nbits.div_ceil(64) as usize;
+    let top = match nbits % 64 {
+        0 => 64,
+        n => n as u8,
+    };
+    (0..count).map(move |i| (i, if i + 1 == count { top } else { 64 }))
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read an `nbits` wide unsigned integer.  If an error occurs part of the
+    /// field may have been consumed.
+    pub fn read_bits_big(&mut self, nbits: u64) -> io::Result<BigUint> {
+        let start = Some(self.position());
+        // The width may come from the stream, so grow as digits arrive.
+        let count = nbits.div_ceil(64) as usize;
+        let mut value = Vec::with_capacity(core::cmp::min(count, 1024));
+
+        let mut read = |(_, n): (usize, u8)| -> io::Result<()> {
+            value.push(
+                self.read_bits(n)
+                    .map_err(|e| Error::wrap(e, "read_bits_big", start))?,
+            );
+            Ok(())
+        };
+        if O::MSB_FIRST {
+            digits(nbits).rev().try_for_each(&mut read)?;
+            value.reverse();
+        } else {
+            digits(nbits).try_for_each(&mut read)?;
+        }
+
+        let mut words = Vec::with_capacity(value.len() * 2);
+        for digit in value {
+            words.push(digit as u32);
+            words.push((digit >> 32) as u32);
+        }
+        Ok(BigUint::new(words))
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write `value` as an `nbits` wide unsigned integer, zero-extended if it's
+    /// shorter, returning the number of bits written.  A value which doesn't
+    /// fit is an `InvalidInput` error carrying its width in bits, and nothing is
+    /// written.
+    pub fn write_bits_big(&mut self, nbits: u64, value: &BigUint) -> io::Result<u64> {
+        let start = Some(self.position());
+        if value.bits() > nbits {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_bits_big",
+                "value too wide for field",
+            )
+            .at(start)
+            .with_value(value.bits())
+            .into());
+        }
+
+        let value: Vec<u64> = value.iter_u64_digits().collect();
+        let mut write = |(i, n): (usize, u8)| -> io::Result<()> {
+            let digit = value.get(i).copied().unwrap_or(0);
+            self.write_bits(n, digit)
+                .map_err(|e| Error::wrap(e, "write_bits_big", start))?;
+            Ok(())
+        };
+        if O::MSB_FIRST {
+            digits(nbits).rev().try_for_each(&mut write)?;
+        } else {
+            digits(nbits).try_for_each(&mut write)?;
+        }
+
+        Ok(nbits)
+    }
+}
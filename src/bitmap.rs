@@ -0,0 +1,217 @@
+//! Scanning and counting bits of raw bitmaps, such as presence masks and
+//! allocation maps, a byte or word at a time rather than bit by bit.
+
+use core::convert::TryInto;
+
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::BitOrder;
+use crate::BitReader;
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Skip to the next 1 bit, consuming it and everything before it, and
+    /// return its offset as given by `position()`.
+    ///
+    /// Runs of zero bits are skipped up to eight bytes at a time.  At the end
+    /// of the stream, or any limit, it returns `None` having consumed
+    /// everything up to there.  If the inner reader fails, the bits scanned
+    /// so far stay consumed.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0x00, 0x00, 0x21][..]);
+    /// assert_eq!(reader.next_set_bit().unwrap(), Some(18));
+    /// assert_eq!(reader.next_set_bit().unwrap(), Some(23));
+    /// assert_eq!(reader.next_set_bit().unwrap(), None);
+    /// assert_eq!(reader.position(), 24);
+    /// ```
+    pub fn next_set_bit(&mut self) -> io::Result<Option<u64>> {
+        self.next_bit(1)
+    }
+
+    /// Skip to the next 0 bit, as for `next_set_bit()`.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xff, 0xfe][..]);
+    /// assert_eq!(reader.next_clear_bit().unwrap(), Some(15));
+    /// ```
+    pub fn next_clear_bit(&mut self) -> io::Result<Option<u64>> {
+        self.next_bit(0)
+    }
+
+    /// Count the 1 bits in the next `nbits` bits, consuming them, as for a
+    /// rank query over a bitmap.  Whole bytes are counted a word at a time.
+    ///
+    /// As with `skip_bits()`, passing any limit fails with `UnexpectedEof`
+    /// without consuming anything, and if the stream ends first everything up
+    /// to the end has been consumed.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0b1011_0000, 0xff, 0b0000_0001][..]);
+    /// reader.read_bits(2).unwrap();
+    /// assert_eq!(reader.count_ones(20).unwrap(), 10);
+    /// assert_eq!(reader.position(), 22);
+    /// ```
+    pub fn count_ones(&mut self, nbits: u64) -> io::Result<u64> {
+        let start = Some(self.position);
+        if self
+            .limit
+            .is_some_and(|limit| self.position.saturating_add(nbits) > limit)
+        {
+            return Err(Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "count_ones",
+                "bit limit reached",
+            )
+            .at(start)
+            .into());
+        }
+
+        let head = core::cmp::min(self.bits.len() as u64, nbits) as u8;
+        let mut ones = 0;
+        let mut left = head;
+        while left > 0 {
+            let chunk = core::cmp::min(left, 64);
+            ones += self.bits.pop(chunk).count_ones() as u64;
+            left -= chunk;
+        }
+        self.position += head as u64;
+        let mut remaining = nbits - head as u64;
+
+        let mut buf = [0u8; 1024];
+        while remaining >= 8 {
+            let want = core::cmp::min(remaining / 8, buf.len() as u64) as usize;
+            match self.inner.read(&mut buf[..want]) {
+                Ok(0) => {
+                    return Err(Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "count_ones",
+                        "end of stream",
+                    )
+                    .at(start)
+                    .into())
+                }
+                Ok(n) => {
+                    let mut words = buf[..n].chunks_exact(8);
+                    for word in &mut words {
+                        let word = u64::from_ne_bytes(word.try_into().unwrap());
+                        ones += word.count_ones() as u64;
+                    }
+                    for byte in words.remainder() {
+                        ones += byte.count_ones() as u64;
+                    }
+                    self.position += n as u64 * 8;
+                    remaining -= n as u64 * 8;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(Error::wrap(e, "count_ones", start)),
+            }
+        }
+        let tail = self
+            .read_bits(remaining as u8)
+            .map_err(|e| Error::wrap(e, "count_ones", start))?;
+        Ok(ones + tail.count_ones() as u64)
+    }
+
+    fn next_bit(&mut self, target: u8) -> io::Result<Option<u64>> {
+        // The bytes passed over, and the same repeated through a word.
+        let skip: u8 = if target == 1 { 0 } else { 0xff };
+        let skip_word = u64::from_ne_bytes([skip; 8]);
+        loop {
+            let left = self.limit.map(|limit| limit.saturating_sub(self.position));
+            if left == Some(0) {
+                return Ok(None);
+            }
+
+            // Buffered bits, and the last few before a limit, go one by one.
+            if self.bits.len() > 0 || left.is_some_and(|left| left < 8) {
+                if self.bits.len() == 0 && !self.load_byte()? {
+                    return Ok(None);
+                }
+                let offset = self.position;
+                self.position += 1;
+                if self.bits.pop(1) as u8 == target {
+                    return Ok(Some(offset));
+                }
+                continue;
+            }
+
+            let max = left.map_or(8, |left| core::cmp::min(left / 8, 8)) as usize;
+            let mut buf = [skip; 8];
+            let n = match self.inner.read(&mut buf[..max]) {
+                Ok(0) => return Ok(None),
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let differs = u64::from_le_bytes(buf) ^ skip_word;
+            if differs == 0 {
+                self.position += n as u64 * 8;
+                continue;
+            }
+
+            let index = (differs.trailing_zeros() / 8) as usize;
+            let byte = buf[index] ^ skip;
+            let bit = if O::MSB_FIRST {
+                byte.leading_zeros()
+            } else {
+                byte.trailing_zeros()
+            } as u8;
+            for &rest in &buf[index..n] {
+                self.bits.push(8, rest as u64);
+            }
+            self.bits.pop(bit + 1);
+            let offset = self.position + index as u64 * 8 + bit as u64;
+            self.position = offset + 1;
+            return Ok(Some(offset));
+        }
+    }
+
+    /// Buffer one more byte from the inner reader, returning false at the end
+    /// of the stream.
+    fn load_byte(&mut self) -> io::Result<bool> {
+        let mut byte = [0];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(false),
+                Ok(_) => {
+                    self.bits.push(8, byte[0] as u64);
+                    return Ok(true);
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek, O: BitOrder> BitReader<R, O> {
+    /// Count the 1 bits in the `nbits` bits from `start_bit`, as for
+    /// `count_ones()`, then seek back to where the reader was, even if
+    /// counting failed.  Positions are as for `seek()`, and any bits pushed
+    /// back with `unread_bits()` are dropped.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BitReader::new(Cursor::new([0x0f, 0xf0, 0xff]));
+    /// assert_eq!(reader.count_ones_at(4, 8).unwrap(), 8);
+    /// assert_eq!(reader.position(), 0);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0x0f);
+    /// ```
+    pub fn count_ones_at(&mut self, start_bit: u64, nbits: u64) -> io::Result<u64> {
+        let here = self.position;
+        let result = self
+            .seek(SeekFrom::Start(start_bit))
+            .and_then(|_| self.count_ones(nbits));
+        self.seek(SeekFrom::Start(here))
+            .map_err(|e| Error::wrap(e, "count_ones_at", Some(start_bit)))?;
+        result
+    }
+}
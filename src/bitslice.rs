@@ -0,0 +1,49 @@
+//! Interop with the `bitvec` crate, enabled with the `bitvec` feature.
+//!
+//! Bit `i` of a `BitSlice` always corresponds to bit `i` of the stream, in the
+//! order the stream is read or written, whatever the `BitOrder` of the reader
+//! or writer.  With the default `Msb0` ordering this means a byte-aligned
+//! `BitSlice<u8, Msb0>` holds exactly the bytes of the stream.
+//!
+//! Data is moved up to 64 bits at a time using `bitvec`'s `BitField` loads and
+//! stores, so the slices may start and end at any alignment.
+
+#[cfg(feature = "alloc")]
+use bitvec::vec::BitVec;
+use bitvec::{field::BitField, order::Msb0 as BvMsb0, slice::BitSlice};
+
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read `nbits` bits into a new `BitVec`.
+    #[cfg(feature = "alloc")]
+    pub fn read_bitvec(&mut self, nbits: usize) -> io::Result<BitVec<u8, BvMsb0>> {
+        let mut bits = BitVec::repeat(false, nbits);
+        self.read_into_bitslice(&mut bits)?;
+        Ok(bits)
+    }
+
+    /// Fill `dst` with the next `dst.len()` bits.  If an error occurs, `dst`
+    /// may have been partially overwritten.
+    pub fn read_into_bitslice(&mut self, dst: &mut BitSlice<u8, BvMsb0>) -> io::Result<()> {
+        for chunk in dst.chunks_mut(64) {
+            let n = chunk.len() as u8;
+            let value = self.read_bits(n)?;
+            chunk.store_be(O::to_msb0(value, n));
+        }
+        Ok(())
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write every bit of `src`, returning the number of bits written.
+    pub fn write_bitslice(&mut self, src: &BitSlice<u8, BvMsb0>) -> io::Result<usize> {
+        for chunk in src.chunks(64) {
+            let n = chunk.len() as u8;
+            self.write_bits(n, O::to_msb0(chunk.load_be::<u64>(), n))?;
+        }
+        Ok(src.len())
+    }
+}
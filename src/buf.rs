@@ -0,0 +1,125 @@
+//! Interop with the `bytes` crate, enabled with the `bytes` feature.
+//!
+//! `BitReader::from_buf()` reads directly from any `Buf`, including ones made
+//! of several non-contiguous chunks, by copying bits out of `chunk()` and
+//! calling `advance()`.  `BitWriter::from_buf_mut()` appends straight into any
+//! `BufMut` such as `BytesMut`.  Neither needs an intermediate `Cursor` or copy
+//! of the data.
+
+use core::cmp;
+
+use bytes::{Buf, BufMut};
+
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::{BitReader, BitWriter};
+
+/// Adapts a `Buf` into an `io::Read` for use by `BitReader`.
+#[derive(Debug)]
+pub struct BufSource<B>(B);
+
+impl<B: Buf> BufSource<B> {
+    /// Wrap the given buffer.
+    pub fn new(buf: B) -> Self {
+        BufSource(buf)
+    }
+
+    /// Get a reference to the buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.0
+    }
+
+    /// Get a mutable reference to the buffer.
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.0
+    }
+
+    /// Unwrap the buffer.  Any bytes not yet read remain in it.
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: Buf> io::Read for BufSource<B> {
+    fn read(&mut self, mut dst: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while !dst.is_empty() && self.0.has_remaining() {
+            let chunk = self.0.chunk();
+            let n = cmp::min(chunk.len(), dst.len());
+            dst[..n].copy_from_slice(&chunk[..n]);
+            self.0.advance(n);
+            dst = &mut dst[n..];
+            read += n;
+        }
+        Ok(read)
+    }
+}
+
+/// Adapts a `BufMut` into an `io::Write` for use by `BitWriter`.  Writes fail
+/// with `WriteZero` once the buffer's `remaining_mut()` is exhausted.
+#[derive(Debug)]
+pub struct BufMutSink<B>(B);
+
+impl<B: BufMut> BufMutSink<B> {
+    /// Wrap the given buffer.
+    pub fn new(buf: B) -> Self {
+        BufMutSink(buf)
+    }
+
+    /// Get a reference to the buffer.
+    pub fn get_ref(&self) -> &B {
+        &self.0
+    }
+
+    /// Get a mutable reference to the buffer.
+    pub fn get_mut(&mut self) -> &mut B {
+        &mut self.0
+    }
+
+    /// Unwrap the buffer.
+    pub fn into_inner(self) -> B {
+        self.0
+    }
+}
+
+impl<B: BufMut> io::Write for BufMutSink<B> {
+    fn write(&mut self, src: &[u8]) -> io::Result<usize> {
+        let n = cmp::min(src.len(), self.0.remaining_mut());
+        self.0.put_slice(&src[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<B: Buf> BitReader<BufSource<B>> {
+    /// Create a new `BitReader` reading from the given `Buf`.
+    pub fn from_buf(buf: B) -> Self {
+        Self::with_order(BufSource::new(buf), Msb0)
+    }
+}
+
+impl<B: Buf, O: BitOrder> BitReader<BufSource<B>, O> {
+    /// Create a new `BitReader` reading from the given `Buf`, unpacking bits in
+    /// the given order.
+    pub fn from_buf_with_order(buf: B, order: O) -> Self {
+        Self::with_order(BufSource::new(buf), order)
+    }
+}
+
+impl<B: BufMut> BitWriter<BufMutSink<B>> {
+    /// Create a new `BitWriter` appending to the given `BufMut`.
+    pub fn from_buf_mut(buf: B) -> Self {
+        Self::with_order(BufMutSink::new(buf), Msb0)
+    }
+}
+
+impl<B: BufMut, O: BitOrder> BitWriter<BufMutSink<B>, O> {
+    /// Create a new `BitWriter` appending to the given `BufMut`, packing bits
+    /// in the given order.
+    pub fn from_buf_mut_with_order(buf: B, order: O) -> Self {
+        Self::with_order(BufMutSink::new(buf), order)
+    }
+}
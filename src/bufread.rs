@@ -0,0 +1,139 @@
+//! Refilling straight from a `BufRead`'s buffer.
+//!
+//! A plain `BitReader` loads each byte with its own `read_exact()` call, which
+//! through a `BufReader` means a bounds check and a copy per byte.  Borrowing
+//! the buffer with `fill_buf()` loads all the bytes a read needs in one go.
+
+use core::fmt;
+use std::io::{self, BufRead};
+
+use crate::order::{BitOrder, Msb0};
+use crate::traits::BitRead;
+use crate::BitReader;
+
+impl<R: BufRead> BitReader<R> {
+    /// Create a `BufBitReader` around the given `BufRead`, refilling from its
+    /// buffer without copying.
+    ///
+    /// ```
+    /// use std::io::BufReader;
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::from_buf_read(BufReader::new(&[0xab, 0xcd][..]));
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0xabc);
+    /// ```
+    pub fn from_buf_read(inner: R) -> BufBitReader<R> {
+        BufBitReader::new(BitReader::new(inner))
+    }
+}
+
+impl<R: BufRead, O: BitOrder> BitReader<R, O> {
+    /// Create a `BufBitReader` around the given `BufRead`, unpacking bits in
+    /// the given order.
+    pub fn from_buf_read_with_order(inner: R, order: O) -> BufBitReader<R, O> {
+        BufBitReader::new(BitReader::with_order(inner, order))
+    }
+}
+
+/// A `BitReader` over a `BufRead`, which loads the bytes for each read from
+/// the `BufRead`'s buffer in one step.  Created by `BitReader::from_buf_read()`.
+///
+/// It loads exactly the bytes the `BitReader` would have, so the two can be
+/// used interchangeably: anything not provided here, such as `seek()` or
+/// `skip_bits()`, can be done with `get_mut()`, and reading carries on as if
+/// it had all been done through the one.  Nothing is held back from the
+/// `BitReader`, so there's nothing to lose with `into_inner()`.
+pub struct BufBitReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+}
+
+impl<R, O: BitOrder> fmt::Debug for BufBitReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BufBitReader")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<R: BufRead, O: BitOrder> BufBitReader<R, O> {
+    /// Wrap an existing `BitReader`, which can be part way through the stream.
+    pub fn new(inner: BitReader<R, O>) -> Self {
+        Self { inner }
+    }
+
+    /// Read up to 64 bits, as for `BitReader::read_bits()`.
+    ///
+    /// If the `BufRead`'s buffer doesn't hold all the bytes needed, or
+    /// refilling it fails, this falls back to the `BitReader`'s usual path,
+    /// so errors and the bits kept buffered after them are the same.
+    #[inline]
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        let reader = &mut self.inner;
+        let have = reader.bits.len();
+        if have < nbits
+            && reader
+                .limit
+                .is_none_or(|limit| reader.position + nbits as u64 <= limit)
+        {
+            let need = (nbits - have).div_ceil(8) as usize;
+            if let Ok(buf) = reader.inner.fill_buf() {
+                if let Some(bytes) = buf.get(..need) {
+                    for &byte in bytes {
+                        reader.bits.push(8, byte as u64);
+                    }
+                    reader.inner.consume(need);
+                }
+            }
+        }
+        reader.read_bits(nbits)
+    }
+
+    /// Read a single bit, as for `BitReader::read_bit()`.
+    #[inline]
+    pub fn read_bit(&mut self) -> io::Result<u8> {
+        self.read_bits(1).map(|bit| bit as u8)
+    }
+
+    /// The number of bits consumed so far, as for `BitReader::position()`.
+    pub fn position(&self) -> u64 {
+        self.inner.position()
+    }
+
+    /// Get a reference to the `BitReader`.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the `BitReader`.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `BufBitReader`, returning the `BitReader`.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: BufRead, O: BitOrder> BitRead for BufBitReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        BufBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<u8> {
+        BufBitReader::read_bit(self)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.inner.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
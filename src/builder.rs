@@ -0,0 +1,199 @@
+//! Builders for configuring `BitReader` and `BitWriter`.
+//!
+//! `BitReader::new()` and `BitWriter::new()` remain the simple path, and always
+//! give the defaults documented here.  Every other option is set on a builder,
+//! so the plain constructors never need to grow more parameters.
+
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::vlc::DEFAULT_VLC_LIMIT;
+use crate::{BitReader, BitWriter};
+
+/// Configures and creates a `BitReader`.
+///
+/// The defaults, as used by `BitReader::new()`, are `Msb0` bit order, no
+/// limit, and variable-length codes limited to 1024 bits.
+///
+/// ```
+/// use bitrw::{BitReaderBuilder, Lsb0};
+///
+/// let mut reader = BitReaderBuilder::new()
+///     .bit_order(Lsb0)
+///     .limit_bits(12)
+///     .build(&[0xab, 0xcd][..]);
+///
+/// assert_eq!(reader.read_bits(12).unwrap(), 0xdab);
+/// assert!(reader.read_bit().is_err());
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BitReaderBuilder<O = Msb0> {
+    order: O,
+    limit: Option<u64>,
+    vlc_limit: Option<u32>,
+}
+
+impl<O: Default> Default for BitReaderBuilder<O> {
+    fn default() -> Self {
+        Self {
+            order: O::default(),
+            limit: None,
+            vlc_limit: Some(DEFAULT_VLC_LIMIT),
+        }
+    }
+}
+
+impl BitReaderBuilder {
+    /// Start with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<O: BitOrder> BitReaderBuilder<O> {
+    /// Unpack bits in the given order.
+    pub fn bit_order<O2: BitOrder>(self, order: O2) -> BitReaderBuilder<O2> {
+        BitReaderBuilder {
+            order,
+            limit: self.limit,
+            vlc_limit: self.vlc_limit,
+        }
+    }
+
+    /// Refuse to read past bit `limit` of the stream, as measured by
+    /// `BitReader::position()`, failing with `UnexpectedEof` as if the stream
+    /// ended there.
+    pub fn limit_bits(self, limit: u64) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Limit variable-length codes to `max_bits_per_code` bits, as for
+    /// `BitReader::set_vlc_limit()`, or remove the limit with `None`.
+    ///
+    /// # Panics
+    ///
+    /// If the limit is zero.
+    pub fn vlc_limit(self, max_bits_per_code: Option<u32>) -> Self {
+        assert!(
+            max_bits_per_code != Some(0),
+            "a code takes at least one bit"
+        );
+        Self {
+            vlc_limit: max_bits_per_code,
+            ..self
+        }
+    }
+
+    /// Create the `BitReader`.
+    pub fn build<R: io::Read>(self, inner: R) -> BitReader<R, O> {
+        let mut reader = BitReader::with_order(inner, self.order);
+        reader.limit = self.limit;
+        reader.vlc_limit = self.vlc_limit;
+        reader
+    }
+}
+
+/// Configures and creates a `BitWriter`.
+///
+/// The defaults, as used by `BitWriter::new()`, are `Msb0` bit order, padding
+/// with zero bits, no limit, and no batching: complete bytes are passed to the
+/// inner writer as soon as they're available.
+///
+/// ```
+/// use bitrw::BitWriterBuilder;
+///
+/// let mut writer = BitWriterBuilder::new()
+///     .capacity(4096)
+///     .pad_with_ones(true)
+///     .build(Vec::new());
+///
+/// writer.write_bits(12, 0xabc).unwrap();
+/// assert!(writer.get_ref().is_empty()); // still batched
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner(), [0xab, 0xcf]);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitWriterBuilder<O = Msb0> {
+    order: O,
+    limit: Option<u64>,
+    pad_ones: bool,
+    auto_flush: u64,
+    #[cfg(feature = "alloc")]
+    capacity: usize,
+}
+
+impl BitWriterBuilder {
+    /// Start with the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<O: BitOrder> BitWriterBuilder<O> {
+    /// Pack bits in the given order.
+    pub fn bit_order<O2: BitOrder>(self, order: O2) -> BitWriterBuilder<O2> {
+        BitWriterBuilder {
+            order,
+            limit: self.limit,
+            pad_ones: self.pad_ones,
+            auto_flush: self.auto_flush,
+            #[cfg(feature = "alloc")]
+            capacity: self.capacity,
+        }
+    }
+
+    /// Batch complete bytes in an internal buffer, passing them to the inner
+    /// writer once `capacity` bytes have accumulated or on `flush()`.  Zero,
+    /// the default, disables batching.
+    #[cfg(feature = "alloc")]
+    pub fn capacity(self, capacity: usize) -> Self {
+        Self { capacity, ..self }
+    }
+
+    /// Flush the inner writer every time at least `bytes` complete bytes have
+    /// been passed on to it since it was last flushed, as by
+    /// `BitWriter::frame_boundary()`, so no more than that is ever waiting
+    /// for stable storage.  Bytes batched in the internal buffer count, and
+    /// are written out first.  Nothing is ever padded: the bits of a partial
+    /// byte wait for the next write.  Zero, the default, disables it.
+    pub fn auto_flush_every_bytes(self, bytes: u64) -> Self {
+        Self {
+            auto_flush: bytes,
+            ..self
+        }
+    }
+
+    /// Pad the final byte with one bits rather than zero bits when flushing.
+    pub fn pad_with_ones(self, ones: bool) -> Self {
+        Self {
+            pad_ones: ones,
+            ..self
+        }
+    }
+
+    /// Refuse to write past bit `limit` of the stream, as measured by
+    /// `BitWriter::position()`, failing with `WriteZero`.  Padding added by
+    /// `flush()` is exempt.
+    pub fn limit_bits(self, limit: u64) -> Self {
+        Self {
+            limit: Some(limit),
+            ..self
+        }
+    }
+
+    /// Create the `BitWriter`.
+    pub fn build<W: io::Write>(self, inner: W) -> BitWriter<W, O> {
+        let mut writer = BitWriter::with_order(inner, self.order);
+        writer.limit = self.limit;
+        writer.pad_ones = self.pad_ones;
+        writer.auto_flush = self.auto_flush;
+        #[cfg(feature = "alloc")]
+        {
+            writer.capacity = self.capacity;
+            writer.buf = alloc::vec::Vec::with_capacity(self.capacity);
+        }
+        writer
+    }
+}
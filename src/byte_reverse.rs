@@ -0,0 +1,85 @@
+//! Mirroring the bits of each byte on the way in or out.
+//!
+//! Some links send each byte least significant bit first while the fields
+//! across bytes are still packed most significant first, so a capture of the
+//! bytes as received has every byte mirrored.  That isn't the same as an
+//! `Lsb0` stream, which changes how fields are packed too: here only the
+//! order within each byte flips, so a `ByteReverse` goes under an ordinary
+//! `Msb0` `BitReader` or `BitWriter`.
+
+use crate::io;
+
+/// Reverses the bits of every byte read from or written to the inner reader
+/// or writer.
+///
+/// Byte offsets are unchanged, so it can also seek when the inner stream can.
+///
+/// ```
+/// use bitrw::{BitReader, ByteReverse};
+///
+/// // A 4 bit field of 0x1 then 12 bits of 0x234, bytes 0x12 0x34 sent LSB
+/// // first.
+/// let capture = [0x48, 0x2c];
+/// let mut reader = BitReader::new(ByteReverse::new(&capture[..]));
+/// assert_eq!(reader.read_bits(4).unwrap(), 0x1);
+/// assert_eq!(reader.read_bits(12).unwrap(), 0x234);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ByteReverse<T> {
+    inner: T,
+}
+
+impl<T> ByteReverse<T> {
+    /// Wrap the given reader or writer.
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the inner reader or writer.
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader or writer.  Bytes passed
+    /// to it directly aren't reversed.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ByteReverse`, returning the inner reader or writer.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<R: io::Read> io::Read for ByteReverse<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        for byte in &mut buf[..len] {
+            *byte = byte.reverse_bits();
+        }
+        Ok(len)
+    }
+}
+
+impl<W: io::Write> io::Write for ByteReverse<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Reversed through a small buffer, as the caller's can't be changed.
+        let mut reversed = [0; 256];
+        let len = core::cmp::min(buf.len(), reversed.len());
+        for (out, byte) in reversed.iter_mut().zip(&buf[..len]) {
+            *out = byte.reverse_bits();
+        }
+        self.inner.write(&reversed[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<S: io::Seek> io::Seek for ByteReverse<S> {
+    fn seek(&mut self, pos: io::SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
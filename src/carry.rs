@@ -0,0 +1,225 @@
+//! Carry propagation into bits already written, for arithmetic and range
+//! coders.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::Msb0;
+use crate::traits::BitWrite;
+use crate::BitWriter;
+
+/// Writes bits which may later need one added to them, as the output of an
+/// arithmetic or range coder does when its low end overflows.
+///
+/// `propagate_carry()` adds one at the last bit written, treating everything
+/// written as a binary number, most significant bit first.  That can ripple
+/// back through any run of 0xff bytes to the byte before them, so each byte
+/// is held back from the inner writer until a later byte other than 0xff
+/// means no carry can reach it.  Only `Msb0` is supported, as that's the
+/// order the carry runs in.
+///
+/// Bytes here are groups of eight bits from the start of this writer, which
+/// needn't line up with the inner writer's bytes.
+///
+/// ```
+/// use bitrw::{BitWriter, CarryBitWriter};
+///
+/// let mut writer = CarryBitWriter::new(BitWriter::new(Vec::new()));
+/// writer.write_bits(24, 0x12_ffff).unwrap();
+/// writer.write_bits(4, 0xf).unwrap();
+/// writer.propagate_carry().unwrap();
+/// writer.write_bits(4, 0x5).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0x13, 0x00, 0x00, 0x05]);
+/// ```
+pub struct CarryBitWriter<W> {
+    inner: BitWriter<W, Msb0>,
+    // The last byte which could take a carry, if any, followed by `ones`
+    // bytes of 0xff.
+    cache: Option<u8>,
+    ones: u64,
+    // The bits since the last whole byte, which take a carry first.
+    partial: Accumulator<Msb0>,
+    position: u64,
+}
+
+impl<W> fmt::Debug for CarryBitWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CarryBitWriter")
+            .field("inner", &self.inner)
+            .field("cache", &self.cache)
+            .field("ones", &self.ones)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: io::Write> CarryBitWriter<W> {
+    /// Wrap `inner`.
+    pub fn new(inner: BitWriter<W, Msb0>) -> Self {
+        Self {
+            inner,
+            cache: None,
+            ones: 0,
+            partial: Accumulator::new(),
+            position: 0,
+        }
+    }
+
+    /// Write up to 64 bits.  Bytes are passed on to the inner writer once
+    /// they can no longer take a carry.
+    ///
+    /// If the inner writer fails, the bytes it was given are lost.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<Msb0>::new();
+        bits.push(nbits, value);
+        while bits.len() > 0 {
+            let chunk = core::cmp::min(bits.len(), 8 - self.partial.len());
+            self.partial.push(chunk, bits.pop(chunk));
+            self.position += chunk as u64;
+            if self.partial.len() == 8 {
+                let byte = self.partial.pop(8) as u8;
+                self.push_byte(byte)?;
+            }
+        }
+        Ok(nbits as usize)
+    }
+
+    /// Add one at the last bit written if `carry` is set, then write `nbits`
+    /// of `value`, as a coder does when moving on from an interval which
+    /// overflowed.
+    pub fn write_bits_with_carry(
+        &mut self,
+        nbits: u8,
+        value: u64,
+        carry: bool,
+    ) -> io::Result<usize> {
+        if carry {
+            self.propagate_carry()?;
+        }
+        self.write_bits(nbits, value)
+    }
+
+    fn push_byte(&mut self, byte: u8) -> io::Result<()> {
+        if byte == 0xff {
+            self.ones += 1;
+            return Ok(());
+        }
+        self.release()?;
+        self.cache = Some(byte);
+        Ok(())
+    }
+
+    /// Pass the cached byte and the 0xff bytes after it on to the inner
+    /// writer.
+    fn release(&mut self) -> io::Result<()> {
+        if let Some(cache) = self.cache.take() {
+            self.inner.write_bits(8, cache as u64)?;
+        }
+        while self.ones > 0 {
+            let run = core::cmp::min(self.ones, 8) as u8;
+            self.inner.write_bits(run * 8, u64::MAX)?;
+            self.ones -= run as u64;
+        }
+        Ok(())
+    }
+
+    /// Add one at the last bit written, carrying back through the bits
+    /// already written as far as it goes.
+    ///
+    /// A carry back past the bytes held, whether into bytes passed on to the
+    /// inner writer or before the first bit written, is an `InvalidInput`
+    /// error at the current position, and nothing is changed.  That can only
+    /// happen if a byte which took a carry takes another before a byte other
+    /// than 0xff follows it, which a correct coder never does.
+    pub fn propagate_carry(&mut self) -> io::Result<()> {
+        let len = self.partial.len();
+        let partial = self.partial.pop(len);
+        if len > 0 && partial + 1 < 1 << len {
+            self.partial.push(len, partial + 1);
+            return Ok(());
+        }
+        self.partial.push(len, partial);
+
+        let cache = match self.cache {
+            Some(cache) if cache != 0xff => cache,
+            _ => {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "propagate_carry",
+                    "carry past bytes held",
+                )
+                .at(Some(self.position))
+                .into())
+            }
+        };
+        self.partial.clear();
+        self.partial.push(len, 0);
+        if self.ones == 0 {
+            self.cache = Some(cache + 1);
+        } else {
+            // Everything but the last of the 0xff bytes, now zero, is safe.
+            self.inner.write_bits(8, cache as u64 + 1)?;
+            self.ones -= 1;
+            while self.ones > 0 {
+                let run = core::cmp::min(self.ones, 8) as u8;
+                self.inner.write_bits(run * 8, 0)?;
+                self.ones -= run as u64;
+            }
+            self.cache = Some(0);
+        }
+        Ok(())
+    }
+
+    /// The number of whole bytes held back in case of a carry.
+    pub fn held_bytes(&self) -> u64 {
+        self.cache.is_some() as u64 + self.ones
+    }
+
+    /// Pass everything held on to the inner writer, then pad it to a byte
+    /// boundary and flush it, as for `BitWriter::flush()`.  Nothing written
+    /// before this can take a carry afterwards.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.release()?;
+        let len = self.partial.len();
+        self.inner.write_bits(len, self.partial.pop(len))?;
+        self.inner.flush()
+    }
+
+    /// The number of bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &BitWriter<W, Msb0> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Bits written to it
+    /// directly go before any bits held back.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, Msb0> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `CarryBitWriter`, returning the inner writer.  Bits held
+    /// back are lost: see `flush()`.
+    pub fn into_inner(self) -> BitWriter<W, Msb0> {
+        self.inner
+    }
+}
+
+impl<W: io::Write> BitWrite for CarryBitWriter<W> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        CarryBitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
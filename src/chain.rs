@@ -0,0 +1,148 @@
+//! Reading one bit stream after another, as if they were one.
+//!
+//! The join needn't fall on a byte boundary: whatever bits the first reader
+//! has left are followed directly by the second's, so a field can straddle
+//! the two.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::io;
+use crate::order::BitOrder;
+use crate::traits::BitRead;
+use crate::BitReader;
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Chain this reader with another of the same bit order, reading from this
+    /// one until it runs out, then from `next`.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut first = BitReader::new(&[0xab][..]);
+    /// first.read_bits(3).unwrap();
+    ///
+    /// let mut reader = first.chain(BitReader::new(&[0xcd][..]));
+    /// assert_eq!(reader.read_bits(9).unwrap(), 0b01011_1100);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xd);
+    /// ```
+    pub fn chain<R2: io::Read>(self, next: BitReader<R2, O>) -> ChainedBitReader<R, R2, O> {
+        ChainedBitReader {
+            position: self.position,
+            first: self,
+            second: next,
+            carry: Accumulator::new(),
+            first_done: false,
+        }
+    }
+}
+
+/// Reads from one `BitReader` until its end, then another, with the bit phase
+/// carrying on across the join.  Created by `BitReader::chain()`.
+///
+/// The first reader is finished when it fails with `UnexpectedEof`, either at
+/// the end of its stream or its limit.  Any other error is returned as usual,
+/// and reading can be retried.
+pub struct ChainedBitReader<R, R2, O> {
+    first: BitReader<R, O>,
+    second: BitReader<R2, O>,
+    // Bits left over from the end of `first`, to be read before `second`.
+    carry: Accumulator<O>,
+    first_done: bool,
+    position: u64,
+}
+
+impl<R, R2, O: BitOrder> fmt::Debug for ChainedBitReader<R, R2, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChainedBitReader")
+            .field("first", &self.first)
+            .field("second", &self.second)
+            .field("carry", &self.carry.len())
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, R2: io::Read, O: BitOrder> ChainedBitReader<R, R2, O> {
+    /// Read up to 64 bits, from the first reader, the second, or both.
+    ///
+    /// As for `BitReader::read_bits()` nothing is consumed if the read fails.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        if !self.first_done {
+            if self.carry.len() == 0 {
+                match self.first.read_bits(nbits) {
+                    Ok(value) => {
+                        self.position += nbits as u64;
+                        return Ok(value);
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            self.finish_first()?;
+        }
+
+        if self.carry.len() < nbits {
+            let tail = nbits - self.carry.len();
+            let value = self.second.read_bits(tail)?;
+            self.carry.push(tail, value);
+        }
+        self.position += nbits as u64;
+        Ok(self.carry.pop(nbits))
+    }
+
+    /// Move whatever's left of the first reader into `carry`.  There's less
+    /// than a read's worth, so this is at most 63 bits.
+    fn finish_first(&mut self) -> io::Result<()> {
+        loop {
+            match self.first.read_bit() {
+                Ok(bit) => self.carry.push(1, bit as u64),
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e),
+            }
+        }
+        self.first_done = true;
+        Ok(())
+    }
+
+    /// The number of bits read so far, starting from the first reader's
+    /// position when they were chained.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Whether the first reader has run out, so reads come from the second.
+    pub fn first_done(&self) -> bool {
+        self.first_done
+    }
+
+    /// Get references to the two readers.
+    pub fn get_ref(&self) -> (&BitReader<R, O>, &BitReader<R2, O>) {
+        (&self.first, &self.second)
+    }
+
+    /// Unwrap this `ChainedBitReader`, returning the two readers.  Any bits
+    /// left over from the end of the first which haven't been read yet are
+    /// lost.
+    pub fn into_inner(self) -> (BitReader<R, O>, BitReader<R2, O>) {
+        (self.first, self.second)
+    }
+}
+
+impl<R: io::Read, R2: io::Read, O: BitOrder> BitRead for ChainedBitReader<R, R2, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        ChainedBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.first.vlc_limit()
+    }
+}
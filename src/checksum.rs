@@ -0,0 +1,185 @@
+//! Pluggable checksums over exact runs of bits.
+//!
+//! `CrcBitReader` and `CrcBitWriter` feed every bit through a `BitDigest`,
+//! which by default is a bitwise `Crc`.  Byte-oriented hashers can be plugged
+//! in with `HasherDigest`, or with the `digest` feature `UpdateDigest`, which
+//! pack the bits into bytes exactly as a reader or writer of the given
+//! `BitOrder` would, buffering any partial byte until it's complete.
+
+use core::hash::Hasher;
+
+use crate::accumulator::Accumulator;
+use crate::order::BitOrder;
+use crate::Msb0;
+
+/// A checksum or hash computed over a sequence of bits.
+pub trait BitDigest {
+    /// The result of the digest.
+    type Output;
+
+    /// Feed the low `nbits` bits of `value` into the digest, most significant
+    /// first, which is the order they appear in the stream.
+    fn update_bits(&mut self, value: u64, nbits: u8);
+
+    /// The digest of the bits fed in so far.
+    fn finalize(&self) -> Self::Output;
+}
+
+/// Packs bits into bytes in order `O`.
+#[derive(Debug, Clone)]
+struct BytePacker<O> {
+    bits: Accumulator<O>,
+}
+
+impl<O: BitOrder> BytePacker<O> {
+    fn new() -> Self {
+        Self {
+            bits: Accumulator::new(),
+        }
+    }
+
+    /// Queue `nbits` bits given most significant first, passing on any
+    /// completed bytes.
+    fn push(&mut self, value: u64, nbits: u8, mut complete: impl FnMut(&[u8])) {
+        assert!(nbits <= 64);
+        if nbits == 0 {
+            return;
+        }
+        self.bits.push(nbits, O::to_msb0(value, nbits));
+
+        let mut bytes = [0; 9];
+        let n = self.bits.drain_bytes(&mut bytes);
+        if n > 0 {
+            complete(&bytes[..n]);
+        }
+    }
+
+    /// The trailing partial byte, padded with zero bits, if there is one.
+    fn tail(&self) -> Option<u8> {
+        let mut bits = self.bits;
+        if bits.len() == 0 {
+            return None;
+        }
+        bits.pad(false);
+        Some(bits.pop(8) as u8)
+    }
+}
+
+/// Adapts a `core::hash::Hasher` into a `BitDigest`, so it sees the bytes a
+/// `BitWriter` of order `O` would produce from the same bits.  When finalizing,
+/// any trailing partial byte is zero-padded, as `BitWriter::flush()` would.
+#[derive(Debug, Clone)]
+pub struct HasherDigest<H, O = Msb0> {
+    hasher: H,
+    packer: BytePacker<O>,
+}
+
+impl<H: Hasher> HasherDigest<H> {
+    /// Wrap `hasher`, packing bits most-significant first.
+    pub fn new(hasher: H) -> Self {
+        Self::with_order(hasher, Msb0)
+    }
+}
+
+impl<H: Hasher, O: BitOrder> HasherDigest<H, O> {
+    /// Wrap `hasher`, packing bits in the given order.
+    pub fn with_order(hasher: H, _order: O) -> Self {
+        Self {
+            hasher,
+            packer: BytePacker::new(),
+        }
+    }
+
+    /// Get a reference to the hasher.  It won't have seen any trailing partial
+    /// byte.
+    pub fn get_ref(&self) -> &H {
+        &self.hasher
+    }
+
+    /// Unwrap this `HasherDigest`, discarding any trailing partial byte.
+    pub fn into_inner(self) -> H {
+        self.hasher
+    }
+}
+
+impl<H: Hasher + Clone, O: BitOrder> BitDigest for HasherDigest<H, O> {
+    type Output = u64;
+
+    fn update_bits(&mut self, value: u64, nbits: u8) {
+        let hasher = &mut self.hasher;
+        self.packer.push(value, nbits, |bytes| hasher.write(bytes));
+    }
+
+    fn finalize(&self) -> u64 {
+        match self.packer.tail() {
+            Some(byte) => {
+                let mut hasher = self.hasher.clone();
+                hasher.write(&[byte]);
+                hasher.finish()
+            }
+            None => self.hasher.finish(),
+        }
+    }
+}
+
+/// Adapts a RustCrypto `digest::Update` hash such as SHA-256 into a
+/// `BitDigest`, packing bits into bytes as for `HasherDigest`.
+#[cfg(feature = "digest")]
+#[derive(Debug, Clone)]
+pub struct UpdateDigest<D, O = Msb0> {
+    digest: D,
+    packer: BytePacker<O>,
+}
+
+#[cfg(feature = "digest")]
+impl<D: digest::Update> UpdateDigest<D> {
+    /// Wrap `digest`, packing bits most-significant first.
+    pub fn new(digest: D) -> Self {
+        Self::with_order(digest, Msb0)
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D: digest::Update, O: BitOrder> UpdateDigest<D, O> {
+    /// Wrap `digest`, packing bits in the given order.
+    pub fn with_order(digest: D, _order: O) -> Self {
+        Self {
+            digest,
+            packer: BytePacker::new(),
+        }
+    }
+
+    /// Get a reference to the digest.  It won't have seen any trailing partial
+    /// byte.
+    pub fn get_ref(&self) -> &D {
+        &self.digest
+    }
+
+    /// Unwrap this `UpdateDigest`, discarding any trailing partial byte.
+    pub fn into_inner(self) -> D {
+        self.digest
+    }
+}
+
+#[cfg(feature = "digest")]
+impl<D, O> BitDigest for UpdateDigest<D, O>
+where
+    D: digest::Update + digest::FixedOutput + Clone,
+    O: BitOrder,
+{
+    type Output = digest::Output<D>;
+
+    fn update_bits(&mut self, value: u64, nbits: u8) {
+        let digest = &mut self.digest;
+        self.packer
+            .push(value, nbits, |bytes| digest::Update::update(digest, bytes));
+    }
+
+    fn finalize(&self) -> Self::Output {
+        let mut digest = self.digest.clone();
+        if let Some(byte) = self.packer.tail() {
+            digest::Update::update(&mut digest, &[byte]);
+        }
+        digest.finalize_fixed()
+    }
+}
@@ -0,0 +1,316 @@
+//! Composable bit-level serialization.
+//!
+//! A type implementing `FromBits` and `ToBits` describes its own bit layout in
+//! terms of `BitRead` and `BitWrite`, so a structure is simply its fields'
+//! implementations called in order:
+//!
+//! ```
+//! use bitrw::{io, BitRead, BitWrite, FromBits, ToBits};
+//!
+//! struct Header {
+//!     version: u8,
+//!     large: bool,
+//!     length: u16,
+//! }
+//!
+//! impl FromBits for Header {
+//!     fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+//!         Ok(Header {
+//!             version: r.read_bits(3)? as u8,
+//!             large: bool::from_bits(r)?,
+//!             length: u16::from_bits(r)?,
+//!         })
+//!     }
+//! }
+//!
+//! impl ToBits for Header {
+//!     fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+//!         Ok(w.write_bits(3, self.version as u64)?
+//!             + self.large.to_bits(w)?
+//!             + self.length.to_bits(w)?)
+//!     }
+//! }
+//! ```
+//!
+//! With the `derive` feature, `#[derive(FromBits, ToBits)]` writes these for a
+//! struct, with `#[bits(n)]`, `#[bits(signed, n)]`, `#[skip(n)]` and
+//! `#[align(n)]` attributes to give fields other widths and padding, as
+//! described by the `bitrw-derive` crate.
+//!
+//! Implementations are provided for the primitive integer and float types
+//! (`T::BITS` wide, in the stream's natural order), `bool` (one bit), arrays
+//! (each element in turn), and `Option<T>` (a presence bit, then the value if
+//! set).  Slices and `Vec`s write each element in turn; since the length isn't
+//! part of the encoding, they're read back with `BitRead::read_vec()`.
+//!
+//! Note `f32` and `f64` have inherent `from_bits` and `to_bits` methods which
+//! take precedence over these traits in method-call syntax; use
+//! `BitRead::read()` and `BitWrite::write()` for them, or the fully qualified
+//! `ToBits::to_bits(&x, w)`.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::io;
+use crate::traits::{BitRead, BitWrite};
+
+/// A type which can be decoded from a bit stream.
+pub trait FromBits: Sized {
+    /// Read a value from `r`.
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self>;
+}
+
+/// A type which can be encoded into a bit stream.
+pub trait ToBits {
+    /// Write this value to `w`, returning the number of bits written.
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize>;
+}
+
+macro_rules! primitive_codec {
+    ($($t:ty),*) => {
+        $(
+            impl FromBits for $t {
+                #[inline]
+                fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+                    r.read::<$t>()
+                }
+            }
+
+            impl ToBits for $t {
+                #[inline]
+                fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+                    w.write(*self)
+                }
+            }
+        )*
+    };
+}
+
+primitive_codec!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+#[cfg(feature = "half")]
+primitive_codec!(half::f16, half::bf16);
+
+impl FromBits for bool {
+    #[inline]
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+        Ok(r.read_bit()? == 1)
+    }
+}
+
+impl ToBits for bool {
+    #[inline]
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        w.write_bit(*self as u8)?;
+        Ok(1)
+    }
+}
+
+impl<T: FromBits, const N: usize> FromBits for [T; N] {
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+        let mut error = None;
+        let items: [Option<T>; N] = core::array::from_fn(|_| {
+            if error.is_some() {
+                return None;
+            }
+            T::from_bits(r).map_err(|e| error = Some(e)).ok()
+        });
+
+        match error {
+            Some(e) => Err(e),
+            None => Ok(items.map(|item| item.expect("element decoded"))),
+        }
+    }
+}
+
+impl<T: ToBits, const N: usize> ToBits for [T; N] {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        self[..].to_bits(w)
+    }
+}
+
+impl<T: ToBits> ToBits for [T] {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        let mut written = 0;
+        for item in self {
+            written += item.to_bits(w)?;
+        }
+        Ok(written)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: ToBits> ToBits for Vec<T> {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        self[..].to_bits(w)
+    }
+}
+
+impl<T: FromBits> FromBits for Option<T> {
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+        if bool::from_bits(r)? {
+            Ok(Some(T::from_bits(r)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+impl<T: ToBits> ToBits for Option<T> {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        match self {
+            Some(value) => Ok(true.to_bits(w)? + value.to_bits(w)?),
+            None => false.to_bits(w),
+        }
+    }
+}
+
+impl<T: ToBits + ?Sized> ToBits for &T {
+    #[inline]
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        (**self).to_bits(w)
+    }
+}
+
+/// Support for the code generated by `#[derive(FromBits, ToBits)]`, not part
+/// of the API.
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive_support {
+    use crate::error::Error;
+    use crate::io;
+    use crate::traits::{BitRead, BitWrite};
+
+    /// Skip `nbits` reserved bits.
+    pub fn skip<R: BitRead>(r: &mut R, nbits: u64) -> io::Result<()> {
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let chunk = core::cmp::min(remaining, 64) as u8;
+            r.read_bits(chunk)?;
+            remaining -= chunk as u64;
+        }
+        Ok(())
+    }
+
+    /// Write `nbits` zero bits in place of reserved bits.
+    pub fn pad<W: BitWrite>(w: &mut W, nbits: u64) -> io::Result<usize> {
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let chunk = core::cmp::min(remaining, 64) as u8;
+            w.write_bits(chunk, 0)?;
+            remaining -= chunk as u64;
+        }
+        Ok(nbits as usize)
+    }
+
+    /// The number of bits from `position` to the next multiple of `align`.
+    fn padding(op: &'static str, position: Option<u64>, align: u64) -> io::Result<u64> {
+        match position {
+            Some(position) => Ok((align - position % align) % align),
+            None => Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                op,
+                "alignment needs the bit position",
+            )
+            .into()),
+        }
+    }
+
+    /// Skip to the next multiple of `align` bits.
+    pub fn align_read<R: BitRead>(r: &mut R, align: u64) -> io::Result<()> {
+        let nbits = padding("align", r.bit_position(), align)?;
+        skip(r, nbits)
+    }
+
+    /// Write zero bits up to the next multiple of `align` bits.
+    pub fn align_write<W: BitWrite>(w: &mut W, align: u64) -> io::Result<usize> {
+        let nbits = padding("align", w.bit_position(), align)?;
+        pad(w, nbits)
+    }
+}
+
+/// Invalid uses of `#[derive(FromBits, ToBits)]`, which must fail to compile,
+/// alongside a valid one which must not.
+///
+/// ```
+/// #[derive(bitrw::FromBits, bitrw::ToBits)]
+/// struct Valid {
+///     #[bits(64)]
+///     a: u64,
+///     #[bits(signed, 8)]
+///     b: i8,
+///     #[skip(100)]
+///     #[align(32)]
+///     c: bool,
+/// }
+/// ```
+///
+/// A width of zero:
+///
+/// ```compile_fail
+/// #[derive(bitrw::FromBits)]
+/// struct Zero {
+///     #[bits(0)]
+///     a: u8,
+/// }
+/// ```
+///
+/// A width of more than 64:
+///
+/// ```compile_fail
+/// #[derive(bitrw::ToBits)]
+/// struct TooWide {
+///     #[bits(65)]
+///     a: u64,
+/// }
+/// ```
+///
+/// A width of more than the field's type:
+///
+/// ```compile_fail
+/// #[derive(bitrw::FromBits)]
+/// struct WiderThanType {
+///     #[bits(9)]
+///     a: u8,
+/// }
+/// ```
+///
+/// No width:
+///
+/// ```compile_fail
+/// #[derive(bitrw::FromBits)]
+/// struct NoWidth {
+///     #[bits(signed)]
+///     a: i8,
+/// }
+/// ```
+///
+/// A width on a field which isn't an integer:
+///
+/// ```compile_fail
+/// #[derive(bitrw::FromBits)]
+/// struct NotInteger {
+///     #[bits(1)]
+///     a: bool,
+/// }
+/// ```
+///
+/// An alignment of zero:
+///
+/// ```compile_fail
+/// #[derive(bitrw::ToBits)]
+/// struct AlignZero {
+///     #[align(0)]
+///     a: u8,
+/// }
+/// ```
+///
+/// An enum:
+///
+/// ```compile_fail
+/// #[derive(bitrw::FromBits)]
+/// enum Enum {
+///     A,
+/// }
+/// ```
+#[cfg(all(doctest, feature = "derive"))]
+pub struct DeriveCompileFail;
@@ -0,0 +1,13 @@
+//! Constrained whole numbers, as ASN.1's unaligned packed encoding rules
+//! (X.691) encode an `INTEGER (lb..ub)`: the offset from `lb` in the fewest
+//! bits which hold every value of the range, so `ceil(log2(ub - lb + 1))`.
+//!
+//! A range of a single value takes no bits at all, and the full range of an
+//! `i64` takes 64.
+
+/// The largest offset in `[lb, ub]`, and the bits which hold it.
+pub(crate) fn span(lb: i64, ub: i64) -> (u64, u8) {
+    assert!(lb <= ub, "constrained range must have lb <= ub");
+    let span = ub.wrapping_sub(lb) as u64;
+    (span, (64 - span.leading_zeros()) as u8)
+}
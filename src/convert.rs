@@ -0,0 +1,178 @@
+//! Turning a reader into a writer over the same stream, and back, for patching
+//! fields in place, and appending to a stream which ends part way through a
+//! byte.
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::{BitOrder, Msb0};
+use crate::{BitReader, BitWriter};
+
+impl<R: io::Read + io::Write + io::Seek, O: BitOrder> BitReader<R, O> {
+    /// Turn this reader into a writer over the same stream, writing from the
+    /// next bit this reader would have read.  Any limit carries over.
+    ///
+    /// Bytes loaded but not yet read are given back by seeking the inner
+    /// stream back over them.  At a position within a byte, that byte is read
+    /// again so the writer can keep the bits before the position; the bits
+    /// after it are overwritten by the writer, so everything written should be
+    /// flushed or passed back with `BitWriter::into_reader()`, which keeps the
+    /// rest of the final byte.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(Cursor::new(vec![0xab, 0xcd]));
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// let mut writer = reader.into_writer().unwrap();
+    /// writer.write_bits(8, 0x00).unwrap();
+    /// let mut reader = writer.into_reader().unwrap();
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xd);
+    /// assert_eq!(reader.into_inner().into_inner(), [0xa0, 0x0d]);
+    /// ```
+    pub fn into_writer(self) -> io::Result<BitWriter<R, O>> {
+        let BitReader {
+            mut inner,
+            bits,
+            position,
+            limit,
+            ..
+        } = self;
+        let loaded = u64::from(bits.len()).div_ceil(8);
+        inner.seek(SeekFrom::Current(-(loaded as i64)))?;
+
+        let mut pending = Accumulator::<O>::new();
+        let phase = (position % 8) as u8;
+        if phase > 0 {
+            let mut byte = [0];
+            inner.read_exact(&mut byte)?;
+            inner.seek(SeekFrom::Current(-1))?;
+            pending.push(8, byte[0] as u64);
+            let kept = pending.pop(phase);
+            pending.clear();
+            pending.push(phase, kept);
+        }
+
+        let mut writer = BitWriter::with_order(inner, O::default());
+        writer.bits = pending;
+        writer.position = position;
+        writer.limit = limit;
+        Ok(writer)
+    }
+}
+
+impl<W: io::Read + io::Write + io::Seek, O: BitOrder> BitWriter<W, O> {
+    /// Turn this writer into a reader over the same stream, reading from the
+    /// next bit this writer would have written.  Any limit carries over.
+    ///
+    /// Everything written is passed on to the inner stream and it's flushed.
+    /// At a position within a byte, the rest of that byte is read from the
+    /// stream and kept, rather than padded, so a field can be overwritten in
+    /// place without disturbing the bits around it.  If the stream ends there
+    /// the byte is padded as `flush()` would, and the padding is left for the
+    /// reader.
+    pub fn into_reader(mut self) -> io::Result<BitReader<W, O>> {
+        self.write_buffered()?;
+        let phase = self.bits.len();
+        let mut rest = Accumulator::<O>::new();
+        if phase > 0 {
+            let mut byte = [0];
+            let original = match self.inner.read(&mut byte)? {
+                0 => {
+                    let mut padded = self.bits;
+                    padded.pad(self.pad_ones);
+                    padded.pop(8) as u8
+                }
+                _ => {
+                    self.inner.seek(SeekFrom::Current(-1))?;
+                    byte[0]
+                }
+            };
+            rest.push(8, original as u64);
+            rest.pop(phase);
+            let mut merged = self.bits;
+            let mut tail = rest;
+            merged.push(8 - phase, tail.pop(8 - phase));
+            self.inner.write_all(&[merged.pop(8) as u8])?;
+        }
+        self.inner.flush()?;
+
+        Ok(BitReader {
+            inner: self.inner,
+            bits: rest,
+            position: self.position,
+            limit: self.limit,
+            vlc_limit: Some(crate::vlc::DEFAULT_VLC_LIMIT),
+        })
+    }
+}
+
+impl<W: io::Read + io::Write + io::Seek> BitWriter<W> {
+    /// Create a writer which carries on from the first `valid_bits` bits of
+    /// `inner`, as for `append_to_with_order()`.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Cursor::new(Vec::new()));
+    /// writer.write_bits(4, 0xa).unwrap();
+    /// writer.flush().unwrap();
+    ///
+    /// let mut writer = BitWriter::append_to(writer.into_inner(), 4).unwrap();
+    /// writer.write_bits(4, 0xb).unwrap();
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner().into_inner(), [0xab]);
+    /// ```
+    pub fn append_to(inner: W, valid_bits: u64) -> io::Result<Self> {
+        Self::append_to_with_order(inner, valid_bits, Msb0)
+    }
+}
+
+impl<W: io::Read + io::Write + io::Seek, O: BitOrder> BitWriter<W, O> {
+    /// Create a writer which carries on from the first `valid_bits` bits of
+    /// `inner`, such as a log written in earlier sessions, so the next bit
+    /// written follows straight on from them.
+    ///
+    /// If `valid_bits` isn't a whole number of bytes, the last byte is read
+    /// back and its valid bits kept, and the padding after them is
+    /// overwritten by the next bits written.  Nothing needs to have been
+    /// saved but the bit count.  `position()` starts at `valid_bits`.  Any
+    /// bytes after the valid bits are overwritten as the writer goes, and the
+    /// stream isn't truncated.
+    ///
+    /// A stream shorter than `valid_bits` is an `InvalidInput` error carrying
+    /// its length in bits, with `valid_bits` as the value expected.
+    pub fn append_to_with_order(mut inner: W, valid_bits: u64, order: O) -> io::Result<Self> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        if len.saturating_mul(8) < valid_bits {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "append_to",
+                "stream shorter than valid bits",
+            )
+            .with_value(len.saturating_mul(8))
+            .with_expected(valid_bits)
+            .into());
+        }
+
+        let phase = (valid_bits % 8) as u8;
+        inner.seek(SeekFrom::Start(valid_bits / 8))?;
+        let mut pending = Accumulator::<O>::new();
+        if phase > 0 {
+            let mut byte = [0];
+            inner.read_exact(&mut byte)?;
+            inner.seek(SeekFrom::Start(valid_bits / 8))?;
+            pending.push(8, byte[0] as u64);
+            let kept = pending.pop(phase);
+            pending.clear();
+            pending.push(phase, kept);
+        }
+
+        let mut writer = Self::with_order(inner, order);
+        writer.bits = pending;
+        writer.position = valid_bits;
+        Ok(writer)
+    }
+}
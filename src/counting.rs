@@ -0,0 +1,118 @@
+//! Measuring how many bits an encoding takes without writing it.
+
+use crate::error::Error;
+use crate::io;
+use crate::traits::BitWrite;
+
+/// A `BitWrite` which only counts the bits written to it, for a dry run of an
+/// encoder to find its size before writing it for real, or to choose between
+/// encodings.
+///
+/// It has no inner writer and discards everything, so it costs little more
+/// than the encoder itself.
+///
+/// Only what's written through `BitWrite` is counted.  The few writers which
+/// depend on a `BitWriter`'s bit order or settings are inherent on it and
+/// aren't available here: `write_rle()`, `write_bits_big()` and
+/// `write_samples_i32()` for the order, `write_rice_block()` for the limit,
+/// `pack_row()` for its padding, and `write_gsm7()`, which is only for `Lsb0`.
+///
+/// ```
+/// use bitrw::{BitWrite, CountingBitWriter};
+///
+/// let mut counter = CountingBitWriter::new();
+/// counter.write_bits(5, 0x1f).unwrap();
+/// counter.write_gamma(9).unwrap();
+/// assert_eq!(counter.bits(), 12);
+/// assert_eq!(counter.flush().unwrap(), 4);
+/// assert_eq!(counter.bits(), 16);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CountingBitWriter {
+    bits: u64,
+}
+
+impl CountingBitWriter {
+    /// Create a `CountingBitWriter` which has counted nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of bits written so far, including any padding from
+    /// `flush()`.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// Count the bits written from here on from zero.
+    pub fn reset(&mut self) {
+        self.bits = 0;
+    }
+
+    /// Count a write of up to 64 bits, as `BitWriter::write_bits()`.
+    #[inline]
+    pub fn write_bits(&mut self, nbits: u8, _value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        self.bits += nbits as u64;
+        Ok(nbits as usize)
+    }
+
+    /// Count a write of a single bit, as `BitWriter::write_bit()`.
+    #[inline]
+    pub fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        assert!(bit <= 1);
+        self.bits += 1;
+        Ok(())
+    }
+
+    /// Count a run of fields, as `BitWriter::write_many()`, failing the same
+    /// way on a width of more than 64.
+    pub fn write_many(&mut self, fields: &[(u8, u64)]) -> io::Result<usize> {
+        let mut total = 0;
+        for (index, &(nbits, _)) in fields.iter().enumerate() {
+            if nbits > 64 {
+                return Err(
+                    Error::new(io::ErrorKind::InvalidInput, "write_many", "invalid width")
+                        .at(Some(self.bits))
+                        .with_value(index as u64)
+                        .into(),
+                );
+            }
+            total += nbits as u64;
+        }
+        self.bits += total;
+        Ok(total as usize)
+    }
+
+    /// Count whole bytes, as `BitWriter::write_bytes()`.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let nbits = bytes.len() as u64 * 8;
+        self.bits += nbits;
+        Ok(nbits as usize)
+    }
+
+    /// Count the padding `BitWriter::flush()` would add to reach a byte
+    /// boundary, returning how many bits that is.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        let padding = (8 - self.bits % 8) % 8;
+        self.bits += padding;
+        Ok(padding as usize)
+    }
+}
+
+impl BitWrite for CountingBitWriter {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        CountingBitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        CountingBitWriter::write_bit(self, bit)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.bits)
+    }
+}
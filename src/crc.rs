@@ -0,0 +1,545 @@
+//! Cyclic redundancy checks over exact runs of bits.
+//!
+//! Many formats protect a region which needn't start or end on a byte
+//! boundary, so the CRC here is computed bit by bit, over the bits in the order
+//! they appear in the stream.  For byte-aligned data this matches the usual
+//! byte-oriented definitions when the stream's `BitOrder` matches the CRC's
+//! input reflection: `Msb0` for the unreflected CRCs such as FLAC's CRC-8 and
+//! CRC-16, and `Lsb0` for reflected ones such as CRC-32.
+
+use core::fmt;
+
+use crate::checksum::BitDigest;
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter, Msb0};
+
+/// The parameters of a CRC, following the Rocksoft model used by CRC
+/// catalogues.
+///
+/// There's no input reflection parameter, since bits are taken in stream order;
+/// see the module documentation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CrcParams {
+    /// The width of the CRC in bits, 1 to 64.
+    pub width: u8,
+    /// The generator polynomial, without its leading term.
+    pub poly: u64,
+    /// The initial value of the register.
+    pub init: u64,
+    /// Whether to reverse the register before the final XOR, the catalogue's
+    /// `refout`.
+    pub reflect: bool,
+    /// The value XORed with the register to produce the result.
+    pub xorout: u64,
+}
+
+impl CrcParams {
+    /// CRC-8 as used by FLAC frame headers: polynomial 0x07, no reflection.
+    pub const CRC_8: CrcParams = CrcParams {
+        width: 8,
+        poly: 0x07,
+        init: 0,
+        reflect: false,
+        xorout: 0,
+    };
+
+    /// CRC-16/UMTS, as used by FLAC frame footers: polynomial 0x8005, no
+    /// reflection.
+    pub const CRC_16_UMTS: CrcParams = CrcParams {
+        width: 16,
+        poly: 0x8005,
+        init: 0,
+        reflect: false,
+        xorout: 0,
+    };
+
+    /// CRC-16/IBM-3740, often called CRC-16/CCITT-FALSE: polynomial 0x1021,
+    /// initial value 0xffff.
+    pub const CRC_16_IBM_3740: CrcParams = CrcParams {
+        width: 16,
+        poly: 0x1021,
+        init: 0xffff,
+        reflect: false,
+        xorout: 0,
+    };
+
+    /// CRC-32/ISO-HDLC, as used by zlib, gzip and PNG.  A reflected CRC, so
+    /// use it over `Lsb0` streams.
+    pub const CRC_32: CrcParams = CrcParams {
+        width: 32,
+        poly: 0x04c1_1db7,
+        init: 0xffff_ffff,
+        reflect: true,
+        xorout: 0xffff_ffff,
+    };
+}
+
+/// A running CRC.
+#[derive(Debug, Clone)]
+pub struct Crc {
+    params: CrcParams,
+    register: u64,
+}
+
+impl Crc {
+    /// Start a new CRC with the given parameters.
+    pub fn new(params: CrcParams) -> Self {
+        assert!((1..=64).contains(&params.width));
+        Self {
+            params,
+            register: params.init & Self::mask(params.width),
+        }
+    }
+
+    fn mask(width: u8) -> u64 {
+        u64::MAX >> (64 - width)
+    }
+
+    /// The parameters of this CRC.
+    pub fn params(&self) -> CrcParams {
+        self.params
+    }
+
+    /// Feed the low `nbits` bits of `value` into the CRC, most significant
+    /// first.
+    pub fn update_bits(&mut self, value: u64, nbits: u8) {
+        assert!(nbits <= 64);
+        let width = self.params.width;
+        let mask = Self::mask(width);
+        for i in (0..nbits).rev() {
+            let bit = (value >> i) & 1;
+            let top = (self.register >> (width - 1)) & 1;
+            self.register = (self.register << 1) & mask;
+            if top ^ bit == 1 {
+                self.register ^= self.params.poly;
+            }
+        }
+        self.register &= mask;
+    }
+
+    /// The CRC of the bits fed in so far.
+    pub fn value(&self) -> u64 {
+        let width = self.params.width;
+        let register = if self.params.reflect {
+            self.register.reverse_bits() >> (64 - width)
+        } else {
+            self.register
+        };
+        (register ^ self.params.xorout) & Self::mask(width)
+    }
+
+    /// Start again from the initial value.
+    pub fn reset(&mut self) {
+        self.register = self.params.init & Self::mask(self.params.width);
+    }
+}
+
+impl BitDigest for Crc {
+    type Output = u64;
+
+    #[inline]
+    fn update_bits(&mut self, value: u64, nbits: u8) {
+        Crc::update_bits(self, value, nbits)
+    }
+
+    #[inline]
+    fn finalize(&self) -> u64 {
+        self.value()
+    }
+}
+
+/// Wraps a `BitReader`, computing a CRC of every bit read through it, or with
+/// `with_digest()` any other `BitDigest`.
+///
+/// Create one at the start of the protected region, or call `reset_crc()`
+/// there, then compare `crc()` with the stored value at its end:
+///
+/// ```
+/// use bitrw::{BitRead, BitReader, CrcBitReader, CrcParams};
+///
+/// // A FLAC frame header, ending with its CRC-8.
+/// let header = [0xff, 0xf8, 0xc9, 0x18, 0x00, 0xc2];
+/// let mut reader = CrcBitReader::new(BitReader::new(&header[..]), CrcParams::CRC_8);
+///
+/// assert_eq!(reader.read_bits(14).unwrap(), 0x3ffe); // sync code
+/// reader.read_bits(26).unwrap(); // the remaining fields
+///
+/// let crc = reader.crc();
+/// assert_eq!(reader.read_bits(8).unwrap(), crc);
+/// ```
+pub struct CrcBitReader<R, O = Msb0, D = Crc> {
+    inner: BitReader<R, O>,
+    digest: D,
+}
+
+impl<R, O: BitOrder, D: fmt::Debug> fmt::Debug for CrcBitReader<R, O, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrcBitReader")
+            .field("inner", &self.inner)
+            .field("digest", &self.digest)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> CrcBitReader<R, O> {
+    /// Wrap `inner`, starting a CRC with the given parameters.
+    pub fn new(inner: BitReader<R, O>, params: CrcParams) -> Self {
+        Self::with_digest(inner, Crc::new(params))
+    }
+
+    /// The CRC of the bits read since creation or the last `reset_crc()`.
+    pub fn crc(&self) -> u64 {
+        self.digest.value()
+    }
+
+    /// Restart the CRC from the next bit read.
+    pub fn reset_crc(&mut self) {
+        self.digest.reset();
+    }
+
+    /// Read a stored CRC, as written by `CrcBitWriter::write_crc()`, and check
+    /// it matches the CRC of the bits read so far.  A mismatch is an
+    /// `InvalidData` error carrying the stored value.
+    ///
+    /// The stored CRC isn't included in the running CRC.
+    pub fn check_crc(&mut self) -> io::Result<()> {
+        let start = Some(self.inner.position());
+        let expected = self.crc();
+        let stored = self
+            .inner
+            .read_bits(self.digest.params().width)
+            .map_err(|e| Error::wrap(e, "check_crc", start))?;
+        if stored == expected {
+            Ok(())
+        } else {
+            Err(
+                Error::new(io::ErrorKind::InvalidData, "check_crc", "CRC mismatch")
+                    .at(start)
+                    .with_value(stored)
+                    .into(),
+            )
+        }
+    }
+}
+
+impl<R: io::Read, O: BitOrder, D: BitDigest> CrcBitReader<R, O, D> {
+    /// Wrap `inner`, feeding every bit read into `digest`.
+    pub fn with_digest(inner: BitReader<R, O>, digest: D) -> Self {
+        Self { inner, digest }
+    }
+
+    /// The digest of the bits read so far.
+    pub fn finalize(&self) -> D::Output {
+        self.digest.finalize()
+    }
+
+    /// Get a reference to the digest.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+
+    /// Get a mutable reference to the digest, e.g. to reset it.
+    pub fn digest_mut(&mut self) -> &mut D {
+        &mut self.digest
+    }
+
+    /// Get a reference to the wrapped `BitReader`.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped `BitReader`.  Bits read directly
+    /// from it aren't included in the CRC.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `CrcBitReader`, returning the wrapped `BitReader`.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder, D: BitDigest> BitRead for CrcBitReader<R, O, D> {
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        let value = self.inner.read_bits(nbits)?;
+        if nbits > 0 {
+            self.digest.update_bits(O::to_msb0(value, nbits), nbits);
+        }
+        Ok(value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.inner.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Wraps a `BitWriter`, computing a CRC of every bit written through it, so
+/// it can be appended with `write_crc()` at the end of the protected region.
+/// Any other `BitDigest` can be used with `with_digest()`.
+///
+/// Padding added by `flush()` is never included in the CRC.  If a format
+/// protects the padding, write it explicitly with `pad_to_byte()` first.
+///
+/// ```
+/// use bitrw::{BitWrite, BitWriter, CrcBitWriter, CrcParams};
+///
+/// let mut writer = CrcBitWriter::new(BitWriter::new(Vec::new()), CrcParams::CRC_8);
+/// writer.write_bits(14, 0x3ffe).unwrap();
+/// writer.write_bits(26, 0x0c9_1800).unwrap();
+/// writer.write_crc().unwrap();
+/// writer.flush().unwrap();
+///
+/// assert_eq!(
+///     writer.into_inner().into_inner(),
+///     [0xff, 0xf8, 0xc9, 0x18, 0x00, 0xc2]
+/// );
+/// ```
+pub struct CrcBitWriter<W, O = Msb0, D = Crc> {
+    inner: BitWriter<W, O>,
+    digest: D,
+}
+
+impl<W, O: BitOrder, D: fmt::Debug> fmt::Debug for CrcBitWriter<W, O, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrcBitWriter")
+            .field("inner", &self.inner)
+            .field("digest", &self.digest)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> CrcBitWriter<W, O> {
+    /// Wrap `inner`, starting a CRC with the given parameters.
+    pub fn new(inner: BitWriter<W, O>, params: CrcParams) -> Self {
+        Self::with_digest(inner, Crc::new(params))
+    }
+
+    /// The CRC of the bits written since creation or the last `reset_crc()`.
+    pub fn crc(&self) -> u64 {
+        self.digest.value()
+    }
+
+    /// Restart the CRC from the next bit written.
+    pub fn reset_crc(&mut self) {
+        self.digest.reset();
+    }
+
+    /// Write the CRC of the bits written so far as a `width` bit field,
+    /// returning the number of bits written.  The CRC itself isn't included in
+    /// the running CRC.
+    pub fn write_crc(&mut self) -> io::Result<usize> {
+        self.inner
+            .write_bits(self.digest.params().width, self.crc())
+    }
+}
+
+impl<W: io::Write, O: BitOrder, D: BitDigest> CrcBitWriter<W, O, D> {
+    /// Wrap `inner`, feeding every bit written into `digest`.
+    pub fn with_digest(inner: BitWriter<W, O>, digest: D) -> Self {
+        Self { inner, digest }
+    }
+
+    /// The digest of the bits written so far.
+    pub fn finalize(&self) -> D::Output {
+        self.digest.finalize()
+    }
+
+    /// Get a reference to the digest.
+    pub fn digest(&self) -> &D {
+        &self.digest
+    }
+
+    /// Get a mutable reference to the digest, e.g. to reset it.
+    pub fn digest_mut(&mut self) -> &mut D {
+        &mut self.digest
+    }
+
+    /// Write zero bits up to the next byte boundary, including them in the digest,
+    /// and return how many were written.  Assumes the stream started on a byte
+    /// boundary.
+    pub fn pad_to_byte(&mut self) -> io::Result<usize> {
+        let padding = ((8 - self.inner.position() % 8) % 8) as u8;
+        self.write_bits(padding, 0)
+    }
+
+    /// Flush the wrapped `BitWriter`, as for `BitWriter::flush()`.  The padding
+    /// isn't included in the CRC.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// Get a reference to the wrapped `BitWriter`.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the wrapped `BitWriter`.  Bits written
+    /// directly to it aren't included in the CRC.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `CrcBitWriter`, returning the wrapped `BitWriter`.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder, D: BitDigest> BitWrite for CrcBitWriter<W, O, D> {
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        let written = self.inner.write_bits(nbits, value)?;
+        if nbits > 0 {
+            self.digest.update_bits(O::to_msb0(value, nbits), nbits);
+        }
+        Ok(written)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.inner.position())
+    }
+}
+
+/// A view of a `BitReader` limited to the payload of a frame, computing the
+/// CRC of every bit read through it.  Passed to the closure given to
+/// `BitReader::read_frame_crc()`.
+pub struct CrcFrame<'a, R, O = Msb0> {
+    reader: &'a mut BitReader<R, O>,
+    crc: Crc,
+    remaining: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for CrcFrame<'_, R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CrcFrame")
+            .field("reader", &self.reader)
+            .field("crc", &self.crc)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> CrcFrame<'_, R, O> {
+    /// The number of payload bits not yet read.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// The absolute bit position of the next read, as for
+    /// `BitReader::position()`.
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// The CRC of the payload bits read so far.
+    pub fn crc(&self) -> u64 {
+        self.crc.value()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for CrcFrame<'_, R, O> {
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        if nbits as u64 > self.remaining {
+            return Err(Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read_frame_crc",
+                "read past end of payload",
+            )
+            .at(Some(self.reader.position()))
+            .into());
+        }
+        let value = self.reader.read_bits(nbits)?;
+        self.remaining -= nbits as u64;
+        if nbits > 0 {
+            self.crc.update_bits(O::to_msb0(value, nbits), nbits);
+        }
+        Ok(value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.reader.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.reader.vlc_limit()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read a frame of `payload_bits` followed by a CRC over exactly those
+    /// bits, with parameters `crc`, decoding the payload with `f`.
+    ///
+    /// `f` reads the payload through a `CrcFrame`, rather than from this
+    /// reader directly, so the CRC sees every bit.  Reading past the end of
+    /// the payload fails with `UnexpectedEof`, and leaving any of it unread is
+    /// an `InvalidData` error with the number of bits read as its value.  If
+    /// the stored CRC doesn't match, the error is `InvalidData` at the offset
+    /// of the CRC, with the stored value as its `value()` and the computed one
+    /// as its `expected()`.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader, CrcParams};
+    ///
+    /// // A FLAC frame header, ending with its CRC-8.
+    /// let header = [0xff, 0xf8, 0xc9, 0x18, 0x00, 0xc2];
+    /// let mut reader = BitReader::new(&header[..]);
+    /// let sync = reader
+    ///     .read_frame_crc(40, CrcParams::CRC_8, |frame| {
+    ///         let sync = frame.read_bits(14)?;
+    ///         frame.read_bits(26)?;
+    ///         Ok(sync)
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(sync, 0x3ffe);
+    /// assert_eq!(reader.position(), 48);
+    /// ```
+    pub fn read_frame_crc<T, F>(&mut self, payload_bits: u64, crc: CrcParams, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut CrcFrame<'_, R, O>) -> io::Result<T>,
+    {
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "read_frame_crc", start);
+        let mut frame = CrcFrame {
+            reader: self,
+            crc: Crc::new(crc),
+            remaining: payload_bits,
+        };
+        let value = f(&mut frame).map_err(wrap)?;
+        if frame.remaining > 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "read_frame_crc",
+                "payload not fully read",
+            )
+            .at(start)
+            .with_value(payload_bits - frame.remaining)
+            .into());
+        }
+
+        let expected = frame.crc.value();
+        let crc_start = Some(self.position);
+        let stored = self.read_bits(crc.width).map_err(wrap)?;
+        if stored != expected {
+            return Err(
+                Error::new(io::ErrorKind::InvalidData, "read_frame_crc", "CRC mismatch")
+                    .at(crc_start)
+                    .with_value(stored)
+                    .with_expected(expected)
+                    .into(),
+            );
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,155 @@
+//! Human-readable views of the state of a `BitReader` or `BitWriter`.
+//!
+//! The pending bits are what's useful when tracking down a decoder bug, so
+//! they're rendered as a string: for a reader, `·` for each bit of the current
+//! byte already consumed, then the bits still to be read; for a writer, the
+//! bits written so far, then `·` for each still to be filled.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+/// The pending bits of a reader or writer, in stream order.
+struct Pending<O> {
+    bits: Accumulator<O>,
+    reader: bool,
+}
+
+impl<O: BitOrder> fmt::Display for Pending<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut bits = self.bits;
+        let empty = (8 - bits.len() % 8) % 8;
+        if self.reader {
+            (0..empty).try_for_each(|_| f.write_str("·"))?;
+        }
+        while bits.len() > 0 {
+            f.write_str(if bits.pop(1) == 1 { "1" } else { "0" })?;
+        }
+        if !self.reader {
+            (0..empty).try_for_each(|_| f.write_str("·"))?;
+        }
+        Ok(())
+    }
+}
+
+impl<O: BitOrder> fmt::Debug for Pending<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "\"{}\"", self)
+    }
+}
+
+/// The state of a reader or writer, as rendered by `state_string()`.
+#[cfg(feature = "alloc")]
+struct State<O> {
+    position: u64,
+    limit: Option<u64>,
+    pending: Pending<O>,
+}
+
+#[cfg(feature = "alloc")]
+impl<O: BitOrder> fmt::Display for State<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bit {} (byte {}, phase {}) [{}]",
+            self.position,
+            self.position / 8,
+            self.position % 8,
+            self.pending
+        )?;
+        if let Some(limit) = self.limit {
+            write!(f, " limit {}", limit)?;
+        }
+        Ok(())
+    }
+}
+
+fn order_name<O: BitOrder>() -> &'static str {
+    if O::MSB_FIRST {
+        "Msb0"
+    } else {
+        "Lsb0"
+    }
+}
+
+impl<R, O: BitOrder> BitReader<R, O> {
+    fn pending(&self) -> Pending<O> {
+        Pending {
+            bits: self.bits,
+            reader: true,
+        }
+    }
+
+    /// A one-line summary of the reader's position and pending bits, for log
+    /// messages, such as `bit 13 (byte 1, phase 5) [·····101]`.
+    #[cfg(feature = "alloc")]
+    pub fn state_string(&self) -> String {
+        use alloc::string::ToString;
+
+        State {
+            position: self.position,
+            limit: self.limit,
+            pending: self.pending(),
+        }
+        .to_string()
+    }
+}
+
+/// Shows the position, the bits of the current byte still to be read, and
+/// any limit.  The inner reader is elided, so it needn't be `Debug`.
+impl<R, O: BitOrder> fmt::Debug for BitReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitReader")
+            .field("order", &format_args!("{}", order_name::<O>()))
+            .field("position", &self.position)
+            .field("phase", &(self.position % 8))
+            .field("pending", &self.pending())
+            .field("limit", &self.limit)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W, O: BitOrder> BitWriter<W, O> {
+    fn pending(&self) -> Pending<O> {
+        Pending {
+            bits: self.bits,
+            reader: false,
+        }
+    }
+
+    /// A one-line summary of the writer's position and pending bits, for log
+    /// messages, such as `bit 13 (byte 1, phase 5) [10110···]`.
+    #[cfg(feature = "alloc")]
+    pub fn state_string(&self) -> String {
+        use alloc::string::ToString;
+
+        State {
+            position: self.position,
+            limit: self.limit,
+            pending: self.pending(),
+        }
+        .to_string()
+    }
+}
+
+/// Shows the position, the bits of the current byte written so far, any
+/// limit, the padding, and the number of bytes held in the internal buffer.
+/// The inner writer is elided, so it needn't be `Debug`.
+impl<W, O: BitOrder> fmt::Debug for BitWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("BitWriter");
+        s.field("order", &format_args!("{}", order_name::<O>()))
+            .field("position", &self.position)
+            .field("phase", &(self.position % 8))
+            .field("pending", &self.pending())
+            .field("limit", &self.limit)
+            .field("pad_ones", &self.pad_ones);
+        #[cfg(feature = "alloc")]
+        s.field("buffered", &self.buf.len());
+        s.finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,135 @@
+//! Finding where two bit streams differ.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::io;
+use crate::traits::BitRead;
+
+/// What `diff_bits()` found at a `BitDiff`'s offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BitDiffKind {
+    /// The two streams have different bits.
+    Bits,
+    /// The first stream ended, and the second didn't.
+    FirstEnded,
+    /// The second stream ended, and the first didn't.
+    SecondEnded,
+}
+
+/// A difference between two streams, found by `diff_bits()`.
+///
+/// For context it carries a window of 32 bits from each stream, aligned on a
+/// multiple of 32 bits from where they started, with the first bit in the
+/// most significant place whatever the streams' `BitOrder`, and zero bits
+/// past the end of a stream.
+///
+/// Its `Display` form is a line such as
+/// `bit 1033: 0x4c000000 vs 0x0c000000 from bit 1024`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitDiff {
+    /// The offset of the difference, counting from where the streams started.
+    pub offset: u64,
+    /// What the difference is.
+    pub kind: BitDiffKind,
+    /// The offset of the first bit of the windows.
+    pub window: u64,
+    /// The window from the first stream.
+    pub first: u32,
+    /// The window from the second stream.
+    pub second: u32,
+}
+
+impl fmt::Display for BitDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "bit {}: ", self.offset)?;
+        match self.kind {
+            BitDiffKind::Bits => {}
+            BitDiffKind::FirstEnded => f.write_str("first stream ended, ")?,
+            BitDiffKind::SecondEnded => f.write_str("second stream ended, ")?,
+        }
+        write!(
+            f,
+            "{:#010x} vs {:#010x} from bit {}",
+            self.first, self.second, self.window
+        )
+    }
+}
+
+/// Read up to 32 bits, one at a time, stopping at the end of the stream.
+fn read_window<R: BitRead>(reader: &mut R) -> io::Result<(u32, u8)> {
+    let mut window = 0;
+    for len in 0..32 {
+        match reader.read_bit() {
+            Ok(bit) => window |= (bit as u32) << (31 - len),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok((window, len)),
+            Err(e) => return Err(e),
+        }
+    }
+    Ok((window, 32))
+}
+
+/// Compare two streams bit by bit from where they are, returning up to
+/// `max_report` of the offsets where they differ, in order.  If one stream
+/// ends before the other, that's reported too and comparison stops there.
+///
+/// The streams are read 32 bits at a time as they're compared, not loaded
+/// into memory, and only as far as the last difference reported, rounded up
+/// to 32 bits.  Errors other than `UnexpectedEof` are returned as they occur.
+///
+/// ```
+/// use bitrw::{diff_bits, BitDiffKind, BitReader};
+///
+/// let mut a = BitReader::new(&[0x12, 0x34, 0x56][..]);
+/// let mut b = BitReader::new(&[0x12, 0x35][..]);
+/// let diffs = diff_bits(&mut a, &mut b, 10).unwrap();
+/// assert_eq!(diffs.len(), 2);
+/// assert_eq!(diffs[0].offset, 15);
+/// assert_eq!(diffs[1].offset, 16);
+/// assert_eq!(diffs[1].kind, BitDiffKind::SecondEnded);
+/// assert_eq!(diffs[0].to_string(), "bit 15: 0x12345600 vs 0x12350000 from bit 0");
+/// ```
+pub fn diff_bits<A: BitRead, B: BitRead>(
+    a: &mut A,
+    b: &mut B,
+    max_report: usize,
+) -> io::Result<Vec<BitDiff>> {
+    let mut diffs = Vec::new();
+    let mut window = 0;
+    while diffs.len() < max_report {
+        let (first, first_len) = read_window(a)?;
+        let (second, second_len) = read_window(b)?;
+        let diff = |offset, kind| BitDiff {
+            offset,
+            kind,
+            window,
+            first,
+            second,
+        };
+
+        let common = first_len.min(second_len);
+        let mut differing = first ^ second;
+        if common < 32 {
+            differing &= !(u32::MAX >> common);
+        }
+        while differing != 0 && diffs.len() < max_report {
+            let bit = differing.leading_zeros();
+            diffs.push(diff(window + bit as u64, BitDiffKind::Bits));
+            differing &= !(1 << (31 - bit));
+        }
+
+        if first_len != second_len && diffs.len() < max_report {
+            let kind = if first_len < second_len {
+                BitDiffKind::FirstEnded
+            } else {
+                BitDiffKind::SecondEnded
+            };
+            diffs.push(diff(window + common as u64, kind));
+        }
+        if common < 32 {
+            break;
+        }
+        window += 32;
+    }
+    Ok(diffs)
+}
@@ -0,0 +1,163 @@
+//! Bit-level dumps of a region of a stream, for debugging and golden tests.
+//!
+//! Bits are shown in stream order, as `0` and `1` characters, and offsets are
+//! in bits from the start of the data.  The formats are stable, so a dump can
+//! be compared against one saved earlier.
+
+use alloc::string::String;
+use core::fmt::Write;
+use core::ops::Range;
+
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::BitReader;
+
+/// Bits of a raw dump shown per line.
+const LINE_BITS: u64 = 32;
+
+/// Annotate the fields of an `Msb0` stream from `start_bit`, as for
+/// `annotate_with_order()`.
+///
+/// ```
+/// let dump = bitrw::dump::annotate(&[0xab, 0xcd], 4, &[("kind", 3), ("length", 6)]);
+/// assert_eq!(
+///     dump,
+///     concat!(
+///         "     4  kind    101      = 5 (0x5)\n",
+///         "     7  length  1|11001  = 57 (0x39)\n",
+///     )
+/// );
+/// ```
+pub fn annotate(data: &[u8], start_bit: u64, fields: &[(&str, u8)]) -> String {
+    annotate_with_order(data, start_bit, fields, Msb0)
+}
+
+/// Annotate the fields of a stream from `start_bit`, given as `(label,
+/// width)` pairs, with a line for each showing its offset, label, bits and
+/// value, as `read_bits()` would read it.
+///
+/// A `|` marks each byte boundary within a field.  The columns are padded to
+/// line up.  If the data ends part way through a field, its line shows the
+/// bits there were and `(truncated)` in place of the value, and the fields
+/// after it are left out.
+///
+/// # Panics
+///
+/// If any width is more than 64, or `start_bit` is past the end of the data.
+pub fn annotate_with_order<O: BitOrder>(
+    data: &[u8],
+    start_bit: u64,
+    fields: &[(&str, u8)],
+    _order: O,
+) -> String {
+    assert!(
+        fields.iter().all(|&(_, width)| width <= 64),
+        "fields are at most 64 bits"
+    );
+    assert!(
+        start_bit <= data.len() as u64 * 8,
+        "start bit out of bounds"
+    );
+    let mut reader = BitReader::with_order(data, O::default());
+    reader.skip_bits(start_bit).unwrap();
+
+    let mut lines = alloc::vec::Vec::new();
+    for &(label, width) in fields {
+        let offset = reader.position();
+        let mut bits = String::new();
+        let mut value = 0u64;
+        let mut truncated = false;
+        for i in 0..width {
+            let position = reader.position();
+            let bit = match reader.read_bit() {
+                Ok(bit) => bit,
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    truncated = true;
+                    break;
+                }
+                Err(e) => unreachable!("reading a slice failed: {}", e),
+            };
+            if i > 0 && position % 8 == 0 {
+                bits.push('|');
+            }
+            bits.push(if bit == 1 { '1' } else { '0' });
+            if O::MSB_FIRST {
+                value = value << 1 | bit as u64;
+            } else {
+                value |= (bit as u64) << i;
+            }
+        }
+        let value = if truncated {
+            String::from("(truncated)")
+        } else {
+            alloc::format!("= {} ({:#x})", value, value)
+        };
+        lines.push((offset, label, bits, value));
+        if truncated {
+            break;
+        }
+    }
+
+    let label_width = lines.iter().map(|line| line.1.len()).max().unwrap_or(0);
+    let bits_width = lines.iter().map(|line| line.2.len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for (offset, label, bits, value) in lines {
+        writeln!(
+            out,
+            "{:>6}  {:<label_width$}  {:<bits_width$}  {}",
+            offset, label, bits, value
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Dump the bits of `range` of an `Msb0` stream, as for
+/// `dump_bits_with_order()`.
+///
+/// ```
+/// let dump = bitrw::dump::dump_bits(&[0xab, 0xcd, 0xef], 4..22);
+/// assert_eq!(dump, "     0  ....1011 11001101 111011..\n");
+/// ```
+pub fn dump_bits(data: &[u8], range: Range<u64>) -> String {
+    dump_bits_with_order(data, range, Msb0)
+}
+
+/// Dump the bits of `range` of a stream, offsets in bits, without labels.
+///
+/// Each line shows up to four bytes, from the one holding the start of the
+/// range, with the offset of the first.  Bytes are separated by spaces, and
+/// bits of them outside the range are shown as `.`.
+///
+/// # Panics
+///
+/// If the range goes past the end of the data.
+pub fn dump_bits_with_order<O: BitOrder>(data: &[u8], range: Range<u64>, _order: O) -> String {
+    assert!(
+        range.end <= data.len() as u64 * 8,
+        "dump range out of bounds"
+    );
+    let mut out = String::new();
+    let mut line = range.start / 8 * 8;
+    while line < range.end {
+        write!(out, "{:>6} ", line).unwrap();
+        let end = core::cmp::min(line + LINE_BITS, range.end.div_ceil(8) * 8);
+        for byte in line / 8..end / 8 {
+            out.push(' ');
+            for i in 0..8 {
+                let bit = byte * 8 + i;
+                let shift = if O::MSB_FIRST { 7 - i } else { i };
+                out.push(if !range.contains(&bit) {
+                    '.'
+                } else if (data[byte as usize] >> shift) & 1 == 1 {
+                    '1'
+                } else {
+                    '0'
+                });
+            }
+        }
+        out.push('\n');
+        line += LINE_BITS;
+    }
+    out
+}
@@ -0,0 +1,207 @@
+//! Elias–Fano coding of non-decreasing sequences, such as sorted ID lists.
+//!
+//! Each of the `n` values below `universe` is split into its low `l` bits and
+//! the rest, with `l` the floor of `log2(universe / n)`, or zero if that's
+//! less than one.  The low bits of every value are written first, as `l` bit
+//! fields, then the high parts as unary codes of the gap from the previous
+//! high part: that many zero bits and a one.  That's at most
+//! `n * (l + 2)` bits, within two bits per value of the least possible.
+//!
+//! Neither `n` nor `universe` is written, so both sides must know them.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+#[cfg(feature = "alloc")]
+use crate::error::Error;
+#[cfg(feature = "alloc")]
+use crate::io;
+#[cfg(feature = "alloc")]
+use crate::order::{BitOrder, Msb0};
+#[cfg(feature = "alloc")]
+use crate::BitReader;
+
+/// The number of low bits per value for `n` values below `universe`.
+pub(crate) fn low_bits(n: u64, universe: u64) -> u8 {
+    if n == 0 || universe / n < 2 {
+        0
+    } else {
+        63 - (universe / n).leading_zeros() as u8
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read `n` values below `universe` written by
+    /// `BitWrite::write_elias_fano()`.
+    ///
+    /// A value outside the universe is an `InvalidData` error carrying its
+    /// index as its value.  The values are non-decreasing by construction.
+    ///
+    /// ```
+    /// use bitrw::{BitReader, BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_elias_fano(&[3, 3, 40, 999], 1000).unwrap();
+    /// writer.flush().unwrap();
+    /// let data = writer.into_inner();
+    ///
+    /// let mut reader = BitReader::new(&data[..]);
+    /// assert_eq!(reader.read_elias_fano(4, 1000).unwrap(), [3, 3, 40, 999]);
+    /// ```
+    pub fn read_elias_fano(&mut self, n: usize, universe: u64) -> io::Result<Vec<u64>> {
+        let mut values = Vec::with_capacity(core::cmp::min(n, 1024));
+        for value in self.read_elias_fano_iter(n, universe)? {
+            values.push(value?);
+        }
+        Ok(values)
+    }
+
+    /// Read `n` values below `universe` written by
+    /// `BitWrite::write_elias_fano()`, one at a time from an iterator.
+    ///
+    /// The low bits are read first and held packed, taking `n * l` bits of
+    /// memory, and the high parts are read as the iterator is advanced, so
+    /// the reader is only past the whole sequence once it's finished.  After
+    /// an error the iterator ends.
+    pub fn read_elias_fano_iter(
+        &mut self,
+        n: usize,
+        universe: u64,
+    ) -> io::Result<EliasFanoIter<'_, R, O>> {
+        let start = Some(self.position);
+        let l = low_bits(n as u64, universe);
+        let mut lows = Vec::new();
+        let mut word = 0;
+        let mut filled = 0;
+        for _ in 0..n {
+            let low = self
+                .read_bits(l)
+                .map_err(|e| Error::wrap(e, "read_elias_fano", start))?;
+            word |= low << filled;
+            filled += l as u32;
+            if filled >= 64 {
+                lows.push(word);
+                filled -= 64;
+                word = low >> (l as u32 - filled);
+            }
+        }
+        lows.push(word);
+
+        Ok(EliasFanoIter {
+            reader: self,
+            start,
+            lows,
+            low_bits: l,
+            universe,
+            max_high: universe.saturating_sub(1) >> l,
+            high: 0,
+            index: 0,
+            n,
+            failed: false,
+        })
+    }
+}
+
+/// An iterator over Elias–Fano coded values, from
+/// `BitReader::read_elias_fano_iter()`.
+#[cfg(feature = "alloc")]
+pub struct EliasFanoIter<'a, R, O = Msb0> {
+    reader: &'a mut BitReader<R, O>,
+    start: Option<u64>,
+    // The low bits of each value, packed from the least significant bit.
+    lows: Vec<u64>,
+    low_bits: u8,
+    universe: u64,
+    max_high: u64,
+    high: u64,
+    index: usize,
+    n: usize,
+    failed: bool,
+}
+
+#[cfg(feature = "alloc")]
+impl<R, O: BitOrder> core::fmt::Debug for EliasFanoIter<'_, R, O> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("EliasFanoIter")
+            .field("reader", &self.reader)
+            .field("low_bits", &self.low_bits)
+            .field("index", &self.index)
+            .field("n", &self.n)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: io::Read, O: BitOrder> EliasFanoIter<'_, R, O> {
+    /// The number of low bits of each value, held by the iterator.
+    pub fn low_bits(&self) -> u8 {
+        self.low_bits
+    }
+
+    fn low(&self, index: usize) -> u64 {
+        let l = self.low_bits as usize;
+        if l == 0 {
+            return 0;
+        }
+        let bit = index * l;
+        let (word, shift) = (bit / 64, bit % 64);
+        let mut low = self.lows[word] >> shift;
+        if shift + l > 64 {
+            low |= self.lows[word + 1] << (64 - shift);
+        }
+        low & (u64::MAX >> (64 - l))
+    }
+
+    fn outside_universe(&self) -> io::Error {
+        Error::new(
+            io::ErrorKind::InvalidData,
+            "read_elias_fano",
+            "value outside universe",
+        )
+        .at(self.start)
+        .with_value(self.index as u64)
+        .into()
+    }
+
+    fn read_high(&mut self) -> io::Result<u64> {
+        let start = self.start;
+        let wrap = |e| Error::wrap(e, "read_elias_fano", start);
+        let mut high = self.high;
+        while self.reader.read_bit().map_err(wrap)? == 0 {
+            high += 1;
+            if high > self.max_high {
+                return Err(self.outside_universe());
+            }
+        }
+        Ok(high)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<R: io::Read, O: BitOrder> Iterator for EliasFanoIter<'_, R, O> {
+    type Item = io::Result<u64>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.index == self.n {
+            return None;
+        }
+        let value = self.read_high().and_then(|high| {
+            self.high = high;
+            let value = high << self.low_bits | self.low(self.index);
+            if value < self.universe {
+                Ok(value)
+            } else {
+                Err(self.outside_universe())
+            }
+        });
+        self.index += 1;
+        self.failed = value.is_err();
+        Some(value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let left = if self.failed { 0 } else { self.n - self.index };
+        (0, Some(left))
+    }
+}
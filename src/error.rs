@@ -0,0 +1,210 @@
+//! An error type carrying the context of a failed bit-level operation.
+
+use core::fmt;
+
+use crate::io;
+
+/// A failure which callers may want to tell apart from others of the same
+/// `io::ErrorKind`, as given by `Error::reason()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorReason {
+    /// A variable-length code was longer than the reader's limit, as set by
+    /// `BitReader::set_vlc_limit()`.  The error's value is the limit.
+    VlcLimit,
+}
+
+/// Describes what went wrong during a higher-level read or write: the
+/// operation, the absolute bit offset it started at, and for validation
+/// failures the offending value.
+///
+/// Every method still returns `io::Result`, so existing code keeps compiling:
+/// the helpers build one of these and convert it into an `io::Error`, from
+/// which it can be recovered with `Error::from_io()`, or by downcasting
+/// `io::Error::get_ref()`.  Its `Display` output is preserved either way, so
+/// simply printing the `io::Error` shows the offset.
+///
+/// Without the `std` feature `io::Error` can't carry a payload, so only the
+/// error kind and message survive the conversion.
+#[derive(Debug)]
+pub struct Error {
+    kind: io::ErrorKind,
+    op: &'static str,
+    offset: Option<u64>,
+    value: Option<u64>,
+    expected: Option<u64>,
+    message: Option<&'static str>,
+    reason: Option<ErrorReason>,
+    source: Option<io::Error>,
+}
+
+impl Error {
+    /// Create an error of the given kind from the named operation.
+    pub fn new(kind: io::ErrorKind, op: &'static str, message: &'static str) -> Self {
+        Error {
+            kind,
+            op,
+            offset: None,
+            value: None,
+            expected: None,
+            message: Some(message),
+            reason: None,
+            source: None,
+        }
+    }
+
+    /// Attribute an underlying IO error to the named operation.
+    pub fn from_source(op: &'static str, source: io::Error) -> Self {
+        Error {
+            kind: source.kind(),
+            op,
+            offset: None,
+            value: None,
+            expected: None,
+            message: None,
+            reason: None,
+            source: Some(source),
+        }
+    }
+
+    /// Record the bit offset at which the operation started, if known.
+    pub fn at(mut self, offset: Option<u64>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Record the value which failed validation.
+    pub fn with_value(mut self, value: u64) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Record the value which was expected instead, for a mismatch such as a
+    /// failed checksum.
+    pub fn with_expected(mut self, expected: u64) -> Self {
+        self.expected = Some(expected);
+        self
+    }
+
+    /// Record why the operation failed, for callers to match on.
+    pub fn with_reason(mut self, reason: ErrorReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+
+    /// The kind of error, as for `io::Error`.
+    pub fn kind(&self) -> io::ErrorKind {
+        self.kind
+    }
+
+    /// The name of the operation which failed, e.g. `"read_gamma"`.
+    pub fn op(&self) -> &'static str {
+        self.op
+    }
+
+    /// The absolute bit offset at which the failed operation started, if known.
+    pub fn offset(&self) -> Option<u64> {
+        self.offset
+    }
+
+    /// The value which failed validation, if that's what went wrong.
+    pub fn value(&self) -> Option<u64> {
+        self.value
+    }
+
+    /// The value which was expected instead of `value()`, for a mismatch.
+    pub fn expected(&self) -> Option<u64> {
+        self.expected
+    }
+
+    /// The description of what went wrong, unless the error came from an
+    /// underlying IO error.
+    pub fn message(&self) -> Option<&'static str> {
+        self.message
+    }
+
+    /// Why the operation failed, for failures callers may want to handle
+    /// specially.
+    pub fn reason(&self) -> Option<ErrorReason> {
+        self.reason
+    }
+
+    /// The underlying IO error, if there was one.
+    pub fn io_source(&self) -> Option<&io::Error> {
+        self.source.as_ref()
+    }
+
+    /// Recover the `Error` from an `io::Error` produced by one of this crate's
+    /// helpers.
+    #[cfg(feature = "std")]
+    pub fn from_io(error: &io::Error) -> Option<&Error> {
+        error.get_ref().and_then(|e| e.downcast_ref::<Error>())
+    }
+
+    /// Attribute `error` to `op` starting at `offset`, unless it already
+    /// carries context from a more specific operation.
+    pub(crate) fn wrap(error: io::Error, op: &'static str, offset: Option<u64>) -> io::Error {
+        #[cfg(feature = "std")]
+        {
+            if Self::from_io(&error).is_some() {
+                return error;
+            }
+        }
+        Self::from_source(op, error).at(offset).into()
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} failed", self.op)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at bit {}", offset)?;
+        }
+        if let Some(message) = self.message {
+            write!(f, ": {}", message)?;
+        }
+        if let Some(value) = self.value {
+            write!(f, " (value {:#x})", value)?;
+        }
+        if let Some(expected) = self.expected {
+            write!(f, " (expected {:#x})", expected)?;
+        }
+        if let Some(ref source) = self.source {
+            write!(f, ": {}", source)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_ref()
+            .map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::from_source("io", error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(error.kind, error)
+    }
+}
+
+#[cfg(not(feature = "std"))]
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match (error.message, error.source) {
+            (Some(message), _) => io::Error::new(error.kind, message),
+            (None, Some(source)) => source,
+            (None, None) => error.kind.into(),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Conversion between `f64` and binary fixed-point fields.
+//!
+//! A Qm.n field is an integer of `m + n` bits scaled by 2^-n, so it has `m`
+//! integer bits, which include the sign bit if it's signed, and `n` fractional
+//! bits.  Signed fields are two's complement.
+
+use crate::float::ldexp;
+
+/// What to do when writing a value outside the range of a fixed-point field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Write the nearest representable value, the field's minimum or maximum.
+    Saturate,
+    /// Fail with `InvalidInput`, writing nothing.
+    Reject,
+}
+
+/// The total width of a field, checking it's valid.
+pub(crate) fn width(int_bits: u8, frac_bits: u8, signed: bool) -> u8 {
+    let nbits = int_bits as u16 + frac_bits as u16;
+    assert!((1..=64).contains(&nbits));
+    assert!(!signed || int_bits > 0);
+    nbits as u8
+}
+
+/// Round to the nearest integer, ties to even, without requiring std.
+pub(crate) fn round_ties_even(x: f64) -> f64 {
+    // Anything this large is already an integer.
+    if x.abs() >= (1u64 << 52) as f64 {
+        return x;
+    }
+    let truncated = x as i64;
+    let rest = (x - truncated as f64).abs();
+    let away = truncated + if x < 0.0 { -1 } else { 1 };
+    if rest > 0.5 || (rest == 0.5 && truncated % 2 != 0) {
+        away as f64
+    } else {
+        truncated as f64
+    }
+}
+
+/// Decode the low `int_bits + frac_bits` bits of `bits`.  The result is exact
+/// for widths up to 53 bits, and rounded to nearest, ties to even, beyond that.
+pub(crate) fn decode(bits: u64, int_bits: u8, frac_bits: u8, signed: bool) -> f64 {
+    let nbits = width(int_bits, frac_bits, signed);
+    let shift = 64 - nbits;
+    let raw = if signed {
+        ((bits << shift) as i64 >> shift) as f64
+    } else {
+        ((bits << shift) >> shift) as f64
+    };
+    ldexp(raw, -(frac_bits as i64))
+}
+
+/// Encode `value`, rounding to nearest, ties to even, or return a message
+/// explaining why it can't be.
+pub(crate) fn encode(
+    value: f64,
+    int_bits: u8,
+    frac_bits: u8,
+    signed: bool,
+    overflow: Overflow,
+) -> Result<u64, &'static str> {
+    let nbits = width(int_bits, frac_bits, signed);
+    if value.is_nan() {
+        return Err("NaN not representable");
+    }
+
+    // Bounds as powers of two, which are exact even at 64 bits.
+    let (min, limit) = if signed {
+        (-ldexp(1.0, nbits as i64 - 1), ldexp(1.0, nbits as i64 - 1))
+    } else {
+        (0.0, ldexp(1.0, nbits as i64))
+    };
+    let mask = u64::MAX >> (64 - nbits);
+    let scaled = round_ties_even(ldexp(value, frac_bits as i64));
+    let scaled = if scaled < min {
+        if overflow == Overflow::Reject {
+            return Err("value out of range");
+        }
+        min
+    } else if scaled >= limit {
+        if overflow == Overflow::Reject {
+            return Err("value out of range");
+        }
+        return Ok(mask >> signed as u8);
+    } else {
+        scaled
+    };
+
+    let bits = if signed {
+        scaled as i64 as u64
+    } else {
+        scaled as u64
+    };
+    Ok(bits & mask)
+}
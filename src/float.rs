@@ -0,0 +1,171 @@
+//! Conversion between `f64` and custom IEEE-754 style float layouts.
+//!
+//! A layout is an optional sign bit, `exp_bits` of biased exponent, and
+//! `mant_bits` of mantissa with an implicit leading one, with the usual
+//! IEEE-754 rules: an all-zero exponent holds zero and the subnormals, and an
+//! all-ones exponent holds the infinities (zero mantissa) and NaNs.
+
+/// `x * 2^k`, without requiring `powi` from std.
+pub(crate) fn ldexp(mut x: f64, mut k: i64) -> f64 {
+    let pow2 = |k: i64| f64::from_bits(((k + 1023) as u64) << 52);
+    while k > 1023 {
+        x *= pow2(1023);
+        k -= 1023;
+        if x.is_infinite() {
+            return x;
+        }
+    }
+    while k < -1022 {
+        x *= pow2(-1022);
+        k += 1022;
+        if x == 0.0 {
+            return x;
+        }
+    }
+    x * pow2(k)
+}
+
+fn check_layout(sign: bool, exp_bits: u8, mant_bits: u8) {
+    assert!((1..=11).contains(&exp_bits));
+    assert!(mant_bits <= 52);
+    assert!(sign as u8 + exp_bits + mant_bits <= 64);
+}
+
+/// Decode the low bits of `bits` in the given layout.
+pub(crate) fn decode(bits: u64, sign: bool, exp_bits: u8, mant_bits: u8, bias: i32) -> f64 {
+    check_layout(sign, exp_bits, mant_bits);
+
+    let emax = (1u64 << exp_bits) - 1;
+    let mant = bits & ((1u64 << mant_bits) - 1);
+    let exp = (bits >> mant_bits) & emax;
+    let negative = sign && (bits >> (mant_bits + exp_bits)) & 1 == 1;
+
+    let magnitude = if exp == emax {
+        if mant == 0 {
+            f64::INFINITY
+        } else {
+            // Keep the payload, including the quiet bit, at the top of the
+            // mantissa.
+            f64::from_bits((0x7ff << 52) | (mant << (52 - mant_bits)))
+        }
+    } else if exp == 0 {
+        ldexp(mant as f64, 1 - bias as i64 - mant_bits as i64)
+    } else {
+        ldexp(
+            (mant | (1 << mant_bits)) as f64,
+            exp as i64 - bias as i64 - mant_bits as i64,
+        )
+    };
+
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// Reasons a value can't be encoded in a layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum EncodeError {
+    /// A negative value in a layout without a sign bit.
+    Negative,
+    /// A NaN in a layout with no mantissa bits to mark it.
+    Nan,
+}
+
+/// Encode `value` in the given layout, rounding to nearest, ties to even.
+/// Values too large for the layout become infinity.
+pub(crate) fn encode(
+    value: f64,
+    sign: bool,
+    exp_bits: u8,
+    mant_bits: u8,
+    bias: i32,
+) -> Result<u64, EncodeError> {
+    check_layout(sign, exp_bits, mant_bits);
+
+    let emax = (1u64 << exp_bits) - 1;
+    let raw = value.to_bits();
+    let negative = raw >> 63 == 1;
+    let sign_bit = if sign && negative {
+        1 << (exp_bits + mant_bits)
+    } else {
+        0
+    };
+
+    if value.is_nan() {
+        if mant_bits == 0 {
+            return Err(EncodeError::Nan);
+        }
+        let mut mant = (raw & ((1 << 52) - 1)) >> (52 - mant_bits);
+        if mant == 0 {
+            mant = 1 << (mant_bits - 1);
+        }
+        return Ok(sign_bit | (emax << mant_bits) | mant);
+    }
+    if negative && !sign && value != 0.0 {
+        return Err(EncodeError::Negative);
+    }
+    if value.is_infinite() {
+        return Ok(sign_bit | (emax << mant_bits));
+    }
+    if value == 0.0 {
+        return Ok(sign_bit);
+    }
+
+    // value = m * 2^e exactly, with m an integer of at most 53 bits.
+    let fexp = ((raw >> 52) & 0x7ff) as i64;
+    let frac = raw & ((1 << 52) - 1);
+    let (m, e) = if fexp == 0 {
+        (frac, -1074)
+    } else {
+        (frac | (1 << 52), fexp - 1075)
+    };
+
+    // The exponent of the lowest mantissa bit in the target layout.
+    let lead = e + 63 - m.leading_zeros() as i64;
+    let bias = bias as i64;
+    let mant_bits64 = mant_bits as i64;
+    let mut q = if lead + bias >= 1 {
+        lead - mant_bits64
+    } else {
+        1 - bias - mant_bits64
+    };
+
+    let shift = q - e;
+    let mut n = if shift <= 0 {
+        m << -shift
+    } else if shift > 64 {
+        0
+    } else {
+        let m = m as u128;
+        let n = m >> shift;
+        let rem = m & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        if rem > half || (rem == half && n & 1 == 1) {
+            (n + 1) as u64
+        } else {
+            n as u64
+        }
+    };
+
+    if n == 0 {
+        return Ok(sign_bit);
+    }
+    if n >> (mant_bits + 1) != 0 {
+        n >>= 1;
+        q += 1;
+    }
+
+    let (exp, mant) = if n >> mant_bits != 0 {
+        (q + mant_bits64 + bias, n & ((1 << mant_bits) - 1))
+    } else {
+        (0, n)
+    };
+
+    if exp >= emax as i64 {
+        Ok(sign_bit | (emax << mant_bits))
+    } else {
+        Ok(sign_bit | ((exp as u64) << mant_bits) | mant)
+    }
+}
@@ -0,0 +1,85 @@
+//! Second readers at the same bit offset, for parsing ahead speculatively
+//! without disturbing the original.
+
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::BitOrder;
+use crate::BitReader;
+
+impl<R: io::Read + Clone, O: BitOrder> BitReader<R, O> {
+    /// Create an independent reader at the same bit offset, with the same
+    /// limits, by cloning the inner reader.  Reads on either reader don't
+    /// affect the other.
+    ///
+    /// This suits in-memory sources such as `&[u8]` and `Cursor`, whose
+    /// clones each keep their own position.  A reader whose clones share one,
+    /// such as a `&File`, needs `fork_with()` instead.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// let mut ahead = reader.fork();
+    /// assert_eq!(ahead.read_bits(8).unwrap(), 0xbc);
+    /// assert_eq!(reader.position(), 4);
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0xbcd);
+    /// ```
+    pub fn fork(&self) -> Self {
+        BitReader {
+            inner: self.inner.clone(),
+            bits: self.bits,
+            position: self.position,
+            limit: self.limit,
+            vlc_limit: self.vlc_limit,
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek, O: BitOrder> BitReader<R, O> {
+    /// Create an independent reader at the same bit offset, with the same
+    /// limits, over a second stream opened by `open`, which is seeked to the
+    /// same byte offset as this reader's inner stream.  Bits already loaded
+    /// by this reader are copied across, so the fork reads exactly what this
+    /// reader would read next.
+    ///
+    /// `open` should give a stream with the same contents which doesn't share
+    /// a position with this one, such as the same file opened again by path.
+    /// `File::try_clone()` shares the offset of the original, so reads on the
+    /// fork would move this reader's stream too.
+    ///
+    /// ```
+    /// use std::io::Cursor;
+    /// use bitrw::BitReader;
+    ///
+    /// let data = vec![0x12, 0x34, 0x56];
+    /// let mut reader = BitReader::new(Cursor::new(&data));
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0x123);
+    /// let mut ahead = reader.fork_with(|_| Ok(Cursor::new(&data))).unwrap();
+    /// assert_eq!(ahead.read_bits(12).unwrap(), 0x456);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0x4);
+    /// ```
+    pub fn fork_with<R2, F>(&mut self, open: F) -> io::Result<BitReader<R2, O>>
+    where
+        R2: io::Read + io::Seek,
+        F: FnOnce(&R) -> io::Result<R2>,
+    {
+        let start = Some(self.position);
+        let offset = self
+            .inner
+            .stream_position()
+            .map_err(|e| Error::wrap(e, "fork_with", start))?;
+        let mut inner = open(&self.inner).map_err(|e| Error::wrap(e, "fork_with", start))?;
+        inner
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| Error::wrap(e, "fork_with", start))?;
+
+        Ok(BitReader {
+            inner,
+            bits: self.bits,
+            position: self.position,
+            limit: self.limit,
+            vlc_limit: self.vlc_limit,
+        })
+    }
+}
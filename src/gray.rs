@@ -0,0 +1,29 @@
+//! Reflected binary Gray code, in which consecutive values differ in exactly
+//! one bit.
+
+/// Convert `value` from binary to Gray code.
+///
+/// ```
+/// assert_eq!(bitrw::gray_encode(2), 0b11);
+/// assert_eq!(bitrw::gray_encode(3), 0b10);
+/// ```
+#[inline]
+pub fn gray_encode(value: u64) -> u64 {
+    value ^ (value >> 1)
+}
+
+/// Convert `value` from Gray code to binary, undoing `gray_encode()`.
+///
+/// ```
+/// assert_eq!(bitrw::gray_decode(0b10), 3);
+/// ```
+#[inline]
+pub fn gray_decode(mut value: u64) -> u64 {
+    // Each bit is the XOR of itself and every bit above it.
+    let mut shift = 1;
+    while shift < 64 {
+        value ^= value >> shift;
+        shift <<= 1;
+    }
+    value
+}
@@ -0,0 +1,109 @@
+//! GSM 7 bit default alphabet packing, as in SMS user data, from 3GPP TS
+//! 23.038.
+//!
+//! Septets are packed least significant bit first, each carrying over into the
+//! next octet, so these are only on `Lsb0` readers and writers.  When a user
+//! data header comes first, fill bits pad it out to a septet boundary; they
+//! come before the first septet, in the low bits of the octet after the
+//! header.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::io;
+use crate::order::Lsb0;
+use crate::{BitReader, BitWriter};
+
+fn check_fill(fill_bits: u8) {
+    assert!(
+        fill_bits <= 6,
+        "at most 6 fill bits come before the septets"
+    );
+}
+
+impl<R: io::Read> BitReader<R, Lsb0> {
+    /// Skip `fill_bits` fill bits, then read `septet_count` packed septets,
+    /// returning each in the low 7 bits of a byte.  The fill bits aren't
+    /// checked.
+    ///
+    /// ```
+    /// use bitrw::{BitReader, Lsb0};
+    ///
+    /// // The example of TS 23.038 6.1.2.1.1.
+    /// let pdu = [0xe8, 0x32, 0x9b, 0xfd, 0x46, 0x97, 0xd9, 0xec, 0x37];
+    /// let mut reader = BitReader::with_order(&pdu[..], Lsb0);
+    /// assert_eq!(reader.read_gsm7(10, 0).unwrap(), b"hellohello");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `fill_bits` is more than 6.
+    #[cfg(feature = "alloc")]
+    pub fn read_gsm7(&mut self, septet_count: usize, fill_bits: u8) -> io::Result<Vec<u8>> {
+        check_fill(fill_bits);
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "read_gsm7", start);
+        self.skip_bits(fill_bits as u64).map_err(wrap)?;
+        let mut septets = Vec::with_capacity(core::cmp::min(septet_count, 1024));
+        for _ in 0..septet_count {
+            septets.push(self.read_bits(7).map_err(wrap)? as u8);
+        }
+        Ok(septets)
+    }
+}
+
+impl<W: io::Write> BitWriter<W, Lsb0> {
+    /// Write `fill_bits` zero fill bits, then `septets` packed, returning the
+    /// number of bits written.  Flushing pads the last octet with zeros.
+    ///
+    /// A byte above 0x7f is an `InvalidInput` error carrying its index as its
+    /// value, and septets which would pass any limit are a `WriteZero` error.
+    /// Either way nothing is written.
+    ///
+    /// ```
+    /// use bitrw::{BitWriter, Lsb0};
+    ///
+    /// let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    /// writer.write_gsm7(b"hellohello", 0).unwrap();
+    /// writer.flush().unwrap();
+    /// assert_eq!(
+    ///     writer.into_inner(),
+    ///     [0xe8, 0x32, 0x9b, 0xfd, 0x46, 0x97, 0xd9, 0xec, 0x37]
+    /// );
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `fill_bits` is more than 6.
+    pub fn write_gsm7(&mut self, septets: &[u8], fill_bits: u8) -> io::Result<usize> {
+        check_fill(fill_bits);
+        let start = Some(self.position);
+        if let Some(index) = septets.iter().position(|&septet| septet > 0x7f) {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_gsm7", "not a septet")
+                    .at(start)
+                    .with_value(index as u64)
+                    .into(),
+            );
+        }
+        let total = fill_bits as u64 + septets.len() as u64 * 7;
+        if self
+            .limit
+            .is_some_and(|limit| self.position.saturating_add(total) > limit)
+        {
+            return Err(
+                Error::new(io::ErrorKind::WriteZero, "write_gsm7", "bit limit reached")
+                    .at(start)
+                    .into(),
+            );
+        }
+
+        let wrap = |e| Error::wrap(e, "write_gsm7", start);
+        self.write_bits(fill_bits, 0).map_err(wrap)?;
+        for &septet in septets {
+            self.write_bits(7, septet as u64).map_err(wrap)?;
+        }
+        Ok(total as usize)
+    }
+}
@@ -0,0 +1,358 @@
+//! Hamming codes, correcting single bit errors in every four data bits.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// The code used by a `HammingReader` and `HammingWriter`.
+///
+/// Each four data bits `d1 d2 d3 d4`, in stream order, are sent as the code
+/// word `p1 p2 d1 p3 d2 d3 d4`, where `p1` covers `d1 d2 d4`, `p2` covers
+/// `d1 d3 d4` and `p3` covers `d2 d3 d4`, each with even parity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HammingCode {
+    /// Seven bit code words, correcting any single bit error.  Two errors in
+    /// a code word are miscorrected.
+    Hamming74,
+    /// Eight bit code words, with a final bit giving the whole code word even
+    /// parity, correcting any single bit error and detecting any two.
+    Secded,
+}
+
+impl HammingCode {
+    /// The length of a code word.
+    pub fn code_bits(self) -> u8 {
+        match self {
+            HammingCode::Hamming74 => 7,
+            HammingCode::Secded => 8,
+        }
+    }
+
+    /// The code word for the data bits `d`, each zero or one, in stream order.
+    fn encode(self, d: [u8; 4]) -> [u8; 8] {
+        let p1 = d[0] ^ d[1] ^ d[3];
+        let p2 = d[0] ^ d[2] ^ d[3];
+        let p3 = d[1] ^ d[2] ^ d[3];
+        let mut word = [p1, p2, d[0], p3, d[1], d[2], d[3], 0];
+        if self == HammingCode::Secded {
+            word[7] = word[..7].iter().fold(0, |acc, bit| acc ^ bit);
+        }
+        word
+    }
+
+    /// Correct a code word in place, returning whether it had an error, or
+    /// `None` if it had an error which couldn't be corrected.
+    fn correct(self, word: &mut [u8; 8]) -> Option<bool> {
+        let check = |positions: [usize; 4]| positions.iter().fold(0, |acc, &i| acc ^ word[i - 1]);
+        let syndrome =
+            (check([1, 3, 5, 7]) | check([2, 3, 6, 7]) << 1 | check([4, 5, 6, 7]) << 2) as usize;
+        let overall = word.iter().fold(0, |acc, bit| acc ^ bit);
+        match (self, syndrome, overall) {
+            (_, 0, 0) | (HammingCode::Hamming74, 0, _) => Some(false),
+            // Only the overall parity bit is wrong.
+            (HammingCode::Secded, 0, _) => {
+                word[7] ^= 1;
+                Some(true)
+            }
+            (HammingCode::Secded, _, 0) => None,
+            (_, position, _) => {
+                word[position - 1] ^= 1;
+                Some(true)
+            }
+        }
+    }
+}
+
+/// Reads Hamming code words, correcting any single bit error in each and
+/// returning the data bits.
+///
+/// Corrections are made silently and counted by `corrections()`.  With
+/// `HammingCode::Secded`, a code word with two bit errors fails the read with
+/// an `InvalidData` error at the offset of the code word, with its index,
+/// counting from zero, as its value.  Its data bits are kept uncorrected, to
+/// be returned by the next read, so the caller can choose whether to carry on.
+///
+/// If the inner reader runs out part way through a code word, the read fails
+/// with its `UnexpectedEof` error, and the code word is left unread.
+///
+/// ```
+/// use bitrw::{BitReader, HammingCode, HammingReader};
+///
+/// // 0b1011 as a Hamming(7, 4) code word, with its fifth bit flipped.
+/// let data = [0b0110_1110];
+/// let mut reader = HammingReader::new(BitReader::new(&data[..]), HammingCode::Hamming74);
+/// assert_eq!(reader.read_bits(4).unwrap(), 0b1011);
+/// assert_eq!(reader.corrections(), 1);
+/// ```
+pub struct HammingReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    code: HammingCode,
+    // Decoded data bits, not yet read.
+    held: Accumulator<O>,
+    position: u64,
+    words: u64,
+    corrections: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for HammingReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HammingReader")
+            .field("inner", &self.inner)
+            .field("code", &self.code)
+            .field("position", &self.position)
+            .field("corrections", &self.corrections)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> HammingReader<R, O> {
+    /// Wrap `inner`, decoding code words of `code`.
+    pub fn new(inner: BitReader<R, O>, code: HammingCode) -> Self {
+        Self {
+            inner,
+            code,
+            held: Accumulator::new(),
+            position: 0,
+            words: 0,
+            corrections: 0,
+        }
+    }
+
+    /// Read up to 64 data bits, decoding as many code words as that takes.
+    ///
+    /// If the read fails, any data bits it decoded are kept to be returned by
+    /// the next read.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        while self.held.len() < nbits {
+            self.decode_word()?;
+        }
+        self.position += nbits as u64;
+        Ok(self.held.pop(nbits))
+    }
+
+    fn decode_word(&mut self) -> io::Result<()> {
+        let start = Some(self.inner.position());
+        let code_bits = self.code.code_bits();
+        let mut bits = Accumulator::<O>::new();
+        bits.push(code_bits, self.inner.read_bits(code_bits)?);
+        let mut word = [0; 8];
+        for bit in &mut word[..code_bits as usize] {
+            *bit = bits.pop(1) as u8;
+        }
+
+        let index = self.words;
+        self.words += 1;
+        let corrected = self.code.correct(&mut word);
+        for &i in &[2, 4, 5, 6] {
+            self.held.push(1, word[i] as u64);
+        }
+        match corrected {
+            Some(true) => self.corrections += 1,
+            Some(false) => {}
+            None => {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "hamming",
+                    "uncorrectable error",
+                )
+                .at(start)
+                .with_value(index)
+                .into())
+            }
+        }
+        Ok(())
+    }
+
+    /// The number of data bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of code words decoded so far.
+    pub fn code_words(&self) -> u64 {
+        self.words
+    }
+
+    /// The number of code words which had an error corrected.
+    pub fn corrections(&self) -> u64 {
+        self.corrections
+    }
+
+    /// Get a reference to the inner reader, whose position counts code bits.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Reading from it directly
+    /// will lose the code word alignment.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `HammingReader`, returning the inner reader.  Any data bits
+    /// decoded but not yet read are lost.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for HammingReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        HammingReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Writes data bits as Hamming code words, one for every four.
+///
+/// Data bits are held until there are four to make a code word, so a stream
+/// whose length isn't a multiple of four should end with `end_block()`.
+///
+/// ```
+/// use bitrw::{BitWriter, HammingCode, HammingWriter};
+///
+/// let mut writer = HammingWriter::new(BitWriter::new(Vec::new()), HammingCode::Secded);
+/// writer.write_bits(4, 0b1011).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0b0110_0110]);
+/// ```
+pub struct HammingWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    code: HammingCode,
+    // Fewer than four data bits, waiting for the rest of their code word.
+    pending: Accumulator<O>,
+    position: u64,
+    words: u64,
+}
+
+impl<W, O: BitOrder> fmt::Debug for HammingWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HammingWriter")
+            .field("inner", &self.inner)
+            .field("code", &self.code)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> HammingWriter<W, O> {
+    /// Wrap `inner`, encoding code words of `code`.
+    pub fn new(inner: BitWriter<W, O>, code: HammingCode) -> Self {
+        Self {
+            inner,
+            code,
+            pending: Accumulator::new(),
+            position: 0,
+            words: 0,
+        }
+    }
+
+    /// Write up to 64 data bits, with the code word of every four.
+    ///
+    /// If the inner writer fails part way, the code words before the failure
+    /// have been written, and the data bits of the rest are lost.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<O>::new();
+        bits.push(nbits, value);
+        while bits.len() > 0 {
+            let chunk = core::cmp::min(bits.len(), 4 - self.pending.len());
+            self.pending.push(chunk, bits.pop(chunk));
+            self.position += chunk as u64;
+            if self.pending.len() == 4 {
+                self.write_word()?;
+            }
+        }
+        Ok(nbits as usize)
+    }
+
+    fn write_word(&mut self) -> io::Result<()> {
+        let mut data = [0; 4];
+        for bit in &mut data {
+            *bit = self.pending.pop(1) as u8;
+        }
+        let word = self.code.encode(data);
+        let code_bits = self.code.code_bits();
+        let mut bits = Accumulator::<O>::new();
+        for &bit in &word[..code_bits as usize] {
+            bits.push(1, bit as u64);
+        }
+        self.words += 1;
+        self.inner.write_bits(code_bits, bits.pop(code_bits))?;
+        Ok(())
+    }
+
+    /// Pad any data bits waiting for the rest of a code word with zero bits
+    /// and write it, returning whether there were any.
+    pub fn end_block(&mut self) -> io::Result<bool> {
+        let len = self.pending.len();
+        if len == 0 {
+            return Ok(false);
+        }
+        self.pending.push(4 - len, 0);
+        self.write_word()?;
+        Ok(true)
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.  The padding isn't encoded, and data bits waiting
+    /// for the rest of a code word aren't written: see `end_block()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// The number of data bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of code words written so far.
+    pub fn code_words(&self) -> u64 {
+        self.words
+    }
+
+    /// Get a reference to the inner writer, whose position counts code bits.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Writing to it directly
+    /// will lose the code word alignment.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `HammingWriter`, returning the inner writer.  Data bits
+    /// waiting for the rest of a code word are lost.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for HammingWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        HammingWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
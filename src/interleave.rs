@@ -0,0 +1,378 @@
+//! Block interleaving, spreading a burst of errors in the stream across many
+//! code words.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// One block of bits, as a matrix filled row by row.
+#[derive(Clone)]
+struct Matrix {
+    rows: usize,
+    cols: usize,
+    words: Vec<u64>,
+}
+
+impl Matrix {
+    fn new(rows: usize, cols: usize) -> Self {
+        assert!(
+            rows > 0 && cols > 0,
+            "interleaver needs at least one row and column"
+        );
+        let size = rows.checked_mul(cols).expect("interleaver block too large");
+        Self {
+            rows,
+            cols,
+            words: vec![0; size.div_ceil(64)],
+        }
+    }
+
+    fn size(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    /// The row-wise index of the `index`th bit in column order.
+    fn transpose(&self, index: usize) -> usize {
+        (index % self.rows) * self.cols + index / self.rows
+    }
+
+    fn get(&self, index: usize) -> u8 {
+        (self.words[index / 64] >> (index % 64)) as u8 & 1
+    }
+
+    fn set(&mut self, index: usize, bit: u8) {
+        let word = &mut self.words[index / 64];
+        *word = *word & !(1 << (index % 64)) | (bit as u64) << (index % 64);
+    }
+}
+
+impl fmt::Debug for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Matrix")
+            .field("rows", &self.rows)
+            .field("cols", &self.cols)
+            .finish()
+    }
+}
+
+/// Writes data bits through a block interleaver: each block of `rows` by
+/// `cols` bits is filled row by row, then written out column by column.
+///
+/// Bits are held until they complete a block, so the final block of a stream
+/// should be ended with `end_block()`, which pads it with zero bits.
+///
+/// ```
+/// use bitrw::{BitWriter, InterleaveBitWriter};
+///
+/// let mut writer = InterleaveBitWriter::new(BitWriter::new(Vec::new()), 2, 4);
+/// writer.write_bits(4, 0b1111).unwrap();
+/// writer.write_bits(4, 0b0000).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0b1010_1010]);
+/// ```
+pub struct InterleaveBitWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    matrix: Matrix,
+    len: usize,
+    position: u64,
+    blocks: u64,
+}
+
+impl<W, O: BitOrder> fmt::Debug for InterleaveBitWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InterleaveBitWriter")
+            .field("inner", &self.inner)
+            .field("matrix", &self.matrix)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> InterleaveBitWriter<W, O> {
+    /// Wrap `inner`, interleaving blocks of `rows` by `cols` bits.
+    ///
+    /// # Panics
+    ///
+    /// If `rows` or `cols` is zero.
+    pub fn new(inner: BitWriter<W, O>, rows: usize, cols: usize) -> Self {
+        Self {
+            inner,
+            matrix: Matrix::new(rows, cols),
+            len: 0,
+            position: 0,
+            blocks: 0,
+        }
+    }
+
+    /// Write up to 64 data bits, writing out any blocks they complete.
+    ///
+    /// If the inner writer fails while writing out a block, the rest of that
+    /// block is lost.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<O>::new();
+        bits.push(nbits, value);
+        while bits.len() > 0 {
+            self.matrix.set(self.len, bits.pop(1) as u8);
+            self.len += 1;
+            self.position += 1;
+            if self.len == self.matrix.size() {
+                self.write_block()?;
+            }
+        }
+        Ok(nbits as usize)
+    }
+
+    fn write_block(&mut self) -> io::Result<()> {
+        self.len = 0;
+        self.blocks += 1;
+        let mut out = Accumulator::<O>::new();
+        for index in 0..self.matrix.size() {
+            out.push(1, self.matrix.get(self.matrix.transpose(index)) as u64);
+            if out.len() == 64 {
+                self.inner.write_bits(64, out.pop(64))?;
+            }
+        }
+        let len = out.len();
+        self.inner.write_bits(len, out.pop(len))?;
+        Ok(())
+    }
+
+    /// Pad a partial block with zero bits and write it out, returning whether
+    /// there was one.  The padding isn't counted by `position()`.
+    pub fn end_block(&mut self) -> io::Result<bool> {
+        if self.len == 0 {
+            return Ok(false);
+        }
+        for index in self.len..self.matrix.size() {
+            self.matrix.set(index, 0);
+        }
+        self.write_block()?;
+        Ok(true)
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.  A partial block isn't written: see
+    /// `end_block()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// The number of data bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of blocks written out.
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Writing to it directly
+    /// will lose the block alignment.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `InterleaveBitWriter`, returning the inner writer.  Bits of
+    /// a partial block are lost.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for InterleaveBitWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        InterleaveBitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
+
+/// Reads data bits through a block deinterleaver, undoing an
+/// `InterleaveBitWriter` with the same `rows` and `cols`.
+///
+/// Each block is loaded from the inner reader as a whole before any of it is
+/// returned.  If the inner reader runs out part way through a block, the read
+/// fails with an `UnexpectedEof` error with the number of bits of the block
+/// loaded as its value, and the rest of it can't be read.  Other errors keep
+/// what was loaded, so the read can be retried.
+///
+/// ```
+/// use bitrw::{BitReader, DeinterleaveBitReader};
+///
+/// let data = [0b1010_1010];
+/// let mut reader = DeinterleaveBitReader::new(BitReader::new(&data[..]), 2, 4);
+/// assert_eq!(reader.read_bits(8).unwrap(), 0b1111_0000);
+/// ```
+pub struct DeinterleaveBitReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    matrix: Matrix,
+    // Bits of the block loaded, in column order.
+    loaded: usize,
+    // Bits of the block read, in row order.
+    next: usize,
+    // Data bits for a read which failed, to be returned first next time.
+    held: Accumulator<O>,
+    position: u64,
+    blocks: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for DeinterleaveBitReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeinterleaveBitReader")
+            .field("inner", &self.inner)
+            .field("matrix", &self.matrix)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> DeinterleaveBitReader<R, O> {
+    /// Wrap `inner`, deinterleaving blocks of `rows` by `cols` bits.
+    ///
+    /// # Panics
+    ///
+    /// If `rows` or `cols` is zero.
+    pub fn new(inner: BitReader<R, O>, rows: usize, cols: usize) -> Self {
+        Self {
+            inner,
+            matrix: Matrix::new(rows, cols),
+            loaded: 0,
+            next: 0,
+            held: Accumulator::new(),
+            position: 0,
+            blocks: 0,
+        }
+    }
+
+    /// Read up to 64 data bits, loading blocks as needed.
+    ///
+    /// If the read fails, any data bits it read are kept to be returned by the
+    /// next read.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        let size = self.matrix.size();
+        while self.held.len() < nbits {
+            if self.loaded < size {
+                self.load_block()?;
+            }
+            while self.held.len() < nbits && self.next < size {
+                self.held.push(1, self.matrix.get(self.next) as u64);
+                self.next += 1;
+            }
+            if self.next == size {
+                self.loaded = 0;
+                self.next = 0;
+                self.blocks += 1;
+            }
+        }
+        self.position += nbits as u64;
+        Ok(self.held.pop(nbits))
+    }
+
+    fn load_block(&mut self) -> io::Result<()> {
+        let size = self.matrix.size();
+        while self.loaded < size {
+            let want = core::cmp::min(size - self.loaded, 64) as u8;
+            let value = match self.inner.read_bits(want) {
+                Ok(value) => value,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(self.truncated(e));
+                }
+                Err(e) => return Err(e),
+            };
+            let mut bits = Accumulator::<O>::new();
+            bits.push(want, value);
+            for _ in 0..want {
+                let index = self.matrix.transpose(self.loaded);
+                self.matrix.set(index, bits.pop(1) as u8);
+                self.loaded += 1;
+            }
+        }
+        Ok(())
+    }
+
+    /// Turn the inner reader running out into an error naming how much of the
+    /// block it supplied, if any.
+    fn truncated(&mut self, eof: io::Error) -> io::Error {
+        loop {
+            match self.inner.read_bit() {
+                Ok(_) => self.loaded += 1,
+                Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return e,
+            }
+        }
+        if self.loaded == 0 {
+            return eof;
+        }
+        Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "deinterleave",
+            "block truncated",
+        )
+        .at(Some(self.inner.position()))
+        .with_value(self.loaded as u64)
+        .into()
+    }
+
+    /// The number of data bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of blocks read completely.
+    pub fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Reading from it directly
+    /// will lose the block alignment.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `DeinterleaveBitReader`, returning the inner reader.  The
+    /// unread bits of a loaded block are lost.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for DeinterleaveBitReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        DeinterleaveBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
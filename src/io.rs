@@ -0,0 +1,239 @@
+//! The IO traits used by `BitReader` and `BitWriter`.
+//!
+//! With the default `std` feature these are simply re-exports from `std::io`.
+//! Without it, this module provides minimal stand-ins with the same names and
+//! semantics, so the rest of the crate - and code generic over it - is written
+//! once against `bitrw::io` and works in both configurations.
+//!
+//! The `no_std` `Error` carries only an `ErrorKind` and a static message, so
+//! anything constructing errors portably should stick to
+//! `Error::new(kind, "literal")` and `Error::other("literal")`.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, IoSlice, Read, Result, Seek, SeekFrom, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::*;
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use core::fmt;
+    use core::result;
+
+    /// A list specifying general categories of IO error, mirroring the subset
+    /// of `std::io::ErrorKind` this crate produces or inspects.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub enum ErrorKind {
+        /// Data not valid for the operation were encountered.
+        InvalidData,
+        /// A parameter was incorrect.
+        InvalidInput,
+        /// The operation was interrupted and may be retried.
+        Interrupted,
+        /// The operation needs to block to complete.
+        WouldBlock,
+        /// The end of the input was reached prematurely.
+        UnexpectedEof,
+        /// A write returned `Ok(0)`.
+        WriteZero,
+        /// The operation is not supported.
+        Unsupported,
+        /// Any other error.
+        Other,
+    }
+
+    impl ErrorKind {
+        fn as_str(self) -> &'static str {
+            match self {
+                ErrorKind::InvalidData => "invalid data",
+                ErrorKind::InvalidInput => "invalid input parameter",
+                ErrorKind::Interrupted => "operation interrupted",
+                ErrorKind::WouldBlock => "operation would block",
+                ErrorKind::UnexpectedEof => "unexpected end of file",
+                ErrorKind::WriteZero => "write zero",
+                ErrorKind::Unsupported => "unsupported",
+                ErrorKind::Other => "other error",
+            }
+        }
+    }
+
+    /// The error type for IO operations without `std`: a kind plus an optional
+    /// static description.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: Option<&'static str>,
+    }
+
+    impl Error {
+        /// Create an error of the given kind with a description.
+        pub fn new(kind: ErrorKind, message: &'static str) -> Self {
+            Error {
+                kind,
+                message: Some(message),
+            }
+        }
+
+        /// Create an error of kind `ErrorKind::Other` with a description.
+        pub fn other(message: &'static str) -> Self {
+            Self::new(ErrorKind::Other, message)
+        }
+
+        /// The kind of this error.
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl From<ErrorKind> for Error {
+        fn from(kind: ErrorKind) -> Self {
+            Error {
+                kind,
+                message: None,
+            }
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str(self.message.unwrap_or_else(|| self.kind.as_str()))
+        }
+    }
+
+    /// A specialized `Result` type for IO operations.
+    pub type Result<T> = result::Result<T, Error>;
+
+    /// A source of bytes, like `std::io::Read`.
+    pub trait Read {
+        /// Pull some bytes into `buf`, returning how many were read.  `Ok(0)`
+        /// means end of file if `buf` is not empty.
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize>;
+
+        /// Read exactly enough bytes to fill `buf`.
+        fn read_exact(&mut self, mut buf: &mut [u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.read(buf) {
+                    Ok(0) => break,
+                    Ok(n) => buf = &mut buf[n..],
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            if buf.is_empty() {
+                Ok(())
+            } else {
+                Err(Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "failed to fill whole buffer",
+                ))
+            }
+        }
+    }
+
+    /// A sink of bytes, like `std::io::Write`.
+    pub trait Write {
+        /// Write some bytes from `buf`, returning how many were written.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flush any buffered output to its destination.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Write all of `buf`.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf) {
+                    Ok(0) => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    Ok(n) => buf = &buf[n..],
+                    Err(ref e) if e.kind() == ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Enumeration of possible methods to seek within an IO object.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum SeekFrom {
+        /// Offset from the start.
+        Start(u64),
+        /// Offset from the end.
+        End(i64),
+        /// Offset from the current position.
+        Current(i64),
+    }
+
+    /// A cursor which can be moved within a stream, like `std::io::Seek`.
+    pub trait Seek {
+        /// Seek to an offset, returning the new position from the start.
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64>;
+
+        /// The current offset from the start.
+        fn stream_position(&mut self) -> Result<u64> {
+            self.seek(SeekFrom::Current(0))
+        }
+    }
+
+    impl<R: Read + ?Sized> Read for &mut R {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            (**self).read(buf)
+        }
+    }
+
+    impl<W: Write + ?Sized> Write for &mut W {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+    }
+
+    impl<S: Seek + ?Sized> Seek for &mut S {
+        fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+            (**self).seek(pos)
+        }
+    }
+
+    impl Read for &[u8] {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+            let n = core::cmp::min(buf.len(), self.len());
+            let (head, tail) = self.split_at(n);
+            buf[..n].copy_from_slice(head);
+            *self = tail;
+            Ok(n)
+        }
+    }
+
+    impl Write for &mut [u8] {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            let n = core::cmp::min(buf.len(), self.len());
+            let (head, tail) = core::mem::take(self).split_at_mut(n);
+            head.copy_from_slice(&buf[..n]);
+            *self = tail;
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    impl Write for alloc::vec::Vec<u8> {
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,213 @@
+//! Reading from iterators of bytes.
+//!
+//! `BitReader::from_byte_iter()` reads from any `Iterator<Item = u8>`, and
+//! `BitReader::from_fallible_iter()` from any `Iterator<Item = io::Result<u8>>`,
+//! without collecting them first.  `BitReader::from_slices()` reads straight
+//! through a sequence of borrowed slices, such as a scatter/gather list, as if
+//! they were concatenated.  The end of the iterator is the end of the stream.
+
+use core::cmp;
+
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::BitReader;
+
+/// Adapts an iterator of bytes into an `io::Read` for use by `BitReader`.
+#[derive(Debug)]
+pub struct IterSource<I>(I);
+
+impl<I: Iterator<Item = u8>> IterSource<I> {
+    /// Wrap the given iterator.
+    pub fn new(iter: I) -> Self {
+        IterSource(iter)
+    }
+
+    /// Get a reference to the iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.0
+    }
+
+    /// Get a mutable reference to the iterator.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.0
+    }
+
+    /// Unwrap the iterator.  Any bytes not yet read remain in it.
+    pub fn into_inner(self) -> I {
+        self.0
+    }
+}
+
+impl<I: Iterator<Item = u8>> io::Read for IterSource<I> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        for slot in dst.iter_mut() {
+            match self.0.next() {
+                Some(byte) => *slot = byte,
+                None => break,
+            }
+            read += 1;
+        }
+        Ok(read)
+    }
+}
+
+/// Adapts an iterator of `io::Result<u8>` into an `io::Read` for use by
+/// `BitReader`.  An error is returned once, in place of the byte it replaced,
+/// after any bytes before it have been delivered.
+#[derive(Debug)]
+pub struct FallibleIterSource<I> {
+    iter: I,
+    error: Option<io::Error>,
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> FallibleIterSource<I> {
+    /// Wrap the given iterator.
+    pub fn new(iter: I) -> Self {
+        Self { iter, error: None }
+    }
+
+    /// Get a reference to the iterator.
+    pub fn get_ref(&self) -> &I {
+        &self.iter
+    }
+
+    /// Get a mutable reference to the iterator.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.iter
+    }
+
+    /// Unwrap the iterator.  Any bytes not yet read remain in it, though an
+    /// error not yet returned is lost.
+    pub fn into_inner(self) -> I {
+        self.iter
+    }
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> io::Read for FallibleIterSource<I> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        let mut read = 0;
+        for slot in dst.iter_mut() {
+            match self.iter.next() {
+                Some(Ok(byte)) => *slot = byte,
+                Some(Err(e)) if read == 0 => return Err(e),
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    break;
+                }
+                None => break,
+            }
+            read += 1;
+        }
+        Ok(read)
+    }
+}
+
+/// Adapts an iterator of byte slices into an `io::Read` for use by
+/// `BitReader`, reading through each in turn without copying them together.
+#[derive(Debug)]
+pub struct SliceChainSource<'a, I> {
+    slices: I,
+    current: &'a [u8],
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> SliceChainSource<'a, I> {
+    /// Wrap the given iterator of slices.
+    pub fn new(slices: I) -> Self {
+        Self {
+            slices,
+            current: &[],
+        }
+    }
+
+    /// The unread remainder of the current slice.  The rest are still in the
+    /// iterator.
+    pub fn current(&self) -> &'a [u8] {
+        self.current
+    }
+
+    /// Unwrap the iterator, discarding the rest of the current slice.
+    pub fn into_inner(self) -> I {
+        self.slices
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> io::Read for SliceChainSource<'a, I> {
+    fn read(&mut self, mut dst: &mut [u8]) -> io::Result<usize> {
+        let mut read = 0;
+        while !dst.is_empty() {
+            if self.current.is_empty() {
+                match self.slices.next() {
+                    Some(slice) => self.current = slice,
+                    None => break,
+                }
+                continue;
+            }
+
+            let n = cmp::min(self.current.len(), dst.len());
+            dst[..n].copy_from_slice(&self.current[..n]);
+            self.current = &self.current[n..];
+            dst = &mut dst[n..];
+            read += n;
+        }
+        Ok(read)
+    }
+}
+
+impl<I: Iterator<Item = u8>> BitReader<IterSource<I>> {
+    /// Create a new `BitReader` reading from the given iterator of bytes.
+    pub fn from_byte_iter(iter: I) -> Self {
+        Self::with_order(IterSource::new(iter), Msb0)
+    }
+}
+
+impl<I: Iterator<Item = u8>, O: BitOrder> BitReader<IterSource<I>, O> {
+    /// Create a new `BitReader` reading from the given iterator of bytes,
+    /// unpacking bits in the given order.
+    pub fn from_byte_iter_with_order(iter: I, order: O) -> Self {
+        Self::with_order(IterSource::new(iter), order)
+    }
+}
+
+impl<I: Iterator<Item = io::Result<u8>>> BitReader<FallibleIterSource<I>> {
+    /// Create a new `BitReader` reading from the given iterator of fallible
+    /// bytes.  Errors are passed on from the read which reaches them.
+    pub fn from_fallible_iter(iter: I) -> Self {
+        Self::with_order(FallibleIterSource::new(iter), Msb0)
+    }
+}
+
+impl<I: Iterator<Item = io::Result<u8>>, O: BitOrder> BitReader<FallibleIterSource<I>, O> {
+    /// Create a new `BitReader` reading from the given iterator of fallible
+    /// bytes, unpacking bits in the given order.
+    pub fn from_fallible_iter_with_order(iter: I, order: O) -> Self {
+        Self::with_order(FallibleIterSource::new(iter), order)
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>> BitReader<SliceChainSource<'a, I>> {
+    /// Create a new `BitReader` reading through each of the given slices in
+    /// turn, e.g. `BitReader::from_slices(parts.iter().copied())` for a
+    /// `&[&[u8]]`.  Fields may straddle any number of slice boundaries.
+    pub fn from_slices<S>(slices: S) -> Self
+    where
+        S: IntoIterator<Item = &'a [u8], IntoIter = I>,
+    {
+        Self::with_order(SliceChainSource::new(slices.into_iter()), Msb0)
+    }
+}
+
+impl<'a, I: Iterator<Item = &'a [u8]>, O: BitOrder> BitReader<SliceChainSource<'a, I>, O> {
+    /// Create a new `BitReader` reading through each of the given slices in
+    /// turn, unpacking bits in the given order.
+    pub fn from_slices_with_order<S>(slices: S, order: O) -> Self
+    where
+        S: IntoIterator<Item = &'a [u8], IntoIter = I>,
+    {
+        Self::with_order(SliceChainSource::new(slices.into_iter()), order)
+    }
+}
@@ -0,0 +1,295 @@
+//! JPEG entropy-coded segments.
+//!
+//! Within a scan, each 0xFF byte of entropy-coded data is followed by a
+//! stuffed 0x00, so that 0xFF followed by anything else is a marker.
+//! `JpegSource` removes the stuffing as bytes are read and ends the stream at
+//! the first marker, which `JpegBitReader` reports via `marker_hit()`.
+//!
+//! For encoding, `JpegSink` stuffs a 0x00 after every 0xFF written to it, and
+//! `JpegBitWriter` writes bits through one, padding with ones as the spec
+//! requires.
+
+use crate::io;
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// Adapts a reader of a JPEG entropy-coded segment into a reader of the data
+/// it encodes, removing the 0x00 stuffed after each 0xFF, and ending at the
+/// first marker.
+///
+/// The marker itself, and any 0xFF fill bytes before it, are consumed from the
+/// inner reader, so it's left positioned just after the marker.
+#[derive(Debug)]
+pub struct JpegSource<R> {
+    inner: R,
+    marker: Option<u8>,
+}
+
+impl<R: io::Read> JpegSource<R> {
+    /// Wrap the given reader, which should be positioned at the start of an
+    /// entropy-coded segment.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            marker: None,
+        }
+    }
+
+    /// The marker which ended the segment, if it's been reached.
+    pub fn marker_hit(&self) -> Option<u8> {
+        self.marker
+    }
+
+    /// Forget the marker, so reading carries on with whatever follows it, such
+    /// as the next interval after a restart marker.  Returns the marker.
+    pub fn clear_marker(&mut self) -> Option<u8> {
+        self.marker.take()
+    }
+
+    /// Get a reference to the reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    fn raw_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let mut byte = [0];
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+impl<R: io::Read> io::Read for JpegSource<R> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if dst.is_empty() || self.marker.is_some() {
+            return Ok(0);
+        }
+        match self.raw_byte()? {
+            Some(0xff) => loop {
+                match self.raw_byte()? {
+                    Some(0x00) => {
+                        dst[0] = 0xff;
+                        return Ok(1);
+                    }
+                    // Fill bytes before a marker.
+                    Some(0xff) => {}
+                    Some(marker) => {
+                        self.marker = Some(marker);
+                        return Ok(0);
+                    }
+                    None => return Ok(0),
+                }
+            },
+            Some(byte) => {
+                dst[0] = byte;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Reads bits from a JPEG entropy-coded segment, removing byte stuffing.
+///
+/// Reads fail with `UnexpectedEof` once the data runs out at a marker, without
+/// consuming anything beyond it.
+///
+/// ```
+/// use bitrw::{BitRead, JpegBitReader};
+///
+/// let mut reader = JpegBitReader::new(&[0xff, 0x00, 0xa0, 0xff, 0xd9][..]);
+/// assert_eq!(reader.read_bits(11).unwrap(), 0x7fd);
+/// assert!(reader.read_bits(8).is_err());
+/// assert_eq!(reader.marker_hit(), Some(0xd9));
+/// ```
+#[derive(Debug)]
+pub struct JpegBitReader<R> {
+    reader: BitReader<JpegSource<R>>,
+}
+
+impl<R: io::Read> JpegBitReader<R> {
+    /// Create a new `JpegBitReader` at the start of an entropy-coded segment.
+    pub fn new(inner: R) -> Self {
+        Self {
+            reader: BitReader::new(JpegSource::new(inner)),
+        }
+    }
+
+    /// The marker which ended the segment, if it's been reached.  Bits read
+    /// before it may still remain to be consumed.
+    pub fn marker_hit(&self) -> Option<u8> {
+        self.reader.get_ref().marker_hit()
+    }
+
+    /// Discard any bits left in the current segment and carry on reading
+    /// after the marker which ended it, as is needed at each restart marker.
+    /// Returns the marker, if one was hit.
+    pub fn restart(&mut self) -> Option<u8> {
+        self.reader.reset();
+        self.reader.get_mut().clear_marker()
+    }
+
+    /// The number of bits of data read so far, excluding stuffing.
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Get a reference to the source.
+    pub fn get_ref(&self) -> &JpegSource<R> {
+        self.reader.get_ref()
+    }
+
+    /// Unwrap this `JpegBitReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner().into_inner()
+    }
+}
+
+impl<R: io::Read> BitRead for JpegBitReader<R> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        self.reader.read_bits(nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.reader.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.reader.vlc_limit()
+    }
+}
+
+/// Adapts a writer to stuff a 0x00 after every 0xFF byte written to it, as
+/// required within a JPEG entropy-coded segment.
+#[derive(Debug)]
+pub struct JpegSink<W> {
+    inner: W,
+}
+
+impl<W: io::Write> JpegSink<W> {
+    /// Wrap the given writer.
+    pub fn new(inner: W) -> Self {
+        Self { inner }
+    }
+
+    /// Get a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the writer, for writing markers and other
+    /// bytes which must not be stuffed.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap the writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> io::Write for JpegSink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for run in buf.split_inclusive(|&byte| byte == 0xff) {
+            self.inner.write_all(run)?;
+            if run.last() == Some(&0xff) {
+                self.inner.write_all(&[0x00])?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes bits to a JPEG entropy-coded segment, stuffing a 0x00 after each
+/// 0xFF byte, including any produced by padding.
+///
+/// Padding is with one bits, as the spec requires at the end of each scan and
+/// restart interval.
+///
+/// ```
+/// use bitrw::{BitWrite, JpegBitWriter};
+///
+/// let mut writer = JpegBitWriter::new(Vec::new());
+/// writer.write_bits(11, 0x7fd).unwrap();
+/// writer.write_marker(0xd9).unwrap();
+/// assert_eq!(writer.into_inner(), [0xff, 0x00, 0xbf, 0xff, 0xd9]);
+/// ```
+#[derive(Debug)]
+pub struct JpegBitWriter<W> {
+    writer: BitWriter<JpegSink<W>>,
+}
+
+impl<W: io::Write> JpegBitWriter<W> {
+    /// Create a new `JpegBitWriter` at the start of an entropy-coded segment.
+    pub fn new(inner: W) -> Self {
+        let mut writer = BitWriter::new(JpegSink::new(inner));
+        writer.pad_ones = true;
+        Self { writer }
+    }
+
+    /// Pad to a byte boundary with one bits, as for `BitWriter::flush()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.writer.flush()
+    }
+
+    /// Pad to a byte boundary with one bits, as for `BitWriter::flush_bits()`.
+    pub fn flush_bits(&mut self) -> io::Result<usize> {
+        self.writer.flush_bits()
+    }
+
+    /// Pad to a byte boundary, then write the marker 0xFF `marker` without
+    /// stuffing, such as a restart marker or EOI.  Returns the number of bits
+    /// of padding.
+    pub fn write_marker(&mut self, marker: u8) -> io::Result<usize> {
+        let padding = self.writer.flush_bits()?;
+        self.writer.get_mut().get_mut().write_all(&[0xff, marker])?;
+        Ok(padding)
+    }
+
+    /// The number of bits of data written so far, including padding but
+    /// excluding stuffing and markers.
+    pub fn position(&self) -> u64 {
+        self.writer.position()
+    }
+
+    /// Get a reference to the sink.
+    pub fn get_ref(&self) -> &JpegSink<W> {
+        self.writer.get_ref()
+    }
+
+    /// Unwrap this `JpegBitWriter`, returning the underlying writer.  As with
+    /// `BitWriter`, call `flush()` or `write_marker()` first.
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().into_inner()
+    }
+}
+
+impl<W: io::Write> BitWrite for JpegBitWriter<W> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        self.writer.write_bits(nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.writer.position())
+    }
+}
@@ -1,67 +1,500 @@
-use std::io;
-use std::io::SeekFrom;
-use std::io::{Error, ErrorKind};
+#![cfg_attr(not(feature = "std"), no_std)]
 
-const MASKS: [u64; 8] = [0, 0b1, 0b11, 0b111, 0b1111, 0b11111, 0b111111, 0b1111111];
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::cmp;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error as BitError;
+use crate::io::SeekFrom;
+
+mod accumulator;
+#[cfg(feature = "tokio")]
+mod async_io;
+#[cfg(feature = "base64")]
+mod base64;
+mod bcd;
+#[cfg(feature = "num-bigint")]
+mod bigint;
+mod bitmap;
+#[cfg(feature = "bitvec")]
+mod bitslice;
+#[cfg(feature = "bytes")]
+mod buf;
+#[cfg(feature = "std")]
+mod bufread;
+mod builder;
+mod byte_reverse;
+mod carry;
+mod chain;
+mod checksum;
+mod codec;
+mod constrained;
+mod convert;
+mod counting;
+mod crc;
+mod debug;
+#[cfg(feature = "alloc")]
+mod diff;
+#[cfg(feature = "alloc")]
+pub mod dump;
+mod elias_fano;
+mod error;
+mod fixed;
+mod float;
+mod fork;
+mod gray;
+mod gsm7;
+mod hamming;
+#[cfg(feature = "alloc")]
+mod interleave;
+pub mod io;
+mod iter;
+mod jpeg;
+mod lzw;
+mod manchester;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod order;
+mod parity;
+mod parts;
+mod pcm;
+mod primitive;
+mod quantize;
+#[cfg(feature = "alloc")]
+mod queue;
+mod rbsp;
+mod realign;
+mod reverse;
+mod rice;
+#[cfg(feature = "alloc")]
+mod rle;
+mod row;
+mod scope;
+mod scramble;
+mod slice;
+#[cfg(feature = "alloc")]
+mod splice;
+mod start_code;
+mod state;
+mod stats;
+mod stop;
+mod stuff;
+mod take;
+mod tee;
+#[cfg(feature = "test-util")]
+pub mod testing;
+mod trace;
+mod traits;
+mod vlc;
+mod width;
+
+#[cfg(feature = "base64")]
+pub use base64::{Base64Alphabet, Base64BitReader, Base64BitWriter};
+#[cfg(feature = "derive")]
+pub use bitrw_derive::{FromBits, ToBits};
+#[cfg(feature = "std")]
+pub use bufread::BufBitReader;
+pub use builder::{BitReaderBuilder, BitWriterBuilder};
+pub use byte_reverse::ByteReverse;
+pub use carry::CarryBitWriter;
+pub use chain::ChainedBitReader;
+#[cfg(feature = "digest")]
+pub use checksum::UpdateDigest;
+pub use checksum::{BitDigest, HasherDigest};
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub use codec::derive_support as __derive;
+pub use codec::{FromBits, ToBits};
+pub use counting::CountingBitWriter;
+pub use crc::{Crc, CrcBitReader, CrcBitWriter, CrcFrame, CrcParams};
+#[cfg(feature = "alloc")]
+pub use diff::{diff_bits, BitDiff, BitDiffKind};
+#[cfg(feature = "alloc")]
+pub use elias_fano::EliasFanoIter;
+pub use error::{Error, ErrorReason};
+pub use fixed::Overflow;
+pub use gray::{gray_decode, gray_encode};
+pub use hamming::{HammingCode, HammingReader, HammingWriter};
+#[cfg(feature = "alloc")]
+pub use interleave::{DeinterleaveBitReader, InterleaveBitWriter};
+pub use iter::{FallibleIterSource, IterSource, SliceChainSource};
+pub use jpeg::{JpegBitReader, JpegBitWriter, JpegSink, JpegSource};
+pub use lzw::{CodeStreamReader, CodeStreamWriter};
+pub use manchester::{Manchester, ManchesterReader, ManchesterWriter};
+pub use order::{BitOrder, Lsb0, Msb0};
+pub use parity::{Parity, ParityBitReader, ParityBitWriter};
+pub use parts::{ReaderParts, WriterParts};
+pub use primitive::{Integer, Primitive};
+#[cfg(feature = "alloc")]
+pub use queue::BitQueue;
+pub use rbsp::{RbspBitReader, RbspBitWriter, RbspSink, RbspSource};
+pub use realign::RealignedBytes;
+pub use reverse::ReverseBitReader;
+pub use rice::rice_optimal_k;
+#[cfg(feature = "alloc")]
+pub use rle::{RleConfig, RleEnd, RunLength};
+pub use scramble::{DescrambleBitReader, Keystream, Lfsr, ScrambleBitWriter, ScrambleReset};
+pub use slice::BitSlice;
+#[cfg(feature = "alloc")]
+pub use splice::{splice_bits, splice_bits_with_order};
+pub use start_code::StartCode;
+pub use state::{ReaderState, WriterState};
+pub use stats::{Stats, StatsBitReader, StatsBitWriter};
+pub use stop::trailing_stop_len;
+pub use stuff::{BitStuffWriter, BitUnstuffReader, StuffRule};
+pub use take::TakeBits;
+pub use tee::TeeBitReader;
+pub use trace::{TraceBitReader, TraceBitWriter, TraceEvent};
+pub use traits::{BitRead, BitWrite};
+pub use width::{FieldType, Width};
+
+#[cfg(feature = "tokio")]
+pub use async_io::{AsyncBitReader, AsyncBitWriter};
+#[cfg(feature = "bytes")]
+pub use buf::{BufMutSink, BufSource};
+#[cfg(feature = "mmap")]
+pub use mmap::MmapBitReader;
 
 /// `The BitReader` struct adds bit-level reading to any io::Reader.
 ///
 /// Most readers should probably be wrapped in a `BufReader` to avoid single-byte
 /// reads.
-#[derive(Debug)]
-pub struct BitReader<R> {
+///
+/// Bits are unpacked most-significant first unless another `BitOrder` is
+/// selected with `with_order()`.
+///
+/// Most higher-level reads are provided by the `BitRead` trait.
+///
+/// Further options are set with a `BitReaderBuilder`.
+///
+/// # Non-blocking readers
+///
+/// Bytes are loaded one at a time and kept buffered if a read fails, so an
+/// error such as `WouldBlock` from a non-blocking reader loses nothing.  Once
+/// it's ready, make the same read again and it carries on from the bytes
+/// already loaded.  This holds for anything which reads a single field, such
+/// as `read_bits()`, `read_bit()` and `BitRead::read()`.
+///
+/// Reads of several fields, such as `read_many()` or `BitRead::read_gamma()`,
+/// keep what they'd read before the error consumed, so retrying them would
+/// read different bits.  Making any other read after a `WouldBlock` is a bug,
+/// as the first read's value is then lost.  `skip_bits()` leaves `position()`
+/// wherever it got to, so the rest can be skipped once ready.
+pub struct BitReader<R, O = Msb0> {
     inner: R,
-    buffer: [u8; 1],
-    unused: u8,
+    bits: Accumulator<O>,
+    position: u64,
+    limit: Option<u64>,
+    vlc_limit: Option<u32>,
 }
 
 impl<R: io::Read> BitReader<R> {
-    /// Create a new `BitReader` around the given reader.
+    /// Create a new `BitReader` around the given reader, with the defaults
+    /// described by `BitReaderBuilder`.
     pub fn new(inner: R) -> Self {
+        Self::with_order(inner, Msb0)
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Create a new `BitReader` around the given reader, unpacking bits in the
+    /// given order, e.g. `BitReader::with_order(inner, Lsb0)`.
+    pub fn with_order(inner: R, _order: O) -> Self {
         Self {
             inner,
-            buffer: [0],
-            unused: 0,
+            bits: Accumulator::new(),
+            position: 0,
+            limit: None,
+            vlc_limit: Some(vlc::DEFAULT_VLC_LIMIT),
         }
     }
 
     /// Reset the internal state of the BitReader. The next read will load fresh
     /// data from the current position of the reader and start from the beginning
     /// of the first byte returned.
+    ///
+    /// Any buffered bits are skipped over, so `position()` moves on to the next
+    /// byte boundary.
     pub fn reset(&mut self) {
-        self.buffer[0] = 0;
-        self.unused = 0;
+        self.position += self.bits.len() as u64;
+        self.bits.clear();
+    }
+
+    /// The number of bits consumed so far: the absolute bit offset of the next
+    /// read, assuming the reader started at the beginning of the stream.
+    ///
+    /// `seek()` sets this to the new position.
+    pub fn position(&self) -> u64 {
+        self.position
     }
 
     /// Read a single bit from the reader.
+    ///
+    /// This is equivalent to `read_bits(1)`, but faster when a bit is already
+    /// buffered.
+    #[inline]
     pub fn read_bit(&mut self) -> io::Result<u8> {
+        if self.bits.len() > 0 && self.limit.is_none_or(|limit| self.position < limit) {
+            self.position += 1;
+            return Ok(self.bits.pop(1) as u8);
+        }
         let bit = self.read_bits(1)?;
         Ok(bit as u8)
     }
 
     /// Read up to 64 bits from the reader.
+    ///
+    /// Bits are only consumed once the whole value is available, so if the
+    /// underlying reader fails any buffered bits remain to be read again.
     pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
         assert!(nbits <= 64);
+        // SAFETY: just checked.
+        unsafe { self.read_bits_unchecked(nbits) }
+    }
+
+    /// Read up to 64 bits from the reader, as for `read_bits()`, without
+    /// checking `nbits`.  Everything else is checked as usual, including any
+    /// limit, and IO errors are returned as normal.
+    ///
+    /// # Safety
+    ///
+    /// `nbits` must be no more than 64.  The compiler is told so, and larger
+    /// values are undefined behaviour.
+    #[inline]
+    pub unsafe fn read_bits_unchecked(&mut self, nbits: u8) -> io::Result<u64> {
+        // SAFETY: guaranteed by the caller.
+        unsafe { core::hint::assert_unchecked(nbits <= 64) };
+
+        if let Some(limit) = self.limit {
+            if self.position + nbits as u64 > limit {
+                return Err(BitError::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "read_bits",
+                    "bit limit reached",
+                )
+                .at(Some(self.position))
+                .into());
+            }
+        }
+
+        while self.bits.len() < nbits {
+            let mut byte = [0];
+            self.inner.read_exact(&mut byte)?;
+            self.bits.push(8, byte[0] as u64);
+        }
 
-        let mut ret: u64 = 0;
-        let mut rbits = nbits;
+        self.position += nbits as u64;
+        Ok(self.bits.pop(nbits))
+    }
 
-        while rbits > self.unused {
-            ret |= (self.buffer[0] as u64) << (rbits - self.unused);
-            rbits -= self.unused;
+    /// Read a run of fields, `widths[i]` bits into `out[i]`, as a sequence of
+    /// `read_bits()` calls would, checking the widths once up front.
+    ///
+    /// If a field can't be read, the error carries its index as its value,
+    /// along with the bit offset it started at.  The fields before it have been
+    /// read into `out` and consumed, and as with `read_bits()` any bits loaded
+    /// for it remain buffered to be read again.
+    ///
+    /// Panics if `widths` and `out` differ in length, or any width is more
+    /// than 64.
+    pub fn read_many(&mut self, widths: &[u8], out: &mut [u64]) -> io::Result<()> {
+        assert_eq!(widths.len(), out.len());
+        assert!(widths.iter().all(|&nbits| nbits <= 64));
 
-            self.inner.read_exact(&mut self.buffer)?;
+        for (index, (&nbits, value)) in widths.iter().zip(out).enumerate() {
+            let start = self.position;
+            // SAFETY: every width was checked above.
+            *value = unsafe { self.read_bits_unchecked(nbits) }.map_err(|e| {
+                io::Error::from(
+                    BitError::from_source("read_many", e)
+                        .at(Some(start))
+                        .with_value(index as u64),
+                )
+            })?;
+        }
+        Ok(())
+    }
 
-            self.unused = 8;
+    /// Skip over `nbits` bits, as if they were read and discarded.  Whole bytes
+    /// are skipped a block at a time, rather than loaded into the buffer.
+    ///
+    /// If this would pass any limit it fails with `UnexpectedEof` without
+    /// skipping anything.  If the stream ends first everything up to the end
+    /// has been skipped, and `position()` says how far that was.
+    pub fn skip_bits(&mut self, nbits: u64) -> io::Result<()> {
+        let start = Some(self.position);
+        if self
+            .limit
+            .is_some_and(|limit| self.position + nbits > limit)
+        {
+            return Err(BitError::new(
+                io::ErrorKind::UnexpectedEof,
+                "skip_bits",
+                "bit limit reached",
+            )
+            .at(start)
+            .into());
         }
 
-        if rbits > 0 {
-            ret |= (self.buffer[0] as u64) >> (self.unused - rbits);
-            self.buffer[0] &= MASKS[(self.unused - rbits) as usize] as u8;
-            self.unused -= rbits;
+        let head = cmp::min(self.bits.len() as u64, nbits) as u8;
+        self.bits.discard(head);
+        self.position += head as u64;
+        let mut remaining = nbits - head as u64;
+
+        let mut buf = [0u8; 1024];
+        while remaining >= 8 {
+            let want = cmp::min(remaining / 8, buf.len() as u64) as usize;
+            match self.inner.read(&mut buf[..want]) {
+                Ok(0) => {
+                    return Err(BitError::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "skip_bits",
+                        "end of stream",
+                    )
+                    .at(start)
+                    .into())
+                }
+                Ok(n) => {
+                    self.position += n as u64 * 8;
+                    remaining -= n as u64 * 8;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(BitError::wrap(e, "skip_bits", start)),
+            }
         }
+        self.read_bits(remaining as u8)
+            .map(|_| ())
+            .map_err(|e| BitError::wrap(e, "skip_bits", start))
+    }
 
-        Ok(ret)
+    /// Read everything left, up to the end of the stream or any limit,
+    /// appending it to `out` packed as a `BitWriter` of the same order would
+    /// write it, and return the exact number of bits read.
+    ///
+    /// If that isn't a whole number of bytes, as when the reader started part
+    /// way through a byte, the last byte appended is padded with zero bits.
+    /// So the tail of a stream can be stashed and written again later with
+    /// `write_bytes()` for the whole bytes and `write_bits()` for the rest.
+    /// On error the bits read so far stay appended to `out`.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// reader.read_bits(4).unwrap();
+    /// let mut tail = Vec::new();
+    /// assert_eq!(reader.read_to_end_bits(&mut tail).unwrap(), 12);
+    /// assert_eq!(tail, [0xbc, 0xd0]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn read_to_end_bits(&mut self, out: &mut Vec<u8>) -> io::Result<u64> {
+        let start = self.position;
+        // The number of bits left before any limit.
+        let left = |reader: &Self| {
+            reader
+                .limit
+                .map_or(u64::MAX, |limit| limit.saturating_sub(reader.position))
+        };
+
+        let mut buf = [0u8; 1024];
+        loop {
+            while self.bits.len() >= 8 && left(self) >= 8 {
+                out.push(self.bits.pop(8) as u8);
+                self.position += 8;
+            }
+            let wanted = left(self).saturating_sub(self.bits.len() as u64);
+            if wanted == 0 {
+                break;
+            }
+            let want = cmp::min(wanted.div_ceil(8), buf.len() as u64) as usize;
+            let n = match self.inner.read(&mut buf[..want]) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(BitError::wrap(e, "read_to_end_bits", Some(start))),
+            };
+            // Whole bytes before any limit go straight on when aligned, and
+            // everything else through the buffer.
+            let mut whole = 0;
+            if self.bits.len() == 0 {
+                whole = cmp::min(n as u64, wanted / 8) as usize;
+                out.extend_from_slice(&buf[..whole]);
+                self.position += whole as u64 * 8;
+            }
+            for &byte in &buf[whole..n] {
+                self.bits.push(8, byte as u64);
+                if left(self) >= 8 {
+                    out.push(self.bits.pop(8) as u8);
+                    self.position += 8;
+                }
+            }
+        }
+
+        let tail = cmp::min(self.bits.len() as u64, left(self)) as u8;
+        if tail > 0 {
+            let mut last = Accumulator::<O>::new();
+            last.push(tail, self.bits.pop(tail));
+            last.pad(false);
+            out.push(last.pop(8) as u8);
+            self.position += tail as u64;
+        }
+        Ok(self.position - start)
+    }
+
+    /// Push the low `nbits` of `value` back onto the front of the stream, to
+    /// be read again before anything else, as if the last `nbits` bits read
+    /// had been `value`.  `position()` moves back by `nbits`.
+    ///
+    /// Pushed back bits share the 127 bit buffer with any bits already loaded,
+    /// of which there are never more than 63, so at least 64 bits can be
+    /// pushed back at once.  Pushing back more than there's room for, or more
+    /// than have been read, is an `InvalidInput` error carrying `nbits`, and
+    /// nothing is pushed back.
+    ///
+    /// Pushed back bits are dropped by `reset()` and `seek()`, and should be
+    /// the bits which were read if the reader's `save_state()` is to be
+    /// resumed.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// let tag = reader.read_bits(12).unwrap();
+    /// assert_eq!(tag, 0xabc);
+    /// reader.unread_bits(8, tag).unwrap();
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0xbcd);
+    /// ```
+    pub fn unread_bits(&mut self, nbits: u8, value: u64) -> io::Result<()> {
+        assert!(nbits <= 64);
+        let message = if nbits as u64 > self.position {
+            Some("unread past the start")
+        } else if self.bits.len() + nbits > 127 {
+            Some("no room to unread")
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Err(
+                BitError::new(io::ErrorKind::InvalidInput, "unread_bits", message)
+                    .at(Some(self.position))
+                    .with_value(nbits as u64)
+                    .into(),
+            );
+        }
+
+        let mut bits = Accumulator::new();
+        bits.push(nbits, value);
+        while self.bits.len() > 0 {
+            let n = cmp::min(self.bits.len(), 64);
+            bits.push(n, self.bits.pop(n));
+        }
+        self.bits = bits;
+        self.position -= nbits as u64;
+        Ok(())
     }
 
     /// Get a reference to the reader.
@@ -80,9 +513,44 @@ impl<R: io::Read> BitReader<R> {
     pub fn into_inner(self) -> R {
         self.inner
     }
+
+    /// Swap in a new underlying reader, returning the old one.  Buffered bits
+    /// are kept, and reading carries on with them before loading from `new`,
+    /// so a stream split across several readers can be decoded as one, even
+    /// if a split falls mid-field.
+    ///
+    /// `position()` isn't changed, and carries on counting from where the old
+    /// reader left off.  `seek()` works in terms of `new` alone, though, so
+    /// seeking afterwards sets the position to one within `new`.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab][..]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// reader.replace_inner(&[0xcd][..]);
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0xbc);
+    /// assert_eq!(reader.position(), 12);
+    /// ```
+    pub fn replace_inner(&mut self, new: R) -> R {
+        core::mem::replace(&mut self.inner, new)
+    }
+
+    /// Wrap or replace the underlying reader with `f`, such as to put a
+    /// decompressor in front of the rest of the stream.  As with
+    /// `replace_inner()`, buffered bits, the position and any limit are kept.
+    pub fn map_inner<R2: io::Read, F: FnOnce(R) -> R2>(self, f: F) -> BitReader<R2, O> {
+        BitReader {
+            inner: f(self.inner),
+            bits: self.bits,
+            position: self.position,
+            limit: self.limit,
+            vlc_limit: self.vlc_limit,
+        }
+    }
 }
 
-impl<R: io::Read + io::Seek> BitReader<R> {
+impl<R: io::Read + io::Seek, O: BitOrder> BitReader<R, O> {
     /// Seek to the given *bit* position in the file.  Currently only
     /// `SeekFrom::Start` and `SeekFrom::End` with negative offsets are supported.
     pub fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
@@ -91,6 +559,7 @@ impl<R: io::Read + io::Seek> BitReader<R> {
                 self.reset();
                 self.inner.seek(SeekFrom::Start(pos / 8))?;
                 self.read_bits((pos % 8) as u8)?;
+                self.position = pos;
                 Ok(pos)
             }
             SeekFrom::End(pos) => {
@@ -103,19 +572,184 @@ impl<R: io::Read + io::Seek> BitReader<R> {
                     }
                     let ipos = self.inner.seek(SeekFrom::End(bypos))?;
                     self.read_bits(bipos as u8)?;
+                    self.position = ipos * 8 + bipos as u64;
                     Ok(ipos + (pos % 8) as u64)
                 } else {
-                    Err(Error::new(
-                        ErrorKind::Other,
+                    Err(io::Error::other(
                         "SeekFrom::End(seeking past end of file not yet supported",
                     ))
                 }
             }
-            SeekFrom::Current(_pos) => Err(Error::new(
-                ErrorKind::Other,
-                "SeekFrom::Current not yet supported",
-            )),
+            SeekFrom::Current(_pos) => Err(io::Error::other("SeekFrom::Current not yet supported")),
+        }
+    }
+
+    /// The length of the inner stream in bits, found by seeking to its end
+    /// and back.  Reading isn't disturbed.
+    pub fn len_bits(&mut self) -> io::Result<u64> {
+        let (_, end) = self.stream_offsets()?;
+        Ok(end * 8)
+    }
+
+    /// The number of bits left to read before the end of the inner stream, or
+    /// any limit if that comes first.  The stream's length is found by seeking
+    /// to its end and back, and reading isn't disturbed.
+    ///
+    /// This counts from where the inner stream really is, less any bits
+    /// buffered but not yet read, so it's right even if the reader was created
+    /// part way through the stream, when `position()` counts from there.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BitReader::new(Cursor::new([0x12, 0x34, 0x56]));
+    /// assert_eq!(reader.len_bits().unwrap(), 24);
+    /// reader.read_bits(5).unwrap();
+    /// assert_eq!(reader.remaining_bits().unwrap(), 19);
+    /// assert_eq!(reader.read_bits(3).unwrap(), 0x2);
+    /// ```
+    pub fn remaining_bits(&mut self) -> io::Result<u64> {
+        let (here, end) = self.stream_offsets()?;
+        let remaining = (end.saturating_sub(here) * 8) + u64::from(self.bits.len());
+        Ok(match self.limit {
+            Some(limit) => remaining.min(limit.saturating_sub(self.position)),
+            None => remaining,
+        })
+    }
+
+    /// The inner stream's current offset and length in bytes, leaving it where
+    /// it was.
+    fn stream_offsets(&mut self) -> io::Result<(u64, u64)> {
+        let here = self.inner.stream_position()?;
+        let end = self.inner.seek(SeekFrom::End(0))?;
+        if end != here {
+            self.inner.seek(SeekFrom::Start(here))?;
         }
+        Ok((here, end))
+    }
+
+    /// Read fields from a known layout, `fields[i]` being the bit offset and
+    /// width of the field read into `out[i]`, then seek back to where the
+    /// reader started, so sequential reading carries on unaffected.
+    ///
+    /// Offsets are absolute, as for `seek()`, and must be in ascending order,
+    /// though fields may overlap.  Out of order fields are an `InvalidInput`
+    /// error, before anything is read.  Gaps of less than 64 bits are skipped
+    /// by reading through them, and anything else by seeking.
+    ///
+    /// If a field can't be read the error carries its index as its value, as
+    /// for `read_many()`, and the reader is still sought back if possible.
+    ///
+    /// Panics if `fields` and `out` differ in length, or any width is more
+    /// than 64.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    /// use std::io::Cursor;
+    ///
+    /// let mut reader = BitReader::new(Cursor::new([0x12, 0x34, 0x56, 0x78]));
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0x1);
+    ///
+    /// let mut out = [0; 2];
+    /// reader.read_scattered(&[(8, 4), (20, 12)], &mut out).unwrap();
+    /// assert_eq!(out, [0x3, 0x678]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0x2);
+    /// ```
+    pub fn read_scattered(&mut self, fields: &[(u64, u8)], out: &mut [u64]) -> io::Result<()> {
+        assert_eq!(fields.len(), out.len());
+        assert!(fields.iter().all(|&(_, nbits)| nbits <= 64));
+        if let Some(index) = fields.windows(2).position(|pair| pair[1].0 < pair[0].0) {
+            return Err(BitError::new(
+                io::ErrorKind::InvalidInput,
+                "read_scattered",
+                "fields out of order",
+            )
+            .with_value(index as u64 + 1)
+            .into());
+        }
+
+        let origin = self.position;
+        let result = self.read_fields(fields, out);
+        let restored = self.seek(SeekFrom::Start(origin));
+        result?;
+        restored
+            .map(|_| ())
+            .map_err(|e| BitError::wrap(e, "read_scattered", Some(origin)))
+    }
+
+    fn read_fields(&mut self, fields: &[(u64, u8)], out: &mut [u64]) -> io::Result<()> {
+        for (index, (&(offset, nbits), value)) in fields.iter().zip(out).enumerate() {
+            let field = |e| {
+                io::Error::from(
+                    BitError::from_source("read_scattered", e)
+                        .at(Some(offset))
+                        .with_value(index as u64),
+                )
+            };
+            if offset >= self.position && offset - self.position < 64 {
+                self.skip_bits(offset - self.position).map_err(field)?;
+            } else {
+                self.seek(SeekFrom::Start(offset)).map_err(field)?;
+            }
+            *value = self.read_bits(nbits).map_err(field)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads whole bytes, for handing a `BitReader` to byte-oriented code at a
+/// byte boundary.  Bytes already buffered are returned first, then bytes from
+/// the inner reader, and `position()` moves on by eight bits for each, so bit
+/// reads can carry on afterwards.
+///
+/// Reading part way through a byte fails with `InvalidInput`.  Any limit is
+/// respected, with the end of the stream at the last whole byte before it.
+///
+/// `BitRead::read()` shares the name of `read()`, so if both traits are in
+/// scope call it as `io::Read::read(&mut reader, buf)`, or use `read_exact()`.
+///
+/// ```
+/// use bitrw::BitReader;
+/// use std::io::Read;
+///
+/// let mut reader = BitReader::new(&[0xab, 0xcd, 0xef, 0x80][..]);
+/// assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+/// let mut bytes = [0; 2];
+/// reader.read_exact(&mut bytes).unwrap();
+/// assert_eq!(bytes, [0xcd, 0xef]);
+/// assert_eq!(reader.read_bits(1).unwrap(), 1);
+/// assert!(reader.read_exact(&mut bytes).is_err());
+/// ```
+impl<R: io::Read, O: BitOrder> io::Read for BitReader<R, O> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.bits.len().is_multiple_of(8) {
+            return Err(BitError::new(
+                io::ErrorKind::InvalidInput,
+                "read",
+                "not at a byte boundary",
+            )
+            .at(Some(self.position))
+            .into());
+        }
+
+        let mut len = buf.len();
+        if let Some(limit) = self.limit {
+            let left = limit.saturating_sub(self.position) / 8;
+            len = cmp::min(len as u64, left) as usize;
+        }
+        let buf = &mut buf[..len];
+
+        let mut n = 0;
+        while self.bits.len() > 0 && n < len {
+            buf[n] = self.bits.pop(8) as u8;
+            n += 1;
+        }
+        if n == 0 && len > 0 {
+            n = self.inner.read(buf)?;
+        }
+        self.position += n as u64 * 8;
+        Ok(n)
     }
 }
 
@@ -123,26 +757,69 @@ impl<R: io::Read + io::Seek> BitReader<R> {
 ///
 /// Most writers should probably be wrapped in a `BufWriter` to avoid single-byte
 /// writes.
-#[derive(Debug)]
-pub struct BitWriter<W> {
+///
+/// Bits are packed most-significant first unless another `BitOrder` is
+/// selected with `with_order()`.
+///
+/// Most higher-level writes are provided by the `BitWrite` trait.
+///
+/// Further options, such as batching writes in an internal buffer, are set
+/// with a `BitWriterBuilder`.
+pub struct BitWriter<W, O = Msb0> {
     inner: W,
-    buffer: u64,
-    unused: u64,
+    bits: Accumulator<O>,
+    position: u64,
+    limit: Option<u64>,
+    pad_ones: bool,
+    // Flush the inner writer after this many bytes, zero for never.
+    auto_flush: u64,
+    // Bytes passed on since the inner writer was last flushed.
+    unflushed: u64,
+    #[cfg(feature = "alloc")]
+    buf: Vec<u8>,
+    #[cfg(feature = "alloc")]
+    capacity: usize,
 }
 
 impl<W: io::Write> BitWriter<W> {
-    /// Create a new `BitWriter` around the given writer.
+    /// Create a new `BitWriter` around the given writer, with the defaults
+    /// described by `BitWriterBuilder`.
     pub fn new(inner: W) -> Self {
+        Self::with_order(inner, Msb0)
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Create a new `BitWriter` around the given writer, packing bits in the
+    /// given order, e.g. `BitWriter::with_order(inner, Lsb0)`.
+    pub fn with_order(inner: W, _order: O) -> Self {
         Self {
             inner,
-            buffer: 0,
-            unused: 8,
+            bits: Accumulator::new(),
+            position: 0,
+            limit: None,
+            pad_ones: false,
+            auto_flush: 0,
+            unflushed: 0,
+            #[cfg(feature = "alloc")]
+            buf: Vec::new(),
+            #[cfg(feature = "alloc")]
+            capacity: 0,
         }
     }
 
     /// Write a single bit to the writer.
+    ///
+    /// This is equivalent to `write_bits(1, bit)`, but faster when the bit
+    /// doesn't complete a byte.
+    #[inline]
     pub fn write_bit(&mut self, bit: u8) -> io::Result<()> {
         assert!(bit <= 1);
+        if self.bits.len() < 7 && self.limit.is_none_or(|limit| self.position < limit) {
+            self.bits.push(1, bit as u64);
+            self.position += 1;
+            return Ok(());
+        }
         self.write_bits(1, bit as u64)?;
         Ok(())
     }
@@ -150,42 +827,261 @@ impl<W: io::Write> BitWriter<W> {
     /// Write up to 64 bits to the writer.
     pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
         assert!(nbits <= 64);
+        // SAFETY: just checked.
+        unsafe { self.write_bits_unchecked(nbits, value) }
+    }
 
-        let mut nbits_remaining = nbits as u64;
+    /// Write up to 64 bits to the writer, as for `write_bits()`, without
+    /// checking `nbits`.  Everything else is checked as usual, including any
+    /// limit, and IO errors are returned as normal.  Bits of `value` above the
+    /// low `nbits` are ignored, as they are by `write_bits()`.
+    ///
+    /// # Safety
+    ///
+    /// `nbits` must be no more than 64.  The compiler is told so, and larger
+    /// values are undefined behaviour.
+    #[inline]
+    pub unsafe fn write_bits_unchecked(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        // SAFETY: guaranteed by the caller.
+        unsafe { core::hint::assert_unchecked(nbits <= 64) };
 
-        // can we fill up a partial byte?
-        if nbits_remaining >= self.unused && self.unused < 8 {
-            let excess_bits = nbits_remaining - self.unused;
-            self.buffer <<= self.unused;
-            self.buffer |= (value >> excess_bits) & MASKS[self.unused as usize];
+        if let Some(limit) = self.limit {
+            if self.position + nbits as u64 > limit {
+                return Err(BitError::new(
+                    io::ErrorKind::WriteZero,
+                    "write_bits",
+                    "bit limit reached",
+                )
+                .at(Some(self.position))
+                .into());
+            }
+        }
 
-            self.inner.write_all(&[self.buffer as u8])?;
+        self.bits.push(nbits, value);
+        self.position += nbits as u64;
 
-            nbits_remaining = excess_bits;
-            self.unused = 8;
-            self.buffer = 0;
+        let mut bytes = [0; 9];
+        let n = self.bits.drain_bytes(&mut bytes);
+        self.emit(&bytes[..n])?;
+
+        Ok(nbits as usize)
+    }
+
+    /// Write a record of `(nbits, value)` fields, as a sequence of
+    /// `write_bits()` calls would, passing the bytes on to the inner writer in
+    /// as few writes as possible.  Returns the total number of bits written.
+    ///
+    /// The whole record is checked before anything is written: a width of more
+    /// than 64 is an `InvalidInput` error carrying its index as its value, and
+    /// a record which would pass any limit is a `WriteZero` error.  As with
+    /// `write_bits()`, bits of each value above its width are ignored.
+    pub fn write_many(&mut self, fields: &[(u8, u64)]) -> io::Result<usize> {
+        let start = self.position;
+        let mut total = 0;
+        for (index, &(nbits, _)) in fields.iter().enumerate() {
+            if nbits > 64 {
+                return Err(BitError::new(
+                    io::ErrorKind::InvalidInput,
+                    "write_many",
+                    "invalid width",
+                )
+                .at(Some(start))
+                .with_value(index as u64)
+                .into());
+            }
+            total += nbits as u64;
+        }
+        if self.limit.is_some_and(|limit| start + total > limit) {
+            return Err(
+                BitError::new(io::ErrorKind::WriteZero, "write_many", "bit limit reached")
+                    .at(Some(start))
+                    .into(),
+            );
         }
 
-        // let's write while we can fill up full bytes
-        while nbits_remaining >= 8 {
-            nbits_remaining -= 8;
-            self.inner.write_all(&[(value >> nbits_remaining) as u8])?;
+        let wrap = |e| BitError::wrap(e, "write_many", Some(start));
+        let mut bytes = [0; 64];
+        let mut len = 0;
+        for &(nbits, value) in fields {
+            self.bits.push(nbits, value);
+            self.position += nbits as u64;
+            len += self.bits.drain_bytes(&mut bytes[len..]);
+            // Leave room for the next field's bytes.
+            if len > bytes.len() - 9 {
+                self.emit(&bytes[..len]).map_err(wrap)?;
+                len = 0;
+            }
         }
+        self.emit(&bytes[..len]).map_err(wrap)?;
+        Ok(total as usize)
+    }
 
-        // put the remaining bits in the buffer
-        if nbits_remaining > 0 {
-            self.buffer <<= nbits_remaining;
-            self.buffer |= value & MASKS[nbits_remaining as usize];
-            self.unused -= nbits_remaining;
+    /// Write whole bytes, as a `write_bits(8, _)` for each would, returning the
+    /// number of bits written.  Writing past any limit is a `WriteZero` error,
+    /// and nothing is written.
+    ///
+    /// When the writer is byte aligned, a slice at least as long as the
+    /// batching capacity bypasses the internal buffer: it's written straight
+    /// to the inner writer after any bytes already batched, rather than being
+    /// copied, with `write_vectored()` where there's `std`.  Otherwise the
+    /// bytes are merged with the pending bits or batched as usual.
+    pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let nbits = bytes.len() as u64 * 8;
+        if self
+            .limit
+            .is_some_and(|limit| self.position + nbits > limit)
+        {
+            return Err(BitError::new(
+                io::ErrorKind::WriteZero,
+                "write_bytes",
+                "bit limit reached",
+            )
+            .at(Some(self.position))
+            .into());
+        }
+        #[cfg(feature = "alloc")]
+        {
+            if self.capacity > 0 && self.bits.len() == 0 && bytes.len() >= self.capacity {
+                self.write_buffered_then(bytes)?;
+                self.position += nbits;
+                self.count_unflushed(bytes.len())?;
+                return Ok(nbits as usize);
+            }
         }
+        self.write_byte_slice(bytes)?;
         Ok(nbits as usize)
     }
 
-    /// Flush any pending writes to the underlying buffer, padding with zero bits
-    /// up to the nearest byte if necessary, and returning the number of padding
-    /// bits written.  The sum of `write_bits()` + `flush()` or `flush_bits()`
-    /// will be the total number of bits delivered to the writer, and will
-    /// always end on a byte boundary.
+    /// Write whole bytes, at any phase, without checking any limit.  When the
+    /// writer is byte aligned they're passed straight on, and otherwise they're
+    /// merged with the pending bits 8 at a time.
+    fn write_byte_slice(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let phase = self.bits.len();
+        if phase == 0 {
+            self.emit(bytes)?;
+        } else {
+            let mut pending = self.bits.pop(phase);
+            let mut out = [0; 512];
+            for chunk in bytes.chunks(512) {
+                let mut len = 0;
+                let mut words = chunk.chunks_exact(8);
+                for word in &mut words {
+                    let mut word_bytes = [0; 8];
+                    word_bytes.copy_from_slice(word);
+                    // The pending bits go first, and the last `phase` bits of
+                    // the word become the new pending bits.
+                    let merged = if O::MSB_FIRST {
+                        let word = u64::from_be_bytes(word_bytes);
+                        let merged = (pending << (64 - phase)) | (word >> phase);
+                        pending = word & ((1 << phase) - 1);
+                        merged.to_be_bytes()
+                    } else {
+                        let word = u64::from_le_bytes(word_bytes);
+                        let merged = pending | (word << phase);
+                        pending = word >> (64 - phase);
+                        merged.to_le_bytes()
+                    };
+                    out[len..len + 8].copy_from_slice(&merged);
+                    len += 8;
+                }
+                self.bits.push(phase, pending);
+                for &byte in words.remainder() {
+                    self.bits.push(8, byte as u64);
+                    len += self.bits.drain_bytes(&mut out[len..]);
+                }
+                pending = self.bits.pop(phase);
+                self.emit(&out[..len])?;
+            }
+            self.bits.push(phase, pending);
+        }
+        self.position += bytes.len() as u64 * 8;
+        Ok(())
+    }
+
+    /// Pass complete bytes on to the inner writer, via the batching buffer if
+    /// there is one.
+    #[inline]
+    fn emit(&mut self, bytes: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "alloc")]
+        {
+            if self.capacity > 0 {
+                self.buf.extend_from_slice(bytes);
+                if self.buf.len() >= self.capacity {
+                    self.write_buffered()?;
+                }
+                return self.count_unflushed(bytes.len());
+            }
+        }
+        self.inner.write_all(bytes)?;
+        self.count_unflushed(bytes.len())
+    }
+
+    /// Count bytes passed on, flushing once there are enough for any
+    /// auto-flush.
+    #[inline]
+    fn count_unflushed(&mut self, len: usize) -> io::Result<()> {
+        self.unflushed += len as u64;
+        if self.auto_flush > 0 && self.unflushed >= self.auto_flush {
+            self.frame_boundary()?;
+        }
+        Ok(())
+    }
+
+    /// Mark the end of a frame: pass every complete byte on to the inner
+    /// writer, including any batched in the internal buffer, and flush it.
+    ///
+    /// Unlike `flush()` nothing is padded, so the bits of a partial byte stay
+    /// pending, to be completed by the next write.  This is what auto-flushing
+    /// calls, as set by `BitWriterBuilder::auto_flush_every_bytes()`, and it
+    /// restarts its count.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_bits(12, 0xabc).unwrap();
+    /// writer.frame_boundary().unwrap();
+    /// assert_eq!(writer.get_ref(), &[0xab]);
+    /// writer.write_bits(4, 0xd).unwrap();
+    /// assert_eq!(writer.into_inner(), [0xab, 0xcd]);
+    /// ```
+    pub fn frame_boundary(&mut self) -> io::Result<()> {
+        self.write_buffered()?;
+        self.inner.flush()?;
+        self.unflushed = 0;
+        Ok(())
+    }
+
+    /// Write out the batching buffer.  Its contents are dropped even on error,
+    /// as they would have been without batching.
+    fn write_buffered(&mut self) -> io::Result<()> {
+        self.write_buffered_then(&[])
+    }
+
+    /// Write out the batching buffer followed by `bytes`, in one vectored
+    /// write where possible, so the two needn't be copied together first.
+    fn write_buffered_then(&mut self, bytes: &[u8]) -> io::Result<()> {
+        #[cfg(feature = "alloc")]
+        {
+            if !self.buf.is_empty() {
+                let result = write_all_pair(&mut self.inner, &self.buf, bytes);
+                self.buf.clear();
+                return result;
+            }
+        }
+        if bytes.is_empty() {
+            Ok(())
+        } else {
+            self.inner.write_all(bytes)
+        }
+    }
+
+    /// Flush any pending writes to the underlying buffer, padding with zero
+    /// bits (or one bits, if so configured) up to the nearest byte if
+    /// necessary, and returning the number of padding bits written.  The sum
+    /// of `write_bits()` + `flush()` or `flush_bits()` will be the total number
+    /// of bits delivered to the writer, and will always end on a byte
+    /// boundary.
     ///
     /// This method should **always** be called prior to calling `into_inner` or
     /// before allowing the `BitWriter` to go out of scope, or buffered bytes may
@@ -195,6 +1091,7 @@ impl<W: io::Write> BitWriter<W> {
     pub fn flush(&mut self) -> io::Result<usize> {
         let written = self.flush_bits()?;
         self.inner.flush()?;
+        self.unflushed = 0;
         Ok(written)
     }
 
@@ -204,15 +1101,37 @@ impl<W: io::Write> BitWriter<W> {
     /// This may be useful if you're going to call `into_inner()` to get at the
     /// wrapped writer in order to perform more bytewise writes, and don't care
     /// if it's all on stable storage just yet.
+    ///
+    /// Any bytes batched in the internal buffer are written out too.
     pub fn flush_bits(&mut self) -> io::Result<usize> {
-        if self.unused != 8 {
-            self.inner.write_all(&[(self.buffer << self.unused) as u8])?;
-            let written = self.unused;
-            self.unused = 8;
-            Ok(written as usize)
-        } else {
-            Ok(0)
+        let padding = self.bits.pad(self.pad_ones);
+        self.position += padding as u64;
+        if self.bits.len() > 0 {
+            let byte = self.bits.pop(8) as u8;
+            self.emit(&[byte])?;
         }
+        self.write_buffered()?;
+        Ok(padding as usize)
+    }
+
+    /// Drop the bits of the current partial byte, which haven't been passed on
+    /// to the inner writer yet, returning how many there were.  `position()`
+    /// moves back to the last byte boundary.
+    ///
+    /// Whole bytes can't be recalled from an arbitrary writer, even if they're
+    /// still batched in the internal buffer, as they've been counted as
+    /// written.  With a `Vec<u8>` use `truncate_to()` instead.
+    pub fn discard_buffered_bits(&mut self) -> u8 {
+        let discarded = self.bits.len();
+        self.bits.clear();
+        self.position -= discarded as u64;
+        discarded
+    }
+
+    /// The number of bits written so far, including any padding added by
+    /// `flush()`: the absolute bit offset of the next write.
+    pub fn position(&self) -> u64 {
+        self.position
     }
 
     /// Get a reference to the writer.
@@ -227,8 +1146,289 @@ impl<W: io::Write> BitWriter<W> {
     }
 
     /// Unwrap this `BitWriter`, returning the underlying writer and discarding any
-    /// unwritten buffered bits.  You should call `flush()` if this is undesirable.
+    /// unwritten buffered bits and batched bytes.  You should call `flush()` if
+    /// this is undesirable.
     pub fn into_inner(self) -> W {
         self.inner
     }
 }
+
+#[cfg(feature = "alloc")]
+impl<O: BitOrder> BitWriter<Vec<u8>, O> {
+    /// Rewind to the earlier bit position `position`, dropping everything
+    /// written since, including whole bytes already in the `Vec` or batched in
+    /// the internal buffer.  The bits before it in its byte are kept pending
+    /// to be written with the next write.
+    ///
+    /// A position after the current one, or before the start of the `Vec`, is
+    /// an `InvalidInput` error, and nothing is dropped.  Bytes written to the
+    /// `Vec` by other means since the last `flush()` will confuse this.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_bits(12, 0xabc).unwrap();
+    /// writer.write_bits(20, 0xfffff).unwrap();
+    /// writer.truncate_to(10).unwrap();
+    /// writer.write_bits(6, 0).unwrap();
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0xab, 0xc0]);
+    /// ```
+    pub fn truncate_to(&mut self, position: u64) -> io::Result<()> {
+        // The bit position of the end of the bytes passed on so far.
+        let complete = self.position - self.bits.len() as u64;
+        let dropped = (complete / 8).saturating_sub(position / 8) as usize;
+        let message = if position > self.position {
+            Some("position not yet written")
+        } else if dropped > self.inner.len() + self.buf.len() {
+            Some("position before the start of the Vec")
+        } else {
+            None
+        };
+        if let Some(message) = message {
+            return Err(
+                BitError::new(io::ErrorKind::InvalidInput, "truncate_to", message)
+                    .at(Some(self.position))
+                    .with_value(position)
+                    .into(),
+            );
+        }
+
+        if position >= complete {
+            let keep = (position - complete) as u8;
+            let kept = self.bits.pop(keep);
+            self.bits.clear();
+            self.bits.push(keep, kept);
+        } else {
+            self.write_buffered()?;
+            let at = self.inner.len() - dropped;
+            let phase = (position % 8) as u8;
+            let mut bits = Accumulator::<O>::new();
+            bits.push(8, self.inner[at] as u64);
+            self.bits.clear();
+            self.bits.push(phase, bits.pop(phase));
+            self.inner.truncate(at);
+        }
+        self.position = position;
+        Ok(())
+    }
+}
+
+/// Copy exactly `nbits` bits from `reader` to `writer`, returning the number of
+/// bits copied.
+///
+/// Both sides may start and end at any bit phase.  Once the reader reaches a
+/// byte boundary, whole bytes are moved straight from its inner reader to the
+/// writer, shifted into place if the writer is mid-byte, unless either side's
+/// limit would be reached first.  No padding is written, so `writer` will still
+/// need flushing as usual.
+pub fn copy_bits<R: io::Read, W: io::Write, O: BitOrder>(
+    reader: &mut BitReader<R, O>,
+    writer: &mut BitWriter<W, O>,
+    nbits: u64,
+) -> io::Result<u64> {
+    let mut remaining = nbits;
+    let unlimited = reader
+        .limit
+        .is_none_or(|limit| reader.position + nbits <= limit)
+        && writer
+            .limit
+            .is_none_or(|limit| writer.position + nbits <= limit);
+    if unlimited && nbits >= 8 {
+        let head = cmp::min(reader.bits.len() as u64, remaining) as u8;
+        copy_buffered(reader, writer, head)?;
+        remaining -= head as u64;
+        if reader.bits.len() == 0 {
+            remaining -= copy_bytes(reader, writer, Some(remaining / 8))? * 8;
+        }
+    }
+
+    // Whatever's left, or everything if the fast path wasn't possible.  If the
+    // reader ran out this reports it.
+    while remaining > 0 {
+        let chunk = cmp::min(remaining, 32) as u8;
+        let bits = reader.read_bits(chunk)?;
+        writer.write_bits(chunk, bits)?;
+        remaining -= chunk as u64;
+    }
+    Ok(nbits)
+}
+
+/// Copy everything remaining in `reader` to `writer`, returning the number of
+/// bits copied.
+///
+/// Underlying readers only ever deliver whole bytes, so the amount copied is
+/// well defined: any bits still buffered in the `BitReader` from a previous
+/// partial read, followed by 8 bits for every byte the inner reader yields
+/// until it reports end of file, or up to the reader's limit if it has one.
+/// Nothing is dropped from the tail and no padding is invented; `writer` is
+/// not flushed, so call `flush()` afterwards as usual.
+///
+/// If the writer's limit is reached first, that's its `WriteZero` error, with
+/// everything up to the limit copied.  Nothing past it is consumed from
+/// `reader`, so the rest can still be read from there.
+pub fn copy_all_bits<R: io::Read, W: io::Write, O: BitOrder>(
+    reader: &mut BitReader<R, O>,
+    writer: &mut BitWriter<W, O>,
+) -> io::Result<u64> {
+    // The bits left before the reader's limit, and the room before the
+    // writer's, if they have them.  Nothing is read beyond either.
+    let left = reader
+        .limit
+        .map(|limit| limit.saturating_sub(reader.position));
+    let room = writer
+        .limit
+        .map(|limit| limit.saturating_sub(writer.position));
+    let bound = match (left, room) {
+        (Some(left), Some(room)) => Some(cmp::min(left, room)),
+        (left, room) => left.or(room),
+    };
+    let pending = bound.map_or(reader.bits.len(), |bound| {
+        cmp::min(bound, reader.bits.len() as u64) as u8
+    });
+    copy_buffered(reader, writer, pending)?;
+    let mut copied = pending as u64;
+
+    if reader.bits.len() == 0 {
+        let max = bound.map(|bound| (bound - copied) / 8);
+        let bytes = if writer.limit.is_none() {
+            copy_bytes(reader, writer, max)?
+        } else {
+            let mut buf = [0u8; 512];
+            let mut bytes = 0;
+            loop {
+                let want = match max {
+                    Some(max) => cmp::min(max - bytes, buf.len() as u64) as usize,
+                    None => buf.len(),
+                };
+                if want == 0 {
+                    break;
+                }
+                let n = match reader.inner.read(&mut buf[..want]) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+
+                reader.position += n as u64 * 8;
+                for &byte in &buf[..n] {
+                    writer.write_bits(8, byte as u64)?;
+                }
+                bytes += n as u64;
+            }
+            bytes
+        };
+        copied += bytes * 8;
+
+        // A limit part way through a byte leaves the start of that byte, if
+        // the stream didn't end first.
+        if let (Some(bound), Some(max)) = (bound, max) {
+            let tail = (bound - copied) as u8;
+            if bytes == max && tail > 0 {
+                match reader.read_bits(tail) {
+                    Ok(bits) => {
+                        writer.write_bits(tail, bits)?;
+                        copied += tail as u64;
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+
+    // Stopped at the writer's limit rather than the reader's: if there's more
+    // to read, report the writer's error for it, leaving it in the reader.
+    if room.is_some_and(|room| copied == room && left.is_none_or(|left| left > room)) {
+        match reader.read_bits(1) {
+            Ok(bit) => {
+                reader.unread_bits(1, bit)?;
+                writer.write_bits(1, bit)?;
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(copied)
+}
+
+/// Copy the first `nbits` of the bits buffered in `reader`, which may be more
+/// than 64 after `unread_bits()`, to `writer`.
+fn copy_buffered<R: io::Read, W: io::Write, O: BitOrder>(
+    reader: &mut BitReader<R, O>,
+    writer: &mut BitWriter<W, O>,
+    nbits: u8,
+) -> io::Result<()> {
+    let mut left = nbits;
+    while left > 0 {
+        let chunk = cmp::min(left, 64);
+        let bits = reader.read_bits(chunk)?;
+        writer.write_bits(chunk, bits)?;
+        left -= chunk;
+    }
+    Ok(())
+}
+
+/// Write all of `head` then all of `tail`, with vectored writes where there's
+/// `std`.  `Write::is_write_vectored()` isn't stable, but writers without
+/// vectored writes default to writing the first slice alone, which is what
+/// writing them one at a time would do anyway.
+#[cfg(feature = "alloc")]
+fn write_all_pair<W: io::Write>(inner: &mut W, head: &[u8], tail: &[u8]) -> io::Result<()> {
+    #[cfg(feature = "std")]
+    {
+        if !tail.is_empty() {
+            let mut slices = [io::IoSlice::new(head), io::IoSlice::new(tail)];
+            let mut slices = &mut slices[..];
+            while !slices.is_empty() {
+                match inner.write_vectored(slices) {
+                    Ok(0) => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    Ok(n) => io::IoSlice::advance_slices(&mut slices, n),
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e),
+                }
+            }
+            return Ok(());
+        }
+    }
+    inner.write_all(head)?;
+    inner.write_all(tail)
+}
+
+/// Move whole bytes from the inner reader of a byte aligned `reader` to
+/// `writer`, up to `max` of them or the end of the stream, returning how many
+/// were copied.  Limits aren't checked.
+fn copy_bytes<R: io::Read, W: io::Write, O: BitOrder>(
+    reader: &mut BitReader<R, O>,
+    writer: &mut BitWriter<W, O>,
+    max: Option<u64>,
+) -> io::Result<u64> {
+    debug_assert_eq!(reader.bits.len(), 0);
+    let mut buf = [0u8; 1024];
+    let mut copied = 0;
+    loop {
+        let want = match max {
+            Some(max) => cmp::min(max - copied, buf.len() as u64) as usize,
+            None => buf.len(),
+        };
+        if want == 0 {
+            return Ok(copied);
+        }
+        let n = match reader.inner.read(&mut buf[..want]) {
+            Ok(0) => return Ok(copied),
+            Ok(n) => n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        reader.position += n as u64 * 8;
+        writer.write_byte_slice(&buf[..n])?;
+        copied += n as u64;
+    }
+}
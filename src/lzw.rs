@@ -0,0 +1,260 @@
+//! Streams of LZW codes whose width grows as the dictionary fills, as in GIF
+//! and TIFF images.
+//!
+//! GIF packs codes `Lsb0` and TIFF `Msb0`, so the packing is the order of the
+//! wrapped reader or writer.  A width change takes effect from the next code,
+//! so the caller applies its format's rule between codes:
+//!
+//! * GIF widens once the next code to be assigned is `1 << width`, up to 12
+//!   bits.  A decoder assigns that code after reading the code which follows
+//!   the one it's widening after, so it widens a code later than the encoder.
+//! * TIFF widens one code earlier than GIF, at `(1 << width) - 1`, from 9 to
+//!   12 bits.
+//!
+//! Both go back to the initial width after a clear code, with
+//! `reset_width()`.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::{BitReader, BitWriter};
+
+/// The initial and maximum widths of a code stream, and the current one.
+#[derive(Debug, Clone, Copy)]
+struct Widths {
+    initial: u8,
+    max: u8,
+    width: u8,
+}
+
+impl Widths {
+    fn new(initial: u8, max: u8) -> Self {
+        assert!(
+            1 <= initial && initial <= max && max <= 16,
+            "code widths must be from 1 to 16 bits, initial no more than max"
+        );
+        Self {
+            initial,
+            max,
+            width: initial,
+        }
+    }
+
+    fn set(&mut self, width: u8) {
+        assert!(
+            self.initial <= width && width <= self.max,
+            "code width must be from the initial to the maximum width"
+        );
+        self.width = width;
+    }
+
+    fn bump(&mut self) -> bool {
+        if self.width == self.max {
+            return false;
+        }
+        self.width += 1;
+        true
+    }
+}
+
+/// Reads LZW codes of a width set by the caller as the dictionary grows.
+///
+/// ```
+/// use bitrw::{BitReader, CodeStreamReader, Lsb0};
+///
+/// // A clear code and a pixel then end of information, in GIF's 3 bit codes.
+/// let data = [0b01_001_100, 0b0000_0001];
+/// let mut reader = CodeStreamReader::new(BitReader::with_order(&data[..], Lsb0), 3, 12);
+/// assert_eq!(reader.read_code().unwrap(), 4);
+/// assert_eq!(reader.read_code().unwrap(), 1);
+/// assert_eq!(reader.read_code().unwrap(), 5);
+/// ```
+pub struct CodeStreamReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    widths: Widths,
+}
+
+impl<R, O: BitOrder> fmt::Debug for CodeStreamReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodeStreamReader")
+            .field("inner", &self.inner)
+            .field("widths", &self.widths)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> CodeStreamReader<R, O> {
+    /// Wrap `inner`, reading codes of `initial_width` bits until told to widen
+    /// them, up to `max_width`.
+    ///
+    /// # Panics
+    ///
+    /// Unless `1 <= initial_width <= max_width <= 16`.
+    pub fn new(inner: BitReader<R, O>, initial_width: u8, max_width: u8) -> Self {
+        Self {
+            inner,
+            widths: Widths::new(initial_width, max_width),
+        }
+    }
+
+    /// Read the next code at the current width.
+    pub fn read_code(&mut self) -> io::Result<u16> {
+        let start = Some(self.inner.position());
+        self.inner
+            .read_bits(self.widths.width)
+            .map(|code| code as u16)
+            .map_err(|e| Error::wrap(e, "read_code", start))
+    }
+
+    /// The width of the next code.
+    pub fn width(&self) -> u8 {
+        self.widths.width
+    }
+
+    /// Set the width of the codes from the next one on.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is less than the initial width or more than the maximum.
+    pub fn set_width(&mut self, width: u8) {
+        self.widths.set(width);
+    }
+
+    /// Widen the codes from the next one on by a bit, returning false without
+    /// changing anything if they're already at the maximum width, as when a
+    /// GIF's dictionary is full but no clear code has come yet.
+    pub fn bump_width(&mut self) -> bool {
+        self.widths.bump()
+    }
+
+    /// Go back to the initial width from the next code on, as after a clear
+    /// code.
+    pub fn reset_width(&mut self) {
+        self.widths.width = self.widths.initial;
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `CodeStreamReader`, returning the inner reader.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+/// Writes LZW codes of a width set by the caller as the dictionary grows.
+///
+/// ```
+/// use bitrw::{BitWriter, CodeStreamWriter, Lsb0};
+///
+/// let mut writer = CodeStreamWriter::new(BitWriter::with_order(Vec::new(), Lsb0), 3, 12);
+/// for &code in &[4, 1, 5] {
+///     writer.write_code(code).unwrap();
+/// }
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0b01_001_100, 0b0000_0001]);
+/// ```
+pub struct CodeStreamWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    widths: Widths,
+}
+
+impl<W, O: BitOrder> fmt::Debug for CodeStreamWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CodeStreamWriter")
+            .field("inner", &self.inner)
+            .field("widths", &self.widths)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> CodeStreamWriter<W, O> {
+    /// Wrap `inner`, writing codes of `initial_width` bits until told to widen
+    /// them, up to `max_width`.
+    ///
+    /// # Panics
+    ///
+    /// Unless `1 <= initial_width <= max_width <= 16`.
+    pub fn new(inner: BitWriter<W, O>, initial_width: u8, max_width: u8) -> Self {
+        Self {
+            inner,
+            widths: Widths::new(initial_width, max_width),
+        }
+    }
+
+    /// Write `code` at the current width.  A code too wide for it is an
+    /// `InvalidInput` error with the code as its value, and nothing is
+    /// written.
+    pub fn write_code(&mut self, code: u16) -> io::Result<()> {
+        let start = Some(self.inner.position());
+        let width = self.widths.width;
+        if code >> width != 0 {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_code", "code too wide")
+                    .at(start)
+                    .with_value(code as u64)
+                    .into(),
+            );
+        }
+        self.inner
+            .write_bits(width, code as u64)
+            .map(|_| ())
+            .map_err(|e| Error::wrap(e, "write_code", start))
+    }
+
+    /// The width of the next code.
+    pub fn width(&self) -> u8 {
+        self.widths.width
+    }
+
+    /// Set the width of the codes from the next one on.
+    ///
+    /// # Panics
+    ///
+    /// If `width` is less than the initial width or more than the maximum.
+    pub fn set_width(&mut self, width: u8) {
+        self.widths.set(width);
+    }
+
+    /// Widen the codes from the next one on by a bit, returning false without
+    /// changing anything if they're already at the maximum width.
+    pub fn bump_width(&mut self) -> bool {
+        self.widths.bump()
+    }
+
+    /// Go back to the initial width from the next code on, as after a clear
+    /// code.
+    pub fn reset_width(&mut self) {
+        self.widths.width = self.widths.initial;
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `CodeStreamWriter`, returning the inner writer.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
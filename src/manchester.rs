@@ -0,0 +1,247 @@
+//! Manchester line coding, in which each bit is sent as a pair of opposite
+//! symbols, so every bit has a transition in the middle.
+//!
+//! Which way round the pair goes is a matter of convention: IEEE 802.3 sends
+//! a one as `01`, low then high, where G. E. Thomas's original convention
+//! sends it as `10`.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// Which symbol pair stands for a one bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Manchester {
+    /// A one is `01` and a zero `10`, as in IEEE 802.3.
+    Ieee,
+    /// A one is `10` and a zero `01`, as in G. E. Thomas's convention.
+    Thomas,
+}
+
+impl Manchester {
+    /// The first symbol of the pair for `bit`.  The second is its complement.
+    fn first_symbol(self, bit: u8) -> u8 {
+        match self {
+            Manchester::Ieee => bit ^ 1,
+            Manchester::Thomas => bit,
+        }
+    }
+}
+
+/// Reads Manchester coded bits, decoding each pair of symbols from the inner
+/// reader into one bit.
+///
+/// A pair of identical symbols is an `InvalidData` error at the offset of the
+/// pair in the inner reader, with the pair as its value in stream order.  The
+/// pair is left unread, so a receiver which has lost sync can skip a symbol
+/// with `get_mut()` and carry on.
+///
+/// ```
+/// use bitrw::{BitRead, BitReader, Manchester, ManchesterReader};
+///
+/// let raw = BitReader::new(&[0b01_10_01_01][..]);
+/// let mut reader = ManchesterReader::new(raw, Manchester::Ieee);
+/// assert_eq!(reader.read_bits(4).unwrap(), 0b1011);
+/// ```
+pub struct ManchesterReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    convention: Manchester,
+    // Bits decoded for a read which failed, to be returned first next time.
+    held: Accumulator<O>,
+    position: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for ManchesterReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManchesterReader")
+            .field("inner", &self.inner)
+            .field("convention", &self.convention)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> ManchesterReader<R, O> {
+    /// Wrap `inner`, which should be at the start of a symbol pair.
+    pub fn new(inner: BitReader<R, O>, convention: Manchester) -> Self {
+        Self {
+            inner,
+            convention,
+            held: Accumulator::new(),
+            position: 0,
+        }
+    }
+
+    /// Read up to 64 decoded bits, from twice as many symbols.
+    ///
+    /// If the read fails, any bits it decoded are kept to be returned by the
+    /// next read.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        while self.held.len() < nbits {
+            let bit = self.decode_pair()?;
+            self.held.push(1, bit as u64);
+        }
+        self.position += nbits as u64;
+        Ok(self.held.pop(nbits))
+    }
+
+    fn decode_pair(&mut self) -> io::Result<u8> {
+        let start = self.inner.position();
+        let pair = self.inner.read_bits(2)?;
+        let (first, second) = if O::MSB_FIRST {
+            (pair >> 1, pair & 1)
+        } else {
+            (pair & 1, pair >> 1)
+        };
+        if first == second {
+            self.inner.unread_bits(2, pair)?;
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "manchester",
+                "invalid symbol pair",
+            )
+            .at(Some(start))
+            .with_value(first << 1 | second)
+            .into());
+        }
+        Ok(self.convention.first_symbol(first as u8))
+    }
+
+    /// The number of decoded bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner reader, whose position counts symbols.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ManchesterReader`, returning the inner reader.  Any bits
+    /// held from a failed read are lost.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for ManchesterReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        ManchesterReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Writes Manchester coded bits, encoding each bit as a pair of symbols on the
+/// inner writer.
+///
+/// ```
+/// use bitrw::{BitWrite, BitWriter, Manchester, ManchesterWriter};
+///
+/// let mut writer = ManchesterWriter::new(BitWriter::new(Vec::new()), Manchester::Ieee);
+/// writer.write_bits(4, 0b1011).unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0b01_10_01_01]);
+/// ```
+pub struct ManchesterWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    convention: Manchester,
+    position: u64,
+}
+
+impl<W, O: BitOrder> fmt::Debug for ManchesterWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ManchesterWriter")
+            .field("inner", &self.inner)
+            .field("convention", &self.convention)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> ManchesterWriter<W, O> {
+    /// Wrap `inner`.
+    pub fn new(inner: BitWriter<W, O>, convention: Manchester) -> Self {
+        Self {
+            inner,
+            convention,
+            position: 0,
+        }
+    }
+
+    /// Write up to 64 bits, as twice as many symbols.
+    ///
+    /// The symbols are written 64 at a time, so if the inner writer fails
+    /// part way some of the bits may have been written.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<O>::new();
+        bits.push(nbits, value);
+        let mut symbols = Accumulator::<O>::new();
+        while bits.len() > 0 {
+            let first = self.convention.first_symbol(bits.pop(1) as u8);
+            symbols.push(1, first as u64);
+            symbols.push(1, first as u64 ^ 1);
+            if symbols.len() == 64 {
+                self.inner.write_bits(64, symbols.pop(64))?;
+            }
+        }
+        let len = symbols.len();
+        self.inner.write_bits(len, symbols.pop(len))?;
+        self.position += nbits as u64;
+        Ok(nbits as usize)
+    }
+
+    /// The number of bits written so far, each as two symbols.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner writer, whose position counts symbols.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer, for writing symbols
+    /// directly, such as a code violation to mark the end of a frame.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ManchesterWriter`, returning the inner writer, which will
+    /// still need flushing if it's part way through a byte.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for ManchesterWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        ManchesterWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
@@ -0,0 +1,39 @@
+//! Reading memory-mapped files.
+//!
+//! A `BitReader` over a `Cursor` of the mapping serves every refill straight
+//! from memory, and `seek()` to any bit offset is a constant-time pointer
+//! adjustment, which suits large bit-packed indexes read at random.
+
+use std::fs::File;
+use std::io::{self, Cursor};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::order::{BitOrder, Msb0};
+use crate::BitReader;
+
+/// A `BitReader` over a memory-mapped file, from `BitReader::from_path()`.
+pub type MmapBitReader<O = Msb0> = BitReader<Cursor<Mmap>, O>;
+
+impl BitReader<Cursor<Mmap>> {
+    /// Map the file at `path` into memory and read from it.
+    ///
+    /// The file must not be modified by this or any other process while it's
+    /// mapped, or reads may return inconsistent data.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::from_path_with_order(path, Msb0)
+    }
+}
+
+impl<O: BitOrder> BitReader<Cursor<Mmap>, O> {
+    /// Map the file at `path` into memory and read from it, unpacking bits in
+    /// the given order.  The same caveat applies as for `from_path()`.
+    pub fn from_path_with_order<P: AsRef<Path>>(path: P, order: O) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the mapping is only read, and the caller is responsible for
+        // the file remaining unchanged, as documented above.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(Self::with_order(Cursor::new(map), order))
+    }
+}
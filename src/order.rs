@@ -0,0 +1,96 @@
+//! Bit orderings, selecting how bits are packed into each byte.
+//!
+//! `BitReader` and `BitWriter` take the ordering as a type parameter, so the
+//! choice is made at compile time and costs nothing at runtime.  Either way a
+//! stream is a sequence of bits; the ordering decides which end of each byte
+//! that sequence starts from, and which end of a multi-bit value comes first.
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A way of packing bits into bytes.  Implemented by `Msb0` and `Lsb0`.
+///
+/// The methods are the primitive operations on the queue of bits buffered by
+/// the readers and writers, and are not generally useful on their own.
+pub trait BitOrder: private::Sealed + Copy + Default {
+    /// Whether multi-bit values are stored starting with their most
+    /// significant bit.
+    const MSB_FIRST: bool;
+
+    /// Append the low `n` bits of `value` to the `len` bits queued in `acc`.
+    /// `n <= 64`, `len + n < 128`.
+    fn push(acc: u128, len: u8, n: u8, value: u64) -> u128;
+
+    /// Remove the first `n` of the `len` bits queued in `acc`, returning them
+    /// and what remains of the queue.  `n <= 64`, `n <= len < 128`.
+    fn pop(acc: u128, len: u8, n: u8) -> (u64, u128);
+
+    /// Convert an `n` bit value as read or written in this order to the value
+    /// the same bits would represent in `Msb0` order, or back again.
+    /// `1 <= n <= 64`.
+    fn to_msb0(value: u64, n: u8) -> u64;
+}
+
+#[inline(always)]
+fn mask(n: u8) -> u128 {
+    (1 << n) - 1
+}
+
+/// Most-significant bit first, the default.  The first bit of the stream is the
+/// high bit of the first byte, and multi-bit values are stored starting with
+/// their most significant bit, so `write_bits(3, 0b110)` then
+/// `write_bits(5, 0b00001)` produces the byte `0b1100_0001`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Msb0;
+
+impl private::Sealed for Msb0 {}
+
+impl BitOrder for Msb0 {
+    const MSB_FIRST: bool = true;
+
+    #[inline(always)]
+    fn push(acc: u128, _len: u8, n: u8, value: u64) -> u128 {
+        (acc << n) | (value as u128 & mask(n))
+    }
+
+    #[inline(always)]
+    fn pop(acc: u128, len: u8, n: u8) -> (u64, u128) {
+        let rest = len - n;
+        (((acc >> rest) & mask(n)) as u64, acc & mask(rest))
+    }
+
+    #[inline(always)]
+    fn to_msb0(value: u64, _n: u8) -> u64 {
+        value
+    }
+}
+
+/// Least-significant bit first, as used by DEFLATE, GIF and many audio codecs.
+/// The first bit of the stream is the low bit of the first byte, and multi-bit
+/// values are stored starting with their least significant bit, so
+/// `write_bits(3, 0b110)` then `write_bits(5, 0b00001)` produces the byte
+/// `0b0000_1110`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Lsb0;
+
+impl private::Sealed for Lsb0 {}
+
+impl BitOrder for Lsb0 {
+    const MSB_FIRST: bool = false;
+
+    #[inline(always)]
+    fn push(acc: u128, len: u8, n: u8, value: u64) -> u128 {
+        acc | ((value as u128 & mask(n)) << len)
+    }
+
+    #[inline(always)]
+    fn pop(acc: u128, _len: u8, n: u8) -> (u64, u128) {
+        ((acc & mask(n)) as u64, acc >> n)
+    }
+
+    #[inline(always)]
+    fn to_msb0(value: u64, n: u8) -> u64 {
+        value.reverse_bits() >> (64 - n)
+    }
+}
@@ -0,0 +1,393 @@
+//! Parity bits, inserted after every block of data bits, as on a UART.
+
+use core::cmp;
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// Whether a block and its parity bit have an even or an odd number of ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Parity {
+    /// The parity bit is one if the block has an odd number of ones.
+    Even,
+    /// The parity bit is one if the block has an even number of ones.
+    Odd,
+}
+
+/// The parity of a block seen so far.
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    parity: Parity,
+    size: u32,
+    len: u32,
+    ones: u8,
+    index: u64,
+}
+
+impl Block {
+    fn new(parity: Parity, size: u32) -> Self {
+        assert!(size > 0, "parity block size must be at least one bit");
+        Self {
+            parity,
+            size,
+            len: 0,
+            ones: 0,
+            index: 0,
+        }
+    }
+
+    /// How many bits of `want` still fit in the block.
+    fn room(&self, want: u8) -> u8 {
+        cmp::min(want as u32, self.size - self.len) as u8
+    }
+
+    fn push(&mut self, nbits: u8, value: u64) {
+        self.len += nbits as u32;
+        self.ones ^= (value.count_ones() & 1) as u8;
+    }
+
+    fn is_full(&self) -> bool {
+        self.len == self.size
+    }
+
+    /// The parity bit for the bits so far.
+    fn parity_bit(&self) -> u8 {
+        match self.parity {
+            Parity::Even => self.ones,
+            Parity::Odd => self.ones ^ 1,
+        }
+    }
+
+    fn next(&mut self) {
+        self.len = 0;
+        self.ones = 0;
+        self.index += 1;
+    }
+}
+
+/// Reads data bits followed by parity bits, checking and removing the parity
+/// bit after every block.
+///
+/// A block's parity bit is checked as soon as its last data bit is read.  If
+/// it doesn't match, the read fails with an `InvalidData` error at the offset
+/// of the parity bit, with the index of the block, counting from zero, as its
+/// value.  The data bits are kept to be returned by the next read, so the
+/// caller can choose whether to carry on.
+///
+/// If the inner reader runs out part way through a block, including before
+/// its parity bit, the read fails with an `UnexpectedEof` error with the
+/// number of data bits read from the block as its value.  Those bits can still
+/// be read, unchecked.
+///
+/// ```
+/// use bitrw::{BitReader, Parity, ParityBitReader};
+///
+/// // Two bytes, each followed by even parity.
+/// let data = [0b1010_0000, 0b0111_1111, 0b1000_0000];
+/// let mut reader = ParityBitReader::new(BitReader::new(&data[..]), Parity::Even, 8);
+/// assert_eq!(reader.read_bits(4).unwrap(), 0b1010);
+/// assert_eq!(reader.read_bits(12).unwrap(), 0x0ff);
+/// ```
+pub struct ParityBitReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    block: Block,
+    // Data bits for a read which failed, to be returned first next time.
+    held: Accumulator<O>,
+    position: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for ParityBitReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParityBitReader")
+            .field("inner", &self.inner)
+            .field("block", &self.block)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> ParityBitReader<R, O> {
+    /// Wrap `inner`, with a parity bit expected after every `block_size` data
+    /// bits.
+    ///
+    /// # Panics
+    ///
+    /// If `block_size` is zero.
+    pub fn new(inner: BitReader<R, O>, parity: Parity, block_size: u32) -> Self {
+        Self {
+            inner,
+            block: Block::new(parity, block_size),
+            held: Accumulator::new(),
+            position: 0,
+        }
+    }
+
+    /// Read up to 64 data bits, checking the parity of any blocks they
+    /// complete.
+    ///
+    /// If the read fails, any data bits it read are kept to be returned by the
+    /// next read.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        loop {
+            if self.block.is_full() {
+                self.check_parity()?;
+            }
+            if self.held.len() >= nbits {
+                break;
+            }
+            let want = self.block.room(nbits - self.held.len());
+            let value = match self.inner.read_bits(want) {
+                Ok(value) => value,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    return Err(self.truncated(want, e));
+                }
+                Err(e) => return Err(e),
+            };
+            self.block.push(want, value);
+            self.held.push(want, value);
+        }
+        self.position += nbits as u64;
+        Ok(self.held.pop(nbits))
+    }
+
+    fn check_parity(&mut self) -> io::Result<()> {
+        let start = Some(self.inner.position());
+        let bit = match self.inner.read_bit() {
+            Ok(bit) => bit,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                return Err(self.truncated(1, e));
+            }
+            Err(e) => return Err(e),
+        };
+        let expected = self.block.parity_bit();
+        let index = self.block.index;
+        self.block.next();
+        if bit != expected {
+            return Err(
+                Error::new(io::ErrorKind::InvalidData, "parity", "parity mismatch")
+                    .at(start)
+                    .with_value(index)
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Turn the inner reader running out while reading `want` bits into an
+    /// error naming the bits of the block read so far, if there are any.
+    /// Whatever bits it had left are moved into `held`, there being fewer than
+    /// `want`.
+    fn truncated(&mut self, want: u8, eof: io::Error) -> io::Error {
+        if !self.block.is_full() {
+            for _ in 1..want {
+                match self.inner.read_bit() {
+                    Ok(bit) => {
+                        self.block.push(1, bit as u64);
+                        self.held.push(1, bit as u64);
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                    Err(e) => return e,
+                }
+            }
+        }
+        if self.block.len == 0 {
+            return eof;
+        }
+        Error::new(io::ErrorKind::UnexpectedEof, "parity", "block truncated")
+            .at(Some(self.inner.position()))
+            .with_value(self.block.len as u64)
+            .into()
+    }
+
+    /// Check the parity of a short block, if the data bits read so far don't
+    /// end on a block boundary, returning whether there was one.  This should
+    /// be called at the end of a stream written with
+    /// `ParityBitWriter::end_block()`.
+    pub fn end_block(&mut self) -> io::Result<bool> {
+        if self.block.len == 0 {
+            return Ok(false);
+        }
+        self.check_parity()?;
+        Ok(true)
+    }
+
+    /// The number of data bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of blocks whose parity has been checked.
+    pub fn blocks(&self) -> u64 {
+        self.block.index
+    }
+
+    /// Get a reference to the inner reader, whose position counts parity
+    /// bits.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Reading from it directly
+    /// doesn't count towards a block.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ParityBitReader`, returning the inner reader.  Any bits
+    /// held from a failed read are lost.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for ParityBitReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        ParityBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Writes data bits, inserting a parity bit after every block.
+///
+/// ```
+/// use bitrw::{BitWriter, Parity, ParityBitWriter};
+///
+/// let mut writer = ParityBitWriter::new(BitWriter::new(Vec::new()), Parity::Even, 8);
+/// writer.write_bits(4, 0b1010).unwrap();
+/// writer.write_bits(12, 0x0ff).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0b1010_0000, 0b0111_1111, 0b1000_0000]);
+/// ```
+pub struct ParityBitWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    block: Block,
+    position: u64,
+}
+
+impl<W, O: BitOrder> fmt::Debug for ParityBitWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ParityBitWriter")
+            .field("inner", &self.inner)
+            .field("block", &self.block)
+            .field("position", &self.position)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> ParityBitWriter<W, O> {
+    /// Wrap `inner`, writing a parity bit after every `block_size` data bits.
+    ///
+    /// # Panics
+    ///
+    /// If `block_size` is zero.
+    pub fn new(inner: BitWriter<W, O>, parity: Parity, block_size: u32) -> Self {
+        Self {
+            inner,
+            block: Block::new(parity, block_size),
+            position: 0,
+        }
+    }
+
+    /// Write up to 64 data bits, with the parity bits of any blocks they
+    /// complete.
+    ///
+    /// If the inner writer fails part way, the bits before the failure have
+    /// been written and counted by `position()`.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<O>::new();
+        bits.push(nbits, value);
+        loop {
+            if self.block.is_full() {
+                self.write_parity()?;
+            }
+            if bits.len() == 0 {
+                break;
+            }
+            let chunk = self.block.room(bits.len());
+            let value = bits.pop(chunk);
+            self.inner.write_bits(chunk, value)?;
+            self.block.push(chunk, value);
+            self.position += chunk as u64;
+        }
+        Ok(nbits as usize)
+    }
+
+    fn write_parity(&mut self) -> io::Result<()> {
+        self.inner.write_bit(self.block.parity_bit())?;
+        self.block.next();
+        Ok(())
+    }
+
+    /// Write the parity bit of a short block, if the data bits written so far
+    /// don't end on a block boundary, returning whether there was one.
+    pub fn end_block(&mut self) -> io::Result<bool> {
+        if self.block.len == 0 {
+            return Ok(false);
+        }
+        self.write_parity()?;
+        Ok(true)
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.  The padding isn't covered by parity, and a short
+    /// block isn't ended: see `end_block()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// The number of data bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The number of blocks whose parity bit has been written.
+    pub fn blocks(&self) -> u64 {
+        self.block.index
+    }
+
+    /// Get a reference to the inner writer, whose position counts parity
+    /// bits.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Writing to it directly
+    /// doesn't count towards a block.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ParityBitWriter`, returning the inner writer.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for ParityBitWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        ParityBitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
@@ -0,0 +1,318 @@
+//! Taking a reader or writer apart into its inner stream and the rest of its
+//! state, and putting it back together.
+//!
+//! Unlike `into_inner()`, nothing is lost: the parts hold every bit buffered,
+//! the position and the settings from the builder, so `from_parts()` gives a
+//! reader or writer which carries on exactly as the original would have, over
+//! the same inner stream or anything positioned the same.
+//!
+//! This differs from `save_state()` and `resume()` in that the inner stream
+//! isn't seeked: the parts describe the state of a reader or writer relative
+//! to wherever its inner stream has got to.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+/// The state of a `BitReader` apart from its inner reader, from
+/// `BitReader::into_parts()`.
+///
+/// The pending bits are those loaded from the inner reader but not yet read,
+/// up to 127 of them, packed as `read_bits()` would return them in the
+/// reader's `BitOrder` if it could return that many: for `Msb0` the first bit
+/// is the most significant, and for `Lsb0` the least.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ReaderParts {
+    position: u64,
+    pending: u128,
+    pending_len: u8,
+    limit: Option<u64>,
+    vlc_limit: Option<u32>,
+}
+
+impl ReaderParts {
+    /// Parts for a reader at `position`, with the low `pending_len` bits of
+    /// `pending` loaded but not yet read, no limit, and the default limit on
+    /// the length of variable-length codes.
+    ///
+    /// More than 127 pending bits, or bits of `pending` above `pending_len`,
+    /// are an `InvalidInput` error.
+    pub fn new(position: u64, pending_len: u8, pending: u128) -> io::Result<Self> {
+        if pending_len > 127 || pending >> pending_len != 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "from_parts",
+                "invalid pending bits",
+            )
+            .at(Some(position))
+            .with_value(pending_len as u64)
+            .into());
+        }
+        Ok(Self {
+            position,
+            pending,
+            pending_len,
+            limit: None,
+            vlc_limit: Some(crate::vlc::DEFAULT_VLC_LIMIT),
+        })
+    }
+
+    /// Set the limit, as by `BitReaderBuilder::limit_bits()`, or remove it.
+    pub fn with_limit(self, limit: Option<u64>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// Set the limit on the length of variable-length codes, as by
+    /// `BitReader::set_vlc_limit()`, or remove it.
+    ///
+    /// # Panics
+    ///
+    /// If the limit is zero.
+    pub fn with_vlc_limit(self, vlc_limit: Option<u32>) -> Self {
+        assert!(vlc_limit != Some(0), "a code takes at least one bit");
+        Self { vlc_limit, ..self }
+    }
+
+    /// The bit position of the next read.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The bits loaded but not yet read, as `(count, value)`.
+    pub fn pending(&self) -> (u8, u128) {
+        (self.pending_len, self.pending)
+    }
+
+    /// The limit, if there is one.
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// The limit on the length of variable-length codes, if there is one.
+    pub fn vlc_limit(&self) -> Option<u32> {
+        self.vlc_limit
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Take this reader apart into its inner reader and the rest of its
+    /// state, for `from_parts()`.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// let (inner, parts) = reader.into_parts();
+    /// assert_eq!(parts.pending(), (4, 0xb));
+    ///
+    /// let mut reader = BitReader::<_>::from_parts(inner, parts);
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0xbcd);
+    /// ```
+    pub fn into_parts(self) -> (R, ReaderParts) {
+        let parts = ReaderParts {
+            position: self.position,
+            pending: self.bits.value(),
+            pending_len: self.bits.len(),
+            limit: self.limit,
+            vlc_limit: self.vlc_limit,
+        };
+        (self.inner, parts)
+    }
+
+    /// Put a reader back together from its inner reader and the rest of its
+    /// state.  `inner` should be positioned just after the pending bits, as
+    /// the original inner reader was.
+    pub fn from_parts(inner: R, parts: ReaderParts) -> Self {
+        Self {
+            inner,
+            bits: Accumulator::from_value(parts.pending_len, parts.pending),
+            position: parts.position,
+            limit: parts.limit,
+            vlc_limit: parts.vlc_limit,
+        }
+    }
+}
+
+/// The state of a `BitWriter` apart from its inner writer, from
+/// `BitWriter::into_parts()`.
+///
+/// The pending bits are those of the current partial byte, fewer than eight,
+/// packed as they were passed to `write_bits()`.  There are always
+/// `position() % 8` of them, as the writer only passes whole bytes on.
+///
+/// With the `alloc` feature the parts also hold any whole bytes batched in
+/// the writer's internal buffer, which come before the pending bits.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WriterParts {
+    position: u64,
+    pending: u8,
+    pending_len: u8,
+    limit: Option<u64>,
+    pad_ones: bool,
+    auto_flush: u64,
+    #[cfg(feature = "alloc")]
+    capacity: usize,
+    #[cfg(feature = "alloc")]
+    batched: Vec<u8>,
+}
+
+impl WriterParts {
+    /// Parts for a writer at `position`, with the low `position % 8` bits of
+    /// `pending` making up the current partial byte, and the default settings.
+    ///
+    /// Bits of `pending` above those are an `InvalidInput` error.
+    pub fn new(position: u64, pending: u8) -> io::Result<Self> {
+        let pending_len = (position % 8) as u8;
+        if pending as u64 >> pending_len != 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "from_parts",
+                "invalid pending bits",
+            )
+            .at(Some(position))
+            .with_value(pending as u64)
+            .into());
+        }
+        Ok(Self {
+            position,
+            pending,
+            pending_len,
+            limit: None,
+            pad_ones: false,
+            auto_flush: 0,
+            #[cfg(feature = "alloc")]
+            capacity: 0,
+            #[cfg(feature = "alloc")]
+            batched: Vec::new(),
+        })
+    }
+
+    /// Set the limit, as by `BitWriterBuilder::limit_bits()`, or remove it.
+    pub fn with_limit(self, limit: Option<u64>) -> Self {
+        Self { limit, ..self }
+    }
+
+    /// Set the padding, as by `BitWriterBuilder::pad_with_ones()`.
+    pub fn with_pad_ones(self, ones: bool) -> Self {
+        Self {
+            pad_ones: ones,
+            ..self
+        }
+    }
+
+    /// Set the auto-flush interval, as by
+    /// `BitWriterBuilder::auto_flush_every_bytes()`.
+    pub fn with_auto_flush(self, bytes: u64) -> Self {
+        Self {
+            auto_flush: bytes,
+            ..self
+        }
+    }
+
+    /// Set the batching capacity, as by `BitWriterBuilder::capacity()`, and
+    /// the whole bytes already batched, which are counted by `position()`.
+    #[cfg(feature = "alloc")]
+    pub fn with_batch(self, capacity: usize, batched: Vec<u8>) -> Self {
+        Self {
+            capacity,
+            batched,
+            ..self
+        }
+    }
+
+    /// The bit position of the next write.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The bits of the current partial byte, as `(count, value)`.
+    pub fn pending(&self) -> (u8, u8) {
+        (self.pending_len, self.pending)
+    }
+
+    /// The limit, if there is one.
+    pub fn limit(&self) -> Option<u64> {
+        self.limit
+    }
+
+    /// Whether the final byte is padded with one bits.
+    pub fn pad_ones(&self) -> bool {
+        self.pad_ones
+    }
+
+    /// The auto-flush interval in bytes, zero if auto-flushing is disabled.
+    pub fn auto_flush(&self) -> u64 {
+        self.auto_flush
+    }
+
+    /// The batching capacity, zero if batching is disabled.
+    #[cfg(feature = "alloc")]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The whole bytes batched but not yet passed to the inner writer.
+    #[cfg(feature = "alloc")]
+    pub fn batched(&self) -> &[u8] {
+        &self.batched
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Take this writer apart into its inner writer and the rest of its
+    /// state, for `from_parts()`.  Nothing is written or flushed.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_bits(12, 0xabc).unwrap();
+    /// let (inner, parts) = writer.into_parts();
+    /// assert_eq!(inner, [0xab]);
+    /// assert_eq!(parts.pending(), (4, 0xc));
+    ///
+    /// let mut writer = BitWriter::<_>::from_parts(inner, parts);
+    /// writer.write_bits(4, 0xd).unwrap();
+    /// assert_eq!(writer.into_inner(), [0xab, 0xcd]);
+    /// ```
+    pub fn into_parts(self) -> (W, WriterParts) {
+        let parts = WriterParts {
+            position: self.position,
+            pending: self.bits.value() as u8,
+            pending_len: self.bits.len(),
+            limit: self.limit,
+            pad_ones: self.pad_ones,
+            auto_flush: self.auto_flush,
+            #[cfg(feature = "alloc")]
+            capacity: self.capacity,
+            #[cfg(feature = "alloc")]
+            batched: self.buf,
+        };
+        (self.inner, parts)
+    }
+
+    /// Put a writer back together from its inner writer and the rest of its
+    /// state.  Writing carries on after whatever `inner` holds, so it should
+    /// be the original inner writer, or hold the same bytes.
+    pub fn from_parts(inner: W, parts: WriterParts) -> Self {
+        Self {
+            inner,
+            bits: Accumulator::from_value(parts.pending_len, parts.pending as u128),
+            position: parts.position,
+            limit: parts.limit,
+            pad_ones: parts.pad_ones,
+            auto_flush: parts.auto_flush,
+            unflushed: 0,
+            #[cfg(feature = "alloc")]
+            buf: parts.batched,
+            #[cfg(feature = "alloc")]
+            capacity: parts.capacity,
+        }
+    }
+}
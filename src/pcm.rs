@@ -0,0 +1,245 @@
+//! Packed PCM audio: runs of signed samples of any depth from 1 to 32 bits,
+//! back to back with no padding, as in 12, 20 and 24 bit formats.
+//!
+//! Interleaving is left to the caller, since the samples of every channel
+//! are the same width either way.
+
+use core::convert::TryInto;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+fn check_depth(bit_depth: u8) {
+    assert!(
+        (1..=32).contains(&bit_depth),
+        "bit depth must be from 1 to 32"
+    );
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read `out.len()` signed, two's complement samples of `bit_depth` bits
+    /// each, as a `read_bits()` and a sign extension for each would.
+    ///
+    /// Bytes are read from the inner reader a block at a time, never past the
+    /// last sample, and each sample is taken from them with a single word
+    /// load.  A run which would pass
+    /// any limit is an `UnexpectedEof` error, and nothing is read.  If the
+    /// stream ends or the inner reader fails, the error carries the index of
+    /// the sample which couldn't be read as its value, along with the bit
+    /// offset it started at; the samples before it have been read into `out`
+    /// and consumed, and any bits loaded for it remain buffered.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0x7f, 0xf8, 0x00, 0xff, 0xf0, 0x01][..]);
+    /// let mut samples = [0; 4];
+    /// reader.read_samples_i32(12, &mut samples).unwrap();
+    /// assert_eq!(samples, [2047, -2048, -1, 1]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `bit_depth` is from 1 to 32.
+    pub fn read_samples_i32(&mut self, bit_depth: u8, out: &mut [i32]) -> io::Result<()> {
+        check_depth(bit_depth);
+        let total = bit_depth as u64 * out.len() as u64;
+        if self
+            .limit
+            .is_some_and(|limit| self.position.saturating_add(total) > limit)
+        {
+            return Err(Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read_samples_i32",
+                "bit limit reached",
+            )
+            .at(Some(self.position))
+            .into());
+        }
+
+        let shift = 64 - bit_depth;
+        let extend = |value: u64| ((value << shift) as i64 >> shift) as i32;
+        let depth = bit_depth as usize;
+        // Kept locally through the loop, so the compiler can keep them in
+        // registers, and put back before anything returns.
+        let mut bits = self.bits;
+        let mut position = self.position;
+
+        let mut index = 0;
+        while index < out.len() && bits.len() >= bit_depth {
+            out[index] = extend(bits.pop(bit_depth));
+            position += bit_depth as u64;
+            index += 1;
+        }
+
+        // Samples are taken from a block of bytes at a bit cursor, a word at a
+        // time.
+        let mut buf = [0u8; 1024];
+        let (mut cursor, mut end) = (0, 0);
+        while index < out.len() {
+            let available = end * 8 - cursor + bits.len() as usize;
+            if available < depth {
+                let first = cursor / 8;
+                buf.copy_within(first..end, 0);
+                end -= first;
+                cursor -= first * 8;
+                // Only as many bytes as the rest of the samples need.
+                let wanted =
+                    ((out.len() - index) as u64 * bit_depth as u64 - available as u64).div_ceil(8);
+                let want = core::cmp::min(wanted, (buf.len() - end) as u64) as usize;
+                let error = match read_some(&mut self.inner, &mut buf[end..end + want]) {
+                    Ok(0) => Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "read_samples_i32",
+                        "end of stream",
+                    ),
+                    Ok(n) => {
+                        end += n;
+                        continue;
+                    }
+                    Err(e) => Error::from_source("read_samples_i32", e),
+                };
+                self.bits = unread(bits, &buf[..end], cursor);
+                self.position = position;
+                return Err(error.at(Some(position)).with_value(index as u64).into());
+            }
+
+            let value = if bits.len() > 0 {
+                // The first sample starts in bits already buffered, and the
+                // cursor starts after what's left of the last byte it takes.
+                while bits.len() < bit_depth {
+                    bits.push(8, buf[cursor / 8] as u64);
+                    cursor += 8;
+                }
+                let value = bits.pop(bit_depth);
+                cursor -= bits.len() as usize;
+                bits.clear();
+                value
+            } else {
+                let byte = cursor / 8;
+                let word: [u8; 8] = match buf.get(byte..byte + 8) {
+                    Some(word) if byte + 8 <= end => word.try_into().unwrap(),
+                    _ => {
+                        let mut word = [0; 8];
+                        word[..end - byte].copy_from_slice(&buf[byte..end]);
+                        word
+                    }
+                };
+                let phase = cursor % 8;
+                cursor += depth;
+                if O::MSB_FIRST {
+                    (u64::from_be_bytes(word) << phase) >> shift
+                } else {
+                    (u64::from_le_bytes(word) >> phase) & (u64::MAX >> shift)
+                }
+            };
+            out[index] = extend(value);
+            position += bit_depth as u64;
+            index += 1;
+        }
+        self.bits = unread(bits, &buf[..end], cursor);
+        self.position = position;
+        Ok(())
+    }
+}
+
+/// Read some bytes into `buf`, retrying if interrupted.
+fn read_some<R: io::Read>(inner: &mut R, buf: &mut [u8]) -> io::Result<usize> {
+    loop {
+        match inner.read(buf) {
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            result => return result,
+        }
+    }
+}
+
+/// Queue the bits of `buf` from the bit offset `cursor` after `bits`.
+fn unread<O: BitOrder>(mut bits: Accumulator<O>, buf: &[u8], cursor: usize) -> Accumulator<O> {
+    if cursor < buf.len() * 8 {
+        let first = cursor / 8;
+        bits.push(8, buf[first] as u64);
+        bits.pop((cursor % 8) as u8);
+        for &byte in &buf[first + 1..] {
+            bits.push(8, byte as u64);
+        }
+    }
+    bits
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write `samples` as signed, two's complement samples of `bit_depth` bits
+    /// each, back to back, returning the number of bits written.  The bytes
+    /// are passed on to the inner writer in blocks.
+    ///
+    /// Every sample is checked before anything is written: one which doesn't
+    /// fit in `bit_depth` bits is an `InvalidInput` error carrying its index
+    /// as its value, and a run which would pass any limit is a `WriteZero`
+    /// error.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_samples_i32(12, &[2047, -2048, -1, 1]).unwrap();
+    /// assert_eq!(writer.into_inner(), [0x7f, 0xf8, 0x00, 0xff, 0xf0, 0x01]);
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// assert!(writer.write_samples_i32(12, &[0, 2048]).is_err());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics unless `bit_depth` is from 1 to 32.
+    pub fn write_samples_i32(&mut self, bit_depth: u8, samples: &[i32]) -> io::Result<usize> {
+        check_depth(bit_depth);
+        let start = Some(self.position);
+        let min = -(1i64 << (bit_depth - 1));
+        let max = (1i64 << (bit_depth - 1)) - 1;
+        if let Some(index) = samples
+            .iter()
+            .position(|&sample| (sample as i64) < min || (sample as i64) > max)
+        {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_samples_i32",
+                "sample out of range",
+            )
+            .at(start)
+            .with_value(index as u64)
+            .into());
+        }
+        let total = bit_depth as u64 * samples.len() as u64;
+        if self
+            .limit
+            .is_some_and(|limit| self.position.saturating_add(total) > limit)
+        {
+            return Err(Error::new(
+                io::ErrorKind::WriteZero,
+                "write_samples_i32",
+                "bit limit reached",
+            )
+            .at(start)
+            .into());
+        }
+
+        let wrap = |e| Error::wrap(e, "write_samples_i32", start);
+        let mask = u64::MAX >> (64 - bit_depth);
+        let mut bytes = [0; 1024];
+        let mut len = 0;
+        for &sample in samples {
+            self.bits.push(bit_depth, sample as u64 & mask);
+            self.position += bit_depth as u64;
+            len += self.bits.drain_bytes(&mut bytes[len..]);
+            // Leave room for the next sample's bytes.
+            if len > bytes.len() - 5 {
+                self.emit(&bytes[..len]).map_err(wrap)?;
+                len = 0;
+            }
+        }
+        self.emit(&bytes[..len]).map_err(wrap)?;
+        Ok(total as usize)
+    }
+}
@@ -0,0 +1,117 @@
+//! Fixed-width primitive types which can be read and written whole.
+
+mod private {
+    pub trait Sealed {}
+
+    pub trait IntegerSealed {
+        // The message for a field too wide for the type.
+        const TOO_WIDE: &'static str;
+    }
+}
+
+/// A primitive type with a fixed bit width, readable with `BitReader::read()`
+/// and friends.  Implemented for the integer types up to 64 bits, and for
+/// `f32` and `f64` via their IEEE-754 bit patterns, and with the `half` feature
+/// for `half::f16` and `half::bf16`.
+pub trait Primitive: private::Sealed + Copy {
+    /// The width of the type in bits.
+    const BITS: u8;
+
+    /// Build a value from the low `BITS` bits of `bits`.
+    fn from_bits(bits: u64) -> Self;
+
+    /// The bit pattern of this value.  Only the low `BITS` bits are significant.
+    fn to_bits(self) -> u64;
+}
+
+macro_rules! int_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Primitive for $t {
+                const BITS: u8 = (core::mem::size_of::<$t>() * 8) as u8;
+
+                #[inline]
+                fn from_bits(bits: u64) -> Self {
+                    bits as $t
+                }
+
+                #[inline]
+                fn to_bits(self) -> u64 {
+                    self as u64
+                }
+            }
+
+            impl private::IntegerSealed for $t {
+                const TOO_WIDE: &'static str = concat!("field wider than ", stringify!($t));
+            }
+
+            impl Integer for $t {}
+        )*
+    };
+}
+
+/// An integer `Primitive`, which can be read from a field narrower than the
+/// type with `BitRead::try_read_into()` and `try_read_signed_into()`.
+pub trait Integer: Primitive + private::IntegerSealed {}
+
+int_primitive!(u8, u16, u32, u64, i8, i16, i32, i64);
+
+impl private::Sealed for f32 {}
+
+impl Primitive for f32 {
+    const BITS: u8 = 32;
+
+    #[inline]
+    fn from_bits(bits: u64) -> Self {
+        f32::from_bits(bits as u32)
+    }
+
+    #[inline]
+    fn to_bits(self) -> u64 {
+        f32::to_bits(self) as u64
+    }
+}
+
+impl private::Sealed for f64 {}
+
+impl Primitive for f64 {
+    const BITS: u8 = 64;
+
+    #[inline]
+    fn from_bits(bits: u64) -> Self {
+        f64::from_bits(bits)
+    }
+
+    #[inline]
+    fn to_bits(self) -> u64 {
+        f64::to_bits(self)
+    }
+}
+
+#[cfg(feature = "half")]
+macro_rules! half_primitive {
+    ($($t:ty),*) => {
+        $(
+            impl private::Sealed for $t {}
+
+            impl Primitive for $t {
+                const BITS: u8 = 16;
+
+                #[inline]
+                fn from_bits(bits: u64) -> Self {
+                    <$t>::from_bits(bits as u16)
+                }
+
+                #[inline]
+                fn to_bits(self) -> u64 {
+                    <$t>::to_bits(self) as u64
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "half")]
+half_primitive!(half::f16, half::bf16);
@@ -0,0 +1,47 @@
+//! Quantizing floats known to lie in a range onto a few bits, as game and
+//! telemetry protocols do with positions and angles.
+//!
+//! An `n` bit field splits `[min, max]` into `2^n - 1` equal steps, so code
+//! zero is exactly `min` and the largest code exactly `max`.  Values are
+//! rounded to the nearest code, ties to even, and those outside the range are
+//! clamped to it, so a value in range comes back within half a step.
+//!
+//! The degenerate cases are defined too: zero bits, or `min == max`, encode
+//! every value as `min`, and one bit encodes just `min` and `max`.
+
+/// Check the range and width, panicking if they're invalid.
+pub(crate) fn check(min: f32, max: f32, nbits: u8) {
+    assert!(
+        min.is_finite() && max.is_finite() && min <= max,
+        "quantization range must be finite with min <= max"
+    );
+    assert!(nbits <= 32, "quantized fields are at most 32 bits");
+}
+
+/// The largest code of an `nbits` field.
+fn steps(nbits: u8) -> u64 {
+    (1u64 << nbits) - 1
+}
+
+/// The code for `value`, which mustn't be NaN.
+pub(crate) fn encode(value: f32, min: f32, max: f32, nbits: u8) -> u64 {
+    let steps = steps(nbits);
+    if min == max || steps == 0 {
+        return 0;
+    }
+    let scaled = (value as f64 - min as f64) / (max as f64 - min as f64) * steps as f64;
+    let scaled = scaled.clamp(0.0, steps as f64);
+    crate::fixed::round_ties_even(scaled) as u64
+}
+
+/// The value of `code`.
+pub(crate) fn decode(code: u64, min: f32, max: f32, nbits: u8) -> f32 {
+    let steps = steps(nbits);
+    if code == 0 || steps == 0 {
+        return min;
+    }
+    if code >= steps {
+        return max;
+    }
+    (min as f64 + (max as f64 - min as f64) * (code as f64 / steps as f64)) as f32
+}
@@ -0,0 +1,168 @@
+//! A growable queue of bits, with no IO behind it.
+
+use alloc::collections::VecDeque;
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+
+/// A first-in first-out queue of bits, of any length: bits are pushed on one
+/// end, and popped from the other as fields or whole bytes, such as between a
+/// demodulator producing bits and a framer consuming bytes.
+///
+/// Fields are packed and unpacked in stream order as for a `BitWriter` and
+/// `BitReader` of the same `BitOrder`, and as they do it implements `BitWrite`
+/// and `BitRead`, reads failing with `UnexpectedEof` if there aren't enough
+/// bits queued.
+///
+/// ```
+/// use bitrw::BitQueue;
+///
+/// let mut queue = BitQueue::new();
+/// queue.push_bits(3, 0b101);
+/// queue.push_bits(9, 0x0cd);
+/// assert_eq!(queue.len_bits(), 12);
+/// assert_eq!(queue.pop_bits(4), Some(0b1010));
+///
+/// let mut bytes = [0; 2];
+/// assert_eq!(queue.pop_bytes(&mut bytes), 1);
+/// assert_eq!(bytes[0], 0xcd);
+/// assert_eq!(queue.pop_bits(1), None);
+/// ```
+#[derive(Clone)]
+pub struct BitQueue<O = Msb0> {
+    // Bits from the front, being popped.
+    head: Accumulator<O>,
+    // Whole bytes between the two.
+    bytes: VecDeque<u8>,
+    // Fewer than eight bits at the back, being pushed.
+    tail: Accumulator<O>,
+}
+
+impl<O: BitOrder> fmt::Debug for BitQueue<O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitQueue")
+            .field("len_bits", &self.len_bits())
+            .finish()
+    }
+}
+
+impl BitQueue {
+    /// An empty queue, packing bits most-significant first.
+    pub fn new() -> Self {
+        Self::with_order(Msb0)
+    }
+}
+
+impl Default for BitQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<O: BitOrder> BitQueue<O> {
+    /// An empty queue, packing bits in the given order.
+    pub fn with_order(_order: O) -> Self {
+        Self {
+            head: Accumulator::new(),
+            bytes: VecDeque::new(),
+            tail: Accumulator::new(),
+        }
+    }
+
+    /// The number of bits queued.
+    pub fn len_bits(&self) -> u64 {
+        self.head.len() as u64 + self.bytes.len() as u64 * 8 + self.tail.len() as u64
+    }
+
+    /// Whether the queue is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len_bits() == 0
+    }
+
+    /// Remove every bit queued.
+    pub fn clear(&mut self) {
+        self.head.clear();
+        self.bytes.clear();
+        self.tail.clear();
+    }
+
+    /// Push the low `nbits` of `value`, up to 64, onto the back of the queue.
+    pub fn push_bits(&mut self, nbits: u8, value: u64) {
+        assert!(nbits <= 64);
+        self.tail.push(nbits, value);
+        while self.tail.len() >= 8 {
+            self.bytes.push_back(self.tail.pop(8) as u8);
+        }
+    }
+
+    /// Push whole bytes onto the back of the queue.
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        if self.tail.len() == 0 {
+            self.bytes.extend(bytes);
+        } else {
+            bytes
+                .iter()
+                .for_each(|&byte| self.push_bits(8, byte as u64));
+        }
+    }
+
+    /// Pop `nbits`, up to 64, from the front of the queue, or `None` if there
+    /// aren't that many, in which case nothing is popped.
+    pub fn pop_bits(&mut self, nbits: u8) -> Option<u64> {
+        assert!(nbits <= 64);
+        if self.len_bits() < nbits as u64 {
+            return None;
+        }
+        while self.head.len() < nbits {
+            match self.bytes.pop_front() {
+                Some(byte) => self.head.push(8, byte as u64),
+                None => {
+                    let len = self.tail.len();
+                    self.head.push(len, self.tail.pop(len));
+                }
+            }
+        }
+        Some(self.head.pop(nbits))
+    }
+
+    /// Pop as many whole bytes as are queued from the front, up to the length
+    /// of `out`, returning how many.  Bits at the back which don't make up a
+    /// whole byte are left queued.
+    pub fn pop_bytes(&mut self, out: &mut [u8]) -> usize {
+        let n = core::cmp::min(out.len() as u64, self.len_bits() / 8) as usize;
+        let mut i = 0;
+        while i < n && self.head.len() > 0 {
+            out[i] = self.pop_bits(8).unwrap() as u8;
+            i += 1;
+        }
+        // Now on a byte boundary, with the rest of the whole bytes in `bytes`.
+        for (byte, queued) in out[i..n].iter_mut().zip(self.bytes.drain(..n - i)) {
+            *byte = queued;
+        }
+        n
+    }
+}
+
+impl<O: BitOrder> BitRead for BitQueue<O> {
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        self.pop_bits(nbits).ok_or_else(|| {
+            Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read_bits",
+                "not enough bits queued",
+            )
+            .into()
+        })
+    }
+}
+
+impl<O: BitOrder> BitWrite for BitQueue<O> {
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        self.push_bits(nbits, value);
+        Ok(nbits as usize)
+    }
+}
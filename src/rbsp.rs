@@ -0,0 +1,391 @@
+//! H.264 and H.265 raw byte sequence payloads.
+//!
+//! Within a NAL unit, the encoder inserts an emulation prevention byte, 0x03,
+//! after any two zero bytes which would otherwise be followed by a byte less
+//! than 4, so the payload can never contain a start code.  `RbspSource`
+//! removes them on the fly as bytes are read, and `RbspBitReader` reads bits
+//! from the result, with the spec's `more_rbsp_data()`.
+//!
+//! Going the other way, `RbspSink` inserts emulation prevention bytes into the
+//! bytes written to it, and `RbspBitWriter` writes bits through one, with
+//! `write_rbsp_trailing_bits()`.
+
+use crate::io;
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// Adapts a reader of NAL unit bytes into a reader of the RBSP they encode, by
+/// removing each 0x03 which follows two zero bytes.
+///
+/// Reads return at most one byte at a time, so should be buffered by the
+/// caller if needed, as for `BitReader`.
+#[derive(Debug)]
+pub struct RbspSource<R> {
+    inner: R,
+    // The number of consecutive zero bytes last read from `inner`, up to 2.
+    zeros: u8,
+    removed: u64,
+    // Bytes read ahead by `more_rbsp_data()`: `first`, then `ahead_zeros`
+    // zero bytes, then `last`.
+    first: Option<u8>,
+    ahead_zeros: u64,
+    last: Option<u8>,
+}
+
+impl<R: io::Read> RbspSource<R> {
+    /// Wrap the given reader, which should be positioned just after a NAL unit
+    /// header.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            zeros: 0,
+            removed: 0,
+            first: None,
+            ahead_zeros: 0,
+            last: None,
+        }
+    }
+
+    /// The number of emulation prevention bytes removed so far.
+    pub fn removed(&self) -> u64 {
+        self.removed
+    }
+
+    /// Get a reference to the reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap the reader.  Any bytes read ahead are lost.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// The next byte of the RBSP from `inner`, ignoring any read ahead.
+    fn raw_byte(&mut self) -> io::Result<Option<u8>> {
+        loop {
+            let mut byte = [0];
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => {}
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+
+            let byte = byte[0];
+            if self.zeros == 2 && byte == 0x03 {
+                self.zeros = 0;
+                self.removed += 1;
+                continue;
+            }
+            self.zeros = if byte == 0 {
+                (self.zeros + 1).min(2)
+            } else {
+                0
+            };
+            return Ok(Some(byte));
+        }
+    }
+
+    /// Make sure the next byte, if there is one, is in `first`.
+    fn peek_first(&mut self) -> io::Result<Option<u8>> {
+        if self.first.is_none() {
+            if self.ahead_zeros > 0 {
+                self.ahead_zeros -= 1;
+                self.first = Some(0);
+            } else if self.last.is_some() {
+                self.first = self.last.take();
+            } else {
+                self.first = self.raw_byte()?;
+            }
+        }
+        Ok(self.first)
+    }
+
+    /// Whether any byte after the next is non-zero, reading ahead as far as
+    /// necessary.
+    fn nonzero_after_first(&mut self) -> io::Result<bool> {
+        while self.last.is_none() {
+            match self.raw_byte()? {
+                None => return Ok(false),
+                Some(0) => self.ahead_zeros += 1,
+                Some(byte) => self.last = Some(byte),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: io::Read> io::Read for RbspSource<R> {
+    fn read(&mut self, dst: &mut [u8]) -> io::Result<usize> {
+        if dst.is_empty() {
+            return Ok(0);
+        }
+        let byte = if let Some(byte) = self.first.take() {
+            Some(byte)
+        } else if self.ahead_zeros > 0 {
+            self.ahead_zeros -= 1;
+            Some(0)
+        } else if let Some(byte) = self.last.take() {
+            Some(byte)
+        } else {
+            self.raw_byte()?
+        };
+        match byte {
+            Some(byte) => {
+                dst[0] = byte;
+                Ok(1)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+/// Reads bits from the RBSP of a NAL unit, removing emulation prevention
+/// bytes.
+///
+/// ```
+/// use bitrw::{BitRead, RbspBitReader};
+///
+/// let mut reader = RbspBitReader::new(&[0x00, 0x00, 0x03, 0x01, 0xc0][..]);
+/// assert_eq!(reader.read_bits(24).unwrap(), 0x000001);
+/// assert!(reader.more_rbsp_data().unwrap());
+/// assert_eq!(reader.read_bit().unwrap(), 1);
+/// assert!(!reader.more_rbsp_data().unwrap());
+/// ```
+#[derive(Debug)]
+pub struct RbspBitReader<R> {
+    reader: BitReader<RbspSource<R>>,
+}
+
+impl<R: io::Read> RbspBitReader<R> {
+    /// Create a new `RbspBitReader` over the bytes of a NAL unit following its
+    /// header.
+    pub fn new(inner: R) -> Self {
+        Self {
+            reader: BitReader::new(RbspSource::new(inner)),
+        }
+    }
+
+    /// Whether there is more data before the `rbsp_trailing_bits()`, which
+    /// are a one bit, the last in the NAL unit, followed by zero bits.
+    ///
+    /// This reads ahead to the next non-zero byte, or the end of the unit.
+    pub fn more_rbsp_data(&mut self) -> io::Result<bool> {
+        // Everything from here to the last one bit is data.  The data is
+        // non-empty if there's any one bit after the next.
+        let mut bits = self.reader.bits;
+        let pending = bits.len();
+        let source = self.reader.get_mut();
+        if pending > 0 {
+            let value = bits.pop(pending);
+            if value & ((1 << (pending - 1)) - 1) != 0 {
+                return Ok(true);
+            }
+            if source.peek_first()?.is_some_and(|byte| byte != 0) {
+                return Ok(true);
+            }
+        } else {
+            match source.peek_first()? {
+                None => return Ok(false),
+                Some(byte) if byte & 0x7f != 0 => return Ok(true),
+                Some(_) => {}
+            }
+        }
+        source.nonzero_after_first()
+    }
+
+    /// The number of bits read from the RBSP so far.
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Whether the next read starts on a byte boundary of the RBSP.
+    pub fn byte_aligned(&self) -> bool {
+        self.reader.position().is_multiple_of(8)
+    }
+
+    /// Get a reference to the source, which knows how many emulation
+    /// prevention bytes have been removed.
+    pub fn get_ref(&self) -> &RbspSource<R> {
+        self.reader.get_ref()
+    }
+
+    /// Unwrap this `RbspBitReader`, returning the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader.into_inner().into_inner()
+    }
+}
+
+impl<R: io::Read> BitRead for RbspBitReader<R> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        self.reader.read_bits(nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.reader.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.reader.vlc_limit()
+    }
+}
+
+/// Adapts a writer to escape the RBSP bytes written to it into NAL unit
+/// bytes, by inserting a 0x03 wherever two zero bytes would otherwise be
+/// followed by 0x00, 0x01, 0x02 or 0x03.
+///
+/// The zero count carries across writes, so the bytes can be written in any
+/// number of pieces.
+#[derive(Debug)]
+pub struct RbspSink<W> {
+    inner: W,
+    // The number of consecutive zero bytes last written to `inner`, up to 2.
+    zeros: u8,
+    inserted: u64,
+}
+
+impl<W: io::Write> RbspSink<W> {
+    /// Wrap the given writer, which should be positioned just after a NAL unit
+    /// header.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            zeros: 0,
+            inserted: 0,
+        }
+    }
+
+    /// The number of emulation prevention bytes inserted so far.
+    pub fn inserted(&self) -> u64 {
+        self.inserted
+    }
+
+    /// Get a reference to the writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Finish the NAL unit, appending the final 0x03 required if the RBSP
+    /// ended with a zero byte, and return the writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.zeros > 0 {
+            self.inner.write_all(&[0x03])?;
+            self.inserted += 1;
+        }
+        Ok(self.inner)
+    }
+
+    /// Unwrap the writer, without finishing the NAL unit.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: io::Write> io::Write for RbspSink<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut run = 0;
+        for (i, &byte) in buf.iter().enumerate() {
+            if self.zeros == 2 && byte <= 0x03 {
+                self.inner.write_all(&buf[run..i])?;
+                self.inner.write_all(&[0x03])?;
+                self.inserted += 1;
+                self.zeros = 0;
+                run = i;
+            }
+            self.zeros = if byte == 0 {
+                (self.zeros + 1).min(2)
+            } else {
+                0
+            };
+        }
+        self.inner.write_all(&buf[run..])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Writes bits as the RBSP of a NAL unit, inserting emulation prevention
+/// bytes.
+///
+/// ```
+/// use bitrw::{BitWrite, RbspBitWriter};
+///
+/// let mut writer = RbspBitWriter::new(Vec::new());
+/// writer.write_bits(24, 0x000001).unwrap();
+/// writer.write_rbsp_trailing_bits().unwrap();
+/// assert_eq!(writer.finish().unwrap(), [0x00, 0x00, 0x03, 0x01, 0x80]);
+/// ```
+#[derive(Debug)]
+pub struct RbspBitWriter<W> {
+    writer: BitWriter<RbspSink<W>>,
+}
+
+impl<W: io::Write> RbspBitWriter<W> {
+    /// Create a new `RbspBitWriter` writing the bytes of a NAL unit following
+    /// its header.
+    pub fn new(inner: W) -> Self {
+        Self {
+            writer: BitWriter::new(RbspSink::new(inner)),
+        }
+    }
+
+    /// Write the `rbsp_trailing_bits()` which end the RBSP: a one bit, then
+    /// zero bits up to the next byte boundary.  Returns the number of bits
+    /// written.
+    pub fn write_rbsp_trailing_bits(&mut self) -> io::Result<usize> {
+        self.writer.write_bits(1, 1)?;
+        Ok(1 + self.writer.flush_bits()?)
+    }
+
+    /// Pad to a byte boundary with zero bits, as for `BitWriter::flush()`.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.writer.flush()
+    }
+
+    /// Pad to a byte boundary with zero bits, as for `BitWriter::flush_bits()`.
+    pub fn flush_bits(&mut self) -> io::Result<usize> {
+        self.writer.flush_bits()
+    }
+
+    /// The number of bits of RBSP written so far.
+    pub fn position(&self) -> u64 {
+        self.writer.position()
+    }
+
+    /// Whether the next write starts on a byte boundary of the RBSP.
+    pub fn byte_aligned(&self) -> bool {
+        self.writer.position().is_multiple_of(8)
+    }
+
+    /// Get a reference to the sink, which knows how many emulation prevention
+    /// bytes have been inserted.
+    pub fn get_ref(&self) -> &RbspSink<W> {
+        self.writer.get_ref()
+    }
+
+    /// Flush any partial byte, finish the NAL unit as for `RbspSink::finish()`,
+    /// and return the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.flush()?;
+        self.writer.into_inner().finish()
+    }
+}
+
+impl<W: io::Write> BitWrite for RbspBitWriter<W> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        self.writer.write_bits(nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.writer.position())
+    }
+}
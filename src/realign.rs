@@ -0,0 +1,134 @@
+//! Reading the rest of a stream as bytes from part way through one, as when a
+//! byte-oriented payload follows a header of some odd number of bits.
+
+use core::fmt;
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::BitReader;
+
+/// Reads the bits of a `BitReader` as bytes of 8 at its current phase, rather
+/// than only at byte boundaries, created by `BitReader::realigned_bytes()`.
+///
+/// It's both an `io::Read`, which shifts whole blocks of input a word at a
+/// time, and an iterator over single bytes.  The reader stays at the phase it
+/// started at, after the last byte returned, so once done with this it can
+/// carry on reading bits from there.  Bits left at the end of the stream, or
+/// before any limit, which don't make up a whole byte are left unread.
+pub struct RealignedBytes<'a, R, O> {
+    reader: &'a mut BitReader<R, O>,
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read the rest of the stream as bytes of 8 bits at the current phase, as
+    /// if it were shifted back to a byte boundary, without consuming anything
+    /// beyond the bytes read through the returned `RealignedBytes`.
+    ///
+    /// On a byte boundary this reads the same as `io::Read::bytes()`, which
+    /// fails part way through a byte.  It isn't called `bytes()` because with
+    /// `io::Read` in scope, as it must be to use this as one, that would be
+    /// the method called.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    /// use std::io::Read;
+    ///
+    /// let mut reader = BitReader::new(&[0xa1, 0x23, 0x45, 0x6f][..]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// let mut payload = [0; 3];
+    /// reader.realigned_bytes().read_exact(&mut payload).unwrap();
+    /// assert_eq!(payload, [0x12, 0x34, 0x56]);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xf);
+    /// ```
+    pub fn realigned_bytes(&mut self) -> RealignedBytes<'_, R, O> {
+        RealignedBytes { reader: self }
+    }
+}
+
+impl<R, O> RealignedBytes<'_, R, O> {
+    /// Get a reference to the underlying `BitReader`.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        self.reader
+    }
+
+    /// Get a mutable reference to the underlying `BitReader`.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        self.reader
+    }
+}
+
+impl<R: io::Read, O: BitOrder> io::Read for RealignedBytes<'_, R, O> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let reader = &mut *self.reader;
+        if reader.bits.len().is_multiple_of(8) {
+            return io::Read::read(reader, buf);
+        }
+
+        let mut len = buf.len();
+        if let Some(limit) = reader.limit {
+            let left = limit.saturating_sub(reader.position) / 8;
+            len = core::cmp::min(len as u64, left) as usize;
+        }
+        let buf = &mut buf[..len];
+
+        // Whole bytes already buffered come first, leaving less than a byte.
+        let mut n = 0;
+        while reader.bits.len() >= 8 && n < len {
+            buf[n] = reader.bits.pop(8) as u8;
+            n += 1;
+        }
+        if n > 0 || len == 0 {
+            reader.position += n as u64 * 8;
+            return Ok(n);
+        }
+
+        // Each byte read makes one byte shifted, leaving the same few bits.
+        let n = reader
+            .inner
+            .read(buf)
+            .map_err(|e| Error::wrap(e, "realigned_bytes", Some(reader.position)))?;
+        let mut words = buf[..n].chunks_exact_mut(8);
+        for word in &mut words {
+            let mut bytes = [0; 8];
+            bytes.copy_from_slice(word);
+            if O::MSB_FIRST {
+                reader.bits.push(64, u64::from_be_bytes(bytes));
+                word.copy_from_slice(&reader.bits.pop(64).to_be_bytes());
+            } else {
+                reader.bits.push(64, u64::from_le_bytes(bytes));
+                word.copy_from_slice(&reader.bits.pop(64).to_le_bytes());
+            }
+        }
+        for byte in words.into_remainder() {
+            reader.bits.push(8, *byte as u64);
+            *byte = reader.bits.pop(8) as u8;
+        }
+        reader.position += n as u64 * 8;
+        Ok(n)
+    }
+}
+
+impl<R: io::Read, O: BitOrder> Iterator for RealignedBytes<'_, R, O> {
+    type Item = io::Result<u8>;
+
+    /// Read the next byte, or `None` at the end of the stream or any limit.
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        let mut byte = [0];
+        loop {
+            return match io::Read::read(self, &mut byte) {
+                Ok(0) => None,
+                Ok(_) => Some(Ok(byte[0])),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => Some(Err(e)),
+            };
+        }
+    }
+}
+
+/// Shows the underlying reader, as its own `Debug` does.
+impl<R, O: BitOrder> fmt::Debug for RealignedBytes<'_, R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("RealignedBytes").field(&self.reader).finish()
+    }
+}
@@ -0,0 +1,195 @@
+//! Reading a stream backwards, from its last bit towards its first.
+//!
+//! Formats such as Zstandard's FSE and Huffman bitstreams are written forwards
+//! but decoded from the end, so the last field written is the first read.  A
+//! `ReverseBitReader` of a given `BitOrder` undoes a `BitWriter` of the same
+//! order: each `read_bits_rev(n)` returns the value of the last `n` bit field
+//! not yet consumed, exactly as it was passed to `write_bits()`.
+
+use core::marker::PhantomData;
+
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::{BitOrder, Lsb0, Msb0};
+
+/// Reads bits from the end of a seekable stream towards its start.
+///
+/// The reader never moves past its starting byte, so forward and reverse
+/// readers can share a buffer, each over their own part of it.
+///
+/// ```
+/// use bitrw::{BitWriter, Lsb0, ReverseBitReader};
+/// use std::io::Cursor;
+///
+/// let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+/// writer.write_bits(5, 17).unwrap();
+/// writer.write_bits(12, 0xabc).unwrap();
+/// writer.write_bit(1).unwrap(); // end marker
+/// writer.flush().unwrap();
+///
+/// let data = writer.into_inner();
+/// let mut reader = ReverseBitReader::with_order(Cursor::new(&data), Lsb0).unwrap();
+/// assert_eq!(reader.skip_sentinel().unwrap(), 7);
+/// assert_eq!(reader.read_bits_rev(12).unwrap(), 0xabc);
+/// assert_eq!(reader.read_bits_rev(5).unwrap(), 17);
+/// assert_eq!(reader.bits_remaining(), 0);
+/// ```
+#[derive(Debug)]
+pub struct ReverseBitReader<R, O = Msb0> {
+    inner: R,
+    start: u64,
+    // Bytes before this offset, back to `start`, are still to be loaded.
+    next: u64,
+    acc: u128,
+    len: u8,
+    order: PhantomData<O>,
+}
+
+impl<R: io::Read + io::Seek> ReverseBitReader<R> {
+    /// Create a new `ReverseBitReader` starting from the end of `inner`.
+    pub fn new(inner: R) -> io::Result<Self> {
+        Self::with_order(inner, Msb0)
+    }
+}
+
+impl<R: io::Read + io::Seek, O: BitOrder> ReverseBitReader<R, O> {
+    /// Create a new `ReverseBitReader` starting from the end of `inner`,
+    /// unpacking bits in the given order.
+    pub fn with_order(mut inner: R, order: O) -> io::Result<Self> {
+        let end = inner.seek(SeekFrom::End(0))?;
+        Ok(Self::with_bounds(inner, 0, end, order))
+    }
+
+    /// Create a new `ReverseBitReader` over bytes `start..end` of `inner`,
+    /// reading from `end` back to `start`.
+    pub fn with_bounds(inner: R, start: u64, end: u64, _order: O) -> Self {
+        assert!(start <= end);
+        Self {
+            inner,
+            start,
+            next: end,
+            acc: 0,
+            len: 0,
+            order: PhantomData,
+        }
+    }
+
+    /// The number of bits left to read.
+    pub fn bits_remaining(&self) -> u64 {
+        (self.next - self.start) * 8 + self.len as u64
+    }
+
+    /// Read the last `nbits` bits not yet consumed, up to 64, returning them as
+    /// they were written.  If fewer remain, fails with `UnexpectedEof` without
+    /// consuming any.
+    pub fn read_bits_rev(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        let remaining = self.bits_remaining();
+        if remaining < nbits as u64 {
+            return Err(Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read_bits_rev",
+                "not enough bits",
+            )
+            .at(Some(self.start * 8 + remaining))
+            .into());
+        }
+
+        while self.len < nbits {
+            self.refill()
+                .map_err(|e| Error::wrap(e, "read_bits_rev", Some(self.start * 8 + remaining)))?;
+        }
+        Ok(self.pop(nbits))
+    }
+
+    /// Read the last bit not yet consumed.
+    pub fn read_bit_rev(&mut self) -> io::Result<u8> {
+        self.read_bits_rev(1).map(|bit| bit as u8)
+    }
+
+    /// Skip the zero bits of padding at the end of the stream and the one bit
+    /// written before them to mark where the data ends, returning the number of
+    /// bits skipped, from 1 to 8.  This should be called before anything else
+    /// is read.
+    ///
+    /// Fails with `InvalidData` if the final byte is zero, so has no marker.
+    pub fn skip_sentinel(&mut self) -> io::Result<u8> {
+        let bits = self.bits_remaining();
+        if bits >= 8 {
+            while self.len < 8 {
+                self.refill()
+                    .map_err(|e| Error::wrap(e, "skip_sentinel", Some(self.start * 8 + bits)))?;
+            }
+
+            let (mut acc, mut len) = (self.acc, self.len);
+            for skipped in 1..=8 {
+                let (bit, rest) = Self::take(acc, len, 1);
+                acc = rest;
+                len -= 1;
+                if bit == 1 {
+                    self.acc = acc;
+                    self.len = len;
+                    return Ok(skipped);
+                }
+            }
+        }
+
+        Err(Error::new(
+            io::ErrorKind::InvalidData,
+            "skip_sentinel",
+            "missing end marker",
+        )
+        .at(Some(self.start * 8 + bits))
+        .into())
+    }
+
+    /// Load up to 8 more bytes from before those loaded so far.
+    fn refill(&mut self) -> io::Result<()> {
+        let room = ((127 - self.len) / 8) as u64;
+        let n = room.min(8).min(self.next - self.start) as usize;
+        let mut bytes = [0; 8];
+        self.inner.seek(SeekFrom::Start(self.next - n as u64))?;
+        self.inner.read_exact(&mut bytes[..n])?;
+        self.next -= n as u64;
+
+        // Bytes are prepended to the front of the queue, last first.
+        for &byte in bytes[..n].iter().rev() {
+            self.acc = if O::MSB_FIRST {
+                Lsb0::push(self.acc, self.len, 8, byte as u64)
+            } else {
+                Msb0::push(self.acc, self.len, 8, byte as u64)
+            };
+            self.len += 8;
+        }
+        Ok(())
+    }
+
+    fn pop(&mut self, nbits: u8) -> u64 {
+        let (value, acc) = Self::take(self.acc, self.len, nbits);
+        self.acc = acc;
+        self.len -= nbits;
+        value
+    }
+
+    /// Take `nbits` from the back of the queue, which means taking them from
+    /// the front of a queue in the opposite order.
+    #[inline(always)]
+    fn take(acc: u128, len: u8, nbits: u8) -> (u64, u128) {
+        if O::MSB_FIRST {
+            Lsb0::pop(acc, len, nbits)
+        } else {
+            Msb0::pop(acc, len, nbits)
+        }
+    }
+
+    /// Get a reference to the reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Unwrap this `ReverseBitReader`, returning the underlying reader.  Its
+    /// position is unspecified.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
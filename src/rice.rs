@@ -0,0 +1,147 @@
+//! Blocks of Rice codes with the parameter chosen per block, as in FLAC's
+//! residual partitions.
+//!
+//! A Rice code with parameter `k` is the value's quotient by `2^k` as a unary
+//! code, as written by `BitWrite::write_unary()`, followed by its low `k` bits.
+//! A block is the parameter in a 5 bit header, then each value's code.  The
+//! number of values isn't written, so must be known to the reader.
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// The largest Rice parameter a block header holds.  The header's last value,
+/// 31, is left unused.
+const MAX_K: u8 = 30;
+
+/// The bits taken by Rice codes of `values` with parameter `k`, not counting
+/// any header.
+fn cost(values: &[u64], k: u8) -> u128 {
+    values
+        .iter()
+        .map(|&value| (value >> k) as u128 + 1 + k as u128)
+        .sum()
+}
+
+/// The Rice parameter from 0 to 30 which codes `values` in the fewest bits,
+/// the smallest of them if several tie.  An empty slice gives 0.
+///
+/// ```
+/// assert_eq!(bitrw::rice_optimal_k(&[0, 1, 0, 2]), 0);
+/// assert_eq!(bitrw::rice_optimal_k(&[100, 90, 120, 80]), 6);
+/// ```
+pub fn rice_optimal_k(values: &[u64]) -> u8 {
+    let mut best = (0, cost(values, 0));
+    for k in 1..=MAX_K {
+        let bits = cost(values, k);
+        if bits < best.1 {
+            best = (k, bits);
+        }
+    }
+    best.0
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write `values` as a block of Rice codes, with the parameter chosen by
+    /// `rice_optimal_k()`, returning the parameter and the number of bits
+    /// written, including the header.
+    ///
+    /// A block which would pass any limit is a `WriteZero` error, and nothing
+    /// is written.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// assert_eq!(writer.write_rice_block(&[3, 9, 4]).unwrap(), (2, 17));
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0b0001_0111, 0b0010_1010, 0b0000_0000]);
+    /// ```
+    pub fn write_rice_block(&mut self, values: &[u64]) -> io::Result<(u8, usize)> {
+        let start = Some(self.position);
+        let k = rice_optimal_k(values);
+        let total = 5 + cost(values, k);
+        if total > u64::MAX as u128
+            || self
+                .limit
+                .is_some_and(|limit| self.position.saturating_add(total as u64) > limit)
+        {
+            return Err(Error::new(
+                io::ErrorKind::WriteZero,
+                "write_rice_block",
+                "bit limit reached",
+            )
+            .at(start)
+            .into());
+        }
+
+        let wrap = |e| Error::wrap(e, "write_rice_block", start);
+        self.write_bits(5, k as u64).map_err(wrap)?;
+        for &value in values {
+            self.write_unary(value >> k).map_err(wrap)?;
+            self.write_bits(k, value & ((1 << k) - 1)).map_err(wrap)?;
+        }
+        Ok((k, total as usize))
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read a block of Rice codes written by `BitWriter::write_rice_block()`
+    /// into `values`, which sets how many are read, returning the block's
+    /// parameter.
+    ///
+    /// A header above 30 is an `InvalidData` error carrying it as its value,
+    /// as is a quotient too large for the value to fit a `u64`.  A code longer
+    /// than `vlc_limit()` is an error as for `set_vlc_limit()`.  If a code
+    /// can't be read, the values before it stay consumed.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let data = [0b0001_0111, 0b0010_1010, 0b0000_0000];
+    /// let mut reader = BitReader::new(&data[..]);
+    /// let mut values = [0; 3];
+    /// assert_eq!(reader.read_rice_block(&mut values).unwrap(), 2);
+    /// assert_eq!(values, [3, 9, 4]);
+    /// ```
+    pub fn read_rice_block(&mut self, values: &mut [u64]) -> io::Result<u8> {
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "read_rice_block", start);
+        let k = self.read_bits(5).map_err(wrap)? as u8;
+        if k > MAX_K {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "read_rice_block",
+                "invalid Rice parameter",
+            )
+            .at(start)
+            .with_value(k as u64)
+            .into());
+        }
+
+        for value in values.iter_mut() {
+            let code_start = Some(self.position);
+            let quotient = self.read_unary().map_err(wrap)?;
+            crate::vlc::check(
+                self.vlc_limit,
+                quotient.saturating_add(1 + k as u64),
+                "read_rice_block",
+                code_start,
+            )?;
+            if quotient.leading_zeros() < k as u32 {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "read_rice_block",
+                    "code too long",
+                )
+                .at(code_start)
+                .with_value(quotient)
+                .into());
+            }
+            *value = (quotient << k) | self.read_bits(k).map_err(wrap)?;
+        }
+        Ok(k)
+    }
+}
@@ -0,0 +1,282 @@
+//! Run-length coding of bitmaps, as alternating runs of zero and one bits.
+
+use alloc::vec::Vec;
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+/// How the length of each run is coded by `write_rle()` and `read_rle()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunLength {
+    /// An Elias gamma code of the length plus one, so a run of `n` bits takes
+    /// `2 * log2(n + 1) + 1` bits.  The code's bits are in stream order,
+    /// most significant first, whatever the `BitOrder`.
+    Gamma,
+    /// A field of this many bits, 1 to 63.  A run too long for it is split
+    /// into runs of the longest length with zero-length runs of the other
+    /// color between them.
+    Fixed(u8),
+}
+
+/// How `read_rle()` knows where the runs end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RleEnd {
+    /// The runs are preceded by the total number of bits, as a gamma code of
+    /// the total plus one.  Writing needs the whole bitmap first, so the runs
+    /// are collected before anything is written.
+    Count,
+    /// The runs are followed by two zero-length runs, which are otherwise
+    /// never written.
+    Terminator,
+}
+
+/// The options for run-length coding with `BitWriter::write_rle()` and
+/// `BitReader::read_rle()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RleConfig {
+    /// How run lengths are coded.
+    pub length: RunLength,
+    /// The color of the first run.  If the bitmap starts with the other color,
+    /// it's coded with a zero-length first run, as fax coding does with its
+    /// initial white run.
+    pub first: bool,
+    /// How the end of the runs is marked.
+    pub end: RleEnd,
+}
+
+impl RleConfig {
+    fn max_run(&self) -> u64 {
+        match self.length {
+            RunLength::Gamma => u64::MAX - 1,
+            RunLength::Fixed(nbits) => (1 << nbits) - 1,
+        }
+    }
+}
+
+/// Split `bits` into runs of alternating colors, starting with `first`.
+fn runs<I: IntoIterator<Item = bool>>(bits: I, first: bool) -> (Vec<u64>, u64) {
+    let mut runs = Vec::new();
+    let mut color = first;
+    let mut len = 0;
+    let mut total = 0;
+    for bit in bits {
+        if bit != color {
+            runs.push(len);
+            color = bit;
+            len = 0;
+        }
+        len += 1;
+        total += 1;
+    }
+    if len > 0 {
+        runs.push(len);
+    }
+    (runs, total)
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write `bits` run-length coded as given by `cfg`, for `read_rle()`,
+    /// returning the number of bits written.
+    ///
+    /// ```
+    /// use bitrw::{BitWriter, RleConfig, RleEnd, RunLength};
+    ///
+    /// let cfg = RleConfig {
+    ///     length: RunLength::Gamma,
+    ///     first: false,
+    ///     end: RleEnd::Terminator,
+    /// };
+    /// let bitmap = [false, false, false, true, true, false];
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// // Runs of 3, 2 and 1 as 00100 011 010, then the terminator 1 1.
+    /// assert_eq!(writer.write_rle(bitmap.iter().copied(), cfg).unwrap(), 13);
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0b0010_0011, 0b0101_1000]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `cfg.length` is `RunLength::Fixed` with a width of zero or more than
+    /// 63.
+    pub fn write_rle<I: IntoIterator<Item = bool>>(
+        &mut self,
+        bits: I,
+        cfg: RleConfig,
+    ) -> io::Result<usize> {
+        if let RunLength::Fixed(nbits) = cfg.length {
+            assert!(
+                (1..64).contains(&nbits),
+                "RLE run length width must be from 1 to 63"
+            );
+        }
+        let start = Some(self.position);
+        let before = self.position;
+        let wrap = |e| Error::wrap(e, "write_rle", start);
+
+        let (runs, total) = runs(bits, cfg.first);
+        if cfg.end == RleEnd::Count {
+            self.write_stream_gamma(total + 1).map_err(wrap)?;
+        }
+        let max = cfg.max_run();
+        for mut run in runs {
+            while run > max {
+                self.write_run(cfg.length, max).map_err(wrap)?;
+                self.write_run(cfg.length, 0).map_err(wrap)?;
+                run -= max;
+            }
+            self.write_run(cfg.length, run).map_err(wrap)?;
+        }
+        if cfg.end == RleEnd::Terminator {
+            self.write_run(cfg.length, 0).map_err(wrap)?;
+            self.write_run(cfg.length, 0).map_err(wrap)?;
+        }
+        Ok((self.position - before) as usize)
+    }
+
+    fn write_run(&mut self, length: RunLength, run: u64) -> io::Result<()> {
+        match length {
+            RunLength::Gamma => self.write_stream_gamma(run + 1),
+            RunLength::Fixed(nbits) => self.write_bits(nbits, run).map(|_| ()),
+        }
+    }
+
+    /// Write a gamma code of `value`, at least one, most significant bit
+    /// first in the stream.
+    fn write_stream_gamma(&mut self, value: u64) -> io::Result<()> {
+        let zeros = 63 - value.leading_zeros() as u8;
+        let code = if O::MSB_FIRST {
+            value
+        } else {
+            value.reverse_bits() >> (63 - zeros)
+        };
+        self.write_bits(zeros, 0)?;
+        self.write_bits(zeros + 1, code)?;
+        Ok(())
+    }
+}
+
+/// The error for a run or total of `len` bits taking a bitmap past the
+/// caller's maximum.
+fn too_long(at: Option<u64>, len: u64) -> io::Error {
+    Error::new(io::ErrorKind::InvalidData, "read_rle", "bitmap too long")
+        .at(at)
+        .with_value(len)
+        .into()
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read a run-length coded bitmap written by `write_rle()` with the same
+    /// `cfg`, appending its bits to `out` and returning how many there were.
+    ///
+    /// The bitmap may be at most `max_bits` long, which bounds how much `out`
+    /// can grow however long a run a corrupt stream claims.  A run taking the
+    /// bitmap past that, or with `RleEnd::Count` a total above it or a run
+    /// past the total, is an `InvalidData` error at the offset of its code,
+    /// with its length as its value, and adds nothing to `out`.  On error, the
+    /// bits of the runs read so far are left in `out`.
+    ///
+    /// ```
+    /// use bitrw::{BitReader, RleConfig, RleEnd, RunLength};
+    ///
+    /// let cfg = RleConfig {
+    ///     length: RunLength::Fixed(2),
+    ///     first: false,
+    ///     end: RleEnd::Count,
+    /// };
+    /// // A total of 6 as 00111, then runs of 3, 2 and 1 as 11 10 01.
+    /// let mut reader = BitReader::new(&[0b0011_1111, 0b0010_0000][..]);
+    /// let mut bitmap = Vec::new();
+    /// assert_eq!(reader.read_rle(cfg, 1024, &mut bitmap).unwrap(), 6);
+    /// assert_eq!(bitmap, [false, false, false, true, true, false]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If `cfg.length` is `RunLength::Fixed` with a width of zero or more than
+    /// 63.
+    pub fn read_rle(
+        &mut self,
+        cfg: RleConfig,
+        max_bits: u64,
+        out: &mut Vec<bool>,
+    ) -> io::Result<u64> {
+        if let RunLength::Fixed(nbits) = cfg.length {
+            assert!(
+                (1..64).contains(&nbits),
+                "RLE run length width must be from 1 to 63"
+            );
+        }
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "read_rle", start);
+
+        let total = match cfg.end {
+            RleEnd::Count => {
+                let total = self.read_stream_gamma().map_err(wrap)? - 1;
+                if total > max_bits {
+                    return Err(too_long(start, total));
+                }
+                Some(total)
+            }
+            RleEnd::Terminator => None,
+        };
+        let mut color = cfg.first;
+        let mut read = 0;
+        let mut zero_run = false;
+        while total.is_none_or(|total| read < total) {
+            let at = self.position;
+            let run = self.read_run(cfg.length).map_err(wrap)?;
+            if total.is_some_and(|total| run > total - read) {
+                return Err(
+                    Error::new(io::ErrorKind::InvalidData, "read_rle", "run past end")
+                        .at(Some(at))
+                        .with_value(run)
+                        .into(),
+                );
+            }
+            if run > max_bits - read {
+                return Err(too_long(Some(at), run));
+            }
+            if run == 0 && zero_run && total.is_none() {
+                break;
+            }
+            zero_run = run == 0;
+            out.extend(core::iter::repeat_n(color, run as usize));
+            read += run;
+            color = !color;
+        }
+        Ok(read)
+    }
+
+    fn read_run(&mut self, length: RunLength) -> io::Result<u64> {
+        match length {
+            RunLength::Gamma => Ok(self.read_stream_gamma()? - 1),
+            RunLength::Fixed(nbits) => self.read_bits(nbits),
+        }
+    }
+
+    /// Read a gamma code written by `write_stream_gamma()`.
+    fn read_stream_gamma(&mut self) -> io::Result<u64> {
+        let start = Some(self.position);
+        let mut zeros = 0;
+        while self.read_bit()? == 0 {
+            zeros += 1;
+            if zeros > 63 {
+                return Err(
+                    Error::new(io::ErrorKind::InvalidData, "read_rle", "code too long")
+                        .at(start)
+                        .with_value(zeros)
+                        .into(),
+                );
+            }
+        }
+        let low = self.read_bits(zeros as u8)?;
+        let low = if O::MSB_FIRST || zeros == 0 {
+            low
+        } else {
+            low.reverse_bits() >> (64 - zeros)
+        };
+        Ok((1 << zeros) | low)
+    }
+}
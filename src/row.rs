@@ -0,0 +1,92 @@
+//! Rows of packed pixels, as in PBM bitmaps and low bit depth PNG scanlines.
+//!
+//! Each row holds pixels of 1, 2, 4 or 8 bits, the first in the first bits of
+//! the stream, and is padded up to a byte boundary so the next row starts at a
+//! fresh byte.
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+fn check_depth(bits_per_pixel: u8) {
+    assert!(
+        matches!(bits_per_pixel, 1 | 2 | 4 | 8),
+        "unsupported bits per pixel"
+    );
+}
+
+/// The number of bits needed to reach the next byte boundary from `position`.
+fn padding(position: u64) -> u8 {
+    ((8 - position % 8) % 8) as u8
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read a row of `width` pixels of `bits_per_pixel` bits each into the
+    /// start of `out`, one pixel per byte, then skip the padding up to the next
+    /// byte boundary, leaving the reader at the start of the next row.
+    ///
+    /// With `Msb0` this matches PBM and PNG, where the first pixel is in the
+    /// high bits of the first byte.  If the read fails the contents of `out`
+    /// are unspecified.
+    ///
+    /// Panics if `bits_per_pixel` isn't 1, 2, 4 or 8, or `out` holds fewer
+    /// than `width` pixels.
+    pub fn unpack_row(
+        &mut self,
+        bits_per_pixel: u8,
+        width: usize,
+        out: &mut [u8],
+    ) -> io::Result<()> {
+        check_depth(bits_per_pixel);
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "unpack_row", start);
+
+        for pixel in &mut out[..width] {
+            *pixel = self.read_bits(bits_per_pixel).map_err(wrap)? as u8;
+        }
+        self.read_bits(padding(self.position)).map_err(wrap)?;
+        Ok(())
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write a row of `pixels` of `bits_per_pixel` bits each, then pad up to
+    /// the next byte boundary as `flush()` would, so the next row starts at a
+    /// fresh byte.  Returns the number of bits written, including padding.
+    ///
+    /// A pixel too large for `bits_per_pixel` is an `InvalidInput` error
+    /// carrying its index, and nothing is written.  Panics if `bits_per_pixel`
+    /// isn't 1, 2, 4 or 8.
+    pub fn pack_row(&mut self, bits_per_pixel: u8, pixels: &[u8]) -> io::Result<usize> {
+        check_depth(bits_per_pixel);
+        let start = self.position;
+        if let Some(index) = pixels
+            .iter()
+            .position(|&pixel| (pixel as u16) >> bits_per_pixel != 0)
+        {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "pack_row",
+                "pixel out of range",
+            )
+            .at(Some(start))
+            .with_value(index as u64)
+            .into());
+        }
+
+        let wrap = |e| Error::wrap(e, "pack_row", Some(start));
+        for &pixel in pixels {
+            self.write_bits(bits_per_pixel, pixel as u64)
+                .map_err(wrap)?;
+        }
+        let padding = padding(self.position);
+        let fill = if self.pad_ones {
+            0xff >> (8 - padding)
+        } else {
+            0
+        };
+        self.write_bits(padding, fill).map_err(wrap)?;
+        Ok((self.position - start) as usize)
+    }
+}
@@ -0,0 +1,220 @@
+//! Structures which must take an exact number of bits.
+//!
+//! Specs often say a structure occupies exactly so many bits, whatever its
+//! fields add up to.  Reading or writing it in a scope checks that, so a
+//! parser or encoder which drifts fails at the structure which drifted
+//! rather than somewhere later in the stream.
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::{BitReader, BitWriter};
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Run `f` to read a structure of exactly `nbits` bits, checking it
+    /// consumed all of them.
+    ///
+    /// While `f` runs, the reader is limited to the end of the scope, so a
+    /// read past it fails there with `UnexpectedEof`, as at any limit.  If `f`
+    /// returns having consumed fewer than `nbits` bits it's an `InvalidData`
+    /// error at the start of the scope, with the number consumed as its value
+    /// and `nbits` as the value expected, and the reader is left wherever `f`
+    /// left it.  Any error from `f` is returned as it is.
+    ///
+    /// Scopes nest, each limited by the ones around it.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0x12, 0x34][..]);
+    /// let fields = reader
+    ///     .read_exact_scope(12, |r| Ok((r.read_bits(4)?, r.read_bits(8)?)))
+    ///     .unwrap();
+    /// assert_eq!(fields, (0x1, 0x23));
+    ///
+    /// // Reading two bits where there should be four.
+    /// assert!(reader.read_exact_scope(4, |r| r.read_bits(2)).is_err());
+    /// ```
+    pub fn read_exact_scope<T, F>(&mut self, nbits: u64, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Self) -> io::Result<T>,
+    {
+        let start = self.position;
+        let value = self.scope(nbits, f)?;
+        let consumed = self.position - start;
+        if consumed != nbits {
+            let message = if consumed < nbits {
+                "scope not fully read"
+            } else {
+                "scope overrun"
+            };
+            return Err(
+                Error::new(io::ErrorKind::InvalidData, "read_exact_scope", message)
+                    .at(Some(start))
+                    .with_value(consumed)
+                    .with_expected(nbits)
+                    .into(),
+            );
+        }
+        Ok(value)
+    }
+
+    /// Run `f` to read a structure of `nbits` bits as for
+    /// `read_exact_scope()`, skipping any bits it didn't consume, as for
+    /// later versions of a format which extend a structure with fields an
+    /// older reader doesn't know about.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0x12, 0x34][..]);
+    /// let version = reader.read_exact_scope_lenient(12, |r| r.read_bits(4)).unwrap();
+    /// assert_eq!(version, 0x1);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0x4);
+    /// ```
+    pub fn read_exact_scope_lenient<T, F>(&mut self, nbits: u64, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Self) -> io::Result<T>,
+    {
+        let start = self.position;
+        let value = self.scope(nbits, f)?;
+        let consumed = self.position - start;
+        if consumed > nbits {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "read_exact_scope_lenient",
+                "scope overrun",
+            )
+            .at(Some(start))
+            .with_value(consumed)
+            .with_expected(nbits)
+            .into());
+        }
+        self.skip_bits(nbits - consumed)
+            .map_err(|e| Error::wrap(e, "read_exact_scope_lenient", Some(start)))?;
+        Ok(value)
+    }
+
+    /// Run `f` limited to the next `nbits` bits, restoring the limit after.
+    fn scope<T, F>(&mut self, nbits: u64, f: F) -> io::Result<T>
+    where
+        F: FnOnce(&mut Self) -> io::Result<T>,
+    {
+        let end = self.position.saturating_add(nbits);
+        let parent_limit = self.limit;
+        self.limit = Some(parent_limit.map_or(end, |limit| limit.min(end)));
+        let result = f(self);
+        self.limit = parent_limit;
+        result
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Run `f` to write a structure of exactly `nbits` bits, checking it
+    /// wrote all of them.
+    ///
+    /// While `f` runs, the writer is limited to the end of the scope, so a
+    /// write past it fails there with `WriteZero`, as at any limit, and
+    /// writes nothing.  If `f` returns having written fewer than `nbits` bits
+    /// it's an `InvalidInput` error at the start of the scope, with the
+    /// number written as its value and `nbits` as the value expected.  Any
+    /// error from `f` is returned as it is.
+    ///
+    /// Scopes nest, each limited by the ones around it.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer
+    ///     .write_exact_scope(12, |w| {
+    ///         w.write_bits(4, 0x1)?;
+    ///         w.write_bits(8, 0x23)?;
+    ///         Ok(())
+    ///     })
+    ///     .unwrap();
+    ///
+    /// // Writing two bits where there should be four.
+    /// assert!(writer.write_exact_scope(4, |w| w.write_bits(2, 0).map(|_| ())).is_err());
+    /// ```
+    pub fn write_exact_scope<F>(&mut self, nbits: u64, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        let start = self.position;
+        self.scope(nbits, f)?;
+        let written = self.position - start;
+        if written != nbits {
+            let message = if written < nbits {
+                "scope not fully written"
+            } else {
+                "scope overrun"
+            };
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_exact_scope", message)
+                    .at(Some(start))
+                    .with_value(written)
+                    .with_expected(nbits)
+                    .into(),
+            );
+        }
+        Ok(())
+    }
+
+    /// Run `f` to write a structure of `nbits` bits as for
+    /// `write_exact_scope()`, filling any bits it didn't write with `bit`, as
+    /// for reserved bits a spec says shall be zero.  Padding past any limit
+    /// around the scope is a `WriteZero` error.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer
+    ///     .write_exact_scope_padded(16, 1, |w| w.write_bits(4, 0).map(|_| ()))
+    ///     .unwrap();
+    /// assert_eq!(writer.into_inner(), [0x0f, 0xff]);
+    /// ```
+    pub fn write_exact_scope_padded<F>(&mut self, nbits: u64, bit: u8, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        assert!(bit <= 1);
+        let start = self.position;
+        self.scope(nbits, f)?;
+        let written = self.position - start;
+        if written > nbits {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_exact_scope_padded",
+                "scope overrun",
+            )
+            .at(Some(start))
+            .with_value(written)
+            .with_expected(nbits)
+            .into());
+        }
+        let fill = if bit == 0 { 0 } else { u64::MAX };
+        let mut remaining = nbits - written;
+        while remaining > 0 {
+            let chunk = core::cmp::min(remaining, 64) as u8;
+            self.write_bits(chunk, fill)
+                .map_err(|e| Error::wrap(e, "write_exact_scope_padded", Some(start)))?;
+            remaining -= chunk as u64;
+        }
+        Ok(())
+    }
+
+    /// Run `f` limited to the next `nbits` bits, restoring the limit after.
+    fn scope<F>(&mut self, nbits: u64, f: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut Self) -> io::Result<()>,
+    {
+        let end = self.position.saturating_add(nbits);
+        let parent_limit = self.limit;
+        self.limit = Some(parent_limit.map_or(end, |limit| limit.min(end)));
+        let result = f(self);
+        self.limit = parent_limit;
+        result
+    }
+}
@@ -0,0 +1,349 @@
+//! Scrambling, XORing a keystream such as an LFSR sequence onto the data bits
+//! for energy dispersal or whitening.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// A source of keystream bits for a `ScrambleBitWriter` or
+/// `DescrambleBitReader`.
+///
+/// It's implemented by `Lfsr`, and by any `FnMut() -> u8` closure returning
+/// zero or one, for which `reset()` does nothing.
+pub trait Keystream {
+    /// The next bit of the keystream, zero or one.
+    fn next_bit(&mut self) -> u8;
+
+    /// Go back to the start of the keystream.
+    fn reset(&mut self) {}
+}
+
+impl<F: FnMut() -> u8> Keystream for F {
+    fn next_bit(&mut self) -> u8 {
+        self() & 1
+    }
+}
+
+/// A linear feedback shift register in Fibonacci form, generating a
+/// pseudo-random binary sequence.
+///
+/// The register has one stage for each degree of its polynomial.  Every bit,
+/// the stages given by the polynomial's terms are XORed together to make the
+/// output, which is fed back into the first stage as the others shift along.
+///
+/// ```
+/// use bitrw::{Keystream, Lfsr};
+///
+/// // 1 + x^14 + x^15, as used for energy dispersal by DVB.
+/// let mut lfsr = Lfsr::new(1 << 15 | 1 << 14 | 1, 0b000_0000_1010_1001);
+/// assert_eq!(lfsr, Lfsr::dvb());
+/// let first: Vec<u8> = (0..8).map(|_| lfsr.next_bit()).collect();
+/// assert_eq!(first, [0, 0, 0, 0, 0, 0, 1, 1]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Lfsr {
+    taps: u64,
+    mask: u64,
+    seed: u64,
+    state: u64,
+}
+
+impl Lfsr {
+    /// A register for `polynomial`, with bit `k` set for each term `x^k`, of
+    /// degree 1 to 63, starting from `seed`, with bit `k - 1` giving the
+    /// initial value of stage `k`.
+    ///
+    /// # Panics
+    ///
+    /// If the polynomial's degree is zero or more than 63, or the seed has
+    /// bits for stages past it.
+    pub fn new(polynomial: u64, seed: u64) -> Self {
+        let degree = 63 - polynomial.leading_zeros().min(63);
+        assert!(
+            (1..64).contains(&degree),
+            "LFSR polynomial degree must be from 1 to 63"
+        );
+        let mask = (1 << degree) - 1;
+        assert!(seed & !mask == 0, "LFSR seed has bits past the last stage");
+        Self {
+            taps: polynomial >> 1,
+            mask,
+            seed,
+            state: seed,
+        }
+    }
+
+    /// The register used by DVB for energy dispersal, `1 + x^14 + x^15` with
+    /// the initial sequence `100101010000000` in stages 1 to 15.
+    pub fn dvb() -> Self {
+        Self::new(1 << 15 | 1 << 14 | 1, 0b000_0000_1010_1001)
+    }
+
+    /// The current contents of the register, in the form of the seed.
+    pub fn state(&self) -> u64 {
+        self.state
+    }
+}
+
+impl Keystream for Lfsr {
+    fn next_bit(&mut self) -> u8 {
+        let out = ((self.state & self.taps).count_ones() & 1) as u8;
+        self.state = (self.state << 1 | out as u64) & self.mask;
+        out
+    }
+
+    fn reset(&mut self) {
+        self.state = self.seed;
+    }
+}
+
+/// When a `ScrambleBitWriter` or `DescrambleBitReader` resets its keystream,
+/// besides calls to their `reset()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrambleReset {
+    /// Only on calls to `reset()`.
+    Manual,
+    /// After every so many data bits, counted from the last reset.
+    Every(u64),
+}
+
+/// The keystream and its reset policy, shared by the reader and writer.
+#[derive(Debug, Clone)]
+struct Scrambler<K> {
+    keystream: K,
+    reset: ScrambleReset,
+    since_reset: u64,
+}
+
+impl<K: Keystream> Scrambler<K> {
+    fn new(keystream: K, reset: ScrambleReset) -> Self {
+        if let ScrambleReset::Every(n) = reset {
+            assert!(n > 0, "scrambler reset interval must be at least one bit");
+        }
+        Self {
+            keystream,
+            reset,
+            since_reset: 0,
+        }
+    }
+
+    /// The next `nbits` of keystream, packed as a field in order `O`.
+    fn next<O: BitOrder>(&mut self, nbits: u8) -> u64 {
+        let mut bits = Accumulator::<O>::new();
+        for _ in 0..nbits {
+            if self.reset == ScrambleReset::Every(self.since_reset) {
+                self.reset();
+            }
+            bits.push(1, self.keystream.next_bit() as u64);
+            self.since_reset += 1;
+        }
+        bits.pop(nbits)
+    }
+
+    fn reset(&mut self) {
+        self.keystream.reset();
+        self.since_reset = 0;
+    }
+}
+
+/// Writes data bits XORed with a keystream, such as from an `Lfsr`.
+///
+/// ```
+/// use bitrw::{BitWriter, Lfsr, ScrambleBitWriter, ScrambleReset};
+///
+/// let mut writer = ScrambleBitWriter::new(
+///     BitWriter::new(Vec::new()),
+///     Lfsr::dvb(),
+///     ScrambleReset::Manual,
+/// );
+/// writer.write_bits(16, 0).unwrap();
+/// writer.reset();
+/// writer.write_bits(8, 0xff).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.into_inner().into_inner(), [0x03, 0xf6, 0xfc]);
+/// ```
+pub struct ScrambleBitWriter<W, K, O = Msb0> {
+    inner: BitWriter<W, O>,
+    scrambler: Scrambler<K>,
+    position: u64,
+}
+
+impl<W, K, O: BitOrder> fmt::Debug for ScrambleBitWriter<W, K, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ScrambleBitWriter")
+            .field("inner", &self.inner)
+            .field("reset", &self.scrambler.reset)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: io::Write, K: Keystream, O: BitOrder> ScrambleBitWriter<W, K, O> {
+    /// Wrap `inner`, XORing data bits with `keystream`, which is reset as
+    /// given by `reset`.
+    ///
+    /// # Panics
+    ///
+    /// If `reset` is `ScrambleReset::Every(0)`.
+    pub fn new(inner: BitWriter<W, O>, keystream: K, reset: ScrambleReset) -> Self {
+        Self {
+            inner,
+            scrambler: Scrambler::new(keystream, reset),
+            position: 0,
+        }
+    }
+
+    /// Write up to 64 data bits, scrambled.
+    ///
+    /// If the write would pass the inner writer's limit it fails without
+    /// using any keystream.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let inner = &self.inner;
+        if inner
+            .limit
+            .is_some_and(|limit| inner.position + nbits as u64 > limit)
+        {
+            return self.inner.write_bits(nbits, value);
+        }
+        let key = self.scrambler.next::<O>(nbits);
+        self.position += nbits as u64;
+        self.inner.write_bits(nbits, value ^ key)
+    }
+
+    /// Reset the keystream, so the next bit written is scrambled with its
+    /// first bit.
+    pub fn reset(&mut self) {
+        self.scrambler.reset();
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.  The padding isn't scrambled.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// The number of data bits written so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Bits written to it
+    /// directly aren't scrambled, and don't use any keystream.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `ScrambleBitWriter`, returning the inner writer.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, K: Keystream, O: BitOrder> BitWrite for ScrambleBitWriter<W, K, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        ScrambleBitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
+
+/// Reads data bits XORed with a keystream, undoing a `ScrambleBitWriter` with
+/// the same keystream and reset policy.
+///
+/// A failed read uses no keystream, so it can be retried.
+pub struct DescrambleBitReader<R, K, O = Msb0> {
+    inner: BitReader<R, O>,
+    scrambler: Scrambler<K>,
+    position: u64,
+}
+
+impl<R, K, O: BitOrder> fmt::Debug for DescrambleBitReader<R, K, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DescrambleBitReader")
+            .field("inner", &self.inner)
+            .field("reset", &self.scrambler.reset)
+            .field("position", &self.position)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: io::Read, K: Keystream, O: BitOrder> DescrambleBitReader<R, K, O> {
+    /// Wrap `inner`, XORing data bits with `keystream`, which is reset as
+    /// given by `reset`.
+    ///
+    /// # Panics
+    ///
+    /// If `reset` is `ScrambleReset::Every(0)`.
+    pub fn new(inner: BitReader<R, O>, keystream: K, reset: ScrambleReset) -> Self {
+        Self {
+            inner,
+            scrambler: Scrambler::new(keystream, reset),
+            position: 0,
+        }
+    }
+
+    /// Read up to 64 data bits, descrambled.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        let value = self.inner.read_bits(nbits)?;
+        self.position += nbits as u64;
+        Ok(value ^ self.scrambler.next::<O>(nbits))
+    }
+
+    /// Reset the keystream, so the next bit read is descrambled with its
+    /// first bit.
+    pub fn reset(&mut self) {
+        self.scrambler.reset();
+    }
+
+    /// The number of data bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Bits read from it
+    /// directly aren't descrambled, and don't use any keystream.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `DescrambleBitReader`, returning the inner reader.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, K: Keystream, O: BitOrder> BitRead for DescrambleBitReader<R, K, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        DescrambleBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
@@ -0,0 +1,144 @@
+//! Readers confined to a range of bits of a seekable stream.
+//!
+//! `BitReader::slice()` hands out a `BitSlice` over part of the stream, so an
+//! element of a container can be parsed on its own, positions within it
+//! starting from zero, without any way of reading past its end.  When the
+//! slice is finished or dropped the parent carries on from where it was.
+
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::{BitOrder, Msb0};
+use crate::traits::BitRead;
+use crate::BitReader;
+
+/// A view of bits `[start, start + len)` of a `BitReader`'s stream, created by
+/// `BitReader::slice()`.
+///
+/// Reading past the end of the range fails with `UnexpectedEof`, as if the
+/// stream ended there.  Not to be confused with bitvec's `BitSlice`, which is
+/// a view of bits in memory.
+#[derive(Debug)]
+pub struct BitSlice<'a, R: io::Read + io::Seek, O: BitOrder = Msb0> {
+    reader: &'a mut BitReader<R, O>,
+    start: u64,
+    end: u64,
+    parent_position: u64,
+    parent_limit: Option<u64>,
+    finished: bool,
+}
+
+impl<R: io::Read + io::Seek, O: BitOrder> BitReader<R, O> {
+    /// Create a reader over `len_bits` bits starting at bit `start_bit` of the
+    /// stream, as measured by `position()`.  The range must lie within any
+    /// limit set with `BitReaderBuilder::limit_bits()`, and reads are still
+    /// cut short by the end of the stream itself.
+    ///
+    /// This reader's position is restored when the slice is dropped, or by
+    /// `BitSlice::finish()`, which reports any error doing so.
+    pub fn slice(&mut self, start_bit: u64, len_bits: u64) -> io::Result<BitSlice<'_, R, O>> {
+        let end = match start_bit.checked_add(len_bits) {
+            Some(end) if self.limit.is_none_or(|limit| end <= limit) => end,
+            _ => {
+                return Err(
+                    Error::new(io::ErrorKind::InvalidInput, "slice", "range out of bounds")
+                        .at(Some(start_bit))
+                        .with_value(len_bits)
+                        .into(),
+                )
+            }
+        };
+
+        let parent_position = self.position;
+        let parent_limit = self.limit.take();
+        if let Err(e) = self.seek(SeekFrom::Start(start_bit)) {
+            self.limit = parent_limit;
+            return Err(Error::wrap(e, "slice", Some(start_bit)));
+        }
+        self.limit = Some(end);
+
+        Ok(BitSlice {
+            reader: self,
+            start: start_bit,
+            end,
+            parent_position,
+            parent_limit,
+            finished: false,
+        })
+    }
+}
+
+impl<'a, R: io::Read + io::Seek, O: BitOrder> BitSlice<'a, R, O> {
+    /// The length of the slice in bits.
+    pub fn len_bits(&self) -> u64 {
+        self.end - self.start
+    }
+
+    /// The number of bits consumed so far, relative to the start of the slice.
+    pub fn position(&self) -> u64 {
+        self.reader.position() - self.start
+    }
+
+    /// The number of bits left before the end of the slice.
+    pub fn remaining_bits(&self) -> u64 {
+        self.end - self.reader.position()
+    }
+
+    /// Create a nested slice of `len_bits` bits from `start_bit`, relative to
+    /// the start of this one.  It must lie within this slice, and restores this
+    /// slice's position when it's done.
+    pub fn slice(&mut self, start_bit: u64, len_bits: u64) -> io::Result<BitSlice<'_, R, O>> {
+        if start_bit
+            .checked_add(len_bits)
+            .is_none_or(|end| end > self.len_bits())
+        {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "slice", "range out of bounds")
+                    .at(Some(start_bit))
+                    .with_value(len_bits)
+                    .into(),
+            );
+        }
+        self.reader.slice(self.start + start_bit, len_bits)
+    }
+
+    /// Finish with the slice, restoring the parent's position.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.finished = true;
+        self.restore()
+    }
+
+    fn restore(&mut self) -> io::Result<()> {
+        self.reader.limit = None;
+        let result = self
+            .reader
+            .seek(SeekFrom::Start(self.parent_position))
+            .map(|_| ());
+        self.reader.limit = self.parent_limit;
+        result
+    }
+}
+
+impl<'a, R: io::Read + io::Seek, O: BitOrder> Drop for BitSlice<'a, R, O> {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = self.restore();
+        }
+    }
+}
+
+impl<'a, R: io::Read + io::Seek, O: BitOrder> BitRead for BitSlice<'a, R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        self.reader.read_bits(nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.reader.vlc_limit()
+    }
+}
@@ -0,0 +1,90 @@
+//! Inserting and removing bits in the middle of a bit-packed buffer.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::order::{BitOrder, Msb0};
+use crate::{copy_bits, BitReader, BitWriter};
+
+/// Replace bits `range` of the first `bit_len` bits of `buf` with the first
+/// `replacement_bits` bits of `replacement`, shifting everything after the
+/// range along, as for a field which grows or shrinks.  Bits are packed
+/// `Msb0`: see `splice_bits_with_order()`.
+///
+/// ```
+/// let mut buf = vec![0b1111_0000, 0b1010_0000];
+/// let mut bit_len = 12;
+/// // Replace the four one bits with three bits of 010.
+/// bitrw::splice_bits(&mut buf, &mut bit_len, 0..4, &[0b0100_0000], 3);
+/// assert_eq!(bit_len, 11);
+/// assert_eq!(buf, [0b0100_0001, 0b0100_0000]);
+/// ```
+///
+/// # Panics
+///
+/// If `range` isn't within `bit_len`, `bit_len` is longer than `buf`, or
+/// `replacement_bits` is longer than `replacement`.
+pub fn splice_bits(
+    buf: &mut Vec<u8>,
+    bit_len: &mut u64,
+    range: Range<u64>,
+    replacement: &[u8],
+    replacement_bits: u64,
+) {
+    splice_bits_with_order(buf, bit_len, range, replacement, replacement_bits, Msb0)
+}
+
+/// Replace bits `range` of the first `bit_len` bits of `buf` with the first
+/// `replacement_bits` bits of `replacement`, both packed in the given order,
+/// and update `bit_len` to match.
+///
+/// The bytes before the range are left alone, and everything from the byte
+/// it starts in is rewritten, a word at a time where the bits allow.
+/// Afterwards `buf` is exactly long enough for `bit_len` bits, with its last
+/// byte padded with zero bits.
+///
+/// # Panics
+///
+/// As for `splice_bits()`.
+pub fn splice_bits_with_order<O: BitOrder>(
+    buf: &mut Vec<u8>,
+    bit_len: &mut u64,
+    range: Range<u64>,
+    replacement: &[u8],
+    replacement_bits: u64,
+    order: O,
+) {
+    assert!(
+        range.start <= range.end && range.end <= *bit_len,
+        "splice range out of bounds"
+    );
+    assert!(
+        *bit_len <= buf.len() as u64 * 8,
+        "bit length longer than buffer"
+    );
+    assert!(
+        replacement_bits <= replacement.len() as u64 * 8,
+        "replacement bits longer than replacement"
+    );
+
+    let first = range.start / 8;
+    let tail = buf.split_off(first as usize);
+    let mut writer = BitWriter::with_order(core::mem::take(buf), order);
+    // Copies between slices and Vecs, within their lengths, can't fail.
+    let copy = |reader: &mut BitReader<&[u8], O>, writer: &mut BitWriter<Vec<u8>, O>, nbits| {
+        copy_bits(reader, writer, nbits).expect("in-memory copy failed");
+    };
+
+    let mut head = BitReader::with_order(&tail[..], O::default());
+    copy(&mut head, &mut writer, range.start - first * 8);
+    let mut inserted = BitReader::with_order(replacement, O::default());
+    copy(&mut inserted, &mut writer, replacement_bits);
+    let mut rest = BitReader::with_order(&tail[..], O::default());
+    rest.skip_bits(range.end - first * 8)
+        .expect("in-memory skip failed");
+    copy(&mut rest, &mut writer, *bit_len - range.end);
+
+    *bit_len = first * 8 + writer.position();
+    writer.flush().expect("in-memory flush failed");
+    *buf = writer.into_inner();
+}
@@ -0,0 +1,61 @@
+//! Scanning for MPEG start codes.
+//!
+//! MPEG-1/2/4 video and systems streams, and H.264/H.265 Annex B byte streams,
+//! are divided up by byte-aligned start codes: the prefix 00 00 01 followed by
+//! a byte identifying what comes next.
+
+use crate::error::Error;
+use crate::io;
+use crate::order::BitOrder;
+use crate::BitReader;
+
+/// A start code found by `BitReader::next_start_code()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StartCode {
+    /// The byte following the 00 00 01 prefix.
+    pub value: u8,
+    /// The number of bytes skipped over before the prefix, including
+    /// `leading_zeros`.
+    pub skipped: u64,
+    /// The number of zero bytes immediately before the prefix, such as the
+    /// `zero_byte` of a four byte H.264 start code, or MPEG zero stuffing.
+    pub leading_zeros: u64,
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Skip to the next byte boundary, then scan forward for a 00 00 01 start
+    /// code prefix, consuming it and the byte which follows.
+    ///
+    /// Fails with `UnexpectedEof` if the stream ends first, at which point
+    /// everything has been consumed.
+    pub fn next_start_code(&mut self) -> io::Result<StartCode> {
+        let start = Some(self.position);
+        let wrap = |e| Error::wrap(e, "next_start_code", start);
+
+        let misalignment = (self.position % 8) as u8;
+        if misalignment > 0 {
+            self.read_bits(8 - misalignment).map_err(wrap)?;
+        }
+
+        let mut skipped = 0;
+        let mut zeros = 0;
+        loop {
+            // Aligned reads of 8 bits give the bytes as they are, in either order.
+            match self.read_bits(8).map_err(wrap)? {
+                0 => zeros += 1,
+                1 if zeros >= 2 => {
+                    let value = self.read_bits(8).map_err(wrap)? as u8;
+                    return Ok(StartCode {
+                        value,
+                        skipped: skipped + zeros - 2,
+                        leading_zeros: zeros - 2,
+                    });
+                }
+                _ => {
+                    skipped += zeros + 1;
+                    zeros = 0;
+                }
+            }
+        }
+    }
+}
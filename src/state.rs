@@ -0,0 +1,194 @@
+//! Saving and restoring the position of a reader or writer, so a long-running
+//! job can checkpoint its progress and carry on later, even in another process.
+//!
+//! A saved state is the bit position plus any bits buffered but not yet
+//! consumed or written.  Resuming seeks a fresh inner stream to the byte
+//! containing that position and restores the bit phase.  With the `serde`
+//! feature the states can be serialized.
+//!
+//! Positions are measured from the start of the inner stream, so the original
+//! reader or writer must have started there, or have been positioned with
+//! `BitReader::seek()`.
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io::{self, SeekFrom};
+use crate::order::{BitOrder, Msb0};
+use crate::{BitReader, BitWriter};
+
+/// The saved position of a `BitReader`, from `BitReader::save_state()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReaderState {
+    position: u64,
+    pending: u128,
+    pending_len: u8,
+}
+
+impl ReaderState {
+    /// The bit position of the next read.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The bits following `position()` which had already been loaded from the
+    /// stream, or pushed back with `unread_bits()`, as `(count, value)`.
+    /// There are up to 127 of them, packed as `read_bits()` would return them
+    /// if it could return that many, as for `ReaderParts::pending()`.
+    pub fn pending(&self) -> (u8, u128) {
+        (self.pending_len, self.pending)
+    }
+}
+
+/// The saved position of a `BitWriter`, from `BitWriter::save_state()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WriterState {
+    position: u64,
+    pending: u8,
+    pending_len: u8,
+}
+
+impl WriterState {
+    /// The bit position of the next write.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The bits of the current partial byte which have been written but not
+    /// yet passed on to the stream, as `(count, value)`.
+    pub fn pending(&self) -> (u8, u8) {
+        (self.pending_len, self.pending)
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Save the current position, for `resume()`.
+    pub fn save_state(&self) -> ReaderState {
+        ReaderState {
+            position: self.position,
+            pending: self.bits.value(),
+            pending_len: self.bits.len(),
+        }
+    }
+}
+
+impl<R: io::Read + io::Seek> BitReader<R> {
+    /// Recreate a reader from a saved state, as for `resume_with_order()`.
+    pub fn resume(inner: R, state: &ReaderState) -> io::Result<Self> {
+        Self::resume_with_order(inner, state, Msb0)
+    }
+}
+
+impl<R: io::Read + io::Seek, O: BitOrder> BitReader<R, O> {
+    /// Recreate a reader from a saved state, seeking `inner` to the saved
+    /// position.
+    ///
+    /// The bits which were buffered when the state was saved are read again
+    /// and compared, so resuming over different data is an `InvalidData`
+    /// error rather than silently misreading.  A state which couldn't have
+    /// been saved, with more than 127 pending bits or pending bits set past
+    /// their length, is an `InvalidInput` error.
+    pub fn resume_with_order(inner: R, state: &ReaderState, order: O) -> io::Result<Self> {
+        let start = Some(state.position);
+        if state.pending_len > 127 || state.pending >> state.pending_len != 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "resume",
+                "inconsistent saved state",
+            )
+            .at(start)
+            .with_value(state.pending_len as u64)
+            .into());
+        }
+
+        let mut reader = Self::with_order(inner, order);
+        let wrap = |e| Error::wrap(e, "resume", start);
+
+        reader
+            .inner
+            .seek(SeekFrom::Start(state.position / 8))
+            .map_err(wrap)?;
+        reader.read_bits((state.position % 8) as u8).map_err(wrap)?;
+
+        if state.pending_len > 0 {
+            let mut pending = Accumulator::<O>::new();
+            let mut left = state.pending_len;
+            while left > 0 {
+                let chunk = core::cmp::min(left, 64);
+                pending.push(chunk, reader.read_bits(chunk).map_err(wrap)?);
+                left -= chunk;
+            }
+            if pending.value() != state.pending {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "resume",
+                    "stream doesn't match saved state",
+                )
+                .at(start)
+                .with_value(pending.value() as u64)
+                .into());
+            }
+
+            // Put them back in front of anything else loaded with them, which
+            // is less than a byte.
+            let rest_len = reader.bits.len();
+            pending.push(rest_len, reader.bits.pop(rest_len));
+            reader.bits = pending;
+        }
+
+        reader.position = state.position;
+        Ok(reader)
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write out every complete byte and flush the inner writer, then save the
+    /// current position, for `resume()`.
+    ///
+    /// No padding is written: the bits of any partial byte are kept in the
+    /// state instead.
+    pub fn save_state(&mut self) -> io::Result<WriterState> {
+        self.write_buffered()?;
+        self.inner.flush()?;
+
+        let mut bits = self.bits;
+        let pending_len = bits.len();
+        Ok(WriterState {
+            position: self.position,
+            pending: bits.pop(pending_len) as u8,
+            pending_len,
+        })
+    }
+}
+
+impl<W: io::Write + io::Seek> BitWriter<W> {
+    /// Recreate a writer from a saved state, as for `resume_with_order()`.
+    pub fn resume(inner: W, state: &WriterState) -> io::Result<Self> {
+        Self::resume_with_order(inner, state, Msb0)
+    }
+}
+
+impl<W: io::Write + io::Seek, O: BitOrder> BitWriter<W, O> {
+    /// Recreate a writer from a saved state, seeking `inner` to the byte
+    /// containing the saved position and restoring any partial byte, so
+    /// writing carries on exactly where it left off.
+    pub fn resume_with_order(mut inner: W, state: &WriterState, order: O) -> io::Result<Self> {
+        if state.pending_len as u64 != state.position % 8 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "resume",
+                "inconsistent saved state",
+            )
+            .at(Some(state.position))
+            .into());
+        }
+
+        inner.seek(SeekFrom::Start(state.position / 8))?;
+
+        let mut writer = Self::with_order(inner, order);
+        writer.bits.push(state.pending_len, state.pending as u64);
+        writer.position = state.position;
+        Ok(writer)
+    }
+}
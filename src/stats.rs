@@ -0,0 +1,304 @@
+//! Counting where the bits go: how many fields of each width are read or
+//! written, and the balance of ones and zeros among them.
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use crate::io;
+use crate::traits::{BitRead, BitWrite};
+
+/// Counters kept by a `StatsBitReader` or `StatsBitWriter`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Stats {
+    // The number of fields of each width, from 0 to 64 bits.
+    widths: [u64; 65],
+    bits: u64,
+    ones: u64,
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self {
+            widths: [0; 65],
+            bits: 0,
+            ones: 0,
+        }
+    }
+}
+
+impl Stats {
+    /// Empty counters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&mut self, nbits: u8, value: u64) {
+        let value = if nbits == 64 {
+            value
+        } else {
+            value & ((1 << nbits) - 1)
+        };
+        self.widths[nbits as usize] += 1;
+        self.bits += nbits as u64;
+        self.ones += value.count_ones() as u64;
+    }
+
+    /// The number of fields of `nbits` bits.
+    ///
+    /// # Panics
+    ///
+    /// If `nbits` is more than 64.
+    pub fn fields_of_width(&self, nbits: u8) -> u64 {
+        self.widths[nbits as usize]
+    }
+
+    /// The number of fields of each width which occurred, narrowest first, as
+    /// `(nbits, count)` pairs.
+    pub fn widths(&self) -> impl Iterator<Item = (u8, u64)> + '_ {
+        self.widths
+            .iter()
+            .enumerate()
+            .filter(|&(_, &count)| count > 0)
+            .map(|(nbits, &count)| (nbits as u8, count))
+    }
+
+    /// The total number of fields.
+    pub fn fields(&self) -> u64 {
+        self.widths.iter().sum()
+    }
+
+    /// The total number of bits.
+    pub fn bits(&self) -> u64 {
+        self.bits
+    }
+
+    /// The number of one bits.
+    pub fn ones(&self) -> u64 {
+        self.ones
+    }
+
+    /// The number of zero bits.
+    pub fn zeros(&self) -> u64 {
+        self.bits - self.ones
+    }
+
+    /// The order-0 entropy of the bits, in bits per bit: 1.0 if ones and zeros
+    /// are equally common, down to 0.0 if they're all the same.  This is the
+    /// best a coder which sees each bit on its own could compress them to.
+    #[cfg(feature = "std")]
+    pub fn entropy(&self) -> f64 {
+        if self.ones == 0 || self.ones == self.bits {
+            return 0.0;
+        }
+        let p = self.ones as f64 / self.bits as f64;
+        -(p * p.log2() + (1.0 - p) * (1.0 - p).log2())
+    }
+
+    /// A summary of the counters, as printed by `Display`.
+    #[cfg(feature = "alloc")]
+    pub fn report(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Summarises the counters over several lines: the totals, the entropy with
+/// the `std` feature, then the count for each width used.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} bits in {} fields: {} ones, {} zeros",
+            self.bits,
+            self.fields(),
+            self.ones,
+            self.zeros()
+        )?;
+        #[cfg(feature = "std")]
+        writeln!(f, "entropy {:.4} bits per bit", self.entropy())?;
+        for (nbits, count) in self.widths() {
+            writeln!(f, "{:2} bits: {}", nbits, count)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        struct Widths<'a>(&'a Stats);
+
+        impl fmt::Debug for Widths<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_map().entries(self.0.widths()).finish()
+            }
+        }
+
+        f.debug_struct("Stats")
+            .field("bits", &self.bits)
+            .field("ones", &self.ones)
+            .field("widths", &Widths(self))
+            .finish()
+    }
+}
+
+/// Wraps a `BitRead`, counting the fields read through it.
+///
+/// Only reads which succeed are counted.  A higher-level read is counted as
+/// the fields it's made of, so `read_gamma()` counts a run of single bits
+/// then the value.
+///
+/// ```
+/// use bitrw::{BitRead, BitReader, StatsBitReader};
+///
+/// let mut reader = StatsBitReader::new(BitReader::new(&[0xf0, 0x0f][..]));
+/// reader.read_bits(4).unwrap();
+/// reader.read::<u8>().unwrap();
+/// assert_eq!(reader.stats().bits(), 12);
+/// assert_eq!(reader.stats().ones(), 4);
+/// ```
+#[derive(Debug)]
+pub struct StatsBitReader<R> {
+    inner: R,
+    stats: Stats,
+}
+
+impl<R: BitRead> StatsBitReader<R> {
+    /// Wrap `inner`, with empty counters.
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            stats: Stats::new(),
+        }
+    }
+
+    /// The counters so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Start counting again, returning the counters so far.
+    pub fn reset_stats(&mut self) -> Stats {
+        core::mem::take(&mut self.stats)
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Reads from it directly
+    /// aren't counted.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this `StatsBitReader`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead> BitRead for StatsBitReader<R> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        let value = self.inner.read_bits(nbits)?;
+        self.stats.record(nbits, value);
+        Ok(value)
+    }
+
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<u8> {
+        let bit = self.inner.read_bit()?;
+        self.stats.record(1, bit as u64);
+        Ok(bit)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        self.inner.bit_position()
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Wraps a `BitWrite`, counting the fields written through it.
+///
+/// Only writes which succeed are counted, and as for `StatsBitReader` a
+/// higher-level write is counted as the fields it's made of.
+///
+/// ```
+/// use bitrw::{BitWrite, BitWriter, StatsBitWriter};
+///
+/// let mut writer = StatsBitWriter::new(BitWriter::new(Vec::new()));
+/// writer.write_bits(3, 0b101).unwrap();
+/// writer.write_bits(3, 0b001).unwrap();
+/// writer.write_bit(0).unwrap();
+/// let stats = writer.stats();
+/// assert_eq!(stats.fields_of_width(3), 2);
+/// assert_eq!((stats.ones(), stats.zeros()), (3, 4));
+/// ```
+#[derive(Debug)]
+pub struct StatsBitWriter<W> {
+    inner: W,
+    stats: Stats,
+}
+
+impl<W: BitWrite> StatsBitWriter<W> {
+    /// Wrap `inner`, with empty counters.
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            stats: Stats::new(),
+        }
+    }
+
+    /// The counters so far.
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Start counting again, returning the counters so far.
+    pub fn reset_stats(&mut self) -> Stats {
+        core::mem::take(&mut self.stats)
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Writes to it directly
+    /// aren't counted.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap this `StatsBitWriter`, returning the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: BitWrite> BitWrite for StatsBitWriter<W> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        let written = self.inner.write_bits(nbits, value)?;
+        self.stats.record(nbits, value);
+        Ok(written)
+    }
+
+    #[inline]
+    fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        self.inner.write_bit(bit)?;
+        self.stats.record(1, bit as u64);
+        Ok(())
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        self.inner.bit_position()
+    }
+}
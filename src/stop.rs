@@ -0,0 +1,59 @@
+//! Ending a segment with a stop bit: a one bit, then zero bits up to the next
+//! byte boundary, as in JPEG 2000 packet headers, CABAC termination and the
+//! RBSP trailing bits of H.264.
+//!
+//! The one bit marks where the payload ends, so a reader can find its length
+//! by scanning back from the end for the last one bit.
+
+use crate::io;
+use crate::order::BitOrder;
+use crate::BitWriter;
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write a stop bit and zero bits up to the next byte boundary, whatever
+    /// the padding set with `BitWriterBuilder`, returning the number of bits
+    /// written.  If the writer is already aligned this is a whole byte, so
+    /// it's always from 1 to 8.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_bits(5, 0b10110).unwrap();
+    /// assert_eq!(writer.write_stop_and_align().unwrap(), 3);
+    /// assert_eq!(writer.write_stop_and_align().unwrap(), 8);
+    /// assert_eq!(writer.into_inner(), [0b10110_100, 0b1000_0000]);
+    /// ```
+    pub fn write_stop_and_align(&mut self) -> io::Result<u8> {
+        let padding = (7 - self.position % 8) as u8;
+        // The stop bit is the first of the field in stream order.
+        let stop = if O::MSB_FIRST { 1 << padding } else { 1 };
+        self.write_bits(1 + padding, stop)?;
+        Ok(1 + padding)
+    }
+}
+
+/// The length in bits of the stop bit and padding at the end of `bytes`, as
+/// written by `BitWriter::write_stop_and_align()`, so the payload is the first
+/// `bytes.len() * 8 - trailing_stop_len(..)` bits.
+///
+/// Any zero bytes after the stop bit are counted as padding.  If there's no
+/// one bit at all there's no stop bit, and this returns `None`.
+///
+/// ```
+/// use bitrw::{trailing_stop_len, Msb0};
+///
+/// assert_eq!(trailing_stop_len(&[0b10110_100], Msb0), Some(3));
+/// assert_eq!(trailing_stop_len(&[0b10110_100, 0x80], Msb0), Some(8));
+/// assert_eq!(trailing_stop_len(&[0x00], Msb0), None);
+/// ```
+pub fn trailing_stop_len<O: BitOrder>(bytes: &[u8], _order: O) -> Option<u64> {
+    let last = bytes.iter().rposition(|&byte| byte != 0)?;
+    let byte = bytes[last];
+    let zeros = if O::MSB_FIRST {
+        byte.trailing_zeros()
+    } else {
+        byte.leading_zeros()
+    };
+    Some((bytes.len() - 1 - last) as u64 * 8 + zeros as u64 + 1)
+}
@@ -0,0 +1,382 @@
+//! Bit stuffing, as used on CAN and HDLC links.
+//!
+//! To keep the receiver's clock in sync, and to keep data from looking like a
+//! flag, the transmitter inserts a stuffing bit after every run of five bits:
+//! on CAN the complement of five identical bits, on HDLC a zero after five
+//! ones.  The receiver deletes them again, and six bits in a row where a
+//! stuffing bit was due is a stuffing violation, which is how both protocols
+//! signal errors and, on HDLC, flags and aborts.
+
+use core::fmt;
+
+use crate::accumulator::Accumulator;
+use crate::error::Error;
+use crate::io;
+use crate::order::{BitOrder, Msb0};
+use crate::traits::{BitRead, BitWrite};
+use crate::{BitReader, BitWriter};
+
+/// The length of run after which a stuffing bit is inserted.
+const RUN: u8 = 5;
+
+/// Which runs of bits are broken up by stuffing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StuffRule {
+    /// After five identical bits, the complement is inserted, as on CAN.  The
+    /// stuffing bit counts towards the next run.
+    Identical,
+    /// After five one bits, a zero is inserted, as on HDLC.
+    Ones,
+}
+
+/// The run of bits seen so far on the wire.
+#[derive(Debug, Clone, Copy)]
+struct Run {
+    rule: StuffRule,
+    last: u8,
+    len: u8,
+}
+
+impl Run {
+    fn new(rule: StuffRule) -> Self {
+        Self {
+            rule,
+            last: 0,
+            len: 0,
+        }
+    }
+
+    /// Count a bit sent or received, returning whether a stuffing bit must
+    /// follow it.
+    fn push(&mut self, bit: u8) -> bool {
+        match self.rule {
+            StuffRule::Identical if bit == self.last => self.len += 1,
+            StuffRule::Identical => {
+                self.last = bit;
+                self.len = 1;
+            }
+            StuffRule::Ones if bit == 1 => self.len += 1,
+            StuffRule::Ones => self.len = 0,
+        }
+        self.len == RUN
+    }
+
+    /// The stuffing bit due after a full run.
+    fn stuffing(&self) -> u8 {
+        match self.rule {
+            StuffRule::Identical => self.last ^ 1,
+            StuffRule::Ones => 0,
+        }
+    }
+
+    /// Start a new run with the stuffing bit.
+    fn stuffed(&mut self) {
+        let bit = self.stuffing();
+        self.len = 0;
+        self.push(bit);
+    }
+}
+
+/// Reads bits from a bit-stuffed stream, deleting each stuffing bit.
+///
+/// A bit where a stuffing bit was due which isn't one is an `InvalidData`
+/// error from the `"unstuff"` operation, at the offset of the bit on the wire.
+/// That bit is consumed, and the run starts again, so reading can carry on by
+/// way of `get_mut()` to deal with a flag or the end of a frame.
+///
+/// ```
+/// use bitrw::{BitRead, BitReader, BitUnstuffReader, StuffRule};
+///
+/// // Five zeros, a stuffing one, then 1011 and a zero of padding.
+/// let raw = BitReader::new(&[0b00000_1_10, 0b11_000000][..]);
+/// let mut reader = BitUnstuffReader::new(raw, StuffRule::Identical);
+/// assert_eq!(reader.read_bits(9).unwrap(), 0b00000_1011);
+/// assert_eq!(reader.raw_position(), 10);
+/// ```
+pub struct BitUnstuffReader<R, O = Msb0> {
+    inner: BitReader<R, O>,
+    run: Run,
+    stuffing_due: bool,
+    // Bits unstuffed for a read which failed, to be returned first next time.
+    held: Accumulator<O>,
+    position: u64,
+    stuffed: u64,
+}
+
+impl<R, O: BitOrder> fmt::Debug for BitUnstuffReader<R, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitUnstuffReader")
+            .field("inner", &self.inner)
+            .field("run", &self.run)
+            .field("position", &self.position)
+            .field("stuffed", &self.stuffed)
+            .finish()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitUnstuffReader<R, O> {
+    /// Wrap `inner`, which should be at the start of the stuffed part of a
+    /// frame.
+    pub fn new(inner: BitReader<R, O>, rule: StuffRule) -> Self {
+        Self {
+            inner,
+            run: Run::new(rule),
+            stuffing_due: false,
+            held: Accumulator::new(),
+            position: 0,
+            stuffed: 0,
+        }
+    }
+
+    /// Read up to 64 bits of unstuffed data.
+    ///
+    /// If the read fails, any bits it unstuffed are kept to be returned by the
+    /// next read, so as with `BitReader::read_bits()` a read can be retried
+    /// after an error such as `WouldBlock`.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        assert!(nbits <= 64);
+        while self.held.len() < nbits {
+            let bit = self.next_bit()?;
+            self.held.push(1, bit as u64);
+        }
+        self.position += nbits as u64;
+        Ok(self.held.pop(nbits))
+    }
+
+    fn next_bit(&mut self) -> io::Result<u8> {
+        if self.stuffing_due {
+            self.take_stuffing()?;
+        }
+        let bit = self.inner.read_bit()?;
+        self.stuffing_due = self.run.push(bit);
+        Ok(bit)
+    }
+
+    /// Read and check the stuffing bit which is due.
+    fn take_stuffing(&mut self) -> io::Result<()> {
+        let start = Some(self.inner.position());
+        let bit = self.inner.read_bit()?;
+        self.stuffing_due = false;
+        if bit != self.run.stuffing() {
+            self.run = Run::new(self.run.rule);
+            return Err(
+                Error::new(io::ErrorKind::InvalidData, "unstuff", "stuffing violation")
+                    .at(start)
+                    .with_value(bit as u64)
+                    .into(),
+            );
+        }
+        self.run.stuffed();
+        self.stuffed += 1;
+        Ok(())
+    }
+
+    /// Consume the stuffing bit due after the last bit read, if there is one,
+    /// returning whether there was.  A CAN frame's CRC is followed by one if
+    /// its last five bits are identical, so this should be called at the end
+    /// of the stuffed part of a frame, before reading the rest with
+    /// `get_mut()`.
+    pub fn end_stuffing(&mut self) -> io::Result<bool> {
+        if !self.stuffing_due {
+            return Ok(false);
+        }
+        self.take_stuffing()?;
+        Ok(true)
+    }
+
+    /// Forget the current run, as at the start of a new frame.
+    pub fn reset_run(&mut self) {
+        self.run = Run::new(self.run.rule);
+        self.stuffing_due = false;
+    }
+
+    /// The number of unstuffed bits read so far.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The position of the inner reader, counting every bit on the wire
+    /// including stuffing bits and any unstuffed bits held from a failed read.
+    pub fn raw_position(&self) -> u64 {
+        self.inner.position()
+    }
+
+    /// The number of stuffing bits deleted so far.
+    pub fn stuffed_bits(&self) -> u64 {
+        self.stuffed
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &BitReader<R, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader, for reading unstuffed
+    /// bits such as a CAN frame's trailer or an HDLC flag.  Call `reset_run()`
+    /// before reading stuffed bits again.
+    pub fn get_mut(&mut self) -> &mut BitReader<R, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `BitUnstuffReader`, returning the inner reader.  Any bits
+    /// held from a failed read are lost.
+    pub fn into_inner(self) -> BitReader<R, O> {
+        self.inner
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for BitUnstuffReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        BitUnstuffReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Writes bits to a bit-stuffed stream, inserting a stuffing bit after each
+/// run of five, as read by `BitUnstuffReader`.
+///
+/// A stuffing bit is written as soon as a run is complete, so one due after
+/// the last bit of a frame's stuffed part is written with it, as CAN requires.
+/// Flags and other intentionally unstuffed bits are written with
+/// `write_raw_bits()`.
+///
+/// ```
+/// use bitrw::{BitStuffWriter, BitWriter, StuffRule};
+///
+/// let mut writer = BitStuffWriter::new(BitWriter::new(Vec::new()), StuffRule::Ones);
+/// writer.write_raw_bits(8, 0x7e).unwrap();
+/// writer.write_bits(8, 0xff).unwrap();
+/// writer.write_raw_bits(8, 0x7e).unwrap();
+/// writer.flush().unwrap();
+/// assert_eq!(writer.stuffed_bits(), 1);
+/// assert_eq!(
+///     writer.into_inner().into_inner(),
+///     [0x7e, 0b11111_0_11, 0b1_0111111, 0b0_0000000]
+/// );
+/// ```
+pub struct BitStuffWriter<W, O = Msb0> {
+    inner: BitWriter<W, O>,
+    run: Run,
+    position: u64,
+    stuffed: u64,
+}
+
+impl<W, O: BitOrder> fmt::Debug for BitStuffWriter<W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitStuffWriter")
+            .field("inner", &self.inner)
+            .field("run", &self.run)
+            .field("position", &self.position)
+            .field("stuffed", &self.stuffed)
+            .finish()
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitStuffWriter<W, O> {
+    /// Wrap `inner`, which should be at the start of the stuffed part of a
+    /// frame.
+    pub fn new(inner: BitWriter<W, O>, rule: StuffRule) -> Self {
+        Self {
+            inner,
+            run: Run::new(rule),
+            position: 0,
+            stuffed: 0,
+        }
+    }
+
+    /// Write up to 64 bits of data, with stuffing bits as needed.
+    ///
+    /// If the inner writer fails part way, the bits before the failure have
+    /// been written and counted by `position()`.
+    pub fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        let mut bits = Accumulator::<O>::new();
+        bits.push(nbits, value);
+        while bits.len() > 0 {
+            let bit = bits.pop(1) as u8;
+            self.inner.write_bit(bit)?;
+            self.position += 1;
+            if self.run.push(bit) {
+                self.inner.write_bit(self.run.stuffing())?;
+                self.run.stuffed();
+                self.stuffed += 1;
+            }
+        }
+        Ok(nbits as usize)
+    }
+
+    /// Write up to 64 bits straight to the inner writer, without stuffing, as
+    /// for a flag or a CAN frame's trailer.  They don't count towards a run,
+    /// which starts again afterwards.
+    pub fn write_raw_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        let written = self.inner.write_bits(nbits, value)?;
+        self.reset_run();
+        Ok(written)
+    }
+
+    /// Forget the current run, as at the start of a new frame.
+    pub fn reset_run(&mut self) {
+        self.run = Run::new(self.run.rule);
+    }
+
+    /// Pad the inner writer to a byte boundary and flush it, as for
+    /// `BitWriter::flush()`.  The padding isn't stuffed.
+    pub fn flush(&mut self) -> io::Result<usize> {
+        self.inner.flush()
+    }
+
+    /// The number of data bits written so far, not counting stuffing bits or
+    /// raw bits.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// The position of the inner writer, counting every bit on the wire.
+    pub fn raw_position(&self) -> u64 {
+        self.inner.position()
+    }
+
+    /// The number of stuffing bits inserted so far.
+    pub fn stuffed_bits(&self) -> u64 {
+        self.stuffed
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &BitWriter<W, O> {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Bits written through it
+    /// aren't stuffed, and don't count towards a run.
+    pub fn get_mut(&mut self) -> &mut BitWriter<W, O> {
+        &mut self.inner
+    }
+
+    /// Unwrap this `BitStuffWriter`, returning the inner writer, which will
+    /// still need flushing.
+    pub fn into_inner(self) -> BitWriter<W, O> {
+        self.inner
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for BitStuffWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        BitStuffWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
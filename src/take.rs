@@ -0,0 +1,77 @@
+//! Limiting how many bits can be read through a `BitRead`.
+
+use crate::error::Error;
+use crate::io;
+use crate::traits::BitRead;
+
+/// Reads at most a given number of bits from another `BitRead`, such as the
+/// payload of a length-prefixed field.  Created by `BitRead::take_bits()`.
+///
+/// A read which would go past the limit fails with `UnexpectedEof` without
+/// reading anything, so whatever follows is left for the inner reader.
+#[derive(Debug)]
+pub struct TakeBits<R> {
+    inner: R,
+    remaining: u64,
+}
+
+impl<R: BitRead> TakeBits<R> {
+    pub(crate) fn new(inner: R, limit: u64) -> Self {
+        Self {
+            inner,
+            remaining: limit,
+        }
+    }
+
+    /// The number of bits which can still be read.
+    pub fn remaining(&self) -> u64 {
+        self.remaining
+    }
+
+    /// Change the number of bits which can still be read.
+    pub fn set_remaining(&mut self, remaining: u64) {
+        self.remaining = remaining;
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this `TakeBits`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead> BitRead for TakeBits<R> {
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        if nbits as u64 > self.remaining {
+            return Err(Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read_bits",
+                "bit limit reached",
+            )
+            .at(self.inner.bit_position())
+            .into());
+        }
+        let value = self.inner.read_bits(nbits)?;
+        self.remaining -= nbits as u64;
+        Ok(value)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        self.inner.bit_position()
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
@@ -0,0 +1,149 @@
+//! Recording the bits a reader consumes, as they're read.
+
+use core::fmt;
+
+use crate::io;
+use crate::order::BitOrder;
+use crate::traits::BitRead;
+use crate::{BitReader, BitWriter};
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Copy every bit read from this reader to `sink` as it's read, in the
+    /// order it's read, such as to capture exactly what a decoder consumed.
+    ///
+    /// ```
+    /// use bitrw::{BitReader, BitWriter};
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]).tee(BitWriter::new(Vec::new()));
+    /// assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    /// reader.skip_bits(4).unwrap();
+    /// assert_eq!(reader.read_bits(8).unwrap(), 0xcd);
+    ///
+    /// let (_, mut sink) = reader.into_inner();
+    /// sink.flush().unwrap();
+    /// assert_eq!(sink.into_inner(), [0xac, 0xd0]);
+    /// ```
+    pub fn tee<W: io::Write>(self, sink: BitWriter<W, O>) -> TeeBitReader<R, W, O> {
+        TeeBitReader {
+            reader: self,
+            sink,
+            record_skipped: false,
+        }
+    }
+}
+
+/// Reads from a `BitReader`, writing each bit read to a `BitWriter` of the
+/// same bit order.  Created by `BitReader::tee()`.
+///
+/// Bits passed over by `skip_bits()` and `reset()` aren't recorded unless
+/// `set_record_skipped()` says so.
+pub struct TeeBitReader<R, W, O> {
+    reader: BitReader<R, O>,
+    sink: BitWriter<W, O>,
+    record_skipped: bool,
+}
+
+impl<R, W, O: BitOrder> fmt::Debug for TeeBitReader<R, W, O> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TeeBitReader")
+            .field("reader", &self.reader)
+            .field("sink", &self.sink)
+            .field("record_skipped", &self.record_skipped)
+            .finish()
+    }
+}
+
+impl<R: io::Read, W: io::Write, O: BitOrder> TeeBitReader<R, W, O> {
+    /// Read up to 64 bits, and write them to the sink.
+    ///
+    /// If writing them fails they're pushed back onto the reader, so as with
+    /// `BitReader::read_bits()` nothing is consumed by a failed read.
+    pub fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        let value = self.reader.read_bits(nbits)?;
+        if let Err(e) = self.sink.write_bits(nbits, value) {
+            // There's always room, as the bits were just in the buffer.
+            self.reader
+                .unread_bits(nbits, value)
+                .expect("unread bits just read");
+            return Err(e);
+        }
+        Ok(value)
+    }
+
+    /// Skip over `nbits` bits, as for `BitReader::skip_bits()`, recording them
+    /// if `set_record_skipped()` says so.
+    ///
+    /// Recorded bits are read 64 at a time, so if this fails part way the
+    /// reader's `position()` says how far it got, and everything before that
+    /// has been recorded.
+    pub fn skip_bits(&mut self, nbits: u64) -> io::Result<()> {
+        if !self.record_skipped {
+            return self.reader.skip_bits(nbits);
+        }
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let chunk = remaining.min(64) as u8;
+            self.read_bits(chunk)?;
+            remaining -= chunk as u64;
+        }
+        Ok(())
+    }
+
+    /// Skip the rest of the buffered bits, moving on to the next byte
+    /// boundary, as for `BitReader::reset()`, recording them if
+    /// `set_record_skipped()` says so.
+    pub fn reset(&mut self) -> io::Result<()> {
+        if self.record_skipped {
+            while self.reader.bits.len() > 0 {
+                let chunk = self.reader.bits.len().min(64);
+                self.read_bits(chunk)?;
+            }
+        }
+        self.reader.reset();
+        Ok(())
+    }
+
+    /// Whether bits skipped over are recorded.  Off by default.
+    pub fn set_record_skipped(&mut self, record: bool) {
+        self.record_skipped = record;
+    }
+
+    /// The reader's position, as for `BitReader::position()`.
+    pub fn position(&self) -> u64 {
+        self.reader.position()
+    }
+
+    /// Get references to the reader and the sink.
+    pub fn get_ref(&self) -> (&BitReader<R, O>, &BitWriter<W, O>) {
+        (&self.reader, &self.sink)
+    }
+
+    /// Get mutable references to the reader and the sink.  Bits read from the
+    /// reader directly aren't recorded.
+    pub fn get_mut(&mut self) -> (&mut BitReader<R, O>, &mut BitWriter<W, O>) {
+        (&mut self.reader, &mut self.sink)
+    }
+
+    /// Unwrap this `TeeBitReader`, returning the reader and the sink, which
+    /// will need flushing.
+    pub fn into_inner(self) -> (BitReader<R, O>, BitWriter<W, O>) {
+        (self.reader, self.sink)
+    }
+}
+
+impl<R: io::Read, W: io::Write, O: BitOrder> BitRead for TeeBitReader<R, W, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        TeeBitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.reader.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.reader.vlc_limit()
+    }
+}
@@ -0,0 +1,206 @@
+//! Helpers for testing code built on bitrw, enabled by the `test-util`
+//! feature.
+//!
+//! These panic with a descriptive message on failure, like `assert_eq!`, so
+//! they're meant for tests rather than production code.
+//!
+//! ```
+//! use bitrw::testing::{assert_bits_eq, roundtrip, RecordingWriter};
+//! use bitrw::BitWrite;
+//!
+//! let bytes = roundtrip(&[(3, 0b110), (5, 1), (4, 0xf)]);
+//! assert_bits_eq(&bytes, "110 00001 1111");
+//!
+//! let mut recorder = RecordingWriter::new();
+//! recorder.write_unary(2).unwrap();
+//! assert_eq!(recorder.calls(), [(2, 0), (1, 1)]);
+//! ```
+
+use std::fmt::Write as _;
+use std::io::{self, Cursor};
+
+use crate::order::{BitOrder, Msb0};
+use crate::traits::BitWrite;
+use crate::{BitReader, BitWriter};
+
+/// Write each `(width, value)` pair with an `Msb0` `BitWriter`, read them back,
+/// and assert they match, returning the bytes written.
+pub fn roundtrip(fields: &[(u8, u64)]) -> Vec<u8> {
+    roundtrip_with_order(fields, Msb0)
+}
+
+/// Write each `(width, value)` pair with a `BitWriter` of the given order,
+/// read them back, and assert they match, returning the bytes written.
+///
+/// Values must fit in their widths.  The bytes must hold exactly the bits
+/// written, plus up to 7 bits of zero padding.
+pub fn roundtrip_with_order<O: BitOrder>(fields: &[(u8, u64)], order: O) -> Vec<u8> {
+    let mut writer = BitWriter::with_order(Vec::new(), order);
+    let mut total = 0;
+    for (i, &(width, value)) in fields.iter().enumerate() {
+        assert!(
+            width == 64 || value >> width == 0,
+            "field {}: value {:#x} doesn't fit in {} bits",
+            i,
+            value,
+            width
+        );
+        writer.write_bits(width, value).unwrap();
+        total += width as u64;
+    }
+    writer.flush().unwrap();
+    let bytes = writer.into_inner();
+    assert_eq!(
+        bytes.len() as u64,
+        total.div_ceil(8),
+        "{} bits written, {} bytes produced",
+        total,
+        bytes.len()
+    );
+
+    let mut reader = BitReader::with_order(Cursor::new(&bytes[..]), order);
+    for (i, &(width, value)) in fields.iter().enumerate() {
+        let position = reader.position();
+        let read = reader.read_bits(width).unwrap();
+        assert_eq!(
+            read, value,
+            "field {}: {} bits at bit {} read back as {:#x}, expected {:#x}",
+            i, width, position, read, value
+        );
+    }
+    let padding = reader.read_bits((bytes.len() as u64 * 8 - total) as u8);
+    assert_eq!(padding.unwrap(), 0, "padding isn't zero");
+    bytes
+}
+
+/// Render `bytes` as a string of `0` and `1` characters, in stream order for
+/// `O`, with a space between each byte.
+pub fn bit_string<O: BitOrder>(bytes: &[u8], _order: O) -> String {
+    let mut s = String::with_capacity(bytes.len() * 9);
+    for (i, &byte) in bytes.iter().enumerate() {
+        if i > 0 {
+            s.push(' ');
+        }
+        let byte = if O::MSB_FIRST {
+            byte
+        } else {
+            byte.reverse_bits()
+        };
+        let _ = write!(s, "{:08b}", byte);
+    }
+    s
+}
+
+/// Assert `bytes` hold the bits of `expected`, read most-significant first.
+///
+/// `expected` is a string of `0` and `1`, with spaces and underscores ignored.
+/// It must account for every bit, except that the final byte may end with zero
+/// padding, as written by `BitWriter::flush()`.  A trailing `...` allows any
+/// further bits.
+pub fn assert_bits_eq(bytes: &[u8], expected: &str) {
+    assert_bits_eq_with_order(bytes, expected, Msb0)
+}
+
+/// Assert `bytes` hold the bits of `expected`, in stream order for `O`, as for
+/// `assert_bits_eq()`.
+pub fn assert_bits_eq_with_order<O: BitOrder>(bytes: &[u8], expected: &str, order: O) {
+    let (pattern, open) = match expected.trim_end().strip_suffix("...") {
+        Some(pattern) => (pattern, true),
+        None => (expected, false),
+    };
+    let want: Vec<bool> = pattern
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '_')
+        .map(|c| match c {
+            '0' => false,
+            '1' => true,
+            _ => panic!("invalid character {:?} in bit pattern {:?}", c, expected),
+        })
+        .collect();
+
+    let actual = bit_string(bytes, order);
+    let have: Vec<bool> = actual
+        .chars()
+        .filter(|&c| c != ' ')
+        .map(|c| c == '1')
+        .collect();
+
+    let mismatch = if want.len() > have.len() {
+        Some(have.len())
+    } else {
+        want.iter().zip(&have).position(|(a, b)| a != b)
+    };
+    let mismatch = mismatch.or_else(|| {
+        if open {
+            return None;
+        }
+        // Beyond the pattern there may only be zero padding within its last byte.
+        let padded = want.len().div_ceil(8) * 8;
+        (want.len()..have.len()).find(|&i| i >= padded || have[i])
+    });
+
+    if let Some(bit) = mismatch {
+        panic!(
+            "bits differ at bit {}\n  expected: {}\n    actual: {}",
+            bit,
+            pattern.trim(),
+            actual
+        );
+    }
+}
+
+/// A `BitWrite` which records each call to `write_bits()`, for comparing the
+/// exact sequence of fields produced by an encoder against a golden list.
+///
+/// Provided `BitWrite` methods are recorded as the `write_bits()` calls they
+/// make.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecordingWriter {
+    calls: Vec<(u8, u64)>,
+    position: u64,
+}
+
+impl RecordingWriter {
+    /// Create an empty `RecordingWriter`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The `(nbits, value)` of each call so far.
+    pub fn calls(&self) -> &[(u8, u64)] {
+        &self.calls
+    }
+
+    /// Forget the calls recorded so far.
+    pub fn clear(&mut self) {
+        self.calls.clear();
+        self.position = 0;
+    }
+
+    /// Make the same calls on another writer.
+    pub fn replay<W: BitWrite + ?Sized>(&self, writer: &mut W) -> io::Result<()> {
+        for &(nbits, value) in &self.calls {
+            writer.write_bits(nbits, value)?;
+        }
+        Ok(())
+    }
+
+    /// Unwrap this `RecordingWriter`, returning the calls recorded.
+    pub fn into_calls(self) -> Vec<(u8, u64)> {
+        self.calls
+    }
+}
+
+impl BitWrite for RecordingWriter {
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        assert!(nbits <= 64);
+        self.calls.push((nbits, value));
+        self.position += nbits as u64;
+        Ok(nbits as usize)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position)
+    }
+}
@@ -0,0 +1,215 @@
+//! Reporting every read or write to a callback, for following a decoder or
+//! encoder through a stream.
+
+use core::fmt;
+
+use crate::io;
+use crate::traits::{BitRead, BitWrite};
+
+/// A single read or write, as reported to the callback of a `TraceBitReader`
+/// or `TraceBitWriter`.
+///
+/// Its `Display` form is a log line such as
+/// `read_bits(5) @ bit 1032 -> 0b10110`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceEvent {
+    /// The operation: `read_bits`, `read_bit`, `write_bits` or `write_bit`.
+    pub op: &'static str,
+    /// The width of the field.
+    pub nbits: u8,
+    /// The offset the operation started at, if the wrapped reader or writer
+    /// knows it.
+    pub offset: Option<u64>,
+    /// The value read or written, which for a failed read is zero.
+    pub value: u64,
+    /// The kind of error the operation failed with, if it did.
+    pub error: Option<io::ErrorKind>,
+}
+
+impl TraceEvent {
+    fn new(
+        op: &'static str,
+        nbits: u8,
+        offset: Option<u64>,
+        value: u64,
+        error: Option<&io::Error>,
+    ) -> Self {
+        let error = error.map(io::Error::kind);
+        Self {
+            op,
+            nbits,
+            offset,
+            value,
+            error,
+        }
+    }
+}
+
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let write = self.op.starts_with("write");
+        f.write_str(self.op)?;
+        match self.op {
+            "write_bits" => write!(
+                f,
+                "({}, 0b{:02$b})",
+                self.nbits, self.value, self.nbits as usize
+            )?,
+            "write_bit" => write!(f, "({})", self.value)?,
+            "read_bits" => write!(f, "({})", self.nbits)?,
+            _ => f.write_str("()")?,
+        }
+        match self.offset {
+            Some(offset) => write!(f, " @ bit {}", offset)?,
+            None => f.write_str(" @ bit ?")?,
+        }
+        match self.error {
+            Some(kind) => write!(f, " -> error: {:?}", kind),
+            None if write => Ok(()),
+            None => write!(f, " -> 0b{:01$b}", self.value, self.nbits as usize),
+        }
+    }
+}
+
+/// Reports each read from another `BitRead` to a callback, before returning
+/// it.  Created by `BitRead::with_trace()`.
+///
+/// Higher-level reads are reported as the reads they're made of, so
+/// `read_gamma()` shows up as its unary prefix, a bit at a time, then the
+/// value.
+pub struct TraceBitReader<R, F> {
+    inner: R,
+    trace: F,
+}
+
+impl<R: fmt::Debug, F> fmt::Debug for TraceBitReader<R, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceBitReader")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R: BitRead, F: FnMut(&TraceEvent)> TraceBitReader<R, F> {
+    pub(crate) fn new(inner: R, trace: F) -> Self {
+        Self { inner, trace }
+    }
+
+    /// Get a reference to the inner reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner reader.  Reads from it directly
+    /// aren't reported.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Unwrap this `TraceBitReader`, returning the inner reader.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BitRead, F: FnMut(&TraceEvent)> BitRead for TraceBitReader<R, F> {
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        let offset = self.inner.bit_position();
+        let result = self.inner.read_bits(nbits);
+        let (value, error) = match result {
+            Ok(value) => (value, None),
+            Err(ref e) => (0, Some(e)),
+        };
+        (self.trace)(&TraceEvent::new("read_bits", nbits, offset, value, error));
+        result
+    }
+
+    fn read_bit(&mut self) -> io::Result<u8> {
+        let offset = self.inner.bit_position();
+        let result = self.inner.read_bit();
+        let (value, error) = match result {
+            Ok(bit) => (bit as u64, None),
+            Err(ref e) => (0, Some(e)),
+        };
+        (self.trace)(&TraceEvent::new("read_bit", 1, offset, value, error));
+        result
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        self.inner.bit_position()
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        self.inner.vlc_limit()
+    }
+}
+
+/// Reports each write to another `BitWrite` to a callback, after making it.
+/// Created by `BitWrite::with_trace()`.
+///
+/// As for `TraceBitReader`, higher-level writes are reported as the writes
+/// they're made of.
+pub struct TraceBitWriter<W, F> {
+    inner: W,
+    trace: F,
+}
+
+impl<W: fmt::Debug, F> fmt::Debug for TraceBitWriter<W, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceBitWriter")
+            .field("inner", &self.inner)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<W: BitWrite, F: FnMut(&TraceEvent)> TraceBitWriter<W, F> {
+    pub(crate) fn new(inner: W, trace: F) -> Self {
+        Self { inner, trace }
+    }
+
+    /// Get a reference to the inner writer.
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Get a mutable reference to the inner writer.  Writes to it directly
+    /// aren't reported.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Unwrap this `TraceBitWriter`, returning the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: BitWrite, F: FnMut(&TraceEvent)> BitWrite for TraceBitWriter<W, F> {
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        let offset = self.inner.bit_position();
+        let result = self.inner.write_bits(nbits, value);
+        let value = if nbits == 64 {
+            value
+        } else {
+            value & ((1 << nbits) - 1)
+        };
+        let error = result.as_ref().err();
+        (self.trace)(&TraceEvent::new("write_bits", nbits, offset, value, error));
+        result
+    }
+
+    fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        let offset = self.inner.bit_position();
+        let result = self.inner.write_bit(bit);
+        let error = result.as_ref().err();
+        (self.trace)(&TraceEvent::new("write_bit", 1, offset, bit as u64, error));
+        result
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        self.inner.bit_position()
+    }
+}
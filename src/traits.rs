@@ -0,0 +1,1351 @@
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::codec::FromBits;
+use core::convert::TryFrom;
+
+use crate::bcd;
+use crate::constrained;
+use crate::elias_fano;
+use crate::error::Error;
+use crate::fixed::{self, Overflow};
+use crate::float;
+use crate::gray;
+use crate::io;
+use crate::order::BitOrder;
+use crate::primitive::{Integer, Primitive};
+use crate::quantize;
+use crate::take::TakeBits;
+use crate::trace::{TraceBitReader, TraceBitWriter, TraceEvent};
+use crate::vlc;
+use crate::{BitReader, BitWriter};
+
+/// Bit-level reading.  Implemented by `BitReader`, and by anything else which
+/// can produce a stream of bits, so higher-level code such as `FromBits`
+/// implementations can be written once against this trait.
+///
+/// Only `read_bits()` is required; everything else is built on top of it.
+///
+/// Errors from the provided helpers carry an `Error` naming the operation and,
+/// if `bit_position()` is known, the offset at which it started.
+pub trait BitRead {
+    /// Read up to 64 bits.
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64>;
+
+    /// The absolute bit offset of the next read, if known.
+    fn bit_position(&self) -> Option<u64> {
+        None
+    }
+
+    /// The most bits a single variable-length code, such as one read by
+    /// `read_unary()` or `read_gamma()`, may take before the read gives up
+    /// with an `InvalidData` error, or `None` for no limit.  1024 unless the
+    /// implementation says otherwise, as `BitReader::set_vlc_limit()` does.
+    fn vlc_limit(&self) -> Option<u32> {
+        Some(vlc::DEFAULT_VLC_LIMIT)
+    }
+
+    /// Read a single bit.
+    fn read_bit(&mut self) -> io::Result<u8> {
+        Ok(self.read_bits(1)? as u8)
+    }
+
+    /// Borrow this reader, so an adapter which takes a reader by value can be
+    /// used without giving it up, as with `std::io::Read::by_ref()`.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// let mut header = reader.by_ref().take_bits(4);
+    /// assert_eq!(header.read_bits(4).unwrap(), 0xa);
+    /// assert!(header.read_bit().is_err());
+    /// assert_eq!(reader.read_bits(12).unwrap(), 0xbcd);
+    /// ```
+    fn by_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self
+    }
+
+    /// An adapter which reads at most `limit` bits from this reader, failing
+    /// with `UnexpectedEof` as a `BitReader` does at its limit.
+    fn take_bits(self, limit: u64) -> TakeBits<Self>
+    where
+        Self: Sized,
+    {
+        TakeBits::new(self, limit)
+    }
+
+    /// An adapter which calls `trace` with a `TraceEvent` for every read,
+    /// giving its width, starting offset and value.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut log = Vec::new();
+    /// let mut reader = BitReader::new(&[0xb4][..]).with_trace(|event| log.push(event.to_string()));
+    /// reader.read_bits(3).unwrap();
+    /// reader.read_bits(5).unwrap();
+    /// assert_eq!(log, ["read_bits(3) @ bit 0 -> 0b101", "read_bits(5) @ bit 3 -> 0b10100"]);
+    /// ```
+    fn with_trace<F: FnMut(&TraceEvent)>(self, trace: F) -> TraceBitReader<Self, F>
+    where
+        Self: Sized,
+    {
+        TraceBitReader::new(self, trace)
+    }
+
+    /// Read a primitive value as a single `T::BITS` wide field, in the natural
+    /// order of the stream: big-endian for `Msb0`, little-endian for `Lsb0`.
+    fn read<T: Primitive>(&mut self) -> io::Result<T>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.read_bits(T::BITS)
+            .map(T::from_bits)
+            .map_err(|e| Error::wrap(e, "read", start))
+    }
+
+    /// Read an `nbits` wide field into an integer type, zero-extending it.
+    /// Unlike `read()` the field may be narrower than the type, but if it's
+    /// wider this fails with `InvalidInput`, carrying `nbits` as its value,
+    /// without reading anything.
+    ///
+    /// A field as wide as a signed type is read as its two's complement bit
+    /// pattern, as for `read()`.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd, 0xef][..]);
+    /// assert_eq!(reader.try_read_into::<u8>(4).unwrap(), 0xa);
+    /// assert_eq!(reader.try_read_into::<u16>(12).unwrap(), 0xbcd);
+    /// assert!(reader.try_read_into::<u8>(9).is_err());
+    /// ```
+    fn try_read_into<T: Integer>(&mut self, nbits: u8) -> io::Result<T>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        if nbits > T::BITS {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "try_read_into", T::TOO_WIDE)
+                    .at(start)
+                    .with_value(nbits as u64)
+                    .into(),
+            );
+        }
+        self.read_bits(nbits)
+            .map(T::from_bits)
+            .map_err(|e| Error::wrap(e, "try_read_into", start))
+    }
+
+    /// Read an `nbits` wide two's complement field into an integer type,
+    /// sign-extending it to the width of the type, so a one bit field is 0 or
+    /// -1 in a signed type.  Fails as for `try_read_into()` if the field is
+    /// wider than the type.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0b1110_0111][..]);
+    /// assert_eq!(reader.try_read_signed_into::<i8>(3).unwrap(), -1);
+    /// assert_eq!(reader.try_read_signed_into::<i16>(5).unwrap(), 7);
+    /// ```
+    fn try_read_signed_into<T: Integer>(&mut self, nbits: u8) -> io::Result<T>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        if nbits > T::BITS {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "try_read_signed_into",
+                T::TOO_WIDE,
+            )
+            .at(start)
+            .with_value(nbits as u64)
+            .into());
+        }
+        let bits = self
+            .read_bits(nbits)
+            .map_err(|e| Error::wrap(e, "try_read_signed_into", start))?;
+        Ok(T::from_bits(match nbits {
+            0 => 0,
+            _ => (((bits << (64 - nbits)) as i64) >> (64 - nbits)) as u64,
+        }))
+    }
+
+    /// Read a big-endian primitive value: `T::BITS / 8` groups of 8 bits, read
+    /// from the current bit phase, the first group being the most significant.
+    fn read_be<T: Primitive>(&mut self) -> io::Result<T>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut value = 0;
+        for _ in 0..T::BITS / 8 {
+            let byte = self
+                .read_bits(8)
+                .map_err(|e| Error::wrap(e, "read_be", start))?;
+            value = (value << 8) | byte;
+        }
+        Ok(T::from_bits(value))
+    }
+
+    /// Read a little-endian primitive value: `T::BITS / 8` groups of 8 bits,
+    /// read from the current bit phase, the first group being the least
+    /// significant.
+    fn read_le<T: Primitive>(&mut self) -> io::Result<T>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut value = 0;
+        for i in 0..T::BITS / 8 {
+            let byte = self
+                .read_bits(8)
+                .map_err(|e| Error::wrap(e, "read_le", start))?;
+            value |= byte << (i * 8);
+        }
+        Ok(T::from_bits(value))
+    }
+
+    /// Read an IEEE-754 binary16 value from its 16 bit pattern, in the natural
+    /// order of the stream, at any bit phase.  The pattern is preserved
+    /// exactly, including NaN payloads and subnormals.
+    #[cfg(feature = "half")]
+    fn read_f16(&mut self) -> io::Result<half::f16>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.read().map_err(|e| Error::wrap(e, "read_f16", start))
+    }
+
+    /// Read a float in a custom IEEE-754 style layout: a sign bit if `sign`,
+    /// then `exp_bits` of exponent biased by `bias`, then `mant_bits` of
+    /// mantissa, as a single field in the natural order of the stream.
+    ///
+    /// An all-zero exponent encodes zero and the subnormals, and all-ones the
+    /// infinities and NaNs, whose payload is kept in the high mantissa bits of
+    /// the result.  So binary16 is `read_float(true, 5, 10, 15)` and bfloat16
+    /// `read_float(true, 8, 7, 127)`.
+    ///
+    /// Panics unless `1 <= exp_bits <= 11` and `mant_bits <= 52`, which
+    /// guarantees the result is exact.
+    fn read_float(&mut self, sign: bool, exp_bits: u8, mant_bits: u8, bias: i32) -> io::Result<f64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.read_bits(sign as u8 + exp_bits + mant_bits)
+            .map(|bits| float::decode(bits, sign, exp_bits, mant_bits, bias))
+            .map_err(|e| Error::wrap(e, "read_float", start))
+    }
+
+    /// Read an `nbits` wide float quantized onto the range `[min, max]`, as
+    /// written by `BitWrite::write_quantized()`.  The codes are equal steps
+    /// from exactly `min` to exactly `max`, as the `quantize` module describes.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// // A 4 bit code of 3, on 15 steps from -1 to 2.
+    /// let mut reader = BitReader::new(&[0x30][..]);
+    /// assert_eq!(reader.read_quantized(-1.0, 2.0, 4).unwrap(), -0.4);
+    /// ```
+    ///
+    /// Panics unless `min` and `max` are finite with `min <= max`, and
+    /// `nbits <= 32`.
+    fn read_quantized(&mut self, min: f32, max: f32, nbits: u8) -> io::Result<f32>
+    where
+        Self: Sized,
+    {
+        quantize::check(min, max, nbits);
+        let start = self.bit_position();
+        self.read_bits(nbits)
+            .map(|code| quantize::decode(code, min, max, nbits))
+            .map_err(|e| Error::wrap(e, "read_quantized", start))
+    }
+
+    /// Read a Qm.n fixed-point value with `int_bits` integer bits, including
+    /// the sign bit if `signed`, and `frac_bits` fractional bits, as a single
+    /// two's complement field in the natural order of the stream.  So a signed
+    /// Q4.12 value is `read_fixed(4, 12, true)`, and 16 bits wide.
+    ///
+    /// The result is exact for fields up to 53 bits wide, and rounded to
+    /// nearest, ties to even, beyond that.  Panics unless the field is 1 to 64
+    /// bits wide, with at least one integer bit if it's signed.
+    fn read_fixed(&mut self, int_bits: u8, frac_bits: u8, signed: bool) -> io::Result<f64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.read_bits(fixed::width(int_bits, frac_bits, signed))
+            .map(|bits| fixed::decode(bits, int_bits, frac_bits, signed))
+            .map_err(|e| Error::wrap(e, "read_fixed", start))
+    }
+
+    /// Read an `nbits` wide field of Gray code, up to 64 bits, and convert it
+    /// to binary.
+    fn read_gray(&mut self, nbits: u8) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.read_bits(nbits)
+            .map(gray::gray_decode)
+            .map_err(|e| Error::wrap(e, "read_gray", start))
+    }
+
+    /// Read an `nbits` wide sign-magnitude integer: a sign bit, set for
+    /// negative, and `nbits - 1` bits of magnitude, as a single field in the
+    /// natural order of the stream, so the sign is its most significant bit.
+    ///
+    /// Negative zero decodes as 0.  Panics unless `1 <= nbits <= 64`.
+    fn read_sign_magnitude(&mut self, nbits: u8) -> io::Result<i64>
+    where
+        Self: Sized,
+    {
+        assert!((1..=64).contains(&nbits));
+        let start = self.bit_position();
+        let bits = self
+            .read_bits(nbits)
+            .map_err(|e| Error::wrap(e, "read_sign_magnitude", start))?;
+        let magnitude = (bits & !(1 << (nbits - 1))) as i64;
+        Ok(if bits >> (nbits - 1) == 1 {
+            -magnitude
+        } else {
+            magnitude
+        })
+    }
+
+    /// Read `count` consecutive values with `FromBits`.
+    #[cfg(feature = "alloc")]
+    fn read_vec<T: FromBits>(&mut self, count: usize) -> io::Result<Vec<T>>
+    where
+        Self: Sized,
+    {
+        let mut items = Vec::with_capacity(core::cmp::min(count, 1024));
+        for _ in 0..count {
+            let start = self.bit_position();
+            items.push(T::from_bits(self).map_err(|e| Error::wrap(e, "read_vec", start))?);
+        }
+        Ok(items)
+    }
+
+    /// Read a `count_bits` wide count, then that many elements with `f`, as
+    /// for a length-prefixed list.
+    ///
+    /// A count above `max_count` is an `InvalidData` error carrying the count,
+    /// with only the count consumed, so a hostile count can't make this
+    /// allocate or read more than expected.  With no maximum the count is
+    /// trusted, though at most 1024 elements are allocated for up front.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0x3a, 0xbc, 0xd0][..]);
+    /// let items = reader.read_counted(4, Some(8), |r| r.read_bits(4)).unwrap();
+    /// assert_eq!(items, [0xa, 0xb, 0xc]);
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_counted<T, F>(
+        &mut self,
+        count_bits: u8,
+        max_count: Option<usize>,
+        mut f: F,
+    ) -> io::Result<Vec<T>>
+    where
+        Self: Sized,
+        F: FnMut(&mut Self) -> io::Result<T>,
+    {
+        let start = self.bit_position();
+        let count = self
+            .read_bits(count_bits)
+            .map_err(|e| Error::wrap(e, "read_counted", start))?;
+        if count > max_count.unwrap_or(usize::MAX) as u64 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "read_counted",
+                "count exceeds maximum",
+            )
+            .at(start)
+            .with_value(count)
+            .into());
+        }
+
+        let mut items = Vec::with_capacity(core::cmp::min(count, 1024) as usize);
+        for _ in 0..count {
+            items.push(f(self).map_err(|e| Error::wrap(e, "read_counted", start))?);
+        }
+        Ok(items)
+    }
+
+    /// Read `nbytes` bytes of UTF-8 text, as groups of 8 bits from the current
+    /// bit phase.  Invalid UTF-8 is an `InvalidData` error whose value is the
+    /// index of the first bad byte.
+    #[cfg(feature = "alloc")]
+    fn read_utf8(&mut self, nbytes: usize) -> io::Result<String>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let bytes = read_bytes(self, nbytes).map_err(|e| Error::wrap(e, "read_utf8", start))?;
+        String::from_utf8(bytes).map_err(|e| {
+            Error::new(io::ErrorKind::InvalidData, "read_utf8", "invalid UTF-8")
+                .at(start)
+                .with_value(e.utf8_error().valid_up_to() as u64)
+                .into()
+        })
+    }
+
+    /// Read `nbytes` bytes of UTF-8 text as for `read_utf8()`, replacing any
+    /// invalid sequences with U+FFFD.
+    #[cfg(feature = "alloc")]
+    fn read_utf8_lossy(&mut self, nbytes: usize) -> io::Result<String>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let bytes =
+            read_bytes(self, nbytes).map_err(|e| Error::wrap(e, "read_utf8_lossy", start))?;
+        Ok(match String::from_utf8(bytes) {
+            Ok(s) => s,
+            Err(e) => String::from_utf8_lossy(e.as_bytes()).into_owned(),
+        })
+    }
+
+    /// Read a NUL-terminated string of up to `max_len` bytes, as groups of 8
+    /// bits from the current bit phase, returning it without the terminator.
+    /// If no terminator follows within `max_len` bytes it's an `InvalidData`
+    /// error, having read `max_len + 1` bytes.
+    #[cfg(feature = "alloc")]
+    fn read_cstr(&mut self, max_len: usize) -> io::Result<Vec<u8>>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut bytes = Vec::new();
+        loop {
+            let byte = self
+                .read_bits(8)
+                .map_err(|e| Error::wrap(e, "read_cstr", start))? as u8;
+            if byte == 0 {
+                return Ok(bytes);
+            }
+            if bytes.len() == max_len {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidData,
+                    "read_cstr",
+                    "missing terminator",
+                )
+                .at(start)
+                .with_value(max_len as u64)
+                .into());
+            }
+            bytes.push(byte);
+        }
+    }
+
+    /// Read `nbits` bits and check they equal `expected`, as for a marker or
+    /// sync word.  A mismatch is an `InvalidData` error carrying the value read.
+    fn expect_bits(&mut self, nbits: u8, expected: u64) -> io::Result<()>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let value = self
+            .read_bits(nbits)
+            .map_err(|e| Error::wrap(e, "expect_bits", start))?;
+        if value == expected {
+            Ok(())
+        } else {
+            Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "expect_bits",
+                "unexpected value",
+            )
+            .at(start)
+            .with_value(value)
+            .into())
+        }
+    }
+
+    /// Read an `nbits` wide field and convert it to `E`, as for a protocol
+    /// field with a fixed set of values.  A value `E` doesn't accept is an
+    /// `InvalidData` error carrying the value read.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    /// use std::convert::TryFrom;
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// enum Kind {
+    ///     Audio = 1,
+    ///     Video = 2,
+    /// }
+    ///
+    /// impl TryFrom<u64> for Kind {
+    ///     type Error = ();
+    ///
+    ///     fn try_from(value: u64) -> Result<Self, ()> {
+    ///         match value {
+    ///             1 => Ok(Kind::Audio),
+    ///             2 => Ok(Kind::Video),
+    ///             _ => Err(()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut reader = BitReader::new(&[0b0010_1100][..]);
+    /// assert_eq!(reader.read_enum::<Kind>(4).unwrap(), Kind::Video);
+    /// assert!(reader.read_enum::<Kind>(4).is_err());
+    /// ```
+    fn read_enum<E: TryFrom<u64>>(&mut self, nbits: u8) -> io::Result<E>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let value = self
+            .read_bits(nbits)
+            .map_err(|e| Error::wrap(e, "read_enum", start))?;
+        E::try_from(value).map_err(|_| {
+            Error::new(
+                io::ErrorKind::InvalidData,
+                "read_enum",
+                "invalid enum value",
+            )
+            .at(start)
+            .with_value(value)
+            .into()
+        })
+    }
+
+    /// Read a unary code: the number of zero bits before the next one bit.
+    fn read_unary(&mut self) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        unary(self, "read_unary", start).map_err(|e| Error::wrap(e, "read_unary", start))
+    }
+
+    /// Read an Elias gamma code: `n` zero bits followed by the `n + 1` bit
+    /// value, whose leading bit is always set.  Decodes values from 1 up to
+    /// `u64::MAX`.
+    fn read_gamma(&mut self) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let zeros =
+            unary(self, "read_gamma", start).map_err(|e| Error::wrap(e, "read_gamma", start))?;
+        if zeros > 63 {
+            return Err(
+                Error::new(io::ErrorKind::InvalidData, "read_gamma", "code too long")
+                    .at(start)
+                    .with_value(zeros)
+                    .into(),
+            );
+        }
+        vlc::check(self.vlc_limit(), zeros * 2 + 1, "read_gamma", start)?;
+
+        let low = self
+            .read_bits(zeros as u8)
+            .map_err(|e| Error::wrap(e, "read_gamma", start))?;
+        Ok((1 << zeros) | low)
+    }
+
+    /// Read a whole number constrained to `[lb, ub]`, as an offset from `lb`
+    /// in `ceil(log2(ub - lb + 1))` bits, as the `constrained` module
+    /// describes.
+    ///
+    /// An offset past `ub`, which a range that isn't a power of two leaves
+    /// room for, is an `InvalidData` error at the start of the field with the
+    /// offset as its value, and the reader is left after it.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// // INTEGER (3..6) of 4, then INTEGER (-10..21) of -3.
+    /// let mut reader = BitReader::new(&[0b0100_1110][..]);
+    /// assert_eq!(reader.read_constrained(3, 6).unwrap(), 4);
+    /// assert_eq!(reader.read_constrained(-10, 21).unwrap(), -3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lb > ub`.
+    fn read_constrained(&mut self, lb: i64, ub: i64) -> io::Result<i64>
+    where
+        Self: Sized,
+    {
+        let (span, nbits) = constrained::span(lb, ub);
+        let start = self.bit_position();
+        let offset = self
+            .read_bits(nbits)
+            .map_err(|e| Error::wrap(e, "read_constrained", start))?;
+        if offset > span {
+            return Err(Error::new(
+                io::ErrorKind::InvalidData,
+                "read_constrained",
+                "value out of range",
+            )
+            .at(start)
+            .with_value(offset)
+            .into());
+        }
+        Ok(lb.wrapping_add(offset as i64))
+    }
+
+    /// Read `digits` BCD digits, returning their value, as the `bcd` module
+    /// describes.
+    ///
+    /// A nibble above 9 is an `InvalidData` error at its offset, with the
+    /// nibble as its value, and a value too large for a `u64` is an
+    /// `InvalidData` error at the digit which overflowed.  Either way the
+    /// reader is left after the nibble which failed.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0x01, 0x23, 0x45][..]);
+    /// assert_eq!(reader.read_bcd(5).unwrap(), 1234);
+    /// assert_eq!(reader.read_bits(4).unwrap(), 5);
+    /// ```
+    fn read_bcd(&mut self, digits: usize) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut value: u64 = 0;
+        for _ in 0..digits {
+            let at = self.bit_position();
+            let digit = bcd::read_digit(self, "read_bcd", start)?;
+            value = value
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(digit))
+                .ok_or_else(|| {
+                    Error::new(io::ErrorKind::InvalidData, "read_bcd", "value too large").at(at)
+                })?;
+        }
+        Ok(value)
+    }
+
+    /// Read `digits` BCD digits as for `read_bcd()`, followed by a 0xF filler
+    /// nibble if `digits` is odd.  Any other filler is an `InvalidData` error
+    /// at its offset, with its value.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0x12, 0x3f][..]);
+    /// assert_eq!(reader.read_bcd_filled(3).unwrap(), 123);
+    /// assert_eq!(reader.position(), 16);
+    /// ```
+    fn read_bcd_filled(&mut self, digits: usize) -> io::Result<u64>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let value = self.read_bcd(digits)?;
+        if digits % 2 == 1 {
+            bcd::read_filler(self, "read_bcd_filled", start)?;
+        }
+        Ok(value)
+    }
+
+    /// Read `digits` BCD digits as a string of ASCII digits, for numbers too
+    /// long for a `u64` such as card and account numbers.  Leading zeros are
+    /// kept.  A nibble above 9 is an `InvalidData` error as for `read_bcd()`.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let data = [0x00, 0x12, 0x34, 0x56, 0x78, 0x90, 0x12, 0x34, 0x56, 0x78, 0x90];
+    /// let mut reader = BitReader::new(&data[..]);
+    /// assert_eq!(reader.read_bcd_string(22).unwrap(), "0012345678901234567890");
+    /// ```
+    #[cfg(feature = "alloc")]
+    fn read_bcd_string(&mut self, digits: usize) -> io::Result<String>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut s = String::with_capacity(core::cmp::min(digits, 1024));
+        for _ in 0..digits {
+            let digit = bcd::read_digit(self, "read_bcd_string", start)?;
+            s.push(char::from(b'0' + digit as u8));
+        }
+        Ok(s)
+    }
+
+    /// Read a presence flag, then if it's set read a value with `f`, so a
+    /// flag-prefixed optional field is `reader.read_optional(|r| r.read_bits(5))`.
+    ///
+    /// If the flag can't be read nothing is consumed.  If `f` fails its error
+    /// is returned, with the flag and whatever `f` read before failing
+    /// consumed.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0b1_10110_0_0][..]);
+    /// assert_eq!(reader.read_optional(|r| r.read_bits(5)).unwrap(), Some(0b10110));
+    /// assert_eq!(reader.read_optional(|r| r.read_bits(5)).unwrap(), None);
+    /// ```
+    fn read_optional<T, F>(&mut self, f: F) -> io::Result<Option<T>>
+    where
+        Self: Sized,
+        F: FnOnce(&mut Self) -> io::Result<T>,
+    {
+        let start = self.bit_position();
+        let present = self
+            .read_bit()
+            .map_err(|e| Error::wrap(e, "read_optional", start))?;
+        if present == 1 {
+            f(self)
+                .map(Some)
+                .map_err(|e| Error::wrap(e, "read_optional", start))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+fn read_bytes<R: BitRead>(r: &mut R, nbytes: usize) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(core::cmp::min(nbytes, 4096));
+    for _ in 0..nbytes {
+        bytes.push(r.read_bits(8)? as u8);
+    }
+    Ok(bytes)
+}
+
+/// Count zero bits up to the next one bit, giving up once the code is longer
+/// than the reader's `vlc_limit()`.
+fn unary<R: BitRead>(r: &mut R, op: &'static str, start: Option<u64>) -> io::Result<u64> {
+    let limit = r.vlc_limit();
+    let mut zeros = 0;
+    while r.read_bit()? == 0 {
+        zeros += 1;
+        vlc::check(limit, zeros + 1, op, start)?;
+    }
+    Ok(zeros)
+}
+
+/// Bit-level writing.  Implemented by `BitWriter`, and by anything else which
+/// can consume a stream of bits, so higher-level code such as `ToBits`
+/// implementations can be written once against this trait.
+///
+/// Only `write_bits()` is required; everything else is built on top of it.
+pub trait BitWrite {
+    /// Write up to 64 bits, returning the number of bits written.
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize>;
+
+    /// The absolute bit offset of the next write, if known.
+    fn bit_position(&self) -> Option<u64> {
+        None
+    }
+
+    /// Write a single bit.
+    fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        assert!(bit <= 1);
+        self.write_bits(1, bit as u64)?;
+        Ok(())
+    }
+
+    /// An adapter which calls `trace` with a `TraceEvent` for every write, as
+    /// for `BitRead::with_trace()`.
+    fn with_trace<F: FnMut(&TraceEvent)>(self, trace: F) -> TraceBitWriter<Self, F>
+    where
+        Self: Sized,
+    {
+        TraceBitWriter::new(self, trace)
+    }
+
+    /// Write a primitive value as a single `T::BITS` wide field, in the natural
+    /// order of the stream: big-endian for `Msb0`, little-endian for `Lsb0`.
+    fn write<T: Primitive>(&mut self, value: T) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        self.write_bits(T::BITS, value.to_bits())
+            .map_err(|e| Error::wrap(e, "write", start))
+    }
+
+    /// Write a big-endian primitive value: `T::BITS / 8` groups of 8 bits from
+    /// the current bit phase, starting with the most significant.
+    fn write_be<T: Primitive>(&mut self, value: T) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let bits = value.to_bits();
+        for i in (0..T::BITS / 8).rev() {
+            self.write_bits(8, bits >> (i * 8))
+                .map_err(|e| Error::wrap(e, "write_be", start))?;
+        }
+        Ok(T::BITS as usize)
+    }
+
+    /// Write a little-endian primitive value: `T::BITS / 8` groups of 8 bits
+    /// from the current bit phase, starting with the least significant.
+    fn write_le<T: Primitive>(&mut self, value: T) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let bits = value.to_bits();
+        for i in 0..T::BITS / 8 {
+            self.write_bits(8, bits >> (i * 8))
+                .map_err(|e| Error::wrap(e, "write_le", start))?;
+        }
+        Ok(T::BITS as usize)
+    }
+
+    /// Write an IEEE-754 binary16 value as its 16 bit pattern, as read by
+    /// `BitRead::read_f16()`.
+    #[cfg(feature = "half")]
+    fn write_f16(&mut self, value: half::f16) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        self.write(value)
+    }
+
+    /// Write `value` in a custom float layout, as read by
+    /// `BitRead::read_float()`, returning the number of bits written.
+    ///
+    /// Values are rounded to the nearest representable value, ties to even, and
+    /// those too large for the layout become infinity.  A negative value with
+    /// no sign bit, or a NaN with no mantissa bits to mark it, is an
+    /// `InvalidInput` error.
+    fn write_float(
+        &mut self,
+        value: f64,
+        sign: bool,
+        exp_bits: u8,
+        mant_bits: u8,
+        bias: i32,
+    ) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let bits = float::encode(value, sign, exp_bits, mant_bits, bias).map_err(|e| {
+            let message = match e {
+                float::EncodeError::Negative => "negative value in unsigned layout",
+                float::EncodeError::Nan => "NaN not representable",
+            };
+            Error::new(io::ErrorKind::InvalidInput, "write_float", message)
+                .at(start)
+                .with_value(value.to_bits())
+        })?;
+        self.write_bits(sign as u8 + exp_bits + mant_bits, bits)
+            .map_err(|e| Error::wrap(e, "write_float", start))
+    }
+
+    /// Write `value` quantized onto the range `[min, max]` in `nbits` bits,
+    /// for `BitRead::read_quantized()`.  It's rounded to the nearest of the
+    /// equal steps from `min` to `max`, ties to even, and clamped to the range
+    /// if it's outside it.  NaN is an `InvalidInput` error, and nothing is
+    /// written.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_quantized(0.5, 0.0, 1.0, 4).unwrap(); // 7.5 steps, so 8
+    /// writer.write_quantized(7.0, 0.0, 1.0, 4).unwrap(); // clamped to 15
+    /// assert_eq!(writer.into_inner(), [0x8f]);
+    /// ```
+    ///
+    /// Panics unless `min` and `max` are finite with `min <= max`, and
+    /// `nbits <= 32`.
+    fn write_quantized(&mut self, value: f32, min: f32, max: f32, nbits: u8) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        quantize::check(min, max, nbits);
+        let start = self.bit_position();
+        if value.is_nan() {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_quantized",
+                "NaN not representable",
+            )
+            .at(start)
+            .with_value(value.to_bits() as u64)
+            .into());
+        }
+        self.write_bits(nbits, quantize::encode(value, min, max, nbits))
+            .map_err(|e| Error::wrap(e, "write_quantized", start))
+    }
+
+    /// Write the bytes of `s` as groups of 8 bits from the current bit phase,
+    /// as read by `BitRead::read_utf8()`.  Returns the number of bits written;
+    /// no length or terminator is written.
+    fn write_utf8(&mut self, s: &str) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        for &byte in s.as_bytes() {
+            self.write_bits(8, byte as u64)
+                .map_err(|e| Error::wrap(e, "write_utf8", start))?;
+        }
+        Ok(s.len() * 8)
+    }
+
+    /// Write `bytes` followed by a NUL terminator, as groups of 8 bits from the
+    /// current bit phase, as read by `BitRead::read_cstr()`.  Returns the
+    /// number of bits written.
+    ///
+    /// A NUL within `bytes` is an `InvalidInput` error whose value is its
+    /// index, and nothing is written.
+    fn write_cstr(&mut self, bytes: &[u8]) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        if let Some(index) = bytes.iter().position(|&byte| byte == 0) {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_cstr", "embedded NUL")
+                    .at(start)
+                    .with_value(index as u64)
+                    .into(),
+            );
+        }
+        for &byte in bytes.iter().chain(&[0]) {
+            self.write_bits(8, byte as u64)
+                .map_err(|e| Error::wrap(e, "write_cstr", start))?;
+        }
+        Ok((bytes.len() + 1) * 8)
+    }
+
+    /// Write `value` as a Qm.n fixed-point field, as read by
+    /// `BitRead::read_fixed()`, returning the number of bits written.
+    ///
+    /// Values are rounded to the nearest multiple of 2^-`frac_bits`, ties to
+    /// even.  Those outside the field's range after rounding, including the
+    /// infinities, are handled according to `overflow`: either clamped to the
+    /// minimum or maximum, or rejected with an `InvalidInput` error.  A NaN is
+    /// always an `InvalidInput` error.
+    fn write_fixed(
+        &mut self,
+        int_bits: u8,
+        frac_bits: u8,
+        signed: bool,
+        value: f64,
+        overflow: Overflow,
+    ) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let nbits = fixed::width(int_bits, frac_bits, signed);
+        let bits =
+            fixed::encode(value, int_bits, frac_bits, signed, overflow).map_err(|message| {
+                Error::new(io::ErrorKind::InvalidInput, "write_fixed", message)
+                    .at(start)
+                    .with_value(value.to_bits())
+            })?;
+        self.write_bits(nbits, bits)
+            .map_err(|e| Error::wrap(e, "write_fixed", start))
+    }
+
+    /// Convert `value` to Gray code and write it in `nbits` bits, up to 64, as
+    /// read by `BitRead::read_gray()`.  A value which doesn't fit in `nbits` is
+    /// an `InvalidInput` error carrying the value, and nothing is written.
+    fn write_gray(&mut self, nbits: u8, value: u64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        assert!(nbits <= 64);
+        let start = self.bit_position();
+        if nbits < 64 && value >> nbits != 0 {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_gray", "value too wide")
+                    .at(start)
+                    .with_value(value)
+                    .into(),
+            );
+        }
+        self.write_bits(nbits, gray::gray_encode(value))
+            .map_err(|e| Error::wrap(e, "write_gray", start))
+    }
+
+    /// Write an `nbits` wide sign-magnitude integer, as read by
+    /// `BitRead::read_sign_magnitude()`.  Zero is always written as positive.
+    ///
+    /// A magnitude which doesn't fit in `nbits - 1` bits is an `InvalidInput`
+    /// error carrying the value, and nothing is written.  Panics unless
+    /// `1 <= nbits <= 64`.
+    fn write_sign_magnitude(&mut self, nbits: u8, value: i64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        assert!((1..=64).contains(&nbits));
+        let start = self.bit_position();
+        let magnitude = value.unsigned_abs();
+        if magnitude >> (nbits - 1) != 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_sign_magnitude",
+                "magnitude too large",
+            )
+            .at(start)
+            .with_value(value as u64)
+            .into());
+        }
+        let sign = (value < 0) as u64;
+        self.write_bits(nbits, (sign << (nbits - 1)) | magnitude)
+            .map_err(|e| Error::wrap(e, "write_sign_magnitude", start))
+    }
+
+    /// Write `value` converted to an integer in an `nbits` wide field, as
+    /// read by `BitRead::read_enum()`.  A value which doesn't fit in `nbits`
+    /// is an `InvalidInput` error carrying the value, and nothing is written.
+    fn write_enum<E: Into<u64>>(&mut self, nbits: u8, value: E) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        assert!(nbits <= 64);
+        let start = self.bit_position();
+        let value = value.into();
+        if nbits < 64 && value >> nbits != 0 {
+            return Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_enum", "value too wide")
+                    .at(start)
+                    .with_value(value)
+                    .into(),
+            );
+        }
+        self.write_bits(nbits, value)
+            .map_err(|e| Error::wrap(e, "write_enum", start))
+    }
+
+    /// Write a unary code: `value` zero bits followed by a one bit.
+    fn write_unary(&mut self, value: u64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut remaining = value;
+        while remaining > 0 {
+            let chunk = core::cmp::min(remaining, 64) as u8;
+            self.write_bits(chunk, 0)
+                .map_err(|e| Error::wrap(e, "write_unary", start))?;
+            remaining -= chunk as u64;
+        }
+        self.write_bits(1, 1)
+            .map_err(|e| Error::wrap(e, "write_unary", start))?;
+        Ok(value as usize + 1)
+    }
+
+    /// Write an Elias gamma code, as read by `BitRead::read_gamma()`.  Zero
+    /// can't be represented, and is an `InvalidInput` error.
+    fn write_gamma(&mut self, value: u64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        if value == 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_gamma",
+                "zero has no code",
+            )
+            .at(start)
+            .with_value(value)
+            .into());
+        }
+
+        let zeros = 63 - value.leading_zeros() as u8;
+        self.write_bits(zeros, 0)
+            .and_then(|_| self.write_bits(zeros + 1, value))
+            .map_err(|e| Error::wrap(e, "write_gamma", start))?;
+        Ok(zeros as usize * 2 + 1)
+    }
+
+    /// Write the length of `items` as a `count_bits` wide count, then each
+    /// item with `f`, as read by `BitRead::read_counted()`.  Returns the number
+    /// of bits written, including the count, so `f` should return the number
+    /// it wrote.
+    ///
+    /// A length which doesn't fit the count is an `InvalidInput` error carrying
+    /// the length, and nothing is written.
+    fn write_counted<T, F>(&mut self, count_bits: u8, items: &[T], mut f: F) -> io::Result<usize>
+    where
+        Self: Sized,
+        F: FnMut(&mut Self, &T) -> io::Result<usize>,
+    {
+        let start = self.bit_position();
+        let count = items.len() as u64;
+        if count_bits < 64 && count >> count_bits != 0 {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_counted",
+                "too many items for count",
+            )
+            .at(start)
+            .with_value(count)
+            .into());
+        }
+
+        let mut written = self
+            .write_bits(count_bits, count)
+            .map_err(|e| Error::wrap(e, "write_counted", start))?;
+        for item in items {
+            written += f(self, item).map_err(|e| Error::wrap(e, "write_counted", start))?;
+        }
+        Ok(written)
+    }
+
+    /// Write `value`, constrained to `[lb, ub]`, as its offset from `lb` in
+    /// `ceil(log2(ub - lb + 1))` bits, as read by
+    /// `BitRead::read_constrained()`.  Returns the number of bits written.
+    ///
+    /// A value outside the range is an `InvalidInput` error with the value,
+    /// cast to a `u64`, as its value, and nothing is written.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_constrained(3, 6, 4).unwrap();
+    /// writer.write_constrained(-10, 21, -3).unwrap();
+    /// writer.write_constrained(7, 7, 7).unwrap(); // no bits at all
+    /// writer.write_bits(1, 0).unwrap();
+    /// assert_eq!(writer.into_inner(), [0b0100_1110]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lb > ub`.
+    fn write_constrained(&mut self, lb: i64, ub: i64, value: i64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let (_, nbits) = constrained::span(lb, ub);
+        let start = self.bit_position();
+        if value < lb || value > ub {
+            return Err(Error::new(
+                io::ErrorKind::InvalidInput,
+                "write_constrained",
+                "value out of range",
+            )
+            .at(start)
+            .with_value(value as u64)
+            .into());
+        }
+        self.write_bits(nbits, value.wrapping_sub(lb) as u64)
+            .map_err(|e| Error::wrap(e, "write_constrained", start))
+    }
+
+    /// Write `value` as `digits` BCD digits, with leading zeros as needed, as
+    /// read by `BitRead::read_bcd()`.  Returns the number of bits written.
+    ///
+    /// A value with more than `digits` digits is an `InvalidInput` error
+    /// carrying the value, and nothing is written.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// assert_eq!(writer.write_bcd(1234, 6).unwrap(), 24);
+    /// assert!(writer.write_bcd(1234, 3).is_err());
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0x00, 0x12, 0x34]);
+    /// ```
+    fn write_bcd(&mut self, value: u64, digits: usize) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        bcd::write_digits(self, "write_bcd", value, digits)
+    }
+
+    /// Write `value` as `digits` BCD digits as for `write_bcd()`, followed by
+    /// a 0xF filler nibble if `digits` is odd, so the field fills whole
+    /// bytes.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// assert_eq!(writer.write_bcd_filled(123, 3).unwrap(), 16);
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0x12, 0x3f]);
+    /// ```
+    fn write_bcd_filled(&mut self, value: u64, digits: usize) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let mut written = bcd::write_digits(self, "write_bcd_filled", value, digits)?;
+        if digits % 2 == 1 {
+            written += self
+                .write_bits(4, bcd::FILLER)
+                .map_err(|e| Error::wrap(e, "write_bcd_filled", start))?;
+        }
+        Ok(written)
+    }
+
+    /// Write `values`, which must be non-decreasing and below `universe`,
+    /// with Elias–Fano coding as the `elias_fano` module describes,
+    /// returning the number of bits written.
+    ///
+    /// A value out of order or outside the universe is an `InvalidInput` error
+    /// carrying its index as its value, and nothing is written.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// // Two low bits each, 01 11 10 10, then the high parts 0, 1, 2 and 3 as
+    /// // gaps of 0, 1, 1 and 1: 1 01 01 01.
+    /// assert_eq!(writer.write_elias_fano(&[1, 7, 10, 14], 16).unwrap(), 15);
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0b0111_1010, 0b1010_1010]);
+    /// ```
+    fn write_elias_fano(&mut self, values: &[u64], universe: u64) -> io::Result<usize>
+    where
+        Self: Sized,
+    {
+        let start = self.bit_position();
+        let invalid = |message, index: usize| {
+            Err(
+                Error::new(io::ErrorKind::InvalidInput, "write_elias_fano", message)
+                    .at(start)
+                    .with_value(index as u64)
+                    .into(),
+            )
+        };
+        if let Some(index) = values.windows(2).position(|pair| pair[1] < pair[0]) {
+            return invalid("values out of order", index + 1);
+        }
+        if let Some(last) = values.last() {
+            if *last >= universe {
+                return invalid("value outside universe", values.len() - 1);
+            }
+        }
+
+        let wrap = |e| Error::wrap(e, "write_elias_fano", start);
+        let l = elias_fano::low_bits(values.len() as u64, universe);
+        let mut written = 0;
+        for &value in values {
+            written += self.write_bits(l, value).map_err(wrap)?;
+        }
+        let mut high = 0;
+        for &value in values {
+            let mut gap = (value >> l) - high;
+            high = value >> l;
+            while gap > 0 {
+                let chunk = core::cmp::min(gap, 64) as u8;
+                written += self.write_bits(chunk, 0).map_err(wrap)?;
+                gap -= chunk as u64;
+            }
+            self.write_bit(1).map_err(wrap)?;
+            written += 1;
+        }
+        Ok(written)
+    }
+
+    /// Write a presence flag, then if there's a value write it with `f`, as
+    /// read by `BitRead::read_optional()`.  Returns the number of bits written,
+    /// including the flag, so `f` should return the number it wrote.
+    ///
+    /// ```
+    /// use bitrw::{BitWrite, BitWriter};
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_optional(Some(&0b10110), |w, &v| w.write_bits(5, v)).unwrap();
+    /// writer.write_optional(None, |w, &v| w.write_bits(5, v)).unwrap();
+    /// writer.flush().unwrap();
+    /// assert_eq!(writer.into_inner(), [0b1_10110_0_0]);
+    /// ```
+    fn write_optional<T, F>(&mut self, value: Option<&T>, f: F) -> io::Result<usize>
+    where
+        Self: Sized,
+        F: Fn(&mut Self, &T) -> io::Result<usize>,
+    {
+        let start = self.bit_position();
+        self.write_bit(value.is_some() as u8)
+            .map_err(|e| Error::wrap(e, "write_optional", start))?;
+        match value {
+            Some(value) => f(self, value)
+                .map(|written| written + 1)
+                .map_err(|e| Error::wrap(e, "write_optional", start)),
+            None => Ok(1),
+        }
+    }
+}
+
+impl<T: BitRead + ?Sized> BitRead for &mut T {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        (**self).read_bits(nbits)
+    }
+
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<u8> {
+        (**self).read_bit()
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        (**self).bit_position()
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        (**self).vlc_limit()
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitRead for BitReader<R, O> {
+    #[inline]
+    fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+        BitReader::read_bits(self, nbits)
+    }
+
+    #[inline]
+    fn read_bit(&mut self) -> io::Result<u8> {
+        BitReader::read_bit(self)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position())
+    }
+
+    #[inline]
+    fn vlc_limit(&self) -> Option<u32> {
+        BitReader::vlc_limit(self)
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWrite for BitWriter<W, O> {
+    #[inline]
+    fn write_bits(&mut self, nbits: u8, value: u64) -> io::Result<usize> {
+        BitWriter::write_bits(self, nbits, value)
+    }
+
+    #[inline]
+    fn write_bit(&mut self, bit: u8) -> io::Result<()> {
+        BitWriter::write_bit(self, bit)
+    }
+
+    #[inline]
+    fn bit_position(&self) -> Option<u64> {
+        Some(self.position())
+    }
+}
@@ -0,0 +1,71 @@
+//! Limits on the length of a single variable-length code, such as a unary or
+//! gamma code, so crafted input can't make one read scan megabytes of
+//! zeros.
+
+use crate::error::{Error, ErrorReason};
+use crate::io;
+use crate::order::BitOrder;
+use crate::BitReader;
+
+/// The default for `BitRead::vlc_limit()` and `BitReader::set_vlc_limit()`.
+pub(crate) const DEFAULT_VLC_LIMIT: u32 = 1024;
+
+/// Fail with the error for a code longer than `limit`, if `code_bits` is.
+pub(crate) fn check(
+    limit: Option<u32>,
+    code_bits: u64,
+    op: &'static str,
+    start: Option<u64>,
+) -> io::Result<()> {
+    match limit {
+        Some(limit) if code_bits > limit as u64 => {
+            Err(
+                Error::new(io::ErrorKind::InvalidData, op, "code longer than limit")
+                    .at(start)
+                    .with_value(limit as u64)
+                    .with_reason(ErrorReason::VlcLimit)
+                    .into(),
+            )
+        }
+        _ => Ok(()),
+    }
+}
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// The most bits a single variable-length code may take, as set by
+    /// `set_vlc_limit()`.
+    pub fn vlc_limit(&self) -> Option<u32> {
+        self.vlc_limit
+    }
+
+    /// Set the most bits a single variable-length code may take, such as
+    /// those read by `BitRead::read_unary()`, `BitRead::read_gamma()` and
+    /// `read_rice_block()`, or remove the limit with `None`.  It's 1024 to
+    /// begin with.
+    ///
+    /// A longer code is an `InvalidData` error with the reason
+    /// `ErrorReason::VlcLimit`, carrying the limit as its value, and the
+    /// offset at which the code started.  The read gives up as soon as the
+    /// code is known to be too long, having consumed at most the limit, rather
+    /// than scanning on to find its end.
+    ///
+    /// ```
+    /// use bitrw::{BitRead, BitReader};
+    ///
+    /// let mut reader = BitReader::new(&[0x00, 0x00, 0x01][..]);
+    /// reader.set_vlc_limit(Some(16));
+    /// assert!(reader.read_unary().is_err());
+    /// assert_eq!(reader.position(), 16);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// If the limit is zero, which no code fits.
+    pub fn set_vlc_limit(&mut self, max_bits_per_code: Option<u32>) {
+        assert!(
+            max_bits_per_code != Some(0),
+            "a code takes at least one bit"
+        );
+        self.vlc_limit = max_bits_per_code;
+    }
+}
@@ -0,0 +1,101 @@
+//! Field widths fixed at compile time, for `read_n()` and `write_n()`.
+
+use crate::io;
+use crate::order::BitOrder;
+use crate::primitive::Primitive;
+use crate::{BitReader, BitWriter};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A field width of `N` bits, as a type.
+///
+/// `Width<N>` implements `FieldType` for `N` from 1 to 64, so any other width
+/// passed to `read_n()` or `write_n()` fails to compile.
+///
+/// ```compile_fail
+/// let mut reader = bitrw::BitReader::new(&[0u8; 16][..]);
+/// reader.read_n::<0>().unwrap();
+/// ```
+///
+/// ```compile_fail
+/// let mut reader = bitrw::BitReader::new(&[0u8; 16][..]);
+/// reader.read_n::<65>().unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Width<const N: u8>;
+
+/// The smallest unsigned integer type which holds a field of a `Width`.
+pub trait FieldType: private::Sealed {
+    /// `u8`, `u16`, `u32` or `u64`.
+    type Type: Primitive;
+}
+
+macro_rules! field_type {
+    ($t:ty: $($n:literal)*) => {
+        $(
+            impl private::Sealed for Width<$n> {}
+
+            impl FieldType for Width<$n> {
+                type Type = $t;
+            }
+        )*
+    };
+}
+
+field_type!(u8: 1 2 3 4 5 6 7 8);
+field_type!(u16: 9 10 11 12 13 14 15 16);
+field_type!(u32: 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+field_type!(u64: 33 34 35 36 37 38 39 40 41 42 43 44 45 46 47 48
+    49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64);
+
+impl<R: io::Read, O: BitOrder> BitReader<R, O> {
+    /// Read an `N` bit field as the smallest unsigned type which holds it, as
+    /// `read_bits(N)` would, with the width checked at compile time.
+    ///
+    /// ```
+    /// use bitrw::BitReader;
+    ///
+    /// let mut reader = BitReader::new(&[0xab, 0xcd][..]);
+    /// let tag: u8 = reader.read_n::<4>().unwrap();
+    /// let value: u16 = reader.read_n::<12>().unwrap();
+    /// assert_eq!((tag, value), (0xa, 0xbcd));
+    /// ```
+    #[inline]
+    pub fn read_n<const N: u8>(&mut self) -> io::Result<<Width<N> as FieldType>::Type>
+    where
+        Width<N>: FieldType,
+    {
+        // SAFETY: every width with a `FieldType` is at most 64.
+        let bits = unsafe { self.read_bits_unchecked(N) }?;
+        Ok(Primitive::from_bits(bits))
+    }
+}
+
+impl<W: io::Write, O: BitOrder> BitWriter<W, O> {
+    /// Write `value` as an `N` bit field, as `write_bits(N, _)` would, with the
+    /// width checked at compile time and the value's type the smallest
+    /// unsigned type which holds it.  Bits of `value` above the low `N` are
+    /// ignored.
+    ///
+    /// ```
+    /// use bitrw::BitWriter;
+    ///
+    /// let mut writer = BitWriter::new(Vec::new());
+    /// writer.write_n::<4>(0xa).unwrap();
+    /// writer.write_n::<12>(0xbcd).unwrap();
+    /// assert_eq!(writer.into_inner(), [0xab, 0xcd]);
+    /// ```
+    #[inline]
+    pub fn write_n<const N: u8>(
+        &mut self,
+        value: <Width<N> as FieldType>::Type,
+    ) -> io::Result<usize>
+    where
+        Width<N>: FieldType,
+    {
+        // SAFETY: every width with a `FieldType` is at most 64.
+        unsafe { self.write_bits_unchecked(N, value.to_bits()) }
+    }
+}
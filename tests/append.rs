@@ -0,0 +1,118 @@
+extern crate bitrw;
+
+use std::fs::OpenOptions;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, BitWriterBuilder, Lsb0};
+
+fn records(writer: &mut BitWriter<impl io::Write, impl bitrw::BitOrder>, fields: &[(u8, u64)]) {
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+}
+
+const FIRST: [(u8, u64); 2] = [(5, 0b10110), (8, 0xa5)];
+const SECOND: [(u8, u64); 2] = [(3, 0b011), (4, 0b1001)];
+
+#[test]
+fn thirteen_then_seven() {
+    let mut writer = BitWriter::new(Cursor::new(Vec::new()));
+    records(&mut writer, &FIRST);
+    let valid = writer.position();
+    assert_eq!(valid, 13);
+    writer.flush().unwrap();
+    let file = writer.into_inner();
+
+    // A new session, with only the bit count kept.
+    let mut writer = BitWriter::append_to(file, valid).unwrap();
+    assert_eq!(writer.position(), 13);
+    records(&mut writer, &SECOND);
+    writer.flush().unwrap();
+    let appended = writer.into_inner().into_inner();
+
+    let mut writer = BitWriter::new(Vec::new());
+    records(&mut writer, &FIRST);
+    records(&mut writer, &SECOND);
+    writer.flush().unwrap();
+    assert_eq!(appended, writer.into_inner());
+
+    let mut reader = BitReader::new(&appended[..]);
+    for &(nbits, value) in FIRST.iter().chain(&SECOND) {
+        assert_eq!(reader.read_bits(nbits).unwrap(), value);
+    }
+}
+
+#[test]
+fn over_ones_padding_and_lsb0() {
+    let mut writer = BitWriterBuilder::new()
+        .bit_order(Lsb0)
+        .pad_with_ones(true)
+        .build(Cursor::new(Vec::new()));
+    records(&mut writer, &FIRST);
+    writer.flush().unwrap();
+    let mut file = writer.into_inner();
+    assert_eq!(file.get_ref()[1] >> 5, 0b111);
+    file.set_position(0);
+
+    let mut writer = BitWriter::append_to_with_order(file, 13, Lsb0).unwrap();
+    records(&mut writer, &SECOND);
+    writer.flush().unwrap();
+    let appended = writer.into_inner().into_inner();
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    records(&mut writer, &FIRST);
+    records(&mut writer, &SECOND);
+    writer.flush().unwrap();
+    assert_eq!(appended, writer.into_inner());
+}
+
+#[test]
+fn whole_bytes_and_empty() {
+    let mut writer = BitWriter::append_to(Cursor::new(vec![0x12, 0xff]), 8).unwrap();
+    writer.write_bits(8, 0x34).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0x12, 0x34]);
+
+    let mut writer = BitWriter::append_to(Cursor::new(Vec::new()), 0).unwrap();
+    writer.write_bits(8, 0x56).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0x56]);
+}
+
+#[test]
+fn shorter_than_valid_bits() {
+    let err = BitWriter::append_to(Cursor::new(vec![0x12]), 9).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!((err.value(), err.expected()), (Some(8), Some(9)));
+}
+
+#[test]
+fn file_across_sessions() {
+    let path = std::env::temp_dir().join(format!("bitrw-append-{}", std::process::id()));
+    let open = || {
+        OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .unwrap()
+    };
+    let mut valid = 0;
+    for session in 0..5u64 {
+        let mut writer = BitWriter::append_to(open(), valid).unwrap();
+        writer.write_bits(3, session).unwrap();
+        writer.write_bits(2, 0b01).unwrap();
+        valid = writer.position();
+        writer.flush().unwrap();
+    }
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(data.len(), 4);
+    let mut reader = BitReader::new(&data[..]);
+    for session in 0..5 {
+        assert_eq!(reader.read_bits(3).unwrap(), session);
+        assert_eq!(reader.read_bits(2).unwrap(), 0b01);
+    }
+}
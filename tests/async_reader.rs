@@ -0,0 +1,94 @@
+#![cfg(feature = "tokio")]
+
+extern crate bitrw;
+extern crate rand;
+extern crate tokio;
+
+use rand::Rng;
+use std::io::Cursor;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+
+use bitrw::{AsyncBitReader, BitReader, BitWrite, BitWriter, Lsb0};
+
+fn random_fields(n: usize) -> Vec<(u8, u64)> {
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|_| {
+            let nbits = rng.gen::<u8>() % 65;
+            let value = rng.gen::<u64>();
+            (
+                nbits,
+                if nbits == 64 {
+                    value
+                } else {
+                    value & ((1 << nbits) - 1)
+                },
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn small_chunks_match_sync_reader() {
+    let fields = random_fields(500);
+    let mut writer = BitWriter::new(Vec::new());
+    for &(nbits, value) in &fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let (mut tx, rx) = tokio::io::duplex(3);
+    let feed = data.clone();
+    let producer = tokio::spawn(async move {
+        for chunk in feed.chunks(2) {
+            tx.write_all(chunk).await.unwrap();
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let mut reader = AsyncBitReader::new(rx);
+    let mut sync = BitReader::new(Cursor::new(data));
+    for &(nbits, value) in &fields {
+        assert_eq!(reader.read_bits(nbits).await.unwrap(), value);
+        assert_eq!(sync.read_bits(nbits).unwrap(), value);
+    }
+    producer.await.unwrap();
+}
+
+#[tokio::test]
+async fn typed_reads_and_eof() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b011).unwrap();
+    writer.write_le(0x1234_5678_u32).unwrap();
+    writer.write_be(-2_i16).unwrap();
+    writer.flush().unwrap();
+
+    let mut reader = AsyncBitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+    assert_eq!(reader.read_bits(3).await.unwrap(), 0b011);
+    assert_eq!(reader.read_le::<u32>().await.unwrap(), 0x1234_5678);
+    assert_eq!(reader.read_be::<i16>().await.unwrap(), -2);
+    assert_eq!(reader.read_bits(5).await.unwrap(), 0);
+
+    let err = reader.read_bit().await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[tokio::test]
+async fn cancelled_read_keeps_fetched_bits() {
+    let (mut tx, rx) = tokio::io::duplex(16);
+    let mut reader = AsyncBitReader::new(rx);
+
+    tx.write_all(&[0xde, 0xad]).await.unwrap();
+    assert_eq!(reader.read_bits(4).await.unwrap(), 0xd);
+
+    // Only 12 of the 36 bits needed are available, so this times out after
+    // pulling the second byte into the bit buffer.
+    let pending = tokio::time::timeout(Duration::from_millis(20), reader.read_bits(36));
+    assert!(pending.await.is_err());
+
+    tx.write_all(&[0xbe, 0xef, 0x42]).await.unwrap();
+    assert_eq!(reader.read_bits(36).await.unwrap(), 0xe_adbe_ef42);
+}
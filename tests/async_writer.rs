@@ -0,0 +1,83 @@
+#![cfg(feature = "tokio")]
+
+extern crate bitrw;
+extern crate rand;
+extern crate tokio;
+
+use rand::Rng;
+use std::time::Duration;
+
+use tokio::io::AsyncReadExt;
+
+use bitrw::{AsyncBitReader, AsyncBitWriter, BitWrite, BitWriter, Lsb0};
+
+#[tokio::test]
+async fn duplex_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..20_000)
+        .map(|_| {
+            let nbits = rng.gen::<u8>() % 64 + 1;
+            (nbits, rng.gen::<u64>() >> (64 - nbits))
+        })
+        .collect();
+
+    let (tx, rx) = tokio::io::duplex(100);
+    let expected = fields.clone();
+    let producer = tokio::spawn(async move {
+        let mut writer = AsyncBitWriter::with_order(tx, Lsb0);
+        let mut total = 0;
+        for &(nbits, value) in &expected {
+            total += writer.write_bits(nbits, value).await.unwrap();
+        }
+        let padding = writer.flush().await.unwrap();
+        assert_eq!((total + padding) % 8, 0);
+        total
+    });
+
+    let mut reader = AsyncBitReader::with_order(rx, Lsb0);
+    let mut total = 0;
+    for &(nbits, value) in &fields {
+        assert_eq!(reader.read_bits(nbits).await.unwrap(), value);
+        total += nbits as usize;
+    }
+    assert_eq!(producer.await.unwrap(), total);
+}
+
+#[tokio::test]
+async fn matches_sync_writer() {
+    let mut sync = BitWriter::new(Vec::new());
+    let mut writer = AsyncBitWriter::new(Vec::new());
+    for phase in 0..8u8 {
+        sync.write_bits(phase, 0x5a).unwrap();
+        sync.write_be(0xbeef_u16).unwrap();
+        sync.write_le(-3_i32).unwrap();
+        sync.write(1.5_f32).unwrap();
+
+        writer.write_bits(phase, 0x5a).await.unwrap();
+        writer.write_be(0xbeef_u16).await.unwrap();
+        writer.write_le(-3_i32).await.unwrap();
+        writer.write(1.5_f32).await.unwrap();
+    }
+    assert_eq!(writer.flush().await.unwrap(), sync.flush().unwrap());
+    assert_eq!(writer.into_inner(), sync.into_inner());
+}
+
+#[tokio::test]
+async fn flush_pushes_padded_final_byte() {
+    let (tx, mut rx) = tokio::io::duplex(16);
+    let mut writer = AsyncBitWriter::new(tx);
+
+    writer.write_bits(12, 0xabc).await.unwrap();
+    writer.write_bit(1).await.unwrap();
+
+    // Complete bytes stay in the writer's buffer until a flush.
+    let mut byte = [0; 1];
+    let early = tokio::time::timeout(Duration::from_millis(20), rx.read_exact(&mut byte));
+    assert!(early.await.is_err());
+
+    assert_eq!(writer.flush().await.unwrap(), 3);
+
+    let mut bytes = [0; 2];
+    rx.read_exact(&mut bytes).await.unwrap();
+    assert_eq!(bytes, [0xab, 0b1100_1000]);
+}
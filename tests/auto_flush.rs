@@ -0,0 +1,149 @@
+extern crate bitrw;
+
+use std::io::{self, Write};
+
+use bitrw::{BitWriter, BitWriterBuilder, Lsb0, WriterParts};
+
+/// Records how much had been written at every flush.
+#[derive(Default)]
+struct Flushes {
+    data: Vec<u8>,
+    flushes: Vec<usize>,
+}
+
+impl Write for Flushes {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.flushes.push(self.data.len());
+        Ok(())
+    }
+}
+
+fn expected(fields: u64) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for i in 0..fields {
+        writer.write_bits(7, i).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn flushes_every_n_bytes() {
+    let mut writer = BitWriterBuilder::new()
+        .auto_flush_every_bytes(10)
+        .build(Flushes::default());
+    for i in 0..80 {
+        writer.write_bits(7, i).unwrap();
+    }
+    assert_eq!(writer.get_ref().flushes, [10, 20, 30, 40, 50, 60, 70]);
+    // 560 bits is exactly 70 bytes, so nothing is pending.
+    assert_eq!(writer.get_ref().data.len(), 70);
+    writer.flush().unwrap();
+    let inner = writer.into_inner();
+    assert_eq!(inner.flushes, [10, 20, 30, 40, 50, 60, 70, 70]);
+    assert_eq!(inner.data, expected(80));
+}
+
+#[test]
+fn never_pads_mid_stream() {
+    let mut writer = BitWriterBuilder::new()
+        .auto_flush_every_bytes(3)
+        .bit_order(Lsb0)
+        .build(Flushes::default());
+    let mut plain = BitWriter::with_order(Vec::new(), Lsb0);
+    for i in 0..1000u64 {
+        let nbits = (i % 13 + 1) as u8;
+        writer.write_bits(nbits, i * 0x9e37).unwrap();
+        plain.write_bits(nbits, i * 0x9e37).unwrap();
+        if i % 7 == 0 {
+            writer.frame_boundary().unwrap();
+        }
+    }
+    assert_eq!(writer.position(), plain.position());
+    writer.flush().unwrap();
+    plain.flush().unwrap();
+    let inner = writer.into_inner();
+    assert_eq!(inner.data, plain.into_inner());
+    assert!(inner.flushes.windows(2).all(|pair| pair[0] <= pair[1]));
+}
+
+#[test]
+fn with_batching() {
+    // The internal buffer is written out before each flush.
+    let mut writer = BitWriterBuilder::new()
+        .capacity(4)
+        .auto_flush_every_bytes(6)
+        .build(Flushes::default());
+    for i in 0..40 {
+        writer.write_bits(7, i).unwrap();
+    }
+    assert_eq!(writer.get_ref().flushes, [6, 12, 18, 24, 30]);
+    assert_eq!(writer.get_ref().data.len(), 34);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().data, expected(40));
+
+    // Bytes bypassing the buffer count too.
+    let mut writer = BitWriterBuilder::new()
+        .capacity(4)
+        .auto_flush_every_bytes(6)
+        .build(Flushes::default());
+    writer.write_bytes(&[1, 2, 3]).unwrap();
+    writer.write_bytes(&[4, 5, 6, 7, 8]).unwrap();
+    assert_eq!(writer.get_ref().flushes, [8]);
+}
+
+#[test]
+fn frame_boundary_keeps_partial_byte() {
+    let mut writer = BitWriterBuilder::new()
+        .capacity(100)
+        .build(Flushes::default());
+    writer.write_bits(20, 0xabcde).unwrap();
+    assert!(writer.get_ref().data.is_empty());
+    writer.frame_boundary().unwrap();
+    assert_eq!(writer.get_ref().data, [0xab, 0xcd]);
+    assert_eq!(writer.get_ref().flushes, [2]);
+    assert_eq!(writer.position(), 20);
+    writer.write_bits(4, 0xf).unwrap();
+    writer.frame_boundary().unwrap();
+    assert_eq!(writer.into_inner().data, [0xab, 0xcd, 0xef]);
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut writer = BitWriter::new(Flushes::default());
+    for i in 0..1000 {
+        writer.write_bits(9, i).unwrap();
+    }
+    assert!(writer.get_ref().flushes.is_empty());
+}
+
+#[test]
+fn kept_by_parts() {
+    let mut writer = BitWriterBuilder::new()
+        .auto_flush_every_bytes(2)
+        .build(Flushes::default());
+    writer.write_bits(12, 0xabc).unwrap();
+    let (inner, parts) = writer.into_parts();
+    assert_eq!(parts.auto_flush(), 2);
+    // The count restarts, so the byte before into_parts() isn't counted.
+    let mut writer = BitWriter::<_>::from_parts(inner, parts);
+    writer.write_bits(12, 0xdef).unwrap();
+    assert_eq!(writer.get_ref().flushes, [3]);
+    writer.write_bits(8, 0x12).unwrap();
+    assert_eq!(writer.get_ref().flushes, [3]);
+    writer.write_bits(8, 0x34).unwrap();
+    assert_eq!(writer.get_ref().flushes, [3, 5]);
+
+    let parts = WriterParts::new(0, 0).unwrap().with_auto_flush(1);
+    let mut writer = BitWriter::<_>::from_parts(Flushes::default(), parts);
+    // Bytes passed on together are flushed together.
+    writer.write_bits(16, 0).unwrap();
+    assert_eq!(writer.get_ref().flushes, [2]);
+    writer.write_bits(8, 0).unwrap();
+    assert_eq!(writer.get_ref().flushes, [2, 3]);
+}
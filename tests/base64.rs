@@ -0,0 +1,175 @@
+#![cfg(feature = "base64")]
+
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{Base64Alphabet, Base64BitReader, Base64BitWriter, BitRead, BitWrite, BitWriter};
+
+fn encode(fields: &[(u8, u64)], alphabet: Base64Alphabet) -> String {
+    let mut writer = Base64BitWriter::new(String::new(), alphabet);
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.finish().unwrap()
+}
+
+/// Plain base64 of whole bytes, as any other encoder would give.
+fn reference(bytes: &[u8], alphabet: Base64Alphabet) -> String {
+    let chars: &[u8] = match alphabet {
+        Base64Alphabet::Standard => {
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+        }
+        Base64Alphabet::UrlSafe => {
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+        }
+    };
+    let mut text = String::new();
+    for group in bytes.chunks(3) {
+        let mut word = [0; 3];
+        word[..group.len()].copy_from_slice(group);
+        let word = (word[0] as u32) << 16 | (word[1] as u32) << 8 | word[2] as u32;
+        for i in 0..=group.len() {
+            text.push(chars[(word >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+        if alphabet == Base64Alphabet::Standard {
+            for _ in group.len()..3 {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[test]
+fn rfc_4648_vectors() {
+    let vectors = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy"),
+    ];
+    for &(plain, text) in &vectors {
+        let fields: Vec<(u8, u64)> = plain.bytes().map(|b| (8, b as u64)).collect();
+        assert_eq!(encode(&fields, Base64Alphabet::Standard), text);
+        assert_eq!(reference(plain.as_bytes(), Base64Alphabet::Standard), text);
+
+        let mut reader = Base64BitReader::new(text, Base64Alphabet::Standard).unwrap();
+        assert_eq!(reader.len_bits(), plain.len() as u64 * 8);
+        let decoded: Vec<u8> = (0..plain.len())
+            .map(|_| reader.read_bits(8).unwrap() as u8)
+            .collect();
+        assert_eq!(decoded, plain.as_bytes());
+    }
+}
+
+#[test]
+fn url_safe() {
+    let fields = [(8, 0xfb), (8, 0xff)];
+    assert_eq!(encode(&fields, Base64Alphabet::Standard), "+/8=");
+    assert_eq!(encode(&fields, Base64Alphabet::UrlSafe), "-_8");
+
+    // Padding is optional when reading either alphabet.
+    for &(text, alphabet) in &[
+        ("+/8=", Base64Alphabet::Standard),
+        ("+/8", Base64Alphabet::Standard),
+        ("-_8", Base64Alphabet::UrlSafe),
+        ("-_8=", Base64Alphabet::UrlSafe),
+    ] {
+        let mut reader = Base64BitReader::new(text, alphabet).unwrap();
+        assert_eq!(reader.read_bits(16).unwrap(), 0xfbff);
+    }
+    assert!(Base64BitReader::new("+/8=", Base64Alphabet::UrlSafe).is_err());
+    assert!(Base64BitReader::new("-_8", Base64Alphabet::Standard).is_err());
+}
+
+#[test]
+fn exact_lengths() {
+    let mut rng = rand::thread_rng();
+    for &alphabet in &[Base64Alphabet::Standard, Base64Alphabet::UrlSafe] {
+        for nbits in 0..=64u8 {
+            let value = rng.gen::<u64>().checked_shr(64 - nbits as u32).unwrap_or(0);
+            let text = encode(&[(nbits, value)], alphabet);
+
+            // The same bytes as a BitWriter, then the length if needed.
+            let mut writer = BitWriter::new(Vec::new());
+            writer.write_bits(nbits, value).unwrap();
+            writer.flush().unwrap();
+            let mut expected = reference(&writer.into_inner(), alphabet);
+            if nbits % 8 != 0 {
+                expected.push_str(&format!(".{}", nbits % 8));
+            }
+            assert_eq!(text, expected);
+
+            let mut reader = Base64BitReader::new(&text, alphabet).unwrap();
+            assert_eq!(reader.len_bits(), nbits as u64, "{}", text);
+            assert_eq!(reader.read_bits(nbits).unwrap(), value, "{}", text);
+            assert_eq!(reader.position(), nbits as u64);
+            let err = reader.read_bits(1).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        }
+    }
+}
+
+#[test]
+fn fields() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..200)
+        .map(|_| {
+            let nbits = rng.gen_range(1, 65);
+            (nbits, rng.gen::<u64>() >> (64 - nbits))
+        })
+        .collect();
+    let text = encode(&fields, Base64Alphabet::Standard);
+    let mut reader = Base64BitReader::new(&text, Base64Alphabet::Standard).unwrap();
+    for &(nbits, value) in &fields {
+        assert_eq!(reader.read_bits(nbits).unwrap(), value);
+    }
+    assert_eq!(reader.position(), reader.len_bits());
+}
+
+#[test]
+fn traits() {
+    let mut writer = Base64BitWriter::new(String::new(), Base64Alphabet::Standard);
+    writer.write(0x1234u16).unwrap();
+    writer.write_bit(1).unwrap();
+    assert_eq!(BitWrite::bit_position(&writer), Some(17));
+    let text = writer.finish().unwrap();
+    assert_eq!(text, "EjSA.1");
+
+    let mut reader = Base64BitReader::new(&text, Base64Alphabet::Standard).unwrap();
+    assert_eq!(reader.read::<u16>().unwrap(), 0x1234);
+    assert_eq!(reader.read_bit().unwrap(), 1);
+}
+
+#[test]
+fn invalid() {
+    let cases = [
+        ("Zg=!", "invalid character", Some(12)),
+        ("Z", "invalid length", Some(6)),
+        ("Zg=", "invalid length", Some(12)),
+        ("Zg===", "invalid length", Some(12)),
+        ("Zm9v=", "invalid length", Some(24)),
+        ("Zh==", "padding bits not zero", Some(6)),
+        ("Zg==.0", "invalid length suffix", Some(24)),
+        ("Zg==.8", "invalid length suffix", Some(24)),
+        (".5", "invalid length", Some(0)),
+        ("Zg.x", "invalid length suffix", Some(12)),
+    ];
+    for &(text, message, offset) in &cases {
+        let err = Base64BitReader::new(text, Base64Alphabet::Standard).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{}", text);
+        assert!(err.to_string().contains(message), "{}: {}", text, err);
+        assert_eq!(
+            bitrw::Error::from_io(&err).unwrap().offset(),
+            offset,
+            "{}",
+            text
+        );
+    }
+}
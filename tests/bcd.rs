@@ -0,0 +1,111 @@
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+#[test]
+fn odd_digits_at_odd_offset() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    assert_eq!(writer.write_bcd(98765, 5).unwrap(), 20);
+    writer.write_bits(1, 1).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data, [0b1011_0011, 0b0000_1110, 0b1100_1011]);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    assert_eq!(reader.read_bcd(5).unwrap(), 98765);
+    assert_eq!(reader.read_bit().unwrap(), 1);
+}
+
+#[test]
+fn leading_zeros() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bcd(42, 24).unwrap();
+    writer.write_bcd(0, 1).unwrap();
+    writer.write_bcd(0, 0).unwrap();
+    writer.write_bcd(u64::MAX, 20).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(&data[..12], &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x42]);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_bcd(24).unwrap(), 42);
+    assert_eq!(reader.read_bcd(1).unwrap(), 0);
+    assert_eq!(reader.read_bcd(20).unwrap(), u64::MAX);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(
+        reader.read_bcd_string(24).unwrap(),
+        "000000000000000000000042"
+    );
+}
+
+#[test]
+fn filler() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_bcd_filled(12345, 5).unwrap(), 24);
+    assert_eq!(writer.write_bcd_filled(12, 2).unwrap(), 8);
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data, [0x12, 0x34, 0x5f, 0x12]);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_bcd_filled(5).unwrap(), 12345);
+    assert_eq!(reader.read_bcd_filled(2).unwrap(), 12);
+
+    let mut reader = BitReader::new(&[0x12, 0x30][..]);
+    let err = reader.read_bcd_filled(3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(12));
+    assert_eq!(err.value(), Some(0));
+}
+
+#[test]
+fn lsb0_low_nibble_first() {
+    // TBCD: 1234567 with the filler in the high nibble of the last byte.
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bcd_filled(1234567, 7).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x21, 0x43, 0x65, 0xf7]);
+}
+
+#[test]
+fn invalid_nibble() {
+    let data = [0x12, 0x3a, 0x45];
+    let mut reader = BitReader::new(&data[..]);
+    let err = reader.read_bcd(6).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(12));
+    assert_eq!(err.value(), Some(0xa));
+    // Left after the bad nibble.
+    assert_eq!(reader.position(), 16);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert!(reader.read_bcd_string(6).is_err());
+}
+
+#[test]
+fn overflow() {
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer.write_bcd(1000, 3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(1000));
+    assert!(writer.write_bcd(1, 0).is_err());
+    assert_eq!(writer.position(), 0);
+    writer.write_bcd(999, 3).unwrap();
+
+    // Twenty nines is more than a u64 holds.
+    let data = [0x99; 10];
+    let mut reader = BitReader::new(&data[..]);
+    let err = reader.read_bcd(20).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(76));
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_bcd_string(20).unwrap(), "9".repeat(20));
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "num-bigint")]
+
+extern crate bitrw;
+extern crate num_bigint;
+extern crate rand;
+
+use num_bigint::BigUint;
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, Error, Lsb0};
+
+fn random_big(nbits: u64) -> BigUint {
+    let mut rng = rand::thread_rng();
+    let bytes: Vec<u8> = (0..nbits.div_ceil(8)).map(|_| rng.gen::<u8>()).collect();
+    let value = BigUint::from_bytes_be(&bytes);
+    // Force the full width so the top digit is exercised.
+    let top = BigUint::from(1u8) << (nbits - 1);
+    (value % &top) | top
+}
+
+#[test]
+fn roundtrip_msb0() {
+    for &nbits in &[65, 256, 1000] {
+        for &phase in &[1, 3, 7] {
+            let values = [random_big(nbits), BigUint::from(5u8), random_big(nbits)];
+
+            let mut writer = BitWriter::new(Vec::new());
+            writer.write_bits(phase, 0x55).unwrap();
+            for value in &values {
+                assert_eq!(writer.write_bits_big(nbits, value).unwrap(), nbits);
+            }
+            writer.write_bits(5, 0b10110).unwrap();
+            writer.flush().unwrap();
+
+            let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+            reader.read_bits(phase).unwrap();
+            for value in &values {
+                assert_eq!(&reader.read_bits_big(nbits).unwrap(), value);
+            }
+            assert_eq!(reader.read_bits(5).unwrap(), 0b10110);
+        }
+    }
+}
+
+#[test]
+fn roundtrip_lsb0() {
+    for &nbits in &[65, 256, 1000] {
+        let value = random_big(nbits);
+
+        let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+        writer.write_bits(3, 0b101).unwrap();
+        writer.write_bits_big(nbits, &value).unwrap();
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.read_bits_big(nbits).unwrap(), value);
+    }
+}
+
+#[test]
+fn matches_read_bits() {
+    let data = vec![0xde, 0xad, 0xbe, 0xef, 0x01, 0x23, 0x45, 0x67, 0x89];
+
+    let mut reader = BitReader::new(Cursor::new(data.clone()));
+    reader.read_bits(4).unwrap();
+    let big = reader.read_bits_big(68).unwrap();
+    assert_eq!(
+        big,
+        BigUint::from_bytes_be(&data) % (BigUint::from(1u8) << 68)
+    );
+
+    let mut reader = BitReader::with_order(Cursor::new(data.clone()), Lsb0);
+    let low = reader.read_bits(64).unwrap();
+    let high = reader.read_bits(8).unwrap();
+
+    let mut reader = BitReader::with_order(Cursor::new(data), Lsb0);
+    let big = reader.read_bits_big(72).unwrap();
+    assert_eq!(big, (BigUint::from(high) << 64) | BigUint::from(low));
+}
+
+#[test]
+fn zero_extends_short_values() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_bits_big(80, &BigUint::from(0x1234u16))
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0, 0, 0, 0, 0, 0, 0, 0, 0x12, 0x34]);
+}
+
+#[test]
+fn oversized_value_rejected() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+
+    let value = BigUint::from(1u8) << 100;
+    let err = writer.write_bits_big(100, &value).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(Error::from_io(&err).unwrap().value(), Some(101));
+
+    assert_eq!(writer.position(), 3);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0]);
+}
+
+#[test]
+fn truncated_read_errors() {
+    let mut reader = BitReader::new(Cursor::new(vec![0xff; 16]));
+    let err = reader.read_bits_big(129).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(Error::from_io(&err).unwrap().op(), "read_bits_big");
+}
+
+#[test]
+fn huge_width_from_a_short_stream() {
+    let mut reader = BitReader::new(Cursor::new(vec![0xff; 16]));
+    let err = reader.read_bits_big(u64::MAX / 2).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(Error::from_io(&err).unwrap().offset(), Some(0));
+}
@@ -0,0 +1,204 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitReaderBuilder, Lsb0};
+
+/// The offsets of every bit equal to `target`, reading the stream `Msb0`.
+fn reference(data: &[u8], target: u8) -> Vec<u64> {
+    (0..data.len() as u64 * 8)
+        .filter(|&i| (data[i as usize / 8] >> (7 - i % 8)) & 1 == target)
+        .collect()
+}
+
+fn scan_set(data: &[u8]) -> Vec<u64> {
+    let mut reader = BitReader::new(data);
+    let mut found = Vec::new();
+    while let Some(offset) = reader.next_set_bit().unwrap() {
+        assert_eq!(reader.position(), offset + 1);
+        found.push(offset);
+    }
+    assert_eq!(reader.position(), data.len() as u64 * 8);
+    found
+}
+
+#[test]
+fn long_zero_runs() {
+    let mut data = vec![0; 5000];
+    data[0] = 0x80;
+    data[1234] = 0x01;
+    data[1235] = 0x80;
+    data[4999] = 0x10;
+    assert_eq!(scan_set(&data), [0, 1234 * 8 + 7, 1235 * 8, 4999 * 8 + 3]);
+    assert_eq!(scan_set(&[0; 3000]), []);
+    assert_eq!(scan_set(&[]), []);
+}
+
+#[test]
+fn byte_boundaries() {
+    let data = [0x01, 0x80, 0x00, 0x01, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x01];
+    assert_eq!(scan_set(&data), reference(&data, 1));
+}
+
+#[test]
+fn random() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let len = rng.gen_range(0, 100);
+        let data: Vec<u8> = (0..len)
+            .map(|_| {
+                if rng.gen_weighted_bool(4) {
+                    rng.gen()
+                } else {
+                    0
+                }
+            })
+            .collect();
+        assert_eq!(scan_set(&data), reference(&data, 1));
+
+        let inverted: Vec<u8> = data.iter().map(|b| !b).collect();
+        let mut reader = BitReader::new(&inverted[..]);
+        let mut found = Vec::new();
+        while let Some(offset) = reader.next_clear_bit().unwrap() {
+            found.push(offset);
+        }
+        assert_eq!(found, reference(&data, 1));
+    }
+}
+
+#[test]
+fn after_unaligned_read() {
+    let data = [0b1010_0000, 0, 0, 0, 0, 0, 0, 0, 0, 0b0000_0100];
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_bits(1).unwrap(), 1);
+    assert_eq!(reader.next_set_bit().unwrap(), Some(2));
+    assert_eq!(reader.next_set_bit().unwrap(), Some(77));
+    // The rest of the byte is still there to read.
+    assert_eq!(reader.read_bits(2).unwrap(), 0);
+    assert_eq!(reader.next_set_bit().unwrap(), None);
+}
+
+#[test]
+fn lsb0() {
+    let data = [0x00, 0x10, 0xff];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    assert_eq!(reader.next_set_bit().unwrap(), Some(12));
+    assert_eq!(reader.read_bits(3).unwrap(), 0);
+    assert_eq!(reader.next_clear_bit().unwrap(), None);
+
+    let data = [0xff, 0xef];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    assert_eq!(reader.next_clear_bit().unwrap(), Some(12));
+}
+
+#[test]
+fn stops_at_limit() {
+    let data = [0, 0, 0, 0x10, 0x80];
+    let mut reader = BitReaderBuilder::new().limit_bits(27).build(&data[..]);
+    assert_eq!(reader.next_set_bit().unwrap(), None);
+    assert_eq!(reader.position(), 27);
+
+    let mut reader = BitReaderBuilder::new().limit_bits(28).build(&data[..]);
+    assert_eq!(reader.next_set_bit().unwrap(), Some(27));
+    assert_eq!(reader.next_set_bit().unwrap(), None);
+}
+
+#[test]
+fn short_reads_and_errors() {
+    // A reader returning a byte at a time, then failing.
+    struct Trickle<'a>(&'a [u8]);
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match self.0.split_first() {
+                Some((&byte, rest)) => {
+                    buf[0] = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                None => Err(io::Error::other("broken")),
+            }
+        }
+    }
+
+    let mut reader = BitReader::new(Trickle(&[0, 0, 0x40, 0]));
+    assert_eq!(reader.next_set_bit().unwrap(), Some(17));
+    assert!(reader.next_set_bit().is_err());
+    assert_eq!(reader.position(), 32);
+}
+
+fn reference_count(data: &[u8], start: u64, nbits: u64) -> u64 {
+    reference(data, 1)
+        .into_iter()
+        .filter(|&i| i >= start && i < start + nbits)
+        .count() as u64
+}
+
+#[test]
+fn count_ones_random() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..300 {
+        let len = rng.gen_range(0, 3000);
+        let data: Vec<u8> = (0..len).map(|_| rng.gen()).collect();
+        let total = len as u64 * 8;
+        let start = rng.gen_range(0, total + 1);
+        let nbits = rng.gen_range(0, total - start + 1);
+
+        let mut reader = BitReader::new(&data[..]);
+        reader.skip_bits(start).unwrap();
+        assert_eq!(
+            reader.count_ones(nbits).unwrap(),
+            reference_count(&data, start, nbits),
+            "{} bits from {}",
+            nbits,
+            start
+        );
+        assert_eq!(reader.position(), start + nbits);
+
+        let mut reader = BitReader::new(io::Cursor::new(&data));
+        assert_eq!(
+            reader.count_ones_at(start, nbits).unwrap(),
+            reference_count(&data, start, nbits)
+        );
+        assert_eq!(reader.position(), 0);
+    }
+}
+
+#[test]
+fn count_ones_mid_byte() {
+    let data = [0xff; 4];
+    let mut reader = BitReader::new(&data[..]);
+    reader.read_bits(3).unwrap();
+    assert_eq!(reader.count_ones(2).unwrap(), 2);
+    assert_eq!(reader.count_ones(20).unwrap(), 20);
+    assert_eq!(reader.read_bits(7).unwrap(), 0x7f);
+
+    let mut reader = BitReader::with_order(&[0x0f, 0xf0][..], Lsb0);
+    assert_eq!(reader.count_ones(6).unwrap(), 4);
+    assert_eq!(reader.count_ones(6).unwrap(), 0);
+}
+
+#[test]
+fn count_ones_errors() {
+    let data = [0xff; 4];
+    let mut reader = BitReaderBuilder::new().limit_bits(20).build(&data[..]);
+    reader.read_bits(1).unwrap();
+    let err = reader.count_ones(20).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 1);
+    assert_eq!(reader.count_ones(19).unwrap(), 19);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(
+        reader.count_ones(33).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // The position is restored after an error too.
+    let mut reader = BitReader::new(io::Cursor::new(&data));
+    reader.read_bits(8).unwrap();
+    assert!(reader.count_ones_at(4, 100).is_err());
+    assert_eq!(reader.position(), 8);
+    assert_eq!(reader.read_bits(8).unwrap(), 0xff);
+}
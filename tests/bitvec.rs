@@ -0,0 +1,98 @@
+#![cfg(feature = "bitvec")]
+
+extern crate bitrw;
+extern crate bitvec;
+extern crate rand;
+
+use bitvec::prelude::{bits, bitvec, BitVec};
+use bitvec::prelude::{Lsb0 as BvLsb0, Msb0 as BvMsb0};
+use rand::Rng;
+use std::io::Cursor;
+
+use bitrw::{BitReader, BitWriter, Lsb0};
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen::<u8>()).collect()
+}
+
+// Slice index i is stream bit i: for Msb0 streams that's exactly the layout of
+// a BitSlice<u8, Msb0> over the same bytes.
+#[test]
+fn msb0_stream_matches_msb0_bitslice_layout() {
+    let data = random_bytes(37);
+
+    let mut reader = BitReader::new(Cursor::new(data.clone()));
+    let bits = reader.read_bitvec(data.len() * 8).unwrap();
+    assert_eq!(bits, BitVec::<u8, BvMsb0>::from_vec(data.clone()));
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bitslice(&bits).unwrap();
+    assert_eq!(writer.into_inner(), data);
+}
+
+// An Lsb0 stream visits each byte from its low bit, so the sequence of stream
+// bits matches a BitSlice<u8, bitvec::Lsb0> over the bytes.
+#[test]
+fn lsb0_stream_ordering() {
+    let data = random_bytes(19);
+
+    let mut reader = BitReader::with_order(Cursor::new(data.clone()), Lsb0);
+    let bits = reader.read_bitvec(data.len() * 8).unwrap();
+    let expected = BitVec::<u8, BvLsb0>::from_vec(data.clone());
+    assert!(bits.iter().eq(expected.iter()));
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bitslice(&bits).unwrap();
+    assert_eq!(writer.into_inner(), data);
+}
+
+#[test]
+fn first_bits_written_in_stream_order() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_bitslice(bits![u8, BvMsb0; 1, 0, 1, 1])
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b1011_0000]);
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer
+        .write_bitslice(bits![u8, BvMsb0; 1, 0, 1, 1])
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b0000_1101]);
+}
+
+#[test]
+fn unaligned_slices_and_phases() {
+    let data = random_bytes(64);
+    let reference = BitVec::<u8, BvMsb0>::from_vec(data.clone());
+
+    for &(phase, start, len) in &[(0, 3, 200), (5, 0, 129), (7, 11, 1), (1, 6, 300)] {
+        let mut reader = BitReader::new(Cursor::new(data.clone()));
+        reader.read_bits(phase).unwrap();
+
+        let mut dst = bitvec![u8, BvMsb0; 1; start + len + 5];
+        reader
+            .read_into_bitslice(&mut dst[start..start + len])
+            .unwrap();
+        let phase = phase as usize;
+        assert_eq!(dst[start..start + len], reference[phase..phase + len]);
+        assert!(dst[..start].all() && dst[start + len..].all());
+
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_bits(phase as u8, 0).unwrap();
+        writer.write_bitslice(&dst[start..start + len]).unwrap();
+        writer.flush().unwrap();
+
+        let written = BitVec::<u8, BvMsb0>::from_vec(writer.into_inner());
+        assert_eq!(written[phase..phase + len], reference[phase..phase + len]);
+    }
+}
+
+#[test]
+fn short_read_errors() {
+    let mut reader = BitReader::new(Cursor::new(vec![0xff]));
+    assert!(reader.read_bitvec(9).is_err());
+}
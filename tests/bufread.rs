@@ -0,0 +1,88 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, BufReader, Cursor, SeekFrom};
+
+use bitrw::{BitRead, BitReader, BitReaderBuilder, Lsb0};
+
+fn random_fields(count: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..count).map(|_| rng.gen_range(0, 65)).collect()
+}
+
+#[test]
+fn same_as_bit_reader() {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..4096).map(|_| rng.gen::<u8>()).collect();
+
+    // Small buffers to make reads straddle refills.
+    for &capacity in &[1, 3, 8, 9, 4096] {
+        let mut plain = BitReader::new(&data[..]);
+        let buffered = BufReader::with_capacity(capacity, &data[..]);
+        let mut reader = BitReader::from_buf_read(buffered);
+        for nbits in random_fields(500) {
+            assert_eq!(
+                reader.read_bits(nbits).unwrap(),
+                plain.read_bits(nbits).unwrap()
+            );
+            assert_eq!(reader.position(), plain.position());
+        }
+    }
+
+    let mut plain = BitReader::with_order(&data[..], Lsb0);
+    let mut reader = BitReader::from_buf_read_with_order(BufReader::new(&data[..]), Lsb0);
+    for nbits in random_fields(500) {
+        assert_eq!(
+            reader.read_bits(nbits).unwrap(),
+            plain.read_bits(nbits).unwrap()
+        );
+    }
+    assert_eq!(reader.read_bit().unwrap(), plain.read_bit().unwrap());
+    assert_eq!(reader.read::<u32>().unwrap(), plain.read::<u32>().unwrap());
+}
+
+#[test]
+fn seek_through_get_mut() {
+    let data: Vec<u8> = (0..=255).collect();
+    let buffered = BufReader::with_capacity(16, Cursor::new(&data));
+    let mut reader = BitReader::from_buf_read(buffered);
+
+    assert_eq!(reader.read_bits(12).unwrap(), 0x000);
+    reader.get_mut().seek(SeekFrom::Start(100 * 8 + 4)).unwrap();
+    assert_eq!(reader.read_bits(16).unwrap(), 0x4656);
+    reader.get_mut().skip_bits(4).unwrap();
+    assert_eq!(reader.read_bits(8).unwrap(), 103);
+    assert_eq!(reader.position(), 104 * 8);
+
+    reader.get_mut().seek(SeekFrom::Start(8)).unwrap();
+    assert_eq!(reader.read_bits(24).unwrap(), 0x010203);
+
+    let mut plain = reader.into_inner();
+    assert_eq!(plain.read_bits(8).unwrap(), 4);
+}
+
+#[test]
+fn end_of_stream() {
+    let data = [0xab, 0xcd, 0xef];
+    let buffered = BufReader::with_capacity(2, &data[..]);
+    let mut reader = BitReader::from_buf_read(buffered);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    let err = reader.read_bits(24).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    // As with a plain BitReader the bits loaded before the error remain.
+    assert_eq!(reader.position(), 4);
+    assert_eq!(reader.read_bits(20).unwrap(), 0xbcdef);
+}
+
+#[test]
+fn limit() {
+    let data = [0xff; 16];
+    let inner = BitReaderBuilder::new()
+        .limit_bits(20)
+        .build(BufReader::new(&data[..]));
+    let mut reader = bitrw::BufBitReader::new(inner);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xffff);
+    assert!(reader.read_bits(8).is_err());
+    assert_eq!(reader.read_bits(4).unwrap(), 0xf);
+}
@@ -0,0 +1,123 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor, Write};
+
+use bitrw::{BitReader, BitReaderBuilder, BitWrite, BitWriter, BitWriterBuilder, Lsb0};
+
+/// Records the size of every write it's given.
+#[derive(Default)]
+struct Recorder {
+    data: Vec<u8>,
+    writes: Vec<usize>,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        self.writes.push(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn defaults_match_new() {
+    let mut built = BitWriterBuilder::new().build(Vec::new());
+    let mut plain = BitWriter::new(Vec::new());
+    for writer in [&mut built, &mut plain].iter_mut() {
+        writer.write_bits(11, 0x5a5).unwrap();
+        writer.flush().unwrap();
+    }
+    assert_eq!(built.into_inner(), plain.into_inner());
+
+    let data = vec![0x12, 0x34];
+    let mut built = BitReaderBuilder::new().build(Cursor::new(data.clone()));
+    let mut plain = BitReader::new(Cursor::new(data));
+    assert_eq!(built.read_bits(13).unwrap(), plain.read_bits(13).unwrap());
+}
+
+#[test]
+fn bit_order_and_padding() {
+    let mut writer = BitWriterBuilder::new()
+        .pad_with_ones(true)
+        .bit_order(Lsb0)
+        .build(Vec::new());
+    writer.write_bits(3, 0b001).unwrap();
+    assert_eq!(writer.flush().unwrap(), 5);
+    assert_eq!(writer.into_inner(), [0b1111_1001]);
+
+    let mut reader = BitReaderBuilder::new()
+        .bit_order(Lsb0)
+        .build(Cursor::new(vec![0b1111_1001]));
+    assert_eq!(reader.read_bits(3).unwrap(), 0b001);
+}
+
+#[test]
+fn capacity_batches_writes() {
+    let mut unbatched = BitWriter::new(Recorder::default());
+    let mut batched = BitWriterBuilder::new()
+        .capacity(16)
+        .build(Recorder::default());
+
+    for i in 0..40u64 {
+        unbatched.write_bits(7, i).unwrap();
+        batched.write_bits(7, i).unwrap();
+    }
+    unbatched.flush().unwrap();
+    batched.flush().unwrap();
+
+    let unbatched = unbatched.into_inner();
+    let batched = batched.into_inner();
+    assert_eq!(batched.data, unbatched.data);
+    assert_eq!(unbatched.data.len(), 35);
+    assert!(unbatched.writes.iter().all(|&n| n <= 1));
+    // Two full batches, then the remainder on flush.
+    assert_eq!(batched.writes, [16, 16, 3]);
+}
+
+#[test]
+fn limits() {
+    let mut reader = BitReaderBuilder::new()
+        .limit_bits(20)
+        .build(Cursor::new(vec![0xff; 4]));
+    reader.read_bits(16).unwrap();
+    let err = reader.read_bits(5).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    // Nothing was consumed by the failed read.
+    assert_eq!(reader.read_bits(4).unwrap(), 0xf);
+    assert!(reader.read_bit().is_err());
+
+    let mut writer = BitWriterBuilder::new()
+        .limit_bits(10)
+        .bit_order(Lsb0)
+        .build(Vec::new());
+    writer.write_bits(8, 0xaa).unwrap();
+    assert_eq!(
+        writer.write_bits(3, 0).unwrap_err().kind(),
+        io::ErrorKind::WriteZero
+    );
+    writer.write_bits(2, 0b11).unwrap();
+    assert_eq!(writer.flush().unwrap(), 6);
+    assert_eq!(writer.into_inner(), [0xaa, 0b11]);
+}
+
+#[test]
+fn all_options_together() {
+    let mut writer = BitWriterBuilder::new()
+        .bit_order(Lsb0)
+        .capacity(3)
+        .pad_with_ones(true)
+        .limit_bits(36)
+        .build(Recorder::default());
+    writer.write_be(0x1234_5678_u32).unwrap();
+    assert!(writer.write_bits(5, 0).is_err());
+    writer.write_bits(4, 0).unwrap();
+    writer.flush().unwrap();
+
+    let recorder = writer.into_inner();
+    assert_eq!(recorder.writes, [3, 2]);
+    assert_eq!(recorder.data, [0x12, 0x34, 0x56, 0x78, 0xf0]);
+}
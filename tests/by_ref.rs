@@ -0,0 +1,71 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, FromBits};
+
+#[test]
+fn by_ref_take_then_continue() {
+    let data: Vec<u8> = (0..32).collect();
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(3).unwrap();
+
+    {
+        let mut taken = reader.by_ref().take_bits(100);
+        let mut total = 0;
+        while taken.read_bits(7).is_ok() {
+            total += 7;
+        }
+        assert_eq!(total, 98);
+        assert_eq!(taken.remaining(), 2);
+        assert_eq!(
+            taken.read_bits(3).unwrap_err().kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+        taken.read_bits(2).unwrap();
+        assert_eq!(taken.remaining(), 0);
+        assert_eq!(taken.bit_position(), Some(103));
+    }
+
+    // The original carries on from bit 103.
+    assert_eq!(reader.position(), 103);
+    let mut expected = BitReader::new(Cursor::new(&data[..]));
+    expected.read_bits(64).unwrap();
+    expected.read_bits(39).unwrap();
+    assert_eq!(
+        reader.read_bits(64).unwrap(),
+        expected.read_bits(64).unwrap()
+    );
+}
+
+#[test]
+fn mut_ref_is_bit_read() {
+    fn read_pair<R: BitRead>(mut r: R) -> io::Result<(u64, u64)> {
+        Ok((r.read_bits(4)?, r.read_bits(4)?))
+    }
+
+    let mut reader = BitReader::new(Cursor::new([0x12, 0x34]));
+    assert_eq!(read_pair(&mut reader).unwrap(), (1, 2));
+    assert_eq!(read_pair(reader.by_ref()).unwrap(), (3, 4));
+    assert_eq!(reader.position(), 16);
+
+    let mut reader = BitReader::new(Cursor::new([0xab]));
+    let mut borrowed = &mut reader;
+    assert_eq!(u8::from_bits(&mut borrowed).unwrap(), 0xab);
+    assert_eq!(borrowed.bit_position(), Some(8));
+}
+
+#[test]
+fn take_nested() {
+    let mut reader = BitReader::new(Cursor::new([0xff; 4]));
+    let mut outer = reader.by_ref().take_bits(20);
+    {
+        let mut inner = outer.by_ref().take_bits(8);
+        assert_eq!(inner.read_bits(8).unwrap(), 0xff);
+        assert!(inner.read_bit().is_err());
+    }
+    assert_eq!(outer.remaining(), 12);
+    assert!(outer.read_bits(13).is_err());
+    assert_eq!(outer.read_bits(12).unwrap(), 0xfff);
+    assert_eq!(reader.position(), 20);
+}
@@ -0,0 +1,70 @@
+extern crate bitrw;
+
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use bitrw::{BitReader, BitWriter, ByteReverse, Lsb0};
+
+// A frame of a 3 bit version of 5, 5 bit type of 0x11, 16 bit length of
+// 0x0102 and 8 bit checksum of 0x7e, as captured from a link sending each
+// byte LSB first.
+const CAPTURE: [u8; 4] = [0x8d, 0x80, 0x40, 0x7e];
+
+fn fields<R: Read>(reader: &mut BitReader<R>) -> (u64, u64, u64, u64) {
+    (
+        reader.read_bits(3).unwrap(),
+        reader.read_bits(5).unwrap(),
+        reader.read_bits(16).unwrap(),
+        reader.read_bits(8).unwrap(),
+    )
+}
+
+#[test]
+fn decodes_capture() {
+    let mut reader = BitReader::new(ByteReverse::new(&CAPTURE[..]));
+    assert_eq!(fields(&mut reader), (5, 0x11, 0x0102, 0x7e));
+
+    // Without the adapter the fields come out wrong.
+    let mut reader = BitReader::new(&CAPTURE[..]);
+    assert_eq!(fields(&mut reader), (4, 13, 0x8040, 0x7e));
+
+    // As does an Lsb0 stream, which changes how fields are packed too.
+    let mut reader = BitReader::with_order(&CAPTURE[..], Lsb0);
+    assert_ne!(
+        (
+            reader.read_bits(3).unwrap(),
+            reader.read_bits(5).unwrap(),
+            reader.read_bits(16).unwrap(),
+        ),
+        (5, 0x11, 0x0102)
+    );
+}
+
+#[test]
+fn encodes_capture() {
+    let mut writer = BitWriter::new(ByteReverse::new(Vec::new()));
+    writer.write_bits(3, 5).unwrap();
+    writer.write_bits(5, 0x11).unwrap();
+    writer.write_bits(16, 0x0102).unwrap();
+    writer.write_bits(8, 0x7e).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), CAPTURE);
+}
+
+#[test]
+fn long_writes_and_seeking() {
+    let data: Vec<u8> = (0..1000).map(|i| (i * 7) as u8).collect();
+    let mut sink = ByteReverse::new(Vec::new());
+    sink.write_all(&data).unwrap();
+    let reversed = sink.into_inner();
+    assert_eq!(reversed.len(), data.len());
+    assert!(reversed
+        .iter()
+        .zip(&data)
+        .all(|(r, d)| *r == d.reverse_bits()));
+
+    let mut source = ByteReverse::new(Cursor::new(reversed));
+    source.seek(SeekFrom::Start(500)).unwrap();
+    let mut back = Vec::new();
+    source.read_to_end(&mut back).unwrap();
+    assert_eq!(back, &data[500..]);
+}
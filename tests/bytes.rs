@@ -0,0 +1,65 @@
+#![cfg(feature = "bytes")]
+
+extern crate bitrw;
+extern crate bytes;
+
+use bytes::{Buf, Bytes, BytesMut};
+
+use bitrw::{BitReader, BitWriter, Lsb0};
+
+#[test]
+fn read_across_chunks() {
+    let mut writer = BitWriter::new(Vec::new());
+    for i in 0..40u64 {
+        writer.write_bits((i % 23 + 1) as u8, i * 0x9e37).unwrap();
+    }
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    // Four non-contiguous pieces, one empty, split mid-field.
+    let a = Bytes::copy_from_slice(&data[..3]);
+    let b = Bytes::new();
+    let c = Bytes::copy_from_slice(&data[3..4]);
+    let d = Bytes::copy_from_slice(&data[4..]);
+    let chained = a.chain(b).chain(c).chain(d);
+    assert_eq!(chained.chunk().len(), 3);
+
+    let mut reader = BitReader::from_buf(chained);
+    for i in 0..40u64 {
+        let nbits = (i % 23 + 1) as u8;
+        let mask = (1 << nbits) - 1;
+        assert_eq!(reader.read_bits(nbits).unwrap(), (i * 0x9e37) & mask);
+    }
+}
+
+#[test]
+fn unread_bytes_remain_in_buf() {
+    let mut reader = BitReader::from_buf(Bytes::from_static(&[0xab, 0xcd, 0xef]));
+    assert_eq!(reader.read_bits(12).unwrap(), 0xabc);
+    assert_eq!(reader.get_ref().get_ref().remaining(), 1);
+    assert_eq!(reader.read_bits(12).unwrap(), 0xdef);
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn write_into_bytes_mut() {
+    let mut buf = BytesMut::new();
+    buf.extend_from_slice(b"hdr");
+
+    let mut writer = BitWriter::from_buf_mut_with_order(buf, Lsb0);
+    writer.write_bits(3, 0b110).unwrap();
+    writer.write_bits(9, 0x1ab).unwrap();
+    assert_eq!(writer.flush().unwrap(), 4);
+
+    let buf = writer.into_inner().into_inner();
+    assert_eq!(&buf[..], b"hdr\x5e\x0d");
+}
+
+#[test]
+fn write_into_full_slice_fails() {
+    let mut storage = [0u8; 1];
+    let mut writer = BitWriter::from_buf_mut(&mut storage[..]);
+    writer.write_bits(8, 0x42).unwrap();
+    assert!(writer.write_bits(8, 0x43).is_err());
+    assert_eq!(storage, [0x42]);
+}
@@ -0,0 +1,193 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+use std::io;
+
+use bitrw::{BitWriter, CarryBitWriter};
+
+const TOP: u64 = 1 << 24;
+
+/// An LZMA-style range encoder, keeping its own cached byte and count of
+/// 0xff bytes to propagate carries, as a reference.
+struct Reference {
+    low: u64,
+    range: u32,
+    cache: u8,
+    cache_size: u64,
+    out: Vec<u8>,
+}
+
+impl Reference {
+    fn new() -> Self {
+        Self {
+            low: 0,
+            range: u32::MAX,
+            cache: 0,
+            cache_size: 1,
+            out: Vec::new(),
+        }
+    }
+
+    fn encode(&mut self, bit: bool, prob: u32) {
+        let bound = (self.range >> 11) * prob;
+        if bit {
+            self.low += bound as u64;
+            self.range -= bound;
+        } else {
+            self.range = bound;
+        }
+        while (self.range as u64) < TOP {
+            self.range <<= 8;
+            self.shift_low();
+        }
+    }
+
+    fn shift_low(&mut self) {
+        if self.low < 0xff00_0000 || self.low > 0xffff_ffff {
+            let carry = (self.low >> 32) as u8;
+            let mut temp = self.cache;
+            loop {
+                self.out.push(temp.wrapping_add(carry));
+                temp = 0xff;
+                self.cache_size -= 1;
+                if self.cache_size == 0 {
+                    break;
+                }
+            }
+            self.cache = (self.low >> 24) as u8;
+        }
+        self.cache_size += 1;
+        self.low = (self.low & 0x00ff_ffff) << 8;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        for _ in 0..5 {
+            self.shift_low();
+        }
+        self.out
+    }
+}
+
+/// The same encoder, with carries propagated by a `CarryBitWriter`.
+struct Encoder {
+    low: u64,
+    range: u32,
+    writer: CarryBitWriter<Vec<u8>>,
+    longest_cascade: u64,
+}
+
+impl Encoder {
+    fn new() -> Self {
+        Self {
+            low: 0,
+            range: u32::MAX,
+            writer: CarryBitWriter::new(BitWriter::new(Vec::new())),
+            longest_cascade: 0,
+        }
+    }
+
+    fn encode(&mut self, bit: bool, prob: u32) {
+        let bound = (self.range >> 11) * prob;
+        if bit {
+            self.low += bound as u64;
+            self.range -= bound;
+            if self.low > 0xffff_ffff {
+                self.low &= 0xffff_ffff;
+                self.longest_cascade = self.longest_cascade.max(self.writer.held_bytes());
+                self.writer.propagate_carry().unwrap();
+            }
+        } else {
+            self.range = bound;
+        }
+        while (self.range as u64) < TOP {
+            self.range <<= 8;
+            self.writer.write_bits(8, self.low >> 24).unwrap();
+            self.low = (self.low & 0x00ff_ffff) << 8;
+        }
+    }
+
+    fn finish(mut self) -> (Vec<u8>, u64) {
+        self.writer.write_bits(32, self.low).unwrap();
+        self.writer.flush().unwrap();
+        (self.writer.into_inner().into_inner(), self.longest_cascade)
+    }
+}
+
+#[test]
+fn matches_range_coder() {
+    let mut longest = 0;
+    // Seeded, so some carry is sure to ripple through two 0xff bytes.
+    for seed in 0..200 {
+        let mut rng = XorShiftRng::from_seed([seed + 1, 2, 3, 4]);
+        let mut reference = Reference::new();
+        let mut encoder = Encoder::new();
+        for _ in 0..20_000 {
+            let prob = rng.gen_range(1, 2048);
+            let bit = rng.gen::<u32>() % 2048 >= prob;
+            reference.encode(bit, prob);
+            encoder.encode(bit, prob);
+        }
+        let expected = reference.finish();
+        let (out, cascade) = encoder.finish();
+        // The reference always starts with its initial cached zero byte.
+        assert_eq!(expected[0], 0);
+        assert_eq!(out, &expected[1..], "seed {}", seed);
+        longest = longest.max(cascade);
+    }
+    assert!(longest >= 3, "{}", longest);
+}
+
+#[test]
+fn cascade_through_partial_byte() {
+    let mut writer = CarryBitWriter::new(BitWriter::new(Vec::new()));
+    writer.write_bits(8, 0x7f).unwrap();
+    writer.write_bits(32, 0xffff_ffff).unwrap();
+    writer.write_bits(3, 0b111).unwrap();
+    assert_eq!(writer.held_bytes(), 5);
+    assert!(writer.get_ref().get_ref().is_empty());
+    writer.write_bits_with_carry(5, 0b10101, true).unwrap();
+    assert_eq!(writer.held_bytes(), 1);
+    assert_eq!(writer.get_ref().get_ref(), &[0x80, 0, 0, 0, 0]);
+    writer.propagate_carry().unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.position(), 48);
+    assert_eq!(
+        writer.into_inner().into_inner(),
+        [0x80, 0, 0, 0, 0, 0b0001_0110]
+    );
+}
+
+#[test]
+fn carry_within_partial_byte() {
+    let mut writer = CarryBitWriter::new(BitWriter::new(Vec::new()));
+    writer.write_bits(5, 0b01011).unwrap();
+    writer.propagate_carry().unwrap();
+    writer.write_bits(3, 0b000).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0b0110_0000]);
+}
+
+#[test]
+fn carry_past_held_bytes() {
+    let mut writer = CarryBitWriter::new(BitWriter::new(Vec::new()));
+    for _ in 0..2 {
+        let err = writer.propagate_carry().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        writer.write_bits(12, 0xfff).unwrap();
+    }
+    assert_eq!(writer.held_bytes(), 3);
+    let err = writer.propagate_carry().unwrap_err();
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(24));
+
+    // A byte carried into 0xff can't take another carry.
+    let mut writer = CarryBitWriter::new(BitWriter::new(Vec::new()));
+    writer.write_bits(8, 0xfe).unwrap();
+    writer.propagate_carry().unwrap();
+    writer.write_bits(4, 0xf).unwrap();
+    assert!(writer.propagate_carry().is_err());
+    // Nothing was changed.
+    writer.write_bits(4, 0x1).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0xff, 0xf1]);
+}
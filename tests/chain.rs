@@ -0,0 +1,99 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitReaderBuilder, Lsb0};
+
+#[test]
+fn chain_field_straddles_join() {
+    let first = BitReader::new(Cursor::new([0b1010_1100]));
+    let second = BitReader::new(Cursor::new([0b0111_0001, 0xff]));
+    let mut reader = first.chain(second);
+
+    assert_eq!(reader.read_bits(5).unwrap(), 0b10101);
+    assert!(!reader.first_done());
+    // Three bits from the first, then seven from the second.
+    assert_eq!(reader.read_bits(10).unwrap(), 0b100_0111000);
+    assert!(reader.first_done());
+    assert_eq!(reader.position(), 15);
+    assert_eq!(reader.read_bits(9).unwrap(), 0b1_1111_1111);
+}
+
+#[test]
+fn chain_matches_concatenation() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let data: Vec<u8> = (0..rng.gen_range(0, 24)).map(|_| rng.gen::<u8>()).collect();
+        let split = rng.gen_range(0, data.len() + 1);
+        // The first reader ends part way through a byte.
+        let limit = (split as u64 * 8).saturating_sub(rng.gen_range(0, 8));
+        let mut joined_bits = Vec::new();
+        let mut whole = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+        for _ in 0..limit {
+            joined_bits.push(whole.read_bit().unwrap());
+        }
+        let mut rest = BitReader::with_order(Cursor::new(&data[split..]), Lsb0);
+        while let Ok(bit) = rest.read_bit() {
+            joined_bits.push(bit);
+        }
+
+        let first = BitReaderBuilder::new()
+            .bit_order(Lsb0)
+            .limit_bits(limit)
+            .build(Cursor::new(&data[..split]));
+        let second = BitReader::with_order(Cursor::new(&data[split..]), Lsb0);
+        let mut reader = first.chain(second);
+
+        let mut offset = 0;
+        loop {
+            let nbits = rng.gen_range(0, 65);
+            match reader.read_bits(nbits) {
+                Ok(value) => {
+                    for i in 0..nbits as usize {
+                        assert_eq!((value >> i) & 1, joined_bits[offset + i] as u64);
+                    }
+                    offset += nbits as usize;
+                    assert_eq!(reader.position(), offset as u64);
+                }
+                Err(e) => {
+                    assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+                    assert!(offset + nbits as usize > joined_bits.len());
+                    break;
+                }
+            }
+        }
+        assert_eq!(reader.position(), offset as u64);
+    }
+}
+
+#[test]
+fn chain_second_runs_dry() {
+    let mut first = BitReader::new(Cursor::new([0b1111_0101]));
+    first.read_bits(4).unwrap();
+    let mut reader = first.chain(BitReader::new(Cursor::new([0xa5])));
+
+    // Needs 4 bits from the first and 8 from the second, plus 1 more.
+    let err = reader.read_bits(13).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 4);
+
+    // Nothing was consumed, so everything left can still be read.
+    assert_eq!(reader.read_bits(12).unwrap(), 0x5a5);
+    assert!(reader.read_bits(1).is_err());
+    assert_eq!(reader.read_bits(0).unwrap(), 0);
+    assert_eq!(reader.position(), 16);
+}
+
+#[test]
+fn chain_empty_readers() {
+    let first = BitReader::new(Cursor::new([]));
+    let mut reader = first.chain(BitReader::new(Cursor::new([0x80])));
+    assert_eq!(reader.read_bit().unwrap(), 1);
+
+    let first = BitReader::new(Cursor::new([0x80]));
+    let mut reader = first.chain(BitReader::new(Cursor::new([])));
+    assert_eq!(reader.read_bits(8).unwrap(), 0x80);
+    assert!(reader.read_bit().is_err());
+}
@@ -0,0 +1,165 @@
+extern crate bitrw;
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, FromBits, ToBits};
+
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    kind: u8,
+    weight: f32,
+}
+
+impl FromBits for Entry {
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+        Ok(Entry {
+            kind: r.read_bits(4)? as u8,
+            weight: r.read()?,
+        })
+    }
+}
+
+impl ToBits for Entry {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        Ok(w.write_bits(4, self.kind as u64)? + w.write(self.weight)?)
+    }
+}
+
+/// A frame header laid out as:
+///
+/// | bits | field                          |
+/// |------|--------------------------------|
+/// | 3    | version                        |
+/// | 4    | flags                          |
+/// | 16   | sequence                       |
+/// | 1+32 | optional timestamp             |
+/// | 5    | entry count                    |
+/// | 36n  | entries                        |
+#[derive(Debug, Clone, PartialEq)]
+struct FrameHeader {
+    version: u8,
+    flags: [bool; 4],
+    sequence: u16,
+    timestamp: Option<u32>,
+    entries: Vec<Entry>,
+}
+
+impl FromBits for FrameHeader {
+    fn from_bits<R: BitRead>(r: &mut R) -> io::Result<Self> {
+        let version = r.read_bits(3)? as u8;
+        let flags = <[bool; 4]>::from_bits(r)?;
+        let sequence = u16::from_bits(r)?;
+        let timestamp = Option::<u32>::from_bits(r)?;
+        let count = r.read_bits(5)? as usize;
+        let entries = r.read_vec(count)?;
+
+        Ok(FrameHeader {
+            version,
+            flags,
+            sequence,
+            timestamp,
+            entries,
+        })
+    }
+}
+
+impl ToBits for FrameHeader {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        Ok(w.write_bits(3, self.version as u64)?
+            + self.flags.to_bits(w)?
+            + self.sequence.to_bits(w)?
+            + self.timestamp.to_bits(w)?
+            + w.write_bits(5, self.entries.len() as u64)?
+            + self.entries.to_bits(w)?)
+    }
+}
+
+fn headers() -> Vec<FrameHeader> {
+    vec![
+        FrameHeader {
+            version: 5,
+            flags: [true, false, false, true],
+            sequence: 0xbeef,
+            timestamp: Some(1_500_000_000),
+            entries: vec![
+                Entry {
+                    kind: 3,
+                    weight: 0.25,
+                },
+                Entry {
+                    kind: 15,
+                    weight: -1e9,
+                },
+            ],
+        },
+        FrameHeader {
+            version: 1,
+            flags: [false; 4],
+            sequence: 1,
+            timestamp: None,
+            entries: vec![],
+        },
+    ]
+}
+
+#[test]
+fn frame_header_roundtrip_vec() {
+    let headers = headers();
+
+    let mut writer = BitWriter::new(Vec::new());
+    let mut written = 0;
+    for header in &headers {
+        written += header.to_bits(&mut writer).unwrap();
+    }
+    assert_eq!(written, (3 + 4 + 16 + 33 + 5 + 72) + (3 + 4 + 16 + 1 + 5));
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    for header in &headers {
+        assert_eq!(&FrameHeader::from_bits(&mut reader).unwrap(), header);
+    }
+}
+
+#[test]
+fn frame_header_roundtrip_file() {
+    let headers = headers();
+    let path = std::env::temp_dir().join(format!("bitrw-codec-{}.bin", std::process::id()));
+
+    {
+        let mut writer = BitWriter::new(BufWriter::new(File::create(&path).unwrap()));
+        headers.to_bits(&mut writer).unwrap();
+        writer.flush().unwrap();
+    }
+
+    let mut reader = BitReader::new(BufReader::new(File::open(&path).unwrap()));
+    let decoded: Vec<FrameHeader> = reader.read_vec(headers.len()).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(decoded, headers);
+}
+
+#[test]
+fn option_and_array_encoding() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(Some(0xa5_u8).to_bits(&mut writer).unwrap(), 9);
+    assert_eq!(None::<u8>.to_bits(&mut writer).unwrap(), 1);
+    assert_eq!([1_u8, 2, 3].to_bits(&mut writer).unwrap(), 24);
+    writer.flush().unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        [
+            0b1101_0010,
+            0b1000_0000,
+            0b0100_0000,
+            0b1000_0000,
+            0b1100_0000
+        ]
+    );
+}
+
+#[test]
+fn truncated_array_errors() {
+    let mut reader = BitReader::new(Cursor::new(vec![1, 2, 3]));
+    assert!(<[u16; 2]>::from_bits(&mut reader).is_err());
+}
@@ -0,0 +1,146 @@
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+/// Encodings worked from X.691 10.5.7: an unaligned constrained whole number
+/// is its offset from `lb` in the fewest bits which hold the range.
+const VECTORS: &[(i64, i64, i64, &[u8])] = &[
+    (0, 7, 5, &[0b1010_0000]),
+    (3, 6, 4, &[0b0100_0000]),
+    (0, 255, 255, &[0xff]),
+    (-128, 127, -128, &[0x00]),
+    (-128, 127, 127, &[0xff]),
+    (0, 256, 1, &[0x00, 0x80]),
+    (1, 1, 1, &[]),
+    (i64::MIN, i64::MAX, 0, &[0x80, 0, 0, 0, 0, 0, 0, 0]),
+    (i64::MIN, i64::MAX, i64::MIN, &[0; 8]),
+    (i64::MIN, i64::MAX, i64::MAX, &[0xff; 8]),
+    (
+        0,
+        i64::MAX,
+        i64::MAX,
+        &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe],
+    ),
+];
+
+#[test]
+fn vectors() {
+    for &(lb, ub, value, bytes) in VECTORS {
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_constrained(lb, ub, value).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), bytes, "{} in {}..{}", value, lb, ub);
+
+        let mut reader = BitReader::new(bytes);
+        assert_eq!(reader.read_constrained(lb, ub).unwrap(), value);
+    }
+}
+
+#[test]
+fn bits_written() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_constrained(5, 5, 5).unwrap(), 0);
+    assert_eq!(writer.write_constrained(0, 1, 1).unwrap(), 1);
+    assert_eq!(writer.write_constrained(0, 2, 1).unwrap(), 2);
+    assert_eq!(writer.write_constrained(-1, 1, 0).unwrap(), 2);
+    assert_eq!(writer.write_constrained(1, 1024, 1000).unwrap(), 10);
+    assert_eq!(writer.write_constrained(0, 1024, 1000).unwrap(), 11);
+    assert_eq!(writer.write_constrained(i64::MIN, i64::MAX, 0).unwrap(), 64);
+    assert_eq!(writer.position(), 90);
+}
+
+#[test]
+fn its_position() {
+    // A CAM's latitude, longitude and speed, from ETSI TS 102 894-2.
+    let fields = [
+        (-900_000_000, 900_000_001, 487_126_000),
+        (-1_800_000_000, 1_800_000_001, -45_000_000),
+        (0, 16383, 1234),
+    ];
+    let mut writer = BitWriter::new(Vec::new());
+    for &(lb, ub, value) in &fields {
+        writer.write_constrained(lb, ub, value).unwrap();
+    }
+    assert_eq!(writer.position(), 77);
+    writer.flush().unwrap();
+    let bytes = writer.into_inner();
+    assert_eq!(
+        bytes,
+        [0xa5, 0x5b, 0xb9, 0xe0, 0xd1, 0x36, 0x59, 0x80, 0x26, 0x90]
+    );
+
+    let mut reader = BitReader::new(&bytes[..]);
+    for &(lb, ub, value) in &fields {
+        assert_eq!(reader.read_constrained(lb, ub).unwrap(), value);
+    }
+}
+
+#[test]
+fn write_out_of_range() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+    for &value in &[2, 7, i64::MIN, i64::MAX] {
+        let err = writer.write_constrained(3, 6, value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = bitrw::Error::from_io(&err).unwrap();
+        assert_eq!(err.offset(), Some(3));
+        assert_eq!(err.value(), Some(value as u64));
+    }
+    assert_eq!(writer.position(), 3);
+
+    let err = writer.write_constrained(4, 4, 5).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn read_out_of_range() {
+    // 0..4 takes 3 bits, leaving offsets 5 to 7 unused.
+    let mut reader = BitReader::new(&[0b1001_0100][..]);
+    assert_eq!(reader.read_constrained(0, 4).unwrap(), 4);
+    let err = reader.read_constrained(0, 4).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(3));
+    assert_eq!(err.value(), Some(5));
+    assert_eq!(reader.position(), 6);
+}
+
+#[test]
+fn short_read() {
+    let mut reader = BitReader::new(&[0xff][..]);
+    let err = reader.read_constrained(0, 1023).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(0));
+
+    // A single value needs nothing from the stream.
+    assert_eq!(
+        BitReader::new(&[][..]).read_constrained(-9, -9).unwrap(),
+        -9
+    );
+}
+
+#[test]
+fn round_trip_widths() {
+    for shift in 0..64 {
+        let lb = -(((1u64 << shift) / 3) as i64);
+        let ub = lb + ((1u64 << shift) - 1) as i64;
+        let mut writer = BitWriter::new(Vec::new());
+        for &value in &[lb, ub, lb / 2 + ub / 2] {
+            writer.write_constrained(lb, ub, value).unwrap();
+        }
+        writer.flush().unwrap();
+        let bytes = writer.into_inner();
+        let mut reader = BitReader::new(&bytes[..]);
+        for &value in &[lb, ub, lb / 2 + ub / 2] {
+            assert_eq!(reader.read_constrained(lb, ub).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn inverted_range() {
+    let _ = BitWriter::new(Vec::new()).write_constrained(1, 0, 0);
+}
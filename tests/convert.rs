@@ -0,0 +1,119 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::fs::{self, OpenOptions};
+use std::io::Cursor;
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter, Lsb0};
+
+#[test]
+fn patch_file_in_place() {
+    // Records of a 3 bit tag, a 13 bit length and a 7 bit flags field.
+    let records: Vec<(u64, u64, u64)> = (0..50).map(|i| (i % 8, i * 101, i % 128)).collect();
+    let path = std::env::temp_dir().join(format!("bitrw-convert-{}.bin", std::process::id()));
+    {
+        let mut writer = BitWriter::new(Vec::new());
+        for &(tag, len, flags) in &records {
+            writer.write_bits(3, tag).unwrap();
+            writer.write_bits(13, len).unwrap();
+            writer.write_bits(7, flags).unwrap();
+        }
+        writer.flush().unwrap();
+        fs::write(&path, writer.into_inner()).unwrap();
+    }
+    let original = fs::read(&path).unwrap();
+
+    // Rewrite the length of record 17, then read on from there.
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(&path)
+        .unwrap();
+    let mut reader = BitReader::new(file);
+    for &(tag, len, flags) in &records[..17] {
+        assert_eq!(reader.read_bits(3).unwrap(), tag);
+        assert_eq!(reader.read_bits(13).unwrap(), len);
+        assert_eq!(reader.read_bits(7).unwrap(), flags);
+    }
+    assert_eq!(reader.read_bits(3).unwrap(), records[17].0);
+    let mut writer = reader.into_writer().unwrap();
+    assert_eq!(writer.position(), 17 * 23 + 3);
+    writer.write_bits(13, 0x1abc).unwrap();
+    let mut reader = writer.into_reader().unwrap();
+    assert_eq!(reader.read_bits(7).unwrap(), records[17].2);
+    for &(tag, len, flags) in &records[18..] {
+        assert_eq!(reader.read_bits(3).unwrap(), tag);
+        assert_eq!(reader.read_bits(13).unwrap(), len);
+        assert_eq!(reader.read_bits(7).unwrap(), flags);
+    }
+    drop(reader.into_inner());
+
+    let patched = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(patched.len(), original.len());
+    let mut reader = BitReader::new(&patched[..]);
+    for (i, &(tag, len, flags)) in records.iter().enumerate() {
+        assert_eq!(reader.read_bits(3).unwrap(), tag);
+        let expected = if i == 17 { 0x1abc } else { len };
+        assert_eq!(reader.read_bits(13).unwrap(), expected);
+        assert_eq!(reader.read_bits(7).unwrap(), flags);
+    }
+}
+
+#[test]
+fn random_patches() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let data: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
+        let offset = rng.gen_range(0, 200);
+        let nbits = rng.gen_range(1, 57);
+        let value = rng.gen::<u64>() >> (64 - nbits);
+
+        let mut reader = BitReader::with_order(Cursor::new(data.clone()), Lsb0);
+        reader.skip_bits(offset).unwrap();
+        // Leave some bytes loaded but unread.
+        let ahead = rng.gen_range(0, 30);
+        let peek = reader.read_bits(ahead).unwrap();
+        reader.unread_bits(ahead, peek).unwrap();
+
+        let mut writer = reader.into_writer().unwrap();
+        writer.write_bits(nbits, value).unwrap();
+        let mut reader = writer.into_reader().unwrap();
+        assert_eq!(reader.position(), offset + nbits as u64);
+        let n = (256 - offset - nbits as u64).min(64) as u8;
+        let after = reader.read_bits(n).unwrap();
+        let patched = reader.into_inner().into_inner();
+
+        // The same patch bit by bit.
+        let mut expected = data.clone();
+        for i in 0..nbits as u64 {
+            let bit = offset + i;
+            let byte = &mut expected[bit as usize / 8];
+            *byte &= !(1 << (bit % 8));
+            *byte |= ((value >> i) as u8 & 1) << (bit % 8);
+        }
+        assert_eq!(patched, expected);
+        let mut check = BitReader::with_order(&expected[..], Lsb0);
+        check.skip_bits(offset + nbits as u64).unwrap();
+        assert_eq!(after, check.read_bits(n).unwrap());
+    }
+}
+
+#[test]
+fn end_of_stream_and_limit() {
+    let mut reader = BitReaderBuilder::new()
+        .limit_bits(14)
+        .build(Cursor::new(vec![0xff]));
+    reader.read_bits(5).unwrap();
+    let mut writer = reader.into_writer().unwrap();
+    writer.write_bits(6, 0).unwrap();
+    assert!(writer.write_bits(4, 0).is_err());
+
+    // Past the end the byte is padded for the reader.
+    let mut reader = writer.into_reader().unwrap();
+    assert_eq!(reader.position(), 11);
+    assert_eq!(reader.read_bits(3).unwrap(), 0);
+    assert!(reader.read_bits(1).is_err());
+    assert_eq!(reader.into_inner().into_inner(), [0xf8, 0x00]);
+}
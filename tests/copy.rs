@@ -0,0 +1,344 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{copy_all_bits, copy_bits, BitReader, BitWriter, Lsb0};
+
+fn source(len: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen::<u8>()).collect()
+}
+
+fn check_copy_all(data: &[u8], phase: u8, out_phase: u8) {
+    let mut reader = BitReader::new(Cursor::new(data.to_vec()));
+    let head = reader.read_bits(phase).unwrap();
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(out_phase, 0).unwrap();
+
+    let copied = copy_all_bits(&mut reader, &mut writer).unwrap();
+    assert_eq!(copied, data.len() as u64 * 8 - phase as u64);
+    assert!(reader.read_bit().is_err());
+
+    let total = out_phase as u64 + copied;
+    let padding = writer.flush().unwrap() as u64;
+    assert_eq!((total + padding) % 8, 0);
+
+    let out = writer.into_inner();
+    assert_eq!(out.len() as u64 * 8, total + padding);
+
+    let mut expected = BitReader::new(Cursor::new(data.to_vec()));
+    let mut actual = BitReader::new(Cursor::new(out));
+    assert_eq!(expected.read_bits(phase).unwrap(), head);
+    assert_eq!(actual.read_bits(out_phase).unwrap(), 0);
+    for _ in 0..copied {
+        assert_eq!(actual.read_bit().unwrap(), expected.read_bit().unwrap());
+    }
+    assert_eq!(actual.read_bits(padding as u8).unwrap(), 0);
+}
+
+#[test]
+fn copy_all_empty_source() {
+    for out_phase in 0..8 {
+        check_copy_all(&[], 0, out_phase);
+    }
+}
+
+#[test]
+fn copy_all_single_byte() {
+    let data = [0b1011_0010];
+    for phase in 0..9 {
+        for out_phase in 0..8 {
+            check_copy_all(&data, phase, out_phase);
+        }
+    }
+}
+
+#[test]
+fn copy_all_several_kb() {
+    let data = source(5 * 1024 + 3);
+    for &phase in &[0, 1, 3, 7, 8, 13] {
+        for &out_phase in &[0, 5] {
+            check_copy_all(&data, phase, out_phase);
+        }
+    }
+}
+
+#[test]
+fn copy_counted() {
+    let data = source(64);
+    let mut reader = BitReader::new(Cursor::new(data.clone()));
+    reader.read_bits(3).unwrap();
+
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(copy_bits(&mut reader, &mut writer, 301).unwrap(), 301);
+    writer.write_bits(4, 0).unwrap();
+
+    let mut expected = BitReader::new(Cursor::new(data));
+    expected.read_bits(3).unwrap();
+    let mut actual = BitReader::new(Cursor::new(writer.into_inner()));
+    for _ in 0..301 {
+        assert_eq!(actual.read_bit().unwrap(), expected.read_bit().unwrap());
+    }
+    assert!(copy_bits(&mut reader, &mut BitWriter::new(Vec::new()), 512).is_err());
+}
+
+/// Copy a bit at a time, as a reference for the fast paths.
+fn slow_copy(data: &[u8], phase: u8, out_phase: u8, nbits: u64) -> Vec<u8> {
+    let mut reader = BitReader::new(Cursor::new(data));
+    reader.read_bits(phase).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(out_phase, 0x55).unwrap();
+    for _ in 0..nbits {
+        writer.write_bit(reader.read_bit().unwrap()).unwrap();
+    }
+    writer.write_bits(7, 0x55).unwrap();
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn copy_matches_slow_path() {
+    let mut rng = rand::thread_rng();
+    let data = source(3000);
+    for _ in 0..300 {
+        let phase = rng.gen_range(0, 8);
+        let out_phase = rng.gen_range(0, 8);
+        let nbits = rng.gen_range(0, data.len() as u64 * 8 - phase as u64 + 1);
+        let buffered = rng.gen::<bool>();
+
+        let mut reader = BitReader::new(Cursor::new(&data[..]));
+        reader.read_bits(phase).unwrap();
+        let mut writer = bitrw::BitWriterBuilder::new()
+            .capacity(if buffered { 100 } else { 0 })
+            .build(Vec::new());
+        writer.write_bits(out_phase, 0x55).unwrap();
+        assert_eq!(copy_bits(&mut reader, &mut writer, nbits).unwrap(), nbits);
+        assert_eq!(reader.position(), phase as u64 + nbits);
+        assert_eq!(writer.position(), out_phase as u64 + nbits);
+        writer.write_bits(7, 0x55).unwrap();
+        writer.flush().unwrap();
+
+        assert_eq!(
+            writer.into_inner(),
+            slow_copy(&data, phase, out_phase, nbits),
+            "phase {} out_phase {} nbits {}",
+            phase,
+            out_phase,
+            nbits
+        );
+    }
+}
+
+#[test]
+fn copy_lsb0_unaligned() {
+    let data = source(100);
+    let mut reader = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b101).unwrap();
+    copy_bits(&mut reader, &mut writer, 800).unwrap();
+    writer.flush().unwrap();
+    let out = writer.into_inner();
+
+    let mut expected = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+    let mut actual = BitReader::with_order(Cursor::new(&out[..]), Lsb0);
+    assert_eq!(actual.read_bits(3).unwrap(), 0b101);
+    for _ in 0..100 {
+        assert_eq!(actual.read_bits(8).unwrap(), expected.read_bits(8).unwrap());
+    }
+}
+
+#[test]
+fn copy_respects_limits() {
+    let data = source(100);
+    let mut reader = bitrw::BitReaderBuilder::new()
+        .limit_bits(100)
+        .build(Cursor::new(&data[..]));
+    let mut writer = BitWriter::new(Vec::new());
+    let err = copy_bits(&mut reader, &mut writer, 200).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    // As before, everything up to the limit was copied, in 32 bit pieces.
+    assert_eq!(reader.position(), 96);
+
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    let mut writer = bitrw::BitWriterBuilder::new()
+        .limit_bits(100)
+        .build(Vec::new());
+    let err = copy_bits(&mut reader, &mut writer, 200).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 96);
+}
+
+#[test]
+fn copy_past_end() {
+    let data = source(10);
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(5).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    let err = copy_bits(&mut reader, &mut writer, 80).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    // Everything there was has been copied.
+    assert_eq!(reader.position(), 80);
+    assert_eq!(writer.position(), 75);
+}
+
+#[test]
+fn skip_matches_read() {
+    let mut rng = rand::thread_rng();
+    let data = source(3000);
+    for _ in 0..300 {
+        let phase = rng.gen_range(0, 8);
+        let nbits = rng.gen_range(0, data.len() as u64 * 8 - phase as u64 + 1);
+
+        let mut skipped = BitReader::new(Cursor::new(&data[..]));
+        let mut read = BitReader::new(Cursor::new(&data[..]));
+        skipped.read_bits(phase).unwrap();
+        read.read_bits(phase).unwrap();
+        skipped.skip_bits(nbits).unwrap();
+        let mut remaining = nbits;
+        while remaining > 0 {
+            let chunk = remaining.min(64) as u8;
+            read.read_bits(chunk).unwrap();
+            remaining -= chunk as u64;
+        }
+        assert_eq!(skipped.position(), read.position());
+        let rest = (data.len() as u64 * 8 - read.position()).min(64) as u8;
+        assert_eq!(
+            skipped.read_bits(rest).unwrap(),
+            read.read_bits(rest).unwrap()
+        );
+    }
+}
+
+#[test]
+fn skip_errors() {
+    let data = source(10);
+    let mut reader = bitrw::BitReaderBuilder::new()
+        .limit_bits(40)
+        .build(Cursor::new(&data[..]));
+    reader.read_bits(3).unwrap();
+    let err = reader.skip_bits(38).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 3);
+    reader.skip_bits(37).unwrap();
+    assert_eq!(reader.position(), 40);
+
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(3).unwrap();
+    let err = reader.skip_bits(100).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 80);
+}
+
+/// The bits from `start` to `end` of `data`, packed and padded with zeros.
+fn bit_range(data: &[u8], start: u64, end: u64) -> Vec<u8> {
+    let mut reader = BitReader::new(data);
+    reader.skip_bits(start).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    for _ in start..end {
+        writer.write_bit(reader.read_bit().unwrap()).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn copy_all_stops_at_reader_limit() {
+    let data = source(32);
+    for &(phase, limit) in &[(0, 12), (3, 12), (5, 8), (0, 200), (7, 256), (2, 3)] {
+        let mut reader = bitrw::BitReaderBuilder::new()
+            .limit_bits(limit)
+            .build(Cursor::new(&data[..]));
+        reader.read_bits(phase).unwrap();
+        let mut writer = BitWriter::new(Vec::new());
+        let copied = copy_all_bits(&mut reader, &mut writer).unwrap();
+        assert_eq!(copied, limit - phase as u64, "{} to {}", phase, limit);
+        assert_eq!(reader.position(), limit);
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), bit_range(&data, phase as u64, limit));
+
+        // And with a limit on the writer which isn't reached.
+        let mut reader = bitrw::BitReaderBuilder::new()
+            .limit_bits(limit)
+            .build(Cursor::new(&data[..]));
+        reader.read_bits(phase).unwrap();
+        let mut writer = bitrw::BitWriterBuilder::new()
+            .limit_bits(1000)
+            .build(Vec::new());
+        assert_eq!(copy_all_bits(&mut reader, &mut writer).unwrap(), copied);
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), bit_range(&data, phase as u64, limit));
+    }
+
+    // A limit past the end copies up to the end.
+    let mut reader = bitrw::BitReaderBuilder::new()
+        .limit_bits(260)
+        .build(Cursor::new(&data[..]));
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(copy_all_bits(&mut reader, &mut writer).unwrap(), 256);
+}
+
+#[test]
+fn copy_all_respects_limit_after_unread() {
+    let data = source(32);
+    let mut reader = bitrw::BitReaderBuilder::new()
+        .limit_bits(70)
+        .build(Cursor::new(&data[..]));
+    reader.read_bits(4).unwrap();
+    let value = reader.read_bits(64).unwrap();
+    reader.unread_bits(64, value).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(copy_all_bits(&mut reader, &mut writer).unwrap(), 66);
+    assert_eq!(reader.position(), 70);
+}
+
+#[test]
+fn copy_all_stops_at_writer_limit() {
+    let data = source(40);
+    for &(phase, limit) in &[(0, 100), (3, 100), (5, 8), (2, 0), (6, 250)] {
+        let mut reader = BitReader::new(Cursor::new(&data[..]));
+        reader.read_bits(phase).unwrap();
+        let mut writer = bitrw::BitWriterBuilder::new()
+            .limit_bits(limit)
+            .build(Vec::new());
+        let err = copy_all_bits(&mut reader, &mut writer).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero, "{} {}", phase, limit);
+        assert_eq!(writer.position(), limit);
+        assert_eq!(reader.position(), phase as u64 + limit);
+        writer.flush().unwrap();
+        let end = phase as u64 + limit;
+        assert_eq!(writer.into_inner(), bit_range(&data, phase as u64, end));
+
+        // Nothing past the limit was lost from the reader.
+        let mut rest = BitWriter::new(Vec::new());
+        assert_eq!(copy_all_bits(&mut reader, &mut rest).unwrap(), 320 - end);
+        rest.flush().unwrap();
+        assert_eq!(rest.into_inner(), bit_range(&data, end, 320));
+    }
+
+    // Room for exactly what's left is no error.
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(3).unwrap();
+    let mut writer = bitrw::BitWriterBuilder::new()
+        .limit_bits(317)
+        .build(Vec::new());
+    assert_eq!(copy_all_bits(&mut reader, &mut writer).unwrap(), 317);
+
+    // Or with the bits pushed back into the reader.
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(4).unwrap();
+    let value = reader.read_bits(64).unwrap();
+    reader.unread_bits(64, value).unwrap();
+    let mut writer = bitrw::BitWriterBuilder::new()
+        .limit_bits(30)
+        .build(Vec::new());
+    copy_all_bits(&mut reader, &mut writer).unwrap_err();
+    assert_eq!(reader.position(), 34);
+    assert_eq!(reader.read_bits(40).unwrap(), {
+        let mut fresh = BitReader::new(&data[..]);
+        fresh.skip_bits(34).unwrap();
+        fresh.read_bits(40).unwrap()
+    });
+}
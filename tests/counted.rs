@@ -0,0 +1,102 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+fn roundtrip(count_bits: u8, items: &[u64]) -> Vec<u64> {
+    let mut writer = BitWriter::new(Vec::new());
+    let written = writer
+        .write_counted(count_bits, items, |w, &item| w.write_bits(7, item))
+        .unwrap();
+    assert_eq!(written, count_bits as usize + items.len() * 7);
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    let read = reader
+        .read_counted(count_bits, None, |r| r.read_bits(7))
+        .unwrap();
+    assert_eq!(reader.position(), written as u64);
+    read
+}
+
+#[test]
+fn counted_empty() {
+    assert!(roundtrip(8, &[]).is_empty());
+    assert!(roundtrip(0, &[]).is_empty());
+}
+
+#[test]
+fn counted_roundtrip() {
+    let items: Vec<u64> = (0..100).collect();
+    assert_eq!(roundtrip(7, &items), items);
+    assert_eq!(roundtrip(64, &items), items);
+}
+
+#[test]
+fn counted_maximum_count() {
+    let items: Vec<u64> = (0..255).map(|i| i & 0x7f).collect();
+    assert_eq!(roundtrip(8, &items), items);
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_counted(1, &[0x7f], |w, &item| w.write_bits(7, item))
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xff]);
+}
+
+#[test]
+fn counted_too_many_to_write() {
+    let items = [0u64; 256];
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer
+        .write_counted(8, &items, |w, &item| w.write_bits(7, item))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "write_counted");
+    assert_eq!(err.value(), Some(256));
+    assert_eq!(writer.position(), 0);
+
+    let err = writer
+        .write_counted(0, &items[..1], |w, &item| w.write_bits(7, item))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn counted_exceeds_maximum() {
+    // A count of 200, then nowhere near enough data.
+    let mut reader = BitReader::new(Cursor::new([200, 0x12, 0x34]));
+    let mut calls = 0;
+    let err = reader
+        .read_counted(8, Some(199), |r| {
+            calls += 1;
+            r.read_bits(8)
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_counted");
+    assert_eq!(err.value(), Some(200));
+    assert_eq!(err.offset(), Some(0));
+    assert_eq!(calls, 0);
+    assert_eq!(reader.position(), 8);
+
+    // At the maximum the count is accepted.
+    let mut reader = BitReader::new(Cursor::new([2, 0x12, 0x34]));
+    let items = reader.read_counted(8, Some(2), |r| r.read_bits(8)).unwrap();
+    assert_eq!(items, [0x12, 0x34]);
+}
+
+#[test]
+fn counted_element_error() {
+    let mut reader = BitReader::new(Cursor::new([3, 0x12, 0x34]));
+    let err = reader
+        .read_counted(8, None, |r| r.read_bits(8))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().op(), "read_counted");
+    assert_eq!(reader.position(), 24);
+}
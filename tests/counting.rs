@@ -0,0 +1,115 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitWrite, BitWriter, CountingBitWriter, ToBits};
+
+/// A record with variable-length fields, so its size depends on its values.
+struct Record {
+    kind: u8,
+    lengths: Vec<u64>,
+    name: Vec<u8>,
+    scale: f32,
+}
+
+impl ToBits for Record {
+    fn to_bits<W: BitWrite>(&self, w: &mut W) -> io::Result<usize> {
+        let mut written = w.write_bits(3, self.kind as u64)?;
+        written += w.write_gamma(self.lengths.len() as u64 + 1)?;
+        for &length in &self.lengths {
+            written += w.write_unary(length)?;
+        }
+        written += w.write_cstr(&self.name)?;
+        written += ToBits::to_bits(&self.scale, w)?;
+        w.write_bit(1)?;
+        Ok(written + 1)
+    }
+}
+
+#[test]
+fn matches_real_writer() {
+    let mut rng = rand::thread_rng();
+    let mut counter = CountingBitWriter::new();
+    let mut writer = BitWriter::new(Vec::new());
+    for _ in 0..500 {
+        let record = Record {
+            kind: rng.gen_range(0, 8),
+            lengths: (0..rng.gen_range(0, 10))
+                .map(|_| rng.gen_range(0, 100))
+                .collect(),
+            name: (0..rng.gen_range(0, 10))
+                .map(|_| rng.gen_range(1, 255))
+                .collect(),
+            scale: rng.gen(),
+        };
+
+        let before = counter.bits();
+        let counted = record.to_bits(&mut counter).unwrap();
+        let written = record.to_bits(&mut writer).unwrap();
+        assert_eq!(counted, written);
+        assert_eq!(counter.bits() - before, written as u64);
+        assert_eq!(counter.bits(), writer.position());
+        assert_eq!(counter.bit_position(), Some(writer.position()));
+
+        if rng.gen_weighted_bool(10) {
+            assert_eq!(counter.flush().unwrap(), writer.flush().unwrap());
+        }
+    }
+    assert_eq!(counter.flush().unwrap(), writer.flush().unwrap());
+    assert_eq!(counter.bits(), writer.into_inner().len() as u64 * 8);
+}
+
+#[test]
+fn inherent_methods() {
+    let mut counter = CountingBitWriter::new();
+    assert_eq!(counter.flush().unwrap(), 0);
+    counter.write_many(&[(3, 1), (64, 0), (0, 0)]).unwrap();
+    counter.write_bytes(&[1, 2, 3]).unwrap();
+    counter.write_bit(0).unwrap();
+    assert_eq!(counter.bits(), 92);
+    assert_eq!(counter.flush().unwrap(), 4);
+    assert_eq!(counter.flush().unwrap(), 0);
+    counter.reset();
+    assert_eq!(counter.bits(), 0);
+}
+
+#[test]
+fn write_many_rejects_wide_fields() {
+    let fields = [(3, 1), (65, 0), (4, 0)];
+    let mut counter = CountingBitWriter::new();
+    counter.write_bits(5, 0).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(5, 0).unwrap();
+
+    let counted = counter.write_many(&fields).unwrap_err();
+    let written = writer.write_many(&fields).unwrap_err();
+    assert_eq!(counted.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(counted.kind(), written.kind());
+    let counted = bitrw::Error::from_io(&counted).unwrap();
+    let written = bitrw::Error::from_io(&written).unwrap();
+    assert_eq!(counted.op(), written.op());
+    assert_eq!(counted.offset(), written.offset());
+    assert_eq!(counted.value(), Some(1));
+    assert_eq!(counted.value(), written.value());
+    assert_eq!(counter.bits(), 5);
+}
+
+#[test]
+fn value_level_writers() {
+    fn encode<W: BitWrite>(w: &mut W) -> io::Result<usize> {
+        let mut written = w.write_constrained(-10, 21, -3)?;
+        written += w.write_bcd(1234, 6)?;
+        written += w.write_bcd_filled(123, 3)?;
+        written += w.write_elias_fano(&[3, 3, 40, 999], 1000)?;
+        Ok(written)
+    }
+
+    let mut counter = CountingBitWriter::new();
+    let mut writer = BitWriter::new(Vec::new());
+    let counted = encode(&mut counter).unwrap();
+    assert_eq!(counted, encode(&mut writer).unwrap());
+    assert_eq!(counter.bits(), counted as u64);
+    assert_eq!(counter.bits(), writer.position());
+}
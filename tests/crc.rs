@@ -0,0 +1,251 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{
+    BitRead, BitReader, BitWrite, BitWriter, Crc, CrcBitReader, CrcBitWriter, CrcParams, Error,
+    Lsb0,
+};
+
+// The first frame header of a 44.1kHz 16 bit stereo FLAC file with a fixed
+// block size of 4096, followed by the start of the first subframe.
+const FLAC_FRAME: [u8; 8] = [0xff, 0xf8, 0xc9, 0x18, 0x00, 0xc2, 0x02, 0x00];
+
+#[test]
+fn flac_frame_header_crc8() {
+    let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(FLAC_FRAME)), CrcParams::CRC_8);
+
+    assert_eq!(reader.read_bits(14).unwrap(), 0x3ffe);
+    assert_eq!(reader.read_bit().unwrap(), 0); // reserved
+    assert_eq!(reader.read_bit().unwrap(), 0); // fixed block size
+    assert_eq!(reader.read_bits(4).unwrap(), 0b1100); // 4096 samples
+    assert_eq!(reader.read_bits(4).unwrap(), 0b1001); // 44.1kHz
+    assert_eq!(reader.read_bits(4).unwrap(), 0b0001); // left/right
+    assert_eq!(reader.read_bits(3).unwrap(), 0b100); // 16 bit
+    assert_eq!(reader.read_bit().unwrap(), 0); // reserved
+    assert_eq!(reader.read_bits(8).unwrap(), 0); // frame number 0
+
+    let crc = reader.crc();
+    assert_eq!(reader.read_bits(8).unwrap(), crc);
+    assert_eq!(crc, 0xc2);
+
+    // Including the stored CRC in an unreflected CRC leaves zero.
+    assert_eq!(reader.crc(), 0);
+    assert_eq!(reader.get_ref().position(), 48);
+}
+
+#[test]
+fn region_ending_mid_byte() {
+    let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(FLAC_FRAME)), CrcParams::CRC_8);
+    reader.read_bits(3).unwrap();
+    reader.reset_crc();
+    assert_eq!(reader.read_bits(13).unwrap(), 0x1ff8);
+    assert_eq!(reader.crc(), 0x72);
+
+    let mut whole = Crc::new(CrcParams::CRC_8);
+    whole.update_bits(0x1ff8, 13);
+    assert_eq!(whole.value(), 0x72);
+
+    let mut one_at_a_time = Crc::new(CrcParams::CRC_8);
+    for _ in 0..13 {
+        one_at_a_time.update_bits(1, 1);
+    }
+    assert_eq!(one_at_a_time.value(), 0x67);
+}
+
+#[test]
+fn catalogue_check_values() {
+    let check = b"123456789";
+
+    for &(params, expected) in &[
+        (CrcParams::CRC_8, 0xf4),
+        (CrcParams::CRC_16_UMTS, 0xfee8),
+        (CrcParams::CRC_16_IBM_3740, 0x29b1),
+    ] {
+        let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(check)), params);
+        // Reading in odd sized pieces makes no difference.
+        reader.read_bits(5).unwrap();
+        reader.read_bits(64).unwrap();
+        reader.read_bits(3).unwrap();
+        assert_eq!(reader.crc(), expected);
+    }
+
+    let mut reader = CrcBitReader::new(
+        BitReader::with_order(Cursor::new(check), Lsb0),
+        CrcParams::CRC_32,
+    );
+    reader.read_bits(7).unwrap();
+    reader.read_bits(60).unwrap();
+    reader.read_bits(5).unwrap();
+    assert_eq!(reader.crc(), 0xcbf4_3926);
+}
+
+#[test]
+fn typed_reads_update_crc() {
+    let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(FLAC_FRAME)), CrcParams::CRC_8);
+    reader.read_be::<u32>().unwrap();
+    reader.read::<u8>().unwrap();
+    assert_eq!(reader.crc(), 0xc2);
+}
+
+#[test]
+fn writer_appends_crc_reader_validates() {
+    for &params in &[
+        CrcParams::CRC_8,
+        CrcParams::CRC_16_UMTS,
+        CrcParams::CRC_16_IBM_3740,
+    ] {
+        let mut writer = CrcBitWriter::new(BitWriter::new(Vec::new()), params);
+        writer.write_bits(3, 0b101).unwrap();
+        writer.write(0xdead_beef_u32).unwrap();
+        writer.write_bits(6, 0b11_0011).unwrap();
+        let crc = writer.crc();
+        assert_eq!(writer.write_crc().unwrap(), params.width as usize);
+        // The appended CRC doesn't change the running value.
+        assert_eq!(writer.crc(), crc);
+        writer.flush().unwrap();
+
+        let bytes = writer.into_inner().into_inner();
+        let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(bytes)), params);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.read::<u32>().unwrap(), 0xdead_beef);
+        assert_eq!(reader.read_bits(6).unwrap(), 0b11_0011);
+        assert_eq!(reader.crc(), crc);
+        reader.check_crc().unwrap();
+    }
+}
+
+#[test]
+fn writer_lsb0_crc32() {
+    let mut writer = CrcBitWriter::new(BitWriter::with_order(Vec::new(), Lsb0), CrcParams::CRC_32);
+    for &byte in b"123456789" {
+        writer.write_bits(8, byte as u64).unwrap();
+    }
+    assert_eq!(writer.crc(), 0xcbf4_3926);
+    writer.write_crc().unwrap();
+    writer.flush().unwrap();
+
+    // Written least significant byte first, as zlib and gzip do.
+    let bytes = writer.into_inner().into_inner();
+    assert_eq!(&bytes[9..], [0x26, 0x39, 0xf4, 0xcb]);
+}
+
+#[test]
+fn padding_excluded_unless_requested() {
+    let mut plain = CrcBitWriter::new(BitWriter::new(Vec::new()), CrcParams::CRC_8);
+    plain.write_bits(5, 0b10110).unwrap();
+    let unpadded = plain.crc();
+    assert_eq!(plain.flush().unwrap(), 3);
+    assert_eq!(plain.crc(), unpadded);
+
+    let mut padded = CrcBitWriter::new(BitWriter::new(Vec::new()), CrcParams::CRC_8);
+    padded.write_bits(5, 0b10110).unwrap();
+    assert_eq!(padded.pad_to_byte().unwrap(), 3);
+    assert_eq!(padded.pad_to_byte().unwrap(), 0);
+
+    let mut expected = Crc::new(CrcParams::CRC_8);
+    expected.update_bits(0b1011_0000, 8);
+    assert_eq!(padded.crc(), expected.value());
+    assert_ne!(padded.crc(), unpadded);
+}
+
+#[test]
+fn check_crc_mismatch() {
+    let mut frame = FLAC_FRAME;
+    frame[3] ^= 0x10;
+
+    let mut reader = CrcBitReader::new(BitReader::new(Cursor::new(frame)), CrcParams::CRC_8);
+    reader.read_bits(40).unwrap();
+    let err = reader.check_crc().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.offset(), Some(40));
+    assert_eq!(context.value(), Some(0xc2));
+}
+
+#[test]
+fn frame_crc() {
+    let header = |reader: &mut BitReader<&[u8]>| {
+        reader.read_frame_crc(40, CrcParams::CRC_8, |frame| {
+            assert_eq!(frame.remaining(), 40);
+            let sync = frame.read_bits(14)?;
+            frame.read_bits(26)?;
+            assert_eq!(frame.remaining(), 0);
+            Ok(sync)
+        })
+    };
+
+    let mut reader = BitReader::new(&FLAC_FRAME[..]);
+    assert_eq!(header(&mut reader).unwrap(), 0x3ffe);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x02);
+
+    // A corrupted payload bit.
+    let mut frame = FLAC_FRAME;
+    frame[3] ^= 0x10;
+    let mut reader = BitReader::new(&frame[..]);
+    let err = header(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(40));
+    assert_eq!(err.value(), Some(0xc2));
+    assert_ne!(err.expected(), Some(0xc2));
+
+    // A corrupted CRC.
+    let mut frame = FLAC_FRAME;
+    frame[5] ^= 0x01;
+    let mut reader = BitReader::new(&frame[..]);
+    let err = header(&mut reader).unwrap_err();
+    let err = Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(0xc3));
+    assert_eq!(err.expected(), Some(0xc2));
+    assert_eq!(
+        err.to_string(),
+        "read_frame_crc failed at bit 40: CRC mismatch (value 0xc3) (expected 0xc2)"
+    );
+}
+
+#[test]
+fn frame_crc_wrong_length() {
+    let mut reader = BitReader::new(&FLAC_FRAME[..]);
+    let err = reader
+        .read_frame_crc(40, CrcParams::CRC_8, |frame| frame.read_bits(39))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(0));
+    assert_eq!(err.value(), Some(39));
+
+    let mut reader = BitReader::new(&FLAC_FRAME[..]);
+    let err = reader
+        .read_frame_crc(40, CrcParams::CRC_8, |frame| {
+            frame.read_bits(32)?;
+            frame.read_bits(9)
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(Error::from_io(&err).unwrap().offset(), Some(32));
+    // Nothing past the payload was read.
+    assert_eq!(reader.position(), 32);
+}
+
+#[test]
+fn frame_crc_unaligned_lsb0() {
+    let mut writer = CrcBitWriter::new(BitWriter::with_order(Vec::new(), Lsb0), CrcParams::CRC_32);
+    writer.get_mut().write_bits(3, 0b101).unwrap();
+    writer.write_bits(29, 0x0bad_cafe).unwrap();
+    writer.write_bits(11, 0x4d5).unwrap();
+    let payload = writer.get_ref().position() - 3;
+    writer.write_crc().unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner().into_inner();
+
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    let decoded = reader
+        .read_frame_crc(payload, CrcParams::CRC_32, |frame| {
+            Ok((frame.read_bits(29)?, frame.read_bits(11)?))
+        })
+        .unwrap();
+    assert_eq!(decoded, (0x0bad_cafe, 0x4d5));
+}
@@ -0,0 +1,72 @@
+extern crate bitrw;
+
+use std::io::Cursor;
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter, BitWriterBuilder, Lsb0};
+
+struct NotDebug;
+
+impl std::io::Write for NotDebug {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn reader_debug() {
+    let mut reader = BitReader::new(Cursor::new([0b1011_0011, 0xff]));
+    assert_eq!(
+        format!("{:?}", reader),
+        r#"BitReader { order: Msb0, position: 0, phase: 0, pending: "", limit: None, .. }"#
+    );
+
+    reader.read_bits(3).unwrap();
+    assert_eq!(
+        format!("{:?}", reader),
+        r#"BitReader { order: Msb0, position: 3, phase: 3, pending: "···10011", limit: None, .. }"#
+    );
+    assert_eq!(reader.state_string(), "bit 3 (byte 0, phase 3) [···10011]");
+
+    reader.read_bits(5).unwrap();
+    assert_eq!(reader.state_string(), "bit 8 (byte 1, phase 0) []");
+
+    let mut reader = BitReaderBuilder::new()
+        .bit_order(Lsb0)
+        .limit_bits(12)
+        .build(Cursor::new([0b1011_0011, 0xff]));
+    reader.read_bits(10).unwrap();
+    assert_eq!(
+        format!("{:?}", reader),
+        r#"BitReader { order: Lsb0, position: 10, phase: 2, pending: "··111111", limit: Some(12), .. }"#
+    );
+    assert_eq!(
+        reader.state_string(),
+        "bit 10 (byte 1, phase 2) [··111111] limit 12"
+    );
+}
+
+#[test]
+fn writer_debug() {
+    let mut writer = BitWriter::new(NotDebug);
+    writer.write_bits(13, 0b1_0110_0111_1101).unwrap();
+    assert_eq!(
+        format!("{:?}", writer),
+        r#"BitWriter { order: Msb0, position: 13, phase: 5, pending: "11101···", limit: None, pad_ones: false, buffered: 0, .. }"#
+    );
+    assert_eq!(writer.state_string(), "bit 13 (byte 1, phase 5) [11101···]");
+
+    let mut writer = BitWriterBuilder::new()
+        .bit_order(Lsb0)
+        .pad_with_ones(true)
+        .capacity(16)
+        .build(Vec::new());
+    writer.write_bits(11, 0b101_0000_0000).unwrap();
+    assert_eq!(
+        format!("{:?}", writer),
+        r#"BitWriter { order: Lsb0, position: 11, phase: 3, pending: "101·····", limit: None, pad_ones: true, buffered: 1, .. }"#
+    );
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "derive")]
+
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWriter, FromBits, Lsb0, ToBits};
+
+#[derive(Debug, Clone, PartialEq, FromBits, ToBits)]
+struct Flags {
+    urgent: bool,
+    #[bits(3)]
+    class: u8,
+}
+
+#[derive(Debug, Clone, PartialEq, FromBits, ToBits)]
+struct Packet {
+    #[bits(4)]
+    version: u8,
+    flags: Flags,
+    #[skip(3)]
+    #[bits(signed, 12)]
+    offset: i16,
+    #[align(8)]
+    length: u16,
+    #[bits(17)]
+    sequence: u32,
+    checksum: [u8; 2],
+    extra: Option<Flags>,
+}
+
+#[derive(Debug, PartialEq, FromBits, ToBits)]
+struct Pair(#[bits(5)] u8, #[bits(signed, 5)] i64);
+
+#[derive(Debug, PartialEq, FromBits, ToBits)]
+struct Wrapper<T> {
+    #[bits(2)]
+    tag: u8,
+    inner: T,
+}
+
+#[derive(Debug, PartialEq, FromBits, ToBits)]
+struct Empty;
+
+fn packet() -> Packet {
+    Packet {
+        version: 0xa,
+        flags: Flags {
+            urgent: true,
+            class: 5,
+        },
+        offset: -1000,
+        length: 0xbeef,
+        sequence: 0x1_2345,
+        checksum: [0x12, 0x34],
+        extra: Some(Flags {
+            urgent: false,
+            class: 3,
+        }),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut writer = BitWriter::new(Vec::new());
+    let written = packet().to_bits(&mut writer).unwrap();
+    // 4 + 4, 3 skipped + 12, then 1 bit of alignment, 16, 17, 16, 1 + 4.
+    assert_eq!(written, 8 + 15 + 1 + 16 + 17 + 16 + 5);
+    writer.write_bits(3, 0b101).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    // The skipped and alignment bits are zero.
+    assert_eq!(data[..5], [0xad, 0b0001_1000, 0b0011_0000, 0xbe, 0xef]);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(Packet::from_bits(&mut reader).unwrap(), packet());
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+}
+
+#[test]
+fn tuple_generic_and_unit() {
+    let value = Wrapper {
+        tag: 2,
+        inner: Pair(17, -9),
+    };
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    assert_eq!(value.to_bits(&mut writer).unwrap(), 12);
+    assert_eq!(Empty.to_bits(&mut writer).unwrap(), 0);
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    assert_eq!(Wrapper::<Pair>::from_bits(&mut reader).unwrap(), value);
+    assert_eq!(Empty::from_bits(&mut reader).unwrap(), Empty);
+}
+
+#[test]
+fn errors() {
+    // Running out part way.
+    let mut reader = BitReader::new(&[0xff, 0xff][..]);
+    let err = Packet::from_bits(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    // Alignment needs a position.
+    struct NoPosition<'a>(BitReader<&'a [u8]>);
+    impl BitRead for NoPosition<'_> {
+        fn read_bits(&mut self, nbits: u8) -> io::Result<u64> {
+            self.0.read_bits(nbits)
+        }
+    }
+    let mut reader = NoPosition(BitReader::new(&[0; 16][..]));
+    let err = Packet::from_bits(&mut reader).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(reader.0.position(), 23);
+}
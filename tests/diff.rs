@@ -0,0 +1,98 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, BufReader, Read};
+
+use bitrw::{diff_bits, BitDiff, BitDiffKind, BitRead, BitReader, Lsb0};
+
+fn flip(data: &mut [u8], bit: u64) {
+    data[bit as usize / 8] ^= 0x80 >> (bit % 8);
+}
+
+#[test]
+fn three_scattered_bits() {
+    let mut rng = rand::thread_rng();
+    let a: Vec<u8> = (0..1 << 20).map(|_| rng.gen()).collect();
+    let mut b = a.clone();
+    let offsets = [77, 3_000_001, 8_388_607];
+    for &offset in &offsets {
+        flip(&mut b, offset);
+    }
+
+    let mut ra = BitReader::new(BufReader::new(&a[..]));
+    let mut rb = BitReader::new(BufReader::new(&b[..]));
+    let diffs = diff_bits(&mut ra, &mut rb, 10).unwrap();
+    let found: Vec<u64> = diffs.iter().map(|d| d.offset).collect();
+    assert_eq!(found, offsets);
+    for diff in &diffs {
+        assert_eq!(diff.kind, BitDiffKind::Bits);
+        assert_eq!(diff.window, diff.offset / 32 * 32);
+        assert_eq!((diff.first ^ diff.second).count_ones(), 1);
+        assert_eq!(
+            (diff.first ^ diff.second).leading_zeros() as u64,
+            diff.offset % 32
+        );
+    }
+}
+
+#[test]
+fn stops_after_max_report() {
+    let a = [0u8; 64];
+    let b = [0xffu8; 64];
+    // Counts how many bytes were read, to check it didn't read everything.
+    struct Counting<'a>(&'a [u8], usize);
+    impl Read for Counting<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.0.read(buf)?;
+            self.1 += n;
+            Ok(n)
+        }
+    }
+    let mut ra = BitReader::new(Counting(&a, 0));
+    let mut rb = BitReader::new(&b[..]);
+    let diffs = diff_bits(&mut ra, &mut rb, 40).unwrap();
+    assert_eq!(diffs.len(), 40);
+    assert_eq!(diffs[39].offset, 39);
+    assert_eq!(ra.get_ref().1, 8);
+    assert!(diff_bits(&mut ra, &mut rb, 0).unwrap().is_empty());
+}
+
+#[test]
+fn length_mismatch() {
+    let a = [0xab; 10];
+    let mut ra = BitReader::new(&a[..]).take_bits(70);
+    let mut rb = BitReader::new(&a[..]);
+    let diffs = diff_bits(&mut ra, &mut rb, 10).unwrap();
+    assert_eq!(
+        diffs,
+        [BitDiff {
+            offset: 70,
+            kind: BitDiffKind::FirstEnded,
+            window: 64,
+            first: 0xa800_0000,
+            second: 0xabab_0000,
+        }]
+    );
+    assert_eq!(
+        diffs[0].to_string(),
+        "bit 70: first stream ended, 0xa8000000 vs 0xabab0000 from bit 64"
+    );
+
+    // Equal streams have no differences.
+    let mut ra = BitReader::with_order(&a[..], Lsb0);
+    let mut rb = BitReader::with_order(&a[..], Lsb0);
+    assert!(diff_bits(&mut ra, &mut rb, 10).unwrap().is_empty());
+}
+
+#[test]
+fn stream_order() {
+    // With Lsb0 the first bit of a byte is its least significant.
+    let mut ra = BitReader::with_order(&[0x01][..], Lsb0);
+    let mut rb = BitReader::with_order(&[0x00, 0x00][..], Lsb0);
+    let diffs = diff_bits(&mut ra, &mut rb, 10).unwrap();
+    assert_eq!(diffs[0].offset, 0);
+    assert_eq!(diffs[0].first, 0x8000_0000);
+    assert_eq!(diffs[1].kind, BitDiffKind::FirstEnded);
+    assert_eq!(diffs[1].offset, 8);
+}
@@ -0,0 +1,149 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::Cursor;
+
+use bitrw::{
+    BitDigest, BitRead, BitReader, BitWrite, BitWriter, Crc, CrcBitReader, CrcBitWriter, CrcParams,
+    HasherDigest, Lsb0,
+};
+
+/// Adler-32, as a byte-oriented `Hasher` the crate knows nothing about.
+#[derive(Clone)]
+struct Adler32 {
+    a: u32,
+    b: u32,
+}
+
+impl Default for Adler32 {
+    fn default() -> Self {
+        Adler32 { a: 1, b: 0 }
+    }
+}
+
+impl Hasher for Adler32 {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.a = (self.a + byte as u32) % 65521;
+            self.b = (self.b + self.a) % 65521;
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        ((self.b << 16) | self.a) as u64
+    }
+}
+
+fn random_fields(count: usize) -> Vec<(u8, u64)> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| {
+            let n = rng.gen_range(0, 65);
+            (n, rng.gen::<u64>())
+        })
+        .collect()
+}
+
+#[test]
+fn adler32_check_value() {
+    let mut digest = HasherDigest::new(Adler32::default());
+    for &byte in b"Wikipedia" {
+        digest.update_bits(byte as u64, 8);
+    }
+    assert_eq!(digest.finalize(), 0x11e6_0398);
+}
+
+#[test]
+fn hasher_matches_packed_bytes() {
+    for _ in 0..50 {
+        let fields = random_fields(100);
+
+        let mut writer = CrcBitWriter::with_digest(
+            BitWriter::new(Vec::new()),
+            HasherDigest::new(Adler32::default()),
+        );
+        let mut sip = CrcBitWriter::with_digest(
+            BitWriter::new(Vec::new()),
+            HasherDigest::new(DefaultHasher::new()),
+        );
+        for &(n, v) in &fields {
+            writer.write_bits(n, v).unwrap();
+            sip.write_bits(n, v).unwrap();
+        }
+        let adler = writer.finalize();
+        let siphash = sip.finalize();
+        writer.flush().unwrap();
+        let bytes = writer.into_inner().into_inner();
+
+        let mut expected = Adler32::default();
+        expected.write(&bytes);
+        assert_eq!(adler, expected.finish());
+
+        let mut expected = DefaultHasher::new();
+        expected.write(&bytes);
+        assert_eq!(siphash, expected.finish());
+
+        // And the reader side agrees.
+        let mut reader = CrcBitReader::with_digest(
+            BitReader::new(Cursor::new(bytes)),
+            HasherDigest::new(Adler32::default()),
+        );
+        for &(n, _) in &fields {
+            reader.read_bits(n).unwrap();
+        }
+        assert_eq!(reader.finalize(), adler);
+    }
+}
+
+#[test]
+fn hasher_matches_packed_bytes_lsb0() {
+    let fields = random_fields(200);
+
+    let mut writer = CrcBitWriter::with_digest(
+        BitWriter::with_order(Vec::new(), Lsb0),
+        HasherDigest::with_order(Adler32::default(), Lsb0),
+    );
+    for &(n, v) in &fields {
+        writer.write_bits(n, v).unwrap();
+    }
+    let adler = writer.finalize();
+    writer.flush().unwrap();
+
+    let mut expected = Adler32::default();
+    expected.write(&writer.into_inner().into_inner());
+    assert_eq!(adler, expected.finish());
+}
+
+#[test]
+fn finalize_is_repeatable() {
+    let mut digest = HasherDigest::new(Adler32::default());
+    digest.update_bits(0b101, 3);
+    let first = digest.finalize();
+    assert_eq!(digest.finalize(), first);
+
+    // The partial byte is zero-padded on finalizing, but still pending after.
+    digest.update_bits(0b00000, 5);
+    assert_eq!(digest.finalize(), first);
+    assert_eq!(digest.get_ref().finish(), first);
+}
+
+#[test]
+fn crc_is_a_digest() {
+    let mut crc = Crc::new(CrcParams::CRC_16_UMTS);
+    for &byte in b"123456789" {
+        BitDigest::update_bits(&mut crc, byte as u64, 8);
+    }
+    assert_eq!(BitDigest::finalize(&crc), 0xfee8);
+
+    let mut writer = CrcBitWriter::with_digest(BitWriter::new(Vec::new()), crc);
+    writer.write_bits(4, 0xf).unwrap();
+    writer.digest_mut().reset();
+    for &byte in b"123456789" {
+        writer.write_bits(8, byte as u64).unwrap();
+    }
+    assert_eq!(writer.finalize(), 0xfee8);
+    assert_eq!(writer.crc(), 0xfee8);
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "digest")]
+
+extern crate bitrw;
+extern crate rand;
+extern crate sha2;
+
+use rand::Rng;
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, CrcBitReader, CrcBitWriter, UpdateDigest};
+
+#[test]
+fn sha256_matches_packed_bytes() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..500)
+        .map(|_| (rng.gen_range(1, 65), rng.gen::<u64>()))
+        .collect();
+
+    let mut writer =
+        CrcBitWriter::with_digest(BitWriter::new(Vec::new()), UpdateDigest::new(Sha256::new()));
+    for &(n, v) in &fields {
+        writer.write_bits(n, v).unwrap();
+    }
+    let hash = writer.finalize();
+    writer.flush().unwrap();
+    let bytes = writer.into_inner().into_inner();
+    assert_eq!(hash, Sha256::digest(&bytes));
+
+    let mut reader = CrcBitReader::with_digest(
+        BitReader::new(Cursor::new(bytes)),
+        UpdateDigest::new(Sha256::new()),
+    );
+    for &(n, _) in &fields {
+        reader.read_bits(n).unwrap();
+    }
+    assert_eq!(reader.finalize(), hash);
+}
+
+#[test]
+fn truncated_sha256_integrity_check() {
+    let mut writer =
+        CrcBitWriter::with_digest(BitWriter::new(Vec::new()), UpdateDigest::new(Sha256::new()));
+    writer.write_bits(13, 0x1abc).unwrap();
+    writer.write(0x0123_4567_89ab_cdef_u64).unwrap();
+    // Protect the payload with the first 32 bits of its hash.
+    let tag = writer.finalize();
+    writer
+        .get_mut()
+        .write_bits(
+            32,
+            u32::from_be_bytes([tag[0], tag[1], tag[2], tag[3]]) as u64,
+        )
+        .unwrap();
+    writer.flush().unwrap();
+
+    let mut reader = CrcBitReader::with_digest(
+        BitReader::new(Cursor::new(writer.into_inner().into_inner())),
+        UpdateDigest::new(Sha256::new()),
+    );
+    assert_eq!(reader.read_bits(13).unwrap(), 0x1abc);
+    assert_eq!(reader.read::<u64>().unwrap(), 0x0123_4567_89ab_cdef);
+    let expected = reader.finalize();
+    let stored = reader.get_mut().read_bits(32).unwrap() as u32;
+    assert_eq!(stored.to_be_bytes(), expected[..4]);
+}
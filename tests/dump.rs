@@ -0,0 +1,118 @@
+extern crate bitrw;
+
+use bitrw::dump::{annotate, annotate_with_order, dump_bits, dump_bits_with_order};
+use bitrw::Lsb0;
+
+const DATA: [u8; 6] = [0x47, 0x1f, 0xff, 0x10, 0x00, 0xa5];
+
+#[test]
+fn transport_stream_header() {
+    // The first 32 bits of an MPEG-TS packet header, fields straddling bytes.
+    let fields = [
+        ("sync_byte", 8),
+        ("transport_error", 1),
+        ("payload_start", 1),
+        ("priority", 1),
+        ("pid", 13),
+        ("scrambling", 2),
+        ("adaptation", 2),
+        ("continuity", 4),
+    ];
+    assert_eq!(
+        annotate(&DATA, 0, &fields),
+        concat!(
+            "     0  sync_byte        01000111        = 71 (0x47)\n",
+            "     8  transport_error  0               = 0 (0x0)\n",
+            "     9  payload_start    0               = 0 (0x0)\n",
+            "    10  priority         0               = 0 (0x0)\n",
+            "    11  pid              11111|11111111  = 8191 (0x1fff)\n",
+            "    24  scrambling       00              = 0 (0x0)\n",
+            "    26  adaptation       01              = 1 (0x1)\n",
+            "    28  continuity       0000            = 0 (0x0)\n",
+        )
+    );
+}
+
+#[test]
+fn straddling_several_bytes() {
+    assert_eq!(
+        annotate(&DATA, 5, &[("wide", 30), ("", 0), ("bit", 1)]),
+        concat!(
+            "     5  wide  111|00011111|11111111|00010000|000  = 956299392 (0x38fff880)\n",
+            "    35                                            = 0 (0x0)\n",
+            "    35  bit   0                                   = 0 (0x0)\n",
+        )
+    );
+}
+
+#[test]
+fn truncated() {
+    assert_eq!(
+        annotate(&DATA, 36, &[("a", 4), ("b", 16), ("c", 1)]),
+        concat!(
+            "    36  a  0000      = 0 (0x0)\n",
+            "    40  b  10100101  (truncated)\n",
+        )
+    );
+    assert_eq!(
+        annotate(&DATA, 48, &[("a", 1)]),
+        "    48  a    (truncated)\n"
+    );
+    assert_eq!(annotate(&DATA, 48, &[]), "");
+}
+
+#[test]
+fn lsb0() {
+    // 0x47 is 11100010 in Lsb0 stream order.
+    assert_eq!(
+        annotate_with_order(&DATA, 2, &[("x", 3), ("y", 5)], Lsb0),
+        concat!(
+            "     2  x  100     = 1 (0x1)\n",
+            "     5  y  010|11  = 26 (0x1a)\n",
+        )
+    );
+    assert_eq!(
+        dump_bits_with_order(&DATA, 3..13, Lsb0),
+        "     0  ...00010 11111...\n"
+    );
+}
+
+#[test]
+fn raw_dumps() {
+    assert_eq!(
+        dump_bits(&DATA, 0..48),
+        concat!(
+            "     0  01000111 00011111 11111111 00010000\n",
+            "    32  00000000 10100101\n",
+        )
+    );
+    assert_eq!(
+        dump_bits(&DATA, 10..48),
+        concat!(
+            "     8  ..011111 11111111 00010000 00000000\n",
+            "    40  10100101\n",
+        )
+    );
+    // Ending part way through a byte.
+    assert_eq!(
+        dump_bits(&DATA, 0..21),
+        "     0  01000111 00011111 11111...\n"
+    );
+    // Starting and ending part way through a byte.
+    assert_eq!(dump_bits(&DATA, 35..45), "    32  ...00000 10100...\n");
+    // Within a single byte.
+    assert_eq!(dump_bits(&DATA, 9..11), "     8  .00.....\n");
+    assert_eq!(dump_bits(&DATA, 8..8), "");
+}
+
+#[test]
+#[should_panic]
+fn range_past_end() {
+    dump_bits(&DATA, 40..49);
+}
+
+#[test]
+#[should_panic]
+fn field_too_wide() {
+    annotate(&[0; 16], 0, &[("wide", 65)]);
+}
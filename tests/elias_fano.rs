@@ -0,0 +1,122 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitWrite, BitWriter, Lsb0};
+
+fn sorted_set(n: usize, universe: u64) -> Vec<u64> {
+    let mut rng = rand::thread_rng();
+    let mut values: Vec<u64> = (0..n).map(|_| rng.gen_range(0, universe)).collect();
+    values.sort_unstable();
+    values
+}
+
+/// log2 of the number of multisets of `n` values below `universe`, the least
+/// possible size of any encoding.
+fn bound(n: usize, universe: u64) -> f64 {
+    // Choosing n from universe + n - 1, with repetition.
+    let total = universe as f64 + n as f64 - 1.0;
+    (0..n)
+        .map(|i| ((total - i as f64) / (i as f64 + 1.0)).log2())
+        .sum()
+}
+
+#[test]
+fn round_trip_densities() {
+    for &(n, universe) in &[
+        (1000, 1000),
+        (1000, 2000),
+        (1000, 1 << 20),
+        (10_000, 1 << 40),
+        (100, u64::MAX),
+        (5000, 100),
+        (1, 1),
+    ] {
+        let values = sorted_set(n, universe);
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_bits(3, 0b101).unwrap();
+        let written = writer.write_elias_fano(&values, universe).unwrap();
+        writer.write_bits(7, 0x55).unwrap();
+        writer.flush().unwrap();
+        let data = writer.into_inner();
+
+        let extra = written as f64 - bound(n, universe);
+        assert!(
+            extra <= 2.0 * n as f64 + 1.0,
+            "{} {} {}",
+            n,
+            universe,
+            extra
+        );
+
+        let mut reader = BitReader::new(&data[..]);
+        reader.read_bits(3).unwrap();
+        assert_eq!(reader.read_elias_fano(n, universe).unwrap(), values);
+        assert_eq!(reader.position(), 3 + written as u64);
+        assert_eq!(reader.read_bits(7).unwrap(), 0x55);
+    }
+}
+
+#[test]
+fn lazy_iteration() {
+    let values = sorted_set(3000, 1 << 33);
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_elias_fano(&values, 1 << 33).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    let mut iter = reader.read_elias_fano_iter(3000, 1 << 33).unwrap();
+    assert_eq!(iter.low_bits(), 21);
+    for &value in &values[..10] {
+        assert_eq!(iter.next().unwrap().unwrap(), value);
+    }
+    let rest: Vec<u64> = iter.map(Result::unwrap).collect();
+    assert_eq!(rest, &values[10..]);
+
+    // Nothing at all.
+    let mut reader = BitReader::new(&[][..]);
+    assert!(reader.read_elias_fano(0, 0).unwrap().is_empty());
+}
+
+#[test]
+fn invalid_input() {
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer.write_elias_fano(&[1, 5, 4], 10).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(2));
+    let err = writer.write_elias_fano(&[1, 5, 10], 10).unwrap_err();
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(2));
+    assert!(writer.write_elias_fano(&[0], 0).is_err());
+    assert_eq!(writer.position(), 0);
+    assert_eq!(writer.write_elias_fano(&[], 0).unwrap(), 0);
+}
+
+#[test]
+fn invalid_data() {
+    // Three values below 8 have one low bit, and high parts up to three.
+    let data = [0b1111_0001];
+    let mut reader = BitReader::new(&data[..]);
+    let mut iter = reader.read_elias_fano_iter(3, 8).unwrap();
+    assert_eq!(iter.low_bits(), 1);
+    assert_eq!(iter.next().unwrap().unwrap(), 1);
+    assert_eq!(iter.next().unwrap().unwrap(), 7);
+    let err = iter.next().unwrap().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(iter.next().is_none());
+
+    // A high part past the universe.
+    let data = [0b0000_0001];
+    let mut reader = BitReader::new(&data[..]);
+    let err = reader.read_elias_fano(3, 8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(0));
+
+    // A value in the last bucket, but past the universe.
+    let data = [0b1000_0010];
+    let mut reader = BitReader::new(&data[..]);
+    let err = reader.read_elias_fano(3, 7).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
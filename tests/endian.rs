@@ -0,0 +1,82 @@
+extern crate bitrw;
+
+use std::io::Cursor;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+#[test]
+fn golden_u32_at_phase_3() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_le(0x1234_5678_u32).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xaf, 0x0a, 0xc6, 0x82, 0x40]);
+
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_be(0x1234_5678_u32).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xa2, 0x46, 0x8a, 0xcf, 0x00]);
+
+    let mut reader = BitReader::new(Cursor::new(vec![0xaf, 0x0a, 0xc6, 0x82, 0x40]));
+    reader.read_bits(3).unwrap();
+    assert_eq!(reader.read_le::<u32>().unwrap(), 0x1234_5678);
+
+    let mut reader = BitReader::new(Cursor::new(vec![0xaf, 0x0a, 0xc6, 0x82, 0x40]));
+    reader.read_bits(3).unwrap();
+    assert_eq!(reader.read_be::<u32>().unwrap(), 0x7856_3412);
+}
+
+#[test]
+fn golden_u32_at_phase_3_lsb0() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_le(0x1234_5678_u32).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xc5, 0xb3, 0xa2, 0x91, 0x00]);
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_be(0x1234_5678_u32).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x95, 0xa0, 0xb1, 0xc2, 0x03]);
+}
+
+#[test]
+fn natural_order_matches_stream_order() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write(0x1234_u16).unwrap();
+    writer.write_be(0x1234_u16).unwrap();
+    let mut lsb = BitWriter::with_order(Vec::new(), Lsb0);
+    lsb.write(0x1234_u16).unwrap();
+    lsb.write_le(0x1234_u16).unwrap();
+
+    assert_eq!(writer.into_inner(), [0x12, 0x34, 0x12, 0x34]);
+    assert_eq!(lsb.into_inner(), [0x34, 0x12, 0x34, 0x12]);
+}
+
+#[test]
+fn roundtrip_all_types() {
+    for phase in 0..8 {
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_bits(phase, 0x55).unwrap();
+        writer.write_be(-2_i8).unwrap();
+        writer.write_le(0xbeef_u16).unwrap();
+        writer.write_be(-123_456_i32).unwrap();
+        writer.write_le(0x0123_4567_89ab_cdef_u64).unwrap();
+        writer.write_be(-1.5_f32).unwrap();
+        writer.write_le(::std::f64::consts::PI).unwrap();
+        writer.write(i16::MIN).unwrap();
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+        reader.read_bits(phase).unwrap();
+        assert_eq!(reader.read_be::<i8>().unwrap(), -2);
+        assert_eq!(reader.read_le::<u16>().unwrap(), 0xbeef);
+        assert_eq!(reader.read_be::<i32>().unwrap(), -123_456);
+        assert_eq!(reader.read_le::<u64>().unwrap(), 0x0123_4567_89ab_cdef);
+        assert_eq!(reader.read_be::<f32>().unwrap(), -1.5);
+        assert_eq!(reader.read_le::<f64>().unwrap(), ::std::f64::consts::PI);
+        assert_eq!(reader.read::<i16>().unwrap(), i16::MIN);
+    }
+}
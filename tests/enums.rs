@@ -0,0 +1,90 @@
+extern crate bitrw;
+
+use std::convert::TryFrom;
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+/// A message type with gaps between its discriminants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Message {
+    Hello = 0,
+    Data = 3,
+    Ack = 4,
+    Goodbye = 15,
+}
+
+impl TryFrom<u64> for Message {
+    type Error = u64;
+
+    fn try_from(value: u64) -> Result<Self, u64> {
+        Ok(match value {
+            0 => Message::Hello,
+            3 => Message::Data,
+            4 => Message::Ack,
+            15 => Message::Goodbye,
+            _ => return Err(value),
+        })
+    }
+}
+
+impl From<Message> for u64 {
+    fn from(message: Message) -> u64 {
+        message as u64
+    }
+}
+
+#[test]
+fn round_trip() {
+    let messages = [
+        Message::Hello,
+        Message::Data,
+        Message::Ack,
+        Message::Goodbye,
+    ];
+    let mut writer = BitWriter::new(Vec::new());
+    for &message in &messages {
+        writer.write_enum(4, message).unwrap();
+    }
+    let data = writer.into_inner();
+    assert_eq!(data, [0x03, 0x4f]);
+
+    let mut reader = BitReader::new(&data[..]);
+    for &message in &messages {
+        assert_eq!(reader.read_enum::<Message>(4).unwrap(), message);
+    }
+}
+
+#[test]
+fn invalid_values() {
+    // 0 is valid, then the gaps at 1 and 2, just past Ack at 5, and 14.
+    let data = [0x01, 0x25, 0xe0];
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.read_enum::<Message>(4).unwrap(), Message::Hello);
+    for &(offset, value) in &[(4, 1), (8, 2), (12, 5), (16, 14)] {
+        let err = reader.read_enum::<Message>(4).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = bitrw::Error::from_io(&err).unwrap();
+        assert_eq!((err.offset(), err.value()), (Some(offset), Some(value)));
+        assert!(err.to_string().contains(&format!("at bit {}", offset)));
+    }
+    // The field is consumed either way.
+    assert_eq!(reader.position(), 20);
+    assert_eq!(
+        reader.read_enum::<Message>(8).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn too_wide() {
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer.write_enum(3, Message::Goodbye).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(15));
+    assert_eq!(writer.position(), 0);
+    writer.write_enum(3, Message::Ack).unwrap();
+    writer.write_enum(64, u64::MAX).unwrap();
+    assert_eq!(writer.position(), 67);
+}
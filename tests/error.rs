@@ -0,0 +1,167 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Error};
+
+#[test]
+fn truncated_gamma_reports_offset() {
+    let mut writer = BitWriter::new(Vec::new());
+    for _ in 0..1234 / 32 {
+        writer.write_bits(32, 0xffff_ffff).unwrap();
+    }
+    writer.write_bits((1234 % 32) as u8, 0x3ffff).unwrap();
+    // 1000 needs 9 leading zeros and 10 value bits; only write the prefix.
+    writer.write_bits(9, 0).unwrap();
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    for _ in 0..1234 {
+        assert_eq!(reader.read_bit().unwrap(), 1);
+    }
+    assert_eq!(reader.position(), 1234);
+
+    let err = reader.read_gamma().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(err.to_string().contains("1234"), "{}", err);
+
+    let context = Error::from_io(&err).expect("bitrw error");
+    assert_eq!(context.op(), "read_gamma");
+    assert_eq!(context.offset(), Some(1234));
+    assert_eq!(context.value(), None);
+}
+
+#[test]
+fn gamma_and_unary_roundtrip() {
+    let values = [1, 2, 3, 4, 7, 8, 1000, u32::MAX as u64, u64::MAX];
+
+    let mut writer = BitWriter::new(Vec::new());
+    for &v in &values {
+        writer.write_gamma(v).unwrap();
+        writer.write_unary(v % 100).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    for &v in &values {
+        assert_eq!(reader.read_gamma().unwrap(), v);
+        assert_eq!(reader.read_unary().unwrap(), v % 100);
+    }
+}
+
+#[test]
+fn gamma_encoding() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_gamma(1).unwrap(), 1);
+    assert_eq!(writer.write_gamma(5).unwrap(), 5);
+    assert_eq!(writer.position(), 6);
+    writer.flush().unwrap();
+    assert_eq!(writer.position(), 8);
+    assert_eq!(writer.into_inner(), [0b1001_0100]);
+}
+
+#[test]
+fn write_gamma_rejects_zero() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+
+    let err = writer.write_gamma(0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "write_gamma");
+    assert_eq!(context.offset(), Some(3));
+    assert_eq!(context.value(), Some(0));
+}
+
+#[test]
+fn expect_bits_reports_value() {
+    let mut reader = BitReader::new(Cursor::new(vec![0xab, 0xcd]));
+    reader.expect_bits(4, 0xa).unwrap();
+
+    let err = reader.expect_bits(8, 0xff).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        err.to_string(),
+        "expect_bits failed at bit 4: unexpected value (value 0xbc)"
+    );
+
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.offset(), Some(4));
+    assert_eq!(context.value(), Some(0xbc));
+}
+
+#[test]
+fn typed_reads_carry_context() {
+    let mut reader = BitReader::new(Cursor::new(vec![0xff; 3]));
+    reader.read_bits(5).unwrap();
+
+    let err = reader.read_be::<u32>().unwrap_err();
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "read_be");
+    assert_eq!(context.offset(), Some(5));
+    assert_eq!(context.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(context.io_source().is_some());
+}
+
+#[test]
+fn typed_writes_carry_context() {
+    let mut buf = [0u8; 2];
+    let mut writer = BitWriter::new(&mut buf[..]);
+    writer.write_bits(5, 0).unwrap();
+
+    let err = writer.write_be(0x1234_5678u32).unwrap_err();
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "write_be");
+    assert_eq!(context.offset(), Some(5));
+    assert_eq!(context.kind(), io::ErrorKind::WriteZero);
+
+    let mut writer = BitWriter::new(&mut buf[..]);
+    let err = writer.write_le(0x1234_5678u32).unwrap_err();
+    assert_eq!(Error::from_io(&err).unwrap().op(), "write_le");
+
+    let mut writer = BitWriter::new(&mut buf[..]);
+    writer.write_bits(3, 0).unwrap();
+    let err = writer.write(0x1234_5678u32).unwrap_err();
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "write");
+    assert_eq!(context.offset(), Some(3));
+}
+
+#[test]
+fn innermost_context_wins() {
+    let mut reader = BitReader::new(Cursor::new(vec![0x12, 0x34, 0x56]));
+    let err = reader.read_vec::<u16>(2).unwrap_err();
+
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "read");
+    assert_eq!(context.offset(), Some(16));
+}
+
+#[test]
+fn converts_to_and_from_io_error() {
+    let error = Error::new(io::ErrorKind::InvalidData, "decode", "bad marker").with_value(7);
+    let io_error: io::Error = error.into();
+    assert_eq!(io_error.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(
+        io_error.to_string(),
+        "decode failed: bad marker (value 0x7)"
+    );
+
+    let wrapped = Error::from(io::Error::new(io::ErrorKind::WriteZero, "full"));
+    assert_eq!(wrapped.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(wrapped.to_string(), "io failed: full");
+}
+
+#[test]
+fn position_tracks_seek_and_reset() {
+    let mut reader = BitReader::new(Cursor::new(vec![0; 8]));
+    reader.read_bits(3).unwrap();
+    reader.reset();
+    assert_eq!(reader.position(), 8);
+
+    reader.seek(io::SeekFrom::Start(21)).unwrap();
+    assert_eq!(reader.position(), 21);
+    reader.read_bits(7).unwrap();
+    assert_eq!(reader.position(), 28);
+}
@@ -0,0 +1,174 @@
+extern crate bitrw;
+extern crate half;
+extern crate rand;
+
+use half::{bf16, f16};
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+fn encode(value: f64, sign: bool, exp_bits: u8, mant_bits: u8, bias: i32) -> u64 {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_float(value, sign, exp_bits, mant_bits, bias)
+        .unwrap();
+    writer.flush().unwrap();
+    let width = sign as u8 + exp_bits + mant_bits;
+    BitReader::new(Cursor::new(writer.into_inner()))
+        .read_bits(width)
+        .unwrap()
+}
+
+fn decode(bits: u64, sign: bool, exp_bits: u8, mant_bits: u8, bias: i32) -> f64 {
+    let width = sign as u8 + exp_bits + mant_bits;
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(width, bits).unwrap();
+    writer.flush().unwrap();
+    BitReader::new(Cursor::new(writer.into_inner()))
+        .read_float(sign, exp_bits, mant_bits, bias)
+        .unwrap()
+}
+
+#[test]
+fn binary16_decodes_like_half() {
+    for bits in 0..=u16::MAX {
+        let expected = f16::from_bits(bits).to_f64();
+        let decoded = decode(bits as u64, true, 5, 10, 15);
+        if expected.is_nan() {
+            assert!(decoded.is_nan(), "{:#x}", bits);
+            assert_eq!(encode(decoded, true, 5, 10, 15), bits as u64);
+        } else {
+            assert_eq!(decoded.to_bits(), expected.to_bits(), "{:#x}", bits);
+        }
+    }
+}
+
+#[test]
+fn bfloat16_decodes_like_half() {
+    for bits in 0..=u16::MAX {
+        let expected = bf16::from_bits(bits).to_f64();
+        let decoded = decode(bits as u64, true, 8, 7, 127);
+        if expected.is_nan() {
+            assert!(decoded.is_nan(), "{:#x}", bits);
+        } else {
+            assert_eq!(decoded.to_bits(), expected.to_bits(), "{:#x}", bits);
+        }
+    }
+}
+
+#[test]
+fn encode_rounds_like_half() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100_000 {
+        // Random f32 bit patterns cover every exponent, including values which
+        // overflow or underflow the narrower layouts.
+        let value = f32::from_bits(rng.gen::<u32>());
+        if value.is_nan() {
+            continue;
+        }
+        assert_eq!(
+            encode(value as f64, true, 5, 10, 15),
+            f16::from_f32(value).to_bits() as u64,
+            "{:e}",
+            value
+        );
+        assert_eq!(
+            encode(value as f64, true, 8, 7, 127),
+            bf16::from_f32(value).to_bits() as u64,
+            "{:e}",
+            value
+        );
+    }
+}
+
+#[test]
+fn binary16_reference_values() {
+    let cases = [
+        (1.0, 0x3c00),
+        (-2.0, 0xc000),
+        (65504.0, 0x7bff),
+        (0.333_251_953_125, 0x3555),
+        (2f64.powi(-14), 0x0400),
+        (2f64.powi(-24), 0x0001),
+        (0.0, 0x0000),
+        (-0.0, 0x8000),
+        (f64::INFINITY, 0x7c00),
+    ];
+    for &(value, bits) in &cases {
+        assert_eq!(encode(value, true, 5, 10, 15), bits, "{}", value);
+        assert_eq!(decode(bits, true, 5, 10, 15), value, "{:#x}", bits);
+    }
+}
+
+#[test]
+fn overflow_to_infinity() {
+    // 65520 is halfway between 65504 and 65536, so rounds up out of range.
+    assert_eq!(encode(65519.0, true, 5, 10, 15), 0x7bff);
+    assert_eq!(encode(65520.0, true, 5, 10, 15), 0x7c00);
+    assert_eq!(encode(-1e10, true, 5, 10, 15), 0xfc00);
+    assert_eq!(encode(f64::MAX, true, 8, 7, 127), 0x7f80);
+}
+
+#[test]
+fn round_to_nearest_even() {
+    // 1 + 2^-11 is halfway between 1.0 and the next binary16 value.
+    assert_eq!(encode(1.0 + 2f64.powi(-11), true, 5, 10, 15), 0x3c00);
+    assert_eq!(encode(1.0 + 3.0 * 2f64.powi(-11), true, 5, 10, 15), 0x3c02);
+    // Below half the smallest subnormal rounds to zero, above it rounds up.
+    assert_eq!(encode(2f64.powi(-25), true, 5, 10, 15), 0x0000);
+    assert_eq!(encode(2f64.powi(-25) * 1.5, true, 5, 10, 15), 0x0001);
+    // The largest subnormal rounds up into the normals.
+    assert_eq!(
+        encode(2f64.powi(-14) * (1.0 - 2f64.powi(-12)), true, 5, 10, 15),
+        0x0400
+    );
+}
+
+#[test]
+fn unsigned_minifloat() {
+    // 0-4-3: no sign bit, a 4 bit exponent biased by 7, 3 bits of mantissa.
+    assert_eq!(encode(1.0, false, 4, 3, 7), 7 << 3);
+    assert_eq!(encode(240.0, false, 4, 3, 7), (14 << 3) | 7);
+    assert_eq!(encode(248.0, false, 4, 3, 7), 15 << 3);
+    assert_eq!(decode(1, false, 4, 3, 7), 2f64.powi(-9));
+    assert!(decode((15 << 3) | 4, false, 4, 3, 7).is_nan());
+
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer.write_float(-1.0, false, 4, 3, 7).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(writer.position(), 0);
+}
+
+#[test]
+fn nan_payloads_roundtrip() {
+    // A signalling NaN with a payload in the high mantissa bits survives.
+    let nan = f64::from_bits(0xfff5_5400_0000_0000);
+    assert_eq!(encode(nan, true, 5, 10, 15), 0xfd55);
+    assert_eq!(decode(0xfd55, true, 5, 10, 15).to_bits(), nan.to_bits());
+
+    // A payload only in bits the layout can't hold is still a NaN.
+    let nan = f64::from_bits(0x7ff0_0000_0000_0001);
+    assert_eq!(encode(nan, true, 5, 10, 15), 0x7e00);
+
+    let mut writer = BitWriter::new(Vec::new());
+    assert!(writer.write_float(f64::NAN, true, 5, 0, 15).is_err());
+}
+
+#[test]
+fn mixed_layouts_unaligned_lsb0() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b101).unwrap();
+    assert_eq!(writer.write_float(1.5, true, 5, 10, 15).unwrap(), 16);
+    assert_eq!(writer.write_float(-3.25, true, 8, 7, 127).unwrap(), 16);
+    assert_eq!(writer.write_float(0.75, false, 4, 3, 7).unwrap(), 7);
+    assert_eq!(writer.write_float(1e300, true, 11, 52, 1023).unwrap(), 64);
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    assert_eq!(reader.read_float(true, 5, 10, 15).unwrap(), 1.5);
+    assert_eq!(reader.read_float(true, 8, 7, 127).unwrap(), -3.25);
+    assert_eq!(reader.read_float(false, 4, 3, 7).unwrap(), 0.75);
+    assert_eq!(reader.read_float(true, 11, 52, 1023).unwrap(), 1e300);
+}
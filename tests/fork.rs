@@ -0,0 +1,97 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::fs::File;
+use std::io::{self, BufReader, Cursor};
+
+use bitrw::{BitReader, BitReaderBuilder, Lsb0};
+
+fn data() -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..64).map(|_| rng.gen()).collect()
+}
+
+/// The `nbits` bits from `start`, read by a fresh reader.
+fn bits_at(data: &[u8], start: u64, nbits: u8) -> u64 {
+    let mut reader = BitReader::new(data);
+    reader.skip_bits(start).unwrap();
+    reader.read_bits(nbits).unwrap()
+}
+
+#[test]
+fn fork_is_independent() {
+    let data = data();
+    let mut reader = BitReader::new(&data[..]);
+    reader.read_bits(13).unwrap();
+
+    let mut fork = reader.fork();
+    assert_eq!(fork.position(), 13);
+    assert_eq!(fork.read_bits(40).unwrap(), bits_at(&data, 13, 40));
+    fork.skip_bits(100).unwrap();
+    assert_eq!(fork.position(), 153);
+    assert_eq!(reader.position(), 13);
+
+    // The original carries on past where the fork got to.
+    assert_eq!(reader.read_bits(40).unwrap(), bits_at(&data, 13, 40));
+    reader.skip_bits(150).unwrap();
+    assert_eq!(reader.read_bits(7).unwrap(), bits_at(&data, 203, 7));
+    assert_eq!(fork.read_bits(7).unwrap(), bits_at(&data, 153, 7));
+    assert_eq!(fork.position(), 160);
+}
+
+#[test]
+fn fork_keeps_order_and_limit() {
+    let data = [0xab, 0xcd, 0xef];
+    let mut reader = BitReaderBuilder::new()
+        .bit_order(Lsb0)
+        .limit_bits(20)
+        .build(Cursor::new(&data[..]));
+    assert_eq!(reader.read_bits(4).unwrap(), 0xb);
+
+    let mut fork = reader.fork();
+    assert_eq!(fork.read_bits(12).unwrap(), 0xcda);
+    assert_eq!(
+        fork.read_bits(8).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+    assert_eq!(fork.read_bits(4).unwrap(), 0xf);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xfcda);
+}
+
+#[test]
+fn fork_with_reopened_file() {
+    let data = data();
+    let path = std::env::temp_dir().join(format!("bitrw-fork-{}.bin", std::process::id()));
+    std::fs::write(&path, &data).unwrap();
+
+    let mut reader = BitReader::new(BufReader::new(File::open(&path).unwrap()));
+    reader.read_bits(21).unwrap();
+    let mut fork = reader
+        .fork_with(|_| File::open(&path).map(BufReader::new))
+        .unwrap();
+    assert_eq!(fork.position(), 21);
+    assert_eq!(fork.read_bits(64).unwrap(), bits_at(&data, 21, 64));
+    fork.skip_bits(200).unwrap();
+    assert_eq!(reader.position(), 21);
+
+    assert_eq!(reader.read_bits(64).unwrap(), bits_at(&data, 21, 64));
+    reader.skip_bits(250).unwrap();
+    assert_eq!(reader.read_bits(33).unwrap(), bits_at(&data, 335, 33));
+    assert_eq!(fork.read_bits(33).unwrap(), bits_at(&data, 285, 33));
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn fork_with_open_error() {
+    let data = [0u8; 4];
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(9).unwrap();
+    let err = reader
+        .fork_with(|_| -> io::Result<Cursor<&[u8]>> { Err(io::Error::other("no")) })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(9));
+    assert_eq!(reader.read_bits(7).unwrap(), 0);
+}
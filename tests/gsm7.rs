@@ -0,0 +1,111 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitWriter, BitWriterBuilder, Lsb0};
+
+const HELLOHELLO: [u8; 9] = [0xe8, 0x32, 0x9b, 0xfd, 0x46, 0x97, 0xd9, 0xec, 0x37];
+
+fn pack(septets: &[u8], fill_bits: u8) -> Vec<u8> {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    let written = writer.write_gsm7(septets, fill_bits).unwrap();
+    assert_eq!(written, fill_bits as usize + septets.len() * 7);
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn spec_example() {
+    assert_eq!(pack(b"hellohello", 0), HELLOHELLO);
+    let mut reader = BitReader::with_order(&HELLOHELLO[..], Lsb0);
+    assert_eq!(reader.read_gsm7(10, 0).unwrap(), b"hellohello");
+    assert_eq!(reader.position(), 70);
+}
+
+#[test]
+fn after_user_data_header() {
+    // A concatenated SMS header of six octets, so one fill bit brings the
+    // septets to a septet boundary at bit 49.
+    let header = [0x05, 0x00, 0x03, 0x2a, 0x02, 0x01];
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bytes(&header).unwrap();
+    writer.write_gsm7(b"hellohello", 1).unwrap();
+    writer.flush().unwrap();
+    let pdu = writer.into_inner();
+    assert_eq!(
+        pdu,
+        [
+            0x05, 0x00, 0x03, 0x2a, 0x02, 0x01, 0xd0, 0x65, 0x36, 0xfb, 0x8d, 0x2e, 0xb3, 0xd9,
+            0x6f
+        ]
+    );
+
+    let mut reader = BitReader::with_order(&pdu[..], Lsb0);
+    reader.skip_bits(48).unwrap();
+    assert_eq!(reader.read_gsm7(10, 1).unwrap(), b"hellohello");
+}
+
+#[test]
+fn round_trips() {
+    let mut rng = rand::thread_rng();
+    for fill_bits in 0..=6 {
+        for len in 0..40 {
+            let septets: Vec<u8> = (0..len).map(|_| rng.gen_range(0, 0x80)).collect();
+            let packed = pack(&septets, fill_bits);
+            assert_eq!(packed.len(), (fill_bits as usize + len * 7).div_ceil(8));
+
+            let mut reader = BitReader::with_order(&packed[..], Lsb0);
+            assert_eq!(reader.read_gsm7(len, fill_bits).unwrap(), septets);
+            assert_eq!(reader.position(), fill_bits as u64 + len as u64 * 7);
+        }
+    }
+}
+
+#[test]
+fn eight_septets_fill_seven_octets() {
+    let packed = pack(b"abcdefgh", 0);
+    assert_eq!(packed.len(), 7);
+    let mut reader = BitReader::with_order(&packed[..], Lsb0);
+    assert_eq!(reader.read_gsm7(8, 0).unwrap(), b"abcdefgh");
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn not_septets() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    let err = writer.write_gsm7(b"ok\xe9", 2).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(2));
+    assert_eq!(writer.position(), 0);
+}
+
+#[test]
+fn limits_and_ends() {
+    let mut writer = BitWriterBuilder::new()
+        .bit_order(Lsb0)
+        .limit_bits(20)
+        .build(Vec::new());
+    let err = writer.write_gsm7(b"abc", 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 0);
+    writer.write_gsm7(b"ab", 6).unwrap();
+
+    let mut reader = BitReader::with_order(&HELLOHELLO[..], Lsb0);
+    reader.read_bits(3).unwrap();
+    let err = reader.read_gsm7(10, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(3));
+
+    // A count from a hostile header runs into the end, not the allocator.
+    let mut reader = BitReader::with_order(&HELLOHELLO[..], Lsb0);
+    let err = reader.read_gsm7(usize::MAX, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+#[should_panic]
+fn too_many_fill_bits() {
+    let _ = BitWriter::with_order(Vec::new(), Lsb0).write_gsm7(b"a", 7);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "half")]
+
+extern crate bitrw;
+extern crate half;
+
+use half::{bf16, f16};
+use std::io::Cursor;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+#[test]
+fn known_constants() {
+    let cases = [
+        (f16::ONE, 0x3c00, 1.0),
+        (f16::MAX, 0x7bff, 65504.0),
+        (f16::from_bits(0x0001), 0x0001, 2f32.powi(-24)),
+        (f16::NEG_INFINITY, 0xfc00, f32::NEG_INFINITY),
+    ];
+
+    for &(value, bits, float) in &cases {
+        assert_eq!(value.to_bits(), bits);
+        assert_eq!(value.to_f32(), float);
+        assert_eq!(f16::from_f32(float).to_bits(), bits);
+
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_f16(value).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), [(bits >> 8) as u8, bits as u8]);
+    }
+}
+
+#[test]
+fn every_pattern_roundtrips_unaligned() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    for bits in 0..=u16::MAX {
+        writer.write_f16(f16::from_bits(bits)).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    for bits in 0..=u16::MAX {
+        // Compare patterns: NaN payloads must survive untouched.
+        assert_eq!(reader.read_f16().unwrap().to_bits(), bits);
+    }
+}
+
+#[test]
+fn nan_payloads_and_subnormals_lsb0() {
+    let values = [0x7e01, 0xfd55, 0x7c01, 0x03ff, 0x8001, 0x8000];
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bit(1).unwrap();
+    for &bits in &values {
+        writer.write(f16::from_bits(bits)).unwrap();
+        writer.write(bf16::from_bits(bits)).unwrap();
+    }
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+    assert_eq!(reader.read_bit().unwrap(), 1);
+    for &bits in &values {
+        assert_eq!(reader.read_f16().unwrap().to_bits(), bits);
+        assert_eq!(reader.read::<bf16>().unwrap().to_bits(), bits);
+    }
+}
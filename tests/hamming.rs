@@ -0,0 +1,134 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitWriter, HammingCode, HammingReader, HammingWriter, Lsb0};
+
+const CODES: [HammingCode; 2] = [HammingCode::Hamming74, HammingCode::Secded];
+
+fn encode(code: HammingCode, fields: &[(u8, u64)]) -> Vec<u8> {
+    let mut writer = HammingWriter::new(BitWriter::new(Vec::new()), code);
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.end_block().unwrap();
+    writer.flush().unwrap();
+    writer.into_inner().into_inner()
+}
+
+fn flip(data: &mut [u8], bit: u64) {
+    data[bit as usize / 8] ^= 0x80 >> (bit % 8);
+}
+
+#[test]
+fn known_code_words() {
+    let mut writer = HammingWriter::new(BitWriter::new(Vec::new()), HammingCode::Hamming74);
+    // Every nibble, each as a 7 bit code word.
+    for nibble in 0..16 {
+        writer.write_bits(4, nibble).unwrap();
+    }
+    assert_eq!(writer.code_words(), 16);
+    writer.flush().unwrap();
+    let data = writer.into_inner().into_inner();
+    let mut reader = BitReader::new(&data[..]);
+    let words: Vec<u64> = (0..16).map(|_| reader.read_bits(7).unwrap()).collect();
+    assert_eq!(words[0b1011], 0b0110011);
+    assert_eq!(words[0b0000], 0);
+    assert_eq!(words[0b1111], 0b1111111);
+    // Any two code words differ in at least three bits.
+    for a in &words {
+        for b in &words {
+            assert!(a == b || (a ^ b).count_ones() >= 3);
+        }
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..200)
+        .map(|_| {
+            let nbits = rng.gen_range(0, 65);
+            let value = if nbits == 0 {
+                0
+            } else {
+                rng.gen::<u64>() >> (64 - nbits)
+            };
+            (nbits, value)
+        })
+        .collect();
+    for &code in &CODES {
+        let data = encode(code, &fields);
+        let mut reader = HammingReader::new(BitReader::new(&data[..]), code);
+        for &(nbits, value) in &fields {
+            assert_eq!(reader.read_bits(nbits).unwrap(), value);
+        }
+        assert_eq!(reader.corrections(), 0);
+    }
+}
+
+#[test]
+fn corrects_every_single_error() {
+    let fields = [(32, 0xdead_beef), (16, 0x0f0f)];
+    for &code in &CODES {
+        let clean = encode(code, &fields);
+        let words = 12;
+        for word in 0..words {
+            for bit in 0..code.code_bits() as u64 {
+                let mut data = clean.clone();
+                flip(&mut data, word * code.code_bits() as u64 + bit);
+                let mut reader = HammingReader::new(BitReader::new(&data[..]), code);
+                assert_eq!(reader.read_bits(32).unwrap(), 0xdead_beef);
+                assert_eq!(reader.read_bits(16).unwrap(), 0x0f0f);
+                assert_eq!(reader.corrections(), 1);
+                assert_eq!(reader.code_words(), words);
+            }
+        }
+    }
+}
+
+#[test]
+fn detects_double_errors() {
+    let clean = encode(HammingCode::Secded, &[(16, 0x1234), (8, 0x56)]);
+    for a in 0..8 {
+        for b in a + 1..8 {
+            let mut data = clean.clone();
+            flip(&mut data, 8 + a);
+            flip(&mut data, 8 + b);
+            let mut reader = HammingReader::new(BitReader::new(&data[..]), HammingCode::Secded);
+            assert_eq!(reader.read_bits(4).unwrap(), 0x1);
+            let err = reader.read_bits(8).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+            let err = bitrw::Error::from_io(&err).unwrap();
+            assert_eq!(err.offset(), Some(8));
+            assert_eq!(err.value(), Some(1));
+
+            // The uncorrected data bits are kept, and reading carries on.
+            reader.read_bits(4).unwrap();
+            assert_eq!(reader.read_bits(16).unwrap(), 0x3456);
+            assert_eq!(reader.corrections(), 0);
+        }
+    }
+}
+
+#[test]
+fn short_block_and_eof() {
+    let mut writer =
+        HammingWriter::new(BitWriter::with_order(Vec::new(), Lsb0), HammingCode::Secded);
+    writer.write_bits(6, 0b101101).unwrap();
+    assert_eq!(writer.code_words(), 1);
+    assert!(writer.end_block().unwrap());
+    assert!(!writer.end_block().unwrap());
+    writer.flush().unwrap();
+    let data = writer.into_inner().into_inner();
+    assert_eq!(data.len(), 2);
+
+    let mut reader =
+        HammingReader::new(BitReader::with_order(&data[..], Lsb0), HammingCode::Secded);
+    assert_eq!(reader.read_bits(8).unwrap(), 0b00101101);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 8);
+}
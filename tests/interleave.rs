@@ -0,0 +1,127 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{
+    BitReader, BitWriter, DeinterleaveBitReader, HammingCode, HammingReader, HammingWriter,
+    InterleaveBitWriter, Lsb0,
+};
+
+fn interleave(rows: usize, cols: usize, data: &[u8]) -> Vec<u8> {
+    let mut writer = InterleaveBitWriter::new(BitWriter::new(Vec::new()), rows, cols);
+    for &byte in data {
+        writer.write_bits(8, byte as u64).unwrap();
+    }
+    writer.end_block().unwrap();
+    writer.flush().unwrap();
+    writer.into_inner().into_inner()
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = rand::thread_rng();
+    for &(rows, cols) in &[(1, 1), (1, 9), (9, 1), (3, 5), (8, 7), (16, 100)] {
+        let fields: Vec<(u8, u64)> = (0..100)
+            .map(|_| {
+                let nbits = rng.gen_range(1, 65);
+                (nbits, rng.gen::<u64>() >> (64 - nbits))
+            })
+            .collect();
+        let mut writer =
+            InterleaveBitWriter::new(BitWriter::with_order(Vec::new(), Lsb0), rows, cols);
+        for &(nbits, value) in &fields {
+            writer.write_bits(nbits, value).unwrap();
+        }
+        let total = writer.position();
+        let partial = writer.end_block().unwrap();
+        assert_eq!(partial, total % (rows * cols) as u64 != 0);
+        assert_eq!(writer.blocks(), total.div_ceil((rows * cols) as u64));
+        writer.flush().unwrap();
+        let data = writer.into_inner().into_inner();
+
+        let mut reader =
+            DeinterleaveBitReader::new(BitReader::with_order(&data[..], Lsb0), rows, cols);
+        for &(nbits, value) in &fields {
+            assert_eq!(reader.read_bits(nbits).unwrap(), value);
+        }
+        assert_eq!(reader.position(), total);
+    }
+}
+
+#[test]
+fn transposes_blocks() {
+    // Rows of 0xf0 and 0x0f come out as alternating pairs of columns.
+    assert_eq!(interleave(2, 8, &[0xf0, 0x0f]), [0xaa, 0x55]);
+    assert_eq!(interleave(1, 8, &[0x12, 0x34]), [0x12, 0x34]);
+}
+
+#[test]
+fn burst_lands_in_distinct_rows() {
+    let rows = 8;
+    let cols = 7;
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..7).map(|_| rng.gen()).collect();
+    let sent = interleave(rows, cols, &data);
+
+    for start in 0..(rows * cols - rows + 1) as u64 {
+        let mut damaged = sent.clone();
+        for bit in start..start + rows as u64 {
+            damaged[bit as usize / 8] ^= 0x80 >> (bit % 8);
+        }
+        let mut reader = DeinterleaveBitReader::new(BitReader::new(&damaged[..]), rows, cols);
+        let received: Vec<u8> = (0..7).map(|_| reader.read_bits(8).unwrap() as u8).collect();
+
+        let mut hit_rows = Vec::new();
+        for bit in 0..rows * cols {
+            let mask = 0x80 >> (bit % 8);
+            if (data[bit / 8] ^ received[bit / 8]) & mask != 0 {
+                hit_rows.push(bit / cols);
+            }
+        }
+        assert_eq!(hit_rows.len(), rows);
+        hit_rows.dedup();
+        assert_eq!(hit_rows.len(), rows);
+    }
+}
+
+#[test]
+fn burst_corrected_by_hamming() {
+    // Eight Hamming(7, 4) code words, one per row.
+    let mut encoder = HammingWriter::new(BitWriter::new(Vec::new()), HammingCode::Hamming74);
+    encoder.write_bits(32, 0xcafe_f00d).unwrap();
+    encoder.flush().unwrap();
+    let code = encoder.into_inner().into_inner();
+    assert_eq!(code.len(), 7);
+
+    let mut sent = interleave(8, 7, &code);
+    for bit in 20..28 {
+        sent[bit / 8] ^= 0x80 >> (bit % 8);
+    }
+
+    let mut reader = DeinterleaveBitReader::new(BitReader::new(&sent[..]), 8, 7);
+    let received: Vec<u8> = (0..7).map(|_| reader.read_bits(8).unwrap() as u8).collect();
+    let mut decoder = HammingReader::new(BitReader::new(&received[..]), HammingCode::Hamming74);
+    assert_eq!(decoder.read_bits(32).unwrap(), 0xcafe_f00d);
+    assert_eq!(decoder.corrections(), 8);
+}
+
+#[test]
+fn truncated_block() {
+    let data = interleave(4, 4, &[0xab, 0xcd, 0xef]);
+    assert_eq!(data.len(), 4);
+    let mut reader = DeinterleaveBitReader::new(BitReader::new(&data[..3]), 4, 4);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xabcd);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(8));
+
+    // At a block boundary the end of the stream is passed through.
+    let mut reader = DeinterleaveBitReader::new(BitReader::new(&data[..2]), 4, 4);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xabcd);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(bitrw::Error::from_io(&err).is_none());
+    assert_eq!(reader.blocks(), 1);
+}
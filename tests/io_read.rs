@@ -0,0 +1,92 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor, Read};
+
+use bitrw::{BitReader, BitReaderBuilder, Lsb0};
+
+#[test]
+fn interleaved() {
+    let data: Vec<u8> = (0..32).collect();
+    let mut reader = BitReader::new(Cursor::new(&data));
+
+    assert_eq!(reader.read_bits(8).unwrap(), 0);
+    let mut buf = [0; 3];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [1, 2, 3]);
+    assert_eq!(reader.position(), 32);
+
+    // Three bits of 4, then the rest of it.
+    assert_eq!(reader.read_bits(3).unwrap(), 0b000);
+    assert_eq!(reader.read_bits(5).unwrap(), 0b00100);
+
+    // A 16 bit read loads byte 5 and 6, and a 12 bit one leaves half of 8
+    // buffered.
+    assert_eq!(reader.read_bits(16).unwrap(), 0x0506);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x070);
+    assert_eq!(reader.read_bits(4).unwrap(), 0x8);
+    assert_eq!(reader.position(), 72);
+
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, &data[9..]);
+    assert_eq!(reader.position(), 256);
+}
+
+#[test]
+fn buffered_bytes_first() {
+    let data = [0x12, 0x34, 0x56, 0x78, 0x9a];
+    let mut reader = BitReader::new(&data[..]);
+
+    // Bits pushed back are whole bytes in the buffer, which are returned
+    // without reading any more.
+    assert_eq!(reader.read_bits(12).unwrap(), 0x123);
+    assert_eq!(reader.read_bits(4).unwrap(), 0x4);
+    reader.unread_bits(16, 0x1234).unwrap();
+    let mut buf = [0; 4];
+    assert_eq!(io::Read::read(&mut reader, &mut buf).unwrap(), 2);
+    assert_eq!(buf[..2], [0x12, 0x34]);
+    assert_eq!(reader.position(), 16);
+
+    let mut buf = [0; 3];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x56, 0x78, 0x9a]);
+    assert_eq!(io::Read::read(&mut reader, &mut buf).unwrap(), 0);
+}
+
+#[test]
+fn lsb0() {
+    let data = [0xab, 0xcd, 0xef];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    let mut buf = [0];
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0xcd]);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b111);
+}
+
+#[test]
+fn mid_byte() {
+    let data = [0xff; 4];
+    let mut reader = BitReader::new(&data[..]);
+    reader.read_bits(3).unwrap();
+    let err = reader.read_exact(&mut [0]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(3));
+
+    // Nothing was consumed, and aligning again allows byte reads.
+    assert_eq!(reader.position(), 3);
+    reader.read_bits(5).unwrap();
+    reader.read_exact(&mut [0]).unwrap();
+    assert_eq!(reader.position(), 16);
+}
+
+#[test]
+fn limit() {
+    let data = [0xff; 8];
+    let mut reader = BitReaderBuilder::new().limit_bits(36).build(&data[..]);
+    let mut rest = Vec::new();
+    reader.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, [0xff; 4]);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xf);
+    assert!(reader.read_bit().is_err());
+}
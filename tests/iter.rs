@@ -0,0 +1,98 @@
+extern crate bitrw;
+
+use std::cell::Cell;
+use std::io;
+
+use bitrw::{BitRead, BitReader, Lsb0};
+
+/// Yields one byte per call, admits to nothing in `size_hint()`, and counts how
+/// many it's been asked for.
+struct Stingy<'a> {
+    data: &'a [u8],
+    pulled: &'a Cell<usize>,
+}
+
+impl<'a> Iterator for Stingy<'a> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let (&first, rest) = self.data.split_first()?;
+        self.data = rest;
+        self.pulled.set(self.pulled.get() + 1);
+        Some(first)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+#[test]
+fn stingy_iterator() {
+    let data = [0xde, 0xad, 0xbe, 0xef, 0x55];
+    let pulled = Cell::new(0);
+    let mut reader = BitReader::from_byte_iter(Stingy {
+        data: &data,
+        pulled: &pulled,
+    });
+
+    assert_eq!(reader.read_bits(4).unwrap(), 0xd);
+    assert_eq!(pulled.get(), 1);
+    assert_eq!(reader.read_bits(9).unwrap(), 0x1d5);
+    assert_eq!(pulled.get(), 2);
+    assert_eq!(reader.read_bits(19).unwrap(), 0x5_beef);
+    assert_eq!(reader.read::<u8>().unwrap(), 0x55);
+
+    let err = reader.read_bit().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn generated_lsb0() {
+    let mut reader = BitReader::from_byte_iter_with_order((0..=255u8).cycle().take(1000), Lsb0);
+    for i in 0..1000u32 {
+        assert_eq!(reader.read_bits(8).unwrap(), (i % 256) as u64);
+    }
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn flattened_chunks() {
+    let chunks = vec![vec![0x12, 0x34], vec![], vec![0x56], vec![0x78]];
+    let mut reader = BitReader::from_byte_iter(chunks.into_iter().flatten());
+    assert_eq!(reader.read_be::<u32>().unwrap(), 0x1234_5678);
+}
+
+#[test]
+fn error_mid_stream() {
+    let items = vec![
+        Ok(0xab),
+        Ok(0xcd),
+        Err(io::Error::other("layer below failed")),
+        Ok(0xef),
+    ];
+    let mut reader = BitReader::from_fallible_iter(items.into_iter());
+
+    assert_eq!(reader.read_bits(12).unwrap(), 0xabc);
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert_eq!(err.to_string(), "layer below failed");
+
+    // The pending bits survived, and the iterator carries on past the error.
+    assert_eq!(reader.read_bits(12).unwrap(), 0xdef);
+    assert_eq!(
+        reader.read_bit().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn fallible_clean_eof() {
+    let mut reader = BitReader::from_fallible_iter_with_order(vec![Ok(0x0f)].into_iter(), Lsb0);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xf);
+    assert_eq!(reader.read_bits(4).unwrap(), 0);
+    assert_eq!(
+        reader.read_bit().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
@@ -0,0 +1,135 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Read};
+
+use bitrw::{BitRead, BitWrite, JpegBitReader, JpegBitWriter, JpegSource};
+
+// Two restart intervals and the end of the image.  The first holds 0xff, 0xfe
+// and 0xff bytes of data, and ends with a partial byte padded with ones.
+const SCAN: &[u8] = &[
+    0x12, 0xff, 0x00, 0xfe, 0xff, 0x00, 0x3f, // 36 bits of data, 4 of padding
+    0xff, 0xd0, // RST0
+    0xab, 0xff, 0xff, 0xd9, // fill byte, then EOI
+    0x00, 0x00,
+];
+
+#[test]
+fn source_unstuffs() {
+    let mut source = JpegSource::new(SCAN);
+    let mut data = Vec::new();
+    source.read_to_end(&mut data).unwrap();
+    assert_eq!(data, [0x12, 0xff, 0xfe, 0xff, 0x3f]);
+    assert_eq!(source.marker_hit(), Some(0xd0));
+
+    // Nothing more is delivered until the marker is cleared.
+    assert_eq!(source.read(&mut [0]).unwrap(), 0);
+    assert_eq!(source.clear_marker(), Some(0xd0));
+    data.clear();
+    source.read_to_end(&mut data).unwrap();
+    assert_eq!(data, [0xab]);
+    assert_eq!(source.marker_hit(), Some(0xd9));
+    assert_eq!(source.get_ref(), &[0x00, 0x00]);
+}
+
+#[test]
+fn reader_stops_at_restart_marker() {
+    let mut reader = JpegBitReader::new(SCAN);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x12);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xfffe);
+    assert_eq!(reader.read_bits(12).unwrap(), 0xff3);
+    assert_eq!(reader.marker_hit(), None);
+    assert_eq!(reader.position(), 36);
+
+    // Only 4 bits of padding remain before the marker, so 5 can't be read.
+    let err = reader.read_bits(5).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.marker_hit(), Some(0xd0));
+    assert_eq!(reader.position(), 36);
+    assert_eq!(reader.read_bits(2).unwrap(), 0b11);
+    assert_eq!(reader.position(), 38);
+
+    // The rest of the padding is discarded at the restart.
+    assert_eq!(reader.restart(), Some(0xd0));
+    assert_eq!(reader.marker_hit(), None);
+    assert_eq!(reader.position(), 40);
+    assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    assert!(reader.read_bit().is_err());
+    assert_eq!(reader.restart(), Some(0xd9));
+    assert_eq!(reader.into_inner(), &[0x00, 0x00]);
+}
+
+#[test]
+fn stuffing_straddles_fields() {
+    // Data ff ff 00: the stuffing sits inside what's read as one field.
+    let mut reader = JpegBitReader::new(&[0xff, 0x00, 0xff, 0x00, 0x00][..]);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b111);
+    assert_eq!(reader.read_bits(21).unwrap(), 0x1fff00);
+    assert!(reader.read_bit().is_err());
+    assert_eq!(reader.marker_hit(), None);
+}
+
+#[test]
+fn writer_stuffs_all_ones() {
+    let mut writer = JpegBitWriter::new(Vec::new());
+    writer.write_bits(12, 0xfff).unwrap();
+    // The padding completes a second 0xff, which must be stuffed too.
+    assert_eq!(writer.flush().unwrap(), 4);
+    assert_eq!(writer.position(), 16);
+    assert_eq!(writer.get_ref().get_ref()[..], [0xff, 0x00, 0xff, 0x00]);
+
+    writer.write_bits(3, 0b010).unwrap();
+    assert_eq!(writer.write_marker(0xd0).unwrap(), 5);
+    writer.write_bits(8, 0xff).unwrap();
+    writer.write_marker(0xd9).unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        [0xff, 0x00, 0xff, 0x00, 0x5f, 0xff, 0xd0, 0xff, 0x00, 0xff, 0xd9]
+    );
+}
+
+#[test]
+fn roundtrip_through_reader() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        // Mostly runs of ones, so plenty of 0xff bytes.
+        let intervals: Vec<Vec<(u8, u64)>> = (0..rng.gen_range(1, 5))
+            .map(|_| {
+                (0..rng.gen_range(0, 50))
+                    .map(|_| {
+                        let width = rng.gen_range(1, 17);
+                        let value = if rng.gen_range(0, 3) == 0 {
+                            rng.gen::<u64>() >> (64 - width)
+                        } else {
+                            (1 << width) - 1
+                        };
+                        (width, value)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut writer = JpegBitWriter::new(Vec::new());
+        for (i, interval) in intervals.iter().enumerate() {
+            for &(width, value) in interval {
+                writer.write_bits(width, value).unwrap();
+            }
+            writer.write_marker(0xd0 + i as u8).unwrap();
+        }
+        let scan = writer.into_inner();
+
+        let mut reader = JpegBitReader::new(&scan[..]);
+        for (i, interval) in intervals.iter().enumerate() {
+            for &(width, value) in interval {
+                assert_eq!(reader.read_bits(width).unwrap(), value);
+            }
+            // Whatever is left before the marker is one bits of padding.
+            while let Ok(bit) = reader.read_bit() {
+                assert_eq!(bit, 1);
+            }
+            assert_eq!(reader.restart(), Some(0xd0 + i as u8));
+        }
+        assert!(reader.into_inner().is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitWriter, CodeStreamReader, CodeStreamWriter, Lsb0};
+
+/// The image data of the 10 by 10, four colour sample GIF from Matthew
+/// Flickinger's "What's In A GIF", without its sub-block framing.
+const GIF_BLOCK: [u8; 22] = [
+    0x8c, 0x2d, 0x99, 0x87, 0x2a, 0x1c, 0xdc, 0x33, 0xa0, 0x02, 0x75, 0xec, 0x95, 0xfa, 0xa8, 0xde,
+    0x60, 0x8c, 0x04, 0x91, 0x4c, 0x01,
+];
+
+const GIF_CODES: [u16; 36] = [
+    4, 1, 6, 6, 2, 9, 9, 7, 8, 10, 2, 12, 1, 14, 15, 6, 0, 21, 0, 10, 7, 22, 23, 18, 26, 7, 10, 29,
+    13, 24, 12, 18, 16, 36, 12, 5,
+];
+
+/// Decode a GIF code stream with a minimum code size of 2, returning the
+/// codes with the width each was read at, and the pixels.
+fn decode_gif(data: &[u8]) -> (Vec<(u16, u8)>, Vec<u8>) {
+    let (clear, end) = (4, 5);
+    let mut reader = CodeStreamReader::new(BitReader::with_order(data, Lsb0), 3, 12);
+    let mut table: Vec<Vec<u8>> = Vec::new();
+    let mut prev: Option<u16> = None;
+    let mut codes = Vec::new();
+    let mut pixels = Vec::new();
+    loop {
+        let width = reader.width();
+        let code = reader.read_code().unwrap();
+        codes.push((code, width));
+        if code == clear {
+            table = (0..4).map(|i| vec![i]).collect();
+            table.extend(vec![Vec::new(), Vec::new()]);
+            reader.reset_width();
+            prev = None;
+            continue;
+        }
+        if code == end {
+            break;
+        }
+        let entry = match prev {
+            None => table[code as usize].clone(),
+            Some(prev) => {
+                let prev = &table[prev as usize];
+                let entry = match table.get(code as usize) {
+                    Some(entry) => entry.clone(),
+                    None => {
+                        let mut entry = prev.clone();
+                        entry.push(prev[0]);
+                        entry
+                    }
+                };
+                let mut added = prev.clone();
+                added.push(entry[0]);
+                table.push(added);
+                if table.len() == 1 << reader.width() {
+                    reader.bump_width();
+                }
+                entry
+            }
+        };
+        pixels.extend_from_slice(&entry);
+        prev = Some(code);
+    }
+    assert_eq!(reader.get_ref().position(), 172);
+    (codes, pixels)
+}
+
+#[test]
+fn gif_sample_block() {
+    let (codes, pixels) = decode_gif(&GIF_BLOCK);
+    let just_codes: Vec<u16> = codes.iter().map(|&(code, _)| code).collect();
+    assert_eq!(just_codes, GIF_CODES);
+
+    let widths: Vec<u8> = codes.iter().map(|&(_, width)| width).collect();
+    assert_eq!(widths[..4], [3; 4]);
+    assert_eq!(widths[4..12], [4; 8]);
+    assert_eq!(widths[12..28], [5; 16]);
+    assert_eq!(widths[28..], [6; 8]);
+
+    let mut expected = Vec::new();
+    for row in 0..10 {
+        let row: &[u8] = match row {
+            0..=2 => &[1, 1, 1, 1, 1, 2, 2, 2, 2, 2],
+            3..=4 => &[1, 1, 1, 0, 0, 0, 0, 2, 2, 2],
+            5..=6 => &[2, 2, 2, 0, 0, 0, 0, 1, 1, 1],
+            _ => &[2, 2, 2, 2, 2, 1, 1, 1, 1, 1],
+        };
+        expected.extend_from_slice(row);
+    }
+    assert_eq!(pixels, expected);
+}
+
+#[test]
+fn gif_sample_block_written() {
+    let (codes, _) = decode_gif(&GIF_BLOCK);
+    let mut writer = CodeStreamWriter::new(BitWriter::with_order(Vec::new(), Lsb0), 3, 12);
+    for &(code, width) in &codes {
+        if width > writer.width() {
+            assert!(writer.bump_width());
+        }
+        assert_eq!(writer.width(), width);
+        writer.write_code(code).unwrap();
+    }
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), GIF_BLOCK);
+}
+
+/// The dictionary of a synthetic TIFF stream, as far as code widths go.
+struct TiffTable {
+    next: u16,
+    fresh: bool,
+}
+
+impl TiffTable {
+    const CLEAR: u16 = 256;
+
+    fn new() -> Self {
+        Self {
+            next: 258,
+            fresh: true,
+        }
+    }
+
+    /// Account for `code`, returning whether to widen the codes after it,
+    /// which TIFF does a code early.
+    fn after(&mut self, code: u16, width: u8) -> bool {
+        if code == Self::CLEAR {
+            *self = Self::new();
+            return false;
+        }
+        if !self.fresh {
+            self.next += 1;
+        }
+        self.fresh = false;
+        self.next == (1 << width) - 1
+    }
+}
+
+#[test]
+fn tiff_round_trip() {
+    // Random codes which the table could hold, other than clear and end of
+    // information, clearing it only once it fills so every width is used.
+    let mut rng = rand::thread_rng();
+    let mut table = TiffTable::new();
+    let mut codes = vec![TiffTable::CLEAR];
+    for _ in 0..10000 {
+        let code = if table.next >= 4094 {
+            TiffTable::CLEAR
+        } else {
+            match rng.gen_range(0, table.next - 2) {
+                literal @ 0..=255 => literal,
+                code => code + 2,
+            }
+        };
+        table.after(code, 9);
+        codes.push(code);
+    }
+
+    let mut writer = CodeStreamWriter::new(BitWriter::new(Vec::new()), 9, 12);
+    let mut table = TiffTable::new();
+    let mut widths = Vec::new();
+    for &code in &codes {
+        widths.push(writer.width());
+        writer.write_code(code).unwrap();
+        if code == TiffTable::CLEAR {
+            writer.reset_width();
+        }
+        if table.after(code, writer.width()) {
+            assert!(writer.bump_width());
+        }
+    }
+    writer.flush().unwrap();
+    let data = writer.into_inner().into_inner();
+    assert!(widths.contains(&12));
+    // A clear code first, most significant bit first.
+    assert_eq!(data[0], 0x80);
+    let bits: u64 = widths.iter().map(|&width| width as u64).sum();
+    assert_eq!(data.len() as u64, bits.div_ceil(8));
+
+    let mut reader = CodeStreamReader::new(BitReader::new(&data[..]), 9, 12);
+    let mut table = TiffTable::new();
+    for (&code, &width) in codes.iter().zip(&widths) {
+        assert_eq!(reader.width(), width);
+        assert_eq!(reader.read_code().unwrap(), code);
+        if code == TiffTable::CLEAR {
+            reader.reset_width();
+        }
+        if table.after(code, reader.width()) {
+            assert!(reader.bump_width());
+        }
+    }
+}
+
+#[test]
+fn widths() {
+    let mut writer = CodeStreamWriter::new(BitWriter::new(Vec::new()), 3, 5);
+    assert_eq!(writer.width(), 3);
+    assert!(writer.bump_width());
+    assert!(writer.bump_width());
+    assert!(!writer.bump_width());
+    assert_eq!(writer.width(), 5);
+    writer.set_width(4);
+    writer.write_code(0xf).unwrap();
+    writer.reset_width();
+    writer.write_code(0x7).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0b1111_1110]);
+}
+
+#[test]
+fn code_too_wide() {
+    let mut writer = CodeStreamWriter::new(BitWriter::new(Vec::new()), 9, 12);
+    writer.write_code(511).unwrap();
+    let err = writer.write_code(512).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(512));
+    assert_eq!(err.offset(), Some(9));
+    assert_eq!(writer.get_ref().position(), 9);
+}
+
+#[test]
+fn end_of_stream() {
+    let mut reader = CodeStreamReader::new(BitReader::new(&[0xff, 0xff][..]), 9, 12);
+    assert_eq!(reader.read_code().unwrap(), 511);
+    let err = reader.read_code().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(9));
+}
+
+#[test]
+#[should_panic]
+fn width_below_initial() {
+    let mut reader = CodeStreamReader::new(BitReader::new(&[][..]), 9, 12);
+    reader.set_width(8);
+}
+
+#[test]
+#[should_panic]
+fn max_below_initial() {
+    let _ = CodeStreamWriter::new(BitWriter::new(Vec::new()), 9, 8);
+}
@@ -0,0 +1,121 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWriter, Lsb0, Manchester, ManchesterReader, ManchesterWriter};
+
+fn encode(convention: Manchester, fields: &[(u8, u64)]) -> Vec<u8> {
+    let mut writer = ManchesterWriter::new(BitWriter::new(Vec::new()), convention);
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    let mut inner = writer.into_inner();
+    inner.flush().unwrap();
+    inner.into_inner()
+}
+
+#[test]
+fn encode_conventions() {
+    // 1, 0 is 01 10 in IEEE 802.3 and 10 01 in Thomas's convention.
+    assert_eq!(encode(Manchester::Ieee, &[(4, 0b1010)]), [0b0110_0110]);
+    assert_eq!(encode(Manchester::Thomas, &[(4, 0b1010)]), [0b1001_1001]);
+    assert_eq!(encode(Manchester::Ieee, &[(8, 0xff)]), [0x55, 0x55]);
+    assert_eq!(encode(Manchester::Thomas, &[(8, 0xff)]), [0xaa, 0xaa]);
+}
+
+#[test]
+fn decode_preamble() {
+    // An Ethernet preamble as captured off the wire: the alternating bits
+    // 1010... coded as 01 10 01 10 ...
+    let capture = [0x66; 14];
+    let mut reader = ManchesterReader::new(BitReader::new(&capture[..]), Manchester::Ieee);
+    for _ in 0..7 {
+        assert_eq!(reader.read_bits(4).unwrap(), 0b1010);
+        assert_eq!(reader.read_bits(2).unwrap(), 0b10);
+        assert_eq!(reader.read_bits(2).unwrap(), 0b10);
+    }
+    assert_eq!(reader.position(), 56);
+    assert_eq!(reader.get_ref().position(), 112);
+    assert_eq!(
+        reader.read_bit().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+
+    // The same symbols with the other polarity are the complement.
+    let mut reader = ManchesterReader::new(BitReader::new(&capture[..]), Manchester::Thomas);
+    assert_eq!(reader.read_bits(56).unwrap(), 0x55_5555_5555_5555);
+}
+
+#[test]
+fn invalid_symbol() {
+    // 01 10 then 11.
+    let data = [0b0110_1101];
+    let mut reader = ManchesterReader::new(BitReader::new(&data[..]), Manchester::Ieee);
+    let err = reader.read_bits(3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(4));
+    assert_eq!(err.value(), Some(0b11));
+
+    // The bits before the violation are kept, and the bad pair left unread,
+    // so slipping a symbol gets back into sync.
+    assert_eq!(reader.get_ref().position(), 4);
+    reader.get_mut().read_bit().unwrap();
+    assert_eq!(reader.read_bits(3).unwrap(), 0b100);
+    assert_eq!(reader.position(), 3);
+
+    let data = [0b0000_0000];
+    let mut reader = ManchesterReader::new(BitReader::new(&data[..]), Manchester::Thomas);
+    let err = reader.read_bit().unwrap_err();
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.offset(), Some(0));
+    assert_eq!(err.value(), Some(0b00));
+}
+
+#[test]
+fn roundtrip() {
+    let mut rng = rand::thread_rng();
+    for &convention in &[Manchester::Ieee, Manchester::Thomas] {
+        let fields: Vec<(u8, u64)> = (0..500)
+            .map(|_| {
+                let nbits = rng.gen_range(0, 65);
+                let value = if nbits == 64 {
+                    rng.gen::<u64>()
+                } else {
+                    rng.gen::<u64>() & ((1 << nbits) - 1)
+                };
+                (nbits, value)
+            })
+            .collect();
+        let encoded = encode(convention, &fields);
+        let total: u64 = fields.iter().map(|&(n, _)| n as u64).sum();
+        assert_eq!(encoded.len() as u64, (total * 2).div_ceil(8));
+
+        let mut reader = ManchesterReader::new(BitReader::new(&encoded[..]), convention);
+        for &(nbits, value) in &fields {
+            assert_eq!(reader.read_bits(nbits).unwrap(), value);
+        }
+        assert_eq!(reader.bit_position(), Some(total));
+    }
+}
+
+#[test]
+fn roundtrip_lsb0() {
+    let mut writer =
+        ManchesterWriter::new(BitWriter::with_order(Vec::new(), Lsb0), Manchester::Ieee);
+    writer.write_bits(4, 0b0101).unwrap();
+    writer.write_bits(40, 0x12_3456_789a).unwrap();
+    assert_eq!(writer.position(), 44);
+    let mut inner = writer.into_inner();
+    inner.flush().unwrap();
+    let encoded = inner.into_inner();
+    // 1, 0, 1, 0 in stream order is 01 10 01 10, packed from the low bit.
+    assert_eq!(encoded[0], 0b0110_0110);
+
+    let reader = BitReader::with_order(Cursor::new(encoded), Lsb0);
+    let mut reader = ManchesterReader::new(reader, Manchester::Ieee);
+    assert_eq!(reader.read_bits(4).unwrap(), 0b0101);
+    assert_eq!(reader.read_bits(40).unwrap(), 0x12_3456_789a);
+}
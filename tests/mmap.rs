@@ -0,0 +1,71 @@
+#![cfg(feature = "mmap")]
+
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::fs;
+use std::io::{Cursor, SeekFrom};
+use std::path::PathBuf;
+
+use bitrw::{BitReader, Lsb0, MmapBitReader};
+
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, data: &[u8]) -> Self {
+        let path = std::env::temp_dir().join(format!("bitrw-{}-{}", std::process::id(), name));
+        fs::write(&path, data).unwrap();
+        TempFile(path)
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn random_access_matches_in_memory() {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..64 * 1024).map(|_| rng.gen::<u8>()).collect();
+    let file = TempFile::new("random", &data);
+
+    let mut mapped: MmapBitReader = BitReader::from_path(&file.0).unwrap();
+    let mut memory = BitReader::new(Cursor::new(&data[..]));
+    let bits = data.len() as u64 * 8;
+
+    for _ in 0..10_000 {
+        let width = rng.gen_range(1, 65);
+        let pos = rng.gen_range(0, bits - width as u64);
+        mapped.seek(SeekFrom::Start(pos)).unwrap();
+        memory.seek(SeekFrom::Start(pos)).unwrap();
+        assert_eq!(
+            mapped.read_bits(width).unwrap(),
+            memory.read_bits(width).unwrap(),
+            "{} bits at {}",
+            width,
+            pos
+        );
+        assert_eq!(mapped.position(), pos + width as u64);
+    }
+
+    mapped.seek(SeekFrom::Start(bits - 3)).unwrap();
+    assert_eq!(
+        mapped.read_bits(3).unwrap(),
+        (data[data.len() - 1] & 7) as u64
+    );
+    assert!(mapped.read_bit().is_err());
+}
+
+#[test]
+fn lsb0_and_missing_file() {
+    let file = TempFile::new("lsb0", &[0xab, 0xcd]);
+    let mut reader = BitReader::from_path_with_order(&file.0, Lsb0).unwrap();
+    assert_eq!(reader.read_bits(12).unwrap(), 0xdab);
+    assert_eq!(reader.get_ref().get_ref()[..], [0xab, 0xcd]);
+
+    let missing = std::env::temp_dir().join("bitrw-does-not-exist");
+    assert!(BitReader::from_path(missing).is_err());
+}
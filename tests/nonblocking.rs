@@ -0,0 +1,117 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWriter};
+
+/// Returns `WouldBlock` before every other byte.
+struct Flaky<R> {
+    inner: R,
+    ready: bool,
+    blocked: usize,
+}
+
+impl<R: io::Read> io::Read for Flaky<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.ready = !self.ready;
+        if !self.ready {
+            self.blocked += 1;
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = buf.len().min(1);
+        self.inner.read(&mut buf[..n])
+    }
+}
+
+fn retry<T>(mut f: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+    loop {
+        match f() {
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            result => return result,
+        }
+    }
+}
+
+#[test]
+fn would_block_resumes() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..1000)
+        .map(|_| {
+            let nbits = rng.gen_range(0, 65);
+            (
+                nbits,
+                rng.gen::<u64>() & (u64::MAX >> (64 - nbits as u32).min(63)),
+            )
+        })
+        .map(|(nbits, value)| (nbits, if nbits == 0 { 0 } else { value }))
+        .collect();
+
+    let mut writer = BitWriter::new(Vec::new());
+    for &(nbits, value) in &fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.write_bits(16, 0xbeef).unwrap();
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Flaky {
+        inner: Cursor::new(writer.into_inner()),
+        ready: false,
+        blocked: 0,
+    });
+    for &(nbits, value) in &fields {
+        assert_eq!(retry(|| reader.read_bits(nbits)).unwrap(), value);
+    }
+    assert_eq!(retry(|| reader.read::<u16>()).unwrap(), 0xbeef);
+    assert!(reader.get_ref().blocked > 0);
+    let padding = (8 - reader.position() % 8) % 8;
+    assert_eq!(retry(|| reader.read_bits(padding as u8)).unwrap(), 0);
+    assert_eq!(
+        retry(|| reader.read_bit()).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn would_block_keeps_position() {
+    let mut reader = BitReader::new(Flaky {
+        inner: Cursor::new([0x12, 0x34, 0x56]),
+        ready: true,
+        blocked: 0,
+    });
+
+    // The first byte blocks straight away, and nothing is consumed.
+    let err = reader.read_bits(20).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(reader.position(), 0);
+
+    // Then the second, with the first still buffered.
+    let err = reader.read_bits(20).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(reader.position(), 0);
+
+    assert_eq!(retry(|| reader.read_bits(20)).unwrap(), 0x12345);
+    assert_eq!(reader.position(), 20);
+    assert_eq!(retry(|| reader.read_bits(4)).unwrap(), 0x6);
+}
+
+#[test]
+fn would_block_skip_progress() {
+    let mut reader = BitReader::new(Flaky {
+        inner: Cursor::new([0xff, 0xff, 0xff, 0x80]),
+        ready: false,
+        blocked: 0,
+    });
+
+    let target = 24;
+    while reader.position() < target {
+        match reader.skip_bits(target - reader.position()) {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => panic!("{}", e),
+        }
+    }
+    assert_eq!(reader.position(), 24);
+    assert_eq!(retry(|| reader.read_bit()).unwrap(), 1);
+}
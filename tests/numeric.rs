@@ -0,0 +1,444 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0, Overflow};
+
+fn bits_of(nbits: u8, value: u64) -> BitReader<Cursor<Vec<u8>>> {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(nbits, value).unwrap();
+    writer.flush().unwrap();
+    BitReader::new(Cursor::new(writer.into_inner()))
+}
+
+#[test]
+fn sign_magnitude_roundtrip() {
+    let mut rng = rand::thread_rng();
+    for nbits in 2..=64u8 {
+        let max = (u64::MAX >> (65 - nbits)) as i64;
+        let mut values = vec![0, 1, -1, max, -max, max - 1, -(max - 1)];
+        values.extend((0..100).map(|_| rng.gen::<i64>() >> (65 - nbits)));
+
+        let mut writer = BitWriter::new(Vec::new());
+        for &value in &values {
+            assert_eq!(
+                writer.write_sign_magnitude(nbits, value).unwrap(),
+                nbits as usize
+            );
+        }
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+        for &value in &values {
+            assert_eq!(
+                reader.read_sign_magnitude(nbits).unwrap(),
+                value,
+                "{} bits",
+                nbits
+            );
+        }
+    }
+}
+
+#[test]
+fn sign_magnitude_layout() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_sign_magnitude(8, -5).unwrap();
+    writer.write_sign_magnitude(8, 5).unwrap();
+    writer.write_sign_magnitude(8, 0).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x85, 0x05, 0x00]);
+
+    // The sign is the high bit of the field, which comes last in Lsb0.
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_sign_magnitude(4, -3).unwrap();
+    writer.write_sign_magnitude(4, 3).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x3b]);
+}
+
+#[test]
+fn sign_magnitude_zeros() {
+    // Negative zero decodes as zero.
+    assert_eq!(bits_of(8, 0x80).read_sign_magnitude(8).unwrap(), 0);
+    assert_eq!(bits_of(8, 0x00).read_sign_magnitude(8).unwrap(), 0);
+    assert_eq!(bits_of(64, 1 << 63).read_sign_magnitude(64).unwrap(), 0);
+    assert_eq!(bits_of(1, 1).read_sign_magnitude(1).unwrap(), 0);
+    assert_eq!(
+        bits_of(64, u64::MAX).read_sign_magnitude(64).unwrap(),
+        -i64::MAX
+    );
+}
+
+#[test]
+fn sign_magnitude_too_large() {
+    let mut writer = BitWriter::new(Vec::new());
+    for &(nbits, value) in &[(2, 2), (2, -2), (8, 128), (8, -128), (64, i64::MIN), (1, 1)] {
+        let err = writer.write_sign_magnitude(nbits, value).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = bitrw::Error::from_io(&err).unwrap();
+        assert_eq!(err.value(), Some(value as u64));
+    }
+    assert_eq!(writer.position(), 0);
+}
+
+#[test]
+fn gray_single_bit_change() {
+    for value in 0..=0xffffu64 {
+        let diff = bitrw::gray_encode(value) ^ bitrw::gray_encode(value + 1);
+        assert_eq!(diff.count_ones(), 1, "{} -> {}", value, value + 1);
+    }
+    // Including the wrap from the largest value of a width back to zero.
+    for nbits in 1..64 {
+        let max = (1u64 << nbits) - 1;
+        assert_eq!(bitrw::gray_encode(max).count_ones(), 1);
+    }
+    let diff = bitrw::gray_encode(u64::MAX) ^ bitrw::gray_encode(u64::MAX - 1);
+    assert_eq!(diff.count_ones(), 1);
+}
+
+#[test]
+fn gray_decode_inverts_encode() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..10_000 {
+        let value = rng.gen::<u64>();
+        assert_eq!(bitrw::gray_decode(bitrw::gray_encode(value)), value);
+        assert_eq!(bitrw::gray_encode(bitrw::gray_decode(value)), value);
+    }
+    assert_eq!(bitrw::gray_decode(bitrw::gray_encode(u64::MAX)), u64::MAX);
+}
+
+#[test]
+fn gray_roundtrip_small_widths() {
+    for nbits in 1..=12u8 {
+        let mut writer = BitWriter::new(Vec::new());
+        for value in 0..1u64 << nbits {
+            writer.write_gray(nbits, value).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+        let mut previous: Option<u64> = None;
+        for value in 0..1u64 << nbits {
+            let position = reader.position();
+            assert_eq!(reader.read_gray(nbits).unwrap(), value);
+
+            // The raw fields differ from each other by a single bit.
+            reader.seek(io::SeekFrom::Start(position)).unwrap();
+            let raw = reader.read_bits(nbits).unwrap();
+            if let Some(previous) = previous {
+                assert_eq!((raw ^ previous).count_ones(), 1);
+            }
+            previous = Some(raw);
+        }
+    }
+}
+
+#[test]
+fn gray_layout() {
+    let mut writer = BitWriter::new(Vec::new());
+    for value in 0..4 {
+        writer.write_gray(2, value).unwrap();
+    }
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b00_01_11_10]);
+}
+
+#[test]
+fn gray_value_too_wide() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+    let err = writer.write_gray(3, 8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "write_gray");
+    assert_eq!(err.offset(), Some(3));
+    assert_eq!(err.value(), Some(8));
+
+    // Nothing was written, and the widest values still fit.
+    writer.write_gray(3, 7).unwrap();
+    writer.write_gray(64, u64::MAX).unwrap();
+    writer.flush().unwrap();
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    assert_eq!(reader.read_bits(3).unwrap(), 0);
+    assert_eq!(reader.read_gray(3).unwrap(), 7);
+    assert_eq!(reader.read_gray(64).unwrap(), u64::MAX);
+}
+
+fn fixed_bits(int_bits: u8, frac_bits: u8, signed: bool, value: f64, overflow: Overflow) -> u64 {
+    let nbits = int_bits + frac_bits;
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(
+        writer
+            .write_fixed(int_bits, frac_bits, signed, value, overflow)
+            .unwrap(),
+        nbits as usize
+    );
+    writer.flush().unwrap();
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    reader.read_bits(nbits).unwrap()
+}
+
+#[test]
+fn fixed_exact_roundtrip() {
+    let mut rng = rand::thread_rng();
+    let layouts = [
+        (4, 12, true),
+        (4, 12, false),
+        (1, 15, true),
+        (0, 8, false),
+        (8, 0, true),
+        (16, 16, true),
+        (1, 52, true),
+        (40, 13, false),
+    ];
+    for &(int_bits, frac_bits, signed) in &layouts {
+        let nbits = int_bits + frac_bits;
+        let mut writer = BitWriter::new(Vec::new());
+        let mut values = Vec::new();
+        for _ in 0..200 {
+            let raw = rng.gen::<u64>() >> (64 - nbits);
+            let raw = if signed {
+                ((raw << (64 - nbits)) as i64 >> (64 - nbits)) as f64
+            } else {
+                raw as f64
+            };
+            let value = raw / (1u64 << frac_bits) as f64;
+            writer
+                .write_fixed(int_bits, frac_bits, signed, value, Overflow::Reject)
+                .unwrap();
+            values.push(value);
+        }
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+        for &value in &values {
+            assert_eq!(
+                reader.read_fixed(int_bits, frac_bits, signed).unwrap(),
+                value
+            );
+        }
+    }
+}
+
+#[test]
+fn fixed_layout() {
+    assert_eq!(fixed_bits(4, 12, true, 1.0, Overflow::Reject), 0x1000);
+    assert_eq!(fixed_bits(4, 12, true, -1.0, Overflow::Reject), 0xf000);
+    assert_eq!(fixed_bits(4, 12, true, -0.5, Overflow::Reject), 0xf800);
+    assert_eq!(
+        fixed_bits(4, 12, true, 7.999755859375, Overflow::Reject),
+        0x7fff
+    );
+    assert_eq!(fixed_bits(4, 12, true, -8.0, Overflow::Reject), 0x8000);
+    assert_eq!(fixed_bits(4, 12, false, 15.5, Overflow::Reject), 0xf800);
+    assert_eq!(fixed_bits(4, 12, true, -0.0, Overflow::Reject), 0);
+
+    let mut reader = BitReader::new(Cursor::new([0x80, 0x00, 0x7f, 0xff]));
+    assert_eq!(reader.read_fixed(4, 12, true).unwrap(), -8.0);
+    assert_eq!(reader.read_fixed(4, 12, false).unwrap(), 7.999755859375);
+}
+
+#[test]
+fn fixed_rounds_half_even() {
+    // Q4.4 steps are 1/16, so 1/32 is exactly half way.
+    let step = 1.0 / 16.0;
+    let half = step / 2.0;
+    for &(value, expected) in &[
+        (half, 0),
+        (3.0 * half, 2),
+        (5.0 * half, 2),
+        (7.0 * half, 4),
+        (half + 1e-9, 1),
+        (half - 1e-9, 0),
+        (1.0 + half, 16),
+        (1.0 + step + half, 18),
+    ] {
+        assert_eq!(
+            fixed_bits(4, 4, true, value, Overflow::Reject),
+            expected,
+            "{}",
+            value
+        );
+        assert_eq!(
+            fixed_bits(4, 4, true, -value, Overflow::Reject),
+            (-(expected as i64) as u64) & 0xff,
+            "{}",
+            -value
+        );
+    }
+
+    // Rounding up may overflow, while rounding to even doesn't.
+    let max = 8.0 - step;
+    assert_eq!(fixed_bits(4, 4, true, max + half, Overflow::Saturate), 0x7f);
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer
+        .write_fixed(4, 4, true, max + half, Overflow::Reject)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+        fixed_bits(4, 4, true, max - step + half, Overflow::Reject),
+        0x7e
+    );
+    assert_eq!(fixed_bits(4, 4, true, -8.0 - half, Overflow::Reject), 0x80);
+}
+
+#[test]
+fn fixed_saturates() {
+    let cases = [
+        (4, 12, true, 8.0, 0x7fff),
+        (4, 12, true, 1e300, 0x7fff),
+        (4, 12, true, f64::INFINITY, 0x7fff),
+        (4, 12, true, -8.001, 0x8000),
+        (4, 12, true, f64::NEG_INFINITY, 0x8000),
+        (4, 12, false, 16.0, 0xffff),
+        (4, 12, false, -1.0, 0),
+        (4, 12, false, f64::NEG_INFINITY, 0),
+        (64, 0, true, 1e19, i64::MAX as u64),
+        (64, 0, true, -1e19, 1 << 63),
+        (64, 0, false, 1e20, u64::MAX),
+        (32, 32, true, -1e10, 1 << 63),
+    ];
+    for &(int_bits, frac_bits, signed, value, expected) in &cases {
+        assert_eq!(
+            fixed_bits(int_bits, frac_bits, signed, value, Overflow::Saturate),
+            expected,
+            "{}",
+            value
+        );
+
+        let mut writer = BitWriter::new(Vec::new());
+        let err = writer
+            .write_fixed(int_bits, frac_bits, signed, value, Overflow::Reject)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = bitrw::Error::from_io(&err).unwrap();
+        assert_eq!(err.value(), Some(value.to_bits()));
+        assert_eq!(writer.position(), 0);
+    }
+
+    // The largest values which fit are fine either way.
+    assert_eq!(
+        fixed_bits(64, 0, false, 18446744073709549568.0, Overflow::Reject),
+        0xffff_ffff_ffff_f800
+    );
+    assert_eq!(
+        fixed_bits(64, 0, true, -9223372036854775808.0, Overflow::Reject),
+        1 << 63
+    );
+}
+
+#[test]
+fn fixed_rejects_nan() {
+    let mut writer = BitWriter::new(Vec::new());
+    for &overflow in &[Overflow::Saturate, Overflow::Reject] {
+        let err = writer
+            .write_fixed(4, 12, true, f64::NAN, overflow)
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+}
+
+#[test]
+fn try_read_into_widths() {
+    assert_eq!(bits_of(1, 1).try_read_into::<u16>(1).unwrap(), 1);
+    assert_eq!(
+        bits_of(16, 0xfedc).try_read_into::<u16>(16).unwrap(),
+        0xfedc
+    );
+    assert_eq!(bits_of(7, 0x7f).try_read_into::<u8>(7).unwrap(), 0x7f);
+    assert_eq!(
+        bits_of(64, u64::MAX).try_read_into::<u64>(64).unwrap(),
+        u64::MAX
+    );
+    assert_eq!(bits_of(8, 0).try_read_into::<u8>(0).unwrap(), 0);
+
+    // Unsigned reads into a signed type zero-extend.
+    assert_eq!(
+        bits_of(15, 0x7fff).try_read_into::<i16>(15).unwrap(),
+        0x7fff
+    );
+    assert_eq!(bits_of(16, 0xffff).try_read_into::<i16>(16).unwrap(), -1);
+}
+
+#[test]
+fn try_read_signed_into_i16() {
+    assert_eq!(bits_of(1, 0).try_read_signed_into::<i16>(1).unwrap(), 0);
+    assert_eq!(bits_of(1, 1).try_read_signed_into::<i16>(1).unwrap(), -1);
+
+    assert_eq!(
+        bits_of(16, 0x7fff).try_read_signed_into::<i16>(16).unwrap(),
+        i16::MAX
+    );
+    assert_eq!(
+        bits_of(16, 0x8000).try_read_signed_into::<i16>(16).unwrap(),
+        i16::MIN
+    );
+    assert_eq!(
+        bits_of(16, 0xffff).try_read_signed_into::<i16>(16).unwrap(),
+        -1
+    );
+
+    let mut rng = rand::thread_rng();
+    for nbits in 1..=16u8 {
+        let value = rng.gen_range(-(1i32 << (nbits - 1)), 1 << (nbits - 1)) as i16;
+        let bits = (value as u16 as u64) & ((1 << nbits) - 1);
+        assert_eq!(
+            bits_of(nbits, bits)
+                .try_read_signed_into::<i16>(nbits)
+                .unwrap(),
+            value
+        );
+    }
+
+    assert_eq!(
+        bits_of(64, 1 << 63)
+            .try_read_signed_into::<i64>(64)
+            .unwrap(),
+        i64::MIN
+    );
+    assert_eq!(
+        bits_of(33, 1 << 32)
+            .try_read_signed_into::<i64>(33)
+            .unwrap(),
+        -(1 << 32)
+    );
+}
+
+#[test]
+fn try_read_into_too_wide() {
+    let mut reader = bits_of(24, 0xabcdef);
+
+    let err = reader.try_read_signed_into::<i16>(17).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "try_read_signed_into failed at bit 0: field wider than i16 (value 0x11)"
+    );
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(17));
+
+    let err = reader.try_read_into::<u8>(9).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(
+        err.to_string(),
+        "try_read_into failed at bit 0: field wider than u8 (value 0x9)"
+    );
+
+    // Nothing was consumed.
+    assert_eq!(reader.position(), 0);
+    assert_eq!(reader.try_read_into::<u32>(24).unwrap(), 0xabcdef);
+}
+
+#[test]
+fn try_read_into_past_end() {
+    let mut reader = bits_of(8, 0xab);
+    let err = reader.try_read_signed_into::<i16>(12).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(
+        bitrw::Error::from_io(&err).unwrap().op(),
+        "try_read_signed_into"
+    );
+}
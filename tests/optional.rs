@@ -0,0 +1,114 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+#[derive(Debug, PartialEq)]
+struct Extension {
+    id: u64,
+    payload: Option<Option<u64>>,
+}
+
+fn write_extension<W: BitWrite>(w: &mut W, ext: &Extension) -> io::Result<usize> {
+    let mut written = w.write_bits(4, ext.id)?;
+    written += w.write_optional(ext.payload.as_ref(), |w, inner| {
+        w.write_optional(inner.as_ref(), |w, &value| w.write_bits(12, value))
+    })?;
+    Ok(written)
+}
+
+fn read_extension<R: BitRead>(r: &mut R) -> io::Result<Extension> {
+    Ok(Extension {
+        id: r.read_bits(4)?,
+        payload: r.read_optional(|r| r.read_optional(|r| r.read_bits(12)))?,
+    })
+}
+
+#[test]
+fn optional_roundtrip_nested() {
+    let records = [
+        None,
+        Some(Extension {
+            id: 3,
+            payload: None,
+        }),
+        Some(Extension {
+            id: 9,
+            payload: Some(None),
+        }),
+        Some(Extension {
+            id: 15,
+            payload: Some(Some(0xabc)),
+        }),
+    ];
+
+    let mut writer = BitWriter::new(Vec::new());
+    let mut total = 0;
+    for record in &records {
+        total += writer
+            .write_optional(record.as_ref(), write_extension)
+            .unwrap();
+    }
+    // 1 + (1 + 4 + 1) + (1 + 4 + 2) + (1 + 4 + 2 + 12)
+    assert_eq!(total, 33);
+    assert_eq!(writer.position(), 33);
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    for record in &records {
+        assert_eq!(&reader.read_optional(read_extension).unwrap(), record);
+    }
+    assert_eq!(reader.position(), 33);
+}
+
+#[test]
+fn optional_layout() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(
+        writer
+            .write_optional(Some(&0x5), |w, &v| w.write_bits(3, v))
+            .unwrap(),
+        4
+    );
+    assert_eq!(
+        writer
+            .write_optional(None::<&u64>, |w, &v| w.write_bits(3, v))
+            .unwrap(),
+        1
+    );
+    assert_eq!(
+        writer
+            .write_optional(Some(&0x2), |w, &v| w.write_bits(3, v))
+            .unwrap(),
+        4
+    );
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b1101_0101, 0b0000_0000]);
+}
+
+#[test]
+fn optional_error_in_closure() {
+    let mut reader = BitReader::new(Cursor::new([0b1110_1000]));
+    reader.read_bits(2).unwrap();
+
+    // The flag is set, but the value runs past the end.
+    let err = reader.read_optional(|r| r.read_bits(12)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_optional");
+    assert_eq!(err.offset(), Some(2));
+
+    // The flag was consumed, but the failed read consumed nothing.
+    assert_eq!(reader.position(), 3);
+    assert_eq!(reader.read_bits(5).unwrap(), 0b01000);
+}
+
+#[test]
+fn optional_error_reading_flag() {
+    let mut reader = BitReader::new(Cursor::new([0xff]));
+    reader.read_bits(8).unwrap();
+    let err = reader.read_optional(|r| r.read_bits(1)).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 8);
+}
@@ -0,0 +1,91 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::Cursor;
+
+use bitrw::{BitReader, BitWriter, Lsb0, Msb0};
+
+#[test]
+fn first_bit_placement() {
+    let mut msb = BitWriter::new(Vec::new());
+    msb.write_bits(3, 0b110).unwrap();
+    msb.write_bits(5, 0b00001).unwrap();
+    msb.write_bit(1).unwrap();
+    assert_eq!(msb.flush().unwrap(), 7);
+    assert_eq!(msb.into_inner(), [0b1100_0001, 0b1000_0000]);
+
+    let mut lsb = BitWriter::with_order(Vec::new(), Lsb0);
+    lsb.write_bits(3, 0b110).unwrap();
+    lsb.write_bits(5, 0b00001).unwrap();
+    lsb.write_bit(1).unwrap();
+    assert_eq!(lsb.flush().unwrap(), 7);
+    assert_eq!(lsb.into_inner(), [0b0000_1110, 0b0000_0001]);
+}
+
+#[test]
+fn lsb0_random_roundtrip() {
+    let mut rng = rand::thread_rng();
+
+    for len in 1..64 {
+        let ios: Vec<(u8, u64)> = (0..len)
+            .map(|_| (rng.gen::<u8>() % 65, rng.gen::<u64>()))
+            .collect();
+
+        let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+        for &(nbits, value) in &ios {
+            writer.write_bits(nbits, value).unwrap();
+        }
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+        for &(nbits, value) in &ios {
+            let mask = if nbits == 64 { !0 } else { (1 << nbits) - 1 };
+            assert_eq!(reader.read_bits(nbits).unwrap(), value & mask);
+        }
+    }
+}
+
+#[test]
+fn orders_disagree_on_same_bytes() {
+    let data = vec![0b1000_0001, 0b0011_1100];
+
+    let mut msb = BitReader::with_order(Cursor::new(data.clone()), Msb0);
+    assert_eq!(msb.read_bits(3).unwrap(), 0b100);
+    assert_eq!(msb.read_bits(9).unwrap(), 0b0_0001_0011);
+
+    let mut lsb = BitReader::with_order(Cursor::new(data), Lsb0);
+    assert_eq!(lsb.read_bits(3).unwrap(), 0b001);
+    assert_eq!(lsb.read_bits(9).unwrap(), 0b1_1001_0000);
+}
+
+// RFC 1951 section 3.2.3: BFINAL is the first bit, BTYPE the next two, both
+// packed starting from the least-significant bit of each byte.
+#[test]
+fn deflate_stored_block_header() {
+    // A final stored block containing "hello".
+    let data = vec![0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o'];
+    let mut reader = BitReader::with_order(Cursor::new(data), Lsb0);
+
+    assert_eq!(reader.read_bit().unwrap(), 1); // BFINAL
+    assert_eq!(reader.read_bits(2).unwrap(), 0b00); // BTYPE: stored
+    assert_eq!(reader.read_bits(5).unwrap(), 0); // skip to byte boundary
+    assert_eq!(reader.read_bits(16).unwrap(), 5); // LEN
+    assert_eq!(reader.read_bits(16).unwrap(), !5 & 0xffff); // NLEN
+
+    let mut text = Vec::new();
+    for _ in 0..5 {
+        text.push(reader.read_bits(8).unwrap() as u8);
+    }
+    assert_eq!(text, b"hello");
+}
+
+#[test]
+fn deflate_fixed_huffman_empty_block() {
+    // zlib's raw deflate output for an empty input.
+    let mut reader = BitReader::with_order(Cursor::new(vec![0x03, 0x00]), Lsb0);
+
+    assert_eq!(reader.read_bit().unwrap(), 1); // BFINAL
+    assert_eq!(reader.read_bits(2).unwrap(), 0b01); // BTYPE: fixed Huffman
+    assert_eq!(reader.read_bits(7).unwrap(), 0); // end-of-block, code 256
+}
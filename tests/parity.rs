@@ -0,0 +1,153 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, Lsb0, Parity, ParityBitReader, ParityBitWriter};
+
+fn encode(parity: Parity, block_size: u32, fields: &[(u8, u64)]) -> Vec<u8> {
+    let mut writer = ParityBitWriter::new(BitWriter::new(Vec::new()), parity, block_size);
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.end_block().unwrap();
+    writer.flush().unwrap();
+    writer.into_inner().into_inner()
+}
+
+#[test]
+fn uart_bytes() {
+    // 8E1 without the start and stop bits: each byte then its parity.
+    let encoded = encode(Parity::Even, 8, &[(8, 0x41), (8, 0x43)]);
+    assert_eq!(encoded, [0x41, 0b0010_0001, 0b1100_0000]);
+
+    let encoded = encode(Parity::Odd, 8, &[(8, 0x41), (8, 0x43)]);
+    assert_eq!(encoded, [0x41, 0b1010_0001, 0b1000_0000]);
+
+    let mut writer = ParityBitWriter::new(BitWriter::new(Vec::new()), Parity::Even, 8);
+    writer.write_bits(12, 0xfff).unwrap();
+    assert_eq!(writer.position(), 12);
+    assert_eq!(writer.blocks(), 1);
+    assert_eq!(writer.get_ref().position(), 13);
+}
+
+#[test]
+fn roundtrip() {
+    let mut rng = rand::thread_rng();
+    for &parity in &[Parity::Even, Parity::Odd] {
+        for &block_size in &[1, 7, 8, 9, 64, 100] {
+            let fields: Vec<(u8, u64)> = (0..200)
+                .map(|_| {
+                    let nbits = rng.gen_range(0, 65);
+                    let value = if nbits == 64 {
+                        rng.gen::<u64>()
+                    } else {
+                        rng.gen::<u64>() & ((1 << nbits) - 1)
+                    };
+                    (nbits, value)
+                })
+                .collect();
+            let encoded = encode(parity, block_size, &fields);
+            let total: u64 = fields.iter().map(|&(n, _)| n as u64).sum();
+            let blocks = total.div_ceil(block_size as u64);
+            assert_eq!(encoded.len() as u64, (total + blocks).div_ceil(8));
+
+            let reader = BitReader::new(&encoded[..]);
+            let mut reader = ParityBitReader::new(reader, parity, block_size);
+            for &(nbits, value) in &fields {
+                assert_eq!(reader.read_bits(nbits).unwrap(), value);
+            }
+            reader.end_block().unwrap();
+            assert_eq!(reader.position(), total);
+            assert_eq!(reader.blocks(), blocks);
+        }
+    }
+}
+
+#[test]
+fn roundtrip_lsb0() {
+    let writer = BitWriter::with_order(Vec::new(), Lsb0);
+    let mut writer = ParityBitWriter::new(writer, Parity::Odd, 5);
+    writer.write_bits(13, 0x1abc).unwrap();
+    writer.write_bits(40, 0x12_3456_789a).unwrap();
+    writer.end_block().unwrap();
+    writer.flush().unwrap();
+    let encoded = writer.into_inner().into_inner();
+
+    let reader = BitReader::with_order(Cursor::new(encoded), Lsb0);
+    let mut reader = ParityBitReader::new(reader, Parity::Odd, 5);
+    assert_eq!(reader.read_bits(13).unwrap(), 0x1abc);
+    assert_eq!(reader.read_bits(40).unwrap(), 0x12_3456_789a);
+    assert!(reader.end_block().unwrap());
+}
+
+#[test]
+fn corrupted_bit() {
+    let data: Vec<(u8, u64)> = (0..4).map(|i| (8, 0x30 + i)).collect();
+    let encoded = encode(Parity::Even, 8, &data);
+
+    // Flip a data bit in each block in turn.
+    for block in 0..4u64 {
+        let mut corrupt = encoded.clone();
+        let bit = block * 9 + 3;
+        corrupt[bit as usize / 8] ^= 0x80 >> (bit % 8);
+
+        let reader = BitReader::new(&corrupt[..]);
+        let mut reader = ParityBitReader::new(reader, Parity::Even, 8);
+        for i in 0..block {
+            assert_eq!(reader.read_bits(8).unwrap(), 0x30 + i);
+        }
+        let err = reader.read_bits(8).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = bitrw::Error::from_io(&err).unwrap();
+        assert_eq!(err.offset(), Some(block * 9 + 8));
+        assert_eq!(err.value(), Some(block));
+
+        // The bad block's data is still there, and reading carries on.
+        assert_eq!(reader.read_bits(8).unwrap(), (0x30 + block) ^ 0x10);
+        for i in block + 1..4 {
+            assert_eq!(reader.read_bits(8).unwrap(), 0x30 + i);
+        }
+    }
+
+    // A flipped parity bit is caught the same way.
+    let mut corrupt = encoded.clone();
+    corrupt[1] ^= 0x80;
+    let mut reader = ParityBitReader::new(BitReader::new(&corrupt[..]), Parity::Even, 8);
+    let err = reader
+        .read_bits(4)
+        .and_then(|_| reader.read_bits(4))
+        .unwrap_err();
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(0));
+}
+
+#[test]
+fn truncated_block() {
+    // One whole block, then seven data bits of the next.
+    let data = [0x41, 0b0010_0001, 0b1100_0000];
+    let mut reader = ParityBitReader::new(BitReader::new(&data[..2]), Parity::Even, 8);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x41);
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(7));
+    assert_eq!(err.offset(), Some(16));
+    // The pending bits can be read without their parity.
+    assert_eq!(reader.read_bits(7).unwrap(), 0x43 >> 1);
+
+    // A block missing only its parity bit.
+    let data = [0x41];
+    let mut reader = ParityBitReader::new(BitReader::new(&data[..]), Parity::Even, 8);
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(8));
+
+    // Running out on a block boundary is a plain end of stream.
+    let data = [0b1000_0010];
+    let mut reader = ParityBitReader::new(BitReader::new(&data[..]), Parity::Even, 7);
+    assert_eq!(reader.read_bits(7).unwrap(), 0x41);
+    assert_eq!(reader.blocks(), 1);
+    let err = reader.read_bits(7).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).and_then(|e| e.value()), None);
+}
@@ -0,0 +1,171 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{
+    BitReader, BitReaderBuilder, BitWriter, BitWriterBuilder, Lsb0, ReaderParts, WriterParts,
+};
+
+#[test]
+fn reader_round_trip_mid_byte() {
+    let data: Vec<u8> = (0..64u8).map(|i| i.wrapping_mul(37)).collect();
+    let widths = [3, 13, 1, 29, 64, 7, 5, 40, 22];
+
+    let mut expected = BitReader::new(&data[..]);
+    let expected: Vec<u64> = widths
+        .iter()
+        .map(|&nbits| expected.read_bits(nbits).unwrap())
+        .collect();
+
+    for split in 0..widths.len() {
+        let mut reader = BitReader::new(&data[..]);
+        for &nbits in &widths[..split] {
+            reader.read_bits(nbits).unwrap();
+        }
+        let position = reader.position();
+        let (inner, parts) = reader.into_parts();
+        assert_eq!(parts.position(), position);
+        assert_eq!(parts.pending().0 as u64, (8 - position % 8) % 8);
+
+        let mut reader = BitReader::<_>::from_parts(inner, parts);
+        for (i, &nbits) in widths.iter().enumerate().skip(split) {
+            assert_eq!(reader.read_bits(nbits).unwrap(), expected[i]);
+        }
+    }
+}
+
+#[test]
+fn reader_round_trip_unread_lsb0() {
+    let data = [0x5a, 0xc3, 0x96, 0x0f, 0xf0, 0x81, 0x7e, 0x24, 0x42, 0x99];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    reader.read_bits(5).unwrap();
+    let a = reader.read_bits(64).unwrap();
+    let b = reader.read_bits(6).unwrap();
+    reader.unread_bits(6, b).unwrap();
+    reader.unread_bits(64, a).unwrap();
+
+    let (inner, parts) = reader.into_parts();
+    assert_eq!(parts.position(), 5);
+    assert_eq!(parts.pending().0, 75);
+
+    let mut reader = BitReader::<_, Lsb0>::from_parts(inner, parts);
+    assert_eq!(reader.read_bits(64).unwrap(), a);
+    assert_eq!(reader.read_bits(6).unwrap(), b);
+    let mut expected = BitReader::with_order(&data[..], Lsb0);
+    expected.read_bits(64).unwrap();
+    expected.read_bits(11).unwrap();
+    assert_eq!(reader.read_bits(5).unwrap(), expected.read_bits(5).unwrap());
+}
+
+#[test]
+fn reader_keeps_limit() {
+    let data = [0xff; 4];
+    let mut reader = BitReaderBuilder::new().limit_bits(12).build(&data[..]);
+    reader.read_bits(3).unwrap();
+    let (inner, parts) = reader.into_parts();
+    assert_eq!(parts.limit(), Some(12));
+
+    let mut reader = BitReader::<_>::from_parts(inner, parts);
+    assert_eq!(reader.read_bits(9).unwrap(), 0x1ff);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn reader_from_constructed_parts() {
+    // Three bits of a byte already taken from the inner reader.
+    let parts = ReaderParts::new(13, 3, 0b101).unwrap().with_limit(Some(24));
+    let mut reader = BitReader::<_>::from_parts(&[0xc0, 0xff][..], parts);
+    assert_eq!(reader.read_bits(5).unwrap(), 0b10111);
+    assert_eq!(reader.position(), 18);
+    assert_eq!(reader.read_bits(6).unwrap(), 0);
+    assert!(reader.read_bits(1).is_err());
+
+    let err = ReaderParts::new(0, 3, 0b1000).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = ReaderParts::new(0, 128, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
+
+fn random_fields(n: usize) -> Vec<(u8, u64)> {
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|_| {
+            let nbits = rng.gen_range(1, 65);
+            let value = rng.gen::<u64>() >> (64 - nbits);
+            (nbits, value)
+        })
+        .collect()
+}
+
+#[test]
+fn writer_round_trip_mid_byte() {
+    let fields = random_fields(200);
+    let mut expected = BitWriterBuilder::new()
+        .pad_with_ones(true)
+        .build(Vec::new());
+    for &(nbits, value) in &fields {
+        expected.write_bits(nbits, value).unwrap();
+    }
+    expected.flush().unwrap();
+    let expected = expected.into_inner();
+
+    for &capacity in &[0, 7, 64] {
+        let mut writer = BitWriterBuilder::new()
+            .pad_with_ones(true)
+            .capacity(capacity)
+            .build(Vec::new());
+        for (i, &(nbits, value)) in fields.iter().enumerate() {
+            if i % 17 == 5 {
+                let position = writer.position();
+                let (inner, parts) = writer.into_parts();
+                assert_eq!(parts.position(), position);
+                assert_eq!(parts.pending().0 as u64, position % 8);
+                assert_eq!(parts.capacity(), capacity);
+                assert!(parts.pad_ones());
+                writer = BitWriter::from_parts(inner, parts);
+            }
+            writer.write_bits(nbits, value).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(writer.into_inner(), expected);
+    }
+}
+
+#[test]
+fn writer_batched_bytes() {
+    let mut writer = BitWriterBuilder::new().capacity(16).build(Vec::new());
+    writer.write_bits(20, 0xabcde).unwrap();
+    let (inner, parts) = writer.into_parts();
+    assert!(inner.is_empty());
+    assert_eq!(parts.batched(), [0xab, 0xcd]);
+    assert_eq!(parts.pending(), (4, 0xe));
+
+    let mut writer = BitWriter::<_>::from_parts(inner, parts);
+    writer.write_bits(4, 0xf).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xab, 0xcd, 0xef]);
+}
+
+#[test]
+fn writer_from_constructed_parts() {
+    // Carry on after two whole bytes and three bits of a third.
+    let parts = WriterParts::new(19, 0b011)
+        .unwrap()
+        .with_limit(Some(32))
+        .with_pad_ones(true);
+    let mut writer = BitWriter::<_>::from_parts(vec![0x12, 0x34], parts);
+    writer.write_bits(9, 0).unwrap();
+    assert_eq!(writer.position(), 28);
+    let err = writer.write_bits(5, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x12, 0x34, 0x60, 0x0f]);
+
+    let err = WriterParts::new(19, 0b1000).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = WriterParts::new(16, 1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+}
@@ -0,0 +1,207 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Read};
+
+use bitrw::{BitOrder, BitReader, BitReaderBuilder, BitWriter, BitWriterBuilder, Lsb0, Msb0};
+
+fn random_samples(bit_depth: u8, count: usize) -> Vec<i32> {
+    let mut rng = rand::thread_rng();
+    let min = -(1i64 << (bit_depth - 1));
+    let max = (1i64 << (bit_depth - 1)) - 1;
+    let mut samples: Vec<i32> = (0..count)
+        .map(|_| rng.gen_range(min, max + 1) as i32)
+        .collect();
+    samples[0] = min as i32;
+    samples[1] = max as i32;
+    samples
+}
+
+/// The samples as a `read_bits()` and sign extension for each would read them.
+fn naive<O: BitOrder>(data: &[u8], skip: u8, bit_depth: u8, count: usize) -> Vec<i32> {
+    let mut reader = BitReader::with_order(data, O::default());
+    reader.read_bits(skip).unwrap();
+    let shift = 64 - bit_depth;
+    (0..count)
+        .map(|_| ((reader.read_bits(bit_depth).unwrap() << shift) as i64 >> shift) as i32)
+        .collect()
+}
+
+fn round_trip<O: BitOrder>() {
+    for bit_depth in 1..=32 {
+        for &skip in &[0, 3] {
+            let samples = random_samples(bit_depth, 1000);
+            let mut writer = BitWriter::with_order(Vec::new(), O::default());
+            writer.write_bits(skip, 0b101).unwrap();
+            let written = writer.write_samples_i32(bit_depth, &samples).unwrap();
+            assert_eq!(written, bit_depth as usize * samples.len());
+            writer.write_bits(5, 0b10011).unwrap();
+            writer.flush().unwrap();
+            let data = writer.into_inner();
+
+            assert_eq!(naive::<O>(&data, skip, bit_depth, samples.len()), samples);
+
+            let mut reader = BitReader::with_order(&data[..], O::default());
+            reader.read_bits(skip).unwrap();
+            let mut out = vec![0; samples.len()];
+            reader.read_samples_i32(bit_depth, &mut out).unwrap();
+            assert_eq!(out, samples, "at {} bits", bit_depth);
+            assert_eq!(reader.read_bits(5).unwrap(), 0b10011);
+        }
+    }
+}
+
+#[test]
+fn round_trip_msb0() {
+    round_trip::<Msb0>();
+}
+
+#[test]
+fn round_trip_lsb0() {
+    round_trip::<Lsb0>();
+}
+
+#[test]
+fn twenty_four_bit() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_samples_i32(24, &[0x123456, -1, -0x800000])
+        .unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        [0x12, 0x34, 0x56, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00]
+    );
+}
+
+#[test]
+fn one_bit() {
+    let mut reader = BitReader::new(&[0b1010_0000][..]);
+    let mut out = [7; 4];
+    reader.read_samples_i32(1, &mut out).unwrap();
+    assert_eq!(out, [-1, 0, -1, 0]);
+
+    let mut writer = BitWriter::new(Vec::new());
+    assert!(writer.write_samples_i32(1, &[1]).is_err());
+}
+
+#[test]
+fn empty() {
+    let mut reader = BitReader::new(&[][..]);
+    reader.read_samples_i32(20, &mut []).unwrap();
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_samples_i32(20, &[]).unwrap(), 0);
+    assert!(writer.into_inner().is_empty());
+}
+
+/// Hands out a byte at a time, interrupting every other read.
+struct Trickle<'a> {
+    data: &'a [u8],
+    interrupt: bool,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::ErrorKind::Interrupted.into());
+        }
+        let n = buf.len().min(1).min(self.data.len());
+        buf[..n].copy_from_slice(&self.data[..n]);
+        self.data = &self.data[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn short_reads() {
+    let samples = random_samples(20, 300);
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_samples_i32(20, &samples).unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = BitReader::new(Trickle {
+        data: &data,
+        interrupt: false,
+    });
+    let mut out = vec![0; samples.len()];
+    reader.read_samples_i32(20, &mut out).unwrap();
+    assert_eq!(out, samples);
+}
+
+#[test]
+fn after_unread() {
+    let mut reader = BitReader::new(&[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xd0][..]);
+    assert_eq!(reader.read_bits(20).unwrap(), 0x12345);
+    reader.unread_bits(20, 0x12345).unwrap();
+    // The first sample comes out of the buffered bits, and the second starts
+    // in what's left of them.
+    let mut out = [0; 3];
+    reader.read_samples_i32(16, &mut out).unwrap();
+    assert_eq!(out, [0x1234, 0x5678, 0x9abc - 0x10000]);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xd);
+}
+
+#[test]
+fn end_of_stream() {
+    let samples = random_samples(12, 10);
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_samples_i32(12, &samples).unwrap();
+    let data = writer.into_inner();
+
+    // Cut part way through the eighth sample.
+    let mut reader = BitReader::new(&data[..11]);
+    let mut out = vec![0; samples.len()];
+    let err = reader.read_samples_i32(12, &mut out).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(7));
+    assert_eq!(err.offset(), Some(7 * 12));
+    assert_eq!(out[..7], samples[..7]);
+    assert_eq!(reader.position(), 7 * 12);
+    assert_eq!(reader.read_bits(4).unwrap(), samples[7] as u64 >> 8 & 0xf);
+}
+
+#[test]
+fn limits() {
+    let mut reader = BitReaderBuilder::new().limit_bits(40).build(&[0; 16][..]);
+    let mut out = [0; 4];
+    let err = reader.read_samples_i32(12, &mut out).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.position(), 0);
+    reader.read_samples_i32(10, &mut out).unwrap();
+
+    let mut writer = BitWriterBuilder::new().limit_bits(40).build(Vec::new());
+    let err = writer.write_samples_i32(12, &[0; 4]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 0);
+    writer.write_samples_i32(10, &[0; 4]).unwrap();
+}
+
+#[test]
+fn out_of_range() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+    let err = writer
+        .write_samples_i32(16, &[0, -32768, 32767, 32768, -40000])
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(3));
+    assert_eq!(err.offset(), Some(3));
+    assert_eq!(writer.position(), 3);
+
+    writer.write_samples_i32(32, &[i32::MIN, i32::MAX]).unwrap();
+}
+
+#[test]
+#[should_panic]
+fn zero_depth() {
+    let _ = BitReader::new(&[0][..]).read_samples_i32(0, &mut [0]);
+}
+
+#[test]
+#[should_panic]
+fn too_deep() {
+    let _ = BitWriter::new(Vec::new()).write_samples_i32(33, &[0]);
+}
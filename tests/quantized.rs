@@ -0,0 +1,130 @@
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter};
+
+fn round_trip(value: f32, min: f32, max: f32, nbits: u8) -> f32 {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(
+        writer.write_quantized(value, min, max, nbits).unwrap(),
+        nbits as usize
+    );
+    writer.flush().unwrap();
+    let bytes = writer.into_inner();
+    BitReader::new(&bytes[..])
+        .read_quantized(min, max, nbits)
+        .unwrap()
+}
+
+#[test]
+fn error_within_half_a_step() {
+    for &(min, max) in &[(0.0f32, 1.0f32), (-180.0, 180.0), (-0.001, 1000.0)] {
+        for nbits in 1..=24 {
+            let step = (max as f64 - min as f64) / ((1u64 << nbits) - 1) as f64;
+            for i in 0..=1000 {
+                let value = min + (max - min) * (i as f32 / 1000.0);
+                let value = value.min(max);
+                let error = (round_trip(value, min, max, nbits) as f64 - value as f64).abs();
+                // Allow for the f32 result rounding off a little more.
+                let bound = step / 2.0 + (max.abs().max(min.abs()) as f64) * 1e-7;
+                assert!(
+                    error <= bound,
+                    "{} in [{}, {}] at {} bits came back {} out",
+                    value,
+                    min,
+                    max,
+                    nbits,
+                    error
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn endpoints_exact() {
+    for nbits in 1..=32 {
+        assert_eq!(round_trip(-1.5, -1.5, 7.25, nbits), -1.5);
+        assert_eq!(round_trip(7.25, -1.5, 7.25, nbits), 7.25);
+    }
+}
+
+#[test]
+fn codes() {
+    let mut writer = BitWriter::new(Vec::new());
+    for &value in &[0.0, 0.2, 0.5, 1.0] {
+        writer.write_quantized(value, 0.0, 1.0, 2).unwrap();
+    }
+    // 0.2 of 3 steps is 0.6, so 1; 0.5 is 1.5, which ties to 2.
+    assert_eq!(writer.into_inner(), [0b00_01_10_11]);
+}
+
+#[test]
+fn clamped() {
+    assert_eq!(round_trip(-1e30, 0.0, 10.0, 8), 0.0);
+    assert_eq!(round_trip(11.0, 0.0, 10.0, 8), 10.0);
+    assert_eq!(round_trip(f32::INFINITY, 0.0, 10.0, 8), 10.0);
+    assert_eq!(round_trip(f32::NEG_INFINITY, 0.0, 10.0, 8), 0.0);
+}
+
+#[test]
+fn one_bit() {
+    assert_eq!(round_trip(0.49, 0.0, 1.0, 1), 0.0);
+    assert_eq!(round_trip(0.51, 0.0, 1.0, 1), 1.0);
+    // A tie goes to the even code.
+    assert_eq!(round_trip(0.5, 0.0, 1.0, 1), 0.0);
+}
+
+#[test]
+fn zero_bits() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_quantized(0.7, 0.0, 1.0, 0).unwrap(), 0);
+    assert_eq!(writer.position(), 0);
+
+    let mut reader = BitReader::new(&[][..]);
+    assert_eq!(reader.read_quantized(0.25, 1.0, 0).unwrap(), 0.25);
+}
+
+#[test]
+fn empty_range() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_quantized(3.0, 2.0, 2.0, 8).unwrap();
+    assert_eq!(writer.into_inner(), [0]);
+
+    let mut reader = BitReader::new(&[0xff][..]);
+    assert_eq!(reader.read_quantized(2.0, 2.0, 8).unwrap(), 2.0);
+}
+
+#[test]
+fn nan() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0).unwrap();
+    let err = writer.write_quantized(f32::NAN, 0.0, 1.0, 8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(3));
+    assert_eq!(writer.position(), 3);
+}
+
+#[test]
+fn short_read() {
+    let mut reader = BitReader::new(&[0xff][..]);
+    reader.read_bits(4).unwrap();
+    let err = reader.read_quantized(0.0, 1.0, 8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(4));
+}
+
+#[test]
+#[should_panic]
+fn inverted_range() {
+    let mut writer = BitWriter::new(Vec::new());
+    let _ = writer.write_quantized(0.5, 1.0, 0.0, 8);
+}
+
+#[test]
+#[should_panic]
+fn too_wide() {
+    let mut reader = BitReader::new(&[0u8; 8][..]);
+    let _ = reader.read_quantized(0.0, 1.0, 33);
+}
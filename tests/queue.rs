@@ -0,0 +1,119 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::collections::VecDeque;
+use std::io;
+
+use bitrw::{BitQueue, BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+fn mask(nbits: u8, value: u64) -> u64 {
+    if nbits == 64 {
+        value
+    } else {
+        value & ((1 << nbits) - 1)
+    }
+}
+
+/// Check the queue against a queue of single bits.
+#[test]
+fn fifo_order() {
+    let mut rng = rand::thread_rng();
+    let mut queue = BitQueue::new();
+    let mut model = VecDeque::new();
+
+    for _ in 0..5000 {
+        if rng.gen::<bool>() {
+            let nbits = rng.gen_range(0, 65);
+            let value = rng.gen::<u64>();
+            queue.push_bits(nbits, value);
+            model.extend((0..nbits).rev().map(|i| (value >> i) as u8 & 1));
+        } else if rng.gen_range(0, 4) == 0 {
+            let mut out = vec![0; rng.gen_range(0, 20)];
+            let n = queue.pop_bytes(&mut out);
+            assert_eq!(n, out.len().min(model.len() / 8));
+            for &byte in &out[..n] {
+                let expected = model.drain(..8).fold(0, |acc, bit| acc << 1 | bit);
+                assert_eq!(byte, expected);
+            }
+        } else {
+            let nbits = rng.gen_range(0, 65);
+            if model.len() < nbits as usize {
+                assert_eq!(queue.pop_bits(nbits), None);
+                continue;
+            }
+            let expected = model
+                .drain(..nbits as usize)
+                .fold(0u64, |acc, bit| acc << 1 | bit as u64);
+            assert_eq!(queue.pop_bits(nbits), Some(expected));
+        }
+        assert_eq!(queue.len_bits(), model.len() as u64);
+    }
+}
+
+#[test]
+fn mismatched_widths() {
+    let mut queue = BitQueue::new();
+    queue.push_bits(3, 0b110);
+    queue.push_bits(7, 0b1010101);
+    assert_eq!(queue.pop_bits(5), Some(0b11010));
+    queue.push_bytes(&[0xf0, 0x0f]);
+    queue.push_bits(1, 1);
+    assert_eq!(queue.len_bits(), 22);
+    assert_eq!(queue.pop_bits(9), Some(0b1_0101_1111));
+    assert_eq!(queue.pop_bits(12), Some(0b0000_0000_1111));
+    assert_eq!(queue.pop_bits(2), None);
+    assert_eq!(queue.pop_bits(1), Some(1));
+    assert!(queue.is_empty());
+
+    queue.push_bits(12, 0xabc);
+    let mut out = [0; 4];
+    assert_eq!(queue.pop_bytes(&mut out), 1);
+    assert_eq!(out[0], 0xab);
+    assert_eq!(queue.len_bits(), 4);
+    queue.clear();
+    assert_eq!(queue.pop_bits(1), None);
+}
+
+#[test]
+fn same_packing_as_writer() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..300)
+        .map(|_| {
+            let nbits = rng.gen_range(0, 65);
+            (nbits, mask(nbits, rng.gen::<u64>()))
+        })
+        .collect();
+
+    let mut queue = BitQueue::with_order(Lsb0);
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    for &(nbits, value) in &fields {
+        queue.write_bits(nbits, value).unwrap();
+        writer.write_bits(nbits, value).unwrap();
+    }
+    let total = queue.len_bits();
+    let mut bytes = vec![0; total as usize / 8];
+    assert_eq!(queue.pop_bytes(&mut bytes), bytes.len());
+    writer.flush().unwrap();
+    let written = writer.into_inner();
+    assert_eq!(bytes, written[..bytes.len()]);
+
+    let mut queue = BitQueue::with_order(Lsb0);
+    queue.push_bytes(&written);
+    let mut reader = BitReader::with_order(&written[..], Lsb0);
+    for &(nbits, value) in &fields {
+        assert_eq!(queue.read_bits(nbits).unwrap(), value);
+        assert_eq!(reader.read_bits(nbits).unwrap(), value);
+    }
+}
+
+#[test]
+fn bit_read_write() {
+    let mut queue = BitQueue::new();
+    queue.write_gamma(9).unwrap();
+    queue.write::<u16>(0xbeef).unwrap();
+    assert_eq!(queue.read_gamma().unwrap(), 9);
+    assert_eq!(queue.read::<u16>().unwrap(), 0xbeef);
+    let err = queue.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,205 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{Read, Write};
+
+use bitrw::{BitRead, BitWrite, RbspBitReader, RbspBitWriter, RbspSink, RbspSource};
+
+fn unescape(nal: &[u8]) -> (Vec<u8>, u64) {
+    let mut source = RbspSource::new(nal);
+    let mut rbsp = Vec::new();
+    source.read_to_end(&mut rbsp).unwrap();
+    (rbsp, source.removed())
+}
+
+#[test]
+fn removes_emulation_prevention() {
+    assert_eq!(
+        unescape(&[0x00, 0x00, 0x03, 0x00]),
+        (vec![0x00, 0x00, 0x00], 1)
+    );
+    // Only the first 0x03 follows two zeros.
+    assert_eq!(
+        unescape(&[0x00, 0x00, 0x03, 0x03]),
+        (vec![0x00, 0x00, 0x03], 1)
+    );
+    // Removing a 0x03 resets the zero count.
+    assert_eq!(
+        unescape(&[0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x01]),
+        (vec![0x00, 0x00, 0x00, 0x00, 0x01], 2)
+    );
+    assert_eq!(
+        unescape(&[0x00, 0x00, 0x00, 0x03, 0x02]),
+        (vec![0x00, 0x00, 0x00, 0x02], 1)
+    );
+}
+
+#[test]
+fn preserves_other_threes() {
+    let nal = [0x03, 0x00, 0x03, 0x01, 0x00, 0x03, 0x03, 0x00, 0x00, 0x04];
+    assert_eq!(unescape(&nal), (nal.to_vec(), 0));
+    // A trailing 0x03 after two zeros is removed too, as in cabac_zero_words.
+    assert_eq!(
+        unescape(&[0x80, 0x00, 0x00, 0x03]),
+        (vec![0x80, 0x00, 0x00], 1)
+    );
+}
+
+#[test]
+fn bits_straddle_removed_bytes() {
+    // The RBSP is 00 00 03 00 00 01 80.
+    let nal = [0x00, 0x00, 0x03, 0x03, 0x00, 0x00, 0x03, 0x01, 0x80];
+    let mut reader = RbspBitReader::new(&nal[..]);
+    assert_eq!(reader.read_bits(12).unwrap(), 0);
+    assert_eq!(reader.read_bits(24).unwrap(), 0x3000);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x001);
+    assert_eq!(reader.get_ref().removed(), 2);
+    assert!(!reader.more_rbsp_data().unwrap());
+    assert!(reader.byte_aligned());
+    assert_eq!(reader.read_bit().unwrap(), 1);
+    assert_eq!(reader.position(), 49);
+}
+
+#[test]
+fn more_rbsp_data_trailing_bits() {
+    // ue(v) 3 = 00100, then the stop bit and alignment.
+    let mut reader = RbspBitReader::new(&[0b0010_0100][..]);
+    assert!(reader.more_rbsp_data().unwrap());
+    assert_eq!(reader.read_gamma().unwrap() - 1, 3);
+    assert!(!reader.more_rbsp_data().unwrap());
+    assert_eq!(reader.read_bits(3).unwrap(), 0b100);
+    assert!(!reader.more_rbsp_data().unwrap());
+
+    // Data finishing on a byte boundary, with trailing zero bytes (cabac_zero_words)
+    // after the stop bit.
+    let nal = [0xab, 0x80, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03];
+    let mut reader = RbspBitReader::new(&nal[..]);
+    assert!(reader.more_rbsp_data().unwrap());
+    assert_eq!(reader.read_bits(8).unwrap(), 0xab);
+    assert!(!reader.more_rbsp_data().unwrap());
+    // The bytes read ahead are still delivered.
+    assert_eq!(reader.read_bits(8).unwrap(), 0x80);
+    assert_eq!(reader.read_bits(32).unwrap(), 0);
+    assert!(reader.read_bit().is_err());
+    assert!(!reader.more_rbsp_data().unwrap());
+
+    // A one bit beyond a run of zeros is more data, and the zeros are kept.
+    let nal = [0x80, 0x00, 0x00, 0x03, 0x00, 0x01];
+    let mut reader = RbspBitReader::new(&nal[..]);
+    assert_eq!(reader.read_bits(4).unwrap(), 0b1000);
+    assert!(reader.more_rbsp_data().unwrap());
+    assert_eq!(reader.read_bits(36).unwrap(), 1);
+
+    let mut reader = RbspBitReader::new(&[][..]);
+    assert!(!reader.more_rbsp_data().unwrap());
+}
+
+fn escape(rbsp: &[&[u8]]) -> (Vec<u8>, u64) {
+    let mut sink = RbspSink::new(Vec::new());
+    for piece in rbsp {
+        sink.write_all(piece).unwrap();
+    }
+    let inserted = sink.inserted();
+    (sink.into_inner(), inserted)
+}
+
+#[test]
+fn inserts_emulation_prevention() {
+    assert_eq!(
+        escape(&[&[0x00, 0x00, 0x00, 0x00, 0x00, 0x01]]),
+        (vec![0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x01], 2)
+    );
+    assert_eq!(
+        escape(&[&[0x00, 0x00, 0x02, 0x00, 0x00, 0x03]]),
+        (vec![0x00, 0x00, 0x03, 0x02, 0x00, 0x00, 0x03, 0x03], 2)
+    );
+    // Larger bytes after two zeros are left alone.
+    assert_eq!(
+        escape(&[&[0x00, 0x00, 0x04, 0x00, 0x00, 0xff]]),
+        (vec![0x00, 0x00, 0x04, 0x00, 0x00, 0xff], 0)
+    );
+    // The zero count carries across writes.
+    assert_eq!(
+        escape(&[&[0x01, 0x00], &[], &[0x00], &[0x01, 0x00]]),
+        (vec![0x01, 0x00, 0x00, 0x03, 0x01, 0x00], 1)
+    );
+}
+
+#[test]
+fn insertion_straddling_flush() {
+    let mut writer = RbspBitWriter::new(Vec::new());
+    writer.write_bits(16, 0).unwrap();
+    writer.write_bits(4, 0).unwrap();
+    // The padded byte is a third zero, so needs escaping.
+    assert_eq!(writer.flush_bits().unwrap(), 4);
+    assert_eq!(writer.get_ref().get_ref()[..], [0x00, 0x00, 0x03, 0x00]);
+
+    // The zero after the escape counts towards the next one.
+    writer.write_bits(12, 0x000).unwrap();
+    writer.write_bits(4, 0x2).unwrap();
+    writer.write_rbsp_trailing_bits().unwrap();
+    assert_eq!(writer.position(), 48);
+    assert_eq!(writer.get_ref().inserted(), 2);
+    assert_eq!(
+        writer.finish().unwrap(),
+        [0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x02, 0x80]
+    );
+}
+
+#[test]
+fn final_zero_byte() {
+    // An RBSP ending in a cabac_zero_word gets a final 0x03.
+    let mut writer = RbspBitWriter::new(Vec::new());
+    writer.write_bits(8, 0xab).unwrap();
+    writer.write_rbsp_trailing_bits().unwrap();
+    writer.write_bits(16, 0).unwrap();
+    assert_eq!(writer.finish().unwrap(), [0xab, 0x80, 0x00, 0x00, 0x03]);
+}
+
+#[test]
+fn roundtrip_through_reader() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        // Mostly zero fields, to provoke plenty of escapes.
+        let fields: Vec<(u8, u64)> = (0..rng.gen_range(1, 200))
+            .map(|_| {
+                let width = rng.gen_range(1, 33);
+                let value = if rng.gen_range(0, 4) == 0 {
+                    rng.gen::<u64>() >> (64 - width)
+                } else {
+                    rng.gen_range(0, 4) & ((1 << width) - 1)
+                };
+                (width, value)
+            })
+            .collect();
+
+        let mut writer = RbspBitWriter::new(Vec::new());
+        for &(width, value) in &fields {
+            writer.write_bits(width, value).unwrap();
+        }
+        writer.write_rbsp_trailing_bits().unwrap();
+        let position = writer.position();
+        let nal = writer.finish().unwrap();
+        for window in nal.windows(3) {
+            assert!(
+                window[0] != 0 || window[1] != 0 || window[2] > 0x02,
+                "{:?}",
+                nal
+            );
+        }
+
+        let mut reader = RbspBitReader::new(&nal[..]);
+        for &(width, value) in &fields {
+            assert!(reader.more_rbsp_data().unwrap());
+            assert_eq!(reader.read_bits(width).unwrap(), value);
+        }
+        assert!(!reader.more_rbsp_data().unwrap());
+        assert_eq!(reader.read_bit().unwrap(), 1);
+        while !reader.byte_aligned() {
+            assert_eq!(reader.read_bit().unwrap(), 0);
+        }
+        assert_eq!(reader.position(), position);
+        assert!(reader.read_bit().is_err());
+    }
+}
@@ -0,0 +1,93 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitReaderBuilder, Lsb0};
+
+#[test]
+fn read_many_matches_read_bits() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..500 {
+        let data: Vec<u8> = (0..rng.gen_range(0, 64)).map(|_| rng.gen::<u8>()).collect();
+        let widths: Vec<u8> = (0..rng.gen_range(0, 20))
+            .map(|_| rng.gen_range(0, 65))
+            .collect();
+        let limit = if rng.gen() {
+            Some(rng.gen_range(0, data.len() as u64 * 8 + 1))
+        } else {
+            None
+        };
+        let build = || {
+            let builder = BitReaderBuilder::new().bit_order(Lsb0);
+            match limit {
+                Some(limit) => builder.limit_bits(limit),
+                None => builder,
+            }
+            .build(Cursor::new(&data[..]))
+        };
+
+        let mut sequential = build();
+        let mut expected = Vec::new();
+        let mut failure = None;
+        for (index, &nbits) in widths.iter().enumerate() {
+            let start = sequential.position();
+            match sequential.read_bits(nbits) {
+                Ok(value) => expected.push(value),
+                Err(e) => {
+                    failure = Some((index, start, e.kind()));
+                    break;
+                }
+            }
+        }
+
+        let mut batched = build();
+        let mut out = vec![u64::MAX; widths.len()];
+        match batched.read_many(&widths, &mut out) {
+            Ok(()) => {
+                assert!(failure.is_none());
+                assert_eq!(out, expected);
+            }
+            Err(e) => {
+                let (index, start, kind) = failure.expect("read_many failed");
+                assert_eq!(e.kind(), kind);
+                let err = bitrw::Error::from_io(&e).unwrap();
+                assert_eq!(err.op(), "read_many");
+                assert_eq!(err.value(), Some(index as u64));
+                assert_eq!(err.offset(), Some(start));
+                assert_eq!(&out[..index], &expected[..]);
+            }
+        }
+        assert_eq!(batched.position(), sequential.position());
+
+        // Whatever's left reads the same either way.
+        let rest = (data.len() as u64 * 8 - batched.position()).min(64) as u8;
+        assert_eq!(
+            batched.read_bits(rest).ok(),
+            sequential.read_bits(rest).ok()
+        );
+    }
+}
+
+#[test]
+fn read_many_record() {
+    let mut reader = BitReader::new(Cursor::new([0xab, 0xcd, 0xef, 0x12]));
+    let mut fields = [0; 4];
+    reader.read_many(&[4, 12, 1, 15], &mut fields).unwrap();
+    assert_eq!(fields, [0xa, 0xbcd, 1, 0x6f12]);
+
+    let mut reader = BitReader::new(Cursor::new([0xab, 0xcd]));
+    let err = reader.read_many(&[4, 8, 8], &mut fields[..3]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(2));
+    assert_eq!(&fields[..2], [0xa, 0xbc]);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xd);
+}
+
+#[test]
+#[should_panic]
+fn read_many_mismatched_lengths() {
+    let mut reader = BitReader::new(Cursor::new([0; 8]));
+    let _ = reader.read_many(&[1, 2, 3], &mut [0; 2]);
+}
@@ -0,0 +1,66 @@
+extern crate bitrw;
+
+use bitrw::{BitReader, BitWriter, Lsb0};
+
+#[test]
+fn boundary_widths() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_n::<8>(0xa5u8).unwrap();
+    writer.write_n::<9>(0x1ffu16).unwrap();
+    writer.write_n::<16>(0xbeefu16).unwrap();
+    writer.write_n::<17>(0x1_2345u32).unwrap();
+    writer.write_n::<32>(0xdead_beefu32).unwrap();
+    writer.write_n::<33>(0x1_cafe_f00du64).unwrap();
+    writer.write_n::<64>(u64::MAX - 1).unwrap();
+    writer.write_n::<1>(1u8).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut expected = BitWriter::new(Vec::new());
+    for &(nbits, value) in &[
+        (8, 0xa5),
+        (9, 0x1ff),
+        (16, 0xbeef),
+        (17, 0x1_2345),
+        (32, 0xdead_beef),
+        (33, 0x1_cafe_f00d),
+        (64, u64::MAX - 1),
+        (1, 1),
+    ] {
+        expected.write_bits(nbits, value).unwrap();
+    }
+    expected.flush().unwrap();
+    assert_eq!(data, expected.into_inner());
+
+    let mut reader = BitReader::new(&data[..]);
+    let a: u8 = reader.read_n::<8>().unwrap();
+    let b: u16 = reader.read_n::<9>().unwrap();
+    let c: u16 = reader.read_n::<16>().unwrap();
+    let d: u32 = reader.read_n::<17>().unwrap();
+    let e: u32 = reader.read_n::<32>().unwrap();
+    let f: u64 = reader.read_n::<33>().unwrap();
+    let g: u64 = reader.read_n::<64>().unwrap();
+    let h: u8 = reader.read_n::<1>().unwrap();
+    assert_eq!(
+        (a, b, c, d, e, f, g, h),
+        (
+            0xa5,
+            0x1ff,
+            0xbeef,
+            0x1_2345,
+            0xdead_beef,
+            0x1_cafe_f00d,
+            u64::MAX - 1,
+            1
+        )
+    );
+    assert!(reader.read_n::<8>().is_err());
+}
+
+#[test]
+fn high_bits_ignored() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_n::<12>(0xffffu16).unwrap();
+    writer.write_n::<4>(0).unwrap();
+    assert_eq!(writer.into_inner(), [0xff, 0x0f]);
+}
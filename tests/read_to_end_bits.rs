@@ -0,0 +1,106 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter, Lsb0};
+
+#[test]
+fn from_each_phase() {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..3000).map(|_| rng.gen()).collect();
+    for phase in 0..16 {
+        let mut reader = BitReader::new(&data[..]);
+        reader.skip_bits(phase).unwrap();
+        let mut tail = vec![0x55];
+        let nbits = reader.read_to_end_bits(&mut tail).unwrap();
+        assert_eq!(nbits, data.len() as u64 * 8 - phase);
+        assert_eq!(reader.position(), data.len() as u64 * 8);
+        assert_eq!(tail.len(), 1 + (nbits as usize).div_ceil(8));
+        assert_eq!(tail[0], 0x55);
+
+        // Each bit is the one at the same offset from the phase.
+        let mut expected = BitReader::new(&data[..]);
+        expected.skip_bits(phase).unwrap();
+        let mut got = BitReader::new(&tail[1..]);
+        for _ in 0..nbits {
+            assert_eq!(got.read_bit().unwrap(), expected.read_bit().unwrap());
+        }
+        // Padded with zeros.
+        while got.read_bit().map(|bit| assert_eq!(bit, 0)).is_ok() {}
+    }
+}
+
+#[test]
+fn re_emitted() {
+    let data = [0x12, 0x34, 0x56, 0x78];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    let head = reader.read_bits(5).unwrap();
+    let mut tail = Vec::new();
+    let nbits = reader.read_to_end_bits(&mut tail).unwrap();
+    assert_eq!(nbits, 27);
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(5, head).unwrap();
+    let whole = nbits as usize / 8;
+    writer.write_bytes(&tail[..whole]).unwrap();
+    writer
+        .write_bits(nbits as u8 % 8, tail[whole] as u64)
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), data);
+}
+
+#[test]
+fn at_eof() {
+    let mut reader = BitReader::new(&[0xff][..]);
+    reader.read_bits(8).unwrap();
+    let mut tail = Vec::new();
+    assert_eq!(reader.read_to_end_bits(&mut tail).unwrap(), 0);
+    assert!(tail.is_empty());
+
+    let mut reader = BitReader::new(&[][..]);
+    assert_eq!(reader.read_to_end_bits(&mut tail).unwrap(), 0);
+    assert!(tail.is_empty());
+}
+
+#[test]
+fn stops_at_limit() {
+    let data = [0xff; 8];
+    for limit in 0..64 {
+        for phase in 0..=limit.min(9) {
+            let mut reader = BitReaderBuilder::new().limit_bits(limit).build(&data[..]);
+            reader.skip_bits(phase).unwrap();
+            let mut tail = Vec::new();
+            assert_eq!(reader.read_to_end_bits(&mut tail).unwrap(), limit - phase);
+            assert_eq!(reader.position(), limit);
+            assert_eq!(tail.len(), ((limit - phase) as usize).div_ceil(8));
+            let ones: u32 = tail.iter().map(|b| b.count_ones()).sum();
+            assert_eq!(ones as u64, limit - phase);
+        }
+    }
+}
+
+#[test]
+fn error_keeps_bits_read() {
+    struct Failing<'a>(&'a [u8]);
+    impl io::Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::other("broken"));
+            }
+            let n = self.0.len().min(buf.len()).min(1);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    let mut reader = BitReader::new(Failing(&[0xab, 0xcd]));
+    reader.read_bits(4).unwrap();
+    let mut tail = Vec::new();
+    assert!(reader.read_to_end_bits(&mut tail).is_err());
+    assert_eq!(tail, [0xbc]);
+    assert_eq!(reader.position(), 12);
+}
@@ -0,0 +1,166 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Read};
+
+use bitrw::{BitReader, BitReaderBuilder, Lsb0};
+
+/// The bytes of `data` from bit `phase`, reading `Msb0`, and the bits left
+/// over at the end.
+fn shifted_msb0(data: &[u8], phase: u8) -> (Vec<u8>, u8) {
+    let bits: Vec<u8> = data
+        .iter()
+        .flat_map(|&byte| (0..8).rev().map(move |i| (byte >> i) & 1))
+        .skip(phase as usize)
+        .collect();
+    let bytes = bits
+        .chunks_exact(8)
+        .map(|bits| bits.iter().fold(0, |acc, &bit| acc << 1 | bit))
+        .collect();
+    (bytes, (bits.len() % 8) as u8)
+}
+
+/// As for `shifted_msb0()`, reading `Lsb0`.
+fn shifted_lsb0(data: &[u8], phase: u8) -> (Vec<u8>, u8) {
+    let bits: Vec<u8> = data
+        .iter()
+        .flat_map(|&byte| (0..8).map(move |i| (byte >> i) & 1))
+        .skip(phase as usize)
+        .collect();
+    let bytes = bits
+        .chunks_exact(8)
+        .map(|bits| bits.iter().rev().fold(0, |acc, &bit| acc << 1 | bit))
+        .collect();
+    (bytes, (bits.len() % 8) as u8)
+}
+
+fn random_data(len: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn every_phase_msb0() {
+    for &len in &[0, 1, 2, 7, 8, 9, 17, 1000, 5000] {
+        let data = random_data(len);
+        for phase in 0..if len == 0 { 1 } else { 8 } {
+            let (expected, left) = shifted_msb0(&data, phase);
+            let mut reader = BitReader::new(&data[..]);
+            reader.read_bits(phase).unwrap();
+            let mut out = Vec::new();
+            reader.realigned_bytes().read_to_end(&mut out).unwrap();
+            assert_eq!(out, expected, "{} bytes from bit {}", len, phase);
+            assert_eq!(reader.position(), phase as u64 + out.len() as u64 * 8);
+            assert_eq!(
+                reader.read_bits(left).unwrap(),
+                data.last()
+                    .map_or(0, |&last| last as u64 & ((1 << left) - 1))
+            );
+            assert!(reader.read_bit().is_err());
+        }
+    }
+}
+
+#[test]
+fn every_phase_lsb0() {
+    for &len in &[1, 9, 1000] {
+        let data = random_data(len);
+        for phase in 0..8 {
+            let (expected, left) = shifted_lsb0(&data, phase);
+            let mut reader = BitReader::with_order(&data[..], Lsb0);
+            reader.read_bits(phase).unwrap();
+            let mut out = Vec::new();
+            reader.realigned_bytes().read_to_end(&mut out).unwrap();
+            assert_eq!(out, expected, "{} bytes from bit {}", len, phase);
+            assert_eq!(
+                reader.read_bits(left).unwrap(),
+                data[len - 1] as u64 >> (8 - left)
+            );
+        }
+    }
+}
+
+#[test]
+fn iterator() {
+    let data = random_data(100);
+    for phase in 0..8 {
+        let (expected, _) = shifted_msb0(&data, phase);
+        let mut reader = BitReader::new(&data[..]);
+        reader.read_bits(phase).unwrap();
+        let out: Vec<u8> = reader.realigned_bytes().collect::<io::Result<_>>().unwrap();
+        assert_eq!(out, expected);
+    }
+}
+
+#[test]
+fn small_reads_then_bits() {
+    let data = random_data(64);
+    let (expected, _) = shifted_msb0(&data, 5);
+    let mut reader = BitReader::new(&data[..]);
+    reader.read_bits(5).unwrap();
+
+    let mut out = vec![0; 3];
+    reader.realigned_bytes().read_exact(&mut out).unwrap();
+    assert_eq!(out, expected[..3]);
+    // Bits can be read between, at the same phase.
+    assert_eq!(reader.read_bits(8).unwrap(), expected[3] as u64);
+    let mut bytes = reader.realigned_bytes();
+    assert_eq!(bytes.next().unwrap().unwrap(), expected[4]);
+    assert_eq!(bytes.get_ref().position(), 5 + 5 * 8);
+    let mut rest = Vec::new();
+    bytes.read_to_end(&mut rest).unwrap();
+    assert_eq!(rest, expected[5..]);
+}
+
+#[test]
+fn buffered_bytes() {
+    // Bits pushed back come out first, whole bytes at a time.
+    let mut reader = BitReader::new(&[0x12, 0x34, 0x56][..]);
+    assert_eq!(reader.read_bits(20).unwrap(), 0x12345);
+    reader.unread_bits(17, 0x12345 & 0x1ffff).unwrap();
+    let mut out = Vec::new();
+    reader.realigned_bytes().read_to_end(&mut out).unwrap();
+    assert_eq!(out, [0x91, 0xa2]);
+    assert_eq!(reader.read_bits(5).unwrap(), 0b1_0110);
+}
+
+#[test]
+fn limits() {
+    let data = random_data(32);
+    let (expected, _) = shifted_msb0(&data, 3);
+    // The last whole byte ends at bit 83, four bits before the limit.
+    let mut reader = BitReaderBuilder::new().limit_bits(87).build(&data[..]);
+    reader.read_bits(3).unwrap();
+    let mut out = Vec::new();
+    reader.realigned_bytes().read_to_end(&mut out).unwrap();
+    assert_eq!(out, expected[..10]);
+    assert_eq!(reader.position(), 83);
+    reader.read_bits(4).unwrap();
+    assert!(reader.read_bit().is_err());
+}
+
+/// Fails once its data runs out.
+struct Failing<'a>(&'a [u8]);
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::other("broken"));
+        }
+        self.0.read(buf)
+    }
+}
+
+#[test]
+fn errors() {
+    let data = [0xff; 4];
+    let mut reader = BitReader::new(Failing(&data));
+    reader.read_bits(2).unwrap();
+    let mut out = Vec::new();
+    let err = reader.realigned_bytes().read_to_end(&mut out).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+    assert_eq!(out, [0xff; 3]);
+    assert_eq!(reader.position(), 2 + 3 * 8);
+    assert_eq!(reader.read_bits(2).unwrap(), 3);
+}
@@ -0,0 +1,79 @@
+extern crate bitrw;
+
+use std::io::{Cursor, Seek, SeekFrom};
+
+use bitrw::{BitReader, Lsb0, ReaderParts};
+
+fn data() -> Vec<u8> {
+    (0..100u8).map(|i| i.wrapping_mul(37)).collect()
+}
+
+#[test]
+fn after_construction() {
+    let mut reader = BitReader::new(Cursor::new(data()));
+    assert_eq!(reader.len_bits().unwrap(), 800);
+    assert_eq!(reader.remaining_bits().unwrap(), 800);
+
+    let mut reader = BitReader::new(Cursor::new(Vec::new()));
+    assert_eq!(reader.len_bits().unwrap(), 0);
+    assert_eq!(reader.remaining_bits().unwrap(), 0);
+}
+
+#[test]
+fn after_unaligned_reads() {
+    let data = data();
+    let mut reader = BitReader::new(Cursor::new(data.clone()));
+    let mut check = BitReader::new(&data[..]);
+    let mut read = 0;
+    for nbits in (1..=64).cycle().take(20) {
+        assert_eq!(reader.remaining_bits().unwrap(), 800 - read);
+        assert_eq!(
+            reader.read_bits(nbits).unwrap(),
+            check.read_bits(nbits).unwrap()
+        );
+        read += nbits as u64;
+    }
+    assert_eq!(reader.remaining_bits().unwrap(), 800 - read);
+    assert_eq!(reader.len_bits().unwrap(), 800);
+    assert_eq!(reader.position(), read);
+
+    // Reading carries on where it was.
+    let rest = 800 - read;
+    for _ in 0..rest {
+        assert_eq!(reader.read_bit().unwrap(), check.read_bit().unwrap());
+    }
+    assert_eq!(reader.remaining_bits().unwrap(), 0);
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn after_seeks() {
+    let mut reader = BitReader::with_order(Cursor::new(data()), Lsb0);
+    for &pos in &[0, 13, 799, 800, 8, 64, 1] {
+        reader.seek(SeekFrom::Start(pos)).unwrap();
+        assert_eq!(reader.remaining_bits().unwrap(), 800 - pos, "at {}", pos);
+    }
+    reader.seek(SeekFrom::End(-8)).unwrap();
+    assert_eq!(reader.remaining_bits().unwrap(), 8);
+}
+
+#[test]
+fn counts_from_the_inner_stream() {
+    // Created part way through the stream, position() counts from there.
+    let mut cursor = Cursor::new(data());
+    cursor.seek(SeekFrom::Start(90)).unwrap();
+    let mut reader = BitReader::new(cursor);
+    reader.read_bits(3).unwrap();
+    assert_eq!(reader.position(), 3);
+    assert_eq!(reader.remaining_bits().unwrap(), 77);
+    assert_eq!(reader.len_bits().unwrap(), 800);
+}
+
+#[test]
+fn respects_limit() {
+    let parts = ReaderParts::new(0, 0, 0).unwrap().with_limit(Some(100));
+    let mut reader = BitReader::<_>::from_parts(Cursor::new(data()), parts);
+    reader.read_bits(30).unwrap();
+    assert_eq!(reader.remaining_bits().unwrap(), 70);
+    assert_eq!(reader.len_bits().unwrap(), 800);
+}
@@ -0,0 +1,86 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor, Read, SeekFrom};
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter};
+
+fn encode(fields: &[(u8, u64)]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for &(nbits, value) in fields {
+        writer.write_bits(nbits, value).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn split_across_cursors() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..100)
+        .map(|_| {
+            let nbits = rng.gen_range(1, 65);
+            (nbits, rng.gen::<u64>() >> (64 - nbits))
+        })
+        .collect();
+    let data = encode(&fields);
+
+    for split in 1..data.len() {
+        let (first, second) = data.split_at(split);
+        let mut reader = BitReader::new(Cursor::new(first.to_vec()));
+        let mut swapped = false;
+        for &(nbits, value) in &fields {
+            // Swap as soon as the first segment can't supply the next field,
+            // with the rest of it buffered.
+            let left = (split as u64 * 8).saturating_sub(reader.position());
+            if !swapped && nbits as u64 > left {
+                let tail = reader.read_bits(left as u8).unwrap();
+                reader.unread_bits(left as u8, tail).unwrap();
+                let old = reader.replace_inner(Cursor::new(second.to_vec()));
+                assert_eq!(old.position(), split as u64);
+                swapped = true;
+            }
+            assert_eq!(reader.read_bits(nbits).unwrap(), value);
+        }
+        assert!(swapped);
+    }
+}
+
+#[test]
+fn swap_mid_field() {
+    let data = encode(&[(5, 0b10110), (20, 0xabcde), (7, 0x55)]);
+    let mut reader = BitReader::new(Cursor::new(data[..2].to_vec()));
+    assert_eq!(reader.read_bits(5).unwrap(), 0b10110);
+    assert_eq!(reader.read_bits(11).unwrap(), 0xabcde >> 9);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    reader.replace_inner(Cursor::new(data[2..].to_vec()));
+    assert_eq!(reader.position(), 16);
+    assert_eq!(reader.read_bits(9).unwrap(), 0xabcde & 0x1ff);
+    assert_eq!(reader.read_bits(7).unwrap(), 0x55);
+
+    // Seeking is within the new reader alone.
+    assert_eq!(reader.seek(SeekFrom::Start(1)).unwrap(), 1);
+    assert_eq!(
+        reader.read_bits(8).unwrap(),
+        (data[2] as u64) << 1 & 0xff | (data[3] as u64) >> 7
+    );
+}
+
+#[test]
+fn map_inner_keeps_state() {
+    let data = encode(&[(3, 0b101), (16, 0x1234), (9, 0x1ff)]);
+    let mut reader = BitReaderBuilder::new().limit_bits(28).build(&data[..1]);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+
+    // Carry on over the rest of the data, via a different type of reader.
+    let rest = &data[1..];
+    let mut reader = reader.map_inner(|first: &[u8]| first.chain(rest));
+    assert_eq!(reader.position(), 3);
+    assert_eq!(reader.read_bits(16).unwrap(), 0x1234);
+    assert_eq!(reader.read_bits(9).unwrap(), 0x1ff);
+    let err = reader.read_bits(1).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,145 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitOrder, BitReader, BitWriter, Lsb0, Msb0, ReverseBitReader};
+
+#[test]
+fn fse_style_tail() {
+    // Three fields written LSB first and then an end marker:
+    //   byte 0: 1 0 0 1 1 1 0 1 = 0x9d, reading from bit 7 down to bit 0
+    //   byte 1: 0 0 1 0 1 1 0 1 = 0x2d
+    // Bits 0-2 are 0b101, 3-8 0b110011, 9-12 0b0110, 13 the marker.
+    let data = [0x9d, 0x2d];
+
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_bits(6, 0b110011).unwrap();
+    writer.write_bits(4, 0b0110).unwrap();
+    writer.write_bit(1).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), data);
+
+    let mut reader = ReverseBitReader::with_order(Cursor::new(&data[..]), Lsb0).unwrap();
+    assert_eq!(reader.bits_remaining(), 16);
+    assert_eq!(reader.skip_sentinel().unwrap(), 3);
+    assert_eq!(reader.bits_remaining(), 13);
+    assert_eq!(reader.read_bits_rev(4).unwrap(), 0b0110);
+    assert_eq!(reader.read_bits_rev(6).unwrap(), 0b110011);
+    assert_eq!(reader.read_bits_rev(3).unwrap(), 0b101);
+
+    let err = reader.read_bit_rev().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+fn roundtrip<O: BitOrder>(order: O) {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..500)
+        .map(|_| {
+            let width = rng.gen_range(0, 65);
+            let value = if width == 0 {
+                0
+            } else {
+                rng.gen::<u64>() >> (64 - width)
+            };
+            (width, value)
+        })
+        .collect();
+
+    let mut writer = BitWriter::with_order(Vec::new(), order);
+    for &(width, value) in &fields {
+        writer.write_bits(width, value).unwrap();
+    }
+    writer.write_bit(1).unwrap();
+    let padding = writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = ReverseBitReader::with_order(Cursor::new(&data[..]), order).unwrap();
+    assert_eq!(reader.skip_sentinel().unwrap() as usize, padding + 1);
+    for &(width, value) in fields.iter().rev() {
+        assert_eq!(
+            reader.read_bits_rev(width).unwrap(),
+            value,
+            "{} bits",
+            width
+        );
+    }
+    assert_eq!(reader.bits_remaining(), 0);
+}
+
+#[test]
+fn roundtrip_lsb0() {
+    roundtrip(Lsb0);
+}
+
+#[test]
+fn roundtrip_msb0() {
+    roundtrip(Msb0);
+}
+
+#[test]
+fn forward_and_reverse_share_buffer() {
+    // A forward header, then a backwards stream as in a Zstandard block.
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.write_bits(16, 0x1234).unwrap();
+    writer.write_bits(8, 0xff).unwrap();
+    for i in 0..20 {
+        writer.write_bits(7, i).unwrap();
+    }
+    writer.write_bit(1).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut forward = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+    let mut reverse =
+        ReverseBitReader::with_bounds(Cursor::new(&data[..]), 3, data.len() as u64, Lsb0);
+    reverse.skip_sentinel().unwrap();
+
+    assert_eq!(forward.read_bits(16).unwrap(), 0x1234);
+    for i in (10..20).rev() {
+        assert_eq!(reverse.read_bits_rev(7).unwrap(), i);
+    }
+    assert_eq!(forward.read_bits(8).unwrap(), 0xff);
+    // The forward reader can carry on into the region read backwards.
+    assert_eq!(forward.read_bits(7).unwrap(), 0);
+    for i in (0..10).rev() {
+        assert_eq!(reverse.read_bits_rev(7).unwrap(), i);
+    }
+
+    // The reverse reader stops at the start of its bounds.
+    assert_eq!(reverse.bits_remaining(), 0);
+    assert!(reverse.read_bit_rev().is_err());
+}
+
+#[test]
+fn short_reads_consume_nothing() {
+    let data = [0xab, 0xcd];
+    let mut reader = ReverseBitReader::new(Cursor::new(&data[..])).unwrap();
+    assert_eq!(reader.read_bits_rev(4).unwrap(), 0xd);
+    let err = reader.read_bits_rev(13).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_bits_rev");
+    assert_eq!(err.offset(), Some(12));
+    assert_eq!(reader.read_bits_rev(12).unwrap(), 0xabc);
+}
+
+#[test]
+fn missing_sentinel() {
+    let data = [0xff, 0x00];
+    let mut reader = ReverseBitReader::with_order(Cursor::new(&data[..]), Lsb0).unwrap();
+    let err = reader.skip_sentinel().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(reader.bits_remaining(), 16);
+
+    let mut reader = ReverseBitReader::new(Cursor::new(&[][..])).unwrap();
+    assert!(reader.skip_sentinel().is_err());
+
+    // Msb0 streams end at the low bit of the final byte.
+    let data = [0x05, 0x80];
+    let mut reader = ReverseBitReader::new(Cursor::new(&data[..])).unwrap();
+    assert_eq!(reader.skip_sentinel().unwrap(), 8);
+    assert_eq!(reader.read_bits_rev(3).unwrap(), 0b101);
+}
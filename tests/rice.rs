@@ -0,0 +1,125 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{rice_optimal_k, BitReader, BitWrite, BitWriter, BitWriterBuilder, Lsb0};
+
+/// Geometric values with the given mean.
+fn geometric(count: usize, mean: f64) -> Vec<u64> {
+    let mut rng = rand::thread_rng();
+    let p = 1.0 / (mean + 1.0);
+    (0..count)
+        .map(|_| {
+            let u: f64 = rng.gen_range(f64::EPSILON, 1.0);
+            (u.ln() / (1.0 - p).ln()).floor() as u64
+        })
+        .collect()
+}
+
+/// The bits taken by Rice codes of `values` with parameter `k`, by writing
+/// them out one by one.
+fn encoded_bits(values: &[u64], k: u8) -> u64 {
+    let mut writer = BitWriter::new(io::sink());
+    for &value in values {
+        writer.write_unary(value >> k).unwrap();
+        writer.write_bits(k, value).unwrap();
+    }
+    writer.position()
+}
+
+#[test]
+fn optimal_k_minimizes_bits() {
+    for &mean in &[0.0, 0.3, 1.0, 3.0, 20.0, 150.0, 1000.0, 40000.0] {
+        for &count in &[1, 7, 64, 1000] {
+            let values = geometric(count, mean);
+            let k = rice_optimal_k(&values);
+            let bits: Vec<u64> = (0..=30).map(|k| encoded_bits(&values, k)).collect();
+            let best = *bits.iter().min().unwrap();
+            assert_eq!(bits[k as usize], best, "mean {} count {}", mean, count);
+            // Ties go to the smallest parameter.
+            assert_eq!(
+                bits.iter().position(|&b| b == best),
+                Some(k as usize),
+                "mean {} count {}",
+                mean,
+                count
+            );
+        }
+    }
+}
+
+#[test]
+fn optimal_k_edges() {
+    assert_eq!(rice_optimal_k(&[]), 0);
+    assert_eq!(rice_optimal_k(&[0; 10]), 0);
+    assert_eq!(rice_optimal_k(&[1, 1]), 0);
+    assert_eq!(rice_optimal_k(&[u64::MAX]), 30);
+    assert_eq!(rice_optimal_k(&[1 << 20; 5]), 19);
+}
+
+#[test]
+fn block_round_trip() {
+    for &mean in &[0.0, 2.0, 90.0, 5e5] {
+        let values = geometric(500, mean);
+        let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+        writer.write_bits(3, 0b101).unwrap();
+        let (k, bits) = writer.write_rice_block(&values).unwrap();
+        assert_eq!(k, rice_optimal_k(&values));
+        assert_eq!(bits as u64, 5 + encoded_bits(&values, k));
+        assert_eq!(writer.position(), 3 + bits as u64);
+        writer.write_rice_block(&[7, 0, 1]).unwrap();
+        writer.flush().unwrap();
+        let data = writer.into_inner();
+
+        let mut reader = BitReader::with_order(&data[..], Lsb0);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        let mut decoded = vec![0; values.len()];
+        assert_eq!(reader.read_rice_block(&mut decoded).unwrap(), k);
+        assert_eq!(decoded, values);
+        let mut tail = [0; 3];
+        reader.read_rice_block(&mut tail).unwrap();
+        assert_eq!(tail, [7, 0, 1]);
+    }
+}
+
+#[test]
+fn empty_block() {
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_rice_block(&[]).unwrap(), (0, 5));
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data, [0]);
+    assert_eq!(
+        BitReader::new(&data[..]).read_rice_block(&mut []).unwrap(),
+        0
+    );
+}
+
+#[test]
+fn write_past_limit() {
+    let mut writer = BitWriterBuilder::new().limit_bits(20).build(Vec::new());
+    writer.write_bits(4, 0).unwrap();
+    // 5 header bits, then 0, 1 and 2 at k = 0 in 1, 2 and 3 bits.
+    let err = writer.write_rice_block(&[0, 1, 2, 0, 1, 2]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 4);
+    assert_eq!(writer.write_rice_block(&[0, 1, 2]).unwrap(), (0, 11));
+}
+
+#[test]
+fn read_errors() {
+    // A header of 31.
+    let mut reader = BitReader::new(&[0b1111_1000][..]);
+    let err = reader.read_rice_block(&mut [0]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(31));
+
+    // A block cut short keeps the values read before the end.
+    let mut values = [9; 3];
+    let mut reader = BitReader::new(&[0b0000_0101, 0b0000_0000][..]);
+    let err = reader.read_rice_block(&mut values).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(values, [0, 1, 9]);
+}
@@ -0,0 +1,207 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitReader, BitWriter, Lsb0, RleConfig, RleEnd, RunLength};
+
+fn configs() -> Vec<RleConfig> {
+    let mut configs = Vec::new();
+    for &length in &[
+        RunLength::Gamma,
+        RunLength::Fixed(1),
+        RunLength::Fixed(3),
+        RunLength::Fixed(12),
+    ] {
+        for &first in &[false, true] {
+            for &end in &[RleEnd::Count, RleEnd::Terminator] {
+                configs.push(RleConfig { length, first, end });
+            }
+        }
+    }
+    configs
+}
+
+fn bitmap(len: usize, density: f64) -> Vec<bool> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen::<f64>() < density).collect()
+}
+
+fn round_trip(bits: &[bool], cfg: RleConfig) -> usize {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    let written = writer.write_rle(bits.iter().copied(), cfg).unwrap();
+    writer.write_bits(5, 0b10011).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = BitReader::new(&data[..]);
+    reader.read_bits(3).unwrap();
+    let mut out = vec![true];
+    assert_eq!(
+        reader.read_rle(cfg, bits.len() as u64, &mut out).unwrap(),
+        bits.len() as u64,
+        "{:?}",
+        cfg
+    );
+    assert_eq!(&out[1..], bits, "{:?}", cfg);
+    assert_eq!(reader.position(), 3 + written as u64);
+    assert_eq!(reader.read_bits(5).unwrap(), 0b10011);
+    written
+}
+
+#[test]
+fn sparse_and_dense() {
+    for cfg in configs() {
+        let sparse = bitmap(10_000, 0.01);
+        let written = round_trip(&sparse, cfg);
+        if cfg.length == RunLength::Gamma {
+            assert!(written < 2_000, "{}", written);
+        }
+        round_trip(&bitmap(10_000, 0.99), cfg);
+        round_trip(&bitmap(1_000, 0.5), cfg);
+    }
+}
+
+#[test]
+fn edge_cases() {
+    let long = vec![true; 10_000];
+    let mut runs = vec![false; 4095];
+    runs.extend(vec![true; 8190]);
+    for cfg in configs() {
+        round_trip(&[], cfg);
+        round_trip(&[false], cfg);
+        round_trip(&[true], cfg);
+        round_trip(&long, cfg);
+        round_trip(&runs, cfg);
+    }
+}
+
+#[test]
+fn golden() {
+    let cfg = RleConfig {
+        length: RunLength::Fixed(2),
+        first: true,
+        end: RleEnd::Terminator,
+    };
+    // A zero-length initial run of ones, zeros split as 3 + 0 + 1, and the
+    // terminator: 00 11 00 01 11 00 00.
+    let bits = [false, false, false, false, true, true, true];
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(writer.write_rle(bits.iter().copied(), cfg).unwrap(), 14);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b0011_0001, 0b1100_0000]);
+
+    // Gamma codes are in stream order whatever the bit order.
+    let cfg = RleConfig {
+        length: RunLength::Gamma,
+        first: false,
+        end: RleEnd::Count,
+    };
+    let bits = [false, false, true];
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    // 00100, then 011 and 010, in the stream from the least significant bit.
+    writer.write_rle(bits.iter().copied(), cfg).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data, [0b1100_0100, 0b0000_0010]);
+    let mut out = Vec::new();
+    BitReader::with_order(&data[..], Lsb0)
+        .read_rle(cfg, u64::MAX, &mut out)
+        .unwrap();
+    assert_eq!(out, bits);
+}
+
+#[test]
+fn errors() {
+    let cfg = RleConfig {
+        length: RunLength::Fixed(4),
+        first: false,
+        end: RleEnd::Count,
+    };
+    // A total of 3, then a run of 5.
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(5, 0b00100).unwrap();
+    writer.write_bits(4, 5).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    let mut out = Vec::new();
+    let err = BitReader::new(&data[..])
+        .read_rle(cfg, u64::MAX, &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let info = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(info.offset(), Some(5));
+    assert_eq!(info.value(), Some(5));
+
+    // Running out before the terminator.
+    let cfg = RleConfig {
+        end: RleEnd::Terminator,
+        ..cfg
+    };
+    let mut out = Vec::new();
+    let err = BitReader::new(&[0x12][..])
+        .read_rle(cfg, u64::MAX, &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(out, [false, true, true]);
+}
+
+/// A gamma code of `value`, most significant bit first.
+fn gamma(writer: &mut BitWriter<Vec<u8>>, value: u64) {
+    let zeros = 63 - value.leading_zeros() as u8;
+    writer.write_bits(zeros, 0).unwrap();
+    writer.write_bits(zeros + 1, value).unwrap();
+}
+
+#[test]
+fn hostile_lengths() {
+    let cfg = RleConfig {
+        length: RunLength::Gamma,
+        first: false,
+        end: RleEnd::Terminator,
+    };
+    // A run of 2^40 - 1 bits, then the terminator, in 11 bytes.
+    let mut writer = BitWriter::new(Vec::new());
+    gamma(&mut writer, 3);
+    gamma(&mut writer, 1 << 40);
+    writer.write_bits(2, 0b11).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data.len(), 11);
+
+    let mut out = Vec::new();
+    let err = BitReader::new(&data[..])
+        .read_rle(cfg, 1 << 20, &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let info = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(info.op(), "read_rle");
+    assert_eq!(info.offset(), Some(3));
+    assert_eq!(info.value(), Some((1 << 40) - 1));
+    assert_eq!(out, [false, false]);
+
+    // A total above the maximum fails before any run is read.
+    let cfg = RleConfig {
+        end: RleEnd::Count,
+        ..cfg
+    };
+    let mut out = Vec::new();
+    let err = BitReader::new(&data[..])
+        .read_rle(cfg, 1, &mut out)
+        .unwrap_err();
+    let info = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(info.offset(), Some(0));
+    assert_eq!(info.value(), Some(2));
+    assert!(out.is_empty());
+
+    // A total within it still bounds the runs.
+    let mut out = Vec::new();
+    let err = BitReader::new(&data[..])
+        .read_rle(cfg, 2, &mut out)
+        .unwrap_err();
+    let info = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(info.offset(), Some(3));
+    assert_eq!(info.value(), Some((1 << 40) - 1));
+}
@@ -0,0 +1,138 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, BitWriterBuilder, Lsb0};
+
+fn pack(bits_per_pixel: u8, rows: &[&[u8]]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for row in rows {
+        writer.pack_row(bits_per_pixel, row).unwrap();
+        assert_eq!(writer.position() % 8, 0);
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+fn unpack(bits_per_pixel: u8, width: usize, rows: usize, data: &[u8]) -> Vec<Vec<u8>> {
+    let mut reader = BitReader::new(Cursor::new(data));
+    let rows = (0..rows)
+        .map(|_| {
+            let mut row = vec![0xee; width];
+            reader.unpack_row(bits_per_pixel, width, &mut row).unwrap();
+            assert_eq!(reader.position() % 8, 0);
+            row
+        })
+        .collect();
+    assert!(reader.read_bit().is_err(), "data left over");
+    rows
+}
+
+#[test]
+fn pbm_rows() {
+    // A 10x2 PBM raster: each row takes two bytes, the last six bits unused.
+    let rows: [&[u8]; 2] = [
+        &[1, 0, 1, 1, 0, 0, 1, 1, 1, 0],
+        &[0, 1, 0, 0, 0, 0, 0, 0, 0, 1],
+    ];
+    let data = [0b1011_0011, 0b1000_0000, 0b0100_0000, 0b0100_0000];
+    assert_eq!(pack(1, &rows), data);
+    assert_eq!(unpack(1, 10, 2, &data), rows);
+}
+
+#[test]
+fn png_rows() {
+    // 2-bit and 4-bit PNG scanlines, without the filter type byte.
+    let two: [&[u8]; 2] = [&[0, 1, 2, 3, 3], &[2, 2, 1, 0, 1]];
+    let data = [0b0001_1011, 0b1100_0000, 0b1010_0100, 0b0100_0000];
+    assert_eq!(pack(2, &two), data);
+    assert_eq!(unpack(2, 5, 2, &data), two);
+
+    let four: [&[u8]; 2] = [&[0xa, 0xb, 0xc], &[0x1, 0x2, 0x3]];
+    let data = [0xab, 0xc0, 0x12, 0x30];
+    assert_eq!(pack(4, &four), data);
+    assert_eq!(unpack(4, 3, 2, &data), four);
+}
+
+#[test]
+fn rows_filling_bytes() {
+    let one: [&[u8]; 2] = [&[1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 1, 0, 1, 0, 1, 0], &[0; 16]];
+    let data = [0xf0, 0xaa, 0x00, 0x00];
+    assert_eq!(pack(1, &one), data);
+    assert_eq!(unpack(1, 16, 2, &data), one);
+
+    let four: [&[u8]; 2] = [&[0xd, 0xe, 0xa, 0xd], &[0xb, 0xe]];
+    let data = [0xde, 0xad, 0xbe];
+    assert_eq!(pack(4, &four), data);
+    assert_eq!(unpack(4, 4, 1, &data[..2]), &four[..1]);
+
+    let eight: [&[u8]; 1] = [&[1, 2, 255]];
+    assert_eq!(pack(8, &eight), [1, 2, 255]);
+    assert_eq!(unpack(8, 3, 1, &[1, 2, 255]), eight);
+}
+
+#[test]
+fn empty_row() {
+    assert_eq!(pack(1, &[&[]]), []);
+    let mut reader = BitReader::new(Cursor::new(&[0xff][..]));
+    reader.unpack_row(1, 0, &mut []).unwrap();
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn row_after_unaligned_data() {
+    // Padding runs to the next byte boundary of the stream.
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    assert_eq!(writer.pack_row(2, &[3, 0]).unwrap(), 5);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b1011_1000]);
+
+    let mut reader = BitReader::new(Cursor::new(&[0b1011_1000, 0xff][..]));
+    reader.read_bits(3).unwrap();
+    let mut row = [0; 2];
+    reader.unpack_row(2, 2, &mut row).unwrap();
+    assert_eq!(row, [3, 0]);
+    assert_eq!(reader.position(), 8);
+}
+
+#[test]
+fn lsb0_rows() {
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    writer.pack_row(2, &[1, 2, 3]).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(data, [0b0011_1001]);
+
+    let mut reader = BitReader::with_order(Cursor::new(&data), Lsb0);
+    let mut row = [0; 3];
+    reader.unpack_row(2, 3, &mut row).unwrap();
+    assert_eq!(row, [1, 2, 3]);
+}
+
+#[test]
+fn pad_ones() {
+    let mut writer = BitWriterBuilder::new()
+        .pad_with_ones(true)
+        .build(Vec::new());
+    writer.pack_row(1, &[0, 1, 0]).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b0101_1111]);
+}
+
+#[test]
+fn pixel_out_of_range() {
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer.pack_row(2, &[0, 3, 4, 1]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().value(), Some(2));
+    assert_eq!(writer.position(), 0);
+}
+
+#[test]
+fn short_row() {
+    let mut reader = BitReader::new(Cursor::new(&[0xff][..]));
+    let mut row = [0; 9];
+    let err = reader.unpack_row(1, 9, &mut row).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,116 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor, SeekFrom};
+
+use bitrw::{BitReader, Lsb0};
+
+#[test]
+fn scattered_matches_seek_and_read() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..500 {
+        let data: Vec<u8> = (0..rng.gen_range(1, 64)).map(|_| rng.gen::<u8>()).collect();
+        let bits = data.len() as u64 * 8;
+        let mut fields: Vec<(u64, u8)> = (0..rng.gen_range(0, 8))
+            .map(|_| {
+                let offset = rng.gen_range(0, bits);
+                (offset, rng.gen_range(0, (bits - offset).min(64) as u8 + 1))
+            })
+            .collect();
+        fields.sort();
+
+        let mut reference = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+        let expected: Vec<u64> = fields
+            .iter()
+            .map(|&(offset, nbits)| {
+                reference.seek(SeekFrom::Start(offset)).unwrap();
+                reference.read_bits(nbits).unwrap()
+            })
+            .collect();
+
+        let start = rng.gen_range(0, bits + 1);
+        let mut reader = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+        reader.seek(SeekFrom::Start(start)).unwrap();
+        let mut out = vec![0; fields.len()];
+        reader.read_scattered(&fields, &mut out).unwrap();
+        assert_eq!(out, expected);
+
+        assert_eq!(reader.position(), start);
+        reference.seek(SeekFrom::Start(start)).unwrap();
+        let rest = (bits - start).min(64) as u8;
+        assert_eq!(
+            reader.read_bits(rest).unwrap(),
+            reference.read_bits(rest).unwrap()
+        );
+    }
+}
+
+#[test]
+fn scattered_fields_sharing_a_byte() {
+    let mut reader = BitReader::new(Cursor::new([0b1011_0110, 0b0101_1100]));
+    let mut out = [0; 4];
+    reader
+        .read_scattered(&[(1, 2), (3, 3), (6, 4), (10, 6)], &mut out)
+        .unwrap();
+    assert_eq!(out, [0b01, 0b101, 0b1001, 0b01_1100]);
+
+    // Overlapping fields, and the same field twice.
+    reader
+        .read_scattered(&[(0, 8), (4, 8), (4, 8), (8, 8)], &mut out)
+        .unwrap();
+    assert_eq!(out, [0xb6, 0x65, 0x65, 0x5c]);
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn scattered_keeps_sequential_position() {
+    let data: Vec<u8> = (0..=255).collect();
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    assert_eq!(reader.read_bits(12).unwrap(), 0x000);
+
+    let mut out = [0; 3];
+    reader
+        .read_scattered(&[(8, 8), (1600, 8), (2040, 8)], &mut out)
+        .unwrap();
+    assert_eq!(out, [1, 200, 255]);
+    assert_eq!(reader.position(), 12);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x102);
+}
+
+#[test]
+fn scattered_out_of_order() {
+    let mut reader = BitReader::new(Cursor::new([0xab, 0xcd]));
+    reader.read_bits(3).unwrap();
+
+    let mut out = [7; 3];
+    let err = reader
+        .read_scattered(&[(0, 4), (8, 4), (4, 4)], &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_scattered");
+    assert_eq!(err.value(), Some(2));
+    assert_eq!(out, [7; 3]);
+    assert_eq!(reader.position(), 3);
+    assert_eq!(reader.read_bits(5).unwrap(), 0x0b);
+}
+
+#[test]
+fn scattered_past_end() {
+    let mut reader = BitReader::new(Cursor::new([0xab, 0xcd]));
+    reader.read_bits(5).unwrap();
+
+    let mut out = [0; 3];
+    let err = reader
+        .read_scattered(&[(0, 4), (8, 4), (12, 8)], &mut out)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_scattered");
+    assert_eq!(err.value(), Some(2));
+    assert_eq!(err.offset(), Some(12));
+    assert_eq!(&out[..2], [0xa, 0xc]);
+    assert_eq!(reader.position(), 5);
+    assert_eq!(reader.read_bits(11).unwrap(), 0x3cd);
+}
@@ -0,0 +1,258 @@
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter, BitWriterBuilder};
+
+const DATA: [u8; 4] = [0x12, 0x34, 0x56, 0x78];
+
+fn scope_error(err: &io::Error) -> (Option<u64>, Option<u64>, Option<u64>) {
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(err).unwrap();
+    (err.offset(), err.value(), err.expected())
+}
+
+#[test]
+fn exact() {
+    let mut reader = BitReader::new(&DATA[..]);
+    reader.read_bits(4).unwrap();
+    let value = reader
+        .read_exact_scope(20, |r| {
+            let a = r.read_bits(12)?;
+            let b = r.read_bits(8)?;
+            Ok((a, b))
+        })
+        .unwrap();
+    assert_eq!(value, (0x234, 0x56));
+    assert_eq!(reader.read_bits(8).unwrap(), 0x78);
+
+    let mut reader = BitReader::new(&DATA[..]);
+    assert_eq!(reader.read_exact_scope(0, |_| Ok(1)).unwrap(), 1);
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn short_strict() {
+    let mut reader = BitReader::new(&DATA[..]);
+    reader.read_bits(4).unwrap();
+    let err = reader
+        .read_exact_scope(16, |r| r.read_bits(10))
+        .unwrap_err();
+    assert_eq!(scope_error(&err), (Some(4), Some(10), Some(16)));
+    // Left where the closure stopped.
+    assert_eq!(reader.position(), 14);
+}
+
+#[test]
+fn short_lenient() {
+    let mut reader = BitReader::new(&DATA[..]);
+    let value = reader
+        .read_exact_scope_lenient(16, |r| r.read_bits(4))
+        .unwrap();
+    assert_eq!(value, 0x1);
+    assert_eq!(reader.position(), 16);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x56);
+
+    // The remainder must be there to skip.
+    let mut reader = BitReader::new(&DATA[..]);
+    let err = reader
+        .read_exact_scope_lenient(40, |r| r.read_bits(4))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn over_consumption() {
+    // Caught at the read which passes the end, not when the closure returns.
+    let mut reader = BitReader::new(&DATA[..]);
+    let mut reached = false;
+    let err = reader
+        .read_exact_scope(12, |r| {
+            r.read_bits(8)?;
+            r.read_bits(8)?;
+            reached = true;
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(!reached);
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(8));
+    // The reader's own limit is back, so it can carry on.
+    assert_eq!(reader.read_bits(16).unwrap(), 0x3456);
+
+    // Skipping past the end from the lenient version too.
+    let mut reader = BitReader::new(&DATA[..]);
+    assert!(reader
+        .read_exact_scope_lenient(12, |r| r.skip_bits(13))
+        .is_err());
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn overrun_after_reset() {
+    // reset() passes the limit, so is caught when the closure returns.
+    let mut reader = BitReader::new(&DATA[..]);
+    let err = reader
+        .read_exact_scope(2, |r| {
+            r.read_bits(1)?;
+            r.reset();
+            Ok(())
+        })
+        .unwrap_err();
+    assert_eq!(scope_error(&err), (Some(0), Some(8), Some(2)));
+}
+
+#[test]
+fn nested() {
+    let mut reader = BitReader::new(&DATA[..]);
+    let err = reader
+        .read_exact_scope(24, |r| {
+            r.read_exact_scope_lenient(16, |r| r.read_bits(4))?;
+            // The outer scope still ends at bit 24.
+            r.read_exact_scope(16, |r| r.read_bits(16))
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(16));
+
+    let mut reader = BitReader::new(&DATA[..]);
+    let value = reader
+        .read_exact_scope(24, |r| {
+            let head = r.read_bits(4)?;
+            let inner = r.read_exact_scope_lenient(12, |r| r.read_bits(4))?;
+            let tail = r.read_exact_scope(8, |r| r.read_bits(8))?;
+            Ok((head, inner, tail))
+        })
+        .unwrap();
+    assert_eq!(value, (0x1, 0x2, 0x56));
+
+    // An inner scope short of its own end fails the outer one too.
+    let mut reader = BitReader::new(&DATA[..]);
+    let err = reader
+        .read_exact_scope(16, |r| r.read_exact_scope(8, |r| r.read_bits(4)))
+        .unwrap_err();
+    assert_eq!(scope_error(&err), (Some(0), Some(4), Some(8)));
+}
+
+#[test]
+fn within_reader_limit() {
+    let mut reader = BitReaderBuilder::new().limit_bits(12).build(&DATA[..]);
+    let err = reader
+        .read_exact_scope(16, |r| r.read_bits(16))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x123);
+    assert!(reader.read_bits(1).is_err());
+}
+
+#[test]
+fn write_exact() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(4, 0x1).unwrap();
+    writer
+        .write_exact_scope(20, |w| {
+            w.write_bits(12, 0x234)?;
+            w.write_bits(8, 0x56)?;
+            Ok(())
+        })
+        .unwrap();
+    writer.write_exact_scope(0, |_| Ok(())).unwrap();
+    writer.write_bits(8, 0x78).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), DATA);
+}
+
+#[test]
+fn write_short_padded() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_exact_scope_padded(12, 0, |w| w.write_bits(4, 0x1).map(|_| ()))
+        .unwrap();
+    writer
+        .write_exact_scope_padded(100, 1, |w| w.write_bits(2, 0).map(|_| ()))
+        .unwrap();
+    assert_eq!(writer.position(), 112);
+    writer.write_bits(4, 0).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+    assert_eq!(&data[..2], &[0x10, 0x03]);
+    assert!(data[2..14].iter().all(|&b| b == 0xff));
+    assert_eq!(data[14..], [0]);
+
+    // Padding can't pass the writer's own limit.
+    let mut writer = BitWriterBuilder::new().limit_bits(8).build(Vec::new());
+    let err = writer
+        .write_exact_scope_padded(12, 0, |w| w.write_bits(4, 0x1).map(|_| ()))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+}
+
+#[test]
+fn write_short_strict() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(4, 0).unwrap();
+    let err = writer
+        .write_exact_scope(16, |w| w.write_bits(10, 0).map(|_| ()))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(
+        (err.offset(), err.value(), err.expected()),
+        (Some(4), Some(10), Some(16))
+    );
+}
+
+#[test]
+fn write_long() {
+    let mut writer = BitWriter::new(Vec::new());
+    let mut reached = false;
+    let err = writer
+        .write_exact_scope(12, |w| {
+            w.write_bits(8, 0xff)?;
+            w.write_bits(8, 0xff)?;
+            reached = true;
+            Ok(())
+        })
+        .unwrap_err();
+    assert!(!reached);
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(8));
+    assert_eq!(writer.position(), 8);
+
+    // The limit is gone again afterwards.
+    writer.write_bits(16, 0).unwrap();
+    assert!(writer
+        .write_exact_scope_padded(4, 0, |w| w.write_bits(5, 0).map(|_| ()))
+        .is_err());
+
+    // As are whole bytes.
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer
+        .write_exact_scope(8, |w| w.write_bytes(&[1, 2]).map(|_| ()))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 0);
+}
+
+#[test]
+fn write_nested() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer
+        .write_exact_scope(24, |w| {
+            w.write_bits(4, 0x1)?;
+            w.write_exact_scope_padded(12, 0, |w| w.write_bits(4, 0x2).map(|_| ()))?;
+            w.write_exact_scope(8, |w| w.write_bits(8, 0x56).map(|_| ()))
+        })
+        .unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0x12, 0x00, 0x56]);
+
+    // An inner scope can't pass the end of the outer one.
+    let mut writer = BitWriter::new(Vec::new());
+    let err = writer
+        .write_exact_scope(8, |w| {
+            w.write_exact_scope_padded(16, 0, |w| w.write_bits(4, 0).map(|_| ()))
+        })
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+}
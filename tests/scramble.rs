@@ -0,0 +1,134 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{
+    BitReader, BitWrite, BitWriter, BitWriterBuilder, DescrambleBitReader, Keystream, Lfsr, Lsb0,
+    ScrambleBitWriter, ScrambleReset,
+};
+
+#[test]
+fn dvb_prbs() {
+    // The start of the DVB energy dispersal sequence, scrambling zeros.
+    let mut writer = ScrambleBitWriter::new(
+        BitWriter::new(Vec::new()),
+        Lfsr::dvb(),
+        ScrambleReset::Manual,
+    );
+    for _ in 0..8 {
+        writer.write::<u8>(0).unwrap();
+    }
+    writer.flush().unwrap();
+    assert_eq!(
+        writer.into_inner().into_inner(),
+        [0x03, 0xf6, 0x08, 0x34, 0x30, 0xb8, 0xa3, 0x93]
+    );
+
+    // A maximal length sequence repeats after 2^15 - 1 bits.
+    let mut lfsr = Lfsr::dvb();
+    let start = lfsr.state();
+    for _ in 0..(1 << 15) - 1 {
+        lfsr.next_bit();
+    }
+    assert_eq!(lfsr.state(), start);
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..300)
+        .map(|_| {
+            let nbits = rng.gen_range(0, 65);
+            let value = if nbits == 0 {
+                0
+            } else {
+                rng.gen::<u64>() >> (64 - nbits)
+            };
+            (nbits, value)
+        })
+        .collect();
+    for &reset in &[
+        ScrambleReset::Manual,
+        ScrambleReset::Every(1),
+        ScrambleReset::Every(100),
+    ] {
+        let lfsr = Lfsr::new(1 << 7 | 1 << 6 | 1, 0x7f);
+        let mut writer =
+            ScrambleBitWriter::new(BitWriter::with_order(Vec::new(), Lsb0), lfsr.clone(), reset);
+        for &(nbits, value) in &fields {
+            writer.write_bits(nbits, value).unwrap();
+        }
+        writer.flush().unwrap();
+        let data = writer.into_inner().into_inner();
+
+        let mut reader =
+            DescrambleBitReader::new(BitReader::with_order(&data[..], Lsb0), lfsr, reset);
+        for &(nbits, value) in &fields {
+            assert_eq!(reader.read_bits(nbits).unwrap(), value);
+        }
+    }
+}
+
+#[test]
+fn periodic_reset_matches_manual() {
+    let scramble = |reset, manual: bool| {
+        let mut writer = ScrambleBitWriter::new(BitWriter::new(Vec::new()), Lfsr::dvb(), reset);
+        for i in 0..40 {
+            if manual && i % 3 == 0 {
+                writer.reset();
+            }
+            writer.write_bits(8, i).unwrap();
+        }
+        writer.flush().unwrap();
+        writer.into_inner().into_inner()
+    };
+    assert_eq!(
+        scramble(ScrambleReset::Every(24), false),
+        scramble(ScrambleReset::Manual, true)
+    );
+    assert_ne!(
+        scramble(ScrambleReset::Manual, false),
+        scramble(ScrambleReset::Manual, true)
+    );
+}
+
+#[test]
+fn closure_keystream() {
+    let mut toggle = 0;
+    let whitening = move || {
+        toggle ^= 1;
+        toggle
+    };
+    let mut writer =
+        ScrambleBitWriter::new(BitWriter::new(Vec::new()), whitening, ScrambleReset::Manual);
+    writer.write_bits(16, 0x0ff0).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0xa5, 0x5a]);
+}
+
+#[test]
+fn failed_operations_use_no_keystream() {
+    let mut writer = ScrambleBitWriter::new(
+        BitWriterBuilder::new().limit_bits(12).build(Vec::new()),
+        Lfsr::dvb(),
+        ScrambleReset::Manual,
+    );
+    writer.write_bits(4, 0).unwrap();
+    let err = writer.write_bits(9, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    writer.write_bits(8, 0).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner().into_inner();
+    assert_eq!(data, [0x03, 0xf0]);
+
+    let mut reader = DescrambleBitReader::new(
+        BitReader::new(&data[..1]),
+        Lfsr::dvb(),
+        ScrambleReset::Manual,
+    );
+    assert!(reader.read_bits(12).is_err());
+    assert_eq!(reader.read_bits(8).unwrap(), 0);
+    assert_eq!(reader.position(), 8);
+}
@@ -0,0 +1,119 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::Cursor;
+
+use bitrw::{BitReaderBuilder, BitWriterBuilder, Lsb0, Msb0};
+
+#[test]
+fn read_bit_matches_read_bits() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let len = rng.gen_range(0, 64);
+        let data: Vec<u8> = (0..len).map(|_| rng.gen::<u8>()).collect();
+        let limit = if rng.gen() {
+            Some(rng.gen_range(0, len as u64 * 8 + 8))
+        } else {
+            None
+        };
+
+        macro_rules! check {
+            ($order:expr) => {{
+                let build = |limit: Option<u64>| {
+                    let builder = BitReaderBuilder::new().bit_order($order);
+                    let builder = match limit {
+                        Some(limit) => builder.limit_bits(limit),
+                        None => builder,
+                    };
+                    builder.build(Cursor::new(&data[..]))
+                };
+                let mut fast = build(limit);
+                let mut slow = build(limit);
+
+                // Mix in wider reads so single bits are taken at every phase.
+                loop {
+                    let (a, b) = if rng.gen_range(0, 4) == 0 {
+                        let nbits = rng.gen_range(2, 20);
+                        (fast.read_bits(nbits), slow.read_bits(nbits))
+                    } else {
+                        (fast.read_bit().map(u64::from), slow.read_bits(1))
+                    };
+                    assert_eq!(fast.position(), slow.position());
+                    match (a, b) {
+                        (Ok(a), Ok(b)) => assert_eq!(a, b),
+                        (Err(a), Err(b)) => {
+                            assert_eq!(a.kind(), b.kind());
+                            assert_eq!(a.to_string(), b.to_string());
+                            break;
+                        }
+                        (a, b) => panic!("{:?} != {:?}", a, b),
+                    }
+                }
+            }};
+        }
+        check!(Msb0);
+        check!(Lsb0);
+    }
+}
+
+#[test]
+fn write_bit_matches_write_bits() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let count = rng.gen_range(0, 500);
+        let limit = if rng.gen() {
+            Some(rng.gen_range(0, 520))
+        } else {
+            None
+        };
+        let capacity = if rng.gen() { 0 } else { rng.gen_range(1, 16) };
+
+        macro_rules! check {
+            ($order:expr) => {{
+                let build = || {
+                    let builder = BitWriterBuilder::new().bit_order($order).capacity(capacity);
+                    let builder = match limit {
+                        Some(limit) => builder.limit_bits(limit),
+                        None => builder,
+                    };
+                    builder.build(Vec::new())
+                };
+                let mut fast = build();
+                let mut slow = build();
+
+                for _ in 0..count {
+                    let (a, b) = if rng.gen_range(0, 4) == 0 {
+                        let nbits = rng.gen_range(2, 20);
+                        let value = rng.gen::<u64>() >> (64 - nbits);
+                        (
+                            fast.write_bits(nbits, value).map(|_| ()),
+                            slow.write_bits(nbits, value).map(|_| ()),
+                        )
+                    } else {
+                        let bit = rng.gen_range(0, 2);
+                        (
+                            fast.write_bit(bit),
+                            slow.write_bits(1, bit as u64).map(|_| ()),
+                        )
+                    };
+                    assert_eq!(fast.position(), slow.position());
+                    match (a, b) {
+                        (Ok(()), Ok(())) => {}
+                        (Err(a), Err(b)) => {
+                            assert_eq!(a.kind(), b.kind());
+                            assert_eq!(a.to_string(), b.to_string());
+                            break;
+                        }
+                        (a, b) => panic!("{:?} != {:?}", a, b),
+                    }
+                }
+                fast.flush().unwrap();
+                slow.flush().unwrap();
+                assert_eq!(fast.into_inner(), slow.into_inner());
+            }};
+        }
+        check!(Msb0);
+        check!(Lsb0);
+    }
+}
@@ -0,0 +1,159 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor, SeekFrom};
+
+use bitrw::{BitRead, BitReader, BitReaderBuilder, BitSlice, BitWriter, Lsb0};
+
+// A container is an 8 bit element count, then elements of a 4 bit tag and a
+// 12 bit body length in bits, followed by the body.  Tag 1 bodies are a list of
+// 5 bit values, tag 2 bodies are a 3 bit flag field then a nested element.
+fn container() -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(8, 3).unwrap();
+
+    writer.write_bits(4, 1).unwrap();
+    writer.write_bits(12, 15).unwrap();
+    for v in &[7, 21, 30] {
+        writer.write_bits(5, *v).unwrap();
+    }
+
+    writer.write_bits(4, 2).unwrap();
+    writer.write_bits(12, 3 + 16 + 10).unwrap();
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_bits(4, 1).unwrap();
+    writer.write_bits(12, 10).unwrap();
+    writer.write_bits(5, 1).unwrap();
+    writer.write_bits(5, 2).unwrap();
+
+    writer.write_bits(4, 1).unwrap();
+    writer.write_bits(12, 0).unwrap();
+
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[derive(Debug, PartialEq)]
+enum Element {
+    Values(Vec<u64>),
+    Flagged(u64, Box<Element>),
+}
+
+fn parse_body<R: io::Read + io::Seek>(tag: u64, body: &mut BitSlice<'_, R>) -> Element {
+    match tag {
+        1 => {
+            let mut values = Vec::new();
+            while body.remaining_bits() > 0 {
+                values.push(body.read_bits(5).unwrap());
+            }
+            // The element can't see past its end.
+            assert!(body.read_bit().is_err());
+            Element::Values(values)
+        }
+        2 => {
+            let flags = body.read_bits(3).unwrap();
+            let tag = body.read_bits(4).unwrap();
+            let len = body.read_bits(12).unwrap();
+            let start = body.position();
+            assert_eq!(start, 19);
+            let mut nested = body.slice(start, len).unwrap();
+            let element = parse_body(tag, &mut nested);
+            nested.finish().unwrap();
+            assert_eq!(body.position(), start);
+            Element::Flagged(flags, Box::new(element))
+        }
+        _ => panic!("unknown tag {}", tag),
+    }
+}
+
+#[test]
+fn parse_container() {
+    let mut reader = BitReader::new(Cursor::new(container()));
+    let count = reader.read_bits(8).unwrap();
+
+    let mut elements = Vec::new();
+    for _ in 0..count {
+        let tag = reader.read_bits(4).unwrap();
+        let len = reader.read_bits(12).unwrap();
+        let start = reader.position();
+        {
+            let mut body = reader.slice(start, len).unwrap();
+            assert_eq!(body.len_bits(), len);
+            assert_eq!(body.bit_position(), Some(0));
+            elements.push(parse_body(tag, &mut body));
+        }
+        assert_eq!(reader.position(), start);
+        reader.seek(SeekFrom::Start(start + len)).unwrap();
+    }
+
+    assert_eq!(
+        elements,
+        [
+            Element::Values(vec![7, 21, 30]),
+            Element::Flagged(0b101, Box::new(Element::Values(vec![1, 2]))),
+            Element::Values(vec![]),
+        ]
+    );
+    assert_eq!(reader.position(), 8 + 16 + 15 + 16 + 29 + 16);
+}
+
+#[test]
+fn restores_unaligned_position() {
+    let data = [0x12, 0x34, 0x56, 0x78];
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    assert_eq!(reader.read_bits(5).unwrap(), 0x12 >> 3);
+
+    let mut slice = reader.slice(12, 12).unwrap();
+    assert_eq!(slice.read_bits(12).unwrap(), 0x456);
+    slice.finish().unwrap();
+
+    assert_eq!(reader.position(), 5);
+    assert_eq!(reader.read_bits(11).unwrap(), 0x1234 & 0x7ff);
+}
+
+#[test]
+fn lsb0_slice() {
+    let data = [0xab, 0xcd];
+    let mut reader = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+    let mut slice = reader.slice(4, 8).unwrap();
+    assert_eq!(slice.read_bits(8).unwrap(), 0xda);
+    drop(slice);
+    assert_eq!(reader.read_bits(16).unwrap(), 0xcdab);
+}
+
+#[test]
+fn out_of_bounds() {
+    let data = [0; 4];
+    let mut reader = BitReaderBuilder::new()
+        .limit_bits(24)
+        .build(Cursor::new(&data[..]));
+    assert!(reader.slice(8, 16).is_ok());
+    let err = reader.slice(8, 17).map(|_| ()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert!(reader.slice(u64::MAX, 1).is_err());
+
+    // Nested slices must lie within their parent.
+    let mut outer = reader.slice(8, 8).unwrap();
+    assert!(outer.slice(4, 5).is_err());
+    let mut inner = outer.slice(4, 4).unwrap();
+    assert_eq!(inner.read_bits(4).unwrap(), 0);
+    assert!(inner.read_bit().is_err());
+    drop(inner);
+    assert_eq!(outer.remaining_bits(), 8);
+    drop(outer);
+
+    // The builder's limit still applies once the slices are gone.
+    assert_eq!(reader.read_bits(24).unwrap(), 0);
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn past_end_of_stream() {
+    let data = [0xff];
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    let mut slice = reader.slice(4, 8).unwrap();
+    assert_eq!(slice.read_bits(4).unwrap(), 0xf);
+    let err = slice.read_bit().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    slice.finish().unwrap();
+    assert_eq!(reader.read_bits(8).unwrap(), 0xff);
+}
@@ -0,0 +1,76 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, Lsb0};
+
+#[test]
+fn field_spanning_two_slices() {
+    let parts: [&[u8]; 2] = [&[0x12, 0x34], &[0x56, 0x78]];
+    let mut reader = BitReader::from_slices(parts.iter().copied());
+    assert_eq!(reader.read_bits(12).unwrap(), 0x123);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x456);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x78);
+    assert_eq!(
+        reader.read_bit().unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
+
+#[test]
+fn field_spanning_three_slices_with_empties() {
+    let parts: [&[u8]; 7] = [&[], &[0xab], &[], &[0xcd], &[], &[], &[0xef, 0x01]];
+    let mut reader = BitReader::from_slices(parts.iter().copied());
+    assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+    // 0xbcdef0 runs from the first non-empty slice through the third.
+    assert_eq!(reader.read_bits(24).unwrap(), 0xbc_def0);
+    assert_eq!(reader.read_bits(4).unwrap(), 0x1);
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn all_empty() {
+    let parts: [&[u8]; 3] = [&[], &[], &[]];
+    let mut reader = BitReader::from_slices(parts.iter().copied());
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn matches_concatenated_copy() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..50 {
+        let data: Vec<u8> = (0..200).map(|_| rng.gen::<u8>()).collect();
+
+        // Cut into random pieces, including empty ones.
+        let mut parts = Vec::new();
+        let mut rest = &data[..];
+        while !rest.is_empty() {
+            let n = rng.gen_range(0, std::cmp::min(rest.len(), 7) + 1);
+            let (part, tail) = rest.split_at(n);
+            parts.push(part);
+            rest = tail;
+        }
+
+        let widths: Vec<u8> = (0..100).map(|_| rng.gen_range(1, 33)).collect();
+        let mut chained = BitReader::from_slices_with_order(parts.iter().copied(), Lsb0);
+        let mut flat = BitReader::with_order(Cursor::new(data.clone()), Lsb0);
+        for &n in &widths {
+            match flat.read_bits(n) {
+                Ok(v) => assert_eq!(chained.read_bits(n).unwrap(), v),
+                Err(_) => {
+                    assert!(chained.read_bits(n).is_err());
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn typed_reads_across_boundaries() {
+    let parts: Vec<&[u8]> = vec![&[0x01], &[0x23, 0x45], &[0x67]];
+    let mut reader = BitReader::from_slices(parts);
+    assert_eq!(reader.read_be::<u32>().unwrap(), 0x0123_4567);
+}
@@ -0,0 +1,129 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+
+use bitrw::{splice_bits, splice_bits_with_order, BitOrder, Lsb0, Msb0};
+
+fn to_bools<O: BitOrder>(bytes: &[u8], nbits: u64) -> Vec<bool> {
+    (0..nbits as usize)
+        .map(|i| {
+            let shift = if O::MSB_FIRST { 7 - i % 8 } else { i % 8 };
+            (bytes[i / 8] >> shift) & 1 == 1
+        })
+        .collect()
+}
+
+fn from_bools<O: BitOrder>(bits: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0; bits.len().div_ceil(8)];
+    for (i, &bit) in bits.iter().enumerate() {
+        let shift = if O::MSB_FIRST { 7 - i % 8 } else { i % 8 };
+        bytes[i / 8] |= (bit as u8) << shift;
+    }
+    bytes
+}
+
+fn check<O: BitOrder>(order: O, rng: &mut impl Rng, grow: i64) {
+    let bit_len = rng.gen_range(0, 400);
+    let mut buf: Vec<u8> = (0..(bit_len as usize).div_ceil(8))
+        .map(|_| rng.gen())
+        .collect();
+    // Zero the padding, as it would be in a real stream.
+    buf = from_bools::<O>(&to_bools::<O>(&buf, bit_len));
+    let start = rng.gen_range(0, bit_len + 1);
+    let end = rng.gen_range(start, bit_len + 1);
+    let removed = (end - start) as i64;
+    let replacement_bits = (removed + grow).clamp(0, 300) as u64;
+    let replacement: Vec<u8> = (0..(replacement_bits as usize).div_ceil(8) + rng.gen_range(0, 3))
+        .map(|_| rng.gen())
+        .collect();
+
+    let mut expected = to_bools::<O>(&buf, bit_len);
+    expected.splice(
+        start as usize..end as usize,
+        to_bools::<O>(&replacement, replacement_bits),
+    );
+
+    let mut len = bit_len;
+    splice_bits_with_order(
+        &mut buf,
+        &mut len,
+        start..end,
+        &replacement,
+        replacement_bits,
+        order,
+    );
+    assert_eq!(len, expected.len() as u64);
+    assert_eq!(
+        buf,
+        from_bools::<O>(&expected),
+        "{}..{} of {}",
+        start,
+        end,
+        bit_len
+    );
+}
+
+#[test]
+fn against_reference() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..500 {
+        let grow = rng.gen_range(-100, 100);
+        check(Msb0, &mut rng, grow);
+        check(Lsb0, &mut rng, grow);
+        // Equal size.
+        check(Msb0, &mut rng, 0);
+        check(Lsb0, &mut rng, 0);
+    }
+}
+
+#[test]
+fn unaligned_growth_and_shrinkage() {
+    // 20 bits: 1010_1010 1100_1100 1111
+    let original = vec![0xaa, 0xcc, 0xf0];
+
+    // Grow: replace bits 3..5 with 5 ones.
+    let mut buf = original.clone();
+    let mut len = 20;
+    splice_bits(&mut buf, &mut len, 3..5, &[0xff], 5);
+    assert_eq!(len, 23);
+    assert_eq!(buf, [0b1011_1111, 0b0101_1001, 0b1001_1110]);
+
+    // Shrink: remove bits 5..13 entirely.
+    let mut buf = original.clone();
+    let mut len = 20;
+    splice_bits(&mut buf, &mut len, 5..13, &[], 0);
+    assert_eq!(len, 12);
+    assert_eq!(buf, [0b1010_1100, 0b1111_0000]);
+
+    // Equal size, leaving the rest alone.
+    let mut buf = original.clone();
+    let mut len = 20;
+    splice_bits(&mut buf, &mut len, 9..12, &[0b0110_0000], 3);
+    assert_eq!(len, 20);
+    assert_eq!(buf, [0xaa, 0b1011_1100, 0xf0]);
+
+    // Shrinking to nothing drops the bytes.
+    let mut buf = original.clone();
+    let mut len = 20;
+    splice_bits(&mut buf, &mut len, 0..20, &[], 0);
+    assert_eq!((len, buf), (0, vec![]));
+}
+
+#[test]
+fn clears_stale_bytes() {
+    // Bytes past bit_len, and padding bits, are dropped.
+    let mut buf = vec![0xff, 0xff, 0xff];
+    let mut len = 10;
+    splice_bits(&mut buf, &mut len, 10..10, &[0x00], 1);
+    assert_eq!(len, 11);
+    assert_eq!(buf, [0xff, 0b1100_0000]);
+}
+
+#[test]
+#[should_panic(expected = "splice range out of bounds")]
+fn range_past_end() {
+    let mut buf = vec![0; 2];
+    let mut len = 12;
+    splice_bits(&mut buf, &mut len, 4..13, &[], 0);
+}
@@ -0,0 +1,84 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, Lsb0, StartCode};
+
+// The start of an MPEG-2 video elementary stream, with some awkward zero runs
+// added between the units.
+const STREAM: &[u8] = &[
+    // sequence_header_code, 720x576, 4:3, 25fps, ...
+    0x00, 0x00, 0x01, 0xb3, 0x2d, 0x02, 0x40, 0x23, 0x27, 0x23, 0x81, 0x80,
+    // sequence_extension, preceded by zero stuffing
+    0x00, 0x00, 0x00, 0x00, 0x01, 0xb5, 0x14, 0x8a, 0x00, 0x01, 0x00, 0x00,
+    // group_start_code, after two zeros ending the extension, with 00 00 02
+    // and 00 00 00 02 in the payload
+    0x00, 0x00, 0x01, 0xb8, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x40,
+    // picture_start_code, after a lone 01 and 00 01
+    0x01, 0x00, 0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x0f, 0xff, 0xf8, // slice 1
+    0x00, 0x00, 0x01, 0x01, 0x0a, 0x00,
+];
+
+fn code(value: u8, skipped: u64, leading_zeros: u64) -> StartCode {
+    StartCode {
+        value,
+        skipped,
+        leading_zeros,
+    }
+}
+
+#[test]
+fn mpeg2_units() {
+    let mut reader = BitReader::new(Cursor::new(STREAM));
+    assert_eq!(reader.next_start_code().unwrap(), code(0xb3, 0, 0));
+    assert_eq!(reader.position(), 32);
+
+    // Parse a little of the sequence header.
+    assert_eq!(reader.read_bits(12).unwrap(), 720);
+    assert_eq!(reader.read_bits(12).unwrap(), 576);
+    assert_eq!(reader.read_bits(4).unwrap(), 2);
+
+    // Trailing fields are skipped, including the leading 00 00 of the
+    // stuffing; the four zeros before the 01 count as leading zeros.
+    let found = reader.next_start_code().unwrap();
+    assert_eq!(found, code(0xb5, 6, 2));
+    assert_eq!(reader.position(), 18 * 8);
+
+    assert_eq!(reader.next_start_code().unwrap(), code(0xb8, 6, 2));
+    assert_eq!(reader.next_start_code().unwrap(), code(0x00, 11, 0));
+    assert_eq!(reader.position(), 43 * 8);
+    assert_eq!(reader.next_start_code().unwrap(), code(0x01, 4, 0));
+    assert_eq!(reader.read_bits(8).unwrap(), 0x0a);
+
+    let err = reader.next_start_code().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "next_start_code");
+    assert_eq!(err.offset(), Some(52 * 8));
+}
+
+#[test]
+fn four_byte_form() {
+    let data = [0xff, 0x00, 0x00, 0x00, 0x01, 0x67, 0x00, 0x00, 0x01, 0x68];
+    let mut reader = BitReader::new(&data[..]);
+    assert_eq!(reader.next_start_code().unwrap(), code(0x67, 2, 1));
+    assert_eq!(reader.next_start_code().unwrap(), code(0x68, 0, 0));
+}
+
+#[test]
+fn no_false_positives() {
+    let data = [0x00, 0x00, 0x02, 0x00, 0x01, 0x00, 0x02, 0x01, 0x00, 0x00];
+    let mut reader = BitReader::new(&data[..]);
+    assert!(reader.next_start_code().is_err());
+    assert_eq!(reader.position(), 80);
+}
+
+#[test]
+fn aligns_first() {
+    // The 00 00 01 at bit 4 isn't byte-aligned, so doesn't count.
+    let data = [0xf0, 0x00, 0x00, 0x1a, 0x00, 0x00, 0x01, 0xba];
+    let mut reader = BitReader::with_order(&data[..], Lsb0);
+    reader.read_bits(3).unwrap();
+    assert_eq!(reader.next_start_code().unwrap(), code(0xba, 3, 0));
+    assert_eq!(reader.position(), 64);
+}
@@ -0,0 +1,178 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, Lsb0, ReaderState, WriterState};
+
+fn fields() -> Vec<(u8, u64)> {
+    let mut rng = rand::thread_rng();
+    (0..200)
+        .map(|_| {
+            let width = rng.gen_range(1, 65);
+            (width, rng.gen::<u64>() >> (64 - width))
+        })
+        .collect()
+}
+
+fn encode(fields: &[(u8, u64)]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for &(width, value) in fields {
+        writer.write_bits(width, value).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn reader_resume_matches_uninterrupted() {
+    let fields = fields();
+    let data = encode(&fields);
+
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    let expected: Vec<u64> = fields
+        .iter()
+        .map(|&(width, _)| reader.read_bits(width).unwrap())
+        .collect();
+
+    for stop in 0..fields.len() {
+        // Partway through the field at `stop`, checkpoint and abandon the reader.
+        let mut reader = BitReader::new(Cursor::new(&data[..]));
+        let mut decoded: Vec<u64> = fields[..stop]
+            .iter()
+            .map(|&(width, _)| reader.read_bits(width).unwrap())
+            .collect();
+        let width = fields[stop].0;
+        let head = width / 2;
+        let high = reader.read_bits(head).unwrap();
+        let state = reader.save_state();
+
+        let mut reader = BitReader::resume(Cursor::new(&data[..]), &state).unwrap();
+        assert_eq!(reader.position(), state.position());
+        let low = reader.read_bits(width - head).unwrap();
+        decoded.push(if head == 0 {
+            low
+        } else {
+            high << (width - head) | low
+        });
+        decoded.extend(
+            fields[stop + 1..]
+                .iter()
+                .map(|&(width, _)| reader.read_bits(width).unwrap()),
+        );
+        assert_eq!(decoded, expected, "stopped in field {}", stop);
+    }
+}
+
+#[test]
+fn reader_resume_lsb0() {
+    let data = [0xa5, 0x3c, 0x0f];
+    let mut reader = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    let state = reader.save_state();
+    assert_eq!(state.position(), 3);
+    assert_eq!(state.pending(), (5, 0xa5 >> 3));
+
+    let mut reader = BitReader::resume_with_order(Cursor::new(&data[..]), &state, Lsb0).unwrap();
+    assert_eq!(reader.read_bits(13).unwrap(), 0x3ca5 >> 3);
+    assert_eq!(reader.read_bits(8).unwrap(), 0x0f);
+}
+
+#[test]
+fn reader_resume_aligned() {
+    let data = [1, 2, 3];
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(16).unwrap();
+    let state = reader.save_state();
+    assert_eq!(state.pending(), (0, 0));
+
+    let mut reader = BitReader::resume(Cursor::new(&data[..]), &state).unwrap();
+    assert_eq!(reader.read_bits(8).unwrap(), 3);
+    assert!(reader.read_bit().is_err());
+}
+
+#[test]
+fn reader_resume_detects_changed_data() {
+    let mut reader = BitReader::new(Cursor::new(&[0xff, 0x0f][..]));
+    reader.read_bits(12).unwrap();
+    let state = reader.save_state();
+
+    let err = BitReader::resume(Cursor::new(&[0xff, 0x0e][..]), &state)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "resume");
+    assert_eq!(err.offset(), Some(12));
+    assert_eq!(err.value(), Some(0xe));
+
+    // A truncated stream can't be resumed either.
+    let err = BitReader::resume(Cursor::new(&[0xff][..]), &state)
+        .map(|_| ())
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn writer_resume_matches_uninterrupted() {
+    let fields = fields();
+    let expected = encode(&fields);
+
+    for stop in (0..fields.len()).step_by(7) {
+        let mut writer = BitWriter::new(Cursor::new(Vec::new()));
+        for &(width, value) in &fields[..stop] {
+            writer.write_bits(width, value).unwrap();
+        }
+        let state = writer.save_state().unwrap();
+        assert_eq!(state.pending().0 as u64, state.position() % 8);
+        let written = writer.into_inner().into_inner();
+        assert_eq!(written.len() as u64, state.position() / 8);
+
+        let mut writer = BitWriter::resume(Cursor::new(written), &state).unwrap();
+        for &(width, value) in &fields[stop..] {
+            writer.write_bits(width, value).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(
+            writer.into_inner().into_inner(),
+            expected,
+            "stopped at field {}",
+            stop
+        );
+    }
+}
+
+#[test]
+fn writer_resume_batched_lsb0() {
+    let mut writer = bitrw::BitWriterBuilder::new()
+        .bit_order(Lsb0)
+        .capacity(64)
+        .build(Cursor::new(Vec::new()));
+    writer.write_bits(20, 0xabcde).unwrap();
+    let state = writer.save_state().unwrap();
+    assert_eq!(state.position(), 20);
+    assert_eq!(state.pending(), (4, 0xa));
+    assert_eq!(writer.get_ref().get_ref()[..], [0xde, 0xbc]);
+
+    let inner = writer.into_inner();
+    let mut writer = BitWriter::resume_with_order(inner, &state, Lsb0).unwrap();
+    writer.write_bits(4, 0x5).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0xde, 0xbc, 0x5a]);
+}
+
+#[test]
+fn states_compare() {
+    let mut reader = BitReader::new(Cursor::new(&[0x12, 0x34][..]));
+    reader.read_bits(5).unwrap();
+    let a: ReaderState = reader.save_state();
+    assert_eq!(a, reader.save_state());
+    reader.read_bit().unwrap();
+    assert_ne!(a, reader.save_state());
+
+    let mut writer = BitWriter::new(Cursor::new(Vec::new()));
+    writer.write_bits(3, 1).unwrap();
+    let b: WriterState = writer.save_state().unwrap();
+    assert_eq!(b.pending(), (3, 1));
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "serde")]
+
+extern crate bitrw;
+extern crate serde_json;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, ReaderState, WriterState};
+
+#[test]
+fn reader_state_survives_serialization() {
+    let data = [0xde, 0xad, 0xbe, 0xef];
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(11).unwrap();
+    let json = serde_json::to_string(&reader.save_state()).unwrap();
+
+    let state: ReaderState = serde_json::from_str(&json).unwrap();
+    let mut reader = BitReader::resume(Cursor::new(&data[..]), &state).unwrap();
+    assert_eq!(reader.read_bits(21).unwrap(), 0xdeadbeef & 0x1f_ffff);
+}
+
+#[test]
+fn writer_state_survives_serialization() {
+    let mut writer = BitWriter::new(Cursor::new(Vec::new()));
+    writer.write_bits(11, 0xdeadbeef >> 21).unwrap();
+    let json = serde_json::to_string(&writer.save_state().unwrap()).unwrap();
+    let written = writer.into_inner().into_inner();
+
+    let state: WriterState = serde_json::from_str(&json).unwrap();
+    let mut writer = BitWriter::resume(Cursor::new(written), &state).unwrap();
+    writer.write_bits(21, 0xdeadbeef & 0x1f_ffff).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0xde, 0xad, 0xbe, 0xef]);
+}
+
+#[test]
+fn inconsistent_state_is_rejected() {
+    let state: WriterState =
+        serde_json::from_str(r#"{"position":11,"pending":1,"pending_len":2}"#).unwrap();
+    assert!(BitWriter::resume(Cursor::new(Vec::new()), &state).is_err());
+
+    for json in &[
+        r#"{"position":0,"pending":0,"pending_len":200}"#,
+        r#"{"position":0,"pending":4,"pending_len":2}"#,
+    ] {
+        let state: ReaderState = serde_json::from_str(json).unwrap();
+        let err = BitReader::resume(Cursor::new([0u8; 32]), &state).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput, "{}", json);
+        assert_eq!(bitrw::Error::from_io(&err).unwrap().op(), "resume");
+    }
+}
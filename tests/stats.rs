@@ -0,0 +1,101 @@
+extern crate bitrw;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Stats, StatsBitReader, StatsBitWriter};
+
+fn write_sequence<W: BitWrite>(writer: &mut W) {
+    writer.write_bits(4, 0xf).unwrap();
+    writer.write_bits(4, 0x0).unwrap();
+    writer.write_bits(12, 0xabc).unwrap();
+    writer.write_bit(1).unwrap();
+    writer.write_bit(0).unwrap();
+    writer.write::<u16>(0x8001).unwrap();
+    // Bits above the width aren't counted.
+    writer.write_bits(3, 0xff).unwrap();
+    writer.write_bits(0, 0).unwrap();
+}
+
+#[test]
+fn writer_counters() {
+    let mut writer = StatsBitWriter::new(BitWriter::new(Vec::new()));
+    write_sequence(&mut writer);
+    assert_eq!(writer.bit_position(), Some(41));
+
+    let stats = writer.stats();
+    assert_eq!(stats.fields(), 8);
+    assert_eq!(stats.bits(), 41);
+    // 4 + 0 + 7 + 1 + 0 + 2 + 3
+    assert_eq!(stats.ones(), 17);
+    assert_eq!(stats.zeros(), 24);
+    assert_eq!(stats.fields_of_width(4), 2);
+    assert_eq!(stats.fields_of_width(1), 2);
+    assert_eq!(stats.fields_of_width(8), 0);
+    assert_eq!(
+        stats.widths().collect::<Vec<_>>(),
+        [(0, 1), (1, 2), (3, 1), (4, 2), (12, 1), (16, 1)]
+    );
+
+    let stats = writer.reset_stats();
+    assert_eq!(stats.bits(), 41);
+    assert_eq!(writer.stats(), &Stats::new());
+}
+
+#[test]
+fn reader_counters() {
+    let mut writer = BitWriter::new(Vec::new());
+    write_sequence(&mut writer);
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = StatsBitReader::new(BitReader::new(&data[..]));
+    assert_eq!(reader.read_bits(8).unwrap(), 0xf0);
+    assert_eq!(reader.read_bits(12).unwrap(), 0xabc);
+    assert_eq!(reader.read_bit().unwrap(), 1);
+    assert_eq!(reader.read_bit().unwrap(), 0);
+    assert_eq!(reader.read::<u16>().unwrap(), 0x8001);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b111);
+
+    let stats = reader.stats();
+    assert_eq!(stats.fields(), 6);
+    assert_eq!(stats.bits(), 41);
+    assert_eq!(stats.ones(), 17);
+    assert_eq!(stats.fields_of_width(1), 2);
+
+    // A failed read isn't counted.
+    assert!(reader.read_bits(16).is_err());
+    assert_eq!(reader.stats().fields(), 6);
+
+    // Nor is one made directly.
+    reader.get_mut().read_bits(3).unwrap();
+    assert_eq!(reader.stats().bits(), 41);
+}
+
+#[test]
+fn entropy() {
+    let mut writer = StatsBitWriter::new(BitWriter::new(Vec::new()));
+    assert_eq!(writer.stats().entropy(), 0.0);
+    writer.write_bits(8, 0xff).unwrap();
+    assert_eq!(writer.stats().entropy(), 0.0);
+    writer.write_bits(8, 0x00).unwrap();
+    assert_eq!(writer.stats().entropy(), 1.0);
+    writer.write_bits(16, 0x0000).unwrap();
+    // A quarter ones.
+    let h = -(0.25f64 * 0.25f64.log2() + 0.75 * 0.75f64.log2());
+    assert!((writer.stats().entropy() - h).abs() < 1e-12);
+}
+
+#[test]
+fn report() {
+    let mut writer = StatsBitWriter::new(BitWriter::new(Vec::new()));
+    write_sequence(&mut writer);
+    assert_eq!(
+        writer.stats().report(),
+        "41 bits in 8 fields: 17 ones, 24 zeros\n\
+         entropy 0.9789 bits per bit\n \
+         0 bits: 1\n \
+         1 bits: 2\n \
+         3 bits: 1\n \
+         4 bits: 2\n\
+         12 bits: 1\n\
+         16 bits: 1\n"
+    );
+}
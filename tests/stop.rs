@@ -0,0 +1,68 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::Cursor;
+
+use bitrw::{trailing_stop_len, BitOrder, BitReader, BitWriterBuilder, Lsb0, Msb0};
+
+fn roundtrip<O: BitOrder + Copy>(order: O) {
+    let mut rng = rand::thread_rng();
+    for phase in 0..8u64 {
+        for &pad_ones in &[false, true] {
+            let nbits = rng.gen_range(0, 4) * 8 + phase;
+            let payload: Vec<u8> = (0..nbits).map(|_| rng.gen_range(0, 2)).collect();
+
+            let mut writer = BitWriterBuilder::new()
+                .bit_order(order)
+                .pad_with_ones(pad_ones)
+                .build(Vec::new());
+            for &bit in &payload {
+                writer.write_bit(bit).unwrap();
+            }
+            let written = writer.write_stop_and_align().unwrap() as u64;
+            assert_eq!(written, 8 - phase);
+            assert_eq!(writer.position() % 8, 0);
+            // Nothing's left to flush.
+            assert_eq!(writer.flush().unwrap(), 0);
+            let bytes = writer.into_inner();
+            assert_eq!(bytes.len() as u64 * 8, nbits + written);
+
+            let trailer = trailing_stop_len(&bytes, order).unwrap();
+            assert_eq!(trailer, written);
+            let mut reader = BitReader::with_order(Cursor::new(&bytes), order);
+            for &bit in &payload {
+                assert_eq!(reader.read_bit().unwrap(), bit);
+            }
+            assert_eq!(reader.position(), bytes.len() as u64 * 8 - trailer);
+            assert_eq!(reader.read_bit().unwrap(), 1);
+        }
+    }
+}
+
+#[test]
+fn stop_roundtrip_msb0() {
+    roundtrip(Msb0);
+}
+
+#[test]
+fn stop_roundtrip_lsb0() {
+    roundtrip(Lsb0);
+}
+
+#[test]
+fn stop_aligned_is_a_whole_byte() {
+    let mut writer = BitWriterBuilder::new().bit_order(Lsb0).build(Vec::new());
+    writer.write_bits(8, 0xff).unwrap();
+    assert_eq!(writer.write_stop_and_align().unwrap(), 8);
+    assert_eq!(writer.into_inner(), [0xff, 0x01]);
+    assert_eq!(trailing_stop_len(&[0xff, 0x01], Lsb0), Some(8));
+    assert_eq!(trailing_stop_len(&[0xff, 0x01], Msb0), Some(1));
+}
+
+#[test]
+fn stop_trailing_zero_bytes() {
+    assert_eq!(trailing_stop_len(&[0x12, 0x80, 0, 0], Msb0), Some(24));
+    assert_eq!(trailing_stop_len(&[0, 0], Msb0), None);
+    assert_eq!(trailing_stop_len(&[], Lsb0), None);
+}
@@ -0,0 +1,137 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, Lsb0};
+
+#[test]
+fn length_prefixed_utf8_unaligned() {
+    // Two, three and four byte code points, all straddling byte boundaries
+    // after the 11 bit length.
+    let text = "héllo, wörld ✓ 🦀";
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(11, text.len() as u64).unwrap();
+    assert_eq!(writer.write_utf8(text).unwrap(), text.len() * 8);
+    writer.write_bits(11, 2).unwrap();
+    writer.write_utf8("ß").unwrap();
+    writer.flush().unwrap();
+
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    let len = reader.read_bits(11).unwrap() as usize;
+    assert_eq!(reader.read_utf8(len).unwrap(), text);
+    let len = reader.read_bits(11).unwrap() as usize;
+    assert_eq!(reader.read_utf8(len).unwrap(), "ß");
+}
+
+#[test]
+fn lsb0_phases() {
+    for phase in 0..8 {
+        let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+        writer.write_bits(phase, 0).unwrap();
+        writer.write_utf8("日本語").unwrap();
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+        reader.read_bits(phase).unwrap();
+        assert_eq!(reader.read_utf8(9).unwrap(), "日本語", "phase {}", phase);
+    }
+}
+
+fn unaligned(bytes: &[u8]) -> BitReader<Cursor<Vec<u8>>> {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    for &byte in bytes {
+        writer.write_bits(8, byte as u64).unwrap();
+    }
+    writer.flush().unwrap();
+    let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+    reader.read_bits(3).unwrap();
+    reader
+}
+
+#[test]
+fn invalid_utf8() {
+    // A three byte sequence cut short by an ASCII byte.
+    let bytes = b"ok \xe2\x9c!";
+    let err = unaligned(bytes).read_utf8(bytes.len()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_utf8");
+    assert_eq!(err.offset(), Some(3));
+    assert_eq!(err.value(), Some(3));
+
+    assert_eq!(
+        unaligned(bytes).read_utf8_lossy(bytes.len()).unwrap(),
+        "ok \u{fffd}!"
+    );
+    assert_eq!(unaligned(b"fine").read_utf8_lossy(4).unwrap(), "fine");
+}
+
+#[test]
+fn truncated() {
+    let err = unaligned(b"abc").read_utf8(4).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().op(), "read_utf8");
+}
+
+#[test]
+fn cstr_roundtrip_at_phases() {
+    let strings: [&[u8]; 4] = [b"", b"a", b"hello world", b"\xff\x80\x01"];
+    for phase in 0..8 {
+        let mut writer = BitWriter::new(Vec::new());
+        writer.write_bits(phase, 0x55).unwrap();
+        for s in &strings {
+            assert_eq!(writer.write_cstr(s).unwrap(), (s.len() + 1) * 8);
+        }
+        writer.write_bits(5, 0x1f).unwrap();
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::new(Cursor::new(writer.into_inner()));
+        reader.read_bits(phase).unwrap();
+        for s in &strings {
+            assert_eq!(reader.read_cstr(16).unwrap(), *s, "phase {}", phase);
+        }
+        assert_eq!(reader.read_bits(5).unwrap(), 0x1f);
+    }
+}
+
+#[test]
+fn cstr_max_len() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bit(1).unwrap();
+    writer.write_cstr(b"abcd").unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    // Exactly max_len bytes is fine.
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bit().unwrap();
+    assert_eq!(reader.read_cstr(4).unwrap(), b"abcd");
+
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bit().unwrap();
+    let err = reader.read_cstr(3).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "read_cstr");
+    assert_eq!(err.offset(), Some(1));
+    assert_eq!(err.value(), Some(3));
+    assert_eq!(reader.position(), 1 + 4 * 8);
+
+    // Running out of data without a terminator.
+    let mut reader = BitReader::new(Cursor::new(&b"abc"[..]));
+    let err = reader.read_cstr(100).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn cstr_embedded_nul() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(4, 0).unwrap();
+    let err = writer.write_cstr(b"ab\0cd").unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(2));
+    assert_eq!(err.offset(), Some(4));
+    assert_eq!(writer.position(), 4);
+}
@@ -0,0 +1,324 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitStuffWriter, BitUnstuffReader, BitWriter, Lsb0, StuffRule};
+
+/// The CAN CRC-15 of a sequence of bits.
+fn crc15(bits: &[u8]) -> u64 {
+    let mut crc = 0u64;
+    for &bit in bits {
+        let next = bit as u64 ^ (crc >> 14);
+        crc = (crc << 1) & 0x7fff;
+        if next == 1 {
+            crc ^= 0x4599;
+        }
+    }
+    crc
+}
+
+fn push_field(bits: &mut Vec<u8>, nbits: u8, value: u64) {
+    for i in (0..nbits).rev() {
+        bits.push((value >> i) as u8 & 1);
+    }
+}
+
+/// The bits of a CAN base frame from the start of frame to the end of the
+/// CRC, which is the part that's stuffed.
+fn can_frame(id: u16, data: &[u8]) -> Vec<u8> {
+    let mut bits = vec![0]; // SOF
+    push_field(&mut bits, 11, id as u64);
+    push_field(&mut bits, 3, 0); // RTR, IDE, r0
+    push_field(&mut bits, 4, data.len() as u64);
+    for &byte in data {
+        push_field(&mut bits, 8, byte as u64);
+    }
+    let crc = crc15(&bits);
+    push_field(&mut bits, 15, crc);
+    bits
+}
+
+/// Stuff bits the simple way, returning the stuffed bits and whether a
+/// stuffing bit was added after the last.
+fn reference_stuff(bits: &[u8], rule: StuffRule) -> (Vec<u8>, bool) {
+    let mut out = Vec::new();
+    let (mut last, mut run) = (2, 0);
+    let mut trailing = false;
+    for &bit in bits {
+        out.push(bit);
+        trailing = false;
+        let counts = match rule {
+            StuffRule::Identical => true,
+            StuffRule::Ones => bit == 1,
+        };
+        if counts && bit == last {
+            run += 1;
+        } else if counts {
+            run = 1;
+        } else {
+            run = 0;
+        }
+        last = bit;
+        if run == 5 {
+            let stuffing = match rule {
+                StuffRule::Identical => bit ^ 1,
+                StuffRule::Ones => 0,
+            };
+            out.push(stuffing);
+            trailing = true;
+            last = stuffing;
+            run = if rule == StuffRule::Identical { 1 } else { 0 };
+        }
+    }
+    (out, trailing)
+}
+
+fn pack(bits: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for &bit in bits {
+        writer.write_bit(bit).unwrap();
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+fn check_can_frame(id: u16, data: &[u8]) {
+    let frame = can_frame(id, data);
+    let (mut wire, trailing) = reference_stuff(&frame, StuffRule::Identical);
+    let stuffed = wire.len() - frame.len();
+    // CRC delimiter, ACK slot and delimiter, and end of frame, all recessive.
+    wire.extend_from_slice(&[1; 10]);
+
+    let mut reader = BitUnstuffReader::new(
+        BitReader::new(Cursor::new(pack(&wire))),
+        StuffRule::Identical,
+    );
+    assert_eq!(reader.read_bits(1).unwrap(), 0);
+    assert_eq!(reader.read_bits(11).unwrap(), id as u64);
+    assert_eq!(reader.read_bits(3).unwrap(), 0);
+    assert_eq!(reader.read_bits(4).unwrap(), data.len() as u64);
+    for &byte in data {
+        assert_eq!(reader.read_bits(8).unwrap(), byte as u64);
+    }
+    assert_eq!(
+        reader.read_bits(15).unwrap(),
+        crc15(&frame[..frame.len() - 15])
+    );
+    assert_eq!(reader.end_stuffing().unwrap(), trailing);
+
+    assert_eq!(reader.position(), frame.len() as u64);
+    assert_eq!(reader.stuffed_bits(), stuffed as u64);
+    assert_eq!(reader.raw_position(), (frame.len() + stuffed) as u64);
+    assert_eq!(reader.get_mut().read_bits(10).unwrap(), 0x3ff);
+}
+
+#[test]
+fn unstuff_can_frames() {
+    // All dominant, stuffed at every opportunity.
+    check_can_frame(0x000, &[0x00; 8]);
+    // All recessive bits in the identifier and data.
+    check_can_frame(0x7ff, &[0xff; 8]);
+    // Runs which straddle the field boundaries.
+    check_can_frame(0x07c, &[0x1f, 0x07, 0xc1, 0xf0]);
+    check_can_frame(0x123, &[0xde, 0xad, 0xbe, 0xef]);
+    check_can_frame(0x555, &[]);
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let data: Vec<u8> = (0..rng.gen_range(0, 9)).map(|_| rng.gen::<u8>()).collect();
+        check_can_frame(rng.gen_range(0, 0x800), &data);
+    }
+}
+
+#[test]
+fn unstuff_known_bits() {
+    // 11111 0 11 00000 1 0000 0 1 1, with stuffing bits at bits 5 and 13.
+    let wire = [
+        1, 1, 1, 1, 1, 0, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 1,
+    ];
+    let mut reader = BitUnstuffReader::new(
+        BitReader::new(Cursor::new(pack(&wire))),
+        StuffRule::Identical,
+    );
+    assert_eq!(reader.read_bits(7).unwrap(), 0b1111111);
+    assert_eq!(reader.read_bits(10).unwrap(), 0b00000_00000);
+    assert_eq!(reader.stuffed_bits(), 2);
+    assert_eq!(reader.raw_position(), 19);
+    assert!(reader.end_stuffing().unwrap());
+    assert_eq!(reader.stuffed_bits(), 3);
+    assert_eq!(reader.get_mut().read_bit().unwrap(), 1);
+}
+
+#[test]
+fn unstuff_violation() {
+    // An error flag: six dominant bits.
+    let wire = [0, 1, 0, 0, 0, 0, 0, 0, 1, 1];
+    let mut reader = BitUnstuffReader::new(
+        BitReader::new(Cursor::new(pack(&wire))),
+        StuffRule::Identical,
+    );
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "unstuff");
+    assert_eq!(err.offset(), Some(7));
+    assert_eq!(err.value(), Some(0));
+    assert_eq!(reader.raw_position(), 8);
+    assert_eq!(reader.position(), 0);
+}
+
+#[test]
+fn unstuff_hdlc_flag() {
+    // Data 0111110 stuffed, then a flag.
+    let mut wire = vec![0, 1, 1, 1, 1, 1, 0, 0];
+    wire.extend_from_slice(&[0, 1, 1, 1, 1, 1, 1, 0]);
+    let mut reader =
+        BitUnstuffReader::new(BitReader::new(Cursor::new(pack(&wire))), StuffRule::Ones);
+    assert_eq!(reader.read_bits(7).unwrap(), 0b0111110);
+    assert_eq!(reader.stuffed_bits(), 1);
+
+    // The flag's sixth one is a violation.
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(bitrw::Error::from_io(&err).unwrap().offset(), Some(14));
+    assert_eq!(reader.get_mut().read_bit().unwrap(), 0);
+}
+
+#[test]
+fn unstuff_matches_reference() {
+    let mut rng = rand::thread_rng();
+    for &rule in &[StuffRule::Identical, StuffRule::Ones] {
+        for _ in 0..200 {
+            // Mostly long runs, to stuff often.
+            let mut bits = Vec::new();
+            while bits.len() < 200 {
+                let bit = rng.gen_range(0, 2);
+                for _ in 0..rng.gen_range(1, 12) {
+                    bits.push(bit);
+                }
+            }
+            let (wire, _) = reference_stuff(&bits, rule);
+            let mut reader = BitUnstuffReader::new(BitReader::new(Cursor::new(pack(&wire))), rule);
+            let mut offset = 0;
+            while offset < bits.len() {
+                let nbits = rng.gen_range(0, 65).min(bits.len() - offset);
+                let value = reader.read_bits(nbits as u8).unwrap();
+                let mut expected = 0;
+                for &bit in &bits[offset..offset + nbits] {
+                    expected = (expected << 1) | bit as u64;
+                }
+                assert_eq!(value, expected);
+                offset += nbits;
+            }
+            reader.end_stuffing().unwrap();
+            assert_eq!(reader.stuffed_bits(), (wire.len() - bits.len()) as u64);
+        }
+    }
+}
+
+fn bits_of(s: &str) -> Vec<u8> {
+    s.bytes().filter(|&c| c != b' ').map(|c| c - b'0').collect()
+}
+
+#[test]
+fn stuff_can_frame_golden() {
+    // A base frame with ID 0x00a and one data byte, 0x01, whose CRC is 0x3ac9:
+    // SOF, ID, RTR IDE r0, DLC, data, CRC.
+    let frame = bits_of("0 00000001010 000 0001 00000001 011101011001001");
+    // Stuffing after SOF and the first four bits of the ID, after the first
+    // bit of the DLC, and within the data.
+    let wire = bits_of("0 0000 1 0001010 000 0 1 001 00000 1 001 011101011001001");
+
+    let mut writer = BitStuffWriter::new(BitWriter::new(Vec::new()), StuffRule::Identical);
+    writer.write_bits(1, 0).unwrap();
+    writer.write_bits(11, 0x00a).unwrap();
+    writer.write_bits(3, 0).unwrap();
+    writer.write_bits(4, 1).unwrap();
+    writer.write_bits(8, 0x01).unwrap();
+    writer.write_bits(15, crc15(&frame[..27])).unwrap();
+    assert_eq!(crc15(&frame[..27]), 0x3ac9);
+    assert_eq!(writer.position(), 42);
+    assert_eq!(writer.stuffed_bits(), 3);
+    assert_eq!(writer.raw_position(), 45);
+    // CRC delimiter, ACK, ACK delimiter, EOF.
+    writer.write_raw_bits(10, 0x3ff).unwrap();
+    writer.flush().unwrap();
+
+    let mut expected = wire.clone();
+    expected.extend_from_slice(&[1; 10]);
+    assert_eq!(writer.into_inner().into_inner(), pack(&expected));
+    assert_eq!(reference_stuff(&frame, StuffRule::Identical).0, wire);
+}
+
+#[test]
+fn stuff_trailing_stuffing_bit() {
+    let mut writer = BitStuffWriter::new(BitWriter::new(Vec::new()), StuffRule::Identical);
+    writer.write_bits(5, 0).unwrap();
+    assert_eq!(writer.raw_position(), 6);
+    writer.write_raw_bits(2, 0).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner().into_inner(), [0b0000_0100]);
+}
+
+#[test]
+fn stuff_roundtrip() {
+    let mut rng = rand::thread_rng();
+    for &rule in &[StuffRule::Identical, StuffRule::Ones] {
+        for _ in 0..200 {
+            let fields: Vec<(u8, u64)> = (0..rng.gen_range(0, 20))
+                .map(|_| {
+                    let nbits = rng.gen_range(0, 65);
+                    // Mostly runs of ones or zeros.
+                    let value = match rng.gen_range(0, 3) {
+                        0 => 0,
+                        1 => u64::MAX,
+                        _ => rng.gen(),
+                    };
+                    (nbits, value.checked_shr(64 - nbits as u32).unwrap_or(0))
+                })
+                .collect();
+
+            let mut writer = BitStuffWriter::new(BitWriter::with_order(Vec::new(), Lsb0), rule);
+            for &(nbits, value) in &fields {
+                writer.write_bits(nbits, value).unwrap();
+            }
+            let raw = writer.raw_position();
+            let stuffed = writer.stuffed_bits();
+            writer.flush().unwrap();
+            let bytes = writer.into_inner().into_inner();
+
+            let mut reader =
+                BitUnstuffReader::new(BitReader::with_order(Cursor::new(bytes), Lsb0), rule);
+            for &(nbits, value) in &fields {
+                assert_eq!(reader.read_bits(nbits).unwrap(), value);
+            }
+            reader.end_stuffing().unwrap();
+            assert_eq!(reader.raw_position(), raw);
+            assert_eq!(reader.stuffed_bits(), stuffed);
+        }
+    }
+}
+
+#[test]
+fn stuff_hdlc_frame() {
+    let payload = [0x7e, 0xff, 0x7d, 0x3f];
+    let mut writer = BitStuffWriter::new(BitWriter::new(Vec::new()), StuffRule::Ones);
+    writer.write_raw_bits(8, 0x7e).unwrap();
+    for &byte in &payload {
+        writer.write_bits(8, byte as u64).unwrap();
+    }
+    writer.write_raw_bits(8, 0x7e).unwrap();
+    writer.flush().unwrap();
+    let bytes = writer.into_inner().into_inner();
+
+    let mut reader = BitReader::new(Cursor::new(bytes));
+    assert_eq!(reader.read_bits(8).unwrap(), 0x7e);
+    let mut reader = BitUnstuffReader::new(reader, StuffRule::Ones);
+    for &byte in &payload {
+        assert_eq!(reader.read_bits(8).unwrap(), byte as u64);
+    }
+    // The closing flag shows up as a violation.
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,100 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor};
+
+use bitrw::{BitRead, BitReader, BitWriter, BitWriterBuilder, Lsb0};
+
+/// A packet of a 4 bit version, a 12 bit length, 8 reserved bits, 3 bits of
+/// flags, padding to a byte, then `length` bytes of payload.
+fn packet() -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(4, 0x2).unwrap();
+    writer.write_bits(12, 5).unwrap();
+    writer.write_bits(8, 0xa5).unwrap();
+    writer.write_bits(3, 0b101).unwrap();
+    writer.write_bits(5, 0b11111).unwrap();
+    for byte in b"hello" {
+        writer.write_bits(8, *byte as u64).unwrap();
+    }
+    writer.write_bits(8, 0xee).unwrap();
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+fn decode<R: io::Read>(reader: &mut bitrw::TeeBitReader<R, Vec<u8>, bitrw::Msb0>) -> Vec<u8> {
+    assert_eq!(reader.read_bits(4).unwrap(), 2);
+    let length = reader.read_bits(12).unwrap();
+    reader.skip_bits(8).unwrap();
+    assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+    reader.reset().unwrap();
+    (0..length).map(|_| reader.read::<u8>().unwrap()).collect()
+}
+
+/// The bits of `data` in the given ranges, one after another.
+fn slices(data: &[u8], ranges: &[(u64, u64)]) -> Vec<u8> {
+    let mut writer = BitWriter::new(Vec::new());
+    for &(start, end) in ranges {
+        for bit in start..end {
+            writer
+                .write_bit(data[bit as usize / 8] >> (7 - bit % 8) & 1)
+                .unwrap();
+        }
+    }
+    writer.flush().unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn consumed_bits() {
+    let data = packet();
+    let mut reader = BitReader::new(Cursor::new(&data)).tee(BitWriter::new(Vec::new()));
+    assert_eq!(decode(&mut reader), b"hello");
+    assert_eq!(reader.position(), 72);
+
+    let (_, mut sink) = reader.into_inner();
+    assert_eq!(sink.position(), 16 + 3 + 40);
+    sink.flush().unwrap();
+    assert_eq!(
+        sink.into_inner(),
+        slices(&data, &[(0, 16), (24, 27), (32, 72)])
+    );
+}
+
+#[test]
+fn record_skipped() {
+    let data = packet();
+    let mut reader = BitReader::new(Cursor::new(&data)).tee(BitWriter::new(Vec::new()));
+    reader.set_record_skipped(true);
+    assert_eq!(decode(&mut reader), b"hello");
+
+    let (_, mut sink) = reader.into_inner();
+    sink.flush().unwrap();
+    assert_eq!(sink.into_inner(), &data[..9]);
+}
+
+#[test]
+fn lsb0() {
+    let data = [0x12, 0x34, 0x56];
+    let sink = BitWriter::with_order(Vec::new(), Lsb0);
+    let mut reader = BitReader::with_order(&data[..], Lsb0).tee(sink);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x412);
+    assert_eq!(reader.read_bits(12).unwrap(), 0x563);
+
+    let (_, mut sink) = reader.into_inner();
+    sink.flush().unwrap();
+    assert_eq!(sink.into_inner(), data);
+}
+
+#[test]
+fn sink_failure() {
+    let data = [0xab, 0xcd];
+    let sink = BitWriterBuilder::new().limit_bits(8).build(Vec::new());
+    let mut reader = BitReader::new(&data[..]).tee(sink);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xa);
+
+    // The sink can't take eight more bits, so the read is undone.
+    assert!(reader.read_bits(8).is_err());
+    assert_eq!(reader.position(), 4);
+    assert_eq!(reader.read_bits(4).unwrap(), 0xb);
+    assert_eq!(reader.get_mut().0.read_bits(8).unwrap(), 0xcd);
+}
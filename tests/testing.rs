@@ -0,0 +1,112 @@
+#![cfg(feature = "test-util")]
+
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::panic;
+
+use bitrw::testing::{
+    assert_bits_eq, assert_bits_eq_with_order, bit_string, roundtrip, roundtrip_with_order,
+    RecordingWriter,
+};
+use bitrw::{BitWrite, BitWriter, Lsb0, Msb0};
+
+fn panics<F: FnOnce() + panic::UnwindSafe>(f: F) -> String {
+    let err = panic::catch_unwind(f).unwrap_err();
+    err.downcast_ref::<String>()
+        .cloned()
+        .or_else(|| err.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap_or_default()
+}
+
+#[test]
+fn roundtrip_random_fields() {
+    let mut rng = rand::thread_rng();
+    let fields: Vec<(u8, u64)> = (0..1000)
+        .map(|_| {
+            let width = rng.gen_range(0, 65);
+            let value = if width == 0 {
+                0
+            } else {
+                rng.gen::<u64>() >> (64 - width)
+            };
+            (width, value)
+        })
+        .collect();
+    roundtrip(&fields);
+    roundtrip_with_order(&fields, Lsb0);
+}
+
+#[test]
+fn roundtrip_rejects_oversized_values() {
+    let msg = panics(|| {
+        roundtrip(&[(4, 0x1f)]);
+    });
+    assert!(msg.contains("doesn't fit in 4 bits"), "{}", msg);
+}
+
+#[test]
+fn bit_patterns() {
+    let bytes = roundtrip(&[(3, 0b110), (5, 1), (4, 0xa)]);
+    assert_eq!(bytes, [0xc1, 0xa0]);
+    assert_bits_eq(&bytes, "110 00001 1010");
+    assert_bits_eq(&bytes, "1100_0001 1010_0000");
+    assert_bits_eq(&bytes, "110...");
+    assert_eq!(bit_string(&bytes, Msb0), "11000001 10100000");
+
+    let bytes = roundtrip_with_order(&[(3, 0b110), (5, 1)], Lsb0);
+    assert_eq!(bytes, [0x0e]);
+    assert_bits_eq_with_order(&bytes, "011 10000", Lsb0);
+    assert_eq!(bit_string(&bytes, Lsb0), "01110000");
+}
+
+#[test]
+fn bit_pattern_mismatches() {
+    let msg = panics(|| assert_bits_eq(&[0xc1], "1100 0011"));
+    assert!(msg.contains("bits differ at bit 6"), "{}", msg);
+    assert!(msg.contains("actual: 11000001"), "{}", msg);
+
+    // Too short, unless the remainder is padding or the pattern is open.
+    let msg = panics(|| assert_bits_eq(&[0xc1, 0x80], "1100 0001"));
+    assert!(msg.contains("bit 8"), "{}", msg);
+    assert_bits_eq(&[0xc1, 0x80], "1100 0001 1");
+    assert_bits_eq(&[0xc1, 0x80], "1100 0001 ...");
+    let msg = panics(|| assert_bits_eq(&[0xc1, 0x81], "1100 0001 1"));
+    assert!(msg.contains("bit 15"), "{}", msg);
+
+    // Too long.
+    let msg = panics(|| assert_bits_eq(&[0xc1], "1100 0001 0"));
+    assert!(msg.contains("bit 8"), "{}", msg);
+
+    let msg = panics(|| assert_bits_eq(&[0], "0x00"));
+    assert!(msg.contains("invalid character"), "{}", msg);
+}
+
+#[test]
+fn recording_writer_golden_sequence() {
+    let mut recorder = RecordingWriter::new();
+    recorder.write_bits(4, 0xa).unwrap();
+    recorder.write_gamma(5).unwrap();
+    recorder.write_be(0x1234u16).unwrap();
+    assert_eq!(recorder.bit_position(), Some(4 + 5 + 16));
+    assert_eq!(recorder.calls()[0], (4, 0xa));
+
+    // Replaying the calls produces the same stream as writing directly.
+    let mut direct = BitWriter::new(Vec::new());
+    direct.write_bits(4, 0xa).unwrap();
+    direct.write_gamma(5).unwrap();
+    direct.write_be(0x1234u16).unwrap();
+    direct.flush().unwrap();
+
+    let mut replayed = BitWriter::new(Vec::new());
+    recorder.replay(&mut replayed).unwrap();
+    replayed.flush().unwrap();
+    assert_eq!(replayed.into_inner(), direct.into_inner());
+
+    let calls = recorder.clone().into_calls();
+    assert_eq!(calls.iter().map(|c| c.0 as u64).sum::<u64>(), 25);
+    recorder.clear();
+    assert!(recorder.calls().is_empty());
+    assert_eq!(recorder.bit_position(), Some(0));
+}
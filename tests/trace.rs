@@ -0,0 +1,122 @@
+extern crate bitrw;
+
+use std::io;
+
+use bitrw::{BitRead, BitReader, BitWrite, BitWriter, TraceEvent};
+
+fn event(op: &'static str, nbits: u8, offset: u64, value: u64) -> TraceEvent {
+    TraceEvent {
+        op,
+        nbits,
+        offset: Some(offset),
+        value,
+        error: None,
+    }
+}
+
+#[test]
+fn decode_events() {
+    // A 4 bit tag, a gamma coded 5, and a byte.
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(4, 0x9).unwrap();
+    writer.write_gamma(5).unwrap();
+    let tail = writer.position();
+    writer.write::<u8>(0x2c).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut events = Vec::new();
+    let mut reader = BitReader::new(&data[..]).with_trace(|event| events.push(*event));
+    assert_eq!(reader.read_bits(4).unwrap(), 0x9);
+    assert_eq!(reader.read_gamma().unwrap(), 5);
+    assert_eq!(reader.read::<u8>().unwrap(), 0x2c);
+    let err = reader.read_bits(8).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+    let gamma: Vec<_> = events[1..events.len() - 2].to_vec();
+    assert_eq!(events[0], event("read_bits", 4, 0, 0x9));
+    assert_eq!(gamma.first().map(|e| e.offset), Some(Some(4)));
+    assert!(gamma.iter().all(|e| e.error.is_none()));
+    assert_eq!(events[events.len() - 2], event("read_bits", 8, tail, 0x2c));
+    assert_eq!(
+        events.last().unwrap(),
+        &TraceEvent {
+            op: "read_bits",
+            nbits: 8,
+            offset: Some(tail + 8),
+            value: 0,
+            error: Some(io::ErrorKind::UnexpectedEof),
+        }
+    );
+    assert_eq!(
+        events.last().unwrap().to_string(),
+        format!("read_bits(8) @ bit {} -> error: UnexpectedEof", tail + 8)
+    );
+
+    // Each event starts where the last finished.
+    for pair in events.windows(2) {
+        assert_eq!(
+            pair[1].offset.unwrap(),
+            pair[0].offset.unwrap() + pair[0].nbits as u64
+        );
+    }
+}
+
+#[test]
+fn display() {
+    assert_eq!(
+        event("read_bits", 5, 1032, 0b10110).to_string(),
+        "read_bits(5) @ bit 1032 -> 0b10110"
+    );
+    assert_eq!(
+        event("read_bits", 5, 0, 0b00110).to_string(),
+        "read_bits(5) @ bit 0 -> 0b00110"
+    );
+    assert_eq!(
+        event("read_bit", 1, 7, 1).to_string(),
+        "read_bit() @ bit 7 -> 0b1"
+    );
+    assert_eq!(
+        event("write_bits", 3, 2, 0b011).to_string(),
+        "write_bits(3, 0b011) @ bit 2"
+    );
+    let mut unknown = event("write_bit", 1, 0, 0);
+    unknown.offset = None;
+    assert_eq!(unknown.to_string(), "write_bit(0) @ bit ?");
+}
+
+#[test]
+fn writer_events() {
+    let mut events = Vec::new();
+    let mut writer = BitWriter::new(Vec::new()).with_trace(|event| events.push(*event));
+    writer.write_bits(4, 0xfa).unwrap();
+    writer.write_bit(1).unwrap();
+    writer.write::<u16>(0x1234).unwrap();
+    let mut inner = writer.into_inner();
+    inner.flush().unwrap();
+    assert_eq!(inner.into_inner(), [0xa8, 0x91, 0xa0]);
+
+    assert_eq!(
+        events,
+        [
+            event("write_bits", 4, 0, 0xa),
+            event("write_bit", 1, 4, 1),
+            event("write_bits", 16, 5, 0x1234),
+        ]
+    );
+}
+
+#[test]
+fn filter_events() {
+    let data = [0xff; 8];
+    let mut wide = Vec::new();
+    let mut reader = BitReader::new(&data[..]).with_trace(|event| {
+        if event.nbits > 8 {
+            wide.push(event.offset.unwrap());
+        }
+    });
+    for &nbits in &[3, 12, 1, 9, 30] {
+        reader.read_bits(nbits).unwrap();
+    }
+    assert_eq!(wide, [3, 16, 25]);
+}
@@ -0,0 +1,96 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io;
+
+use bitrw::{BitWriter, BitWriterBuilder, Lsb0};
+
+#[test]
+fn discard_partial_byte() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(8, 0xab).unwrap();
+    assert_eq!(writer.discard_buffered_bits(), 0);
+    writer.write_bits(5, 0b11111).unwrap();
+    assert_eq!(writer.position(), 13);
+    assert_eq!(writer.discard_buffered_bits(), 5);
+    assert_eq!(writer.position(), 8);
+
+    writer.write_bits(4, 0b0101).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xab, 0b0101_0000]);
+}
+
+#[test]
+fn discard_keeps_batched_bytes() {
+    let mut writer = BitWriterBuilder::new().capacity(64).build(Vec::new());
+    writer.write_bits(20, 0xabcde).unwrap();
+    assert_eq!(writer.discard_buffered_bits(), 4);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xab, 0xcd]);
+}
+
+#[test]
+fn truncate_matches_shorter_write() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..500 {
+        let fields: Vec<(u8, u64)> = (0..rng.gen_range(0, 20))
+            .map(|_| (rng.gen_range(0, 65), rng.gen::<u64>()))
+            .collect();
+        let capacity = [0, 1, 16][rng.gen_range(0, 3)];
+
+        let build = || {
+            BitWriterBuilder::new()
+                .bit_order(Lsb0)
+                .capacity(capacity)
+                .build(Vec::new())
+        };
+        let mut writer = build();
+        for &(nbits, value) in &fields {
+            writer.write_bits(nbits, value).unwrap();
+        }
+        let position = rng.gen_range(0, writer.position() + 1);
+        writer.truncate_to(position).unwrap();
+        assert_eq!(writer.position(), position);
+        writer.write_bits(13, 0x1abc).unwrap();
+        writer.flush().unwrap();
+
+        // The same, writing only the fields up to the position.
+        let mut expected = build();
+        let mut remaining = position;
+        for &(nbits, value) in &fields {
+            let n = remaining.min(nbits as u64) as u8;
+            expected.write_bits(n, value).unwrap();
+            remaining -= n as u64;
+        }
+        expected.write_bits(13, 0x1abc).unwrap();
+        expected.flush().unwrap();
+
+        assert_eq!(writer.into_inner(), expected.into_inner());
+    }
+}
+
+#[test]
+fn truncate_errors() {
+    let mut writer = BitWriter::new(vec![0xff]);
+    writer.write_bits(12, 0xabc).unwrap();
+
+    let err = writer.truncate_to(13).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "truncate_to");
+    assert_eq!(err.value(), Some(13));
+
+    // Bytes already in the Vec come before position 0, and are kept.
+    let mut writer = BitWriter::new(vec![0x12, 0xff]);
+    writer.write_bits(16, 0xabcd).unwrap();
+    writer.truncate_to(0).unwrap();
+    assert_eq!(writer.get_ref(), &[0x12, 0xff]);
+
+    // Bytes removed behind the writer's back can't be truncated.
+    let mut writer = BitWriter::new(vec![0x12, 0xff]);
+    writer.write_bits(8, 0xab).unwrap();
+    writer.get_mut().clear();
+    assert!(writer.truncate_to(0).is_err());
+    assert_eq!(writer.position(), 8);
+}
@@ -0,0 +1,89 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitReaderBuilder, BitWriter, BitWriterBuilder, Lsb0};
+
+#[test]
+fn read_bits_unchecked_matches() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let data: Vec<u8> = (0..rng.gen_range(0, 100))
+            .map(|_| rng.gen::<u8>())
+            .collect();
+        let mut safe = BitReader::new(Cursor::new(&data[..]));
+        let mut unchecked = BitReader::new(Cursor::new(&data[..]));
+        let mut safe_lsb = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+        let mut unchecked_lsb = BitReader::with_order(Cursor::new(&data[..]), Lsb0);
+        loop {
+            let nbits = rng.gen_range(0, 65);
+            let expected = safe.read_bits(nbits);
+            let actual = unsafe { unchecked.read_bits_unchecked(nbits) };
+            assert_eq!(safe_lsb.read_bits(nbits).ok(), unsafe {
+                unchecked_lsb.read_bits_unchecked(nbits).ok()
+            });
+            assert_eq!(safe.position(), unchecked.position());
+            match (expected, actual) {
+                (Ok(a), Ok(b)) => assert_eq!(a, b),
+                (Err(a), Err(b)) => {
+                    assert_eq!(a.kind(), b.kind());
+                    break;
+                }
+                (a, b) => panic!("{:?} != {:?}", a, b),
+            }
+        }
+    }
+}
+
+#[test]
+fn read_bits_unchecked_limit() {
+    let mut reader = BitReaderBuilder::new()
+        .limit_bits(12)
+        .build(Cursor::new([0xab, 0xcd, 0xef]));
+    assert_eq!(unsafe { reader.read_bits_unchecked(8) }.unwrap(), 0xab);
+    let err = unsafe { reader.read_bits_unchecked(8) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(unsafe { reader.read_bits_unchecked(4) }.unwrap(), 0xc);
+}
+
+#[test]
+fn write_bits_unchecked_matches() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..100 {
+        let mut safe = BitWriter::new(Vec::new());
+        let mut unchecked = BitWriter::new(Vec::new());
+        let mut safe_lsb = BitWriter::with_order(Vec::new(), Lsb0);
+        let mut unchecked_lsb = BitWriter::with_order(Vec::new(), Lsb0);
+        for _ in 0..rng.gen_range(0, 100) {
+            let nbits = rng.gen_range(0, 65);
+            // Including bits beyond the width, which are ignored.
+            let value = rng.gen::<u64>();
+            assert_eq!(
+                safe.write_bits(nbits, value).unwrap(),
+                unsafe { unchecked.write_bits_unchecked(nbits, value) }.unwrap()
+            );
+            safe_lsb.write_bits(nbits, value).unwrap();
+            unsafe { unchecked_lsb.write_bits_unchecked(nbits, value) }.unwrap();
+            assert_eq!(safe.position(), unchecked.position());
+        }
+        safe.flush().unwrap();
+        unchecked.flush().unwrap();
+        safe_lsb.flush().unwrap();
+        unchecked_lsb.flush().unwrap();
+        assert_eq!(safe.into_inner(), unchecked.into_inner());
+        assert_eq!(safe_lsb.into_inner(), unchecked_lsb.into_inner());
+    }
+}
+
+#[test]
+fn write_bits_unchecked_limit() {
+    let mut writer = BitWriterBuilder::new().limit_bits(12).build(Vec::new());
+    unsafe { writer.write_bits_unchecked(8, 0xab) }.unwrap();
+    let err = unsafe { writer.write_bits_unchecked(8, 0xcd) }.unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    unsafe { writer.write_bits_unchecked(4, 0xc) }.unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xab, 0xc0]);
+}
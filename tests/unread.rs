@@ -0,0 +1,180 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor, SeekFrom};
+
+use bitrw::{copy_all_bits, copy_bits, BitOrder, BitReader, BitWriter, Lsb0, Msb0};
+
+#[test]
+fn unread_across_byte_boundary() {
+    let mut reader = BitReader::new(Cursor::new([0b1010_1100, 0b0011_0101, 0xff]));
+    assert_eq!(reader.read_bits(5).unwrap(), 0b10101);
+    let value = reader.read_bits(6).unwrap();
+    assert_eq!(value, 0b100_001);
+    assert_eq!(reader.position(), 11);
+
+    reader.unread_bits(6, value).unwrap();
+    assert_eq!(reader.position(), 5);
+    assert_eq!(reader.read_bits(3).unwrap(), 0b100);
+    assert_eq!(reader.read_bits(8).unwrap(), 0b0011_0101);
+    assert_eq!(reader.position(), 16);
+}
+
+#[test]
+fn unread_something_else() {
+    let mut reader = BitReader::with_order(Cursor::new([0x00, 0x00]), Lsb0);
+    reader.read_bits(12).unwrap();
+    reader.unread_bits(4, 0b1011).unwrap();
+    reader.unread_bits(4, 0b0110).unwrap();
+    assert_eq!(reader.read_bits(12).unwrap(), 0b0000_1011_0110);
+}
+
+#[test]
+fn unread_interleaved() {
+    let mut rng = rand::thread_rng();
+    let data: Vec<u8> = (0..256).map(|_| rng.gen::<u8>()).collect();
+    let mut reference = BitReader::new(Cursor::new(&data[..]));
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+
+    // Push back part of each read half the time.
+    while reference.position() + 64 <= data.len() as u64 * 8 {
+        let nbits = rng.gen_range(0, 65);
+        let value = reader.read_bits(nbits).unwrap();
+        assert_eq!(value, reference.read_bits(nbits).unwrap());
+        if rng.gen() {
+            let unread = rng.gen_range(0, nbits + 1);
+            reader.unread_bits(unread, value).unwrap();
+            reference.seek(SeekFrom::Start(reader.position())).unwrap();
+        }
+        assert_eq!(reader.position(), reference.position());
+    }
+}
+
+#[test]
+fn unread_capacity() {
+    let mut reader = BitReader::new(Cursor::new([0xff; 32]));
+    reader.read_bits(64).unwrap();
+    reader.read_bits(64).unwrap();
+    reader.read_bits(3).unwrap();
+
+    // Five bits are left loaded, so there's always room for 64 more.
+    reader.unread_bits(64, 0).unwrap();
+    reader.unread_bits(58, 0).unwrap();
+    let err = reader.unread_bits(1, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.op(), "unread_bits");
+    assert_eq!(err.value(), Some(1));
+
+    assert_eq!(reader.position(), 131 - 122);
+    assert_eq!(reader.read_bits(64).unwrap(), 0);
+    assert_eq!(reader.read_bits(58).unwrap(), 0);
+    assert_eq!(reader.read_bits(64).unwrap(), u64::MAX);
+}
+
+#[test]
+fn unread_past_start() {
+    let mut reader = BitReader::new(Cursor::new([0xff]));
+    reader.read_bits(3).unwrap();
+    let err = reader.unread_bits(4, 0).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    assert_eq!(reader.position(), 3);
+    reader.unread_bits(3, 0).unwrap();
+    assert_eq!(reader.read_bits(4).unwrap(), 0b0001);
+}
+
+#[test]
+fn unread_then_seek() {
+    let mut reader = BitReader::new(Cursor::new([0x12, 0x34]));
+    reader.read_bits(8).unwrap();
+    reader.unread_bits(8, 0xee).unwrap();
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    assert_eq!(reader.read_bits(8).unwrap(), 0x23);
+}
+
+/// A reader over `data` which has read 68 bits then pushed back the last 64,
+/// so 68 bits are buffered, and a fresh reader of the same order at the same
+/// position for reference.
+fn overfull<O: BitOrder>(data: &[u8], order: O) -> [BitReader<Cursor<&[u8]>, O>; 2] {
+    let mut reader = BitReader::with_order(Cursor::new(data), order);
+    reader.read_bits(4).unwrap();
+    let value = reader.read_bits(64).unwrap();
+    reader.unread_bits(64, value).unwrap();
+    let mut reference = BitReader::with_order(Cursor::new(data), order);
+    reference.read_bits(4).unwrap();
+    [reader, reference]
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..len).map(|_| rng.gen()).collect()
+}
+
+#[test]
+fn skip_more_than_64_unread() {
+    let data = random_bytes(32);
+    let [mut reader, mut reference] = overfull(&data, Msb0);
+    reader.skip_bits(70).unwrap();
+    reference.skip_bits(70).unwrap();
+    assert_eq!(
+        reader.read_bits(64).unwrap(),
+        reference.read_bits(64).unwrap()
+    );
+
+    let [mut reader, mut reference] = overfull(&data, Lsb0);
+    assert_eq!(
+        reader.count_ones(100).unwrap(),
+        reference.count_ones(100).unwrap()
+    );
+    assert_eq!(
+        reader.read_bits(7).unwrap(),
+        reference.read_bits(7).unwrap()
+    );
+}
+
+#[test]
+fn copy_more_than_64_unread() {
+    let data = random_bytes(32);
+    let [mut reader, mut reference] = overfull(&data, Msb0);
+    let mut writer = BitWriter::new(Vec::new());
+    assert_eq!(copy_bits(&mut reader, &mut writer, 100).unwrap(), 100);
+    let mut expected = BitWriter::new(Vec::new());
+    copy_bits(&mut reference, &mut expected, 100).unwrap();
+    assert_eq!(writer.into_inner(), expected.into_inner());
+
+    let [mut reader, mut reference] = overfull(&data, Lsb0);
+    let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+    assert_eq!(copy_all_bits(&mut reader, &mut writer).unwrap(), 252);
+    let mut expected = BitWriter::with_order(Vec::new(), Lsb0);
+    copy_all_bits(&mut reference, &mut expected).unwrap();
+    writer.flush().unwrap();
+    expected.flush().unwrap();
+    assert_eq!(writer.into_inner(), expected.into_inner());
+}
+
+#[test]
+fn save_more_than_64_unread() {
+    let data = random_bytes(32);
+    let [reader, mut reference] = overfull(&data, Lsb0);
+    let state = reader.save_state();
+    assert_eq!(state.position(), 4);
+    assert_eq!(state.pending().0, 68);
+
+    let mut resumed = BitReader::resume_with_order(Cursor::new(&data[..]), &state, Lsb0).unwrap();
+    for _ in 0..3 {
+        assert_eq!(
+            resumed.read_bits(60).unwrap(),
+            reference.read_bits(60).unwrap()
+        );
+    }
+
+    // Bits pushed back which weren't those read don't match the stream.
+    let mut reader = BitReader::new(Cursor::new(&data[..]));
+    reader.read_bits(4).unwrap();
+    let value = reader.read_bits(64).unwrap();
+    reader.unread_bits(64, !value).unwrap();
+    let state = reader.save_state();
+    let err = BitReader::resume(Cursor::new(&data[..]), &state).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
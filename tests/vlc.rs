@@ -0,0 +1,246 @@
+extern crate bitrw;
+
+use std::io::{self, Cursor, Read};
+
+use bitrw::{
+    BitQueue, BitRead, BitReader, BitReaderBuilder, BitWrite, BitWriter, BufBitReader,
+    CrcBitReader, CrcParams, Error, ErrorReason, Parity, ParityBitReader,
+};
+
+/// A reader over `data` which counts the bytes taken from it.
+struct Counting<'a> {
+    data: &'a [u8],
+    taken: usize,
+}
+
+impl Read for Counting<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.data.read(buf)?;
+        self.taken += n;
+        Ok(n)
+    }
+}
+
+#[test]
+fn megabyte_of_zeros() {
+    let data = vec![0; 1 << 20];
+    let mut reader = BitReader::new(Counting {
+        data: &data,
+        taken: 0,
+    });
+    reader.read_bits(3).unwrap();
+    let err = reader.read_unary().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "read_unary");
+    assert_eq!(context.offset(), Some(3));
+    assert_eq!(context.value(), Some(1024));
+    assert_eq!(context.reason(), Some(ErrorReason::VlcLimit));
+    assert_eq!(context.message(), Some("code longer than limit"));
+
+    // It stopped at the limit rather than scanning the rest.
+    assert_eq!(reader.position(), 3 + 1024);
+    assert!(reader.get_ref().taken <= 130, "{}", reader.get_ref().taken);
+
+    let err = reader.read_gamma().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    assert!(reader.get_ref().taken <= 260);
+}
+
+#[test]
+fn codes_up_to_the_limit() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_unary(15).unwrap();
+    writer.write_unary(16).unwrap();
+    writer.write_gamma(0xff).unwrap();
+    writer.write_gamma(0x1ff).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut reader = BitReader::new(&data[..]);
+    reader.set_vlc_limit(Some(16));
+    assert_eq!(reader.vlc_limit(), Some(16));
+    assert_eq!(reader.read_unary().unwrap(), 15);
+    let err = reader.read_unary().unwrap_err();
+    assert_eq!(Error::from_io(&err).unwrap().offset(), Some(16));
+    assert_eq!(reader.position(), 32);
+    reader.read_bit().unwrap();
+
+    // A gamma code of 0xff takes 15 bits, and one of 0x1ff 17, which fails
+    // once its prefix has been read.
+    assert_eq!(reader.read_gamma().unwrap(), 0xff);
+    let err = reader.read_gamma().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.op(), "read_gamma");
+    assert_eq!(context.offset(), Some(48));
+    assert_eq!(reader.position(), 48 + 9);
+}
+
+#[test]
+fn without_a_limit() {
+    let mut data = vec![0; 1 << 14];
+    data.push(0x80);
+    let mut reader = BitReaderBuilder::new().vlc_limit(None).build(&data[..]);
+    assert_eq!(reader.vlc_limit(), None);
+    assert_eq!(reader.read_unary().unwrap(), 1 << 17);
+
+    let mut reader = BitReader::new(&data[..]);
+    assert!(reader.read_unary().is_err());
+    reader.set_vlc_limit(None);
+    assert_eq!(reader.read_unary().unwrap(), (1 << 17) - 1024);
+}
+
+#[test]
+fn rice_quotients() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(5, 2).unwrap();
+    writer.write_unary(3).unwrap();
+    writer.write_bits(2, 1).unwrap();
+    writer.write_unary(2000).unwrap();
+    writer.write_bits(2, 0).unwrap();
+    writer.flush().unwrap();
+    let data = writer.into_inner();
+
+    let mut values = [0; 2];
+    let err = BitReader::new(&data[..])
+        .read_rice_block(&mut values)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.offset(), Some(11));
+    assert_eq!(context.reason(), Some(ErrorReason::VlcLimit));
+    assert_eq!(values[0], 13);
+
+    // The remainder counts towards the limit too.
+    let mut reader = BitReader::new(&data[..]);
+    reader.set_vlc_limit(Some(5));
+    let err = reader.read_rice_block(&mut values).unwrap_err();
+    assert_eq!(Error::from_io(&err).unwrap().offset(), Some(5));
+
+    let mut reader = BitReaderBuilder::new()
+        .vlc_limit(Some(2003))
+        .build(&data[..]);
+    reader.read_rice_block(&mut values).unwrap();
+    assert_eq!(values, [13, 8000]);
+}
+
+fn unary<R: BitRead>(mut reader: R) -> io::Result<u64> {
+    reader.read_unary()
+}
+
+#[test]
+fn limit_carries_over() {
+    let data = [0x00, 0x00, 0x80];
+    let mut reader = BitReader::new(&data[..]);
+    reader.set_vlc_limit(Some(8));
+
+    let fork = reader.fork();
+    assert_eq!(fork.vlc_limit(), Some(8));
+
+    let (inner, parts) = reader.into_parts();
+    assert_eq!(parts.vlc_limit(), Some(8));
+    let mut reader = BitReader::<_>::from_parts(inner, parts);
+    assert!(reader.read_unary().is_err());
+
+    // Through a `&mut` reader, and a `TakeBits`.
+    let mut reader = BitReader::new(&data[..]);
+    reader.set_vlc_limit(Some(8));
+    assert!((&mut reader).take_bits(24).read_unary().is_err());
+    reader.set_vlc_limit(Some(17));
+    assert_eq!(unary(&mut reader).unwrap(), 8);
+}
+
+/// A reader over zeros with codes limited to 16 bits.
+fn zeros() -> BitReader<Cursor<Vec<u8>>> {
+    let mut reader = BitReader::new(Cursor::new(vec![0; 512]));
+    reader.set_vlc_limit(Some(16));
+    reader
+}
+
+/// Check that `err` is from a code passing a 16 bit limit.
+fn check_limited(err: io::Error) {
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.reason(), Some(ErrorReason::VlcLimit));
+    assert_eq!(context.value(), Some(16));
+}
+
+#[test]
+fn wrappers_keep_limit() {
+    let mut reader = zeros();
+    let mut slice = reader.slice(8, 2000).unwrap();
+    assert_eq!(slice.vlc_limit(), Some(16));
+    check_limited(slice.read_unary().unwrap_err());
+    drop(slice);
+
+    let mut reader = zeros();
+    check_limited(
+        reader
+            .read_frame_crc(2000, CrcParams::CRC_8, |frame| {
+                assert_eq!(frame.vlc_limit(), Some(16));
+                frame.read_unary()
+            })
+            .unwrap_err(),
+    );
+
+    check_limited(
+        CrcBitReader::new(zeros(), CrcParams::CRC_32)
+            .read_gamma()
+            .unwrap_err(),
+    );
+    check_limited(BufBitReader::new(zeros()).read_unary().unwrap_err());
+    check_limited(
+        zeros()
+            .chain(BitReader::new(Cursor::new(vec![0; 8])))
+            .read_unary()
+            .unwrap_err(),
+    );
+    check_limited(
+        zeros()
+            .tee(BitWriter::new(io::sink()))
+            .read_unary()
+            .unwrap_err(),
+    );
+    check_limited(
+        ParityBitReader::new(zeros(), Parity::Even, 7)
+            .read_unary()
+            .unwrap_err(),
+    );
+}
+
+#[test]
+fn other_readers_default() {
+    let mut queue = BitQueue::new();
+    for _ in 0..21 {
+        queue.write_bits(64, 0).unwrap();
+    }
+    queue.write_bit(1).unwrap();
+    assert_eq!(queue.vlc_limit(), Some(1024));
+    let err = queue.read_unary().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+#[should_panic(expected = "at least one bit")]
+fn zero_limit() {
+    BitReader::new(&[0u8][..]).set_vlc_limit(Some(0));
+}
+
+#[test]
+fn other_errors_have_no_reason() {
+    // An invalid Rice parameter is `InvalidData` too, but not a long code.
+    let mut values = [0; 1];
+    let err = BitReader::new(&[0xff][..])
+        .read_rice_block(&mut values)
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let context = Error::from_io(&err).unwrap();
+    assert_eq!(context.reason(), None);
+    assert_eq!(context.message(), Some("invalid Rice parameter"));
+
+    // Nor is the end of the stream part way through a code.
+    let err = BitReader::new(&[0x00][..]).read_unary().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert_eq!(Error::from_io(&err).and_then(Error::reason), None);
+}
@@ -0,0 +1,130 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, IoSlice, Write};
+
+use bitrw::{BitWriter, BitWriterBuilder, Lsb0};
+
+/// Records the address and length of every slice it's given.
+#[derive(Default)]
+struct Recorder {
+    data: Vec<u8>,
+    writes: Vec<Vec<(usize, usize)>>,
+    // Accept at most this many bytes a call, if set.
+    short: Option<usize>,
+}
+
+impl Write for Recorder {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> io::Result<usize> {
+        self.writes.push(
+            bufs.iter()
+                .map(|b| (b.as_ptr() as usize, b.len()))
+                .collect(),
+        );
+        let mut n = 0;
+        for buf in bufs {
+            let take = match self.short {
+                Some(max) => buf.len().min(max - n),
+                None => buf.len(),
+            };
+            self.data.extend_from_slice(&buf[..take]);
+            n += take;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn large_payload_bypasses_buffer() {
+    let payload: Vec<u8> = (0..4 << 20).map(|i| (i * 7) as u8).collect();
+    let mut writer = BitWriterBuilder::new()
+        .capacity(4096)
+        .build(Recorder::default());
+    writer.write_bits(16, 0xcafe).unwrap();
+    assert_eq!(writer.write_bytes(&payload).unwrap(), payload.len() * 8);
+    assert_eq!(writer.position(), 16 + payload.len() as u64 * 8);
+    writer.write_bits(4, 0xf).unwrap();
+    writer.flush().unwrap();
+    let out = writer.into_inner();
+
+    // The batched header and the payload itself, in one call.
+    assert_eq!(out.writes[0].len(), 2);
+    assert_eq!(out.writes[0][0].1, 2);
+    assert_eq!(out.writes[0][1], (payload.as_ptr() as usize, payload.len()));
+    assert_eq!(out.writes.len(), 2);
+
+    assert_eq!(&out.data[..2], [0xca, 0xfe]);
+    assert_eq!(&out.data[2..out.data.len() - 1], &payload[..]);
+    assert_eq!(out.data[out.data.len() - 1], 0xf0);
+}
+
+#[test]
+fn short_vectored_writes() {
+    let payload: Vec<u8> = (0..10_000).map(|i| (i * 13) as u8).collect();
+    let mut writer = BitWriterBuilder::new().capacity(64).build(Recorder {
+        short: Some(1000),
+        ..Recorder::default()
+    });
+    writer.write_bits(24, 0x123456).unwrap();
+    writer.write_bytes(&payload).unwrap();
+    writer.flush().unwrap();
+    let out = writer.into_inner();
+    assert_eq!(&out.data[..3], [0x12, 0x34, 0x56]);
+    assert_eq!(&out.data[3..], &payload[..]);
+    assert!(out
+        .writes
+        .iter()
+        .flatten()
+        .any(|&(ptr, _)| ptr == payload.as_ptr() as usize + 997));
+}
+
+#[test]
+fn same_as_write_bits() {
+    let mut rng = rand::thread_rng();
+    for &capacity in &[0, 1, 16, 300] {
+        let mut bytes_writer = BitWriterBuilder::new()
+            .bit_order(Lsb0)
+            .capacity(capacity)
+            .build(Vec::new());
+        let mut bits_writer = BitWriter::with_order(Vec::new(), Lsb0);
+        for _ in 0..200 {
+            if rng.gen::<bool>() {
+                let nbits = rng.gen_range(0, 65);
+                let value = rng.gen::<u64>();
+                bytes_writer.write_bits(nbits, value).unwrap();
+                bits_writer.write_bits(nbits, value).unwrap();
+            } else {
+                let bytes: Vec<u8> = (0..rng.gen_range(0, 400)).map(|_| rng.gen()).collect();
+                bytes_writer.write_bytes(&bytes).unwrap();
+                for &byte in &bytes {
+                    bits_writer.write_bits(8, byte as u64).unwrap();
+                }
+            }
+            assert_eq!(bytes_writer.position(), bits_writer.position());
+        }
+        bytes_writer.flush().unwrap();
+        bits_writer.flush().unwrap();
+        assert_eq!(bytes_writer.into_inner(), bits_writer.into_inner());
+    }
+}
+
+#[test]
+fn limit() {
+    let mut writer = BitWriterBuilder::new().limit_bits(20).build(Vec::new());
+    writer.write_bytes(&[1, 2]).unwrap();
+    let err = writer.write_bytes(&[3]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 16);
+    writer.write_bits(4, 0xf).unwrap();
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [1, 2, 0xf0]);
+}
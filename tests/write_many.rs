@@ -0,0 +1,93 @@
+extern crate bitrw;
+extern crate rand;
+
+use rand::Rng;
+use std::io::{self, Cursor};
+
+use bitrw::{BitReader, BitWriter, BitWriterBuilder, Lsb0};
+
+#[test]
+fn write_many_matches_write_bits() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let capacity = if rng.gen() { 0 } else { rng.gen_range(1, 32) };
+        let mut sequential = BitWriterBuilder::new().capacity(capacity).build(Vec::new());
+        let mut batched = BitWriterBuilder::new().capacity(capacity).build(Vec::new());
+
+        for _ in 0..rng.gen_range(0, 10) {
+            // Including values with bits beyond their widths, which are ignored.
+            let fields: Vec<(u8, u64)> = (0..rng.gen_range(0, 100))
+                .map(|_| (rng.gen_range(0, 65), rng.gen::<u64>()))
+                .collect();
+            let mut total = 0;
+            for &(nbits, value) in &fields {
+                total += sequential.write_bits(nbits, value).unwrap();
+            }
+            assert_eq!(batched.write_many(&fields).unwrap(), total);
+            assert_eq!(batched.position(), sequential.position());
+        }
+        sequential.flush().unwrap();
+        batched.flush().unwrap();
+        assert_eq!(batched.into_inner(), sequential.into_inner());
+    }
+}
+
+#[test]
+fn write_many_roundtrip() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let widths: Vec<u8> = (0..rng.gen_range(0, 40))
+            .map(|_| rng.gen_range(0, 65))
+            .collect();
+        let fields: Vec<(u8, u64)> = widths
+            .iter()
+            .map(|&nbits| {
+                (
+                    nbits,
+                    rng.gen::<u64>().checked_shr(64 - nbits as u32).unwrap_or(0),
+                )
+            })
+            .collect();
+
+        let mut writer = BitWriter::with_order(Vec::new(), Lsb0);
+        writer.write_many(&fields).unwrap();
+        writer.flush().unwrap();
+
+        let mut reader = BitReader::with_order(Cursor::new(writer.into_inner()), Lsb0);
+        let mut values = vec![0; widths.len()];
+        reader.read_many(&widths, &mut values).unwrap();
+        let expected: Vec<u64> = fields.iter().map(|&(_, value)| value).collect();
+        assert_eq!(values, expected);
+    }
+}
+
+#[test]
+fn write_many_invalid_width() {
+    let mut writer = BitWriter::new(Vec::new());
+    writer.write_bits(3, 0b101).unwrap();
+    let err = writer
+        .write_many(&[(8, 0xff), (64, 1), (65, 0), (1, 1)])
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    let err = bitrw::Error::from_io(&err).unwrap();
+    assert_eq!(err.value(), Some(2));
+    assert_eq!(err.offset(), Some(3));
+
+    // Nothing of the record was written.
+    assert_eq!(writer.position(), 3);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0b1010_0000]);
+}
+
+#[test]
+fn write_many_limit() {
+    let mut writer = BitWriterBuilder::new().limit_bits(16).build(Vec::new());
+    let err = writer.write_many(&[(8, 0xab), (9, 0)]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+    assert_eq!(writer.position(), 0);
+
+    assert_eq!(writer.write_many(&[(8, 0xab), (8, 0xcd)]).unwrap(), 16);
+    assert_eq!(writer.write_many(&[]).unwrap(), 0);
+    writer.flush().unwrap();
+    assert_eq!(writer.into_inner(), [0xab, 0xcd]);
+}